@@ -1,5 +1,10 @@
 use std::fs;
 use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[path = "src/release_version.rs"]
+mod release_version;
 
 fn main() -> anyhow::Result<()> {
     // Necessary for ESP-IDF
@@ -41,6 +46,96 @@ fn main() -> anyhow::Result<()> {
         println!("cargo:rustc-env=WIFI_PASSWORD=");
         println!("cargo:warning=wifi_config.h not found! Copy wifi_config.h.example to wifi_config.h and add your credentials.");
     }
-    
+
+    // Hash of the web assets compiled into the firmware, used as their ETag
+    let mut assets_hash = 0xcbf2_9ce4_8422_2325u64;
+    for path in ASSET_SOURCES {
+        println!("cargo:rerun-if-changed={path}");
+        hash_path(Path::new(path), &mut assets_hash)?;
+    }
+    println!("cargo:rustc-env=ASSETS_HASH={assets_hash:016x}");
+
+    // On-screen version, from Cargo.toml's like everything else that names a release
+    let cargo_version = std::env::var("CARGO_PKG_VERSION")?;
+    let release = release_version::ReleaseVersion::parse(&cargo_version)
+        .ok_or_else(|| anyhow::anyhow!("Cargo.toml version {cargo_version} is not MAJOR.MINOR.PATCH"))?;
+    println!("cargo:rustc-env=DISPLAY_VERSION={}", release.display());
+
+    // Build provenance for /api/firmware and the Settings screen
+    for path in [".git/HEAD", ".git/index"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    println!("cargo:rustc-env=GIT_COMMIT={}", git_commit());
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={}", build_timestamp());
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = command_output(&rustc, &["--version"]).unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=RUSTC_VERSION={rustc_version}");
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(|f| f.to_lowercase()))
+        .collect();
+    features.sort();
+    println!("cargo:rustc-env=ENABLED_FEATURES={}", features.join(","));
+
+    Ok(())
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// HEAD commit, with `-dirty` if tracked files have uncommitted changes
+fn git_commit() -> String {
+    let Some(commit) = command_output("git", &["rev-parse", "HEAD"]) else {
+        return "unknown".to_string();
+    };
+    let dirty = command_output("git", &["status", "--porcelain", "--untracked-files=no"])
+        .is_some_and(|status| !status.is_empty());
+    if dirty { format!("{commit}-dirty") } else { commit }
+}
+
+/// UTC time of this build script run as RFC 3339; SOURCE_DATE_EPOCH for reproducible builds
+fn build_timestamp() -> String {
+    let secs = std::env::var("SOURCE_DATE_EPOCH").ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()));
+    let (days, rem) = ((secs / 86_400) as i64, secs % 86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z", rem / 3600, rem % 3600 / 60, rem % 60)
+}
+
+/// Files and directories the served pages and styles are embedded from
+const ASSET_SOURCES: &[&str] = &[
+    "src/templates",
+    "src/network/streaming_dashboard.rs",
+    "src/network/templated_home.rs",
+];
+
+/// FNV-1a over a file, or over every file below a directory in name order
+fn hash_path(path: &Path, hash: &mut u64) -> anyhow::Result<()> {
+    if path.is_dir() {
+        let mut entries: Vec<_> = fs::read_dir(path)?.collect::<Result<_, _>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            hash_path(&entry.path(), hash)?;
+        }
+    } else {
+        for byte in fs::read(path)? {
+            *hash ^= byte as u64;
+            *hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,6 @@
+fn main() {
+    // Config::default() reads the compiled-in WiFi credentials like the firmware
+    // build does; host tests always use empty ones
+    println!("cargo:rustc-env=WIFI_SSID=");
+    println!("cargo:rustc-env=WIFI_PASSWORD=");
+}
@@ -0,0 +1,38 @@
+//! Render a sample layout with the firmware's widgets and write it to a PNG.
+//!
+//!     cargo run --example preview --target x86_64-unknown-linux-gnu -- preview.png
+
+use anyhow::Result;
+use esp32_dashboard_tests::display::{colors::*, DisplayManager};
+use esp32_dashboard_tests::preview::save_png;
+use esp32_dashboard_tests::ui::components::{CircularProgress, TextField};
+use esp32_dashboard_tests::ui::layout::{Rect, Size};
+
+fn main() -> Result<()> {
+    let path = std::env::args().nth(1).unwrap_or_else(|| "preview.png".to_string());
+    let mut display = DisplayManager::new();
+    display.clear(BLACK)?;
+
+    let [header, body, footer] = Rect::screen().rows([Size::Fixed(30), Size::Fill(1), Size::Fixed(12)], 4);
+    display.fill_rect(header.x, header.y, header.w, header.h, SURFACE_DARK)?;
+    display.draw_text_centered(header.text_y(2), "PREVIEW", TEXT_PRIMARY, None, 2)?;
+
+    let [rows, ring] = body.inset_xy(10, 0).cols([Size::Fill(1), Size::Fixed(80)], 8);
+    let rows = rows.rows([Size::Fill(1); 4], 0);
+    for (row, (label, value)) in rows.iter().zip([("Uptime:", "1d 02:03"), ("Heap:", "142 KB"), ("CPU:", "23%"), ("Temp:", "41.5C")]) {
+        let [label_zone, value_zone] = row.cols([Size::Fixed(80), Size::Fill(1)], 0);
+        display.draw_text(label_zone.x, label_zone.text_y(1), label, TEXT_SECONDARY, None, 1)?;
+        TextField::in_zone(value_zone, 1, BLACK).set(&mut display, value, TEXT_PRIMARY)?;
+    }
+
+    let mut progress = CircularProgress::new(ring.x + ring.w / 2, ring.y + ring.h / 2, 30, 6);
+    progress.set_value(65.0);
+    progress.draw(&mut display)?;
+
+    display.draw_text(footer.x + 10, footer.text_y(1), "host backend", TEXT_SECONDARY, None, 1)?;
+    display.flush()?;
+
+    save_png(&display, &path)?;
+    println!("Wrote {}", path);
+    Ok(())
+}
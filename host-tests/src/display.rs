@@ -0,0 +1,28 @@
+//! Host display backend: the firmware's framebuffer renderer and font, compiled
+//! from `src/display` so screens draw exactly as they do on the device.
+
+#[path = "../../src/display/colors.rs"]
+pub mod colors;
+#[path = "../../src/display/font5x7.rs"]
+pub mod font5x7;
+#[path = "../../src/display/error.rs"]
+pub mod error;
+#[path = "../../src/display/framebuffer.rs"]
+pub mod framebuffer;
+
+pub use framebuffer::FrameBuffer as DisplayManager;
+
+#[path = "../../src/display/burn_in.rs"]
+pub mod burn_in;
+
+#[path = "../../src/display/draw_list.rs"]
+pub mod draw_list;
+
+#[path = "../../src/display/health.rs"]
+pub mod health;
+
+#[path = "../../src/display/benchmark.rs"]
+pub mod benchmark;
+
+#[path = "../../src/display/pacing.rs"]
+pub mod pacing;
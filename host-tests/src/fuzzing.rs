@@ -0,0 +1,152 @@
+//! Fuzz entry points for the parsers that take untrusted network input on the
+//! device. The cargo-fuzz targets in `host-tests/fuzz` call these with
+//! libFuzzer input; the tests below run a short deterministic mutation pass
+//! over the same functions so every `cargo test` exercises them too.
+//! Each entry point must return normally for any input: a panic is a bug.
+
+use crate::config::Config;
+use crate::network::api_core::{self, ControlCommand};
+use crate::network::binary_protocol::{self, MetricsBinaryPacket};
+use crate::network::ics;
+
+/// Stored config blob (NVS) and POST /api/config body
+pub fn config_json(data: &[u8]) {
+    // Older firmware's blobs load through serde defaults; whatever loads must
+    // save and load again
+    if let Ok(stored) = serde_json::from_slice::<Config>(data) {
+        let saved = serde_json::to_vec(&stored).expect("config serializes");
+        serde_json::from_slice::<Config>(&saved).expect("saved config loads again");
+    }
+    if data.len() <= api_core::MAX_CONFIG_BODY {
+        let _ = api_core::apply_config_update(&Config::default(), data);
+    }
+}
+
+/// POST /api/control body
+pub fn control_command(data: &[u8]) {
+    if data.len() <= api_core::MAX_CONTROL_BODY {
+        let _ = ControlCommand::parse(data);
+    }
+}
+
+/// GET /api/metrics/binary packet (v2 frame or v1 layout), as decoded by clients
+pub fn metrics_packet(data: &[u8]) {
+    if let Some(packet) = MetricsBinaryPacket::from_bytes(data) {
+        assert_eq!(packet.to_bytes(), data, "decoding is lossless");
+    }
+    if let Ok(frame) = binary_protocol::decode(data) {
+        assert!(frame.fields.len() <= data.len() / 2, "every field costs at least a tag and a length");
+    }
+    // Random bytes almost never carry a valid CRC; frame them as a payload so
+    // the TLV parser sees them too
+    if data.len() <= u16::MAX as usize {
+        let _ = binary_protocol::decode(&binary_protocol::encode_frame(data));
+    }
+}
+
+/// Calendar feed fetched for the Agenda screen
+pub fn ics_feed(data: &[u8]) {
+    const NOW: i64 = 1_715_594_400; // 2024-05-13 10:00 UTC
+    let events = ics::upcoming(&String::from_utf8_lossy(data), NOW, 10, |c| c.days() * 86_400);
+    assert!(events.len() <= 10);
+    assert!(events.windows(2).all(|w| w[0].start <= w[1].start), "soonest first");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG_SEEDS: &[&str] = &[
+        r#"{"brightness": 128, "screen_order": ["system", "network"]}"#,
+        r#"{"wifi_ssid": "lab", "static_ip": "192.168.1.50", "static_netmask": "255.255.255.0", "static_gateway": "192.168.1.1"}"#,
+        r#"{"quiet_start": "22:30", "quiet_end": "07:00", "timezone": "CET-1CEST,M3.5.0,M10.5.0/3"}"#,
+        r#"{"sample_schedule": {"sensors": {"interval_ms": 60000, "enabled": true}}, "target_fps": 30}"#,
+        r#"{"custom_widgets": [{"label": "Temp", "url": "http://x/t", "path": "a.b", "unit": "C"}]}"#,
+        r#"{"metrics_labels": {"site": "lab"}, "buzzer_gpio": 10, "led_gpio": 11, "thermal_limit": 85.5}"#,
+    ];
+
+    const CONTROL_SEEDS: &[&str] = &[
+        r#"{"brightness": 300, "mute": true}"#,
+        r#"{"sound": "timer_done", "input": "user_long"}"#,
+        r#"{"screen": 18446744073709551615, "mode": "turbo", "quiet_override": false}"#,
+    ];
+
+    const ICS_SEEDS: &[&str] = &[
+        "BEGIN:VEVENT\r\nUID:a\r\nDTSTART;TZID=Europe/Berlin:20240506T093000\r\nDURATION:PT15M\r\nRRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=40\r\nEXDATE:20240515T093000\r\nSUMMARY:Stand-up\\, daily\r\nEND:VEVENT\r\n",
+        "BEGIN:VEVENT\nUID:a\nRECURRENCE-ID:20240513T093000\nDTSTART:20240513T110000Z\nDTEND:20240513T111500Z\nSUMMARY:Moved\nBEGIN:VALARM\nEND:VALARM\nEND:VEVENT\n",
+        "BEGIN:VEVENT\nDTSTART;VALUE=DATE:20000131\nRRULE:FREQ=MONTHLY;INTERVAL=4294967295;UNTIL=99991231\nSUMMARY:x\n y\nEND:VEVENT\n",
+    ];
+
+    // xorshift64: the same mutations on every run
+    struct Mutator(u64);
+
+    impl Mutator {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n.max(1) as u64) as usize
+        }
+
+        fn mutate(&mut self, seed: &[u8]) -> Vec<u8> {
+            const TOKENS: &[&[u8]] = &[b"\"", b"{", b"}", b"[", b"]", b",", b":", b"-1", b"1e999", b"null", b"\"\\u0000\"", b"99:99", b"255"];
+            let mut data = seed.to_vec();
+            for _ in 0..=self.below(4) {
+                let at = self.below(data.len() + 1);
+                match self.below(4) {
+                    0 if at < data.len() => data[at] = self.next() as u8,
+                    1 => data.splice(at..at, TOKENS[self.below(TOKENS.len())].iter().copied()).for_each(drop),
+                    2 => data.truncate(at),
+                    _ => {
+                        let end = (at + self.below(8)).min(data.len());
+                        data.drain(at..end);
+                    }
+                }
+            }
+            data
+        }
+    }
+
+    fn run(entry: fn(&[u8]), seeds: &[Vec<u8>], rounds: usize) {
+        let mut mutator = Mutator(0x9E37_79B9_7F4A_7C15);
+        for seed in seeds {
+            entry(seed);
+            for _ in 0..rounds {
+                entry(&mutator.mutate(seed));
+            }
+        }
+    }
+
+    #[test]
+    fn test_config_json_survives_mutation() {
+        let mut seeds: Vec<Vec<u8>> = CONFIG_SEEDS.iter().map(|s| s.as_bytes().to_vec()).collect();
+        seeds.push(serde_json::to_vec(&Config::default()).unwrap());
+        run(config_json, &seeds, 2000);
+    }
+
+    #[test]
+    fn test_control_command_survives_mutation() {
+        let seeds: Vec<Vec<u8>> = CONTROL_SEEDS.iter().map(|s| s.as_bytes().to_vec()).collect();
+        run(control_command, &seeds, 2000);
+        assert_eq!(ControlCommand::parse(br#"{"brightness": 300}"#).unwrap().brightness, Some(255));
+        assert!(ControlCommand::parse(br#"{"sound": "fanfare"}"#).is_err());
+    }
+
+    #[test]
+    fn test_metrics_packet_survives_mutation() {
+        let mut packet = vec![0u8; MetricsBinaryPacket::SIZE];
+        packet[0] = MetricsBinaryPacket::VERSION;
+        let frame = MetricsBinaryPacket::from_bytes(&packet).unwrap().to_v2_bytes();
+        run(metrics_packet, &[packet, frame, vec![MetricsBinaryPacket::VERSION], Vec::new()], 2000);
+    }
+
+    #[test]
+    fn test_ics_feed_survives_mutation() {
+        let seeds: Vec<Vec<u8>> = ICS_SEEDS.iter().map(|s| s.as_bytes().to_vec()).collect();
+        run(ics_feed, &seeds, 2000);
+    }
+}
@@ -1,6 +1,44 @@
 //! Host-based tests for ESP32-S3 Dashboard
 //! These tests run on the development machine, not on the ESP32
 
+#[path = "../../src/config/mod.rs"]
+pub mod config;
+pub mod display;
+#[path = "../../src/error.rs"]
+pub mod error;
+pub mod fuzzing;
+#[path = "../../src/hardware/mod.rs"]
+pub mod hardware;
+#[path = "../../src/i18n.rs"]
+pub mod i18n;
+#[path = "../../src/input/mod.rs"]
+pub mod input;
+pub mod logging;
+#[path = "../../src/metrics_data.rs"]
+pub mod metrics_data;
+#[path = "../../src/metrics_formatter.rs"]
+pub mod metrics_formatter;
+pub mod mock_http;
+pub mod network;
+pub mod ota;
+pub mod power;
+pub mod preview;
+#[path = "../../src/release_version.rs"]
+pub mod release_version;
+#[path = "../../src/ring_buffer.rs"]
+pub mod ring_buffer;
+pub mod sensors;
+pub mod snapshot;
+pub mod storage;
+pub mod system;
+#[path = "../../src/text_buf.rs"]
+pub mod text_buf;
+#[path = "../../src/trace.rs"]
+pub mod trace;
+pub mod ui;
+#[path = "../../src/units.rs"]
+pub mod units;
+
 #[cfg(test)]
 mod tests {
 
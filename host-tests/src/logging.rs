@@ -0,0 +1,4 @@
+//! Host-compilable parts of the firmware logger
+
+#[path = "../../src/logging/syslog.rs"]
+pub mod syslog;
@@ -0,0 +1,138 @@
+//! In-process stand-in for the firmware's HTTP server. Routes are registered
+//! per method and path like `EspHttpServer::fn_handler`, and requests are
+//! dispatched synchronously so the web API logic can be tested without a device.
+
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+}
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: Method,
+    /// Path plus optional query string, as `Request::uri()` returns on the device
+    pub uri: String,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn get(uri: &str) -> Self {
+        Self { method: Method::Get, uri: uri.to_string(), body: Vec::new() }
+    }
+
+    pub fn post(uri: &str, body: impl Into<Vec<u8>>) -> Self {
+        Self { method: Method::Post, uri: uri.to_string(), body: body.into() }
+    }
+
+    pub fn path(&self) -> &str {
+        self.uri.split('?').next().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn json(status: u16, value: &Value) -> Self {
+        Self { status, content_type: "application/json", body: value.to_string().into_bytes() }
+    }
+
+    /// Body written through network::json_stream, as the streamed handlers do
+    pub fn streamed_json<T: serde::Serialize>(status: u16, value: &T) -> Self {
+        let mut body = Vec::new();
+        crate::network::json_stream::write_json(value, |chunk| {
+            body.extend_from_slice(chunk);
+            Ok(())
+        })
+        .expect("serializing into memory cannot fail");
+        Self { status, content_type: "application/json", body }
+    }
+
+    pub fn ok() -> Self {
+        Self { status: 200, content_type: "text/plain", body: Vec::new() }
+    }
+
+    /// Same shape as the firmware's ErrorResponse, minus request id and timestamp
+    pub fn error(status: u16, code: &str, message: &str) -> Self {
+        Self::json(status, &json!({ "error": { "code": code, "message": message } }))
+    }
+
+    pub fn bad_request(message: &str) -> Self {
+        Self::error(400, "BAD_REQUEST", message)
+    }
+
+    /// A rejected config update, with the per-field failures the firmware's
+    /// ErrorResponse adds as `error.fields`
+    pub fn config_error(e: &crate::config::ConfigError) -> Self {
+        let mut error = json!({ "code": "BAD_REQUEST", "message": e.to_string() });
+        if !e.field_errors().is_empty() {
+            error["fields"] = json!(e.field_errors());
+        }
+        Self::json(400, &json!({ "error": error }))
+    }
+
+    pub fn body_str(&self) -> &str {
+        std::str::from_utf8(&self.body).unwrap_or_default()
+    }
+
+    /// Parsed JSON body; panics if the body is not JSON, which is a test failure
+    pub fn json_body(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap_or_else(|e| panic!("response is not JSON ({}): {}", e, self.body_str()))
+    }
+
+    /// `error.message` of an error response
+    pub fn error_message(&self) -> Option<String> {
+        self.json_body()["error"]["message"].as_str().map(str::to_string)
+    }
+}
+
+type Handler = Box<dyn Fn(&Request) -> Response>;
+
+#[derive(Default)]
+pub struct MockServer {
+    routes: Vec<(Method, String, Handler)>,
+}
+
+impl MockServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn fn_handler(&mut self, path: &str, method: Method, handler: impl Fn(&Request) -> Response + 'static) -> &mut Self {
+        self.routes.push((method, path.to_string(), Box::new(handler)));
+        self
+    }
+
+    /// Dispatch to the handler registered for the request's method and path
+    pub fn handle(&self, req: &Request) -> Response {
+        self.routes
+            .iter()
+            .find(|(method, path, _)| *method == req.method && path == req.path())
+            .map(|(_, _, handler)| handler(req))
+            .unwrap_or_else(|| Response::error(404, "NOT_FOUND", "No route"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes_match_method_and_path() {
+        let mut server = MockServer::new();
+        server.fn_handler("/ping", Method::Get, |req| Response::json(200, &json!({ "uri": req.uri })));
+
+        let res = server.handle(&Request::get("/ping?x=1"));
+        assert_eq!(res.status, 200);
+        assert_eq!(res.json_body()["uri"], "/ping?x=1");
+        assert_eq!(server.handle(&Request::post("/ping", "")).status, 404);
+        assert_eq!(server.handle(&Request::get("/pong")).status, 404);
+    }
+}
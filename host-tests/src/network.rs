@@ -0,0 +1,54 @@
+//! Host-compilable parts of the firmware web API
+
+#[path = "../../src/network/validators.rs"]
+pub mod validators;
+#[path = "../../src/network/api_core.rs"]
+pub mod api_core;
+#[path = "../../src/network/offline_queue.rs"]
+pub mod offline_queue;
+#[path = "../../src/network/error.rs"]
+pub mod error;
+#[path = "../../src/network/log_filter.rs"]
+pub mod log_filter;
+#[path = "../../src/network/http_client.rs"]
+pub mod http_client;
+#[path = "../../src/network/template_engine.rs"]
+pub mod template_engine;
+#[path = "../../src/network/wifi_scan.rs"]
+pub mod wifi_scan;
+#[path = "../../src/network/csrf.rs"]
+pub mod csrf;
+#[path = "../../src/network/net_stats.rs"]
+pub mod net_stats;
+#[path = "../../src/network/latency_monitor.rs"]
+pub mod latency_monitor;
+#[path = "../../src/network/heap_tracker.rs"]
+pub mod heap_tracker;
+#[path = "../../src/network/admission.rs"]
+pub mod admission;
+#[path = "../../src/network/json_stream.rs"]
+pub mod json_stream;
+#[path = "../../src/network/compression.rs"]
+pub mod compression;
+#[path = "../../src/network/etag.rs"]
+pub mod etag;
+#[path = "../../src/network/nettest.rs"]
+pub mod nettest;
+#[path = "../../src/network/connection_history.rs"]
+pub mod connection_history;
+#[path = "../../src/network/disconnect_reasons.rs"]
+pub mod disconnect_reasons;
+#[path = "../../src/network/softap.rs"]
+pub mod softap;
+#[path = "../../src/network/ipv6.rs"]
+pub mod ipv6;
+#[path = "../../src/network/file_manager.rs"]
+pub mod file_manager;
+#[path = "../../src/network/binary_protocol.rs"]
+pub mod binary_protocol;
+#[path = "../../src/network/sse_hub.rs"]
+pub mod sse_hub;
+#[path = "../../src/network/mdns_manager.rs"]
+pub mod mdns_manager;
+#[path = "../../src/network/ics.rs"]
+pub mod ics;
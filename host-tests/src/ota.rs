@@ -0,0 +1,16 @@
+//! Host-compilable parts of the firmware OTA layer
+
+#[path = "../../src/ota/delta.rs"]
+pub mod delta;
+
+#[path = "../../src/ota/error.rs"]
+pub mod error;
+
+#[path = "../../src/ota/progress.rs"]
+pub mod progress;
+
+#[path = "../../src/ota/resume.rs"]
+pub mod resume;
+
+#[path = "../../src/ota/window.rs"]
+pub mod window;
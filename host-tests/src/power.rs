@@ -0,0 +1,19 @@
+//! Host-compilable parts of the firmware power layer
+
+#[path = "../../src/power/events.rs"]
+pub mod events;
+
+#[path = "../../src/power/quiet_hours.rs"]
+pub mod quiet_hours;
+
+#[path = "../../src/power/thermal.rs"]
+pub mod thermal;
+
+#[path = "../../src/power/duty_cycle.rs"]
+pub mod duty_cycle;
+
+#[path = "../../src/power/adaptive_brightness.rs"]
+pub mod adaptive_brightness;
+
+#[path = "../../src/power/backlight.rs"]
+pub mod backlight;
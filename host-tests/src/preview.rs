@@ -0,0 +1,34 @@
+//! PNG export of framebuffer contents for previews and snapshots
+
+use anyhow::Result;
+use std::path::Path;
+use crate::display::DisplayManager;
+
+/// Encode the framebuffer as an 8-bit RGB PNG
+pub fn encode_png(display: &DisplayManager) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut out, display.width() as u32, display.height() as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&display.to_rgb888())?;
+    }
+    Ok(out)
+}
+
+pub fn save_png(display: &DisplayManager, path: impl AsRef<Path>) -> Result<()> {
+    std::fs::write(path, encode_png(display)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_png_header() {
+        let png = encode_png(&DisplayManager::new()).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    }
+}
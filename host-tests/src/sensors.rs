@@ -0,0 +1,16 @@
+//! Host-compilable parts of the firmware sensor layer
+
+#[path = "../../src/sensors/history.rs"]
+pub mod history;
+
+#[path = "../../src/sensors/power_state.rs"]
+pub mod power_state;
+
+#[path = "../../src/sensors/adc_cal.rs"]
+pub mod adc_cal;
+
+#[path = "../../src/sensors/registry.rs"]
+pub mod registry;
+
+#[path = "../../src/sensors/air_quality.rs"]
+pub mod air_quality;
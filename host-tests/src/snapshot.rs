@@ -0,0 +1,70 @@
+//! Golden-image comparison for rendered screens.
+//!
+//! Goldens live in `host-tests/snapshots/<name>.png`. Run the tests with
+//! `UPDATE_SNAPSHOTS=1` to (re)record them after an intentional UI change; on a
+//! mismatch the rendered frame is written next to the golden as `<name>.actual.png`.
+
+use anyhow::{bail, Context, Result};
+use std::path::PathBuf;
+use crate::display::DisplayManager;
+use crate::preview::save_png;
+
+fn snapshot_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("snapshots")
+}
+
+/// Decode an 8-bit RGB PNG into (width, height, pixels)
+pub fn decode_png(bytes: &[u8]) -> Result<(u32, u32, Vec<u8>)> {
+    let decoder = png::Decoder::new(bytes);
+    let mut reader = decoder.read_info()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf)?;
+    if info.color_type != png::ColorType::Rgb || info.bit_depth != png::BitDepth::Eight {
+        bail!("expected 8-bit RGB, got {:?} {:?}", info.color_type, info.bit_depth);
+    }
+    buf.truncate(info.buffer_size());
+    Ok((info.width, info.height, buf))
+}
+
+/// Compare the framebuffer against its golden image, or record it when
+/// `UPDATE_SNAPSHOTS` is set
+pub fn check_snapshot(name: &str, display: &DisplayManager) -> Result<()> {
+    let dir = snapshot_dir();
+    let golden = dir.join(format!("{}.png", name));
+    let actual = dir.join(format!("{}.actual.png", name));
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(&dir)?;
+        save_png(display, &golden)?;
+        let _ = std::fs::remove_file(&actual);
+        return Ok(());
+    }
+
+    let bytes = std::fs::read(&golden)
+        .with_context(|| format!("missing golden {} (run with UPDATE_SNAPSHOTS=1)", golden.display()))?;
+    let (width, height, expected) = decode_png(&bytes)?;
+    if (width, height) != (display.width() as u32, display.height() as u32) {
+        save_png(display, &actual)?;
+        bail!("{}: size {}x{} differs from golden {}x{}", name, display.width(), display.height(), width, height);
+    }
+
+    let rendered = display.to_rgb888();
+    let mut diffs = expected.chunks(3).zip(rendered.chunks(3)).enumerate().filter(|(_, (a, b))| a != b);
+    if let Some((first, _)) = diffs.next() {
+        let count = 1 + diffs.count();
+        save_png(display, &actual)?;
+        bail!(
+            "{}: {} pixels differ from golden (first at {},{}); see {}",
+            name, count, first as u32 % width, first as u32 / width, actual.display()
+        );
+    }
+    let _ = std::fs::remove_file(&actual);
+    Ok(())
+}
+
+/// Panicking wrapper for use in tests
+pub fn assert_snapshot(name: &str, display: &DisplayManager) {
+    if let Err(e) = check_snapshot(name, display) {
+        panic!("snapshot mismatch: {:#}", e);
+    }
+}
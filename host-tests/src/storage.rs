@@ -0,0 +1,6 @@
+//! Host-compilable parts of the firmware storage layer
+
+#[path = "../../src/storage/kv.rs"]
+pub mod kv;
+#[path = "../../src/storage/log_ring.rs"]
+pub mod log_ring;
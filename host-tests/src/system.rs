@@ -0,0 +1,13 @@
+//! Host-compilable parts of the firmware system layer
+
+#[path = "../../src/system/encoder.rs"]
+pub mod encoder;
+
+#[path = "../../src/system/selftest.rs"]
+pub mod selftest;
+
+#[path = "../../src/system/lifetime_stats.rs"]
+pub mod lifetime_stats;
+
+#[path = "../../src/system/safe_mode.rs"]
+pub mod safe_mode;
@@ -0,0 +1,16 @@
+//! Host-compilable parts of the firmware UI (layout engine and widgets)
+
+#[path = "../../src/ui/layout.rs"]
+pub mod layout;
+#[path = "../../src/ui/components/mod.rs"]
+pub mod components;
+#[path = "../../src/ui/views.rs"]
+pub mod views;
+#[path = "../../src/ui/registry.rs"]
+pub mod registry;
+#[path = "../../src/ui/animation.rs"]
+pub mod animation;
+#[path = "../../src/ui/ui_state.rs"]
+pub mod ui_state;
+#[path = "../../src/ui/kiosk.rs"]
+pub mod kiosk;
@@ -0,0 +1,482 @@
+//! Web API tests: the firmware's request/response logic (network::api_core)
+//! wired into a mock server the same way web_server.rs and api_routes.rs wire
+//! it into EspHttpServer.
+
+use std::sync::{Arc, Mutex};
+use serde_json::json;
+use esp32_dashboard_tests::config::Config;
+use esp32_dashboard_tests::metrics_data::MetricsData;
+use esp32_dashboard_tests::mock_http::{Method, MockServer, Request, Response};
+use esp32_dashboard_tests::network::api_core;
+use esp32_dashboard_tests::sensors::history::SensorHistory;
+
+const UPTIME: u64 = 3600;
+const HEAP_FREE: u32 = 150_000;
+
+struct Device {
+    config: Arc<Mutex<Config>>,
+    metrics: Arc<Mutex<MetricsData>>,
+    history: Arc<Mutex<SensorHistory>>,
+}
+
+impl Device {
+    fn new() -> Self {
+        Self {
+            config: Arc::new(Mutex::new(Config::default())),
+            metrics: Arc::new(Mutex::new(MetricsData::default())),
+            history: Arc::new(Mutex::new(SensorHistory::new())),
+        }
+    }
+
+    fn server(&self) -> MockServer {
+        let mut server = MockServer::new();
+
+        let config = self.config.clone();
+        server.fn_handler("/api/config", Method::Get, move |_| {
+            let cfg = config.lock().unwrap();
+            Response::json(200, &serde_json::to_value(&*cfg).unwrap())
+        });
+
+        let config = self.config.clone();
+        server.fn_handler("/api/config", Method::Post, move |req| {
+            if req.body.len() > api_core::MAX_CONFIG_BODY {
+                return Response::error(413, "PAYLOAD_TOO_LARGE", "Payload too large (max 4KB)");
+            }
+            let current = config.lock().unwrap().clone();
+            match api_core::apply_config_update(&current, &req.body) {
+                Ok(cfg) => {
+                    *config.lock().unwrap() = cfg;
+                    Response::ok()
+                }
+                Err(e) => Response::config_error(&e),
+            }
+        });
+
+        let config = self.config.clone();
+        server.fn_handler("/api/wifi/pin", Method::Post, move |req| {
+            let current = config.lock().unwrap().clone();
+            match api_core::apply_bssid_pin(&current, &req.body) {
+                Ok((cfg, _)) => {
+                    *config.lock().unwrap() = cfg;
+                    Response::ok()
+                }
+                Err(e) => Response::bad_request(&e.to_string()),
+            }
+        });
+
+        let metrics = self.metrics.clone();
+        server.fn_handler("/api/metrics", Method::Get, move |_| {
+            let guard = metrics.try_lock().ok();
+            Response::streamed_json(200, &api_core::metrics_json(UPTIME, HEAP_FREE, guard.as_deref()))
+        });
+
+        let metrics = self.metrics.clone();
+        server.fn_handler("/api/summary", Method::Get, move |_| {
+            let snapshot = metrics.lock().unwrap().clone();
+            Response::json(200, &api_core::summary_json(&snapshot, Some("192.168.1.42"), UPTIME, true, "v1.2.3"))
+        });
+
+        for (path, key, unit) in [
+            ("/api/v1/sensors/temperature/history", "temperature", "celsius"),
+            ("/api/v1/sensors/battery/history", "battery", "percentage"),
+        ] {
+            let history = self.history.clone();
+            server.fn_handler(path, Method::Get, move |req| {
+                let hours = api_core::history_hours(&req.uri);
+                history.lock().unwrap().with_history(key, hours, |data| {
+                    Response::streamed_json(200, &api_core::history_json(hours, data, unit))
+                })
+            });
+        }
+
+        let history = self.history.clone();
+        server.fn_handler("/api/v1/sensors/history", Method::Get, move |req| {
+            let hours = api_core::history_hours(&req.uri);
+            let Some(key) = api_core::history_key(&req.uri) else {
+                return Response::bad_request("key is required");
+            };
+            let history = history.lock().unwrap();
+            if !history.keys().iter().any(|k| k == key) {
+                return Response::error(404, "NOT_FOUND", &format!("no history for {}", key));
+            }
+            history.with_history(key, hours, |data| Response::streamed_json(200, &api_core::history_json(hours, data, "ppm")))
+        });
+
+        server.fn_handler("/api/grafana/", Method::Get, |_| Response::ok());
+
+        let history = self.history.clone();
+        server.fn_handler("/api/grafana/search", Method::Post, move |req| {
+            let keys = history.lock().unwrap().keys();
+            Response::streamed_json(200, &api_core::grafana_search(&req.body, keys))
+        });
+
+        let history = self.history.clone();
+        server.fn_handler("/api/grafana/query", Method::Post, move |req| {
+            match api_core::grafana_query(&req.body, &history.lock().unwrap()) {
+                Ok(series) => Response::streamed_json(200, &series),
+                Err(e) => Response::bad_request(&e.to_string()),
+            }
+        });
+
+        server
+    }
+}
+
+fn post_config(server: &MockServer, body: serde_json::Value) -> Response {
+    server.handle(&Request::post("/api/config", body.to_string()))
+}
+
+#[test]
+fn config_partial_update_only_touches_given_fields() {
+    let device = Device::new();
+    let server = device.server();
+    let before = device.config.lock().unwrap().clone();
+
+    let res = post_config(&server, json!({ "brightness": 42, "dim_timeout": 5, "push_interval": 3600, "thermal_limit": 100.0 }));
+    assert_eq!(res.status, 200);
+
+    let cfg = device.config.lock().unwrap().clone();
+    assert_eq!(cfg.brightness, 42);
+    assert_eq!(cfg.dim_timeout_secs, 5);
+    assert_eq!(cfg.push_interval_secs, 3600);
+    assert_eq!(cfg.thermal_limit_c, 100.0);
+    assert_eq!(cfg.timezone, before.timezone);
+    assert_eq!(cfg.push_url, before.push_url);
+}
+
+#[test]
+fn config_get_reflects_post() {
+    let device = Device::new();
+    let server = device.server();
+
+    post_config(&server, json!({ "day_start": "06:30", "timezone": "CET-1CEST,M3.5.0,M10.5.0/3", "hostname": "desk", "theme": "Light" }));
+    let body = server.handle(&Request::get("/api/config")).json_body();
+    assert_eq!(body["day_start_minutes"], 390);
+    assert_eq!(body["timezone"], "CET-1CEST,M3.5.0,M10.5.0/3");
+    assert_eq!(body["hostname"], "desk");
+    // The web pages read and set the theme by these names
+    assert_eq!(body["theme"], "Light");
+}
+
+#[test]
+fn config_rejects_invalid_input_without_changes() {
+    let device = Device::new();
+    let server = device.server();
+    let before = serde_json::to_value(&*device.config.lock().unwrap()).unwrap();
+
+    let cases = [
+        ("{not json", "Invalid JSON"),
+        (r#"{"brightness": 300}"#, "Invalid brightness: integer `300`, expected u8"),
+        (r#"{"push_interval": 0}"#, "Push interval must be 5-3600 s"),
+        (r#"{"weather_latitude": 91.5}"#, "Latitude must be -90 to 90"),
+        (r#"{"push_url": "ftp://host/x"}"#, "Push URL must start with http:// or https://"),
+        (r#"{"alerts_filters": ["a", "b", "c", "d", "e"]}"#, "At most 4 alert filters of up to 64 characters"),
+        (r#"{"kiosk_metric": "CO2"}"#, "Kiosk metric must be temperature, battery, a sensor key like scd40.co2 or widget:<label>"),
+        (r#"{"timer_alert_gpio": 38}"#, "GPIO38 is reserved or invalid"),
+        (r#"{"timer_alert_gpio": 10, "buzzer_gpio": 10}"#, "Each optional peripheral needs its own GPIO"),
+        (r#"{"buzzer_gpio": 11, "led_gpio": 11}"#, "Each optional peripheral needs its own GPIO"),
+        (r#"{"encoder_a_gpio": 12, "encoder_b_gpio": 12}"#, "Each optional peripheral needs its own GPIO"),
+        (r#"{"power_monitor_sda": 17, "power_monitor_scl": 17}"#, "Each optional peripheral needs its own GPIO"),
+        (r#"{"power_monitor_address": 96}"#, "INA219 address must be 0x40-0x4F"),
+        (r#"{"power_monitor_sda": 17, "air_quality_sda": 17}"#, "Each optional peripheral needs its own GPIO"),
+        (r#"{"night_start": "24:00"}"#, "Time must be HH:MM (24h)"),
+        (r#"{"timezone": "Europe/Paris; rm"}"#, "Timezone must be a POSIX TZ string"),
+        (r#"{"wifi_ssid": ""}"#, "WiFi SSID cannot be empty"),
+        (r#"{"hostname": "my_desk"}"#, "Hostname may only contain a-z, 0-9 and '-'"),
+        (r#"{"latency_host": "1.1.1.1/32"}"#, "Latency host must be a hostname or IPv4 address"),
+        (r#"{"nettest_url": "ftp://host/x"}"#, "Push URL must start with http:// or https://"),
+        (r#"{"button_actions": {"user_long": {"mqtt": {"topic": "desk/lamp", "payload": "on"}}}}"#, "MQTT button actions need an MQTT broker"),
+        (r#"{"static_ip": "192.168.1.50", "static_gateway": "10.0.0.1"}"#, "Gateway must be another address in the static IP's subnet"),
+        (r#"{"static_ip": "192.168.1.50", "static_netmask": "255.0.255.0", "static_gateway": "192.168.1.1"}"#, "Netmask must be contiguous, between /8 and /30"),
+        (r#"{"backlight_easing": "bounce"}"#, "Invalid backlight_easing: unknown variant `bounce`, expected one of `linear`, `ease_in`, `ease_out`, `ease_in_out`"),
+        (r#"{"sample_schedule": {"sensors": {"interval_ms": 100}}}"#, "sensors interval must be 500-3600000 ms"),
+        (r#"{"sample_schedule": {"processing": {"interval_ms": 100, "enabled": false}}}"#, "processing cannot be disabled"),
+        (r#"{"sample_schedule": {"display": {"interval_ms": 100}}}"#, "Invalid sample_schedule: unknown variant `display`, expected one of `sensors`, `network`, `processing`, `latency`"),
+    ];
+    for (body, message) in cases {
+        let res = server.handle(&Request::post("/api/config", body));
+        assert_eq!(res.status, 400, "{}", body);
+        assert_eq!(res.error_message().as_deref(), Some(message), "{}", body);
+    }
+
+    let after = serde_json::to_value(&*device.config.lock().unwrap()).unwrap();
+    assert_eq!(before, after);
+}
+
+#[test]
+fn config_errors_list_every_bad_field() {
+    let device = Device::new();
+    let server = device.server();
+    let before = device.config.lock().unwrap().clone();
+
+    let res = post_config(&server, json!({ "brightness": 80, "dim_timeout": 0, "wifi_ssid": "", "hostname": "Desk", "led_gpio": 38 }));
+    assert_eq!(res.status, 400);
+    assert_eq!(res.json_body()["error"]["fields"], json!([
+        { "field": "wifi_ssid", "message": "WiFi SSID cannot be empty" },
+        { "field": "led_gpio", "message": "GPIO38 is reserved or invalid" },
+        { "field": "hostname", "message": "Hostname may only contain a-z, 0-9 and '-'" },
+        { "field": "dim_timeout", "message": "Dim timeout must be 5-3600 s" },
+    ]));
+    assert_eq!(device.config.lock().unwrap().brightness, before.brightness, "nothing applied");
+
+    // Type errors are pinned to their keys too
+    let res = post_config(&server, json!({ "brightness": 300, "led_count": "many", "auto_dim": true }));
+    let fields = res.json_body()["error"]["fields"].clone();
+    assert_eq!(fields.as_array().unwrap().iter().map(|f| f["field"].as_str().unwrap()).collect::<Vec<_>>(), ["brightness", "led_count"]);
+
+    // Clashes found on the merged config name the field that was just sent
+    post_config(&server, json!({ "buzzer_gpio": 10 }));
+    let res = post_config(&server, json!({ "led_gpio": 10 }));
+    assert_eq!(res.json_body()["error"]["fields"][0]["field"], "led_gpio");
+}
+
+#[test]
+fn config_sample_schedule_updates_only_given_sources() {
+    let device = Device::new();
+    let server = device.server();
+    let res = post_config(&server, json!({ "sample_schedule": { "sensors": { "interval_ms": 60000 }, "latency": { "interval_ms": 30000, "enabled": false } } }));
+    assert_eq!(res.status, 200);
+
+    let schedule = device.config.lock().unwrap().sample_schedule;
+    assert_eq!(schedule.sensors.interval_ms, 60_000);
+    assert!(!schedule.latency.enabled);
+    assert_eq!(schedule.network, Config::default().sample_schedule.network);
+}
+
+#[test]
+fn config_round_trips_button_actions() {
+    let device = Device::new();
+    let server = device.server();
+    let map = json!({ "boot_long": "toggle_display", "user_long": { "mqtt": { "topic": "desk/lamp", "payload": "on" } } });
+
+    let res = post_config(&server, json!({ "button_actions": map, "mqtt_broker": " mqtt://10.0.0.5:1883 " }));
+    assert_eq!(res.status, 200);
+
+    let body = server.handle(&Request::get("/api/config")).json_body();
+    assert_eq!(body["mqtt_broker"], "mqtt://10.0.0.5:1883");
+    assert_eq!(body["button_actions"]["boot_long"], "toggle_display");
+    assert_eq!(body["button_actions"]["user_long"]["mqtt"]["topic"], "desk/lamp");
+    // Slots left out keep their defaults
+    assert_eq!(body["button_actions"]["user_click"], "next_screen");
+
+    let res = post_config(&server, json!({ "mqtt_broker": "" }));
+    assert_eq!(res.error_message().as_deref(), Some("MQTT button actions need an MQTT broker"));
+}
+
+#[test]
+fn config_validates_syslog_server() {
+    let device = Device::new();
+    let server = device.server();
+
+    let res = post_config(&server, json!({ "syslog_enabled": true }));
+    assert_eq!(res.error_message().as_deref(), Some("Syslog needs a server"));
+    let res = post_config(&server, json!({ "syslog_server": "udp://logs.lan" }));
+    assert_eq!(res.status, 400);
+
+    assert_eq!(post_config(&server, json!({ "syslog_enabled": true, "syslog_server": " logs.lan:1514 " })).status, 200);
+    let cfg = device.config.lock().unwrap().clone();
+    assert_eq!((cfg.syslog_enabled, cfg.syslog_server.as_str()), (true, "logs.lan:1514"));
+}
+
+#[test]
+fn config_validates_custom_widgets() {
+    let device = Device::new();
+    let server = device.server();
+    let widget = json!({ "label": "Solar", "url": "http://10.0.0.2/api", "path": "$.power" });
+
+    let res = post_config(&server, json!({ "custom_widgets": vec![widget.clone(); 5] }));
+    assert_eq!(res.error_message().as_deref(), Some("At most 4 custom widgets"));
+
+    let res = post_config(&server, json!({ "custom_widgets": [{ "label": "", "url": "http://x", "path": "$.a" }] }));
+    assert_eq!(res.error_message().as_deref(), Some("Widget label must be 1-20 characters"));
+
+    assert_eq!(post_config(&server, json!({ "custom_widgets": [widget] })).status, 200);
+    assert_eq!(device.config.lock().unwrap().custom_widgets[0].label, "Solar");
+}
+
+#[test]
+fn config_alert_gpio_zero_disables() {
+    let device = Device::new();
+    let server = device.server();
+
+    assert_eq!(post_config(&server, json!({ "timer_alert_gpio": 21 })).status, 200);
+    assert_eq!(device.config.lock().unwrap().timer_alert_gpio, Some(21));
+    assert_eq!(post_config(&server, json!({ "timer_alert_gpio": 0 })).status, 200);
+    assert_eq!(device.config.lock().unwrap().timer_alert_gpio, None);
+}
+
+#[test]
+fn config_rejects_oversized_body() {
+    let server = Device::new().server();
+    let res = server.handle(&Request::post("/api/config", vec![b' '; api_core::MAX_CONFIG_BODY + 1]));
+    assert_eq!(res.status, 413);
+}
+
+#[test]
+fn wifi_pin_normalises_and_clears_bssid() {
+    let device = Device::new();
+    let server = device.server();
+
+    let res = server.handle(&Request::post("/api/wifi/pin", r#"{"bssid": "24-0A-C4-AA-BB-CC"}"#));
+    assert_eq!(res.status, 200);
+    assert_eq!(device.config.lock().unwrap().wifi_bssid, "24:0a:c4:aa:bb:cc");
+
+    let res = server.handle(&Request::post("/api/wifi/pin", r#"{"bssid": "24:0a:c4"}"#));
+    assert_eq!(res.error_message().as_deref(), Some("BSSID must look like aa:bb:cc:dd:ee:ff"));
+    assert_eq!(device.config.lock().unwrap().wifi_bssid, "24:0a:c4:aa:bb:cc");
+
+    let res = server.handle(&Request::post("/api/wifi/pin", r#"{"bssid": null}"#));
+    assert_eq!(res.status, 200);
+    assert_eq!(device.config.lock().unwrap().wifi_bssid, "");
+}
+
+#[test]
+fn metrics_json_rounds_and_derives_fields() {
+    let device = Device::new();
+    {
+        let mut m = device.metrics.lock().unwrap();
+        m.temperature = 41.26;
+        m.fps_actual = 58.34;
+        m.frame_count = 200;
+        m.skip_count = 50;
+        m.wifi_ssid = "HomeLab".to_string();
+    }
+    let body = device.server().handle(&Request::get("/api/metrics")).json_body();
+
+    assert_eq!(body["uptime"], UPTIME);
+    assert_eq!(body["heap_free"], HEAP_FREE);
+    assert!((body["temperature"].as_f64().unwrap() - 41.3).abs() < 1e-4);
+    assert!((body["fps_actual"].as_f64().unwrap() - 58.3).abs() < 1e-4);
+    assert_eq!(body["skip_rate"], 25.0);
+    assert_eq!(body["wifi_ssid"], "HomeLab");
+    assert!(body.get("error").is_none());
+}
+
+#[test]
+fn metrics_json_degrades_when_store_is_busy() {
+    let device = Device::new();
+    let server = device.server();
+    let _held = device.metrics.lock().unwrap();
+
+    let body = server.handle(&Request::get("/api/metrics")).json_body();
+    assert_eq!(body, json!({ "uptime": UPTIME, "heap_free": HEAP_FREE, "error": "metrics_locked" }));
+}
+
+#[test]
+fn summary_is_flat_and_flags_degraded_state() {
+    let device = Device::new();
+    {
+        let mut m = device.metrics.lock().unwrap();
+        m.wifi_connected = true;
+        m.wifi_rssi = -61;
+        m.temperature = 38.04;
+        m.fps_actual = 29.96;
+        m.heap_free = HEAP_FREE;
+        m.battery_percentage = 80;
+    }
+    let server = device.server();
+    let body = server.handle(&Request::get("/api/summary")).json_body();
+    assert_eq!(body, json!({
+        "status": "ok", "ip": "192.168.1.42", "rssi": -61, "temp": 38.0, "battery": 80,
+        "charging": false, "fps": 30.0, "heap": HEAP_FREE, "version": "v1.2.3", "uptime": UPTIME
+    }));
+
+    device.metrics.lock().unwrap().wifi_connected = false;
+    let body = server.handle(&Request::get("/api/summary")).json_body();
+    assert_eq!(body["status"], "degraded");
+    assert!(body["rssi"].is_null());
+}
+
+#[test]
+fn history_export_uses_hours_and_unit() {
+    let device = Device::new();
+    {
+        let history = device.history.lock().unwrap();
+        history.add_temperature(21.5);
+        history.add_battery(87.0);
+    }
+    let server = device.server();
+
+    let body = server.handle(&Request::get("/api/v1/sensors/temperature/history")).json_body();
+    assert_eq!(body["hours"], api_core::DEFAULT_HISTORY_HOURS);
+    assert_eq!(body["unit"], "celsius");
+    assert_eq!(body["data"][0]["value"], 21.5);
+
+    let body = server.handle(&Request::get("/api/v1/sensors/battery/history?foo=1&hours=2")).json_body();
+    assert_eq!(body["hours"], 2);
+    assert_eq!(body["unit"], "percentage");
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+
+    let body = server.handle(&Request::get("/api/v1/sensors/battery/history?hours=abc")).json_body();
+    assert_eq!(body["hours"], api_core::DEFAULT_HISTORY_HOURS);
+}
+
+#[test]
+fn history_export_covers_registered_sensors() {
+    let device = Device::new();
+    {
+        let history = device.history.lock().unwrap();
+        history.add("scd40.co2", 812.0);
+        history.add_temperature(21.5);
+    }
+    let server = device.server();
+
+    let body = server.handle(&Request::get("/api/v1/sensors/history?key=scd40.co2&hours=1")).json_body();
+    assert_eq!(body["hours"], 1);
+    assert_eq!(body["data"][0]["value"], 812.0);
+    let body = server.handle(&Request::get("/api/v1/sensors/history?key=temperature")).json_body();
+    assert_eq!(body["data"][0]["value"], 21.5);
+
+    assert_eq!(server.handle(&Request::get("/api/v1/sensors/history?key=sgp30.tvoc")).status, 404);
+    assert_eq!(server.handle(&Request::get("/api/v1/sensors/history?hours=1&key=")).status, 400);
+}
+
+#[test]
+fn grafana_datasource_serves_history() {
+    let device = Device::new();
+    {
+        let history = device.history.lock().unwrap();
+        history.add_temperature(21.5);
+        for co2 in [600.0, 650.0, 700.0, 750.0, 800.0] {
+            history.add("scd40.co2", co2);
+        }
+    }
+    let server = device.server();
+    assert_eq!(server.handle(&Request::get("/api/grafana/")).status, 200);
+
+    let body = server.handle(&Request::post("/api/grafana/search", json!({ "target": "" }).to_string())).json_body();
+    assert_eq!(body, json!(["scd40.co2", "temperature"]));
+    let body = server.handle(&Request::post("/api/grafana/search", json!({ "target": "co2" }).to_string())).json_body();
+    assert_eq!(body, json!(["scd40.co2"]));
+
+    let query = |from: &str, max_points: u32| json!({
+        "range": { "from": from, "to": "2100-01-01T00:00:00.000Z" },
+        "targets": [{ "target": "scd40.co2", "refId": "A", "type": "timeserie" }, { "target": "nope", "refId": "B" }],
+        "maxDataPoints": max_points,
+    }).to_string();
+    let body = server.handle(&Request::post("/api/grafana/query", query("2000-01-01T00:00:00.000Z", 100))).json_body();
+    assert_eq!(body[0]["target"], "scd40.co2");
+    let points = body[0]["datapoints"].as_array().unwrap();
+    assert_eq!(points.len(), 5);
+    assert_eq!(points[0][0], 600.0);
+    assert!(points[0][1].as_u64().unwrap() > 1_000_000_000_000, "milliseconds");
+    assert_eq!(body[1], json!({ "target": "nope", "datapoints": [] }));
+
+    let body = server.handle(&Request::post("/api/grafana/query", query("2000-01-01T00:00:00.000Z", 2))).json_body();
+    assert_eq!(body[0]["datapoints"], json!([[600.0, points[0][1]], [750.0, points[3][1]]]));
+    let body = server.handle(&Request::post("/api/grafana/query", query("2099-12-31T23:00:00+01:00", 100))).json_body();
+    assert_eq!(body[0]["datapoints"], json!([]));
+
+    let res = server.handle(&Request::post("/api/grafana/query", query("yesterday", 100)));
+    assert_eq!(res.status, 400);
+}
+
+#[test]
+fn grafana_times_are_rfc3339() {
+    assert_eq!(api_core::parse_rfc3339("1970-01-01T00:00:00Z"), Some(0));
+    assert_eq!(api_core::parse_rfc3339("2024-05-01T12:30:00.123Z"), Some(1_714_566_600));
+    assert_eq!(api_core::parse_rfc3339("2024-05-01T14:30:00+02:00"), Some(1_714_566_600));
+    for bad in ["2024-05-01", "2024-05-01T12:30:00", "2024-13-01T00:00:00Z", "2024-05-01T12:30:00.Z", "2024-05-01T12:30:00+2:00", "2024-05-01T12:30:00+-1:00"] {
+        assert_eq!(api_core::parse_rfc3339(bad), None, "{}", bad);
+    }
+}
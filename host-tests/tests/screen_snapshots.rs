@@ -0,0 +1,329 @@
+//! Golden-image tests for the firmware screens, rendered with fixed fake data
+//! through the same view code UiManager uses on the device.
+//!
+//! After an intentional UI change, re-record with:
+//!     UPDATE_SNAPSHOTS=1 cargo test --target x86_64-unknown-linux-gnu --test screen_snapshots
+
+use esp32_dashboard_tests::display::{colors::*, draw_list::FramePresenter, DisplayManager};
+use esp32_dashboard_tests::snapshot::assert_snapshot;
+use esp32_dashboard_tests::ui::registry::{TextRow, TextView};
+use esp32_dashboard_tests::ui::views::{self, *};
+
+const VERSION: &str = "v0.0-test";
+
+fn connected_network() -> NetworkView<'static> {
+    NetworkView {
+        connected: true,
+        ssid: "HomeLab",
+        ip: Some("192.168.1.42"),
+        ipv6: None,
+        show_ipv6: false,
+        signal: -58,
+        mac: "24:6F:28:AA:BB:CC",
+        gateway: Some("192.168.1.1"),
+        gateway_rtt: &[],
+        host_rtt: &[],
+        disconnect_reasons: "",
+    }
+}
+
+#[test]
+fn system_screen() {
+    let mut display = DisplayManager::new();
+    let layout = SystemLayout::new();
+    let mut fields = SystemFields::new(&layout);
+
+    views::draw_system_chrome(&mut display, &layout).unwrap();
+    fields.clock.set(&mut display, "02:14:07", WHITE).unwrap();
+    views::draw_system_battery(&mut display, &layout, &mut fields, 76, false, false).unwrap();
+    let values = [
+        ("2h 14m", PRIMARY_GREEN),
+        ("142 KB", PRIMARY_GREEN),
+        ("240 MHz C0:23% C1:8%", PRIMARY_GREEN),
+        ("1/16MB", PRIMARY_GREEN),
+        ("41.5°C", YELLOW),
+        ("7MB free", PRIMARY_GREEN),
+        ("99.8% 7d, 1 crashes", YELLOW),
+    ];
+    for (field, (text, color)) in fields.values.iter_mut().zip(values) {
+        field.set(&mut display, text, color).unwrap();
+    }
+    views::draw_system_progress(&mut display, &layout, 60).unwrap();
+    views::draw_fps(&mut display, 58.3).unwrap();
+
+    assert_snapshot("system", &display);
+}
+
+#[test]
+fn network_screen_connected() {
+    let mut display = DisplayManager::new();
+    let mut fields = NetworkFields::new();
+
+    views::draw_network_chrome(&mut display).unwrap();
+    fields.clock.set(&mut display, "02:14:07", WHITE).unwrap();
+    views::draw_network(&mut display, &mut fields, &connected_network()).unwrap();
+
+    assert_snapshot("network_connected", &display);
+}
+
+#[test]
+fn network_screen_latency() {
+    let mut display = DisplayManager::new();
+    let mut fields = NetworkFields::new();
+    let gateway = [Some(3), Some(4), Some(3), Some(18), Some(5), Some(4), Some(3), Some(4)];
+    let host = [Some(21), Some(24), None, Some(96), Some(40), Some(26), Some(23), Some(22)];
+    let view = NetworkView { gateway_rtt: &gateway, host_rtt: &host, disconnect_reasons: "beacon_timeout 5, auth_fail 2", ..connected_network() };
+
+    views::draw_network_chrome(&mut display).unwrap();
+    views::draw_network(&mut display, &mut fields, &view).unwrap();
+
+    assert_snapshot("network_latency", &display);
+}
+
+#[test]
+fn network_screen_disconnect_reasons() {
+    let mut display = DisplayManager::new();
+    let mut fields = NetworkFields::new();
+    let view = NetworkView { connected: false, ip: None, disconnect_reasons: "auth_fail 12, beacon_timeout 3", ..connected_network() };
+
+    views::draw_network_chrome(&mut display).unwrap();
+    views::draw_network(&mut display, &mut fields, &view).unwrap();
+
+    assert_snapshot("network_disconnect_reasons", &display);
+}
+
+#[test]
+fn network_screen_ipv6() {
+    let mut display = DisplayManager::new();
+    let mut fields = NetworkFields::new();
+    let view = NetworkView { ipv6: Some("2001:db8:85a3::8a2e:370:7334"), show_ipv6: true, ..connected_network() };
+
+    views::draw_network_chrome(&mut display).unwrap();
+    views::draw_network(&mut display, &mut fields, &view).unwrap();
+
+    assert_snapshot("network_ipv6", &display);
+}
+
+#[test]
+fn network_screen_unconfigured() {
+    let mut display = DisplayManager::new();
+    let mut fields = NetworkFields::new();
+    let view = NetworkView { connected: false, ssid: "Not connected", ip: None, ipv6: None, show_ipv6: false, signal: -100, mac: "Unknown", gateway: None, gateway_rtt: &[], host_rtt: &[], disconnect_reasons: "" };
+
+    views::draw_network_chrome(&mut display).unwrap();
+    views::draw_network(&mut display, &mut fields, &view).unwrap();
+
+    assert_snapshot("network_unconfigured", &display);
+}
+
+#[test]
+fn sensor_screen() {
+    let mut display = DisplayManager::new();
+    let mut fields = SensorFields::new();
+    let view = SensorView {
+        battery_percent: 64,
+        battery_mv: 3912,
+        charging: false,
+        on_usb: true,
+        temperature: 27.4,
+        light: 312,
+        activity: 0.5,
+        power_warning: None,
+        power: None,
+        readings: None,
+    };
+
+    views::draw_sensor_chrome(&mut display, false).unwrap();
+    views::draw_sensor(&mut display, &mut fields, &view).unwrap();
+
+    assert_snapshot("sensor", &display);
+}
+
+#[test]
+fn sensor_screen_power_warning() {
+    let mut display = DisplayManager::new();
+    let mut fields = SensorFields::new();
+    let view = SensorView {
+        battery_percent: 100,
+        battery_mv: 4630,
+        charging: false,
+        on_usb: true,
+        temperature: 31.0,
+        light: 0,
+        activity: 0.5,
+        power_warning: Some("Brown-out reset, 3 voltage dips"),
+        power: None,
+        readings: None,
+    };
+
+    views::draw_sensor_chrome(&mut display, false).unwrap();
+    views::draw_sensor(&mut display, &mut fields, &view).unwrap();
+
+    assert_snapshot("sensor_power_warning", &display);
+}
+
+#[test]
+fn sensor_screen_power_monitor() {
+    let mut display = DisplayManager::new();
+    let mut fields = SensorFields::new();
+    let view = SensorView {
+        battery_percent: 100,
+        battery_mv: 4630,
+        charging: false,
+        on_usb: true,
+        temperature: 29.2,
+        light: 0,
+        activity: 0.5,
+        power_warning: None,
+        power: Some(PowerRow { now_mw: 612.4, average_mw: 548.9, energy_mwh: 1234.56 }),
+        readings: None,
+    };
+
+    views::draw_sensor_chrome(&mut display, true).unwrap();
+    views::draw_sensor(&mut display, &mut fields, &view).unwrap();
+
+    assert_snapshot("sensor_power_monitor", &display);
+}
+
+#[test]
+fn sensor_screen_readings() {
+    let mut display = DisplayManager::new();
+    let mut fields = SensorFields::new();
+    let view = SensorView {
+        battery_percent: 82,
+        battery_mv: 4015,
+        charging: false,
+        on_usb: false,
+        temperature: 23.6,
+        light: 0,
+        activity: 0.5,
+        power_warning: None,
+        power: None,
+        readings: Some("moisture 41 %  raw 410.0"),
+    };
+
+    views::draw_sensor_chrome(&mut display, false).unwrap();
+    views::draw_sensor(&mut display, &mut fields, &view).unwrap();
+
+    assert_snapshot("sensor_readings", &display);
+}
+
+#[test]
+fn settings_screen() {
+    let mut display = DisplayManager::new();
+    let mut fields = SettingsFields::new();
+
+    views::draw_settings_chrome(&mut display).unwrap();
+    views::draw_settings(&mut display, &mut fields, 80, false, VERSION).unwrap();
+
+    assert_snapshot("settings", &display);
+}
+
+#[test]
+fn ota_screen_idle() {
+    let mut display = DisplayManager::new();
+    let mut fields = OtaFields::new();
+
+    views::draw_ota_chrome(&mut display).unwrap();
+    fields.clock.set(&mut display, "02:14:07", WHITE).unwrap();
+    views::draw_ota_info(&mut display, VERSION, "ota_0").unwrap();
+    fields.status.set(&mut display, "Ready", TEXT_SECONDARY).unwrap();
+    views::draw_ota_endpoints(&mut display, true, Some("192.168.1.42")).unwrap();
+
+    assert_snapshot("ota_idle", &display);
+}
+
+#[test]
+fn ota_download_overlay() {
+    let mut display = DisplayManager::new();
+    let mut fields = OtaFields::new();
+
+    views::draw_ota_chrome(&mut display).unwrap();
+    views::draw_ota_info(&mut display, VERSION, "ota_0").unwrap();
+    fields.status.set(&mut display, "Downloading 42%", PRIMARY_BLUE).unwrap();
+    views::draw_ota_progress(&mut display, Some(42)).unwrap();
+    views::draw_ota_endpoints(&mut display, true, Some("192.168.1.42")).unwrap();
+    views::draw_ota_overlay(&mut display, 42).unwrap();
+
+    assert_snapshot("ota_overlay", &display);
+}
+
+#[test]
+fn alert_banner_overlay() {
+    let mut display = DisplayManager::new();
+    let mut fields = NetworkFields::new();
+
+    views::draw_network_chrome(&mut display).unwrap();
+    views::draw_network(&mut display, &mut fields, &connected_network()).unwrap();
+    views::draw_alert_bar(&mut display, "LOW BATTERY", "8%", PRIMARY_RED, 1).unwrap();
+
+    assert_snapshot("alert_banner", &display);
+}
+
+#[test]
+fn large_text_high_contrast() {
+    let mut display = DisplayManager::new();
+    display.set_high_contrast(true);
+    let view = TextView {
+        title: "System Status",
+        rows: vec![
+            TextRow::new("Uptime:", "2h 14m", PRIMARY_GREEN),
+            TextRow::new("Free Heap:", "142 KB", PRIMARY_GREEN),
+            TextRow::new("CPU Freq:", "240 MHz C0:23% C1:8%", PRIMARY_GREEN),
+            TextRow::new("Temp:", "41.5C", YELLOW),
+        ],
+    };
+
+    let frame = views::large_text_frame(&LargeTextLayout::new(), &view, 0);
+    FramePresenter::default().present(frame, &mut display).unwrap();
+    views::draw_alert_bar(&mut display, "LOW BATTERY", "8%", PRIMARY_RED, 2).unwrap();
+
+    assert_snapshot("large_text", &display);
+}
+
+#[test]
+fn air_quality_screen() {
+    let mut display = DisplayManager::new();
+    // A stuffy afternoon: CO2 climbing past the Moderate line, then a window opened
+    let co2_history: Vec<f32> = (0..120).map(|i| if i < 90 { 520.0 + i as f32 * 6.0 } else { 1060.0 - (i - 90) as f32 * 8.0 }).collect();
+    let view = AirQualityView {
+        co2: Some((820.0, false)),
+        tvoc_ppb: Some(140.0),
+        temperature: Some(22.4),
+        humidity: Some(41.0),
+        co2_history: &co2_history,
+    };
+
+    FramePresenter::default().present(views::air_quality_frame(&view), &mut display).unwrap();
+
+    assert_snapshot("air_quality", &display);
+}
+
+#[test]
+fn agenda_screen() {
+    let mut display = DisplayManager::new();
+    let row = |when: &str, title, today| AgendaRow { when: when.to_string(), title, today };
+    let rows = [
+        row("Now", "Stand-up", true),
+        row("14:00", "Design review, Q2 roadmap and hiring plan", true),
+        row("Tomorrow", "Team day", false),
+        row("Tomorrow 09:30", "Stand-up", false),
+        row("Fri 17 18:00", "Dinner", false),
+        row("Mon 20 09:30", "Not shown: sixth row", false),
+    ];
+    let view = AgendaView { rows: Some(&rows), error: Some("Calendar unreachable") };
+
+    FramePresenter::default().present(views::agenda_frame(&view), &mut display).unwrap();
+
+    assert_snapshot("agenda", &display);
+}
+
+#[test]
+fn kiosk_screen() {
+    let mut display = DisplayManager::new();
+    let view = KioskView { label: "scd40.co2", value: Some("812"), unit: "ppm", min_max: Some(("431", "1240")) };
+
+    FramePresenter::default().present(views::kiosk_frame(&view), &mut display).unwrap();
+
+    assert_snapshot("kiosk", &display);
+}
@@ -0,0 +1,116 @@
+#!/usr/bin/env python3
+"""
+Build a delta OTA patch for the dashboard firmware.
+
+Diffs two app images with bsdiff and repacks the result into the streaming
+format the device applies while receiving (see src/ota/delta.rs). The base
+must be the exact image the device is running, e.g. the previous release .bin.
+
+Uses the bsdiff4 Python module when installed, otherwise the bsdiff command.
+
+Usage: ota-delta.py <base.bin> <new.bin> <out.delta>
+"""
+
+import bz2
+import hashlib
+import os
+import shutil
+import struct
+import subprocess
+import sys
+import tempfile
+
+MAGIC = b"ESPDELTA"
+
+
+def bsdiff(base_path, new_path):
+    """Return a BSDIFF40 patch from base to new."""
+    try:
+        import bsdiff4
+        with open(base_path, "rb") as b, open(new_path, "rb") as n:
+            return bsdiff4.diff(b.read(), n.read())
+    except ImportError:
+        pass
+
+    if not shutil.which("bsdiff"):
+        sys.exit("bsdiff not found: pip install bsdiff4, or install bsdiff (brew/apt install bsdiff)")
+    with tempfile.TemporaryDirectory() as tmp:
+        patch_path = os.path.join(tmp, "patch")
+        subprocess.run(["bsdiff", base_path, new_path, patch_path], check=True)
+        with open(patch_path, "rb") as f:
+            return f.read()
+
+
+def offtin(raw):
+    """bsdiff's sign-magnitude 64-bit integer."""
+    value = int.from_bytes(raw, "little")
+    if value & (1 << 63):
+        value = -(value & ~(1 << 63))
+    return value
+
+
+def repack(patch, base, new):
+    """Interleave the control, diff and extra blocks of a BSDIFF40 patch."""
+    if patch[:8] != b"BSDIFF40":
+        sys.exit("Unexpected bsdiff output (not BSDIFF40)")
+    ctrl_len, diff_len, new_size = (offtin(patch[i:i + 8]) for i in (8, 16, 24))
+    if new_size != len(new):
+        sys.exit("bsdiff output does not match the new image size")
+
+    ctrl = bz2.decompress(patch[32:32 + ctrl_len])
+    diff = bz2.decompress(patch[32 + ctrl_len:32 + ctrl_len + diff_len])
+    extra = bz2.decompress(patch[32 + ctrl_len + diff_len:])
+
+    out = bytearray(MAGIC)
+    out += struct.pack("<II", len(base), len(new))
+    out += hashlib.sha256(base).digest()
+
+    diff_pos = extra_pos = 0
+    for i in range(0, len(ctrl), 24):
+        add, copy, seek = (offtin(ctrl[i + j:i + j + 8]) for j in (0, 8, 16))
+        out += struct.pack("<IIi", add, copy, seek)
+        out += diff[diff_pos:diff_pos + add]
+        out += extra[extra_pos:extra_pos + copy]
+        diff_pos += add
+        extra_pos += copy
+    return bytes(out)
+
+
+def apply(delta, base):
+    """Rebuild the new image from a repacked patch, as the device does."""
+    new_size = struct.unpack_from("<I", delta, 12)[0]
+    pos, base_pos = 48, 0
+    out = bytearray()
+    while len(out) < new_size:
+        add, copy, seek = struct.unpack_from("<IIi", delta, pos)
+        pos += 12
+        out += bytes((base[base_pos + k] + delta[pos + k]) & 0xFF for k in range(add))
+        pos += add
+        base_pos += add
+        out += delta[pos:pos + copy]
+        pos += copy
+        base_pos += seek
+    return bytes(out)
+
+
+def main():
+    if len(sys.argv) != 4:
+        sys.exit(__doc__.strip().splitlines()[-1])
+    base_path, new_path, out_path = sys.argv[1:]
+
+    with open(base_path, "rb") as f:
+        base = f.read()
+    with open(new_path, "rb") as f:
+        new = f.read()
+
+    delta = repack(bsdiff(base_path, new_path), base, new)
+    if apply(delta, base) != new:
+        sys.exit("Delta self-check failed")
+
+    with open(out_path, "wb") as f:
+        f.write(delta)
+    print(f"Delta: {len(delta)} bytes for a {len(new)} byte image")
+
+
+if __name__ == "__main__":
+    main()
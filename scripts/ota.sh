@@ -14,6 +14,12 @@ NC='\033[0m' # No Color
 # Default values
 FIRMWARE="${FIRMWARE:-target/xtensa-esp32s3-espidf/release/esp32-s3-dashboard}"
 PORT="${PORT:-80}"  # OTA endpoint is on main server port 80
+GITHUB_REPO="${GITHUB_REPO:-jtn0123/ESP32-S3-Display-Dashboard}"
+SCRIPT_DIR="$(cd "$(dirname "$0")" && pwd)"
+COMPRESS=0      # --compress: gzip the upload
+DELTA_BASE=""   # --delta <base.bin>: send a patch against the running image
+FORCE=0         # --force: install an image older than the one the device runs
+OTA_RESUME_RETRIES="${OTA_RESUME_RETRIES:-3}"  # resumes after a dropped plain upload
 
 # Function to print colored output
 print_color() {
@@ -22,18 +28,85 @@ print_color() {
     echo -e "${color}$@${NC}"
 }
 
+# Base URL for a device address; IPv6 literals (optionally already bracketed,
+# with a %zone for link-local) are bracketed as URLs require
+device_url() {
+    local host=${1#[}
+    host=${host%]}
+    if [[ "$host" == *:* ]]; then
+        host="[${host//%/%25}]"
+    fi
+    echo "http://${host}:${PORT}"
+}
+
+# Compact device status; firmware without /api/summary falls back to /api/system
+device_summary() {
+    local ip=$1
+    curl -gsf --connect-timeout 1 "$(device_url "$ip")/api/summary" 2>/dev/null \
+        || curl -gs --connect-timeout 1 "$(device_url "$ip")/api/system" 2>/dev/null
+}
+
 # Function to check if device is reachable and is an ESP32
 check_device() {
     local ip=$1
-    local response=$(curl -s --connect-timeout 1 "http://${ip}:${PORT}/api/system" 2>/dev/null)
+    local response=$(device_summary "$ip")
     # Check if response contains ESP32 identifiers
-    echo "$response" | grep -q '"version"' && echo "$response" | grep -q '"free_heap"'
+    echo "$response" | grep -q '"version"' && echo "$response" | grep -Eq '"(heap|free_heap)"'
 }
 
 # Function to get device info
 get_device_info() {
     local ip=$1
-    curl -s "http://${ip}:${PORT}/api/system" 2>/dev/null | grep -o '"version":"[^"]*"' | cut -d'"' -f4
+    device_summary "$ip" | grep -o '"version":"[^"]*"' | cut -d'"' -f4
+}
+
+# Release version ("0.6.26") of "v6.26", "6.26" or "0.6.26-dirty"; empty if
+# it is none of those. Same rules as src/release_version.rs
+release_of() {
+    local v=${1#v}
+    v=${v%%[-+]*}
+    [[ $v =~ ^[0-9]+\.[0-9]+$ ]] && v="0.$v"
+    [[ $v =~ ^[0-9]+\.[0-9]+\.[0-9]+$ ]] && echo "$v"
+}
+
+# Whether release $1 is older than release $2
+release_older() {
+    local IFS=.
+    local a=($1) b=($2) i
+    for i in 0 1 2; do
+        [ "${a[i]}" -lt "${b[i]}" ] && return 0
+        [ "${a[i]}" -gt "${b[i]}" ] && return 1
+    done
+    return 1
+}
+
+# Version stamped into a .bin image: the app descriptor follows the image and
+# first segment headers (magic 0xABCD5432 at 0x20, version string at 0x30)
+image_version() {
+    local image=$1
+    [ "$(od -An -tx1 -j 32 -N 4 "$image" 2>/dev/null | tr -d ' \n')" = "3254cdab" ] || return 0
+    dd if="$image" bs=1 skip=48 count=32 2>/dev/null | tr -d '\0'
+}
+
+# Release the device runs: the Cargo version from /api/firmware, or the display
+# version older firmware reports
+device_release() {
+    local ip=$1
+    local version=$(curl -gsf --connect-timeout 2 "$(device_url "$ip")/api/firmware" 2>/dev/null \
+        | grep -o '"cargo_version":"[^"]*"' | cut -d'"' -f4)
+    release_of "${version:-$(get_device_info "$ip")}"
+}
+
+# Uptime in seconds (/api/summary reports seconds, /api/system milliseconds)
+get_device_uptime() {
+    local ip=$1
+    local response=$(device_summary "$ip")
+    local secs=$(echo "$response" | grep -o '"uptime":[0-9]*' | cut -d':' -f2)
+    if [ -z "$secs" ]; then
+        local ms=$(echo "$response" | grep -o '"uptime_ms":[0-9]*' | cut -d':' -f2)
+        [ -n "$ms" ] && secs=$((ms / 1000))
+    fi
+    echo "$secs"
 }
 
 # Function to upload firmware
@@ -49,7 +122,7 @@ upload_firmware() {
     
     # Get device info before update
     local old_version=$(get_device_info "$ip")
-    local old_uptime=$(curl -s "http://${ip}:${PORT}/api/system" 2>/dev/null | grep -o '"uptime_ms":[0-9]*' | cut -d':' -f2)
+    local old_uptime=$(get_device_uptime "$ip")
     
     # Check if it's an ELF file and convert to binary if needed
     if file "$firmware" | grep -q "ELF"; then
@@ -78,6 +151,21 @@ upload_firmware() {
         print_color "$GREEN" "✅ Binary conversion successful"
     fi
     
+    # Going back a release needs --force
+    local new_release=$(release_of "$(image_version "$firmware")")
+    local old_release=$(device_release "$ip")
+    if [ -n "$new_release" ] && [ -n "$old_release" ] && release_older "$new_release" "$old_release"; then
+        if [ "$FORCE" != 1 ]; then
+            print_color "$RED" "❌ Image is $new_release but the device runs $old_release; not downgrading"
+            print_color "$YELLOW" "   Pass --force to install it anyway"
+            if [[ "$firmware" == *.bin ]] && [[ -f "${firmware%.bin}" ]]; then
+                rm -f "$firmware"
+            fi
+            return 1
+        fi
+        print_color "$YELLOW" "⚠️  Downgrading from $old_release to $new_release (--force)"
+    fi
+    
     local size=$(stat -f%z "$firmware" 2>/dev/null || stat -c%s "$firmware" 2>/dev/null)
     local size_mb=$(echo "scale=2; $size / 1024 / 1024" | bc)
     
@@ -85,12 +173,41 @@ upload_firmware() {
     print_color "$BLUE" "🔐 Calculating SHA256..."
     local sha256=$(shasum -a 256 "$firmware" | cut -d' ' -f1)
     
+    # Optional delta patch and gzip; the device decodes while writing, and
+    # X-SHA256 / X-Image-Size always describe the full image
+    local payload="$firmware"
+    local payload_dir=""
+    local encoding_headers=()
+    # Skip the device's maintenance window, if one is configured
+    [ "$APPLY_NOW" = 1 ] && encoding_headers+=(-H "X-OTA-Apply: now")
+    if [ -n "$DELTA_BASE" ] || [ "$COMPRESS" = 1 ]; then
+        payload_dir=$(mktemp -d)
+        if [ -n "$DELTA_BASE" ]; then
+            print_color "$BLUE" "🧩 Building delta against $DELTA_BASE..."
+            if ! python3 "$SCRIPT_DIR/ota-delta.py" "$DELTA_BASE" "$firmware" "$payload_dir/update.delta"; then
+                print_color "$RED" "❌ Failed to build delta patch"
+                rm -rf "$payload_dir"
+                return 1
+            fi
+            payload="$payload_dir/update.delta"
+            encoding_headers+=(-H "X-OTA-Delta: 1")
+        fi
+        gzip -9 -c "$payload" > "$payload_dir/update.gz"
+        payload="$payload_dir/update.gz"
+        encoding_headers+=(-H "Content-Encoding: gzip" -H "X-Image-Size: $size")
+    fi
+    local payload_size=$(stat -f%z "$payload" 2>/dev/null || stat -c%s "$payload" 2>/dev/null)
+    
     print_color "$BLUE" "\n📡 OTA Update Process"
     echo "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"
     echo "📍 Target device: $ip"
     echo "📦 Firmware size: ${size_mb} MB ($size bytes)"
+    if [ "$payload" != "$firmware" ]; then
+        echo "🗜️  Upload size: $payload_size bytes ($((payload_size * 100 / size))% of image)"
+    fi
     echo "🔐 SHA256: ${sha256:0:16}...${sha256: -16}"
     echo "🏷️  Current version: ${old_version:-unknown}"
+    echo "🏷️  Image version: ${new_release:-unknown}"
     echo "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"
     echo ""
     
@@ -106,30 +223,62 @@ upload_firmware() {
     ) &
     PROGRESS_PID=$!
     
-    # Upload with curl and capture response
-    response=$(curl -X POST \
-        -H "Content-Length: $size" \
-        -H "X-OTA-Password: esp32" \
-        -H "X-SHA256: $sha256" \
-        --data-binary "@$firmware" \
-        --connect-timeout 5 \
-        --max-time 60 \
-        -w "\n|||HTTP_CODE:%{http_code}|||TIME:%{time_total}|||" \
-        -s \
-        "http://${ip}:${PORT}/ota/update" 2>&1)
+    # Upload with curl and capture response. A plain upload that drops midway
+    # continues from the device's flashed offset once its prefix hash matches.
+    local offset=0
+    local resumes=0
+    while true; do
+        local offset_headers=()
+        if [ "$offset" -gt 0 ]; then
+            offset_headers=(-H "X-OTA-Offset: $offset" -H "X-Image-Size: $size")
+        fi
+        response=$(tail -c +$((offset + 1)) "$payload" | curl -X POST \
+            -H "Content-Length: $((payload_size - offset))" \
+            -H "X-OTA-Password: esp32" \
+            -H "X-SHA256: $sha256" \
+            "${encoding_headers[@]}" \
+            "${offset_headers[@]}" \
+            --data-binary "@-" \
+            --connect-timeout 5 \
+            --max-time 60 \
+            -w "\n|||HTTP_CODE:%{http_code}|||TIME:%{time_total}|||" \
+            -s -g \
+            "$(device_url "$ip")/ota/update" 2>&1 || true)
+        
+        http_code=$(echo "$response" | grep -o "|||HTTP_CODE:[0-9]*|||" | sed 's/|||HTTP_CODE://g' | sed 's/|||//g')
+        
+        # Only a dropped connection is worth resuming
+        if [ "$payload" != "$firmware" ] || { [ -n "$http_code" ] && [ "$http_code" != "000" ]; } \
+            || [ "$resumes" -ge "$OTA_RESUME_RETRIES" ]; then
+            break
+        fi
+        sleep 2
+        local resume_info=$(curl -gs --connect-timeout 3 "$(device_url "$ip")/api/ota/offset" 2>/dev/null)
+        echo "$resume_info" | grep -q '"resumable":true' || break
+        local device_offset=$(echo "$resume_info" | grep -o '"offset":[0-9]*' | cut -d':' -f2)
+        local device_prefix=$(echo "$resume_info" | grep -o '"prefix_sha256":"[^"]*"' | cut -d'"' -f4)
+        local local_prefix=$(head -c "$device_offset" "$firmware" | shasum -a 256 | cut -d' ' -f1)
+        if [ "$device_prefix" != "$local_prefix" ]; then
+            print_color "$RED" "\n❌ Flashed data does not match local firmware; not resuming"
+            break
+        fi
+        ((++resumes))
+        offset=$device_offset
+        print_color "$YELLOW" "\n🔁 Connection lost - resuming at $offset of $size bytes (attempt $resumes/$OTA_RESUME_RETRIES)"
+    done
     
     # Stop progress indicator
     kill $PROGRESS_PID 2>/dev/null
     echo "" # New line after dots
     
-    http_code=$(echo "$response" | grep -o "|||HTTP_CODE:[0-9]*|||" | sed 's/|||HTTP_CODE://g' | sed 's/|||//g')
     upload_time=$(echo "$response" | grep -o "|||TIME:[0-9.]*|||" | sed 's/|||TIME://g' | sed 's/|||//g')
     body=$(echo "$response" | sed 's/|||HTTP_CODE:[0-9]*|||//g' | sed 's/|||TIME:[0-9.]*|||//g')
     
-    # Clean up temporary binary if we created one
+    # Clean up temporary binary and payload if we created them
     if [[ "$firmware" == *.bin ]] && [[ -f "${firmware%.bin}" ]]; then
         rm -f "$firmware"
     fi
+    [ -n "$payload_dir" ] && rm -rf "$payload_dir"
     
     if [ "$http_code" = "200" ]; then
         print_color "$GREEN" "✅ Upload successful! (${upload_time}s)"
@@ -145,7 +294,7 @@ upload_firmware() {
         local device_online=false
         
         while [ $retries -lt $max_retries ]; do
-            if curl -s --connect-timeout 1 "http://${ip}:${PORT}/api/system" >/dev/null 2>&1; then
+            if [ -n "$(device_summary "$ip")" ]; then
                 device_online=true
                 break
             fi
@@ -158,22 +307,26 @@ upload_firmware() {
         if [ "$device_online" = true ]; then
             # Get new device info
             local new_version=$(get_device_info "$ip")
-            local new_uptime=$(curl -s "http://${ip}:${PORT}/api/system" 2>/dev/null | grep -o '"uptime_ms":[0-9]*' | cut -d':' -f2)
+            local new_uptime=$(get_device_uptime "$ip")
             
             print_color "$GREEN" "\n✨ OTA Update Complete!"
             echo "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"
             echo "📍 Device: $ip"
             echo "🏷️  Version: ${new_version:-unknown}"
-            if [ -n "$new_uptime" ] && [ "$new_uptime" -lt 60000 ]; then
+            if [ -n "$new_uptime" ] && [ "$new_uptime" -lt 60 ]; then
                 print_color "$GREEN" "✅ Device successfully restarted"
-                echo "⏱️  Uptime: $(($new_uptime / 1000))s (fresh boot)"
+                echo "⏱️  Uptime: ${new_uptime}s (fresh boot)"
             fi
             echo "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━"
         else
             print_color "$YELLOW" "⚠️  Device is still restarting or may need manual check"
-            echo "   Try accessing: http://${ip}/"
+            echo "   Try accessing: $(device_url "$ip")/"
         fi
         
+        return 0
+    elif [ "$http_code" = "202" ]; then
+        print_color "$GREEN" "✅ Upload successful! (${upload_time}s)"
+        print_color "$YELLOW" "⏳ $body"
         return 0
     else
         print_color "$RED" "\n❌ Upload failed! (HTTP $http_code)"
@@ -259,9 +412,145 @@ scan_network() {
     fi
 }
 
+# Function to hash a file (shasum on macOS, sha256sum on Linux)
+sha256_of() {
+    if command -v shasum >/dev/null 2>&1; then
+        shasum -a 256 "$1" | cut -d' ' -f1
+    else
+        sha256sum "$1" | cut -d' ' -f1
+    fi
+}
+
+# Function to list devices as "ip|version" lines on stdout (progress goes to stderr)
+discover_devices() {
+    local subnet=$1
+    local found=""
+
+    # Try common known IPs first for fast discovery
+    for ip in "10.27.27.201" "192.168.1.201" "192.168.0.201" "10.0.0.201"; do
+        if check_device "$ip"; then
+            echo "${ip}|$(get_device_info "$ip")"
+            found=1
+        fi
+    done
+    [ -n "$found" ] && return 0
+
+    subnet=${subnet:-$(ifconfig | grep "inet " | grep -v 127.0.0.1 | head -1 | awk '{print $2}' | cut -d. -f1-3)}
+    print_color "$YELLOW" "  Scanning subnet ${subnet}.0/24..." >&2
+
+    local tmpfile=$(mktemp)
+    for i in {1..254}; do
+        (
+            if check_device "${subnet}.${i}"; then
+                echo "${subnet}.${i}|$(get_device_info "${subnet}.${i}")" >> "$tmpfile"
+            fi
+        ) &
+
+        # Limit concurrent jobs (macOS compatible)
+        while (( $(jobs -r | wc -l) >= 20 )); do
+            sleep 0.1
+        done
+    done
+    wait
+
+    sort -t . -k 4 -n "$tmpfile"
+    rm -f "$tmpfile"
+}
+
+# Function to download the firmware asset of a GitHub release and verify it
+# against the SHA256 published in the release notes. Prints the local path.
+#
+# The notes must contain either a sha256sum-style line ("<hex>  <asset name>")
+# or a line like "SHA256: <hex>" when the release has a single .bin asset.
+download_release() {
+    local tag=$1
+    local api="https://api.github.com/repos/${GITHUB_REPO}/releases/tags/${tag}"
+    local auth=()
+    [ -n "$GITHUB_TOKEN" ] && auth=(-H "Authorization: Bearer $GITHUB_TOKEN")
+
+    print_color "$BLUE" "📥 Fetching release ${tag} from ${GITHUB_REPO}..." >&2
+    local release
+    if ! release=$(curl -sfL "${auth[@]}" -H "Accept: application/vnd.github+json" "$api"); then
+        print_color "$RED" "❌ Release ${tag} not found (or GitHub API rate limit hit; set GITHUB_TOKEN)" >&2
+        return 1
+    fi
+
+    local asset_urls=$(echo "$release" | grep -o '"browser_download_url": *"[^"]*\.bin"' | sed 's/.*"\(http[^"]*\)"$/\1/')
+    local asset_count=$(echo "$asset_urls" | grep -c . || true)
+    if [ "$asset_count" -ne 1 ]; then
+        print_color "$RED" "❌ Expected exactly one .bin asset in ${tag}, found ${asset_count}" >&2
+        return 1
+    fi
+    local asset_url=$asset_urls
+    local asset_name=$(basename "$asset_url")
+
+    # The API pretty-prints one field per line; unescape the notes into lines
+    local notes=$(printf '%b' "$(echo "$release" | grep '^ *"body":' | sed 's/^ *"body": *"//; s/",\{0,1\} *$//')" | tr -d '\r')
+    local expected=$(echo "$notes" | grep -F "$asset_name" | grep -oiE '\b[0-9a-f]{64}\b' | head -1)
+    if [ -z "$expected" ]; then
+        expected=$(echo "$notes" | grep -iE 'sha-?256' | grep -oiE '\b[0-9a-f]{64}\b' | head -1)
+    fi
+    if [ -z "$expected" ]; then
+        print_color "$RED" "❌ No SHA256 for ${asset_name} in the ${tag} release notes" >&2
+        return 1
+    fi
+
+    local dir=$(mktemp -d)
+    local firmware="${dir}/${asset_name}"
+    print_color "$YELLOW" "⬇️  Downloading ${asset_name}..." >&2
+    if ! curl -sfL "${auth[@]}" -o "$firmware" "$asset_url"; then
+        print_color "$RED" "❌ Download failed: $asset_url" >&2
+        rm -rf "$dir"
+        return 1
+    fi
+
+    local actual=$(sha256_of "$firmware")
+    if [ "$(echo "$actual" | tr 'A-F' 'a-f')" != "$(echo "$expected" | tr 'A-F' 'a-f')" ]; then
+        print_color "$RED" "❌ Checksum mismatch for ${asset_name}" >&2
+        echo "   expected: $expected" >&2
+        echo "   actual:   $actual" >&2
+        rm -rf "$dir"
+        return 1
+    fi
+    print_color "$GREEN" "✅ Checksum verified (${actual:0:16}...)" >&2
+    echo "$firmware"
+}
+
 # Main script
-print_color "$BLUE" "ESP32-S3 Dashboard OTA Tool"
-echo "==========================="
+# Upload options go before the command
+while [ $# -gt 0 ]; do
+    case "$1" in
+        --compress)
+            COMPRESS=1
+            shift
+            ;;
+        --now)
+            APPLY_NOW=1
+            shift
+            ;;
+        --force)
+            FORCE=1
+            shift
+            ;;
+        --delta)
+            if [ ! -f "$2" ] || file "$2" | grep -q "ELF"; then
+                print_color "$RED" "Usage: $0 --delta <base.bin> ... (base must be the .bin image the device runs)"
+                exit 1
+            fi
+            DELTA_BASE=$2
+            shift 2
+            ;;
+        *)
+            break
+            ;;
+    esac
+done
+
+# Keep stdout clean for machine-readable output
+if [ "$1 $2" != "devices --json" ]; then
+    print_color "$BLUE" "ESP32-S3 Dashboard OTA Tool"
+    echo "==========================="
+fi
 
 case "${1:-help}" in
     find)
@@ -294,8 +583,8 @@ case "${1:-help}" in
             
             if [ -n "$MDNS_DEVICES" ]; then
                 for device in $MDNS_DEVICES; do
-                    # Resolve the hostname to IP
-                    IP=$(timeout 1 dscacheutil -q host -a name "${device}.local" 2>/dev/null | grep "ip_address" | awk '{print $2}' | head -1 || true)
+                    # Resolve the hostname, preferring IPv4 but accepting an IPv6-only device
+                    IP=$(timeout 1 dscacheutil -q host -a name "${device}.local" 2>/dev/null | awk '/^ip_address/ && !v4 {v4=$2} /^ipv6_address/ && !v6 {v6=$2} END {print (v4 ? v4 : v6)}' || true)
                     if [ -n "$IP" ]; then
                         version=$(get_device_info "$IP")
                         print_color "$GREEN" "✓ Found via mDNS: $device at $IP (v${version:-unknown})"
@@ -346,49 +635,10 @@ case "${1:-help}" in
         print_color "$BLUE" "🔍 Finding ESP32 devices..."
         
         devices=()
-        
-        # First, try common known IPs for fast discovery
-        for ip in "10.27.27.201" "192.168.1.201" "192.168.0.201" "10.0.0.201"; do
-            if check_device "$ip"; then
-                devices+=("$ip")
-                print_color "$GREEN" "  ✓ Found ESP32: $ip"
-            fi
-        done
-        
-        # If no devices found, do subnet scan
-        if [ ${#devices[@]} -eq 0 ]; then
-            subnet=${2:-$(ifconfig | grep "inet " | grep -v 127.0.0.1 | head -1 | awk '{print $2}' | cut -d. -f1-3)}
-            print_color "$YELLOW" "  Scanning subnet ${subnet}.0/24..."
-            
-            # Create temp file for results
-            tmpfile=$(mktemp)
-            
-            # Scan in parallel using background jobs
-            for i in {1..254}; do
-                (
-                    if check_device "${subnet}.${i}"; then
-                        echo "${subnet}.${i}" >> "$tmpfile"
-                        print_color "$GREEN" "  ✓ Found ESP32: ${subnet}.${i}"
-                    fi
-                ) &
-                
-                # Limit concurrent jobs (macOS compatible)
-                while (( $(jobs -r | wc -l) >= 20 )); do
-                    sleep 0.1
-                done
-            done
-            
-            # Wait for all background jobs
-            wait
-            
-            # Read found devices
-            if [ -f "$tmpfile" ]; then
-                while IFS= read -r device; do
-                    devices+=("$device")
-                done < "$tmpfile"
-                rm -f "$tmpfile"
-            fi
-        fi
+        while IFS='|' read -r ip version; do
+            devices+=("$ip")
+            print_color "$GREEN" "  ✓ Found ESP32: $ip (v${version:-unknown})"
+        done < <(discover_devices "$2")
         
         if [ ${#devices[@]} -eq 0 ]; then
             print_color "$RED" "\n❌ No devices found"
@@ -412,26 +662,109 @@ case "${1:-help}" in
         print_color "$GREEN" "\n✨ Update complete: $success/${#devices[@]} successful"
         ;;
     
+    devices)
+        # List devices, optionally as JSON for scripting
+        if [ "$2" = "--json" ]; then
+            first=1
+            printf '['
+            while IFS='|' read -r ip version; do
+                [ -z "$first" ] && printf ','
+                printf '{"ip":"%s","version":"%s"}' "$ip" "$version"
+                first=""
+            done < <(discover_devices "$3")
+            printf ']\n'
+        else
+            count=0
+            while IFS='|' read -r ip version; do
+                print_color "$GREEN" "  ✓ $ip (v${version:-unknown})"
+                ((count++))
+            done < <(discover_devices "$2")
+            print_color "$BLUE" "\n📱 Found $count ESP32 device(s)"
+        fi
+        ;;
+
+    release)
+        # Flash a published GitHub release: release --tag <tag> [IP ...]
+        if [ "$2" != "--tag" ] || [ -z "$3" ]; then
+            print_color "$RED" "Usage: $0 release --tag <tag> [IP ...]"
+            exit 1
+        fi
+        tag=$3
+        if [[ ! $tag =~ ^[A-Za-z0-9._-]+$ ]]; then
+            print_color "$RED" "Invalid tag: $tag"
+            exit 1
+        fi
+        shift 3
+
+        firmware=$(download_release "$tag") || exit 1
+        trap 'rm -rf "$(dirname "$firmware")"' EXIT
+
+        devices=("$@")
+        if [ ${#devices[@]} -eq 0 ]; then
+            print_color "$BLUE" "🔍 No devices given, discovering..."
+            while IFS='|' read -r ip version; do
+                devices+=("$ip")
+                print_color "$GREEN" "  ✓ Found ESP32: $ip (v${version:-unknown})"
+            done < <(discover_devices)
+            if [ ${#devices[@]} -eq 0 ]; then
+                print_color "$RED" "\n❌ No devices found"
+                exit 1
+            fi
+            print_color "$YELLOW" "\nFlash ${tag} to all ${#devices[@]} device(s)? (y/N): "
+            read -r response
+            if [[ ! "$response" =~ ^[Yy]$ ]]; then
+                echo "Update cancelled"
+                exit 0
+            fi
+        fi
+
+        success=0
+        for device in "${devices[@]}"; do
+            if upload_firmware "$device" "$firmware"; then
+                ((success++))
+            fi
+        done
+        print_color "$GREEN" "\n✨ Release ${tag}: $success/${#devices[@]} successful"
+        [ "$success" -eq ${#devices[@]} ] || exit 1
+        ;;
+
     help|--help|-h)
-        echo "Usage: $0 [COMMAND] [OPTIONS]"
+        echo "Usage: $0 [--compress] [--delta <base.bin>] [--now] [--force] [COMMAND] [OPTIONS]"
+        echo ""
+        echo "Upload options:"
+        echo "  --compress          Gzip the upload (device decompresses while flashing)"
+        echo "  --delta <base.bin>  Send a bsdiff patch against the image the device runs"
+        echo "                      (implies --compress; needs bsdiff or pip bsdiff4)"
+        echo "  --now               Apply immediately instead of staging for the maintenance window"
+        echo "  --force             Install even if the image is older than the device's firmware"
         echo ""
         echo "Commands:"
-        echo "  <IP>          Update specific device"
+        echo "  <IP>          Update specific device (IPv4 or IPv6)"
         echo "  find          Quick find first ESP32 device"
         echo "  scan [subnet] Scan network for all devices"  
         echo "  auto [subnet] Auto-discover and update all devices"
+        echo "  devices [--json] [subnet]  List devices (JSON: [{\"ip\",\"version\"}])"
+        echo "  release --tag <tag> [IP ...]  Flash a GitHub release (all devices if no IP)"
         echo "  help          Show this help"
         echo ""
         echo "Examples:"
         echo "  $0 find                   # Find first device"
         echo "  $0 192.168.1.100         # Update specific device by IP"
         echo "  $0 esp32.local           # Update using mDNS hostname"
+        echo "  $0 2001:db8::42          # Update by IPv6 address (fe80::1%en0 for link-local)"
         echo "  $0 scan                  # List all devices"
         echo "  $0 auto                  # Update all devices"
+        echo "  $0 devices --json        # Machine-readable device list"
+        echo "  $0 release --tag v5.2.0 192.168.1.100"
+        echo "  $0 --compress 192.168.1.100"
+        echo "  $0 --delta v5.1.0.bin 192.168.1.100"
         echo ""
         echo "Environment variables:"
         echo "  FIRMWARE  Path to firmware file (default: target/xtensa-esp32s3-espidf/release/esp32-s3-dashboard)"
         echo "  PORT      Device HTTP port (default: 80)"
+        echo "  GITHUB_REPO   Release source (default: jtn0123/ESP32-S3-Display-Dashboard)"
+        echo "  GITHUB_TOKEN  Optional token for the GitHub API (avoids rate limits)"
+        echo "  OTA_RESUME_RETRIES  Resumes after a dropped uncompressed upload (default: 3)"
         ;;
     
     *)
@@ -447,7 +780,7 @@ case "${1:-help}" in
             
             # Method 1: dscacheutil (macOS)
             if command -v dscacheutil >/dev/null 2>&1; then
-                IP=$(dscacheutil -q host -a name "$TARGET" 2>/dev/null | grep "ip_address" | awk '{print $2}' | head -1)
+                IP=$(dscacheutil -q host -a name "$TARGET" 2>/dev/null | awk '/^ip_address/ && !v4 {v4=$2} /^ipv6_address/ && !v6 {v6=$2} END {print (v4 ? v4 : v6)}')
             fi
             
             # Method 2: getent (Linux)
@@ -470,8 +803,9 @@ case "${1:-help}" in
             fi
         fi
         
-        # Now upload to the target (IP address)
-        if [[ $TARGET =~ ^[0-9]+\.[0-9]+\.[0-9]+\.[0-9]+$ ]]; then
+        # Now upload to the target (IPv4 or IPv6 address, brackets optional)
+        if [[ $TARGET =~ ^[0-9]+\.[0-9]+\.[0-9]+\.[0-9]+$ ]] \
+            || [[ $TARGET == *:* && $TARGET =~ ^\[?[0-9A-Fa-f:.]+(%[A-Za-z0-9_.-]+)?\]?$ ]]; then
             if upload_firmware "$TARGET" "$FIRMWARE"; then
                 print_color "$GREEN" "\n✨ OTA update completed successfully!"
             else
@@ -39,10 +39,41 @@ impl BootStage {
     }
 }
 
+/// User logo uploaded through the settings page
+struct BootLogo {
+    width: u16,
+    height: u16,
+    /// Big-endian RGB565 rows
+    pixels: Vec<u8>,
+}
+
+impl BootLogo {
+    /// The uploaded logo, if there is one and it is still valid
+    fn load() -> Option<Self> {
+        use crate::network::file_manager::{validate_logo, BOOT_LOGO_PATH, LOGO_HEADER_LEN};
+        if let Err(e) = crate::storage::ensure_spiffs_mounted() {
+            log::warn!("Boot logo unavailable, SPIFFS not mounted: {:?}", e);
+            return None;
+        }
+        let mut bytes = std::fs::read(BOOT_LOGO_PATH).ok()?;
+        match validate_logo(&bytes) {
+            Ok((width, height)) => {
+                log::info!("Boot: showing uploaded {}x{} logo", width, height);
+                Some(Self { width, height, pixels: bytes.split_off(LOGO_HEADER_LEN) })
+            }
+            Err(e) => {
+                log::warn!("Ignoring boot logo: {}", e);
+                None
+            }
+        }
+    }
+}
+
 pub struct BootManager {
     current_stage: Arc<Mutex<BootStage>>,
     animation_frame: u32,
     circuit_points: Vec<(u16, u16)>,
+    logo: Option<BootLogo>,
 }
 
 impl BootManager {
@@ -115,6 +146,7 @@ impl BootManager {
             current_stage: Arc::new(Mutex::new(BootStage::PowerOn)),
             animation_frame: 0,
             circuit_points,
+            logo: BootLogo::load(),
         }
     }
     
@@ -143,15 +175,24 @@ impl BootManager {
             display.clear(BLACK)?;
         }
         
-        // Draw animated background circuit pattern
-        self.draw_circuit_pattern(display)?;
-        
         // Main content area with subtle gradient effect
         let content_y = 50;
         
-        // Title with cleaner design
-        display.draw_text_centered(content_y, "ESP32-S3", PRIMARY_BLUE, None, 2)?;
-        display.draw_text_centered(content_y + 25, "DASHBOARD", TEXT_PRIMARY, None, 1)?;
+        if let Some(logo) = &self.logo {
+            // The logo replaces the sparkles and title; it is static, so draw it once
+            if self.animation_frame == 0 {
+                let x = (320 - logo.width) / 2;
+                let y = 2 + (content_y + 40 - logo.height) / 2;
+                display.draw_rgb565(x, y, logo.width, logo.height, &logo.pixels)?;
+            }
+        } else {
+            // Draw animated background circuit pattern
+            self.draw_circuit_pattern(display)?;
+            
+            // Title with cleaner design
+            display.draw_text_centered(content_y, "ESP32-S3", PRIMARY_BLUE, None, 2)?;
+            display.draw_text_centered(content_y + 25, "DASHBOARD", TEXT_PRIMARY, None, 1)?;
+        }
         
         // Subtle separator line
         let line_y = content_y + 45;
@@ -0,0 +1,62 @@
+// Config errors. Validation messages are written for the 400 response the web
+// UI shows next to the form, so they carry no prefix of their own.
+
+use thiserror::Error;
+use crate::error::HttpStatus;
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Invalid JSON")]
+    InvalidJson,
+    /// A value failed validation
+    #[error("{0}")]
+    Invalid(String),
+    /// One or more fields of a config update failed validation
+    #[error("{}", join_messages(.0))]
+    Fields(Vec<FieldError>),
+    #[error("Config not found in NVS")]
+    NotFound,
+    /// NVS read or write failed
+    #[error("config storage failed: {0}")]
+    Storage(String),
+}
+
+/// A rejected field of a POST /api/config body, for the web UI to show next
+/// to that input
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FieldError {
+    /// Key in the request body, e.g. `dim_timeout`
+    pub field: String,
+    pub message: String,
+}
+
+impl ConfigError {
+    /// The per-field failures, empty for other errors
+    pub fn field_errors(&self) -> &[FieldError] {
+        match self {
+            Self::Fields(errors) => errors,
+            _ => &[],
+        }
+    }
+}
+
+fn join_messages(errors: &[FieldError]) -> String {
+    errors.iter().map(|e| e.message.as_str()).collect::<Vec<_>>().join("; ")
+}
+
+impl HttpStatus for ConfigError {
+    fn http_status(&self) -> u16 {
+        match self {
+            Self::InvalidJson | Self::Invalid(_) | Self::Fields(_) => 400,
+            Self::NotFound => 404,
+            Self::Storage(_) => 500,
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl From<anyhow::Error> for ConfigError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Storage(format!("{:#}", e))
+    }
+}
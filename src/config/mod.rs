@@ -0,0 +1,555 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+pub mod error;
+pub mod profiles;
+pub mod schedule;
+// NVS persistence and remote sync are device-only; the config types themselves
+// are also compiled into host-tests
+#[cfg(target_os = "espidf")]
+pub mod remote_sync;
+
+pub use error::{ConfigError, FieldError};
+use profiles::ConfigProfile;
+use schedule::SampleSchedule;
+use crate::hardware::buzzer::SoundMap;
+use crate::input::action_map::ButtonMap;
+use crate::ui::animation::EasingFunction;
+use crate::display::pacing::PacingStrategy;
+use crate::i18n::Language;
+use crate::units::{ClockFormat, TemperatureUnit};
+use crate::network::offline_queue::DropPolicy;
+
+#[cfg(target_os = "espidf")]
+const CONFIG_NAMESPACE: &str = "dashboard";
+#[cfg(target_os = "espidf")]
+const CONFIG_KEY: &str = "config";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    // WiFi settings
+    pub wifi_ssid: String,
+    pub wifi_password: String,
+    /// Only associate with this access point (`aa:bb:cc:dd:ee:ff`); empty allows any
+    #[serde(default)]
+    pub wifi_bssid: String,
+    /// Also run a fallback access point (APSTA) serving the web UI on
+    /// 192.168.4.1; AP settings apply on the next boot
+    #[serde(default)]
+    pub ap_enabled: bool,
+    /// Empty uses `<hostname>-setup`
+    #[serde(default)]
+    pub ap_ssid: String,
+    /// WPA2 passphrase (8-63 characters); empty runs an open AP
+    #[serde(default)]
+    pub ap_password: String,
+    /// Minutes of stable station link before the AP switches off; 0 keeps it up
+    #[serde(default = "default_ap_auto_off_mins")]
+    pub ap_auto_off_mins: u32,
+    /// Static IPv4 address for the station; empty uses DHCP. Applied on the
+    /// next boot, falling back to DHCP if the settings don't validate.
+    #[serde(default)]
+    pub static_ip: String,
+    #[serde(default = "default_static_netmask")]
+    pub static_netmask: String,
+    #[serde(default)]
+    pub static_gateway: String,
+    /// Up to two DNS servers; empty uses the gateway
+    #[serde(default)]
+    pub static_dns: Vec<String>,
+    
+    // Display settings
+    pub brightness: u8,
+    pub auto_brightness: bool,
+    
+    // Power management
+    pub dim_timeout_secs: u32,
+    pub sleep_timeout_secs: u32,
+    
+    // UI preferences
+    pub theme: Theme,
+    pub show_animations: bool,
+    /// Screen names in display order (`system`, `network`, `sensor`, ...);
+    /// empty shows every compiled-in screen
+    #[serde(default)]
+    pub screen_order: Vec<String>,
+    /// Language of on-device and web UI strings
+    #[serde(default)]
+    pub language: Language,
+    /// Display units; exports and the API stay in °C
+    #[serde(default)]
+    pub temperature_unit: TemperatureUnit,
+    #[serde(default)]
+    pub clock_format: ClockFormat,
+    /// Large text, simplified layouts and a high-contrast palette on every screen
+    #[serde(default)]
+    pub accessibility_mode: bool,
+    /// Kiosk mode: only `kiosk_metric` in giant digits (wall gauge)
+    #[serde(default)]
+    pub kiosk_enabled: bool,
+    /// `temperature`, `battery`, a sensor key like `scd40.co2` or `widget:<label>`
+    #[serde(default = "default_kiosk_metric")]
+    pub kiosk_metric: String,
+    
+    // OTA settings
+    pub ota_enabled: bool,
+    pub ota_check_interval_hours: u32,
+
+    // Metrics push exporter (serde defaults keep older NVS blobs loadable)
+    #[serde(default)]
+    pub push_enabled: bool,
+    #[serde(default)]
+    pub push_url: String,
+    #[serde(default)]
+    pub push_format: PushFormat,
+    #[serde(default = "default_push_interval_secs")]
+    pub push_interval_secs: u32,
+    #[serde(default = "default_push_batch_size")]
+    pub push_batch_size: u32,
+    /// Samples kept while offline before the drop policy applies
+    #[serde(default = "default_push_queue_size")]
+    pub push_queue_size: u32,
+    #[serde(default)]
+    pub push_drop_policy: DropPolicy,
+
+    // Prometheus naming: series are `<prefix>_<name>` and carry these labels
+    #[serde(default = "default_metrics_prefix")]
+    pub metrics_prefix: String,
+    #[serde(default)]
+    pub metrics_labels: BTreeMap<String, String>,
+
+    // Weather screen (Open-Meteo)
+    #[serde(default)]
+    pub weather_enabled: bool,
+    #[serde(default)]
+    pub weather_latitude: f32,
+    #[serde(default)]
+    pub weather_longitude: f32,
+
+    // Custom REST widgets shown on the Custom screen
+    #[serde(default)]
+    pub custom_widgets: Vec<CustomWidget>,
+
+    // Alertmanager integration (Alerts screen)
+    #[serde(default)]
+    pub alerts_enabled: bool,
+    /// Base URL, e.g. http://alertmanager:9093
+    #[serde(default)]
+    pub alerts_url: String,
+    /// Label matchers passed as `filter=`, e.g. `severity="critical"`
+    #[serde(default)]
+    pub alerts_filters: Vec<String>,
+
+    // Calendar (Agenda screen)
+    /// ICS feed URL; empty disables
+    #[serde(default)]
+    pub agenda_url: String,
+    /// Upcoming events shown
+    #[serde(default = "default_agenda_events")]
+    pub agenda_events: u8,
+
+    // Pomodoro timer
+    #[serde(default = "default_pomodoro_minutes")]
+    pub pomodoro_minutes: u32,
+    /// Optional GPIO pulsed (LED/buzzer) when a session completes
+    #[serde(default)]
+    pub timer_alert_gpio: Option<u8>,
+
+    // Remote config sync (fleet management)
+    #[serde(default)]
+    pub remote_sync_enabled: bool,
+    #[serde(default)]
+    pub remote_sync_url: String,
+    /// Shared HMAC-SHA256 key used to verify config documents
+    #[serde(default)]
+    pub remote_sync_key: String,
+    #[serde(default = "default_remote_sync_interval_secs")]
+    pub remote_sync_interval_secs: u32,
+    /// Allow the server to change WiFi credentials
+    #[serde(default)]
+    pub remote_sync_allow_wifi: bool,
+    /// Version of the last applied remote document
+    #[serde(default)]
+    pub remote_config_version: u64,
+
+    // Day/night profiles (switched by local time once SNTP has synced)
+    #[serde(default)]
+    pub profiles_enabled: bool,
+    #[serde(default = "ConfigProfile::default_day")]
+    pub day_profile: ConfigProfile,
+    #[serde(default = "ConfigProfile::default_night")]
+    pub night_profile: ConfigProfile,
+    /// Minutes after local midnight when the day profile starts
+    #[serde(default = "default_day_start_minutes")]
+    pub day_start_minutes: u16,
+    #[serde(default = "default_night_start_minutes")]
+    pub night_start_minutes: u16,
+    /// POSIX TZ string, e.g. `CET-1CEST,M3.5.0,M10.5.0/3`
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// DHCP and mDNS name (`<hostname>.local`); applied on the next boot
+    #[serde(default = "default_hostname")]
+    pub hostname: String,
+    /// Reference host pinged next to the gateway (name or IPv4); empty disables it
+    #[serde(default = "default_latency_host")]
+    pub latency_host: String,
+    /// Download used by the on-demand throughput test (/api/nettest)
+    #[serde(default = "default_nettest_url")]
+    pub nettest_url: String,
+
+    // Polling interval and on/off per data source (sensors, network, ...)
+    #[serde(default)]
+    pub sample_schedule: SampleSchedule,
+
+    // Frame pacing: main-loop target rate (0 = uncapped) and how it waits
+    #[serde(default = "default_target_fps")]
+    pub target_fps: u8,
+    #[serde(default)]
+    pub frame_pacing: PacingStrategy,
+
+    // Thermal throttling: die temperature (°C) that triggers it
+    #[serde(default = "default_thermal_limit_c")]
+    pub thermal_limit_c: f32,
+
+    // Quiet hours: display off and notifications silenced between these local times
+    #[serde(default)]
+    pub quiet_hours_enabled: bool,
+    #[serde(default = "default_quiet_start_minutes")]
+    pub quiet_start_minutes: u16,
+    #[serde(default = "default_quiet_end_minutes")]
+    pub quiet_end_minutes: u16,
+
+    // OTA maintenance window: pushed updates are staged and applied between these local times
+    #[serde(default)]
+    pub ota_window_enabled: bool,
+    #[serde(default = "default_ota_window_start_minutes")]
+    pub ota_window_start_minutes: u16,
+    #[serde(default = "default_ota_window_end_minutes")]
+    pub ota_window_end_minutes: u16,
+
+    // Burn-in mitigation: ±2 px UI shift every few minutes, and a bouncing
+    // clock instead of the backlight going off at the dim timeout
+    #[serde(default)]
+    pub burn_in_shift: bool,
+    #[serde(default)]
+    pub burn_in_screensaver: bool,
+
+    // Battery profile: WiFi woken every N minutes, 1 FPS display, deep sleep when idle
+    #[serde(default)]
+    pub battery_profile: bool,
+    #[serde(default = "default_battery_wifi_interval_mins")]
+    pub battery_wifi_interval_mins: u32,
+    /// Seconds the radio stays up per wake
+    #[serde(default = "default_battery_wifi_window_secs")]
+    pub battery_wifi_window_secs: u32,
+    /// Seconds without input before deep sleep (while the radio is off)
+    #[serde(default = "default_battery_sleep_after_secs")]
+    pub battery_sleep_after_secs: u32,
+
+    // Piezo buzzer (None = not fitted) and which events sound
+    #[serde(default)]
+    pub buzzer_gpio: Option<u8>,
+    #[serde(default)]
+    pub buzzer_sounds: SoundMap,
+    #[serde(default)]
+    pub buzzer_muted: bool,
+
+    // WS2812 status LEDs (None = not fitted)
+    #[serde(default)]
+    pub led_gpio: Option<u8>,
+    #[serde(default = "default_led_count")]
+    pub led_count: u8,
+    #[serde(default = "default_led_enabled")]
+    pub led_enabled: bool,
+    #[serde(default = "default_led_brightness")]
+    pub led_brightness: u8,
+
+    // INA219 power monitor (active when both SDA and SCL are set)
+    #[serde(default)]
+    pub power_monitor_sda: Option<u8>,
+    #[serde(default)]
+    pub power_monitor_scl: Option<u8>,
+    #[serde(default = "default_power_monitor_address")]
+    pub power_monitor_address: u8,
+    #[serde(default = "default_power_monitor_shunt_mohm")]
+    pub power_monitor_shunt_mohm: u16,
+
+    // SCD40 / SGP30 air quality sensors on I2C port 1 (active when both SDA
+    // and SCL are set)
+    #[serde(default)]
+    pub air_quality_sda: Option<u8>,
+    #[serde(default)]
+    pub air_quality_scl: Option<u8>,
+
+    // Rotary encoder (active when both A and B are set)
+    #[serde(default)]
+    pub encoder_a_gpio: Option<u8>,
+    #[serde(default)]
+    pub encoder_b_gpio: Option<u8>,
+    #[serde(default)]
+    pub encoder_button_gpio: Option<u8>,
+
+    // What each button event does, and the broker MQTT actions publish to
+    #[serde(default)]
+    pub button_actions: ButtonMap,
+    /// e.g. "mqtt://192.168.1.10:1883"; empty disables MQTT actions
+    #[serde(default)]
+    pub mqtt_broker: String,
+
+    // Backlight fades for dim, sleep, wake and boot: time for a full 0-255
+    // change (0 switches instantly) and its curve
+    #[serde(default = "default_backlight_fade_ms")]
+    pub backlight_fade_ms: u32,
+    #[serde(default = "default_backlight_easing")]
+    pub backlight_easing: EasingFunction,
+    /// Scale `brightness` by a curve learned from the ambient light history
+    /// (see power::adaptive_brightness); needs a sensor reporting lux
+    #[serde(default)]
+    pub adaptive_brightness: bool,
+
+    // Remote syslog (RFC 5424 over UDP); every line the log level lets
+    // through is also sent here
+    #[serde(default)]
+    pub syslog_enabled: bool,
+    /// "host" or "host:port" (default port 514)
+    #[serde(default)]
+    pub syslog_server: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Theme {
+    Dark,
+    Light,
+    Auto,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushFormat {
+    #[default]
+    InfluxLine,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomWidget {
+    pub label: String,
+    pub url: String,
+    /// JSONPath-style extractor, e.g. `$.data.temperature`
+    pub path: String,
+    #[serde(default)]
+    pub unit: String,
+    #[serde(default = "default_widget_interval_secs")]
+    pub interval_secs: u32,
+}
+
+fn default_kiosk_metric() -> String { "temperature".to_string() }
+fn default_agenda_events() -> u8 { 5 }
+fn default_pomodoro_minutes() -> u32 { 25 }
+fn default_remote_sync_interval_secs() -> u32 { 900 }
+fn default_day_start_minutes() -> u16 { 7 * 60 }
+fn default_night_start_minutes() -> u16 { 22 * 60 }
+fn default_timezone() -> String { "UTC0".to_string() }
+fn default_hostname() -> String { "esp32".to_string() }
+fn default_latency_host() -> String { "1.1.1.1".to_string() }
+fn default_nettest_url() -> String { "https://speed.cloudflare.com/__down?bytes=25000000".to_string() }
+fn default_led_count() -> u8 { 8 }
+fn default_led_enabled() -> bool { true }
+fn default_led_brightness() -> u8 { 64 }
+fn default_power_monitor_address() -> u8 { crate::hardware::power_monitor::DEFAULT_ADDRESS }
+fn default_power_monitor_shunt_mohm() -> u16 { crate::hardware::power_monitor::DEFAULT_SHUNT_MILLIOHM }
+fn default_backlight_fade_ms() -> u32 { crate::power::backlight::DEFAULT_FADE_MS }
+fn default_backlight_easing() -> EasingFunction { EasingFunction::EaseInOut }
+fn default_thermal_limit_c() -> f32 { crate::power::thermal::DEFAULT_LIMIT_C }
+fn default_target_fps() -> u8 { crate::display::pacing::DEFAULT_TARGET_FPS }
+fn default_widget_interval_secs() -> u32 { 60 }
+fn default_quiet_start_minutes() -> u16 { 23 * 60 }
+fn default_quiet_end_minutes() -> u16 { 7 * 60 }
+fn default_ota_window_start_minutes() -> u16 { 2 * 60 }
+fn default_ota_window_end_minutes() -> u16 { 4 * 60 }
+fn default_battery_wifi_interval_mins() -> u32 { 15 }
+fn default_battery_wifi_window_secs() -> u32 { 60 }
+fn default_battery_sleep_after_secs() -> u32 { 120 }
+fn default_push_interval_secs() -> u32 { 30 }
+fn default_push_batch_size() -> u32 { 4 }
+/// 12 hours at the default 30 s interval
+fn default_push_queue_size() -> u32 { 1440 }
+fn default_metrics_prefix() -> String { "esp32".to_string() }
+fn default_ap_auto_off_mins() -> u32 { 10 }
+fn default_static_netmask() -> String { "255.255.255.0".to_string() }
+
+impl Default for Config {
+    fn default() -> Self {
+        // Get WiFi credentials from environment variables set by build.rs
+        // These come from wifi_config.h which should NOT be committed to git
+        let wifi_ssid = env!("WIFI_SSID");
+        let wifi_password = env!("WIFI_PASSWORD");
+        
+        log::info!("Config default: SSID='{}', Password={}", 
+            wifi_ssid, 
+            if wifi_password.is_empty() { "<empty>" } else { "<set>" }
+        );
+        
+        Self {
+            wifi_ssid: wifi_ssid.to_string(),
+            wifi_password: wifi_password.to_string(),
+            wifi_bssid: String::new(),
+            ap_enabled: false,
+            ap_ssid: String::new(),
+            ap_password: String::new(),
+            ap_auto_off_mins: default_ap_auto_off_mins(),
+            static_ip: String::new(),
+            static_netmask: default_static_netmask(),
+            static_gateway: String::new(),
+            static_dns: Vec::new(),
+            brightness: 80,
+            auto_brightness: true,
+            dim_timeout_secs: 30,
+            sleep_timeout_secs: 300,
+            theme: Theme::Dark,
+            show_animations: true,
+            screen_order: Vec::new(),
+            language: Language::En,
+            temperature_unit: TemperatureUnit::Celsius,
+            clock_format: ClockFormat::H24,
+            accessibility_mode: false,
+            kiosk_enabled: false,
+            kiosk_metric: default_kiosk_metric(),
+            ota_enabled: true,
+            ota_check_interval_hours: 24,
+            push_enabled: false,
+            push_url: String::new(),
+            push_format: PushFormat::InfluxLine,
+            push_interval_secs: default_push_interval_secs(),
+            push_batch_size: default_push_batch_size(),
+            push_queue_size: default_push_queue_size(),
+            push_drop_policy: DropPolicy::Oldest,
+            metrics_prefix: default_metrics_prefix(),
+            metrics_labels: BTreeMap::new(),
+            weather_enabled: false,
+            weather_latitude: 0.0,
+            weather_longitude: 0.0,
+            custom_widgets: Vec::new(),
+            alerts_enabled: false,
+            alerts_url: String::new(),
+            alerts_filters: Vec::new(),
+            agenda_url: String::new(),
+            agenda_events: default_agenda_events(),
+            pomodoro_minutes: default_pomodoro_minutes(),
+            timer_alert_gpio: None,
+            remote_sync_enabled: false,
+            remote_sync_url: String::new(),
+            remote_sync_key: String::new(),
+            remote_sync_interval_secs: default_remote_sync_interval_secs(),
+            remote_sync_allow_wifi: false,
+            remote_config_version: 0,
+            profiles_enabled: false,
+            day_profile: ConfigProfile::default_day(),
+            night_profile: ConfigProfile::default_night(),
+            day_start_minutes: default_day_start_minutes(),
+            night_start_minutes: default_night_start_minutes(),
+            timezone: default_timezone(),
+            hostname: default_hostname(),
+            latency_host: default_latency_host(),
+            nettest_url: default_nettest_url(),
+            sample_schedule: SampleSchedule::default(),
+            target_fps: default_target_fps(),
+            frame_pacing: PacingStrategy::default(),
+            thermal_limit_c: default_thermal_limit_c(),
+            quiet_hours_enabled: false,
+            quiet_start_minutes: default_quiet_start_minutes(),
+            quiet_end_minutes: default_quiet_end_minutes(),
+            ota_window_enabled: false,
+            ota_window_start_minutes: default_ota_window_start_minutes(),
+            ota_window_end_minutes: default_ota_window_end_minutes(),
+            burn_in_shift: false,
+            burn_in_screensaver: false,
+            battery_profile: false,
+            battery_wifi_interval_mins: default_battery_wifi_interval_mins(),
+            battery_wifi_window_secs: default_battery_wifi_window_secs(),
+            battery_sleep_after_secs: default_battery_sleep_after_secs(),
+            buzzer_gpio: None,
+            buzzer_sounds: SoundMap::default(),
+            buzzer_muted: false,
+            led_gpio: None,
+            led_count: default_led_count(),
+            led_enabled: default_led_enabled(),
+            led_brightness: default_led_brightness(),
+            power_monitor_sda: None,
+            power_monitor_scl: None,
+            power_monitor_address: default_power_monitor_address(),
+            power_monitor_shunt_mohm: default_power_monitor_shunt_mohm(),
+            air_quality_sda: None,
+            air_quality_scl: None,
+            encoder_a_gpio: None,
+            encoder_b_gpio: None,
+            encoder_button_gpio: None,
+            button_actions: ButtonMap::default(),
+            mqtt_broker: String::new(),
+            backlight_fade_ms: default_backlight_fade_ms(),
+            backlight_easing: default_backlight_easing(),
+            adaptive_brightness: false,
+            syslog_enabled: false,
+            syslog_server: String::new(),
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl Config {
+    pub fn save(&self) -> Result<(), ConfigError> {
+        save_to_nvs(self)?;
+        log::info!("Configuration saved to NVS");
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub fn load_or_default() -> Result<Config, ConfigError> {
+    match load_from_nvs() {
+        Ok(mut config) => {
+            log::info!("Loaded configuration from NVS");
+            
+            // If NVS has empty WiFi credentials, use the compiled-in ones
+            if config.wifi_ssid.is_empty() || config.wifi_password.is_empty() {
+                let default_config = Config::default();
+                log::warn!("NVS WiFi credentials empty, using compiled defaults: SSID='{}'", default_config.wifi_ssid);
+                config.wifi_ssid = default_config.wifi_ssid;
+                config.wifi_password = default_config.wifi_password;
+                
+                // Save the updated config back to NVS
+                if let Err(e) = config.save() {
+                    log::warn!("Failed to save updated config with WiFi credentials: {:?}", e);
+                }
+            }
+            
+            Ok(config)
+        }
+        Err(e) => {
+            log::warn!("Failed to load config from NVS: {:?}, using defaults", e);
+            let config = Config::default();
+            
+            // Try to save default config to NVS for next time
+            if let Err(save_err) = config.save() {
+                log::warn!("Failed to save default config to NVS: {:?}", save_err);
+            }
+            
+            Ok(config)
+        }
+    }
+}
+
+// Remove duplicate save function - already exists as method on Config
+
+#[cfg(target_os = "espidf")]
+fn load_from_nvs() -> Result<Config, ConfigError> {
+    crate::storage::kv::open(CONFIG_NAMESPACE)?
+        .get(CONFIG_KEY)
+        .ok_or(ConfigError::NotFound)
+}
+
+#[cfg(target_os = "espidf")]
+fn save_to_nvs(config: &Config) -> Result<(), ConfigError> {
+    Ok(crate::storage::kv::open(CONFIG_NAMESPACE)?.set(CONFIG_KEY, config)?)
+}
+
+// CONFIG_HTML moved to web_server module where it's actually used
\ No newline at end of file
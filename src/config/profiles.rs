@@ -0,0 +1,165 @@
+// Day/night config profiles: two named sets of display and update settings,
+// switched automatically at configured local times once SNTP has synced.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+use serde::{Deserialize, Serialize};
+use super::{Config, Theme};
+#[cfg(target_os = "espidf")]
+use {
+    anyhow::Result,
+    core::sync::atomic::AtomicBool,
+    std::sync::{Arc, Mutex},
+    esp_idf_hal::delay::FreeRtos,
+    crate::system::{clock, ShutdownSignal},
+};
+
+#[cfg(target_os = "espidf")]
+const POLL_INTERVAL_SECS: u32 = 30;
+
+// 0 = no profile active, otherwise ProfileKind as u8 + 1
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileKind {
+    Day,
+    Night,
+}
+
+impl ProfileKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Night => "night",
+        }
+    }
+}
+
+/// Settings that change between day and night
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigProfile {
+    pub brightness: u8,
+    pub theme: Theme,
+    pub dim_timeout_secs: u32,
+    pub push_interval_secs: u32,
+}
+
+impl ConfigProfile {
+    pub fn default_day() -> Self {
+        Self { brightness: 80, theme: Theme::Dark, dim_timeout_secs: 30, push_interval_secs: 30 }
+    }
+
+    pub fn default_night() -> Self {
+        Self { brightness: 20, theme: Theme::Dark, dim_timeout_secs: 15, push_interval_secs: 120 }
+    }
+}
+
+/// Profile that should be active at `minute` (minutes since local midnight).
+/// Handles night windows that wrap past midnight.
+pub fn scheduled_profile(minute: u16, day_start: u16, night_start: u16) -> ProfileKind {
+    let in_day = if day_start <= night_start {
+        minute >= day_start && minute < night_start
+    } else {
+        minute >= day_start || minute < night_start
+    };
+    if in_day { ProfileKind::Day } else { ProfileKind::Night }
+}
+
+/// Copy a profile's settings into the live config, clamped like the web API
+pub fn apply(cfg: &mut Config, profile: &ConfigProfile) {
+    cfg.brightness = profile.brightness;
+    cfg.theme = profile.theme;
+    cfg.dim_timeout_secs = profile.dim_timeout_secs.clamp(5, 3600);
+    cfg.push_interval_secs = profile.push_interval_secs.clamp(5, 3600);
+}
+
+/// Currently applied profile, or None if scheduling is off or time is unknown
+pub fn current() -> Option<ProfileKind> {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Some(ProfileKind::Day),
+        2 => Some(ProfileKind::Night),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "espidf")]
+fn set_current(kind: Option<ProfileKind>) {
+    CURRENT.store(kind.map_or(0, |k| k as u8 + 1), Ordering::Relaxed);
+}
+
+/// Start the profile scheduler. Profiles are applied to the in-memory config only;
+/// settings saved from the web UI keep the profile values that were live at the time.
+/// Safe to call multiple times.
+#[cfg(target_os = "espidf")]
+pub fn start(config: Arc<Mutex<Config>>, shutdown: ShutdownSignal) -> Result<()> {
+    static STARTED: AtomicBool = AtomicBool::new(false);
+    if STARTED.swap(true, Ordering::SeqCst) { return Ok(()); }
+
+    std::thread::Builder::new()
+        .name("profiles".to_string())
+        .stack_size(6 * 1024)
+        .spawn(move || {
+            let mut timezone = String::new();
+            let mut last_applied: Option<(ProfileKind, ConfigProfile)> = None;
+            loop {
+                if let Ok(mut cfg) = config.lock() {
+                    if cfg.timezone != timezone {
+                        timezone = cfg.timezone.clone();
+                        clock::set_timezone(&timezone);
+                    }
+
+                    let scheduled = match clock::local_minutes_of_day() {
+                        Some(minute) if cfg.profiles_enabled => {
+                            Some(scheduled_profile(minute, cfg.day_start_minutes, cfg.night_start_minutes))
+                        }
+                        _ => None,
+                    };
+
+                    match scheduled {
+                        Some(kind) => {
+                            let profile = match kind {
+                                ProfileKind::Day => cfg.day_profile.clone(),
+                                ProfileKind::Night => cfg.night_profile.clone(),
+                            };
+                            // Only re-apply on a switch or an edit, so manual tweaks stick until then
+                            if last_applied.as_ref() != Some(&(kind, profile.clone())) {
+                                apply(&mut cfg, &profile);
+                                log::info!("Switched to {} profile", kind.name());
+                                last_applied = Some((kind, profile));
+                            }
+                        }
+                        None => last_applied = None,
+                    }
+                    set_current(scheduled);
+                }
+
+                for _ in 0..POLL_INTERVAL_SECS {
+                    if shutdown.is_shutdown_requested() {
+                        return;
+                    }
+                    FreeRtos::delay_ms(1000);
+                }
+            }
+        })?;
+
+    log::info!("Profile scheduler started");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheduled_profile() {
+        // Day 07:00-22:00
+        assert_eq!(scheduled_profile(420, 420, 1320), ProfileKind::Day);
+        assert_eq!(scheduled_profile(1319, 420, 1320), ProfileKind::Day);
+        assert_eq!(scheduled_profile(1320, 420, 1320), ProfileKind::Night);
+        assert_eq!(scheduled_profile(60, 420, 1320), ProfileKind::Night);
+        // Night shift: day 20:00-06:00 wraps past midnight
+        assert_eq!(scheduled_profile(1380, 1200, 360), ProfileKind::Day);
+        assert_eq!(scheduled_profile(120, 1200, 360), ProfileKind::Day);
+        assert_eq!(scheduled_profile(720, 1200, 360), ProfileKind::Night);
+    }
+}
@@ -0,0 +1,213 @@
+// Remote configuration sync for fleets: periodically fetch a config document from
+// a central server, verify its HMAC-SHA256 signature (hex, in the
+// `X-Config-Signature` response header) and merge the fields we allow remotely.
+
+use anyhow::{bail, Result};
+use core::sync::atomic::{AtomicBool, Ordering};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use esp_idf_hal::delay::FreeRtos;
+use super::{Config, Theme};
+use crate::system::ShutdownSignal;
+use crate::network::http_client::{self, RequestOptions};
+
+const SIGNATURE_HEADER: &str = "X-Config-Signature";
+const MIN_INTERVAL_SECS: u32 = 60;
+const IDLE_POLL_SECS: u32 = 30;
+const MAX_DOCUMENT_BYTES: usize = 4096;
+const HTTP_TIMEOUT_MS: u64 = 10_000;
+
+static LAST_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+/// Fields a central server may set. Everything else stays device-local.
+#[derive(Debug, Deserialize)]
+pub struct RemoteConfig {
+    /// Monotonic document version; older or equal versions are ignored
+    pub version: u64,
+    pub brightness: Option<u8>,
+    pub auto_brightness: Option<bool>,
+    pub theme: Option<Theme>,
+    pub show_animations: Option<bool>,
+    pub dim_timeout_secs: Option<u32>,
+    pub sleep_timeout_secs: Option<u32>,
+    pub ota_enabled: Option<bool>,
+    pub ota_check_interval_hours: Option<u32>,
+    pub push_enabled: Option<bool>,
+    pub push_url: Option<String>,
+    pub push_interval_secs: Option<u32>,
+    pub alerts_enabled: Option<bool>,
+    pub alerts_url: Option<String>,
+    pub alerts_filters: Option<Vec<String>>,
+    /// Only applied when `remote_sync_allow_wifi` is set on the device
+    pub wifi_ssid: Option<String>,
+    pub wifi_password: Option<String>,
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.trim();
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Check a hex HMAC-SHA256 of `body` keyed with `key` (constant-time compare)
+pub fn verify_signature(body: &[u8], signature_hex: &str, key: &str) -> bool {
+    let Some(signature) = decode_hex(signature_hex) else { return false };
+    let Ok(mut mac) = HmacSha256::new_from_slice(key.as_bytes()) else { return false };
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Merge a remote document into `cfg`. Values are clamped like the web API does;
+/// invalid URLs are skipped. Returns the number of fields applied.
+pub fn merge(cfg: &mut Config, remote: RemoteConfig, allow_wifi: bool) -> usize {
+    let mut applied = 0;
+    if let Some(v) = remote.brightness { cfg.brightness = v; applied += 1; }
+    if let Some(v) = remote.auto_brightness { cfg.auto_brightness = v; applied += 1; }
+    if let Some(v) = remote.theme { cfg.theme = v; applied += 1; }
+    if let Some(v) = remote.show_animations { cfg.show_animations = v; applied += 1; }
+    if let Some(v) = remote.dim_timeout_secs { cfg.dim_timeout_secs = v.clamp(5, 3600); applied += 1; }
+    if let Some(v) = remote.sleep_timeout_secs { cfg.sleep_timeout_secs = v.clamp(10, 24 * 3600); applied += 1; }
+    if let Some(v) = remote.ota_enabled { cfg.ota_enabled = v; applied += 1; }
+    if let Some(v) = remote.ota_check_interval_hours { cfg.ota_check_interval_hours = v.max(1); applied += 1; }
+    if let Some(v) = remote.push_enabled { cfg.push_enabled = v; applied += 1; }
+    if let Some(v) = remote.push_url {
+        let ok = crate::network::validators::validate_push_url(&v).is_ok();
+        if ok { cfg.push_url = v; }
+        applied += ok as usize;
+    }
+    if let Some(v) = remote.push_interval_secs { cfg.push_interval_secs = v.clamp(5, 3600); applied += 1; }
+    if let Some(v) = remote.alerts_enabled { cfg.alerts_enabled = v; applied += 1; }
+    if let Some(v) = remote.alerts_url {
+        let ok = crate::network::validators::validate_push_url(&v).is_ok();
+        if ok { cfg.alerts_url = v; }
+        applied += ok as usize;
+    }
+    if let Some(v) = remote.alerts_filters {
+        let ok = v.len() <= 4 && v.iter().all(|f| f.len() <= 64);
+        if ok { cfg.alerts_filters = v; }
+        applied += ok as usize;
+    }
+    if allow_wifi {
+        if let Some(v) = remote.wifi_ssid.filter(|s| !s.is_empty() && s.len() <= 32) { cfg.wifi_ssid = v; applied += 1; }
+        if let Some(v) = remote.wifi_password.filter(|p| p.len() <= 64) { cfg.wifi_password = v; applied += 1; }
+    }
+    cfg.remote_config_version = remote.version;
+    applied
+}
+
+/// Error from the most recent sync attempt, if it failed
+pub fn last_error() -> Option<String> {
+    LAST_ERROR.lock().ok().and_then(|e| e.clone())
+}
+
+fn set_last_error(error: Option<String>) {
+    if let Ok(mut slot) = LAST_ERROR.lock() {
+        *slot = error;
+    }
+}
+
+/// GET `url`, returning the body and the signature header
+fn fetch(url: &str) -> Result<(Vec<u8>, Option<String>)> {
+    let options = RequestOptions {
+        timeout: Duration::from_millis(HTTP_TIMEOUT_MS),
+        max_response_bytes: MAX_DOCUMENT_BYTES,
+        capture_header: Some(SIGNATURE_HEADER),
+        ..Default::default()
+    };
+    let response = http_client::get(url, &options)?;
+    Ok((response.body, response.header))
+}
+
+fn sync_once(config: &Arc<Mutex<Config>>, url: &str, key: &str) -> Result<()> {
+    let (body, signature) = fetch(url)?;
+    let Some(signature) = signature else { bail!("missing {} header", SIGNATURE_HEADER) };
+    if !verify_signature(&body, &signature, key) {
+        bail!("signature mismatch");
+    }
+    let remote: RemoteConfig = serde_json::from_slice(&body)?;
+
+    let mut cfg = config.lock().map_err(|_| anyhow::anyhow!("config lock poisoned"))?;
+    if remote.version <= cfg.remote_config_version {
+        return Ok(());
+    }
+    let version = remote.version;
+    let allow_wifi = cfg.remote_sync_allow_wifi;
+    let applied = merge(&mut cfg, remote, allow_wifi);
+    cfg.save()?;
+    log::info!("Remote config v{} applied ({} fields)", version, applied);
+    Ok(())
+}
+
+/// Start the sync thread. Settings are re-read each cycle. Safe to call multiple times.
+pub fn start(config: Arc<Mutex<Config>>, shutdown: ShutdownSignal) -> Result<()> {
+    static STARTED: AtomicBool = AtomicBool::new(false);
+    if STARTED.swap(true, Ordering::SeqCst) { return Ok(()); }
+
+    std::thread::Builder::new()
+        .name("remote-config".to_string())
+        .stack_size(10 * 1024)
+        .spawn(move || loop {
+            let target = match config.lock() {
+                Ok(cfg) if cfg.remote_sync_enabled && !cfg.remote_sync_url.is_empty() => Some((
+                    cfg.remote_sync_url.clone(),
+                    cfg.remote_sync_key.clone(),
+                    cfg.remote_sync_interval_secs.max(MIN_INTERVAL_SECS),
+                )),
+                _ => None,
+            };
+
+            let wait_secs = match target {
+                Some((_, key, _)) if key.is_empty() => {
+                    set_last_error(Some("no signing key configured".to_string()));
+                    IDLE_POLL_SECS
+                }
+                Some((url, key, interval)) => {
+                    match sync_once(&config, &url, &key) {
+                        Ok(()) => set_last_error(None),
+                        Err(e) => {
+                            log::warn!("Remote config sync failed: {:?}", e);
+                            set_last_error(Some(e.to_string()));
+                        }
+                    }
+                    interval
+                }
+                None => IDLE_POLL_SECS,
+            };
+
+            for _ in 0..wait_secs {
+                if shutdown.is_shutdown_requested() {
+                    return;
+                }
+                FreeRtos::delay_ms(1000);
+            }
+        })?;
+
+    log::info!("Remote config sync started");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_signature() {
+        // RFC 4231 test case 2
+        let body = b"what do ya want for nothing?";
+        let sig = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+        assert!(verify_signature(body, sig, "Jefe"));
+        assert!(!verify_signature(body, sig, "jefe"));
+        assert!(!verify_signature(b"tampered", sig, "Jefe"));
+        assert!(!verify_signature(body, "zz", "Jefe"));
+    }
+}
@@ -0,0 +1,185 @@
+// Sampling schedule: how often each data source is polled, and whether it is
+// polled at all. Battery installs can slow everything down and bench setups
+// speed it up without a rebuild. The Core 0 sampler and the latency thread
+// read it from the config; the Core 1 processor has no config handle and
+// reads the copy the main loop publishes here.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataSource {
+    /// Temperature and battery ADC (Core 0)
+    Sensors,
+    /// WiFi monitor (Core 1)
+    Network,
+    /// Sensor/frame aggregation (Core 1)
+    Processing,
+    /// Gateway and reference-host pings (Core 1)
+    Latency,
+}
+
+impl DataSource {
+    pub const ALL: [Self; 4] = [Self::Sensors, Self::Network, Self::Processing, Self::Latency];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sensors => "sensors",
+            Self::Network => "network",
+            Self::Processing => "processing",
+            Self::Latency => "latency",
+        }
+    }
+
+    pub fn default_interval_ms(self) -> u32 {
+        match self {
+            Self::Sensors => 5_000,
+            Self::Network => 10_000,
+            Self::Processing => 100,
+            Self::Latency => 30_000,
+        }
+    }
+
+    /// Accepted interval range (ms)
+    pub fn bounds_ms(self) -> (u32, u32) {
+        match self {
+            Self::Sensors => (500, 3_600_000),
+            Self::Network => (1_000, 3_600_000),
+            Self::Processing => (20, 5_000),
+            Self::Latency => (5_000, 3_600_000),
+        }
+    }
+
+    /// The UI gets its sensor readings through the processor, so it always runs
+    pub fn can_disable(self) -> bool {
+        self != Self::Processing
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceSchedule {
+    pub interval_ms: u32,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool { true }
+
+impl SourceSchedule {
+    pub fn default_for(source: DataSource) -> Self {
+        Self { interval_ms: source.default_interval_ms(), enabled: true }
+    }
+}
+
+/// One entry per data source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SampleSchedule {
+    #[serde(default = "default_sensors")]
+    pub sensors: SourceSchedule,
+    #[serde(default = "default_network")]
+    pub network: SourceSchedule,
+    #[serde(default = "default_processing")]
+    pub processing: SourceSchedule,
+    #[serde(default = "default_latency")]
+    pub latency: SourceSchedule,
+}
+
+fn default_sensors() -> SourceSchedule { SourceSchedule::default_for(DataSource::Sensors) }
+fn default_network() -> SourceSchedule { SourceSchedule::default_for(DataSource::Network) }
+fn default_processing() -> SourceSchedule { SourceSchedule::default_for(DataSource::Processing) }
+fn default_latency() -> SourceSchedule { SourceSchedule::default_for(DataSource::Latency) }
+
+impl Default for SampleSchedule {
+    fn default() -> Self {
+        Self {
+            sensors: default_sensors(),
+            network: default_network(),
+            processing: default_processing(),
+            latency: default_latency(),
+        }
+    }
+}
+
+impl SampleSchedule {
+    pub fn get(&self, source: DataSource) -> SourceSchedule {
+        match source {
+            DataSource::Sensors => self.sensors,
+            DataSource::Network => self.network,
+            DataSource::Processing => self.processing,
+            DataSource::Latency => self.latency,
+        }
+    }
+
+    pub fn set(&mut self, source: DataSource, entry: SourceSchedule) {
+        match source {
+            DataSource::Sensors => self.sensors = entry,
+            DataSource::Network => self.network = entry,
+            DataSource::Processing => self.processing = entry,
+            DataSource::Latency => self.latency = entry,
+        }
+    }
+
+    /// Polling interval, None while the source is disabled
+    pub fn interval(&self, source: DataSource) -> Option<Duration> {
+        let entry = self.get(source);
+        (entry.enabled || !source.can_disable()).then(|| Duration::from_millis(entry.interval_ms as u64))
+    }
+}
+
+// Published for Core 1: interval in ms per source, 0 = disabled
+static PUBLISHED: [AtomicU32; 4] = [
+    AtomicU32::new(5_000),
+    AtomicU32::new(10_000),
+    AtomicU32::new(100),
+    AtomicU32::new(30_000),
+];
+
+/// Make `schedule` visible to `current` (main loop, after each config read)
+pub fn publish(schedule: &SampleSchedule) {
+    for source in DataSource::ALL {
+        let ms = schedule.interval(source).map_or(0, |d| d.as_millis() as u32);
+        PUBLISHED[source.index()].store(ms, Ordering::Relaxed);
+    }
+}
+
+/// Last published interval for `source`, None while it is disabled
+pub fn current(source: DataSource) -> Option<Duration> {
+    match PUBLISHED[source.index()].load(Ordering::Relaxed) {
+        0 => None,
+        ms => Some(Duration::from_millis(ms as u64)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_match_published() {
+        let schedule = SampleSchedule::default();
+        for source in DataSource::ALL {
+            assert_eq!(current(source), schedule.interval(source), "{}", source.name());
+            let (min, max) = source.bounds_ms();
+            assert!((min..=max).contains(&source.default_interval_ms()));
+        }
+        // Entries missing from a stored config come back as defaults
+        let parsed: SampleSchedule = serde_json::from_str(r#"{"sensors": {"interval_ms": 60000}}"#).unwrap();
+        assert_eq!(parsed.sensors, SourceSchedule { interval_ms: 60_000, enabled: true });
+        assert_eq!(parsed.latency, schedule.latency);
+    }
+
+    #[test]
+    fn test_disabled_sources() {
+        let mut schedule = SampleSchedule::default();
+        schedule.set(DataSource::Latency, SourceSchedule { interval_ms: 60_000, enabled: false });
+        schedule.set(DataSource::Processing, SourceSchedule { interval_ms: 200, enabled: false });
+        assert_eq!(schedule.interval(DataSource::Latency), None);
+        assert_eq!(schedule.interval(DataSource::Processing), Some(Duration::from_millis(200)));
+    }
+}
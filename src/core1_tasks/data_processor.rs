@@ -1,8 +1,16 @@
 // Data processing pipeline for Core 1
-// Aggregates sensor and network data, performs filtering, and sends updates to Core 0
+// Aggregates sensor and network data, performs filtering, and sends updates to Core 0.
+// Also owns the bookkeeping the render loop used to do inline: the metrics
+// store, sensor history and the [PERF]/[CORES] log lines are all fed from
+// the raw numbers Core 0 sends, so a frame never waits on them.
 
 use std::sync::mpsc::{Receiver, Sender, TryRecvError};
-use super::{SensorUpdate, NetworkUpdate};
+use super::{FrameReport, SensorUpdate, NetworkUpdate};
+use crate::text_buf::TextBuf;
+
+// Logging optimization - only log when values change noticeably
+const FPS_CHANGE_THRESHOLD: f32 = 5.0;  // Only log if FPS changes by more than 5
+const CPU_CHANGE_THRESHOLD: u8 = 10;    // Only log if CPU usage changes by more than 10%
 
 #[derive(Debug, Clone)]
 pub struct ProcessedData {
@@ -18,33 +26,43 @@ pub struct ProcessedData {
 
 pub struct DataProcessor {
     sensor_rx: Receiver<SensorUpdate>,
+    frame_rx: Receiver<FrameReport>,
     network_rx: Receiver<NetworkUpdate>,
     tx: Sender<ProcessedData>,
-    
+
     // Last known values
     last_sensor: Option<SensorUpdate>,
+    last_logged_fps: f32,
+    last_logged_cpu0: u8,
+    last_logged_cpu1: u8,
 }
 
 impl DataProcessor {
-    
+
     pub fn new_with_channel(
         sensor_rx: Receiver<SensorUpdate>,
+        frame_rx: Receiver<FrameReport>,
         network_rx: Receiver<NetworkUpdate>,
         tx: Sender<ProcessedData>,
     ) -> Self {
         Self {
             sensor_rx,
+            frame_rx,
             network_rx,
             tx,
             last_sensor: None,
+            last_logged_fps: 0.0,
+            last_logged_cpu0: 0,
+            last_logged_cpu1: 0,
         }
     }
-    
+
     pub fn process(&mut self) {
         // Process all pending sensor updates
         loop {
             match self.sensor_rx.try_recv() {
                 Ok(update) => {
+                    Self::record_sensor(&update);
                     self.last_sensor = Some(update);
                 },
                 Err(TryRecvError::Empty) => break,
@@ -54,12 +72,17 @@ impl DataProcessor {
                 }
             }
         }
-        
+
+        // Render-loop figures (one a second)
+        while let Ok(report) = self.frame_rx.try_recv() {
+            self.record_frame(report);
+        }
+
         // Drain network updates (not currently used)
         while let Ok(_) = self.network_rx.try_recv() {
             // Network data not currently used in ProcessedData
         }
-        
+
         // Generate processed data if we have sensor data
         if let Some(sensor) = &self.last_sensor {
             let processed = ProcessedData {
@@ -71,11 +94,102 @@ impl DataProcessor {
                 cpu_usage_core0: sensor.cpu_usage_core0,
                 cpu_usage_core1: sensor.cpu_usage_core1,
             };
-            
+
             // Send processed data (will block if channel is full)
             let _ = self.tx.send(processed);
         }
     }
-    
-    
-}
\ No newline at end of file
+
+    /// Temperature and battery into the metrics store and sensor history
+    fn record_sensor(update: &SensorUpdate) {
+        let store = crate::metrics_rwlock::metrics();
+        store.update_temperature(update.temperature);
+        store.update_battery(update.battery_voltage, update.battery_percentage, update.is_charging);
+
+        if let Some(history) = crate::sensors::history::get() {
+            if let Ok(hist) = history.lock() {
+                hist.add_temperature(update.temperature);
+                hist.add_battery(update.battery_percentage as f32);
+            }
+        }
+    }
+
+    /// Publish a render-loop report to the metrics store and log it when it moved
+    fn record_frame(&mut self, mut r: FrameReport) {
+        let store = crate::metrics_rwlock::metrics();
+        store.update_fps(r.current_fps, r.target_fps);
+        store.update_frame_stats(r.total_frames, r.skipped_frames);
+        if r.current_fps > 100.0 {
+            log::warn!("[METRICS] Unrealistic FPS being stored: {:.1} (frames: {}, skipped: {})",
+                r.current_fps, r.total_frames, r.skipped_frames);
+        }
+        store.update_cpu_cores(r.cpu_usage_core0, r.cpu_usage_core1);
+        store.update_cpu(((r.cpu_usage_core0 as u16 + r.cpu_usage_core1 as u16) / 2) as u8, r.cpu_freq_mhz as u16);
+        store.update_timings(r.render_us / 1000, r.flush_us / 1000);
+        store.update_render_allocs(r.allocs_per_frame);
+        store.update_wifi_signal(r.rssi);
+        match r.ssid.take() {
+            Some(ssid) => store.update_wifi_status(r.wifi_connected, ssid),
+            None => store.update_wifi_connected(r.wifi_connected),
+        }
+        store.update_display(r.backlight_level);
+        if crate::psram::PsramAllocator::is_available() {
+            store.update_psram(
+                crate::psram::PsramAllocator::get_free_size() as u32,
+                crate::psram::PsramAllocator::get_size() as u32,
+            );
+        }
+        store.update_wifi_reconnects(r.wifi_disconnects, r.wifi_reconnects);
+        if let Some(secs) = r.uptime_secs {
+            store.update_uptime(secs);
+        }
+
+        if r.current_fps < 1.0 {
+            log::debug!("[FPS] Low/zero FPS detected: {:.1}, frames: {}, skipped: {}",
+                r.current_fps, r.total_frames, r.skipped_frames);
+        }
+
+        let fps_changed = (r.current_fps - self.last_logged_fps).abs() > FPS_CHANGE_THRESHOLD;
+        let cpu0_changed = r.cpu_usage_core0.abs_diff(self.last_logged_cpu0) > CPU_CHANGE_THRESHOLD;
+        let cpu1_changed = r.cpu_usage_core1.abs_diff(self.last_logged_cpu1) > CPU_CHANGE_THRESHOLD;
+        if !(fps_changed || cpu0_changed || cpu1_changed) {
+            return;
+        }
+
+        // Calculate if we're meeting the target
+        let fps_status = if r.current_fps >= r.target_fps * 0.9 {
+            "MAX"  // At target
+        } else if r.current_fps >= r.target_fps * 0.5 {
+            "OK"   // Acceptable performance
+        } else {
+            "LOW"  // Below target
+        };
+        log::info!("[PERF] FPS: {:.1}/{:.0} [{}] | Skip: {:.1}% | Render: {:.1}ms | Flush: {:.1}ms | Allocs: {:.1}/frame | CPU: {}MHz | Heap: {}KB",
+            r.current_fps,
+            r.target_fps,
+            fps_status,
+            r.skip_rate,
+            r.render_us as f32 / 1000.0,
+            r.flush_us as f32 / 1000.0,
+            r.allocs_per_frame,
+            r.cpu_freq_mhz,
+            r.heap_free / 1024
+        );
+
+        // Format CPU usage - show "N/A" if 0 (not available)
+        let cpu_str = |usage: u8| if usage == 0 { TextBuf::<8>::from("N/A") } else { TextBuf::from_args(format_args!("{}%", usage)) };
+        let (cpu0_str, cpu1_str) = (cpu_str(r.cpu_usage_core0), cpu_str(r.cpu_usage_core1));
+        log::info!("[CORES] CPU0: {} | CPU1: {} | Tasks: C0={} C1={} Total={} | Avg: {}μs",
+            cpu0_str,
+            cpu1_str,
+            r.core0_tasks,
+            r.core1_tasks,
+            r.total_tasks,
+            r.avg_task_time_us
+        );
+
+        self.last_logged_fps = r.current_fps;
+        self.last_logged_cpu0 = r.cpu_usage_core0;
+        self.last_logged_cpu1 = r.cpu_usage_core1;
+    }
+}
@@ -28,10 +28,42 @@ pub struct SensorUpdate {
 }
 pub use network_monitor::NetworkUpdate;
 
+/// Raw render-loop figures Core 0 sends once a second; Core 1 turns them
+/// into metrics and the [PERF]/[CORES] log lines
+#[derive(Debug, Clone)]
+pub struct FrameReport {
+    pub current_fps: f32,
+    pub target_fps: f32,
+    pub total_frames: u64,
+    pub skipped_frames: u64,
+    pub skip_rate: f32,
+    pub render_us: u32,
+    pub flush_us: u32,
+    /// Heap allocations per rendered frame since the last report
+    pub allocs_per_frame: f32,
+    pub cpu_freq_mhz: u32,
+    pub cpu_usage_core0: u8,
+    pub cpu_usage_core1: u8,
+    pub heap_free: u32,
+    pub core0_tasks: u32,
+    pub core1_tasks: u32,
+    pub total_tasks: u32,
+    pub avg_task_time_us: u32,
+    pub rssi: i8,
+    pub wifi_connected: bool,
+    /// Only set when the SSID changed since the last report
+    pub ssid: Option<String>,
+    pub backlight_level: u8,
+    pub wifi_disconnects: u32,
+    pub wifi_reconnects: u32,
+    pub uptime_secs: Option<u64>,
+}
+
 // Channels for communication between cores
 pub struct Core1Channels {
     pub processed_rx: std::sync::mpsc::Receiver<ProcessedData>,
     pub sensor_tx: std::sync::mpsc::Sender<SensorUpdate>,  // Core 0 sends sensor data to Core 1
+    pub frame_tx: std::sync::mpsc::Sender<FrameReport>,    // Core 0 sends render-loop figures to Core 1
 }
 
 // Shared state between cores
@@ -47,6 +79,7 @@ impl Core1Manager {
     pub fn new() -> Result<(Self, Core1Channels)> {
         // Create channels for sensor data FROM Core 0
         let (core0_sensor_tx, core0_sensor_rx) = std::sync::mpsc::channel();
+        let (core0_frame_tx, core0_frame_rx) = std::sync::mpsc::channel();
         
         // Create channels for network data
         let (network_tx, network_rx) = std::sync::mpsc::channel();
@@ -58,6 +91,7 @@ impl Core1Manager {
         let network_monitor = Arc::new(Mutex::new(NetworkMonitor::new_with_channel(network_tx)));
         let data_processor = Arc::new(Mutex::new(DataProcessor::new_with_channel(
             core0_sensor_rx,  // Will receive sensor data from Core 0
+            core0_frame_rx,
             network_rx,
             processed_tx
         )));
@@ -66,6 +100,7 @@ impl Core1Manager {
         let channels = Core1Channels {
             processed_rx,
             sensor_tx: core0_sensor_tx,  // Core 0 will use this to send sensor data
+            frame_tx: core0_frame_tx,
         };
 
         Ok((
@@ -128,11 +163,11 @@ unsafe extern "C" fn core1_task_entry(pv_parameters: *mut std::ffi::c_void) {
     log::info!("CORE1: Task started on CPU {:?}", esp_idf_hal::cpu::core());
     // Normal info-level for steady-state
     log::info!("CORE1: Starting background monitoring tasks");
-    log::info!("Core 1: Network interval: 10s, Process interval: 100ms");
     
-    // Task intervals
-    let network_interval = Duration::from_secs(10);
-    let process_interval = Duration::from_millis(100);
+    // Task intervals follow the config's sample schedule (published by Core 0)
+    use crate::config::schedule::{self, DataSource};
+    // Upper bound on a sleep, to keep the watchdog happy and pick up schedule changes
+    const MAX_SLEEP: Duration = Duration::from_millis(100);
     
     let mut last_network = Instant::now();
     let mut last_process = Instant::now();
@@ -147,8 +182,11 @@ unsafe extern "C" fn core1_task_entry(pv_parameters: *mut std::ffi::c_void) {
             log::info!("CORE1: Heartbeat - iteration {}", loop_counter);
         }
         
-        // Network monitoring (10s interval)
-        if now.duration_since(last_network) >= network_interval {
+        let network_interval = schedule::current(DataSource::Network);
+        let process_interval = schedule::current(DataSource::Processing).unwrap_or(MAX_SLEEP);
+        
+        // Network monitoring (10s by default, skipped while disabled)
+        if network_interval.is_some_and(|interval| now.duration_since(last_network) >= interval) {
             if let Ok(mut monitor) = network_monitor.try_lock() {
                 if let Err(e) = monitor.update() {
                     log::warn!("Network monitor error: {}", e);
@@ -157,7 +195,7 @@ unsafe extern "C" fn core1_task_entry(pv_parameters: *mut std::ffi::c_void) {
             last_network = now;
         }
         
-        // Data processing (100ms interval) - only process when there's likely new data
+        // Data processing (100ms by default) - only process when there's likely new data
         if now.duration_since(last_process) >= process_interval {
             if let Ok(mut processor) = data_processor.try_lock() {
                 processor.process();
@@ -166,13 +204,12 @@ unsafe extern "C" fn core1_task_entry(pv_parameters: *mut std::ffi::c_void) {
         }
         
         // Calculate next wake time to reduce CPU usage
-        let next_network = last_network + network_interval;
         let next_process = last_process + process_interval;
-        let next_wake = next_network.min(next_process);
+        let next_wake = network_interval.map_or(next_process, |interval| (last_network + interval).min(next_process));
         let sleep_duration = next_wake.saturating_duration_since(now);
         
-        // Sleep until next event (up to 100ms max to keep watchdog happy)
-        let sleep_ms = sleep_duration.as_millis().min(100) as u32;
+        // Sleep until next event (up to MAX_SLEEP)
+        let sleep_ms = sleep_duration.min(MAX_SLEEP).as_millis() as u32;
         if sleep_ms > 0 {
             esp_idf_hal::delay::FreeRtos::delay_ms(sleep_ms);
         } else {
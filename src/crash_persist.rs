@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use esp_idf_svc::nvs::{EspDefaultNvsPartition, EspNvs};
+use crate::storage::kv;
 
 const CRASH_NS: &str = "crash";
 const CRASH_KEY: &str = "last";
@@ -59,43 +59,20 @@ pub fn save_last_crash(panic_reason: &str) {
         log_excerpt,
     };
 
-    if let Ok(bytes) = serde_json::to_vec(&record) {
-        if let Ok(nvs_part) = EspDefaultNvsPartition::take() {
-            if let Ok(mut nvs) = EspNvs::new(nvs_part, CRASH_NS, true) {
-                let _ = nvs.set_blob(CRASH_KEY, &bytes);
-            }
-        }
+    if let Ok(mut store) = kv::open(CRASH_NS) {
+        let _ = store.set(CRASH_KEY, &record);
     }
 }
 
 pub fn read_last_crash() -> anyhow::Result<Option<LastCrash>> {
-    let nvs_part = match EspDefaultNvsPartition::take() {
-        Ok(p) => p,
-        Err(_) => return Ok(None),
-    };
-    let nvs = match EspNvs::new(nvs_part, CRASH_NS, true) {
-        Ok(n) => n,
-        Err(_) => return Ok(None),
-    };
-
-    let mut buf = vec![0u8; 4096];
-    let data_opt = nvs.get_blob(CRASH_KEY, &mut buf)?;
-    let data = match data_opt {
-        Some(d) if !d.is_empty() => d,
-        _ => return Ok(None),
-    };
-    match serde_json::from_slice::<LastCrash>(data) {
-        Ok(val) => Ok(Some(val)),
+    match kv::open(CRASH_NS) {
+        Ok(mut store) => Ok(store.get(CRASH_KEY)),
         Err(_) => Ok(None),
     }
 }
 
 pub fn clear_last_crash() -> anyhow::Result<()> {
-    let nvs_part = EspDefaultNvsPartition::take()?;
-    let mut nvs = EspNvs::new(nvs_part, CRASH_NS, true)?;
-    // Write minimal empty JSON to logically clear
-    nvs.set_blob(CRASH_KEY, b"{}")?;
-    Ok(())
+    kv::open(CRASH_NS)?.remove(CRASH_KEY)
 }
 
 
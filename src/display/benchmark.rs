@@ -0,0 +1,257 @@
+// Display benchmark: fill-rate, text-rate and dirty-rect microbenchmarks
+// that run on any build, started with POST /api/display/benchmark instead of
+// recompiling with a debug flag. Like the self-test, the main loop runs it
+// since it owns the display; results are shown on screen and served as JSON
+// from GET /api/display/benchmark, and the UI is redrawn afterwards.
+
+use super::error::DisplayError;
+use core::sync::atomic::{AtomicBool, Ordering};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::display::font5x7::{FONT_HEIGHT, FONT_WIDTH};
+use crate::display::{colors, DisplayManager};
+use crate::ui::layout::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+type Result<T> = core::result::Result<T, DisplayError>;
+
+/// Full-screen fills
+pub const FILL_FRAMES: u32 = 30;
+/// Screens of text
+pub const TEXT_FRAMES: u32 = 20;
+/// Frames of scattered small updates
+pub const DIRTY_FRAMES: u32 = 60;
+/// Small rects drawn per dirty-rect frame
+pub const DIRTY_RECTS_PER_FRAME: u16 = 8;
+/// Side of each small rect
+pub const DIRTY_RECT_SIZE: u16 = 32;
+
+const TEXT_LINE: &str = "The quick brown fox jumps over 0123456789";
+const FILL_COLORS: [u16; 4] = [colors::PRIMARY_RED, colors::PRIMARY_GREEN, colors::PRIMARY_BLUE, colors::WHITE];
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static LATEST: Mutex<Option<BenchmarkReport>> = Mutex::new(None);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchResult {
+    pub name: &'static str,
+    pub frames: u32,
+    pub pixels: u64,
+    pub elapsed_ms: f32,
+    pub pixels_per_sec: u64,
+    pub ms_per_frame: f32,
+}
+
+impl BenchResult {
+    pub fn new(name: &'static str, frames: u32, pixels: u64, elapsed: Duration) -> Self {
+        let secs = elapsed.as_secs_f64();
+        let pixels_per_sec = if secs > 0.0 { (pixels as f64 / secs) as u64 } else { 0 };
+        let elapsed_ms = (secs * 1000.0) as f32;
+        let ms_per_frame = if frames > 0 { elapsed_ms / frames as f32 } else { 0.0 };
+        Self { name, frames, pixels, elapsed_ms, pixels_per_sec, ms_per_frame }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BenchmarkReport {
+    pub width: u16,
+    pub height: u16,
+    pub results: Vec<BenchResult>,
+}
+
+/// GET /api/display/benchmark
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkStatus {
+    pub running: bool,
+    pub report: Option<BenchmarkReport>,
+}
+
+/// Ask the main loop to run the benchmark; false if one is already running
+pub fn request() -> bool {
+    if RUNNING.load(Ordering::Relaxed) {
+        return false;
+    }
+    REQUESTED.store(true, Ordering::Relaxed);
+    true
+}
+
+/// Consume a pending request (main loop)
+pub fn take_request() -> bool {
+    REQUESTED.swap(false, Ordering::Relaxed)
+}
+
+pub fn is_running() -> bool {
+    RUNNING.load(Ordering::Relaxed) || REQUESTED.load(Ordering::Relaxed)
+}
+
+/// Last finished report
+pub fn latest() -> Option<BenchmarkReport> {
+    LATEST.lock().ok().and_then(|r| r.clone())
+}
+
+pub fn status() -> BenchmarkStatus {
+    BenchmarkStatus { running: is_running(), report: latest() }
+}
+
+fn feed_watchdog() {
+    #[cfg(target_os = "espidf")]
+    unsafe {
+        esp_idf_sys::esp_task_wdt_reset();
+    }
+}
+
+/// Alternating full-screen colours, one flush per frame
+fn bench_fill(display: &mut DisplayManager) -> Result<BenchResult> {
+    let start = Instant::now();
+    for frame in 0..FILL_FRAMES {
+        display.fill_rect(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT, FILL_COLORS[frame as usize % FILL_COLORS.len()])?;
+        display.flush()?;
+        feed_watchdog();
+    }
+    let pixels = FILL_FRAMES as u64 * SCREEN_WIDTH as u64 * SCREEN_HEIGHT as u64;
+    Ok(BenchResult::new("fill", FILL_FRAMES, pixels, start.elapsed()))
+}
+
+/// Screens of scale-1 text over a cleared background; counts glyph cells
+fn bench_text(display: &mut DisplayManager) -> Result<BenchResult> {
+    let line_height = FONT_HEIGHT as u16 + 3;
+    let char_width = FONT_WIDTH as u16 + 1;
+    let chars_per_line = (TEXT_LINE.len() as u16).min(SCREEN_WIDTH / char_width);
+    let lines = SCREEN_HEIGHT / line_height;
+    let start = Instant::now();
+    for frame in 0..TEXT_FRAMES {
+        display.clear(colors::BLACK)?;
+        let color = if frame % 2 == 0 { colors::WHITE } else { colors::ACCENT_ORANGE };
+        for line in 0..lines {
+            display.draw_text(0, line * line_height, TEXT_LINE, color, None, 1)?;
+        }
+        display.flush()?;
+        feed_watchdog();
+    }
+    let pixels = TEXT_FRAMES as u64 * lines as u64 * chars_per_line as u64 * char_width as u64 * FONT_HEIGHT as u64;
+    Ok(BenchResult::new("text", TEXT_FRAMES, pixels, start.elapsed()))
+}
+
+/// A few small rects at shifting positions per frame, as a live screen updates
+fn bench_dirty_rects(display: &mut DisplayManager) -> Result<BenchResult> {
+    let cols = SCREEN_WIDTH / DIRTY_RECT_SIZE;
+    let rows = SCREEN_HEIGHT / DIRTY_RECT_SIZE;
+    let start = Instant::now();
+    for frame in 0..DIRTY_FRAMES {
+        for i in 0..DIRTY_RECTS_PER_FRAME {
+            let cell = (frame as u16 * 7 + i * 5) % (cols * rows);
+            let (x, y) = ((cell % cols) * DIRTY_RECT_SIZE, (cell / cols) * DIRTY_RECT_SIZE);
+            display.fill_rect(x, y, DIRTY_RECT_SIZE, DIRTY_RECT_SIZE, FILL_COLORS[(frame + i as u32) as usize % FILL_COLORS.len()])?;
+        }
+        display.flush()?;
+        feed_watchdog();
+    }
+    let pixels = DIRTY_FRAMES as u64 * DIRTY_RECTS_PER_FRAME as u64 * DIRTY_RECT_SIZE as u64 * DIRTY_RECT_SIZE as u64;
+    Ok(BenchResult::new("dirty_rect", DIRTY_FRAMES, pixels, start.elapsed()))
+}
+
+/// Run all three benchmarks and publish the report. Leaves the screen
+/// covered in test patterns; the caller redraws the UI.
+pub fn run_suite(display: &mut DisplayManager) -> Result<BenchmarkReport> {
+    RUNNING.store(true, Ordering::Relaxed);
+    log::warn!("Display benchmark started");
+    let results = (|| -> Result<Vec<BenchResult>> {
+        Ok(vec![bench_fill(display)?, bench_text(display)?, bench_dirty_rects(display)?])
+    })();
+    RUNNING.store(false, Ordering::Relaxed);
+
+    let report = BenchmarkReport { width: SCREEN_WIDTH, height: SCREEN_HEIGHT, results: results? };
+    for r in &report.results {
+        log::info!("Display benchmark: {} {} px/s, {:.2} ms/frame", r.name, r.pixels_per_sec, r.ms_per_frame);
+    }
+    if let Ok(mut latest) = LATEST.lock() {
+        *latest = Some(report.clone());
+    }
+    Ok(report)
+}
+
+/// Results table
+pub fn draw_report(display: &mut DisplayManager, report: &BenchmarkReport) -> Result<()> {
+    display.clear(colors::BLACK)?;
+    display.draw_text_centered(8, "DISPLAY BENCHMARK", colors::ACCENT_ORANGE, None, 2)?;
+    display.draw_text(10, 36, "test", colors::TEXT_SECONDARY, None, 1)?;
+    display.draw_text(100, 36, "Mpx/s", colors::TEXT_SECONDARY, None, 1)?;
+    display.draw_text(190, 36, "ms/frame", colors::TEXT_SECONDARY, None, 1)?;
+    for (i, r) in report.results.iter().enumerate() {
+        let y = 56 + i as u16 * 20;
+        display.draw_text(10, y, r.name, colors::WHITE, None, 1)?;
+        display.draw_text(100, y, &format!("{:.2}", r.pixels_per_sec as f32 / 1_000_000.0), colors::PRIMARY_GREEN, None, 1)?;
+        display.draw_text(190, y, &format!("{:.1}", r.ms_per_frame), colors::PRIMARY_GREEN, None, 1)?;
+    }
+    display.draw_text_centered(SCREEN_HEIGHT - 12, "Press any button", colors::TEXT_SECONDARY, None, 1)?;
+    display.flush()
+}
+
+#[cfg(target_os = "espidf")]
+pub use self::device::run;
+
+#[cfg(target_os = "espidf")]
+mod device {
+    use super::*;
+    use esp_idf_hal::delay::FreeRtos;
+    use crate::input::{InputEvent, InputSource};
+
+    /// How long the results stay up without a button press
+    const RESULTS_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Run the benchmarks and show the results until a button press or
+    /// RESULTS_TIMEOUT. Blocks the main loop for a few seconds.
+    pub fn run(display: &mut DisplayManager, inputs: &mut [Box<dyn InputSource>]) -> Option<BenchmarkReport> {
+        let report = match run_suite(display) {
+            Ok(report) => report,
+            Err(e) => {
+                log::warn!("Display benchmark failed: {:?}", e);
+                return None;
+            }
+        };
+        if let Err(e) = draw_report(display, &report) {
+            log::warn!("Display benchmark: failed to draw results: {:?}", e);
+        }
+        let start = Instant::now();
+        while start.elapsed() < RESULTS_TIMEOUT {
+            if inputs.iter_mut().any(|s| matches!(s.poll(), Some(InputEvent::Press(_) | InputEvent::Click(_)))) {
+                break;
+            }
+            feed_watchdog();
+            FreeRtos::delay_ms(20);
+        }
+        Some(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rates() {
+        let r = BenchResult::new("fill", 10, 2_000_000, Duration::from_millis(500));
+        assert_eq!(r.pixels_per_sec, 4_000_000);
+        assert!((r.ms_per_frame - 50.0).abs() < 0.01);
+        assert!((r.elapsed_ms - 500.0).abs() < 0.01);
+        let idle = BenchResult::new("none", 0, 0, Duration::ZERO);
+        assert_eq!((idle.pixels_per_sec, idle.ms_per_frame), (0, 0.0));
+    }
+
+    #[test]
+    fn test_suite_on_framebuffer() {
+        let mut display = DisplayManager::new();
+        let report = run_suite(&mut display).unwrap();
+        let names: Vec<_> = report.results.iter().map(|r| r.name).collect();
+        assert_eq!(names, ["fill", "text", "dirty_rect"]);
+        assert_eq!(report.results[0].pixels, FILL_FRAMES as u64 * SCREEN_WIDTH as u64 * SCREEN_HEIGHT as u64);
+        assert!(report.results.iter().all(|r| r.pixels > 0));
+        assert_eq!(status().report.unwrap().results.len(), 3);
+        assert!(!status().running);
+
+        draw_report(&mut display, &report).unwrap();
+        let json = serde_json::to_value(&report).unwrap();
+        assert!(json["results"][1]["ms_per_frame"].is_number());
+    }
+}
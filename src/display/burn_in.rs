@@ -0,0 +1,156 @@
+// Burn-in mitigation for the ST7789. Static headers and labels can leave image
+// retention after months of uptime, so the whole UI is nudged by up to ±2 px
+// on a slow cycle, and an optional screensaver (a bouncing clock) replaces the
+// UI once the device has been idle for the dim timeout, until it sleeps.
+
+use super::error::DisplayError;
+use std::time::{Duration, Instant};
+use super::colors;
+use super::font5x7::{FONT_HEIGHT, FONT_WIDTH};
+use super::DisplayManager;
+
+type Result<T> = core::result::Result<T, DisplayError>;
+
+/// Largest offset in either direction
+pub const MAX_SHIFT: i8 = 2;
+/// How long the UI stays at one offset
+pub const SHIFT_INTERVAL: Duration = Duration::from_secs(300);
+/// Time between screensaver moves
+pub const SCREENSAVER_STEP: Duration = Duration::from_millis(500);
+
+const SCREENSAVER_SCALE: u8 = 3;
+const SCREENSAVER_SPEED: i32 = 3;
+
+/// Offsets visited in turn; each step moves 2 px along one axis
+const SHIFT_PATTERN: [(i8, i8); 8] = [(0, 0), (2, 0), (2, 2), (0, 2), (-2, 2), (-2, 0), (-2, -2), (0, -2)];
+
+/// Slow orbit of the UI offset around the origin
+#[derive(Debug)]
+pub struct PixelShift {
+    step: usize,
+    last_change: Instant,
+}
+
+impl PixelShift {
+    pub fn new(now: Instant) -> Self {
+        Self { step: 0, last_change: now }
+    }
+
+    pub fn offset(&self) -> (i8, i8) {
+        SHIFT_PATTERN[self.step]
+    }
+
+    /// Move to the next offset once `interval` has passed; returns it if it changed
+    pub fn update(&mut self, now: Instant, interval: Duration) -> Option<(i8, i8)> {
+        if now.duration_since(self.last_change) < interval {
+            return None;
+        }
+        self.last_change = now;
+        self.step = (self.step + 1) % SHIFT_PATTERN.len();
+        Some(self.offset())
+    }
+}
+
+/// Text bouncing off the screen edges
+#[derive(Debug)]
+pub struct Screensaver {
+    x: i32,
+    y: i32,
+    dx: i32,
+    dy: i32,
+    last_step: Option<Instant>,
+    /// Area covered by the last draw, erased before the next one
+    drawn: Option<(u16, u16, u16, u16)>,
+}
+
+impl Default for Screensaver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Screensaver {
+    pub fn new() -> Self {
+        Self { x: 0, y: 0, dx: SCREENSAVER_SPEED, dy: SCREENSAVER_SPEED, last_step: None, drawn: None }
+    }
+
+    /// Advance a `w`x`h` box inside a `width`x`height` screen and return its top-left corner
+    pub fn step(&mut self, w: u16, h: u16, width: u16, height: u16) -> (u16, u16) {
+        let max_x = width.saturating_sub(w) as i32;
+        let max_y = height.saturating_sub(h) as i32;
+        self.x += self.dx;
+        self.y += self.dy;
+        if self.x <= 0 || self.x >= max_x {
+            self.dx = -self.dx;
+            self.x = self.x.clamp(0, max_x);
+        }
+        if self.y <= 0 || self.y >= max_y {
+            self.dy = -self.dy;
+            self.y = self.y.clamp(0, max_y);
+        }
+        (self.x as u16, self.y as u16)
+    }
+
+    /// Draw `text` at its next position if a step is due. The first call after
+    /// `reset` clears the screen. Returns true if anything was drawn.
+    pub fn draw(&mut self, display: &mut DisplayManager, text: &str, width: u16, height: u16) -> Result<bool> {
+        let now = Instant::now();
+        match self.last_step {
+            Some(last) if now.duration_since(last) < SCREENSAVER_STEP => return Ok(false),
+            Some(_) => {}
+            None => display.clear(colors::BLACK)?,
+        }
+        self.last_step = Some(now);
+
+        let scale = SCREENSAVER_SCALE as u16;
+        let w = text.len() as u16 * (FONT_WIDTH as u16 * scale + 1);
+        let h = FONT_HEIGHT as u16 * scale;
+        if let Some((x, y, w, h)) = self.drawn.take() {
+            display.fill_rect(x, y, w, h, colors::BLACK)?;
+        }
+        let (x, y) = self.step(w, h, width, height);
+        display.draw_text(x, y, text, colors::TEXT_SECONDARY, None, SCREENSAVER_SCALE)?;
+        self.drawn = Some((x, y, w, h));
+        Ok(true)
+    }
+
+    /// Forget the on-screen state, e.g. after the UI took the screen back
+    pub fn reset(&mut self) {
+        self.last_step = None;
+        self.drawn = None;
+    }
+
+    pub fn is_showing(&self) -> bool {
+        self.last_step.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shift_orbits_within_two_pixels() {
+        let t0 = Instant::now();
+        let mut shift = PixelShift::new(t0);
+        assert_eq!(shift.offset(), (0, 0));
+        assert_eq!(shift.update(t0 + Duration::from_secs(10), SHIFT_INTERVAL), None);
+        let mut previous = shift.offset();
+        for i in 1..=SHIFT_PATTERN.len() as u32 {
+            let offset = shift.update(t0 + SHIFT_INTERVAL * i, SHIFT_INTERVAL).unwrap();
+            assert!(offset.0.abs() <= MAX_SHIFT && offset.1.abs() <= MAX_SHIFT);
+            assert_eq!((offset.0 - previous.0).abs() + (offset.1 - previous.1).abs(), 2);
+            previous = offset;
+        }
+        assert_eq!(previous, (0, 0));
+    }
+
+    #[test]
+    fn test_screensaver_bounces_inside_screen() {
+        let mut saver = Screensaver::new();
+        for _ in 0..500 {
+            let (x, y) = saver.step(90, 21, 300, 168);
+            assert!(x + 90 <= 300 && y + 21 <= 168);
+        }
+    }
+}
@@ -33,3 +33,41 @@ pub fn rgb565(r: u8, g: u8, b: u8) -> u16 {
     ((r as u16 & 0xF8) << 8) | ((g as u16 & 0xFC) << 3) | ((b as u16 & 0xF8) >> 3)
 }
 
+
+/// `color` scaled towards black; `level` 255 leaves it unchanged, 0 is black
+pub fn fade(color: u16, level: u8) -> u16 {
+    if level == 255 {
+        return color;
+    }
+    let scale = |channel: u16| channel * level as u16 / 255;
+    let r = scale((color >> 11) & 0x1F);
+    let g = scale((color >> 5) & 0x3F);
+    let b = scale(color & 0x1F);
+    (r << 11) | (g << 5) | b
+}
+
+/// Nearest colour of the high-contrast palette (accessibility mode). Dark
+/// colours become black and greys white; everything else becomes the pure hue
+/// it leans towards, blue as cyan so it stays readable on black.
+pub fn high_contrast(color: u16) -> u16 {
+    // Channels on a common 0-63 scale
+    let r = ((color >> 11) & 0x1F) << 1;
+    let g = (color >> 5) & 0x3F;
+    let b = (color & 0x1F) << 1;
+    let max = r.max(g).max(b);
+    if max < 32 {
+        return BLACK;
+    }
+    if max - r.min(g).min(b) < 12 {
+        return WHITE;
+    }
+    // Channels at least half as strong as the strongest one make up the hue
+    match (r * 2 >= max, g * 2 >= max, b * 2 >= max) {
+        (true, false, false) => 0xF800,
+        (false, true, false) => 0x07E0,
+        (true, true, false) => YELLOW,
+        (true, false, true) => 0xF81F,
+        (false, _, true) => 0x07FF,
+        _ => WHITE,
+    }
+}
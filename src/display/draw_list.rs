@@ -0,0 +1,258 @@
+// Draw command recording. A screen describes its whole frame as a DrawList
+// instead of drawing directly; FramePresenter compares it with the previous
+// frame's list and only touches the pixels under commands that changed: their
+// old and new areas are erased to the clear colour and every command covering
+// them is replayed in order. Screens therefore need no "initialized" flags or
+// per-field caches - describing the same frame twice draws nothing.
+
+use super::error::DisplayError;
+use super::font5x7::{FONT_HEIGHT, FONT_WIDTH};
+use super::DisplayManager;
+
+type Result<T> = core::result::Result<T, DisplayError>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    /// Whole-screen background; only valid as the first command
+    Clear(u16),
+    FillRect { x: u16, y: u16, w: u16, h: u16, color: u16 },
+    Rect { x: u16, y: u16, w: u16, h: u16, color: u16 },
+    Line { x0: u16, y0: u16, x1: u16, y1: u16, color: u16 },
+    Text { x: u16, y: u16, text: String, color: u16, bg: Option<u16>, scale: u8 },
+    Circle { cx: u16, cy: u16, r: u16, color: u16 },
+    FillCircle { cx: u16, cy: u16, r: u16, color: u16 },
+    Battery { x: u16, y: u16, percentage: u8, charging: bool, scale: u8 },
+}
+
+/// Screen-space rectangle as (x, y, w, h)
+type Bounds = (u16, u16, u16, u16);
+
+impl DrawCommand {
+    /// Area the command can touch
+    fn bounds(&self) -> Bounds {
+        match *self {
+            Self::Clear(_) => (0, 0, u16::MAX, u16::MAX),
+            Self::FillRect { x, y, w, h, .. } | Self::Rect { x, y, w, h, .. } => (x, y, w, h),
+            Self::Line { x0, y0, x1, y1, .. } => (x0.min(x1), y0.min(y1), x0.abs_diff(x1) + 1, y0.abs_diff(y1) + 1),
+            Self::Text { x, y, ref text, scale, .. } => {
+                let char_width = (FONT_WIDTH * scale + 1) as u16;
+                (x, y, text.chars().count() as u16 * char_width, (FONT_HEIGHT * scale) as u16)
+            }
+            Self::Circle { cx, cy, r, .. } | Self::FillCircle { cx, cy, r, .. } => {
+                (cx.saturating_sub(r), cy.saturating_sub(r), 2 * r + 1, 2 * r + 1)
+            }
+            // Body plus terminal, see draw_battery_icon
+            Self::Battery { x, y, scale, .. } => (x, y, 26 * scale as u16, 12 * scale as u16),
+        }
+    }
+
+    fn execute(&self, display: &mut DisplayManager) -> Result<()> {
+        match *self {
+            Self::Clear(color) => display.clear(color),
+            Self::FillRect { x, y, w, h, color } => display.fill_rect(x, y, w, h, color),
+            Self::Rect { x, y, w, h, color } => display.draw_rect(x, y, w, h, color),
+            Self::Line { x0, y0, x1, y1, color } => display.draw_line(x0, y0, x1, y1, color),
+            Self::Text { x, y, ref text, color, bg, scale } => display.draw_text(x, y, text, color, bg, scale),
+            Self::Circle { cx, cy, r, color } => display.draw_circle(cx, cy, r, color),
+            Self::FillCircle { cx, cy, r, color } => display.fill_circle(cx, cy, r, color),
+            Self::Battery { x, y, percentage, charging, scale } => display.draw_battery_icon(x, y, percentage, charging, scale),
+        }
+    }
+}
+
+fn intersects(a: Bounds, b: Bounds) -> bool {
+    a.0 < b.0.saturating_add(b.2) && b.0 < a.0.saturating_add(a.2)
+        && a.1 < b.1.saturating_add(b.3) && b.1 < a.1.saturating_add(a.3)
+}
+
+/// One frame's drawing, recorded with the DisplayManager drawing API
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DrawList {
+    width: u16,
+    commands: Vec<DrawCommand>,
+}
+
+impl DrawList {
+    /// Empty list for a `width` pixel wide screen (used for centring)
+    pub fn new(width: u16) -> Self {
+        Self { width, commands: Vec::new() }
+    }
+
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    pub fn clear(&mut self, color: u16) {
+        self.commands.clear();
+        self.commands.push(DrawCommand::Clear(color));
+    }
+
+    pub fn fill_rect(&mut self, x: u16, y: u16, w: u16, h: u16, color: u16) {
+        self.commands.push(DrawCommand::FillRect { x, y, w, h, color });
+    }
+
+    pub fn draw_rect(&mut self, x: u16, y: u16, w: u16, h: u16, color: u16) {
+        self.commands.push(DrawCommand::Rect { x, y, w, h, color });
+    }
+
+    pub fn draw_line(&mut self, x0: u16, y0: u16, x1: u16, y1: u16, color: u16) {
+        self.commands.push(DrawCommand::Line { x0, y0, x1, y1, color });
+    }
+
+    pub fn draw_text(&mut self, x: u16, y: u16, text: &str, color: u16, bg: Option<u16>, scale: u8) {
+        self.commands.push(DrawCommand::Text { x, y, text: text.to_string(), color, bg, scale });
+    }
+
+    pub fn draw_text_centered(&mut self, y: u16, text: &str, color: u16, bg: Option<u16>, scale: u8) {
+        let char_width = (FONT_WIDTH * scale + 1) as u16;
+        let x = self.width.saturating_sub(text.len() as u16 * char_width) / 2;
+        self.draw_text(x, y, text, color, bg, scale);
+    }
+
+    pub fn draw_circle(&mut self, cx: u16, cy: u16, r: u16, color: u16) {
+        self.commands.push(DrawCommand::Circle { cx, cy, r, color });
+    }
+
+    pub fn fill_circle(&mut self, cx: u16, cy: u16, r: u16, color: u16) {
+        self.commands.push(DrawCommand::FillCircle { cx, cy, r, color });
+    }
+
+    pub fn draw_battery_icon(&mut self, x: u16, y: u16, percentage: u8, charging: bool, scale: u8) {
+        self.commands.push(DrawCommand::Battery { x, y, percentage, charging, scale });
+    }
+}
+
+/// Executes DrawLists, drawing only what changed since the last one
+#[derive(Debug, Default)]
+pub struct FramePresenter {
+    previous: Option<DrawList>,
+}
+
+impl FramePresenter {
+    /// Draw the next one in full (e.g. after another screen used the display)
+    pub fn invalidate(&mut self) {
+        self.previous = None;
+    }
+
+    /// Bring the display from the previous frame to `frame`; returns the
+    /// number of commands executed (0 when nothing changed)
+    pub fn present(&mut self, frame: DrawList, display: &mut DisplayManager) -> Result<usize> {
+        let new = &frame.commands;
+        let old = self.previous.as_ref().map_or(&[][..], |p| &p.commands[..]);
+
+        // A missing or different background means a full redraw
+        let background = match (new.first(), old.first()) {
+            (Some(DrawCommand::Clear(color)), Some(DrawCommand::Clear(previous))) if color == previous => Some(*color),
+            _ => None,
+        };
+        let Some(background) = background else {
+            for command in new {
+                command.execute(display)?;
+            }
+            let executed = new.len();
+            self.previous = Some(frame);
+            return Ok(executed);
+        };
+
+        // Areas of commands that changed, were added or were removed
+        let mut damage: Vec<Bounds> = Vec::new();
+        for i in 1..new.len().max(old.len()) {
+            match (new.get(i), old.get(i)) {
+                (Some(a), Some(b)) if a == b => {}
+                (a, b) => damage.extend(a.into_iter().chain(b).map(|c| c.bounds())),
+            }
+        }
+        if damage.is_empty() {
+            self.previous = Some(frame);
+            return Ok(0);
+        }
+
+        // Anything replayed must be fully inside the erased area, so grow the
+        // damage by every command that overlaps it until nothing changes
+        let mut replay = vec![false; new.len()];
+        loop {
+            let mut grew = false;
+            for (i, command) in new.iter().enumerate().skip(1) {
+                let bounds = command.bounds();
+                if !replay[i] && damage.iter().any(|&d| intersects(d, bounds)) {
+                    replay[i] = true;
+                    damage.push(bounds);
+                    grew = true;
+                }
+            }
+            if !grew {
+                break;
+            }
+        }
+
+        for &(x, y, w, h) in &damage {
+            display.fill_rect(x, y, w, h, background)?;
+        }
+        let mut executed = 0;
+        for (command, _) in new.iter().zip(&replay).filter(|(_, &r)| r) {
+            command.execute(display)?;
+            executed += 1;
+        }
+        self.previous = Some(frame);
+        Ok(executed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::colors::{BLACK, PRIMARY_BLUE, WHITE};
+
+    fn frame(clock: &str, status: &str) -> DrawList {
+        let mut list = DrawList::new(300);
+        list.clear(BLACK);
+        list.fill_rect(0, 0, 300, 30, PRIMARY_BLUE);
+        list.draw_text_centered(8, "WEATHER", WHITE, None, 2);
+        list.draw_text(250, 10, clock, WHITE, None, 1);
+        list.draw_text(10, 60, status, WHITE, None, 2);
+        list.draw_text(10, 155, "< Prev", WHITE, None, 1);
+        list
+    }
+
+    fn full_draw(list: DrawList) -> DisplayManager {
+        let mut display = DisplayManager::new();
+        FramePresenter::default().present(list, &mut display).unwrap();
+        display
+    }
+
+    #[test]
+    fn test_unchanged_frame_draws_nothing() {
+        let mut display = DisplayManager::new();
+        let mut presenter = FramePresenter::default();
+        assert_eq!(presenter.present(frame("12:00", "Sunny"), &mut display).unwrap(), 6);
+        assert_eq!(presenter.present(frame("12:00", "Sunny"), &mut display).unwrap(), 0);
+        presenter.invalidate();
+        assert_eq!(presenter.present(frame("12:00", "Sunny"), &mut display).unwrap(), 6);
+    }
+
+    #[test]
+    fn test_partial_update_matches_full_redraw() {
+        let mut display = DisplayManager::new();
+        let mut presenter = FramePresenter::default();
+        presenter.present(frame("12:00", "Sunny"), &mut display).unwrap();
+
+        // The clock sits on the header, so the header and its title are replayed too
+        assert_eq!(presenter.present(frame("12:01", "Sunny"), &mut display).unwrap(), 3);
+        assert_eq!(display.pixels(), full_draw(frame("12:01", "Sunny")).pixels());
+
+        // A shorter text must not leave the tail of the old one behind
+        assert_eq!(presenter.present(frame("12:01", "Fog"), &mut display).unwrap(), 1);
+        assert_eq!(display.pixels(), full_draw(frame("12:01", "Fog")).pixels());
+    }
+
+    #[test]
+    fn test_removed_commands_are_erased() {
+        let mut display = DisplayManager::new();
+        let mut presenter = FramePresenter::default();
+        presenter.present(frame("12:00", "Sunny"), &mut display).unwrap();
+        let mut shorter = frame("12:00", "Sunny");
+        shorter.commands.truncate(4);
+        presenter.present(shorter.clone(), &mut display).unwrap();
+        assert_eq!(display.pixels(), full_draw(shorter).pixels());
+    }
+}
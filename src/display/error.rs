@@ -0,0 +1,35 @@
+// Display layer errors. The parallel bus and the power, RD and backlight pins
+// only fail with an ESP-IDF error code; a panel that reads back as absent is
+// reported on its own so callers run headless instead of retrying writes.
+
+use thiserror::Error;
+use crate::error::HttpStatus;
+
+#[derive(Debug, Error)]
+pub enum DisplayError {
+    /// A GPIO driver call on the bus or control pins failed
+    #[error("display GPIO call failed (ESP error {0})")]
+    Gpio(i32),
+    /// RDDID read back 00 00 00: nothing is answering on the bus
+    #[error("panel not responding (RDDID read 00 00 00)")]
+    PanelNotResponding,
+    /// LEDC backlight setup or duty change failed
+    #[error("backlight {0} failed (ESP error {1})")]
+    Backlight(&'static str, i32),
+}
+
+impl HttpStatus for DisplayError {
+    fn http_status(&self) -> u16 {
+        match self {
+            Self::PanelNotResponding => 503,
+            Self::Gpio(_) | Self::Backlight(..) => 500,
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl From<esp_idf_sys::EspError> for DisplayError {
+    fn from(e: esp_idf_sys::EspError) -> Self {
+        Self::Gpio(e.code())
+    }
+}
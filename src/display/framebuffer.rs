@@ -0,0 +1,391 @@
+// Host display backend: renders the same drawing calls as DisplayManager into an
+// in-memory RGB565 framebuffer so screens can be previewed and snapshot-tested
+// on the desktop. Drawing semantics (clipping, font spacing, circle/line
+// algorithms) must stay in step with the LCD implementation in mod.rs.
+
+use super::error::DisplayError;
+use super::colors;
+use super::font5x7::{get_char_data, FONT_HEIGHT, FONT_WIDTH};
+
+type Result<T> = core::result::Result<T, DisplayError>;
+
+pub const WIDTH: u16 = 300;
+pub const HEIGHT: u16 = 168;
+
+pub struct FrameBuffer {
+    width: u16,
+    height: u16,
+    pixels: Vec<u16>,
+    backlight_level: u8,
+    // Screen transition adjustments, see set_transform
+    offset_x: i16,
+    fade: u8,
+    // Accessibility mode, see set_high_contrast
+    high_contrast: bool,
+}
+
+impl Default for FrameBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameBuffer {
+    /// Framebuffer matching the visible T-Display-S3 area, cleared to black
+    pub fn new() -> Self {
+        Self::with_size(WIDTH, HEIGHT)
+    }
+
+    pub fn with_size(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![colors::BLACK; width as usize * height as usize],
+            backlight_level: 255,
+            offset_x: 0,
+            fade: 255,
+            high_contrast: false,
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// RGB565 value at (x, y), or None outside the buffer
+    pub fn pixel(&self, x: u16, y: u16) -> Option<u16> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.pixels[y as usize * self.width as usize + x as usize])
+    }
+
+    /// Row-major RGB565 pixels
+    pub fn pixels(&self) -> &[u16] {
+        &self.pixels
+    }
+
+    /// Row-major 8-bit RGB triplets, e.g. for PNG encoding
+    pub fn to_rgb888(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.pixels.len() * 3);
+        for &p in &self.pixels {
+            let r = ((p >> 11) & 0x1F) as u8;
+            let g = ((p >> 5) & 0x3F) as u8;
+            let b = (p & 0x1F) as u8;
+            out.extend_from_slice(&[(r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2)]);
+        }
+        out
+    }
+
+    /// Whether the last set_backlight call left the backlight on
+    pub fn is_backlight_on(&self) -> bool {
+        self.backlight_level > 0
+    }
+
+    pub fn backlight_level(&self) -> u8 {
+        self.backlight_level
+    }
+
+    /// Shift everything drawn after this by `offset_x` pixels and scale its
+    /// colours by `fade` (255 = unchanged); clear() fills the whole buffer
+    /// regardless of the shift. Used for screen transitions.
+    pub fn set_transform(&mut self, offset_x: i16, fade: u8) {
+        self.offset_x = offset_x;
+        self.fade = fade;
+    }
+
+    /// Draw everything in the high-contrast palette (`colors::high_contrast`)
+    pub fn set_high_contrast(&mut self, enabled: bool) {
+        self.high_contrast = enabled;
+    }
+
+    /// The colour actually written for `color` under the current palette and fade
+    fn ink(&self, color: u16) -> u16 {
+        let color = if self.high_contrast { colors::high_contrast(color) } else { color };
+        colors::fade(color, self.fade)
+    }
+
+    /// Buffer columns for logical columns `x0..x1` under the current shift,
+    /// or None when they are all shifted out of view
+    fn shift_span(&self, x0: u16, x1: u16) -> Option<(u16, u16)> {
+        let x0 = (x0 as i32 + self.offset_x as i32).max(0);
+        let x1 = (x1 as i32 + self.offset_x as i32).min(self.width as i32);
+        (x0 < x1).then_some((x0 as u16, x1 as u16))
+    }
+
+    pub fn clear(&mut self, color: u16) -> Result<()> {
+        let color = self.ink(color);
+        self.pixels.fill(color);
+        Ok(())
+    }
+
+    pub fn draw_pixel(&mut self, x: u16, y: u16, color: u16) -> Result<()> {
+        if x >= self.width || y >= self.height {
+            return Ok(());
+        }
+        if let Some((x, _)) = self.shift_span(x, x + 1) {
+            self.pixels[y as usize * self.width as usize + x as usize] = self.ink(color);
+        }
+        Ok(())
+    }
+
+    pub fn fill_rect(&mut self, x: u16, y: u16, w: u16, h: u16, color: u16) -> Result<()> {
+        if x >= self.width || y >= self.height || w == 0 || h == 0 {
+            return Ok(());
+        }
+        let x1 = x.saturating_add(w).min(self.width);
+        let y1 = y.saturating_add(h).min(self.height);
+        let Some((x, x1)) = self.shift_span(x, x1) else {
+            return Ok(());
+        };
+        let color = self.ink(color);
+        for row in y..y1 {
+            let start = row as usize * self.width as usize;
+            self.pixels[start + x as usize..start + x1 as usize].fill(color);
+        }
+        Ok(())
+    }
+
+    pub fn draw_line(&mut self, x0: u16, y0: u16, x1: u16, y1: u16, color: u16) -> Result<()> {
+        let dx = (x1 as i32 - x0 as i32).abs();
+        let dy = (y1 as i32 - y0 as i32).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx - dy;
+        let mut x = x0 as i32;
+        let mut y = y0 as i32;
+
+        loop {
+            self.draw_pixel(x as u16, y as u16, color)?;
+            if x == x1 as i32 && y == y1 as i32 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 > -dy {
+                err -= dy;
+                x += sx;
+            }
+            if e2 < dx {
+                err += dx;
+                y += sy;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn draw_rect(&mut self, x: u16, y: u16, w: u16, h: u16, color: u16) -> Result<()> {
+        if w == 0 || h == 0 {
+            return Ok(());
+        }
+        self.draw_line(x, y, x + w - 1, y, color)?;
+        self.draw_line(x, y + h - 1, x + w - 1, y + h - 1, color)?;
+        self.draw_line(x, y, x, y + h - 1, color)?;
+        self.draw_line(x + w - 1, y, x + w - 1, y + h - 1, color)
+    }
+
+    pub fn reset_activity_timer(&mut self) {}
+
+    pub fn set_backlight(&mut self, level: u8) -> Result<()> {
+        self.backlight_level = level;
+        Ok(())
+    }
+
+    pub fn ensure_display_on(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn draw_char(&mut self, x: u16, y: u16, c: char, color: u16, bg_color: Option<u16>, scale: u8) -> Result<()> {
+        let char_data = get_char_data(c);
+        if let Some(bg) = bg_color {
+            self.fill_rect(x, y, (FONT_WIDTH * scale) as u16, (FONT_HEIGHT * scale) as u16, bg)?;
+        }
+        for row in 0..FONT_HEIGHT {
+            for col in 0..FONT_WIDTH {
+                if (char_data[col as usize] >> row) & 1 == 1 {
+                    let px = x + (col * scale) as u16;
+                    let py = y + (row * scale) as u16;
+                    self.fill_rect(px, py, scale as u16, scale as u16, color)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn draw_text(&mut self, x: u16, y: u16, text: &str, color: u16, bg_color: Option<u16>, scale: u8) -> Result<()> {
+        let char_width = (FONT_WIDTH * scale + 1) as u16; // +1 for spacing
+        let mut cursor_x = x;
+        for c in text.chars() {
+            if cursor_x + char_width > self.width {
+                break;
+            }
+            self.draw_char(cursor_x, y, c, color, bg_color, scale)?;
+            cursor_x += char_width;
+        }
+        Ok(())
+    }
+
+    pub fn draw_text_centered(&mut self, y: u16, text: &str, color: u16, bg_color: Option<u16>, scale: u8) -> Result<()> {
+        let char_width = (FONT_WIDTH * scale + 1) as u16;
+        let text_width = text.len() as u16 * char_width;
+        let x = self.width.saturating_sub(text_width) / 2;
+        self.draw_text(x, y, text, color, bg_color, scale)
+    }
+
+    pub fn draw_circle(&mut self, cx: u16, cy: u16, r: u16, color: u16) -> Result<()> {
+        let mut x = r as i32;
+        let mut y = 0i32;
+        let mut err = 0i32;
+        let (cx, cy) = (cx as i32, cy as i32);
+
+        while x >= y {
+            // Negative coordinates wrap to large u16 values and are clipped
+            for (px, py) in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)] {
+                self.draw_pixel((cx + px) as u16, (cy + py) as u16, color)?;
+            }
+            if err <= 0 {
+                y += 1;
+                err += 2 * y + 1;
+            }
+            if err > 0 {
+                x -= 1;
+                err -= 2 * x + 1;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn fill_circle(&mut self, cx: u16, cy: u16, r: u16, color: u16) -> Result<()> {
+        for dy in 0..=r as i32 {
+            let dx = ((r as i32 * r as i32 - dy * dy) as f32).sqrt() as i32;
+            if dx > 0 {
+                let x_start = (cx as i32 - dx).max(0) as u16;
+                let x_end = (cx as i32 + dx).min(self.width as i32 - 1) as u16;
+                let width = x_end - x_start + 1;
+                if cy as i32 - dy >= 0 {
+                    self.fill_rect(x_start, (cy as i32 - dy) as u16, width, 1, color)?;
+                }
+                if dy > 0 && cy as i32 + dy < self.height as i32 {
+                    self.fill_rect(x_start, (cy as i32 + dy) as u16, width, 1, color)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Signature mirrors DisplayManager::draw_progress_bar
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_progress_bar(&mut self, x: u16, y: u16, w: u16, h: u16, progress: u8, fg_color: u16, bg_color: u16, border_color: u16) -> Result<()> {
+        self.draw_rect(x, y, w, h, border_color)?;
+        self.fill_rect(x + 1, y + 1, w.saturating_sub(2), h.saturating_sub(2), bg_color)?;
+        let progress_width = (w.saturating_sub(2) as u32 * progress as u32 / 100) as u16;
+        if progress_width > 0 {
+            self.fill_rect(x + 1, y + 1, progress_width, h.saturating_sub(2), fg_color)?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn draw_battery_icon(&mut self, x: u16, y: u16, percentage: u8, is_charging: bool, scale: u8) -> Result<()> {
+        let width = 24 * scale as u16;
+        let height = 12 * scale as u16;
+        let terminal_width = 2 * scale as u16;
+        let terminal_height = 6 * scale as u16;
+
+        let battery_color = if percentage > 50 {
+            colors::PRIMARY_GREEN
+        } else if percentage > 20 {
+            colors::YELLOW
+        } else {
+            colors::PRIMARY_RED
+        };
+
+        self.draw_rect(x, y, width, height, colors::WHITE)?;
+        self.fill_rect(x + width, y + (height - terminal_height) / 2, terminal_width, terminal_height, colors::WHITE)?;
+
+        let fill_width = ((width - 4) as u32 * percentage as u32 / 100) as u16;
+        if fill_width > 0 {
+            self.fill_rect(x + 2, y + 2, fill_width, height - 4, battery_color)?;
+        }
+
+        if is_charging {
+            let cx = x + width / 2;
+            let cy = y + height / 2;
+            self.draw_line(cx - 2, cy - 3, cx + 1, cy, colors::WHITE)?;
+            self.draw_line(cx + 1, cy, cx - 1, cy + 3, colors::WHITE)?;
+            self.draw_pixel(cx, cy - 1, colors::WHITE)?;
+            self.draw_pixel(cx - 1, cy + 1, colors::WHITE)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_rect_clips_to_buffer() {
+        let mut fb = FrameBuffer::with_size(10, 10);
+        fb.fill_rect(8, 8, 5, 5, colors::WHITE).unwrap();
+        assert_eq!(fb.pixel(9, 9), Some(colors::WHITE));
+        assert_eq!(fb.pixel(7, 7), Some(colors::BLACK));
+        assert_eq!(fb.pixels().iter().filter(|&&p| p == colors::WHITE).count(), 4);
+    }
+
+    #[test]
+    fn test_text_uses_font_spacing() {
+        let mut fb = FrameBuffer::new();
+        fb.draw_text(0, 0, "II", colors::WHITE, Some(colors::BLACK), 1).unwrap();
+        // 'I' has its vertical bar in column 2; the second glyph starts 6px later
+        assert_eq!(fb.pixel(2, 3), Some(colors::WHITE));
+        assert_eq!(fb.pixel(8, 3), Some(colors::WHITE));
+        assert_eq!(fb.pixel(5, 3), Some(colors::BLACK));
+    }
+
+    #[test]
+    fn test_transform_shifts_and_fades() {
+        let mut fb = FrameBuffer::with_size(10, 10);
+        fb.set_transform(6, 255);
+        fb.fill_rect(0, 0, 6, 1, colors::WHITE).unwrap();
+        // Only the part still on the buffer is drawn
+        assert_eq!(fb.pixel(5, 0), Some(colors::BLACK));
+        assert_eq!(fb.pixel(6, 0), Some(colors::WHITE));
+        assert_eq!(fb.pixels().iter().filter(|&&p| p == colors::WHITE).count(), 4);
+
+        fb.set_transform(-3, 255);
+        fb.draw_pixel(2, 5, colors::WHITE).unwrap();
+        fb.draw_pixel(3, 5, colors::WHITE).unwrap();
+        assert_eq!(fb.pixel(0, 5), Some(colors::WHITE));
+
+        fb.set_transform(0, 0);
+        fb.clear(colors::WHITE).unwrap();
+        assert_eq!(fb.pixel(9, 9), Some(colors::BLACK));
+        assert_eq!(colors::fade(colors::WHITE, 128), (15 << 11) | (31 << 5) | 15);
+        assert_eq!(colors::fade(colors::PRIMARY_RED, 255), colors::PRIMARY_RED);
+    }
+
+    #[test]
+    fn test_high_contrast_palette() {
+        let mut fb = FrameBuffer::with_size(4, 1);
+        fb.set_high_contrast(true);
+        let theme = [colors::SURFACE_DARK, colors::TEXT_SECONDARY, colors::PRIMARY_GREEN, colors::PRIMARY_BLUE];
+        for (x, color) in theme.into_iter().enumerate() {
+            fb.draw_pixel(x as u16, 0, color).unwrap();
+        }
+        assert_eq!(fb.pixels(), &[colors::BLACK, colors::WHITE, 0x07E0, 0x07FF]);
+
+        assert_eq!(colors::high_contrast(colors::PRIMARY_RED), 0xF800);
+        assert_eq!(colors::high_contrast(colors::ACCENT_ORANGE), colors::YELLOW);
+        assert_eq!(colors::high_contrast(colors::BORDER_COLOR), colors::BLACK);
+        fb.set_high_contrast(false);
+        fb.clear(colors::SURFACE_DARK).unwrap();
+        assert_eq!(fb.pixel(0, 0), Some(colors::SURFACE_DARK));
+    }
+}
@@ -0,0 +1,161 @@
+// Panel health watchdog. Power glitches can leave the ST7789 blank or
+// scrambled (reset to sleep mode, wrong pixel format or orientation) while
+// the firmware keeps writing to it happily. Every CHECK_INTERVAL the render
+// loop reads the panel ID (RDDID) and display status (RDDST) back over the
+// RD strobe; when the panel no longer reports the state init() left it in,
+// twice in a row, the init sequence is re-run and the UI redrawn in full.
+
+use std::time::{Duration, Instant};
+
+/// Time between readbacks
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Consecutive failed readbacks before the panel is re-initialised; one bad
+/// read can be noise on the bus
+pub const FAILURES_BEFORE_REINIT: u32 = 2;
+
+/// MADCTL value init() writes (landscape)
+pub const EXPECTED_MADCTL: u8 = 0x60;
+
+/// RDDST pixel format field for 16 bits per pixel
+const PIXEL_FORMAT_16BIT: u8 = 0b101;
+
+/// What a readback found wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelFault {
+    /// Data lines stuck (all zeros or all ones)
+    NoResponse,
+    /// RDDID differs from the ID read at init
+    IdChanged,
+    SleepMode,
+    DisplayOff,
+    PixelFormat,
+    Orientation,
+    InversionOff,
+}
+
+impl PanelFault {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NoResponse => "no_response",
+            Self::IdChanged => "id_changed",
+            Self::SleepMode => "sleep_mode",
+            Self::DisplayOff => "display_off",
+            Self::PixelFormat => "pixel_format",
+            Self::Orientation => "orientation",
+            Self::InversionOff => "inversion_off",
+        }
+    }
+}
+
+/// Compare a readback with the state init() configured: `id` is RDDID now,
+/// `expected_id` the one read at init, `status` the four RDDST bytes (D31..D0)
+pub fn check_readback(id: [u8; 3], expected_id: [u8; 3], status: [u8; 4]) -> Result<(), PanelFault> {
+    if status == [0; 4] || status == [0xFF; 4] || id == [0; 3] || id == [0xFF; 3] {
+        return Err(PanelFault::NoResponse);
+    }
+    if id != expected_id {
+        return Err(PanelFault::IdChanged);
+    }
+    // D17 SLPOUT
+    if status[1] & 0x02 == 0 {
+        return Err(PanelFault::SleepMode);
+    }
+    // D10 DISON
+    if status[2] & 0x04 == 0 {
+        return Err(PanelFault::DisplayOff);
+    }
+    // D22-D20 interface pixel format
+    if (status[1] >> 4) & 0x07 != PIXEL_FORMAT_16BIT {
+        return Err(PanelFault::PixelFormat);
+    }
+    // D30-D25 mirror MADCTL D7-D2
+    if status[0] & 0x7E != (EXPECTED_MADCTL >> 1) & 0x7E {
+        return Err(PanelFault::Orientation);
+    }
+    // D13 INVON
+    if status[2] & 0x20 == 0 {
+        return Err(PanelFault::InversionOff);
+    }
+    Ok(())
+}
+
+/// When to read back and when to give up on the panel state
+#[derive(Debug)]
+pub struct HealthMonitor {
+    last_check: Instant,
+    failures: u32,
+}
+
+impl HealthMonitor {
+    pub fn new(now: Instant) -> Self {
+        Self { last_check: now, failures: 0 }
+    }
+
+    /// A readback is due; marks it as done
+    pub fn due(&mut self, now: Instant) -> bool {
+        if now.duration_since(self.last_check) < CHECK_INTERVAL {
+            return false;
+        }
+        self.last_check = now;
+        true
+    }
+
+    /// Record a readback; true when the panel should be re-initialised
+    pub fn record(&mut self, result: Result<(), PanelFault>) -> bool {
+        match result {
+            Ok(()) => {
+                self.failures = 0;
+                false
+            }
+            Err(_) => {
+                self.failures += 1;
+                if self.failures >= FAILURES_BEFORE_REINIT {
+                    self.failures = 0;
+                    return true;
+                }
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: [u8; 3] = [0x85, 0x85, 0x52];
+    // Booster on, MX|MV, 16 bpp, sleep out, normal mode, inversion on, display on
+    const GOOD: [u8; 4] = [0xB0, 0x53, 0x24, 0x00];
+
+    #[test]
+    fn test_readback_matches_init_state() {
+        assert_eq!(check_readback(ID, ID, GOOD), Ok(()));
+        assert_eq!(check_readback([0; 3], ID, GOOD), Err(PanelFault::NoResponse));
+        assert_eq!(check_readback(ID, ID, [0xFF; 4]), Err(PanelFault::NoResponse));
+        assert_eq!(check_readback([0x85, 0x85, 0x00], ID, GOOD), Err(PanelFault::IdChanged));
+        // Power-on reset state: sleep in, display off, 18 bpp
+        assert_eq!(check_readback(ID, ID, [0x00, 0x61, 0x00, 0x00]), Err(PanelFault::SleepMode));
+        assert_eq!(check_readback(ID, ID, [0xB0, 0x53, 0x20, 0x00]), Err(PanelFault::DisplayOff));
+        assert_eq!(check_readback(ID, ID, [0xB0, 0x63, 0x24, 0x00]), Err(PanelFault::PixelFormat));
+        assert_eq!(check_readback(ID, ID, [0x80, 0x53, 0x24, 0x00]), Err(PanelFault::Orientation));
+        assert_eq!(check_readback(ID, ID, [0xB0, 0x53, 0x04, 0x00]), Err(PanelFault::InversionOff));
+    }
+
+    #[test]
+    fn test_monitor_needs_repeated_failures() {
+        let t0 = Instant::now();
+        let mut monitor = HealthMonitor::new(t0);
+        assert!(!monitor.due(t0 + CHECK_INTERVAL / 2));
+        assert!(monitor.due(t0 + CHECK_INTERVAL));
+        assert!(!monitor.due(t0 + CHECK_INTERVAL));
+
+        assert!(!monitor.record(Err(PanelFault::SleepMode)));
+        // A good read in between resets the count
+        assert!(!monitor.record(Ok(())));
+        assert!(!monitor.record(Err(PanelFault::SleepMode)));
+        assert!(monitor.record(Err(PanelFault::DisplayOff)));
+        // Starts over after asking for a reinit
+        assert!(!monitor.record(Err(PanelFault::DisplayOff)));
+    }
+}
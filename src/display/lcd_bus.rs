@@ -1,7 +1,9 @@
-use anyhow::Result;
+use super::error::DisplayError;
 use esp_idf_hal::gpio::{AnyIOPin, PinDriver, Output};
 use esp_idf_hal::delay::FreeRtos;  // For delay_ms
 
+type Result<T> = core::result::Result<T, DisplayError>;
+
 /// Low-level 8-bit parallel LCD bus driver for ST7789
 pub struct LcdBus {
     data_pins: [PinDriver<'static, AnyIOPin, Output>; 8],
@@ -229,6 +231,60 @@ impl LcdBus {
         Ok(())
     }
 
+    /// Read the three panel ID bytes (RDDID), strobing `rd` (owned by DisplayManager).
+    /// Data pins are switched to pulled-down inputs for the read, so a missing
+    /// panel reads as all zeros.
+    pub fn read_id(&mut self, rd: &mut PinDriver<'static, AnyIOPin, Output>) -> Result<[u8; 3]> {
+        const CMD_RDDID: u8 = 0x04;
+        let mut id = [0u8; 3];
+        self.read_register(rd, CMD_RDDID, &mut id)?;
+        Ok(id)
+    }
+
+    /// Read the four display status bytes (RDDST), D31..D0
+    pub fn read_status(&mut self, rd: &mut PinDriver<'static, AnyIOPin, Output>) -> Result<[u8; 4]> {
+        const CMD_RDDST: u8 = 0x09;
+        let mut status = [0u8; 4];
+        self.read_register(rd, CMD_RDDST, &mut status)?;
+        Ok(status)
+    }
+
+    /// Send a read command and fill `out` with the bytes the panel returns
+    fn read_register(&mut self, rd: &mut PinDriver<'static, AnyIOPin, Output>, cmd: u8, out: &mut [u8]) -> Result<()> {
+        use esp_idf_sys::*;
+        self.write_command(cmd)?;
+        self.dc.set_high()?;
+
+        let gpios: Vec<i32> = self.data_pins.iter().map(|p| p.pin()).collect();
+        unsafe {
+            for &gpio in &gpios {
+                gpio_set_direction(gpio, gpio_mode_t_GPIO_MODE_INPUT);
+                gpio_set_pull_mode(gpio, gpio_pull_mode_t_GPIO_PULLDOWN_ONLY);
+            }
+        }
+
+        // First read after a read command is a dummy cycle
+        for i in 0..=out.len() {
+            rd.set_low()?;
+            unsafe { esp_rom_delay_us(1); }
+            let byte = gpios.iter().enumerate()
+                .fold(0u8, |acc, (bit, &gpio)| acc | ((unsafe { gpio_get_level(gpio) } as u8 & 1) << bit));
+            rd.set_high()?;
+            unsafe { esp_rom_delay_us(1); }
+            if i > 0 {
+                out[i - 1] = byte;
+            }
+        }
+
+        unsafe {
+            for &gpio in &gpios {
+                gpio_set_pull_mode(gpio, gpio_pull_mode_t_GPIO_FLOATING);
+                gpio_set_direction(gpio, gpio_mode_t_GPIO_MODE_OUTPUT);
+            }
+        }
+        Ok(())
+    }
+
     // /// Begin a data write sequence (caller must call end_write when done)
     // pub fn begin_write(&mut self) -> Result<()> {
     //     // CS already low from init
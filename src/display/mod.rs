@@ -1,18 +1,62 @@
 pub mod colors;
+pub mod error;
 pub mod font5x7;
+#[cfg(target_os = "espidf")]
 pub mod lcd_bus;
 pub mod dirty_rect_manager; // Enhanced dirty rectangle management
+pub mod burn_in;
+pub mod health;
+pub mod draw_list;
+pub mod benchmark;
+pub mod pacing;
+// Host builds render into an in-memory framebuffer instead of the LCD
+#[cfg(not(target_os = "espidf"))]
+pub mod framebuffer;
+#[cfg(not(target_os = "espidf"))]
+pub use framebuffer::FrameBuffer as DisplayManager;
 
 // Color type not used - colors are defined as u16 constants
 
-use anyhow::Result;
+#[cfg(target_os = "espidf")]
+use self::error::DisplayError;
+#[cfg(target_os = "espidf")]
+type Result<T> = core::result::Result<T, DisplayError>;
+#[cfg(target_os = "espidf")]
 use self::font5x7::{FONT_WIDTH, FONT_HEIGHT, get_char_data};
+#[cfg(target_os = "espidf")]
 use self::lcd_bus::LcdBus;
 // use self::perf_metrics::DisplayMetrics;
+#[cfg(target_os = "espidf")]
 use self::dirty_rect_manager::DirtyRectManager;
+#[cfg(target_os = "espidf")]
 use esp_idf_hal::gpio::{AnyIOPin, PinDriver, Output};
+#[cfg(target_os = "espidf")]
 use esp_idf_hal::delay::FreeRtos;
+#[cfg(target_os = "espidf")]
 use std::time::Instant;
+#[cfg(target_os = "espidf")]
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+#[cfg(target_os = "espidf")]
+use self::health::HealthMonitor;
+
+/// Panel initialised and responding; false while running headless
+#[cfg(target_os = "espidf")]
+static DISPLAY_OK: AtomicBool = AtomicBool::new(false);
+
+/// For /health and metrics, which don't own the DisplayManager
+#[cfg(target_os = "espidf")]
+pub fn display_ok() -> bool {
+    DISPLAY_OK.load(Ordering::Relaxed)
+}
+
+/// Times the health watchdog re-initialised the panel since boot
+#[cfg(target_os = "espidf")]
+static DISPLAY_REINITS: AtomicU32 = AtomicU32::new(0);
+
+#[cfg(target_os = "espidf")]
+pub fn display_reinits() -> u32 {
+    DISPLAY_REINITS.load(Ordering::Relaxed)
+}
 
 
 // Display boundaries - Discovered values from Arduino testing
@@ -27,6 +71,51 @@ const CONTROLLER_HEIGHT: u16 = 320;
 
 // Controller dimensions removed - not used
 
+// Backlight PWM (the buzzer uses LEDC timer/channel 1)
+#[cfg(target_os = "espidf")]
+const BACKLIGHT_LEDC_TIMER: u32 = esp_idf_sys::ledc_timer_t_LEDC_TIMER_2;
+#[cfg(target_os = "espidf")]
+const BACKLIGHT_LEDC_CHANNEL: u32 = esp_idf_sys::ledc_channel_t_LEDC_CHANNEL_2;
+#[cfg(target_os = "espidf")]
+const BACKLIGHT_LEDC_MODE: u32 = esp_idf_sys::ledc_mode_t_LEDC_LOW_SPEED_MODE;
+#[cfg(target_os = "espidf")]
+const BACKLIGHT_RESOLUTION_BITS: u32 = 10;
+
+/// Route `gpio` to an LEDC channel at 0% duty, with the hardware fader installed
+#[cfg(target_os = "espidf")]
+fn init_backlight_pwm(gpio: i32) -> Result<()> {
+    use esp_idf_sys::*;
+    let timer = ledc_timer_config_t {
+        speed_mode: BACKLIGHT_LEDC_MODE,
+        duty_resolution: ledc_timer_bit_t_LEDC_TIMER_10_BIT,
+        timer_num: BACKLIGHT_LEDC_TIMER,
+        freq_hz: 2000,
+        ..Default::default()
+    };
+    let result = unsafe { ledc_timer_config(&timer) };
+    if result != ESP_OK {
+        return Err(DisplayError::Backlight("timer config", result));
+    }
+    let channel = ledc_channel_config_t {
+        gpio_num: gpio,
+        speed_mode: BACKLIGHT_LEDC_MODE,
+        channel: BACKLIGHT_LEDC_CHANNEL,
+        timer_sel: BACKLIGHT_LEDC_TIMER,
+        duty: 0,
+        ..Default::default()
+    };
+    let result = unsafe { ledc_channel_config(&channel) };
+    if result != ESP_OK {
+        return Err(DisplayError::Backlight("channel config", result));
+    }
+    // Also makes the duty calls below thread-safe
+    let result = unsafe { ledc_fade_func_install(0) };
+    if result != ESP_OK && result != ESP_ERR_INVALID_STATE {
+        return Err(DisplayError::Backlight("fade install", result));
+    }
+    Ok(())
+}
+
 // Dirty rectangle tracking for optimized rendering
 #[derive(Debug, Clone, Copy)]
 pub struct DirtyRect {
@@ -76,20 +165,34 @@ const CMD_VDVS: u8 = 0xC4;
 const CMD_FRCTRL2: u8 = 0xC6;
 const CMD_PWRCTRL1: u8 = 0xD0;
 
-#[cfg(not(feature = "esp_lcd_driver"))]
+#[cfg(all(target_os = "espidf", not(feature = "esp_lcd_driver")))]
 pub struct DisplayManager {
     lcd_bus: LcdBus,
     backlight_pin: Option<PinDriver<'static, AnyIOPin, Output>>, // Keep backlight alive
     lcd_power_pin: Option<PinDriver<'static, AnyIOPin, Output>>, // Keep LCD power alive
-    _rd_pin: Option<PinDriver<'static, AnyIOPin, Output>>, // Keep RD pin high
+    rd_pin: PinDriver<'static, AnyIOPin, Output>, // Kept high except for ID reads
     width: u16,
     height: u16,
     last_activity: Instant,
     dirty_rect_manager: DirtyRectManager,
+    ready: bool,
+    // Burn-in mitigation: whole-UI offset applied in set_window
+    pixel_shift: (i8, i8),
+    // Screen transition adjustments, see set_transform
+    offset_x: i16,
+    fade: u8,
+    // Accessibility mode, see set_high_contrast
+    high_contrast: bool,
+    // RDDID read at the last successful init, and the watchdog comparing against it
+    panel_id: [u8; 3],
+    health: HealthMonitor,
+    // LEDC drives the backlight pin (else it is switched), last level set
+    backlight_pwm: bool,
+    backlight_level: u8,
     // metrics: DisplayMetrics, // Performance tracking
 }
 
-#[cfg(not(feature = "esp_lcd_driver"))]
+#[cfg(all(target_os = "espidf", not(feature = "esp_lcd_driver")))]
 impl DisplayManager {
     pub fn new(
         d0: impl Into<AnyIOPin> + 'static,
@@ -108,7 +211,6 @@ impl DisplayManager {
         lcd_power: impl Into<AnyIOPin> + 'static,
         rd: impl Into<AnyIOPin> + 'static,
     ) -> Result<Self> {
-        // For now, use simple GPIO for backlight
         use esp_idf_hal::gpio::PinDriver;
         
         // Set up LCD power pin (GPIO 15) FIRST - CRITICAL: Must keep alive!
@@ -121,29 +223,89 @@ impl DisplayManager {
         rd_pin.set_high()?;
         log::info!("RD pin set high and will be kept alive");
         
-        // Set up backlight
+        // Backlight: LEDC PWM on the pin, dark until fade_backlight_in (plain
+        // on/off if LEDC setup fails)
         let mut backlight_pin = PinDriver::output(backlight.into())?;
-        log::info!("About to set backlight HIGH during initialization");
-        backlight_pin.set_high()?;
-        log::info!("Backlight enabled (GPIO high) - display should be visible now");
-        
+        let backlight_pwm = match init_backlight_pwm(backlight_pin.pin()) {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("Backlight PWM unavailable, using on/off: {:?}", e);
+                backlight_pin.set_high()?;
+                false
+            }
+        };
         
         let mut display = Self {
             lcd_bus: LcdBus::new(d0, d1, d2, d3, d4, d5, d6, d7, wr, dc, cs, rst)?,
             backlight_pin: Some(backlight_pin),
             lcd_power_pin: Some(lcd_power_pin),
-            _rd_pin: Some(rd_pin),
+            rd_pin,
             width: DISPLAY_WIDTH,
             height: DISPLAY_HEIGHT,
             last_activity: Instant::now(),
             dirty_rect_manager: DirtyRectManager::new(),
+            ready: false,
+            pixel_shift: (0, 0),
+            offset_x: 0,
+            fade: 255,
+            high_contrast: false,
+            panel_id: [0; 3],
+            health: HealthMonitor::new(Instant::now()),
+            backlight_pwm,
+            backlight_level: 0,
             // metrics: DisplayMetrics::new(),
         };
         
-        display.init()?;
+        // A missing or dead panel shouldn't take WiFi, the web server and OTA down
+        // with it: keep the pins and run headless until retry_init succeeds
+        if let Err(e) = display.init() {
+            log::error!("Display init failed, running headless: {:?}", e);
+        }
         Ok(display)
     }
 
+    /// False while running headless; callers skip rendering
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+
+    /// Try to bring the panel up again after a failed init
+    pub fn retry_init(&mut self) -> bool {
+        match self.init() {
+            Ok(()) => log::info!("Display recovered, leaving headless mode"),
+            Err(e) => log::debug!("Display still unavailable: {:?}", e),
+        }
+        self.ready
+    }
+
+    /// Periodic panel readback (see health.rs). Re-runs init when the panel
+    /// lost its configuration and returns true: the UI must be redrawn in full.
+    pub fn check_health(&mut self) -> Result<bool> {
+        if !self.ready || !self.health.due(Instant::now()) {
+            return Ok(false);
+        }
+        let id = self.lcd_bus.read_id(&mut self.rd_pin)?;
+        let status = self.lcd_bus.read_status(&mut self.rd_pin)?;
+        let result = health::check_readback(id, self.panel_id, status);
+        if let Err(fault) = result {
+            log::warn!("Display readback failed ({}): ID {:02X?}, status {:02X?}", fault.as_str(), id, status);
+        }
+        if !self.health.record(result) {
+            return Ok(false);
+        }
+
+        log::error!("Display lost its configuration - re-initialising");
+        DISPLAY_REINITS.fetch_add(1, Ordering::Relaxed);
+        self.ready = false;
+        if let Err(e) = self.init() {
+            // Headless until retry_init brings it back
+            log::error!("Display re-init failed: {:?}", e);
+            DISPLAY_OK.store(false, Ordering::Relaxed);
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
     fn init(&mut self) -> Result<()> {
         log::info!("Initializing ST7789 display (LilyGO T-Display-S3)...");
 
@@ -169,9 +331,18 @@ impl DisplayManager {
         self.lcd_bus.write_command(CMD_SLPOUT)?;
         FreeRtos::delay_ms(120);
 
+        // The bus is write-only GPIO, so a disconnected panel would otherwise
+        // look fine; it reads back as all zeros
+        let id = self.lcd_bus.read_id(&mut self.rd_pin)?;
+        if id == [0, 0, 0] {
+            return Err(DisplayError::PanelNotResponding);
+        }
+        log::info!("Panel ID: {:02X} {:02X} {:02X}", id[0], id[1], id[2]);
+        self.panel_id = id;
+
         // Memory access control - matching Arduino implementation
         self.lcd_bus.write_command(CMD_MADCTL)?;
-        self.lcd_bus.write_data(0x60)?; // Same as Arduino - landscape mode
+        self.lcd_bus.write_data(health::EXPECTED_MADCTL)?; // Same as Arduino - landscape mode
 
         // Pixel format - 16-bit RGB565
         self.lcd_bus.write_command(CMD_COLMOD)?;
@@ -230,6 +401,8 @@ impl DisplayManager {
         self.clear(colors::BLACK)?;
         
 
+        self.ready = true;
+        DISPLAY_OK.store(true, Ordering::Relaxed);
         log::info!("Display initialized successfully");
         Ok(())
     }
@@ -276,12 +449,14 @@ impl DisplayManager {
 
 
     fn set_window(&mut self, x0: u16, y0: u16, x1: u16, y1: u16) -> Result<()> {
-        // Apply display boundaries offsets
-        let x0_offset = x0 + DISPLAY_X_START;
-        let x1_offset = x1 + DISPLAY_X_START;
-        let y0_offset = y0 + DISPLAY_Y_START;
-        let y1_offset = y1 + DISPLAY_Y_START;
-        
+        // Apply display boundaries offsets plus the burn-in shift
+        let x_start = DISPLAY_X_START.saturating_add_signed(self.pixel_shift.0 as i16);
+        let y_start = DISPLAY_Y_START.saturating_add_signed(self.pixel_shift.1 as i16);
+        self.set_raw_window(x0 + x_start, y0 + y_start, x1 + x_start, y1 + y_start)
+    }
+
+    /// Address window in controller coordinates
+    fn set_raw_window(&mut self, x0_offset: u16, y0_offset: u16, x1_offset: u16, y1_offset: u16) -> Result<()> {
         // Column address set
         self.lcd_bus.write_command(CMD_CASET)?;
         self.lcd_bus.write_data_16(x0_offset)?;
@@ -296,7 +471,58 @@ impl DisplayManager {
         Ok(())
     }
 
+    /// Move all subsequent drawing by (dx, dy) pixels (burn-in mitigation).
+    /// On a change the panel is blanked, including the margins the old offset
+    /// may have touched, and true is returned: the whole UI must be redrawn.
+    pub fn set_pixel_shift(&mut self, dx: i8, dy: i8) -> Result<bool> {
+        let max = burn_in::MAX_SHIFT as u16;
+        let shift = (dx.clamp(-burn_in::MAX_SHIFT, burn_in::MAX_SHIFT), dy.clamp(-burn_in::MAX_SHIFT, burn_in::MAX_SHIFT));
+        if shift == self.pixel_shift {
+            return Ok(false);
+        }
+        self.pixel_shift = shift;
+        self.set_raw_window(
+            DISPLAY_X_START - max,
+            DISPLAY_Y_START - max,
+            DISPLAY_X_START + self.width - 1 + max,
+            DISPLAY_Y_START + self.height - 1 + max,
+        )?;
+        self.lcd_bus.write_command(CMD_RAMWR)?;
+        let total_pixels = (self.width + 2 * max) as u32 * (self.height + 2 * max) as u32;
+        self.lcd_bus.write_pixels(colors::BLACK, total_pixels)?;
+        self.dirty_rect_manager.add_rect(0, 0, self.width, self.height);
+        Ok(true)
+    }
+
+    /// Shift everything drawn after this by `offset_x` pixels and scale its
+    /// colours by `fade` (255 = unchanged); clear() fills the whole panel
+    /// regardless of the shift, images are not faded. Used for screen transitions.
+    pub fn set_transform(&mut self, offset_x: i16, fade: u8) {
+        self.offset_x = offset_x;
+        self.fade = fade;
+    }
+
+    /// Draw everything in the high-contrast palette (`colors::high_contrast`)
+    pub fn set_high_contrast(&mut self, enabled: bool) {
+        self.high_contrast = enabled;
+    }
+
+    /// The colour actually written for `color` under the current palette and fade
+    fn ink(&self, color: u16) -> u16 {
+        let color = if self.high_contrast { colors::high_contrast(color) } else { color };
+        colors::fade(color, self.fade)
+    }
+
+    /// Panel columns for logical columns `x0..x1` under the current shift,
+    /// or None when they are all shifted out of view
+    fn shift_span(&self, x0: u16, x1: u16) -> Option<(u16, u16)> {
+        let x0 = (x0 as i32 + self.offset_x as i32).max(0);
+        let x1 = (x1 as i32 + self.offset_x as i32).min(self.width as i32);
+        (x0 < x1).then_some((x0 as u16, x1 as u16))
+    }
+
     pub fn clear(&mut self, color: u16) -> Result<()> {
+        let color = self.ink(color);
         // Direct clear - original implementation
         self.set_window(0, 0, self.width - 1, self.height - 1)?;
         
@@ -342,12 +568,15 @@ impl DisplayManager {
         if x >= self.width || y >= self.height {
             return Ok(());
         }
+        let Some((x, _)) = self.shift_span(x, x + 1) else {
+            return Ok(());
+        };
 
         // Direct pixel write - original implementation
         self.set_window(x, y, x, y)?;
         // CRITICAL: Must send RAMWR before pixel data
         self.lcd_bus.write_command(CMD_RAMWR)?;
-        self.lcd_bus.write_data_16(color)?;
+        self.lcd_bus.write_data_16(self.ink(color))?;
         
         // Track dirty region
         self.dirty_rect_manager.add_rect(x, y, 1, 1);
@@ -360,7 +589,12 @@ impl DisplayManager {
             return Ok(());
         }
 
-        let x1 = (x + w - 1).min(self.width - 1);
+        let Some((x, x_end)) = self.shift_span(x, x.saturating_add(w).min(self.width)) else {
+            return Ok(());
+        };
+        let color = self.ink(color);
+
+        let x1 = x_end - 1;
         let y1 = (y + h - 1).min(self.height - 1);
         let actual_width = x1 - x + 1;
         let actual_height = y1 - y + 1;
@@ -381,6 +615,24 @@ impl DisplayManager {
         Ok(())
     }
 
+    /// Blit a `w` x `h` image of big-endian RGB565 pixels (2 bytes each, row
+    /// by row); images reaching past the screen edge are skipped
+    pub fn draw_rgb565(&mut self, x: u16, y: u16, w: u16, h: u16, data: &[u8]) -> Result<()> {
+        if w == 0 || h == 0 || x + w > self.width || y + h > self.height || data.len() != w as usize * h as usize * 2 {
+            return Ok(());
+        }
+        let x = match self.shift_span(x, x + w) {
+            Some((x, x_end)) if x_end - x == w => x,
+            _ => return Ok(()),
+        };
+        self.set_window(x, y, x + w - 1, y + h - 1)?;
+        // CRITICAL: Must send RAMWR before pixel data
+        self.lcd_bus.write_command(CMD_RAMWR)?;
+        self.lcd_bus.write_data_bytes(data)?;
+        self.dirty_rect_manager.add_rect(x, y, w, h);
+        Ok(())
+    }
+
     pub fn draw_line(&mut self, x0: u16, y0: u16, x1: u16, y1: u16, color: u16) -> Result<()> {
         // Calculate bounding box for the line
         let min_x = x0.min(x1);
@@ -432,19 +684,22 @@ impl DisplayManager {
         Ok(())
     }
 
-    // set_brightness removed - PWM not implemented yet
-    
     pub fn reset_activity_timer(&mut self) {
         self.last_activity = Instant::now();
     }
     
-    pub fn update_auto_dim(&mut self, should_display_on: bool) -> Result<()> {
-        // Control backlight based on power manager state
-        if let Some(ref mut pin) = self.backlight_pin {
-            if should_display_on {
-                pin.set_high()?;
-            } else {
-                pin.set_low()?;  // Turn off backlight in sleep mode
+    /// Backlight level 0-255 (PowerManager::update_backlight does the fading)
+    pub fn set_backlight(&mut self, level: u8) -> Result<()> {
+        if level != self.backlight_level {
+            self.backlight_level = level;
+            if self.backlight_pwm {
+                let duty = crate::power::backlight::pwm_duty(level, BACKLIGHT_RESOLUTION_BITS);
+                let result = unsafe { esp_idf_sys::ledc_set_duty_and_update(BACKLIGHT_LEDC_MODE, BACKLIGHT_LEDC_CHANNEL, duty, 0) };
+                if result != esp_idf_sys::ESP_OK {
+                    return Err(DisplayError::Backlight("duty update", result));
+                }
+            } else if let Some(ref mut pin) = self.backlight_pin {
+                if level > 0 { pin.set_high()? } else { pin.set_low()? }
             }
         }
         
@@ -456,6 +711,24 @@ impl DisplayManager {
         Ok(())
     }
     
+    /// Boot fade-in to full brightness. The boot sequence blocks, so this uses
+    /// the LEDC hardware fader (linear) instead of the main loop's ramp.
+    pub fn fade_backlight_in(&mut self, duration: std::time::Duration) -> Result<()> {
+        if !self.backlight_pwm {
+            return self.set_backlight(255);
+        }
+        let duty = crate::power::backlight::pwm_duty(255, BACKLIGHT_RESOLUTION_BITS);
+        let result = unsafe {
+            esp_idf_sys::ledc_set_fade_time_and_start(BACKLIGHT_LEDC_MODE, BACKLIGHT_LEDC_CHANNEL, duty,
+                duration.as_millis().max(1) as i32, esp_idf_sys::ledc_fade_mode_t_LEDC_FADE_NO_WAIT)
+        };
+        if result != esp_idf_sys::ESP_OK {
+            return Err(DisplayError::Backlight("fade", result));
+        }
+        self.backlight_level = 255;
+        Ok(())
+    }
+    
     // mark_dirty and clear_dirty_rects removed - dirty rect tracking not implemented
 
     
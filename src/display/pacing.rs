@@ -0,0 +1,158 @@
+// Main-loop frame pacing. The target rate and how the remainder of a frame
+// is spent come from the config instead of compile-time constants. The
+// parallel bus tops out around 10 FPS, so the frame period never drops
+// below the measured (smoothed) flush time: asking for 60 FPS on a panel
+// that takes 90 ms to flush paces at the flush rate instead of spinning.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// What the panel manages with the parallel GPIO bus
+pub const DEFAULT_TARGET_FPS: u8 = 10;
+pub const MAX_TARGET_FPS: u8 = 60;
+/// Left to busy-wait by the hybrid strategy; sleeps have tick (1 ms) granularity
+pub const SPIN_MARGIN: Duration = Duration::from_millis(2);
+/// Weight of a new flush time in the running average, as 1/N
+const FLUSH_SMOOTHING: u32 = 8;
+
+/// How the main loop waits out the rest of a frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PacingStrategy {
+    /// Yield the whole remainder to other tasks (TCP/IP, HTTPD)
+    #[default]
+    Sleep,
+    /// Sleep all but SPIN_MARGIN, then busy-wait for an exact frame period
+    Hybrid,
+}
+
+/// Time to spend before the next frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameWait {
+    pub sleep_ms: u32,
+    pub spin: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct FramePacer {
+    /// 0 = uncapped
+    target_fps: u8,
+    strategy: PacingStrategy,
+    avg_flush: Duration,
+}
+
+impl FramePacer {
+    pub fn new(target_fps: u8, strategy: PacingStrategy) -> Self {
+        Self { target_fps: target_fps.min(MAX_TARGET_FPS), strategy, avg_flush: Duration::ZERO }
+    }
+
+    pub fn configure(&mut self, target_fps: u8, strategy: PacingStrategy) {
+        self.target_fps = target_fps.min(MAX_TARGET_FPS);
+        self.strategy = strategy;
+    }
+
+    pub fn target_fps(&self) -> u8 {
+        self.target_fps
+    }
+
+    /// Fold a measured flush into the running average
+    pub fn record_flush(&mut self, flush: Duration) {
+        self.avg_flush = if self.avg_flush.is_zero() {
+            flush
+        } else {
+            (self.avg_flush * (FLUSH_SMOOTHING - 1) + flush) / FLUSH_SMOOTHING
+        };
+    }
+
+    pub fn average_flush(&self) -> Duration {
+        self.avg_flush
+    }
+
+    /// Frame period: the target's, or the average flush if that is longer.
+    /// None when uncapped.
+    pub fn frame_period(&self) -> Option<Duration> {
+        (self.target_fps > 0).then(|| (Duration::from_secs(1) / self.target_fps as u32).max(self.avg_flush))
+    }
+
+    /// Highest rate the loop will run at, for the FPS gauges
+    pub fn effective_fps(&self) -> f32 {
+        self.frame_period().map_or(0.0, |p| 1.0 / p.as_secs_f32())
+    }
+
+    /// How to spend the rest of a frame that has taken `frame_time` so far
+    pub fn wait_for(&self, frame_time: Duration) -> FrameWait {
+        let Some(remaining) = self.frame_period().and_then(|p| p.checked_sub(frame_time)) else {
+            return FrameWait::default();
+        };
+        match self.strategy {
+            PacingStrategy::Sleep => FrameWait { sleep_ms: remaining.as_millis() as u32, spin: Duration::ZERO },
+            PacingStrategy::Hybrid => {
+                let sleep = remaining.saturating_sub(SPIN_MARGIN);
+                let sleep_ms = sleep.as_millis() as u32;
+                FrameWait { sleep_ms, spin: remaining - Duration::from_millis(sleep_ms as u64) }
+            }
+        }
+    }
+}
+
+impl Default for FramePacer {
+    fn default() -> Self {
+        Self::new(DEFAULT_TARGET_FPS, PacingStrategy::default())
+    }
+}
+
+/// Sleep, then busy-wait, as planned by `FramePacer::wait_for`
+#[cfg(target_os = "espidf")]
+pub fn wait(plan: FrameWait) {
+    let start = std::time::Instant::now();
+    if plan.sleep_ms > 0 {
+        esp_idf_hal::delay::FreeRtos::delay_ms(plan.sleep_ms);
+    }
+    let deadline = Duration::from_millis(plan.sleep_ms as u64) + plan.spin;
+    while start.elapsed() < deadline {
+        core::hint::spin_loop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sleep_pacing() {
+        let pacer = FramePacer::new(10, PacingStrategy::Sleep);
+        assert_eq!(pacer.wait_for(Duration::from_millis(30)), FrameWait { sleep_ms: 70, spin: Duration::ZERO });
+        // An overrunning frame starts the next one straight away
+        assert_eq!(pacer.wait_for(Duration::from_millis(120)), FrameWait::default());
+
+        let uncapped = FramePacer::new(0, PacingStrategy::Sleep);
+        assert_eq!(uncapped.frame_period(), None);
+        assert_eq!(uncapped.wait_for(Duration::ZERO), FrameWait::default());
+    }
+
+    #[test]
+    fn test_hybrid_pacing() {
+        let pacer = FramePacer::new(60, PacingStrategy::Hybrid);
+        let wait = pacer.wait_for(Duration::from_millis(5));
+        // 16.67 ms period: 9 ms asleep, the last ~2.67 ms spun
+        assert_eq!(wait.sleep_ms, 9);
+        assert_eq!(Duration::from_millis(9) + wait.spin, Duration::from_secs(1) / 60 - Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_period_follows_flush_time() {
+        let mut pacer = FramePacer::new(60, PacingStrategy::Sleep);
+        pacer.record_flush(Duration::from_millis(90));
+        assert_eq!(pacer.frame_period(), Some(Duration::from_millis(90)));
+        assert!((pacer.effective_fps() - 11.1).abs() < 0.1);
+
+        // Averaged, so one fast flush barely moves it
+        pacer.record_flush(Duration::from_millis(10));
+        assert_eq!(pacer.average_flush(), Duration::from_millis(80));
+
+        pacer.configure(5, PacingStrategy::Sleep);
+        assert_eq!(pacer.frame_period(), Some(Duration::from_millis(200)));
+        pacer.configure(200, PacingStrategy::Sleep);
+        assert_eq!(pacer.target_fps(), MAX_TARGET_FPS);
+    }
+}
@@ -0,0 +1,9 @@
+// Common ground for the typed errors of the display, network, OTA and config
+// layers (display::error, network::error, ota::error, config::error). Callers
+// match on those enums, e.g. to retry only transient network failures; when
+// one ends a web request, error_handler sends the status it maps to here.
+
+/// HTTP status for a failure that ends a web request
+pub trait HttpStatus: std::error::Error {
+    fn http_status(&self) -> u16;
+}
@@ -0,0 +1,296 @@
+// Piezo buzzer on a configurable GPIO, driven with LEDC tones. Events (button
+// click, OTA complete, alert fired, timer done) map to short melodies; which
+// ones sound is set in Config and can be muted from the web UI.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+#[cfg(target_os = "espidf")]
+use {
+    anyhow::{anyhow, Result},
+    std::sync::Mutex,
+    crate::network::validators::is_pin_available,
+};
+
+#[cfg(target_os = "espidf")]
+const LEDC_TIMER: u32 = esp_idf_sys::ledc_timer_t_LEDC_TIMER_1;
+#[cfg(target_os = "espidf")]
+const LEDC_CHANNEL: u32 = esp_idf_sys::ledc_channel_t_LEDC_CHANNEL_1;
+#[cfg(target_os = "espidf")]
+const LEDC_MODE: u32 = esp_idf_sys::ledc_mode_t_LEDC_LOW_SPEED_MODE;
+/// 50% duty at 10-bit resolution
+#[cfg(target_os = "espidf")]
+const TONE_DUTY: u32 = 512;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoundEvent {
+    ButtonClick,
+    OtaComplete,
+    AlertFired,
+    TimerDone,
+}
+
+impl SoundEvent {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "button_click" => Some(Self::ButtonClick),
+            "ota_complete" => Some(Self::OtaComplete),
+            "alert_fired" => Some(Self::AlertFired),
+            "timer_done" => Some(Self::TimerDone),
+            _ => None,
+        }
+    }
+
+    pub fn melody(self) -> &'static [Note] {
+        match self {
+            Self::ButtonClick => CLICK,
+            Self::OtaComplete => RISING_ARPEGGIO,
+            Self::AlertFired => TRIPLE_BEEP,
+            Self::TimerDone => CHIME,
+        }
+    }
+}
+
+const CLICK: &[Note] = &[Note::tone(4000, 15)];
+const RISING_ARPEGGIO: &[Note] = &[
+    Note::tone(1047, 90), Note::tone(1319, 90), Note::tone(1568, 90), Note::tone(2093, 200),
+];
+const TRIPLE_BEEP: &[Note] = &[
+    Note::tone(2800, 150), Note::rest(100), Note::tone(2800, 150), Note::rest(100), Note::tone(2800, 150),
+];
+const CHIME: &[Note] = &[
+    Note::tone(1568, 200), Note::rest(100), Note::tone(1568, 200), Note::rest(100), Note::tone(2093, 400),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Note {
+    /// 0 = silence
+    pub freq_hz: u32,
+    pub ms: u32,
+}
+
+impl Note {
+    const fn tone(freq_hz: u32, ms: u32) -> Self {
+        Self { freq_hz, ms }
+    }
+
+    const fn rest(ms: u32) -> Self {
+        Self { freq_hz: 0, ms }
+    }
+}
+
+/// Which events make a sound
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundMap {
+    pub button_click: bool,
+    pub ota_complete: bool,
+    pub alert_fired: bool,
+    pub timer_done: bool,
+}
+
+impl Default for SoundMap {
+    fn default() -> Self {
+        // Clicks on every button press get old quickly
+        Self { button_click: false, ota_complete: true, alert_fired: true, timer_done: true }
+    }
+}
+
+impl SoundMap {
+    pub fn enabled(&self, event: SoundEvent) -> bool {
+        match event {
+            SoundEvent::ButtonClick => self.button_click,
+            SoundEvent::OtaComplete => self.ota_complete,
+            SoundEvent::AlertFired => self.alert_fired,
+            SoundEvent::TimerDone => self.timer_done,
+        }
+    }
+}
+
+/// Steps through a melody without blocking
+#[derive(Debug, Default)]
+pub struct Sequencer {
+    notes: &'static [Note],
+    index: usize,
+    next_at: Option<Instant>,
+}
+
+impl Sequencer {
+    pub fn start(&mut self, notes: &'static [Note], now: Instant) {
+        self.notes = notes;
+        self.index = 0;
+        self.next_at = Some(now);
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.next_at.is_some()
+    }
+
+    /// Frequency to switch to (0 = silence) when the next note is due
+    pub fn poll(&mut self, now: Instant) -> Option<u32> {
+        let next_at = self.next_at?;
+        if now < next_at {
+            return None;
+        }
+        match self.notes.get(self.index) {
+            Some(note) => {
+                self.index += 1;
+                self.next_at = Some(now + Duration::from_millis(note.ms as u64));
+                Some(note.freq_hz)
+            }
+            None => {
+                self.next_at = None;
+                Some(0)
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
+struct Buzzer {
+    sequencer: Sequencer,
+    sounds: SoundMap,
+    muted: bool,
+}
+
+#[cfg(target_os = "espidf")]
+static BUZZER: Mutex<Option<Buzzer>> = Mutex::new(None);
+
+/// Set up the LEDC timer and channel for a piezo on `gpio`. Call once at startup.
+#[cfg(target_os = "espidf")]
+pub fn init(gpio: u8) -> Result<()> {
+    use esp_idf_sys::*;
+    if !is_pin_available(gpio) {
+        return Err(anyhow!("GPIO{} is reserved or invalid", gpio));
+    }
+
+    let timer = ledc_timer_config_t {
+        speed_mode: LEDC_MODE,
+        duty_resolution: ledc_timer_bit_t_LEDC_TIMER_10_BIT,
+        timer_num: LEDC_TIMER,
+        freq_hz: 2000,
+        ..Default::default()
+    };
+    let result = unsafe { ledc_timer_config(&timer) };
+    if result != ESP_OK {
+        return Err(anyhow!("LEDC timer config failed: {}", result));
+    }
+
+    let channel = ledc_channel_config_t {
+        gpio_num: gpio as i32,
+        speed_mode: LEDC_MODE,
+        channel: LEDC_CHANNEL,
+        timer_sel: LEDC_TIMER,
+        duty: 0,
+        ..Default::default()
+    };
+    let result = unsafe { ledc_channel_config(&channel) };
+    if result != ESP_OK {
+        return Err(anyhow!("LEDC channel config failed: {}", result));
+    }
+
+    if let Ok(mut slot) = BUZZER.lock() {
+        *slot = Some(Buzzer { sequencer: Sequencer::default(), sounds: SoundMap::default(), muted: false });
+    }
+    log::info!("Buzzer on GPIO{}", gpio);
+    Ok(())
+}
+
+/// Apply the event map and mute flag from Config
+#[cfg(target_os = "espidf")]
+pub fn configure(sounds: &SoundMap, muted: bool) {
+    if let Ok(mut slot) = BUZZER.lock() {
+        if let Some(buzzer) = slot.as_mut() {
+            buzzer.sounds = *sounds;
+            buzzer.muted = muted;
+        }
+    }
+}
+
+/// Play the sound for `event` if it is enabled; no-op without a buzzer
+#[cfg(target_os = "espidf")]
+pub(super) fn play(event: SoundEvent) {
+    start(event, false);
+}
+
+/// Play `event` even if it is disabled or muted (web UI test button)
+#[cfg(target_os = "espidf")]
+pub fn test(event: SoundEvent) {
+    start(event, true);
+}
+
+#[cfg(target_os = "espidf")]
+fn start(event: SoundEvent, force: bool) {
+    let Ok(mut slot) = BUZZER.lock() else { return };
+    let Some(buzzer) = slot.as_mut() else { return };
+    if !force && (buzzer.muted || !buzzer.sounds.enabled(event)) {
+        return;
+    }
+    // Don't cut a longer melody short for a click
+    if event == SoundEvent::ButtonClick && buzzer.sequencer.is_playing() {
+        return;
+    }
+    buzzer.sequencer.start(event.melody(), Instant::now());
+}
+
+/// Advance the current melody; call from the main loop
+#[cfg(target_os = "espidf")]
+pub fn update() {
+    let Ok(mut slot) = BUZZER.lock() else { return };
+    let Some(buzzer) = slot.as_mut() else { return };
+    if let Some(freq_hz) = buzzer.sequencer.poll(Instant::now()) {
+        set_tone(freq_hz);
+    }
+}
+
+#[cfg(target_os = "espidf")]
+fn set_tone(freq_hz: u32) {
+    use esp_idf_sys::*;
+    unsafe {
+        if freq_hz > 0 {
+            ledc_set_freq(LEDC_MODE, LEDC_TIMER, freq_hz);
+            ledc_set_duty(LEDC_MODE, LEDC_CHANNEL, TONE_DUTY);
+        } else {
+            ledc_set_duty(LEDC_MODE, LEDC_CHANNEL, 0);
+        }
+        ledc_update_duty(LEDC_MODE, LEDC_CHANNEL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequencer_plays_melody_then_stops() {
+        let t0 = Instant::now();
+        let mut seq = Sequencer::default();
+        assert_eq!(seq.poll(t0), None);
+
+        seq.start(SoundEvent::AlertFired.melody(), t0);
+        assert_eq!(seq.poll(t0), Some(2800));
+        assert_eq!(seq.poll(t0 + Duration::from_millis(100)), None);
+        assert_eq!(seq.poll(t0 + Duration::from_millis(150)), Some(0));
+
+        let mut now = t0 + Duration::from_millis(150);
+        let mut changes = 0;
+        while seq.is_playing() {
+            now += Duration::from_millis(10);
+            if seq.poll(now).is_some() {
+                changes += 1;
+            }
+        }
+        // Remaining three notes plus the final silence
+        assert_eq!(changes, 4);
+    }
+
+    #[test]
+    fn test_sound_map_defaults_and_names() {
+        let map: SoundMap = serde_json::from_str(r#"{"button_click":true}"#).unwrap();
+        assert!(map.enabled(SoundEvent::ButtonClick));
+        assert!(map.enabled(SoundEvent::TimerDone));
+        assert!(!SoundMap::default().enabled(SoundEvent::ButtonClick));
+        assert_eq!(SoundEvent::from_name("ota_complete"), Some(SoundEvent::OtaComplete));
+        assert_eq!(SoundEvent::from_name("beep"), None);
+    }
+}
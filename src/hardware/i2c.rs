@@ -0,0 +1,87 @@
+// I2C master buses for the optional sensors. Each controller is installed once
+// on the pins from Config; the INA219 power monitor has port 0 and the air
+// quality sensors port 1, so both can be fitted on separate pins.
+
+#[cfg(target_os = "espidf")]
+mod device {
+    use anyhow::{anyhow, Result};
+    use core::time::Duration;
+    use esp_idf_hal::delay::TickType;
+    use esp_idf_sys::*;
+    use crate::network::validators::is_pin_available;
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    /// An installed I2C controller
+    #[derive(Debug, Clone, Copy)]
+    pub struct Bus {
+        port: i2c_port_t,
+    }
+
+    impl Bus {
+        /// Install controller `port` on `sda`/`scl`. Call once per port.
+        pub fn install(port: i2c_port_t, sda: u8, scl: u8, clock_hz: u32) -> Result<Self> {
+            for gpio in [sda, scl] {
+                if !is_pin_available(gpio) {
+                    return Err(anyhow!("GPIO{} is reserved or invalid", gpio));
+                }
+            }
+            let mut config = i2c_config_t {
+                mode: i2c_mode_t_I2C_MODE_MASTER,
+                sda_io_num: sda as i32,
+                scl_io_num: scl as i32,
+                sda_pullup_en: true,
+                scl_pullup_en: true,
+                ..Default::default()
+            };
+            config.__bindgen_anon_1.master.clk_speed = clock_hz;
+            let ret = unsafe { i2c_param_config(port, &config) };
+            if ret != ESP_OK {
+                return Err(anyhow!("I2C{} config failed: {}", port, ret));
+            }
+            let ret = unsafe { i2c_driver_install(port, i2c_mode_t_I2C_MODE_MASTER, 0, 0, 0) };
+            if ret != ESP_OK {
+                return Err(anyhow!("I2C{} driver install failed: {}", port, ret));
+            }
+            Ok(Self { port })
+        }
+
+        /// Remove the driver, e.g. when nothing answered on the bus
+        pub fn uninstall(self) {
+            unsafe { i2c_driver_delete(self.port) };
+        }
+
+        pub fn write(&self, address: u8, bytes: &[u8]) -> Result<()> {
+            let ticks = TickType::from(TIMEOUT).ticks();
+            let ret = unsafe { i2c_master_write_to_device(self.port, address, bytes.as_ptr(), bytes.len(), ticks) };
+            if ret != ESP_OK {
+                return Err(anyhow!("I2C write to {:#04x} failed: {}", address, ret));
+            }
+            Ok(())
+        }
+
+        pub fn read(&self, address: u8, out: &mut [u8]) -> Result<()> {
+            let ticks = TickType::from(TIMEOUT).ticks();
+            let ret = unsafe { i2c_master_read_from_device(self.port, address, out.as_mut_ptr(), out.len(), ticks) };
+            if ret != ESP_OK {
+                return Err(anyhow!("I2C read from {:#04x} failed: {}", address, ret));
+            }
+            Ok(())
+        }
+
+        /// Write `bytes`, then read `out` in the same transaction (repeated start)
+        pub fn write_read(&self, address: u8, bytes: &[u8], out: &mut [u8]) -> Result<()> {
+            let ticks = TickType::from(TIMEOUT).ticks();
+            let ret = unsafe {
+                i2c_master_write_read_device(self.port, address, bytes.as_ptr(), bytes.len(), out.as_mut_ptr(), out.len(), ticks)
+            };
+            if ret != ESP_OK {
+                return Err(anyhow!("I2C read from {:#04x} failed: {}", address, ret));
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub use device::Bus;
@@ -0,0 +1,40 @@
+// Optional external peripherals wired to spare GPIOs. Device events go through
+// `notify`, which fans them out to the buzzer and the status LEDs.
+
+pub mod buzzer;
+pub mod i2c;
+pub mod power_monitor;
+pub mod status_led;
+
+use buzzer::SoundEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notification {
+    ButtonClick,
+    WifiState(bool),
+    OtaProgress(u8),
+    OtaComplete,
+    AlertFired,
+    TimerDone,
+}
+
+impl Notification {
+    pub fn sound(self) -> Option<SoundEvent> {
+        match self {
+            Self::ButtonClick => Some(SoundEvent::ButtonClick),
+            Self::OtaComplete => Some(SoundEvent::OtaComplete),
+            Self::AlertFired => Some(SoundEvent::AlertFired),
+            Self::TimerDone => Some(SoundEvent::TimerDone),
+            Self::WifiState(_) | Self::OtaProgress(_) => None,
+        }
+    }
+}
+
+/// Publish a device event to every fitted output; cheap when none are configured
+#[cfg(target_os = "espidf")]
+pub fn notify(notification: Notification) {
+    if let Some(event) = notification.sound() {
+        buzzer::play(event);
+    }
+    status_led::notify(notification);
+}
@@ -0,0 +1,234 @@
+// INA219 current/voltage monitor on I2C, wired in series with the board's
+// supply. Measures what the board actually draws, so dimming, sleep and
+// battery-profile changes can be checked in milliwatts instead of guessed.
+// Sampled once a second from the main loop; the latest reading, the average
+// since boot and the energy used since boot go to the Sensor screen,
+// /metrics and /api/system.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+#[cfg(target_os = "espidf")]
+use {
+    anyhow::{anyhow, Result},
+    super::i2c::Bus,
+};
+
+/// Default 7-bit address (A0 and A1 to GND)
+pub const DEFAULT_ADDRESS: u8 = 0x40;
+
+/// Shunt on common INA219 breakouts (R100)
+pub const DEFAULT_SHUNT_MILLIOHM: u16 = 100;
+
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Shunt voltage register LSB
+const SHUNT_LSB_UV: i32 = 10;
+/// Bus voltage register LSB (value in bits 15-3)
+const BUS_LSB_MV: u32 = 4;
+/// Bus voltage register: the last conversion overflowed
+const BUS_OVF: u16 = 0x0001;
+
+/// One conversion
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PowerReading {
+    pub bus_mv: u32,
+    pub current_ma: f32,
+    pub power_mw: f32,
+}
+
+/// Convert the shunt and bus voltage registers; None when the current was
+/// out of range for the shunt
+pub fn decode(shunt_reg: i16, bus_reg: u16, shunt_milliohm: u16) -> Option<PowerReading> {
+    if bus_reg & BUS_OVF != 0 || shunt_milliohm == 0 {
+        return None;
+    }
+    let bus_mv = (bus_reg >> 3) as u32 * BUS_LSB_MV;
+    // µV / mΩ = mA
+    let current_ma = (shunt_reg as i32 * SHUNT_LSB_UV) as f32 / shunt_milliohm as f32;
+    Some(PowerReading { bus_mv, current_ma, power_mw: bus_mv as f32 * current_ma / 1000.0 })
+}
+
+/// Integrates power readings into energy used
+#[derive(Debug, Clone, Default)]
+pub struct EnergyMeter {
+    first: Option<Instant>,
+    last: Option<(Instant, f32)>,
+    mwh: f64,
+}
+
+impl EnergyMeter {
+    /// Add a reading taken at `now`; the span since the previous one is
+    /// counted at the mean of both
+    pub fn record(&mut self, power_mw: f32, now: Instant) {
+        if let Some((at, previous)) = self.last {
+            let hours = now.saturating_duration_since(at).as_secs_f64() / 3600.0;
+            self.mwh += (previous + power_mw) as f64 / 2.0 * hours;
+        }
+        self.first.get_or_insert(now);
+        self.last = Some((now, power_mw));
+    }
+
+    /// Energy since the first reading
+    pub fn energy_mwh(&self) -> f64 {
+        self.mwh
+    }
+
+    /// Mean power since the first reading; the last reading until there are two
+    pub fn average_mw(&self) -> Option<f32> {
+        let (first, (last_at, last_mw)) = (self.first?, self.last?);
+        let hours = last_at.saturating_duration_since(first).as_secs_f64() / 3600.0;
+        if hours <= 0.0 {
+            return Some(last_mw);
+        }
+        Some((self.mwh / hours) as f32)
+    }
+}
+
+/// What the UI, /metrics and /api/system show
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PowerSnapshot {
+    #[serde(flatten)]
+    pub reading: PowerReading,
+    pub average_mw: f32,
+    pub energy_mwh: f64,
+}
+
+static LATEST: Mutex<Option<PowerSnapshot>> = Mutex::new(None);
+
+/// Latest reading; None without a monitor or before its first sample
+pub fn snapshot() -> Option<PowerSnapshot> {
+    LATEST.lock().ok().and_then(|latest| *latest)
+}
+
+#[cfg(target_os = "espidf")]
+mod device {
+    use super::*;
+    use esp_idf_sys::i2c_port_t;
+
+    const REG_CONFIG: u8 = 0x00;
+    const REG_SHUNT_VOLTAGE: u8 = 0x01;
+    const REG_BUS_VOLTAGE: u8 = 0x02;
+
+    /// 32 V bus range, ±320 mV shunt range, 12-bit conversions, continuous
+    const CONFIG_CONTINUOUS: u16 = 0x399F;
+
+    const I2C_PORT: i2c_port_t = 0;
+    const I2C_CLOCK_HZ: u32 = 400_000;
+
+    struct Ina219 {
+        bus: Bus,
+        address: u8,
+        shunt_milliohm: u16,
+        meter: EnergyMeter,
+        last_sample: Option<Instant>,
+    }
+
+    impl Ina219 {
+        fn write_register(&self, reg: u8, value: u16) -> Result<()> {
+            let [hi, lo] = value.to_be_bytes();
+            self.bus.write(self.address, &[reg, hi, lo])
+                .map_err(|e| anyhow!("INA219 write {:#04x}: {}", reg, e))
+        }
+
+        fn read_register(&self, reg: u8) -> Result<u16> {
+            let mut out = [0u8; 2];
+            self.bus.write_read(self.address, &[reg], &mut out)
+                .map_err(|e| anyhow!("INA219 read {:#04x}: {}", reg, e))?;
+            Ok(u16::from_be_bytes(out))
+        }
+
+        fn read(&self) -> Result<Option<PowerReading>> {
+            let shunt = self.read_register(REG_SHUNT_VOLTAGE)? as i16;
+            let bus = self.read_register(REG_BUS_VOLTAGE)?;
+            Ok(decode(shunt, bus, self.shunt_milliohm))
+        }
+    }
+
+    static MONITOR: Mutex<Option<Ina219>> = Mutex::new(None);
+
+    /// Install the I2C driver on `sda`/`scl` and configure an INA219 at
+    /// `address`. Call once at startup.
+    pub fn init(sda: u8, scl: u8, address: u8, shunt_milliohm: u16) -> Result<()> {
+        if shunt_milliohm == 0 {
+            return Err(anyhow!("Shunt resistance must be above 0"));
+        }
+        let bus = Bus::install(I2C_PORT, sda, scl, I2C_CLOCK_HZ)?;
+
+        let monitor = Ina219 { bus, address, shunt_milliohm, meter: EnergyMeter::default(), last_sample: None };
+        if let Err(e) = monitor.write_register(REG_CONFIG, CONFIG_CONTINUOUS) {
+            bus.uninstall();
+            return Err(e);
+        }
+        if let Ok(mut slot) = MONITOR.lock() {
+            *slot = Some(monitor);
+        }
+        log::info!("INA219 at {:#04x} on SDA GPIO{} / SCL GPIO{}, {} mΩ shunt", address, sda, scl, shunt_milliohm);
+        Ok(())
+    }
+
+    /// Take a reading when one is due; call from the main loop
+    pub fn update() {
+        let Ok(mut slot) = MONITOR.lock() else { return };
+        let Some(monitor) = slot.as_mut() else { return };
+        let now = Instant::now();
+        if monitor.last_sample.is_some_and(|at| now.duration_since(at) < SAMPLE_INTERVAL) {
+            return;
+        }
+        monitor.last_sample = Some(now);
+        match monitor.read() {
+            Ok(Some(reading)) => {
+                monitor.meter.record(reading.power_mw, now);
+                let snapshot = PowerSnapshot {
+                    reading,
+                    average_mw: monitor.meter.average_mw().unwrap_or(reading.power_mw),
+                    energy_mwh: monitor.meter.energy_mwh(),
+                };
+                if let Ok(mut latest) = LATEST.lock() {
+                    *latest = Some(snapshot);
+                }
+            }
+            Ok(None) => log::warn!("INA219: current out of range for the shunt"),
+            Err(e) => log::warn!("INA219: {:?}", e),
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub use device::{init, update};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_registers() {
+        // 5.04 V bus, 2.50 mV across 100 mΩ
+        let bus = (1260 << 3) | 0x0002;
+        let reading = decode(250, bus, 100).unwrap();
+        assert_eq!(reading.bus_mv, 5040);
+        assert_eq!(reading.current_ma, 25.0);
+        assert_eq!(reading.power_mw, 126.0);
+        // Reverse current reads negative
+        assert_eq!(decode(-100, bus, 100).unwrap().current_ma, -10.0);
+        // Math overflow flag, or no shunt configured
+        assert_eq!(decode(250, bus | BUS_OVF, 100), None);
+        assert_eq!(decode(250, bus, 0), None);
+    }
+
+    #[test]
+    fn test_energy_meter_integrates() {
+        let t0 = Instant::now();
+        let mut meter = EnergyMeter::default();
+        assert_eq!(meter.average_mw(), None);
+        meter.record(400.0, t0);
+        assert_eq!(meter.average_mw(), Some(400.0));
+        assert_eq!(meter.energy_mwh(), 0.0);
+
+        // 30 min ramping 400 -> 600 mW, then 30 min at 600 mW
+        meter.record(600.0, t0 + Duration::from_secs(1800));
+        meter.record(600.0, t0 + Duration::from_secs(3600));
+        assert!((meter.energy_mwh() - 550.0).abs() < 1e-6);
+        assert!((meter.average_mw().unwrap() - 550.0).abs() < 1e-3);
+    }
+}
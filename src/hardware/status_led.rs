@@ -0,0 +1,230 @@
+// WS2812 (NeoPixel) strip or ring on a configurable GPIO, driven via RMT.
+// Shows an ambient WiFi color, an OTA progress sweep and a red flash when an
+// alert fires, all fed from hardware::notify.
+
+use std::time::{Duration, Instant};
+use super::Notification;
+#[cfg(target_os = "espidf")]
+use {
+    anyhow::{anyhow, Result},
+    std::sync::Mutex,
+    esp_idf_hal::gpio::AnyOutputPin,
+    esp_idf_hal::rmt::{config::TransmitConfig, PinState, Pulse, TxRmtDriver, VariableLengthSignal, CHANNEL0},
+    crate::network::validators::is_pin_available,
+};
+
+pub const MAX_LEDS: usize = 64;
+const ALERT_FLASH_FOR: Duration = Duration::from_secs(3);
+const ALERT_FLASH_PERIOD_MS: u128 = 250;
+/// OTA sweep is dropped if progress stops arriving (failed or aborted upload)
+const OTA_STALE_AFTER: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const OFF: Self = Self::new(0, 0, 0);
+    const WIFI_OK: Self = Self::new(0, 80, 20);
+    const WIFI_DOWN: Self = Self::new(120, 40, 0);
+    const OTA: Self = Self::new(0, 60, 255);
+    const ALERT: Self = Self::new(255, 0, 0);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    fn scaled(self, brightness: u8) -> Self {
+        let s = |c: u8| ((c as u16 * brightness as u16) / 255) as u8;
+        Self::new(s(self.r), s(self.g), s(self.b))
+    }
+}
+
+/// What the LEDs should show, updated from notifications
+#[derive(Debug, Default)]
+pub struct LedState {
+    wifi_connected: bool,
+    ota: Option<(u8, Instant)>,
+    alert_until: Option<Instant>,
+}
+
+impl LedState {
+    pub fn apply(&mut self, notification: Notification, now: Instant) {
+        match notification {
+            Notification::WifiState(connected) => self.wifi_connected = connected,
+            Notification::OtaProgress(percent) => self.ota = Some((percent.min(100), now)),
+            Notification::OtaComplete => self.ota = Some((100, now)),
+            Notification::AlertFired => self.alert_until = Some(now + ALERT_FLASH_FOR),
+            Notification::ButtonClick | Notification::TimerDone => {}
+        }
+    }
+
+    /// Fill `leds` for time `now`. Priority: alert flash, OTA sweep, WiFi color.
+    pub fn render(&mut self, leds: &mut [Rgb], brightness: u8, now: Instant) {
+        if self.alert_until.is_some_and(|until| now >= until) {
+            self.alert_until = None;
+        }
+        if self.ota.is_some_and(|(_, at)| now.duration_since(at) >= OTA_STALE_AFTER) {
+            self.ota = None;
+        }
+
+        if let Some(until) = self.alert_until {
+            let remaining = until.duration_since(now).as_millis();
+            let on = (remaining / ALERT_FLASH_PERIOD_MS).is_multiple_of(2);
+            leds.fill(if on { Rgb::ALERT } else { Rgb::OFF });
+        } else if let Some((percent, _)) = self.ota {
+            let lit = (leds.len() * percent as usize).div_ceil(100);
+            for (i, led) in leds.iter_mut().enumerate() {
+                *led = if i < lit { Rgb::OTA } else { Rgb::OFF };
+            }
+        } else {
+            leds.fill(if self.wifi_connected { Rgb::WIFI_OK } else { Rgb::WIFI_DOWN });
+        }
+
+        for led in leds.iter_mut() {
+            *led = led.scaled(brightness);
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
+struct StatusLed {
+    tx: TxRmtDriver<'static>,
+    state: LedState,
+    leds: Vec<Rgb>,
+    last_sent: Vec<Rgb>,
+    brightness: u8,
+    enabled: bool,
+}
+
+#[cfg(target_os = "espidf")]
+static STATUS_LED: Mutex<Option<StatusLed>> = Mutex::new(None);
+
+/// Set up an RMT channel for `count` LEDs on `gpio`. Call once at startup.
+#[cfg(target_os = "espidf")]
+pub fn init(gpio: u8, count: u8) -> Result<()> {
+    if !is_pin_available(gpio) {
+        return Err(anyhow!("GPIO{} is reserved or invalid", gpio));
+    }
+    let count = (count as usize).clamp(1, MAX_LEDS);
+    // Safety: the pin was checked against every GPIO claimed elsewhere in main, and
+    // nothing else uses RMT channel 0
+    let (channel, pin) = unsafe { (CHANNEL0::new(), AnyOutputPin::new(gpio as i32)) };
+    let tx = TxRmtDriver::new(channel, pin, &TransmitConfig::new().clock_divider(1))?;
+
+    if let Ok(mut slot) = STATUS_LED.lock() {
+        *slot = Some(StatusLed {
+            tx,
+            state: LedState::default(),
+            leds: vec![Rgb::OFF; count],
+            last_sent: Vec::new(),
+            brightness: 0,
+            enabled: false,
+        });
+    }
+    log::info!("Status LEDs: {} on GPIO{}", count, gpio);
+    Ok(())
+}
+
+/// Apply brightness and the enable flag from Config
+#[cfg(target_os = "espidf")]
+pub fn configure(enabled: bool, brightness: u8) {
+    if let Ok(mut slot) = STATUS_LED.lock() {
+        if let Some(led) = slot.as_mut() {
+            led.enabled = enabled;
+            led.brightness = brightness;
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub(super) fn notify(notification: Notification) {
+    if let Ok(mut slot) = STATUS_LED.lock() {
+        if let Some(led) = slot.as_mut() {
+            led.state.apply(notification, Instant::now());
+        }
+    }
+}
+
+/// Render and send a frame if it changed; call from the main loop
+#[cfg(target_os = "espidf")]
+pub fn update() {
+    let Ok(mut slot) = STATUS_LED.lock() else { return };
+    let Some(led) = slot.as_mut() else { return };
+
+    let brightness = if led.enabled { led.brightness } else { 0 };
+    led.state.render(&mut led.leds, brightness, Instant::now());
+    if led.leds == led.last_sent {
+        return;
+    }
+    match write(&mut led.tx, &led.leds) {
+        Ok(()) => led.last_sent.clone_from(&led.leds),
+        Err(e) => log::warn!("Status LED write failed: {:?}", e),
+    }
+}
+
+/// Send one frame in WS2812 GRB order
+#[cfg(target_os = "espidf")]
+fn write(tx: &mut TxRmtDriver<'static>, leds: &[Rgb]) -> Result<()> {
+    let ticks_hz = tx.counter_clock()?;
+    let pulse = |state, ns| Pulse::new_with_duration(ticks_hz, state, &Duration::from_nanos(ns));
+    let bit0 = [pulse(PinState::High, 350)?, pulse(PinState::Low, 800)?];
+    let bit1 = [pulse(PinState::High, 700)?, pulse(PinState::Low, 600)?];
+
+    let mut signal = VariableLengthSignal::with_capacity(leds.len() * 24 * 2);
+    for led in leds {
+        for byte in [led.g, led.r, led.b] {
+            for bit in (0..8).rev() {
+                let pulses = if byte & (1 << bit) != 0 { &bit1 } else { &bit0 };
+                signal.push(pulses)?;
+            }
+        }
+    }
+    tx.start_blocking(&signal)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priorities_and_sweep() {
+        let t0 = Instant::now();
+        let mut state = LedState::default();
+        let mut leds = [Rgb::OFF; 8];
+
+        state.render(&mut leds, 255, t0);
+        assert_eq!(leds[0], Rgb::WIFI_DOWN);
+        state.apply(Notification::WifiState(true), t0);
+        state.render(&mut leds, 255, t0);
+        assert_eq!(leds[7], Rgb::WIFI_OK);
+
+        state.apply(Notification::OtaProgress(50), t0);
+        state.render(&mut leds, 255, t0);
+        assert_eq!(leds[3], Rgb::OTA);
+        assert_eq!(leds[4], Rgb::OFF);
+
+        // Alert flash wins over the OTA sweep, then both expire
+        state.apply(Notification::AlertFired, t0);
+        state.render(&mut leds, 255, t0);
+        assert_eq!(leds[7], Rgb::ALERT);
+        state.render(&mut leds, 255, t0 + Duration::from_secs(6));
+        assert_eq!(leds[0], Rgb::WIFI_OK);
+    }
+
+    #[test]
+    fn test_brightness_scaling() {
+        let t0 = Instant::now();
+        let mut state = LedState::default();
+        let mut leds = [Rgb::OFF; 2];
+        state.apply(Notification::AlertFired, t0);
+        state.render(&mut leds, 128, t0);
+        assert_eq!(leds[0], Rgb::new(128, 0, 0));
+        state.render(&mut leds, 0, t0);
+        assert_eq!(leds[1], Rgb::OFF);
+    }
+}
@@ -0,0 +1,616 @@
+// UI string tables. Screens and web templates look strings up by key in the
+// configured language; keys missing from a table fall back to English. The
+// template engine substitutes `{{@key}}` tags, and page scripts fetch the
+// merged table from /api/i18n/<lang>.json.
+//
+// The device font only has printable ASCII, so on-screen strings are written
+// without umlauts or accents. Keys under `web.` are only used in HTML and may
+// use any UTF-8.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+use serde::{Deserialize, Serialize};
+
+static CURRENT: AtomicU8 = AtomicU8::new(Language::En as u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Language {
+    #[default]
+    En,
+    De,
+    Es,
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [Language::En, Language::De, Language::Es];
+
+    /// ISO 639-1 code, also used as the HTML `lang` attribute
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::De => "de",
+            Self::Es => "es",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|language| language.code() == code)
+    }
+
+    pub fn table(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::En => EN,
+            Self::De => DE,
+            Self::Es => ES,
+        }
+    }
+}
+
+/// Switch the language used by `t`
+pub fn set_language(language: Language) {
+    CURRENT.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Language::De,
+        2 => Language::Es,
+        _ => Language::En,
+    }
+}
+
+/// `key` in the current language
+pub fn t(key: &'static str) -> &'static str {
+    lookup(language(), key)
+}
+
+/// `key` in `language`, else English, else the key itself (so a typo shows up
+/// on screen instead of a blank)
+pub fn lookup(language: Language, key: &'static str) -> &'static str {
+    find(language.table(), key).or_else(|| find(EN, key)).unwrap_or(key)
+}
+
+/// Like `lookup` for keys that aren't static (template tags); None if no table has it
+pub fn translate(language: Language, key: &str) -> Option<&'static str> {
+    find(language.table(), key).or_else(|| find(EN, key))
+}
+
+/// Every key with its text in `language`, English where it has none.
+/// Served as /api/i18n/<lang>.json for scripts in the web UI.
+pub fn strings(language: Language) -> impl Iterator<Item = (&'static str, &'static str)> {
+    EN.iter().map(move |&(key, english)| (key, find(language.table(), key).unwrap_or(english)))
+}
+
+fn find(table: &'static [(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+    table.iter().find(|(k, _)| *k == key).map(|&(_, text)| text)
+}
+
+const EN: &[(&str, &str)] = &[
+    ("hint.prev", "[BOOT] Prev"),
+    ("hint.next", "[USER] Next"),
+    ("hint.select", "[USER] Select"),
+    ("hint.check", "[USER] Check"),
+    ("common.not_available", "Not available"),
+    ("common.status", "Status:"),
+    ("system.title", "System Status"),
+    ("system.uptime", "Uptime:"),
+    ("system.free_heap", "Free Heap:"),
+    ("system.cpu_freq", "CPU Freq:"),
+    ("system.flash", "Flash:"),
+    ("system.temp", "Temp:"),
+    ("system.psram", "PSRAM/DMA:"),
+    ("system.reliability", "Reliability:"),
+    ("network.title", "Network Status"),
+    ("network.ssid", "SSID:"),
+    ("network.ip", "IP:"),
+    ("network.signal", "Signal:"),
+    ("network.connected", "Connected"),
+    ("network.disconnected", "Disconnected"),
+    ("network.no_config", "No WiFi Config"),
+    ("network.obtaining_ip", "Obtaining IP..."),
+    ("network.excellent", "Excellent"),
+    ("network.good", "Good"),
+    ("network.fair", "Fair"),
+    ("network.weak", "Weak"),
+    ("network.poor", "Poor"),
+    ("network.no_signal", "No signal"),
+    ("network.mac", "MAC:"),
+    ("network.gateway", "Gateway:"),
+    ("network.drops", "Drops:"),
+    ("network.web_config", "Web Configuration"),
+    ("network.not_configured", "WiFi Not Configured"),
+    ("network.edit_config", "Edit wifi_config.h:"),
+    ("network.rebuild", "Then rebuild & flash"),
+    ("network.failed", "WiFi Connection Failed"),
+    ("network.check", "Check password & signal"),
+    ("network.retrying", "Retrying connection..."),
+    ("sensor.title", "Sensor Data"),
+    ("sensor.battery", "Battery:"),
+    ("sensor.temp", "Temp:"),
+    ("sensor.light", "Light:"),
+    ("sensor.power", "Power:"),
+    ("sensor.no_battery", "No Battery"),
+    ("settings.title", "Settings"),
+    ("settings.brightness", "Brightness:"),
+    ("settings.auto_dim", "Auto-dim:"),
+    ("settings.update", "Update:"),
+    ("settings.version", "Version:"),
+    ("settings.large_text", "Large text:"),
+    ("settings.on", "ON"),
+    ("settings.off", "OFF"),
+    ("settings.normal", "Normal"),
+    ("ota.title", "OTA Updates"),
+    ("ota.firmware", "Firmware:"),
+    ("ota.partition", "Partition:"),
+    ("ota.network_required", "Network Required"),
+    ("ota.connect_wifi", "Connect to WiFi to enable OTA"),
+    ("ota.endpoints", "OTA Endpoints"),
+    ("ota.upload", "Upload:"),
+    ("ota.upload_hint", "Upload .bin file at OTA URL"),
+    ("ota.restart_hint", "Device auto-restarts after update"),
+    ("ota.in_progress", "OTA UPDATE IN PROGRESS"),
+    ("ota.do_not_power_off", "DO NOT POWER OFF"),
+    ("ota.ready", "Ready"),
+    ("ota.downloading", "Downloading"),
+    ("ota.verifying", "Verifying Update"),
+    ("ota.restart", "Update Ready - Restart"),
+    ("ota.failed", "Update Failed"),
+    ("ota.staged", "Update staged for"),
+    ("weather.title", "Weather"),
+    ("weather.no_data", "No weather data"),
+    ("weather.set_location", "Set location in web settings"),
+    ("widgets.title", "Custom"),
+    ("widgets.none", "No widgets configured"),
+    ("widgets.add", "Add them via /api/config"),
+    ("stats.title", "Lifetime"),
+    ("stats.boots", "Boots"),
+    ("stats.ota", "OTA updates"),
+    ("stats.frames", "Frames drawn"),
+    ("stats.buttons", "Button presses"),
+    ("stats.wifi", "WiFi reconnects"),
+    ("stats.unavailable", "Stats unavailable"),
+    ("aq.title", "Air Quality"),
+    ("aq.good", "Good"),
+    ("aq.moderate", "Moderate"),
+    ("aq.poor", "Poor"),
+    ("aq.bad", "Bad"),
+    ("aq.estimated", "eCO2 estimate"),
+    ("aq.humidity", "RH"),
+    ("aq.none", "No air quality sensor"),
+    ("aq.set_pins", "Set air_quality_sda/scl"),
+    ("agenda.title", "Agenda"),
+    ("agenda.now", "Now"),
+    ("agenda.all_day", "All day"),
+    ("agenda.tomorrow", "Tomorrow"),
+    ("agenda.empty", "Nothing coming up"),
+    ("agenda.not_configured", "No calendar configured"),
+    ("agenda.set_url", "Set agenda_url via /api/config"),
+    ("agenda.mon", "Mon"),
+    ("agenda.tue", "Tue"),
+    ("agenda.wed", "Wed"),
+    ("agenda.thu", "Thu"),
+    ("agenda.fri", "Fri"),
+    ("agenda.sat", "Sat"),
+    ("agenda.sun", "Sun"),
+    ("kiosk.temperature", "Temperature"),
+    ("kiosk.battery", "Battery"),
+    ("kiosk.min", "Min"),
+    ("kiosk.max", "Max"),
+    ("alerts.title", "Alerts"),
+    ("alerts.not_configured", "Alertmanager not configured"),
+    ("alerts.set_url", "Set alerts_url via /api/config"),
+    ("alerts.firing_one", "alert firing"),
+    ("alerts.firing_many", "alerts firing"),
+    ("alert.temp_high", "TEMP HIGH"),
+    ("alert.weak_wifi", "WEAK WIFI"),
+    ("alert.low_battery", "LOW BATTERY"),
+    ("alert.safe_mode", "SAFE MODE"),
+    ("alert.safe_mode_detail", "WEB + OTA ONLY"),
+    ("timer.title", "Focus"),
+    ("timer.start", "[USER] Start"),
+    ("timer.pause", "[USER] Pause"),
+    ("timer.hold_reset", "Hold: reset"),
+    ("timer.ready", "Ready"),
+    ("timer.running", "Focus"),
+    ("timer.paused", "Paused"),
+    ("timer.done", "Done!"),
+    ("timer.today", "Today:"),
+    ("web.nav.home", "Home"),
+    ("web.nav.dashboard", "Dashboard"),
+    ("web.nav.logs", "Logs"),
+    ("web.nav.files", "Files"),
+    ("web.nav.update", "Update"),
+    ("web.nav.stats", "Stats"),
+    ("web.nav.dev", "Dev Tools"),
+    ("web.home.quick_links", "Quick Links"),
+    ("web.home.view_metrics", "View Metrics"),
+    ("web.home.system_info", "System Info"),
+    ("web.home.configuration", "Configuration"),
+    ("web.header.version", "Version"),
+    ("web.metrics.system_status", "System Status"),
+    ("web.metrics.uptime", "Uptime"),
+    ("web.metrics.free_memory", "Free Memory"),
+    ("web.metrics.internal_dram", "Internal DRAM"),
+    ("web.metrics.psram", "PSRAM"),
+    ("web.stats.title", "Lifetime Stats"),
+    ("web.stats.boots", "Boots"),
+    ("web.stats.ota_updates", "OTA updates applied"),
+    ("web.stats.frames", "Frames rendered"),
+    ("web.stats.button_presses", "Button presses"),
+    ("web.stats.wifi_reconnects", "WiFi reconnects"),
+    ("web.stats.note", "Counted since the stats were first enabled; saved to flash every 10 minutes."),
+    ("web.stats.unavailable", "Stats unavailable"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("hint.prev", "[BOOT] Zurueck"),
+    ("hint.next", "[USER] Weiter"),
+    ("hint.select", "[USER] Waehlen"),
+    ("hint.check", "[USER] Pruefen"),
+    ("common.not_available", "Nicht verfuegbar"),
+    ("common.status", "Status:"),
+    ("system.title", "Systemstatus"),
+    ("system.uptime", "Laufzeit:"),
+    ("system.free_heap", "Freier Heap:"),
+    ("system.cpu_freq", "CPU-Takt:"),
+    ("system.flash", "Flash:"),
+    ("system.temp", "Temp:"),
+    ("system.psram", "PSRAM/DMA:"),
+    ("system.reliability", "Stabilitaet:"),
+    ("network.title", "Netzwerk"),
+    ("network.ssid", "SSID:"),
+    ("network.ip", "IP:"),
+    ("network.signal", "Signal:"),
+    ("network.connected", "Verbunden"),
+    ("network.disconnected", "Getrennt"),
+    ("network.no_config", "Kein WLAN konfiguriert"),
+    ("network.obtaining_ip", "Beziehe IP..."),
+    ("network.excellent", "Sehr gut"),
+    ("network.good", "Gut"),
+    ("network.fair", "Mittel"),
+    ("network.weak", "Schwach"),
+    ("network.poor", "Schlecht"),
+    ("network.no_signal", "Kein Signal"),
+    ("network.mac", "MAC:"),
+    ("network.gateway", "Gateway:"),
+    ("network.drops", "Ausfall:"),
+    ("network.web_config", "Web-Konfiguration"),
+    ("network.not_configured", "WLAN nicht konfiguriert"),
+    ("network.edit_config", "wifi_config.h bearbeiten:"),
+    ("network.rebuild", "Dann neu bauen & flashen"),
+    ("network.failed", "WLAN-Verbindung fehlgeschlagen"),
+    ("network.check", "Passwort & Signal pruefen"),
+    ("network.retrying", "Neuer Versuch..."),
+    ("sensor.title", "Sensordaten"),
+    ("sensor.battery", "Akku:"),
+    ("sensor.temp", "Temp:"),
+    ("sensor.light", "Licht:"),
+    ("sensor.power", "Leistung:"),
+    ("sensor.no_battery", "Kein Akku"),
+    ("settings.title", "Einstellungen"),
+    ("settings.brightness", "Helligkeit:"),
+    ("settings.auto_dim", "Auto-Dimmen:"),
+    ("settings.update", "Update:"),
+    ("settings.version", "Version:"),
+    ("settings.large_text", "Grosse Schrift:"),
+    ("settings.on", "AN"),
+    ("settings.off", "AUS"),
+    ("settings.normal", "Normal"),
+    ("ota.title", "OTA-Updates"),
+    ("ota.firmware", "Firmware:"),
+    ("ota.partition", "Partition:"),
+    ("ota.network_required", "Netzwerk erforderlich"),
+    ("ota.connect_wifi", "Fuer OTA mit WLAN verbinden"),
+    ("ota.endpoints", "OTA-Endpunkte"),
+    ("ota.upload", "Upload:"),
+    ("ota.upload_hint", ".bin-Datei an OTA-URL senden"),
+    ("ota.restart_hint", "Neustart nach dem Update"),
+    ("ota.in_progress", "OTA-UPDATE LAEUFT"),
+    ("ota.do_not_power_off", "NICHT AUSSCHALTEN"),
+    ("ota.ready", "Bereit"),
+    ("ota.downloading", "Lade"),
+    ("ota.verifying", "Pruefe Update"),
+    ("ota.restart", "Update bereit - Neustart"),
+    ("ota.failed", "Update fehlgeschlagen"),
+    ("ota.staged", "Update geplant um"),
+    ("weather.title", "Wetter"),
+    ("weather.no_data", "Keine Wetterdaten"),
+    ("weather.set_location", "Standort in den Web-Einstellungen setzen"),
+    ("widgets.title", "Eigene"),
+    ("widgets.none", "Keine Widgets konfiguriert"),
+    ("widgets.add", "Hinzufuegen ueber /api/config"),
+    ("stats.title", "Lebensdauer"),
+    ("stats.boots", "Starts"),
+    ("stats.ota", "OTA-Updates"),
+    ("stats.frames", "Bilder"),
+    ("stats.buttons", "Tastendruecke"),
+    ("stats.wifi", "WLAN-Neuverb."),
+    ("stats.unavailable", "Statistik fehlt"),
+    ("aq.title", "Luftqualitaet"),
+    ("aq.good", "Gut"),
+    ("aq.moderate", "Maessig"),
+    ("aq.poor", "Schlecht"),
+    ("aq.bad", "Sehr schlecht"),
+    ("aq.estimated", "eCO2 geschaetzt"),
+    ("aq.humidity", "rF"),
+    ("aq.none", "Kein Luftsensor"),
+    ("aq.set_pins", "air_quality_sda/scl setzen"),
+    ("agenda.title", "Termine"),
+    ("agenda.now", "Jetzt"),
+    ("agenda.all_day", "Ganztags"),
+    ("agenda.tomorrow", "Morgen"),
+    ("agenda.empty", "Keine Termine"),
+    ("agenda.not_configured", "Kein Kalender konfiguriert"),
+    ("agenda.set_url", "agenda_url ueber /api/config setzen"),
+    ("agenda.mon", "Mo"),
+    ("agenda.tue", "Di"),
+    ("agenda.wed", "Mi"),
+    ("agenda.thu", "Do"),
+    ("agenda.fri", "Fr"),
+    ("agenda.sat", "Sa"),
+    ("agenda.sun", "So"),
+    ("kiosk.temperature", "Temperatur"),
+    ("kiosk.battery", "Akku"),
+    ("kiosk.min", "Min"),
+    ("kiosk.max", "Max"),
+    ("alerts.title", "Alarme"),
+    ("alerts.not_configured", "Alertmanager nicht konfiguriert"),
+    ("alerts.set_url", "alerts_url ueber /api/config setzen"),
+    ("alerts.firing_one", "Alarm aktiv"),
+    ("alerts.firing_many", "Alarme aktiv"),
+    ("alert.temp_high", "TEMP HOCH"),
+    ("alert.weak_wifi", "WLAN SCHWACH"),
+    ("alert.low_battery", "AKKU LEER"),
+    ("alert.safe_mode", "SICHERER MODUS"),
+    ("alert.safe_mode_detail", "NUR WEB + OTA"),
+    ("timer.title", "Fokus"),
+    ("timer.start", "[USER] Start"),
+    ("timer.pause", "[USER] Pause"),
+    ("timer.hold_reset", "Halten: Reset"),
+    ("timer.ready", "Bereit"),
+    ("timer.running", "Fokus"),
+    ("timer.paused", "Pausiert"),
+    ("timer.done", "Fertig!"),
+    ("timer.today", "Heute:"),
+    ("web.nav.home", "Start"),
+    ("web.nav.dashboard", "Dashboard"),
+    ("web.nav.logs", "Protokoll"),
+    ("web.nav.files", "Dateien"),
+    ("web.nav.update", "Update"),
+    ("web.nav.stats", "Statistik"),
+    ("web.nav.dev", "Entwicklung"),
+    ("web.home.quick_links", "Schnellzugriff"),
+    ("web.home.view_metrics", "Metriken anzeigen"),
+    ("web.home.system_info", "Systeminfo"),
+    ("web.home.configuration", "Konfiguration"),
+    ("web.header.version", "Version"),
+    ("web.metrics.system_status", "Systemstatus"),
+    ("web.metrics.uptime", "Laufzeit"),
+    ("web.metrics.free_memory", "Freier Speicher"),
+    ("web.metrics.internal_dram", "Interner DRAM"),
+    ("web.metrics.psram", "PSRAM"),
+    ("web.stats.title", "Gesamtstatistik"),
+    ("web.stats.boots", "Starts"),
+    ("web.stats.ota_updates", "Installierte OTA-Updates"),
+    ("web.stats.frames", "Gezeichnete Frames"),
+    ("web.stats.button_presses", "Tastendrücke"),
+    ("web.stats.wifi_reconnects", "WLAN-Neuverbindungen"),
+    ("web.stats.note", "Gezählt seit Aktivierung der Statistik; alle 10 Minuten im Flash gespeichert."),
+    ("web.stats.unavailable", "Statistik nicht verfügbar"),
+];
+
+const ES: &[(&str, &str)] = &[
+    ("hint.prev", "[BOOT] Atras"),
+    ("hint.next", "[USER] Sig."),
+    ("hint.select", "[USER] Elegir"),
+    ("hint.check", "[USER] Buscar"),
+    ("common.not_available", "No disponible"),
+    ("common.status", "Estado:"),
+    ("system.title", "Estado del sistema"),
+    ("system.uptime", "Activo:"),
+    ("system.free_heap", "Heap libre:"),
+    ("system.cpu_freq", "Frec. CPU:"),
+    ("system.flash", "Flash:"),
+    ("system.temp", "Temp:"),
+    ("system.psram", "PSRAM/DMA:"),
+    ("system.reliability", "Fiabilidad:"),
+    ("network.title", "Estado de red"),
+    ("network.ssid", "SSID:"),
+    ("network.ip", "IP:"),
+    ("network.signal", "Senal:"),
+    ("network.connected", "Conectado"),
+    ("network.disconnected", "Desconectado"),
+    ("network.no_config", "WiFi sin configurar"),
+    ("network.obtaining_ip", "Obteniendo IP..."),
+    ("network.excellent", "Excelente"),
+    ("network.good", "Buena"),
+    ("network.fair", "Regular"),
+    ("network.weak", "Debil"),
+    ("network.poor", "Mala"),
+    ("network.no_signal", "Sin senal"),
+    ("network.mac", "MAC:"),
+    ("network.gateway", "Puerta:"),
+    ("network.drops", "Caidas:"),
+    ("network.web_config", "Configuracion web"),
+    ("network.not_configured", "WiFi no configurado"),
+    ("network.edit_config", "Edite wifi_config.h:"),
+    ("network.rebuild", "Luego compile y grabe"),
+    ("network.failed", "Fallo la conexion WiFi"),
+    ("network.check", "Revise clave y senal"),
+    ("network.retrying", "Reintentando..."),
+    ("sensor.title", "Sensores"),
+    ("sensor.battery", "Bateria:"),
+    ("sensor.temp", "Temp:"),
+    ("sensor.light", "Luz:"),
+    ("sensor.power", "Potencia:"),
+    ("sensor.no_battery", "Sin bateria"),
+    ("settings.title", "Ajustes"),
+    ("settings.brightness", "Brillo:"),
+    ("settings.auto_dim", "Atenuar:"),
+    ("settings.update", "Refresco:"),
+    ("settings.version", "Version:"),
+    ("settings.large_text", "Texto grande:"),
+    ("settings.on", "SI"),
+    ("settings.off", "NO"),
+    ("settings.normal", "Normal"),
+    ("ota.title", "Actualizacion OTA"),
+    ("ota.firmware", "Firmware:"),
+    ("ota.partition", "Particion:"),
+    ("ota.network_required", "Se requiere red"),
+    ("ota.connect_wifi", "Conecte WiFi para usar OTA"),
+    ("ota.endpoints", "Direcciones OTA"),
+    ("ota.upload", "Subir:"),
+    ("ota.upload_hint", "Suba el .bin en la URL OTA"),
+    ("ota.restart_hint", "Se reinicia al terminar"),
+    ("ota.in_progress", "ACTUALIZANDO"),
+    ("ota.do_not_power_off", "NO APAGAR"),
+    ("ota.ready", "Listo"),
+    ("ota.downloading", "Descargando"),
+    ("ota.verifying", "Verificando"),
+    ("ota.restart", "Listo - Reiniciar"),
+    ("ota.failed", "Fallo la actualizacion"),
+    ("ota.staged", "Actualizacion a las"),
+    ("weather.title", "Tiempo"),
+    ("weather.no_data", "Sin datos del tiempo"),
+    ("weather.set_location", "Fije la ubicacion en la web"),
+    ("widgets.title", "Propio"),
+    ("widgets.none", "Sin widgets"),
+    ("widgets.add", "Agreguelos en /api/config"),
+    ("stats.title", "Historico"),
+    ("stats.boots", "Arranques"),
+    ("stats.ota", "Act. OTA"),
+    ("stats.frames", "Fotogramas"),
+    ("stats.buttons", "Pulsaciones"),
+    ("stats.wifi", "Reconex. WiFi"),
+    ("stats.unavailable", "Sin estadisticas"),
+    ("aq.title", "Calidad del aire"),
+    ("aq.good", "Buena"),
+    ("aq.moderate", "Moderada"),
+    ("aq.poor", "Mala"),
+    ("aq.bad", "Muy mala"),
+    ("aq.estimated", "eCO2 estimado"),
+    ("aq.humidity", "HR"),
+    ("aq.none", "Sin sensor de aire"),
+    ("aq.set_pins", "Configure air_quality_sda/scl"),
+    ("agenda.title", "Agenda"),
+    ("agenda.now", "Ahora"),
+    ("agenda.all_day", "Todo el dia"),
+    ("agenda.tomorrow", "Manana"),
+    ("agenda.empty", "Nada pendiente"),
+    ("agenda.not_configured", "Sin calendario"),
+    ("agenda.set_url", "Fije agenda_url en /api/config"),
+    ("agenda.mon", "Lun"),
+    ("agenda.tue", "Mar"),
+    ("agenda.wed", "Mie"),
+    ("agenda.thu", "Jue"),
+    ("agenda.fri", "Vie"),
+    ("agenda.sat", "Sab"),
+    ("agenda.sun", "Dom"),
+    ("kiosk.temperature", "Temperatura"),
+    ("kiosk.battery", "Bateria"),
+    ("kiosk.min", "Min"),
+    ("kiosk.max", "Max"),
+    ("alerts.title", "Alertas"),
+    ("alerts.not_configured", "Alertmanager sin configurar"),
+    ("alerts.set_url", "Fije alerts_url en /api/config"),
+    ("alerts.firing_one", "alerta activa"),
+    ("alerts.firing_many", "alertas activas"),
+    ("alert.temp_high", "TEMP ALTA"),
+    ("alert.weak_wifi", "WIFI DEBIL"),
+    ("alert.low_battery", "BATERIA BAJA"),
+    ("alert.safe_mode", "MODO SEGURO"),
+    ("alert.safe_mode_detail", "SOLO WEB + OTA"),
+    ("timer.title", "Enfoque"),
+    ("timer.start", "[USER] Iniciar"),
+    ("timer.pause", "[USER] Pausa"),
+    ("timer.hold_reset", "Mantener: reset"),
+    ("timer.ready", "Listo"),
+    ("timer.running", "Enfoque"),
+    ("timer.paused", "En pausa"),
+    ("timer.done", "Hecho!"),
+    ("timer.today", "Hoy:"),
+    ("web.nav.home", "Inicio"),
+    ("web.nav.dashboard", "Panel"),
+    ("web.nav.logs", "Registros"),
+    ("web.nav.files", "Archivos"),
+    ("web.nav.update", "Actualizar"),
+    ("web.nav.stats", "Estadisticas"),
+    ("web.nav.dev", "Desarrollo"),
+    ("web.home.quick_links", "Accesos rápidos"),
+    ("web.home.view_metrics", "Ver métricas"),
+    ("web.home.system_info", "Info del sistema"),
+    ("web.home.configuration", "Configuración"),
+    ("web.header.version", "Versión"),
+    ("web.metrics.system_status", "Estado del sistema"),
+    ("web.metrics.uptime", "Tiempo activo"),
+    ("web.metrics.free_memory", "Memoria libre"),
+    ("web.metrics.internal_dram", "DRAM interna"),
+    ("web.metrics.psram", "PSRAM"),
+    ("web.stats.title", "Estadísticas totales"),
+    ("web.stats.boots", "Arranques"),
+    ("web.stats.ota_updates", "Actualizaciones OTA aplicadas"),
+    ("web.stats.frames", "Fotogramas dibujados"),
+    ("web.stats.button_presses", "Pulsaciones de botón"),
+    ("web.stats.wifi_reconnects", "Reconexiones WiFi"),
+    ("web.stats.note", "Contado desde que se activaron las estadísticas; se guarda en flash cada 10 minutos."),
+    ("web.stats.unavailable", "Estadísticas no disponibles"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_language_has_every_key() {
+        for language in Language::ALL {
+            let table = language.table();
+            for (key, _) in EN {
+                assert!(find(table, key).is_some(), "{:?} is missing '{}'", language, key);
+            }
+            for (key, _) in table {
+                assert!(find(EN, key).is_some(), "{:?} has unknown key '{}'", language, key);
+            }
+            for (i, (key, _)) in table.iter().enumerate() {
+                assert!(!table[..i].iter().any(|(k, _)| k == key), "{:?} repeats '{}'", language, key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_device_strings_fit_the_font() {
+        for language in Language::ALL {
+            for (key, text) in language.table().iter().filter(|(k, _)| !k.starts_with("web.")) {
+                assert!(text.chars().all(|c| (' '..='~').contains(&c)), "{:?} '{}' is not ASCII", language, key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_web_templates_use_known_keys() {
+        let templates = [
+            include_str!("templates/home_template.html"),
+            include_str!("templates/partials/header.html"),
+            include_str!("templates/partials/metrics.html"),
+            include_str!("templates/stats.html"),
+        ];
+        for template in templates {
+            for tag in template.split("{{@").skip(1) {
+                let key = tag.split("}}").next().unwrap().trim();
+                assert!(find(EN, key).is_some(), "template uses unknown key '{}'", key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup_falls_back() {
+        assert_eq!(lookup(Language::De, "system.title"), "Systemstatus");
+        assert_eq!(lookup(Language::Es, "hint.prev"), "[BOOT] Atras");
+        assert_eq!(lookup(Language::De, "no.such.key"), "no.such.key");
+        assert_eq!(serde_json::from_str::<Language>("\"de\"").unwrap(), Language::De);
+        assert_eq!(translate(Language::Es, "web.nav.logs"), Some("Registros"));
+        assert_eq!(translate(Language::De, "web.nope"), None);
+        assert_eq!(Language::from_code("es"), Some(Language::Es));
+        assert_eq!(Language::from_code("fr"), None);
+        assert_eq!(strings(Language::De).count(), EN.len());
+    }
+}
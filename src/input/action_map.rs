@@ -0,0 +1,136 @@
+// User-configurable button actions. Events coming off the input bus pass
+// through the ButtonMap (config `button_actions`) before UiManager sees them:
+// navigation actions become the equivalent UI event, device actions are
+// carried out by the main loop, and everything unmapped passes unchanged.
+// The shutdown and self-test combos are fixed and never remapped.
+
+use serde::{Deserialize, Serialize};
+use super::{InputEvent, Key};
+
+/// What a button event does
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ButtonAction {
+    /// Nothing
+    None,
+    NextScreen,
+    PrevScreen,
+    /// Backlight off until pressed again (or any other input)
+    ToggleDisplay,
+    /// Jump to the OTA screen (firmware, partitions, update progress)
+    OtaCheck,
+    /// Run the hardware self-test
+    SelfTest,
+    /// Publish `payload` to `topic` on the configured MQTT broker
+    Mqtt { topic: String, payload: String },
+}
+
+/// Action for each remappable event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ButtonMap {
+    pub boot_click: ButtonAction,
+    pub user_click: ButtonAction,
+    pub boot_long: ButtonAction,
+    pub user_long: ButtonAction,
+    /// Rotary encoder push
+    pub select: ButtonAction,
+}
+
+impl Default for ButtonMap {
+    /// The fixed behaviour from before actions were configurable
+    fn default() -> Self {
+        Self {
+            boot_click: ButtonAction::PrevScreen,
+            user_click: ButtonAction::NextScreen,
+            boot_long: ButtonAction::None,
+            user_long: ButtonAction::None,
+            select: ButtonAction::NextScreen,
+        }
+    }
+}
+
+/// Where an event goes after mapping
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Dispatch {
+    /// Hand to UiManager
+    Ui(InputEvent),
+    /// Carry out on the device (display, self-test, MQTT, ...)
+    Device(ButtonAction),
+    /// Mapped to nothing
+    Ignore,
+}
+
+impl ButtonMap {
+    /// Slot an event is looked up in, if it can be remapped
+    fn action_for(&self, event: InputEvent) -> Option<&ButtonAction> {
+        match event {
+            InputEvent::Click(Key::Boot) => Some(&self.boot_click),
+            InputEvent::Click(Key::User) => Some(&self.user_click),
+            InputEvent::LongPress(Key::Boot) => Some(&self.boot_long),
+            InputEvent::LongPress(Key::User) => Some(&self.user_long),
+            InputEvent::Select => Some(&self.select),
+            _ => None,
+        }
+    }
+
+    pub fn dispatch(&self, event: InputEvent) -> Dispatch {
+        match self.action_for(event) {
+            None => Dispatch::Ui(event),
+            // Navigation goes through the UI as a click so the visible screen
+            // still gets first refusal (e.g. USER starts the timer)
+            Some(ButtonAction::NextScreen) => Dispatch::Ui(InputEvent::Click(Key::User)),
+            Some(ButtonAction::PrevScreen) => Dispatch::Ui(InputEvent::Click(Key::Boot)),
+            Some(ButtonAction::None) => Dispatch::Ignore,
+            Some(action) => Dispatch::Device(action.clone()),
+        }
+    }
+
+    /// MQTT actions in the map, as (slot, topic)
+    pub fn mqtt_topics(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("boot_click", &self.boot_click),
+            ("user_click", &self.user_click),
+            ("boot_long", &self.boot_long),
+            ("user_long", &self.user_long),
+            ("select", &self.select),
+        ]
+        .into_iter()
+        .filter_map(|(slot, action)| match action {
+            ButtonAction::Mqtt { topic, .. } => Some((slot, topic.as_str())),
+            _ => None,
+        })
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_map_keeps_old_behaviour() {
+        let map = ButtonMap::default();
+        assert_eq!(map.dispatch(InputEvent::Click(Key::Boot)), Dispatch::Ui(InputEvent::Click(Key::Boot)));
+        assert_eq!(map.dispatch(InputEvent::Click(Key::User)), Dispatch::Ui(InputEvent::Click(Key::User)));
+        assert_eq!(map.dispatch(InputEvent::Select), Dispatch::Ui(InputEvent::Click(Key::User)));
+        assert_eq!(map.dispatch(InputEvent::LongPress(Key::User)), Dispatch::Ignore);
+        // Not remappable
+        assert_eq!(map.dispatch(InputEvent::Rotate(2)), Dispatch::Ui(InputEvent::Rotate(2)));
+        assert_eq!(map.dispatch(InputEvent::SelfTestCombo), Dispatch::Ui(InputEvent::SelfTestCombo));
+    }
+
+    #[test]
+    fn test_remapped_actions_and_json_shape() {
+        let map: ButtonMap = serde_json::from_str(
+            r#"{"boot_click": "next_screen", "user_long": "toggle_display",
+                "boot_long": {"mqtt": {"topic": "home/desk", "payload": "lamp"}}}"#,
+        ).unwrap();
+        assert_eq!(map.dispatch(InputEvent::Click(Key::Boot)), Dispatch::Ui(InputEvent::Click(Key::User)));
+        assert_eq!(map.dispatch(InputEvent::LongPress(Key::User)), Dispatch::Device(ButtonAction::ToggleDisplay));
+        // Missing slots keep their defaults
+        assert_eq!(map.user_click, ButtonAction::NextScreen);
+        assert_eq!(map.mqtt_topics(), vec![("boot_long", "home/desk")]);
+        assert!(serde_json::from_str::<ButtonMap>(r#"{"user_click": "reboot"}"#).is_err());
+    }
+}
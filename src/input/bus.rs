@@ -0,0 +1,158 @@
+use std::collections::VecDeque;
+use super::{InputEvent, Origin};
+use crate::trace::TraceId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Priority {
+    Remote,
+    Local,
+    Shutdown,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Queued {
+    event: InputEvent,
+    origin: Origin,
+    priority: Priority,
+    /// Request that queued it, so the UI work it causes logs under the same ID
+    trace: Option<TraceId>,
+}
+
+/// Bounded input queue with priorities and coalescing.
+///
+/// Events come out highest priority first (shutdown, then physical input,
+/// then remote input), FIFO within a priority. Consecutive rotations merge,
+/// a newer screen jump replaces a queued one, and a remote event identical to
+/// one still queued is dropped. When full, the oldest lowest-priority event
+/// makes room unless it outranks the new one.
+#[derive(Debug)]
+pub struct EventBus {
+    queue: VecDeque<Queued>,
+    capacity: usize,
+}
+
+impl EventBus {
+    pub const fn new(capacity: usize) -> Self {
+        Self { queue: VecDeque::new(), capacity }
+    }
+
+    /// Queue an event; false if it was dropped
+    pub fn push(&mut self, event: InputEvent, origin: Origin) -> bool {
+        self.push_traced(event, origin, None)
+    }
+
+    pub fn push_traced(&mut self, event: InputEvent, origin: Origin, trace: Option<TraceId>) -> bool {
+        let priority = match (event, origin) {
+            (InputEvent::ShutdownCombo, _) => Priority::Shutdown,
+            (_, Origin::Local) => Priority::Local,
+            (_, Origin::Remote) => Priority::Remote,
+        };
+
+        match event {
+            InputEvent::Rotate(steps) => {
+                if let Some(last) = self.queue.back_mut() {
+                    if let (InputEvent::Rotate(queued), true) = (last.event, last.origin == origin) {
+                        last.event = InputEvent::Rotate(queued + steps);
+                        last.trace = trace.or(last.trace);
+                        return true;
+                    }
+                }
+            }
+            InputEvent::ShowScreen(_) => {
+                self.queue.retain(|q| !matches!(q.event, InputEvent::ShowScreen(_)));
+            }
+            _ if origin == Origin::Remote
+                && self.queue.iter().any(|q| q.origin == Origin::Remote && q.event == event) => {
+                return false;
+            }
+            _ => {}
+        }
+
+        if self.queue.len() >= self.capacity {
+            let victim = self.queue.iter().enumerate()
+                .min_by_key(|(_, q)| q.priority)
+                .filter(|(_, q)| q.priority <= priority)
+                .map(|(i, _)| i);
+            match victim {
+                Some(i) => { self.queue.remove(i); }
+                None => return false,
+            }
+        }
+        self.queue.push_back(Queued { event, origin, priority, trace });
+        true
+    }
+
+    pub fn pop(&mut self) -> Option<InputEvent> {
+        self.pop_traced().map(|(event, _)| event)
+    }
+
+    /// Next event together with the trace it was queued under
+    pub fn pop_traced(&mut self) -> Option<(InputEvent, Option<TraceId>)> {
+        let top = self.queue.iter().map(|q| q.priority).max()?;
+        let index = self.queue.iter().position(|q| q.priority == top)?;
+        self.queue.remove(index).map(|q| (q.event, q.trace))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Key;
+
+    #[test]
+    fn test_priority_order_and_fifo() {
+        let mut bus = EventBus::new(8);
+        bus.push(InputEvent::Click(Key::User), Origin::Remote);
+        bus.push(InputEvent::Click(Key::Boot), Origin::Local);
+        bus.push(InputEvent::Click(Key::User), Origin::Local);
+        bus.push(InputEvent::ShutdownCombo, Origin::Local);
+
+        assert_eq!(bus.pop(), Some(InputEvent::ShutdownCombo));
+        assert_eq!(bus.pop(), Some(InputEvent::Click(Key::Boot)));
+        assert_eq!(bus.pop(), Some(InputEvent::Click(Key::User)));
+        assert_eq!(bus.pop(), Some(InputEvent::Click(Key::User)));
+        assert_eq!(bus.pop(), None);
+    }
+
+    #[test]
+    fn test_coalescing_and_dedup() {
+        let mut bus = EventBus::new(8);
+        bus.push(InputEvent::Rotate(1), Origin::Local);
+        bus.push(InputEvent::Rotate(2), Origin::Local);
+        bus.push(InputEvent::ShowScreen(2), Origin::Remote);
+        bus.push(InputEvent::ShowScreen(5), Origin::Remote);
+        assert!(bus.push(InputEvent::Select, Origin::Remote));
+        // Double-submitted web click
+        assert!(!bus.push(InputEvent::Select, Origin::Remote));
+
+        assert_eq!(bus.pop(), Some(InputEvent::Rotate(3)));
+        assert_eq!(bus.pop(), Some(InputEvent::ShowScreen(5)));
+        assert_eq!(bus.pop(), Some(InputEvent::Select));
+        assert_eq!(bus.pop(), None);
+    }
+
+    #[test]
+    fn test_overflow_drops_remote_first() {
+        let mut bus = EventBus::new(2);
+        bus.push(InputEvent::Click(Key::User), Origin::Remote);
+        bus.push(InputEvent::Click(Key::Boot), Origin::Local);
+        assert!(bus.push(InputEvent::Click(Key::User), Origin::Local));
+        // Queue is all local now; remote input can't displace it
+        assert!(!bus.push(InputEvent::Select, Origin::Remote));
+        assert_eq!(bus.pop(), Some(InputEvent::Click(Key::Boot)));
+        assert_eq!(bus.pop(), Some(InputEvent::Click(Key::User)));
+        assert_eq!(bus.pop(), None);
+        assert_eq!(InputEvent::from_name("right"), Some(InputEvent::Rotate(1)));
+    }
+
+    #[test]
+    fn test_events_keep_their_trace() {
+        let mut bus = EventBus::new(4);
+        let id = TraceId::next();
+        bus.push(InputEvent::Rotate(1), Origin::Remote);
+        bus.push_traced(InputEvent::Rotate(1), Origin::Remote, Some(id));
+        bus.push(InputEvent::Click(Key::Boot), Origin::Local);
+        assert_eq!(bus.pop_traced(), Some((InputEvent::Click(Key::Boot), None)));
+        assert_eq!(bus.pop_traced(), Some((InputEvent::Rotate(2), Some(id))));
+    }
+}
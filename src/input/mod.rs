@@ -0,0 +1,101 @@
+// Unified input layer. Every source (buttons, rotary encoder, web UI, ...)
+// publishes InputEvents to one bus; UiManager only ever sees what comes off it,
+// after the user's button mapping (action_map) has been applied.
+
+pub mod action_map;
+mod bus;
+
+pub use bus::EventBus;
+use std::sync::Mutex;
+#[cfg(target_os = "espidf")]
+use crate::{config::Config, system::ButtonManager};
+
+/// Events queued beyond this are dropped (remote events first)
+const BUS_CAPACITY: usize = 16;
+
+/// Physical key on the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    /// BOOT button (GPIO0), navigates back
+    Boot,
+    /// USER button (GPIO14), navigates forward
+    User,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputEvent {
+    Press(Key),
+    Release(Key),
+    Click(Key),
+    LongPress(Key),
+    /// Both keys held 1-5 s and released; triggers shutdown
+    ShutdownCombo,
+    /// Both keys held 5 s; runs the hardware self-test
+    SelfTestCombo,
+    /// Turned by n detents (positive = clockwise)
+    Rotate(i32),
+    /// Encoder push; acts like a USER click
+    Select,
+    /// Jump straight to a screen
+    ShowScreen(usize),
+}
+
+impl InputEvent {
+    /// Parse the names accepted by /api/control {"input": "..."}
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "boot" | "prev" => Some(Self::Click(Key::Boot)),
+            "user" | "next" => Some(Self::Click(Key::User)),
+            "boot_long" => Some(Self::LongPress(Key::Boot)),
+            "user_long" => Some(Self::LongPress(Key::User)),
+            "left" => Some(Self::Rotate(-1)),
+            "right" => Some(Self::Rotate(1)),
+            "select" => Some(Self::Select),
+            _ => None,
+        }
+    }
+}
+
+/// Where an event came from; remote events yield to physical input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    Local,
+    Remote,
+}
+
+/// Anything the main loop polls for input
+pub trait InputSource {
+    fn poll(&mut self) -> Option<InputEvent>;
+}
+
+static BUS: Mutex<EventBus> = Mutex::new(EventBus::new(BUS_CAPACITY));
+
+/// Queue an event for the UI; safe to call from any thread (e.g. HTTP handlers).
+/// The caller's current trace travels with it.
+pub fn publish(event: InputEvent, origin: Origin) {
+    if let Ok(mut bus) = BUS.lock() {
+        if !bus.push_traced(event, origin, crate::trace::current()) {
+            log::debug!("Input: dropped {:?} from {:?}", event, origin);
+        }
+    }
+}
+
+/// Next event for the UI, highest priority first, with the trace it was published under
+pub fn next_event() -> Option<(InputEvent, Option<crate::trace::TraceId>)> {
+    BUS.lock().ok()?.pop_traced()
+}
+
+/// The board buttons plus the rotary encoder when it is configured
+#[cfg(target_os = "espidf")]
+pub fn local_sources(buttons: ButtonManager, config: &Mutex<Config>) -> Vec<Box<dyn InputSource>> {
+    let mut sources: Vec<Box<dyn InputSource>> = vec![Box::new(buttons)];
+    let encoder_pins = config.lock().ok()
+        .and_then(|cfg| Some((cfg.encoder_a_gpio?, cfg.encoder_b_gpio?, cfg.encoder_button_gpio)));
+    if let Some((a, b, button)) = encoder_pins {
+        match crate::system::encoder::RotaryEncoder::new(a, b, button) {
+            Ok(encoder) => sources.push(Box::new(encoder)),
+            Err(e) => log::warn!("Rotary encoder unavailable: {:?}", e),
+        }
+    }
+    sources
+}
@@ -1,3 +1,5 @@
+pub mod syslog;
+
 use log::{Level, LevelFilter, Metadata, Record};
 use std::sync::{Arc, OnceLock};
 use std::time::SystemTime;
@@ -17,7 +19,7 @@ mod colors {
     pub const GRAY: &str = "\x1b[90m";
 }
 
-/// Enhanced logger that prints colored, timestamped lines and forwards to telnet/log streamer
+/// Enhanced logger that prints colored, timestamped lines and forwards to telnet/log streamer/syslog
 struct EnhancedLogger;
 
 impl log::Log for EnhancedLogger {
@@ -58,16 +60,19 @@ impl log::Log for EnhancedLogger {
             Level::Debug => (colors::BRIGHT_BLUE, "DEBUG", 'D'),
             Level::Trace => (colors::GRAY, "TRACE", 'T'),
         };
-        let module = record
-            .module_path()
-            .unwrap_or("unknown")
-            .split("::")
-            .last()
-            .unwrap_or("unknown");
+        // Full path for the telnet and /api/logs filters, last segment for display
+        let module_path = crate::network::log_filter::module_path(
+            record.module_path().unwrap_or("unknown"),
+            env!("CARGO_CRATE_NAME"),
+        );
+        let module = module_path.rsplit("::").next().unwrap_or("unknown");
         let module_display = if module.len() > 12 { &module[..12] } else { module };
 
-        // Message
-        let message = format!("{}", record.args());
+        // Message, tagged with the thread's trace (see trace.rs)
+        let message = match crate::trace::current() {
+            Some(id) => format!("[{}] {}", id, record.args()),
+            None => format!("{}", record.args()),
+        };
 
         // Console output (serial). ANSI colors are fine over serial; telnet gets plain text below.
         println!(
@@ -75,20 +80,27 @@ impl log::Log for EnhancedLogger {
             color, ts_compact, level_char, module_display, message, colors::RESET
         );
 
-        // Telnet (no colors)
+        // Plain line for telnet and the crash-safe ring
+        let plain = format!(
+            "{} [{}] {:>12} | {}",
+            ts_compact, level_str, module_display, message
+        );
         if let Some(server) = TELNET_SERVER.get() {
-            let telnet_msg = format!(
-                "{} [{}] {:>12} | {}",
-                ts_compact, level_str, module_display, message
-            );
-            server.log_message(level_str, &telnet_msg);
+            server.log_record(record.level(), &module_path, &plain);
         }
+        crate::storage::log_ring::write_line(&plain);
 
         // Append to in-memory log streamer (non-blocking; drop on contention)
-        log_streamer::append(level_str, Some(module), &message, ts_ms);
+        log_streamer::append(level_str, Some(&module_path), &message, ts_ms);
+
+        // Remote syslog, when configured (queued, never blocks)
+        syslog::forward(record.level(), &module_path, &message);
     }
 
-    fn flush(&self) {}
+    fn flush(&self) {
+        // Telnet and the log ring write through; only stdout buffers
+        let _ = std::io::Write::flush(&mut std::io::stdout());
+    }
 }
 
 static LOGGER: EnhancedLogger = EnhancedLogger;
@@ -0,0 +1,259 @@
+// Remote syslog: log records sent as RFC 5424 messages over UDP (RFC 5426)
+// to `syslog_server`, for collectors such as Graylog, Vector or rsyslog. The
+// logger only queues; a worker thread formats, resolves the server and sends,
+// so logging never waits on DNS or the network. Records that don't fit the
+// queue, or arrive while the server can't be resolved, are dropped.
+
+use crate::network::ics::civil_from_days;
+use log::Level;
+use std::fmt::Write;
+#[cfg(target_os = "espidf")]
+use crate::config::Config;
+#[cfg(target_os = "espidf")]
+use crate::system::ShutdownSignal;
+#[cfg(target_os = "espidf")]
+use anyhow::Result;
+#[cfg(target_os = "espidf")]
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+#[cfg(target_os = "espidf")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(target_os = "espidf")]
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender};
+#[cfg(target_os = "espidf")]
+use std::sync::{Arc, Mutex, OnceLock};
+#[cfg(target_os = "espidf")]
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_PORT: u16 = 514;
+/// local0
+pub const FACILITY: u8 = 16;
+pub const APP_NAME: &str = "esp32-dashboard";
+/// Longest datagram sent; RFC 5426 receivers must take 480 bytes and should
+/// take 2048
+pub const MAX_MESSAGE_LEN: usize = 1024;
+
+/// Records waiting for the worker; more are dropped
+#[cfg(target_os = "espidf")]
+const QUEUE_LEN: usize = 32;
+/// How often the worker re-reads the config (and retries a failed lookup)
+#[cfg(target_os = "espidf")]
+const CONFIG_INTERVAL: Duration = Duration::from_secs(30);
+
+/// RFC 5424 severity of a log level
+pub fn severity(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+/// `syslog_server` as host and port: "logs.lan", "10.0.0.5:1514". None if
+/// malformed.
+pub fn parse_server(server: &str) -> Option<(&str, u16)> {
+    let (host, port) = match server.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().ok().filter(|&p| p != 0)?),
+        None => (server, DEFAULT_PORT),
+    };
+    let valid = !host.is_empty() && host.len() <= 64
+        && host.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    valid.then_some((host, port))
+}
+
+pub struct SyslogMessage<'a> {
+    pub level: Level,
+    /// Wall-clock time; None before SNTP sync
+    pub unix_ms: Option<u64>,
+    pub hostname: &'a str,
+    /// Sent as MSGID, so collectors can filter by module
+    pub module: &'a str,
+    pub message: &'a str,
+}
+
+impl SyslogMessage<'_> {
+    /// `<PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID SD MSG`, cut to
+    /// MAX_MESSAGE_LEN
+    pub fn format(&self) -> String {
+        let mut out = String::with_capacity(96 + self.message.len());
+        let _ = write!(out, "<{}>1 ", FACILITY * 8 + severity(self.level));
+        match self.unix_ms {
+            Some(ms) => write_timestamp(&mut out, ms),
+            None => out.push('-'),
+        }
+        let _ = write!(out, " {} {} - {} - {}", header_field(self.hostname, 255), APP_NAME, header_field(self.module, 32), self.message);
+        if out.len() > MAX_MESSAGE_LEN {
+            let mut end = MAX_MESSAGE_LEN;
+            while !out.is_char_boundary(end) {
+                end -= 1;
+            }
+            out.truncate(end);
+        }
+        out
+    }
+}
+
+/// Header fields are 1-`max` printable ASCII characters; "-" (NILVALUE) when
+/// nothing is left
+fn header_field(value: &str, max: usize) -> String {
+    let field: String = value.chars().filter(|c| c.is_ascii_graphic()).take(max).collect();
+    if field.is_empty() { "-".to_string() } else { field }
+}
+
+/// UTC, millisecond precision: 2026-10-16T08:30:05.123Z
+fn write_timestamp(out: &mut String, unix_ms: u64) {
+    let secs = unix_ms / 1000;
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let time = secs % 86_400;
+    let _ = write!(out, "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year, month, day, time / 3600, time % 3600 / 60, time % 60, unix_ms % 1000);
+}
+
+#[cfg(target_os = "espidf")]
+struct Entry {
+    level: Level,
+    unix_ms: Option<u64>,
+    module: String,
+    message: String,
+}
+
+/// Set while there is a server to send to, so the logger skips the copy otherwise
+#[cfg(target_os = "espidf")]
+static ENABLED: AtomicBool = AtomicBool::new(false);
+#[cfg(target_os = "espidf")]
+static QUEUE: OnceLock<SyncSender<Entry>> = OnceLock::new();
+
+/// Queue a record (called by the logger for every line it prints)
+#[cfg(target_os = "espidf")]
+pub fn forward(level: Level, module: &str, message: &str) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    if let Some(queue) = QUEUE.get() {
+        let _ = queue.try_send(Entry { level, unix_ms: wall_clock_ms(), module: module.to_string(), message: message.to_string() });
+    }
+}
+
+/// Wall-clock ms only if the RTC looks synced (after 2020-01-01)
+#[cfg(target_os = "espidf")]
+fn wall_clock_ms() -> Option<u64> {
+    let ms = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok()?.as_millis() as u64;
+    (ms > 1_577_836_800_000).then_some(ms)
+}
+
+#[cfg(target_os = "espidf")]
+fn resolve(server: &str) -> Option<(UdpSocket, SocketAddr)> {
+    let (host, port) = parse_server(server)?;
+    let addr = (host, port).to_socket_addrs().ok()?.next()?;
+    let socket = UdpSocket::bind(if addr.is_ipv6() { "[::]:0" } else { "0.0.0.0:0" }).ok()?;
+    Some((socket, addr))
+}
+
+/// Start the forwarder thread. Settings are re-read from config every
+/// CONFIG_INTERVAL so changes made through the web UI apply without a restart.
+/// Safe to call multiple times.
+#[cfg(target_os = "espidf")]
+pub fn start(config: Arc<Mutex<Config>>, shutdown: ShutdownSignal) -> Result<()> {
+    let (sender, receiver) = mpsc::sync_channel::<Entry>(QUEUE_LEN);
+    if QUEUE.set(sender).is_err() {
+        return Ok(());
+    }
+
+    std::thread::Builder::new()
+        .name("syslog".to_string())
+        .stack_size(6144)
+        .spawn(move || {
+            let mut server = String::new();
+            let mut target: Option<(UdpSocket, SocketAddr)> = None;
+            let mut hostname = String::new();
+            let mut last_check: Option<Instant> = None;
+            loop {
+                if shutdown.is_shutdown_requested() {
+                    ENABLED.store(false, Ordering::Relaxed);
+                    return;
+                }
+                if last_check.is_none_or(|t| t.elapsed() >= CONFIG_INTERVAL) {
+                    last_check = Some(Instant::now());
+                    let (enabled, configured) = config.lock()
+                        .map(|cfg| (cfg.syslog_enabled, cfg.syslog_server.clone()))
+                        .unwrap_or_default();
+                    let configured = if enabled { configured } else { String::new() };
+                    if configured != server {
+                        server = configured;
+                        target = None;
+                        ENABLED.store(false, Ordering::Relaxed);
+                        if !server.is_empty() {
+                            log::info!("Syslog: forwarding to {}", server);
+                        }
+                    }
+                    // Looked up again after a failure, and only once WiFi is up
+                    if !server.is_empty() && target.is_none() && crate::network::wifi_stats::snapshot().connected {
+                        target = resolve(&server);
+                        ENABLED.store(target.is_some(), Ordering::Relaxed);
+                    }
+                    hostname = crate::network::hostname();
+                }
+
+                match receiver.recv_timeout(Duration::from_secs(1)) {
+                    Ok(entry) => {
+                        let Some((socket, addr)) = target.as_ref() else { continue };
+                        let message = SyslogMessage {
+                            level: entry.level,
+                            unix_ms: entry.unix_ms,
+                            hostname: &hostname,
+                            module: &entry.module,
+                            message: &entry.message,
+                        };
+                        // Not logged: the failure would be forwarded too
+                        let _ = socket.send_to(message.format().as_bytes(), addr);
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc5424_format() {
+        let message = SyslogMessage {
+            level: Level::Warn,
+            unix_ms: Some(1_792_139_405_123),
+            hostname: "esp32-dashboard-a1b2",
+            module: "network::wifi",
+            message: "Disconnected (beacon_timeout)",
+        };
+        assert_eq!(
+            message.format(),
+            "<132>1 2026-10-16T08:30:05.123Z esp32-dashboard-a1b2 esp32-dashboard - network::wifi - Disconnected (beacon_timeout)"
+        );
+
+        // Unsynced clock and empty header fields become NILVALUE
+        let message = SyslogMessage { level: Level::Error, unix_ms: None, hostname: "", module: "a b", message: "x" };
+        assert_eq!(message.format(), "<131>1 - - esp32-dashboard - ab - x");
+
+        let long = "é".repeat(MAX_MESSAGE_LEN);
+        let message = SyslogMessage { level: Level::Info, unix_ms: None, hostname: "h", module: "m", message: &long };
+        let formatted = message.format();
+        assert!(formatted.len() <= MAX_MESSAGE_LEN && formatted.len() > MAX_MESSAGE_LEN - 2);
+        assert!(formatted.starts_with("<134>1 "));
+    }
+
+    #[test]
+    fn test_severity_and_server() {
+        let severities: Vec<u8> = [Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace].into_iter().map(severity).collect();
+        assert_eq!(severities, [3, 4, 6, 7, 7]);
+
+        assert_eq!(parse_server("logs.lan"), Some(("logs.lan", 514)));
+        assert_eq!(parse_server("10.0.0.5:1514"), Some(("10.0.0.5", 1514)));
+        for bad in ["", ":514", "logs.lan:0", "logs.lan:x", "udp://logs.lan", "logs lan"] {
+            assert_eq!(parse_server(bad), None, "{:?}", bad);
+        }
+    }
+}
@@ -24,6 +24,10 @@ mod app_desc {
 mod boot;
 mod config;
 mod display;
+mod error;
+mod hardware;
+mod i18n;
+mod input;
 mod network;
 mod ota;
 mod sensors;
@@ -33,6 +37,8 @@ mod diagnostics;
 mod crash_diagnostics;
 mod crash_persist;
 mod ui;
+mod text_buf;
+mod units;
 mod version;
 mod dual_core;
 mod psram;
@@ -40,12 +46,15 @@ mod performance;
 mod core1_tasks;
 mod logging;
 mod metrics;
+mod metrics_data;
 mod metrics_formatter;
 mod metrics_rwlock;
 mod feature_gates;
-// mod ring_buffer;  // TODO: Integrate ring buffer optimization
+mod ring_buffer;
 mod templates;
 mod power;
+mod storage;
+mod trace;
 
 use crate::boot::{BootManager, BootStage};
 use crate::display::{DisplayManager, colors};
@@ -55,7 +64,7 @@ use crate::ui::UiManager;
 use crate::system::{ShutdownManager, ShutdownSignal};
 use crate::dual_core::{DualCoreProcessor, CpuMonitor};
 use crate::performance::PerformanceMetrics;
-use crate::power::{PowerManager, PowerConfig};
+use crate::power::{PowerManager, PowerConfig, TaskPowerManager};
 
 // Global error storage for web server initialization (safe)
 use std::sync::OnceLock;
@@ -131,6 +140,9 @@ fn main() -> Result<()> {
         }
     }));
     
+    // Take the crash-safe log ring's contents before this boot's logging reuses it
+    let previous_boot_log = crate::storage::log_ring::recover();
+    
     // Initialize our logger with colors and timestamps
     if let Err(e) = logging::init_logger() {
         log::error!("Logger initialization failed: {:?}", e);
@@ -139,6 +151,14 @@ fn main() -> Result<()> {
 
     // Initialize in-memory log streamer early so logs are captured from boot
     crate::network::log_streamer::init(None);
+    
+    if let Some(log) = previous_boot_log {
+        let reason = crate::system::reset::get_reset_reason();
+        match crate::storage::log_ring::save_previous(reason, &log) {
+            Ok(()) => log::warn!("{} reset: previous boot's log saved to /api/logs/previous-boot", reason),
+            Err(e) => log::error!("Failed to save previous boot's log: {:?}", e),
+        }
+    }
 
     // Start periodic crash/health diagnostics
     crate::crash_diagnostics::init();
@@ -158,6 +178,15 @@ fn main() -> Result<()> {
     // Check reset reason and log it
     let reset_reason_str = crate::system::reset::get_reset_reason();
     log::info!("Boot reason: {}", reset_reason_str);
+    // One shared NVS handle for config, counters and logs (WiFi uses it too)
+    if let Err(e) = crate::storage::kv::init() {
+        log::error!("NVS unavailable, settings will not persist: {:?}", e);
+    }
+    crate::power::events::init();
+    crate::system::lifetime_stats::init();
+    crate::network::disconnect_reasons::init();
+    // Repeated early crashes: boot with only WiFi, the web server and OTA
+    let safe_mode = crate::system::safe_mode::init();
     
     let reset_reason = unsafe { esp_idf_sys::esp_reset_reason() };
     let is_ota_restart = match reset_reason {
@@ -226,20 +255,7 @@ fn main() -> Result<()> {
     }
     
     // Configure power management for dynamic frequency scaling
-    unsafe {
-        use esp_idf_sys::*;
-        let pm_config = esp_pm_config_esp32s3_t {
-            max_freq_mhz: 240,  // Maximum frequency
-            min_freq_mhz: 80,   // Minimum frequency when idle
-            light_sleep_enable: false, // Keep false for responsiveness
-        };
-        let result = esp_pm_configure(&pm_config as *const esp_pm_config_esp32s3_t as *const core::ffi::c_void);
-        if result == ESP_OK {
-            info!("Power management configured: 80-240MHz DFS");
-        } else {
-            log::warn!("Failed to configure power management: {:?}", result);
-        }
-    }
+    crate::power::thermal::configure_cpu(crate::power::thermal::NORMAL_MAX_FREQ_MHZ);
 
     // Take peripherals and system event loop
     let peripherals = Peripherals::take()?;
@@ -259,30 +275,6 @@ fn main() -> Result<()> {
         );
     }
 
-    // Debug flag - set to true to run display tests
-    // Change this to true and recompile to run baseline performance test
-    const RUN_DISPLAY_DEBUG_TEST: bool = false;
-    
-    if RUN_DISPLAY_DEBUG_TEST {
-        log::warn!("Running display debug tests - normal boot disabled");
-        log::warn!("Set RUN_DISPLAY_DEBUG_TEST to false for normal operation");
-        
-        // CRITICAL: Wait for power to stabilize before initializing display
-        // Use RTOS-friendly delay (yield) instead of ROM busy-wait
-        esp_idf_hal::delay::FreeRtos::delay_ms(500);  // Longer delay for power stability
-        
-        // LCD_CAM tests disabled - hardware acceleration not currently used
-        log::warn!("LCD_CAM tests have been disabled");
-        log::warn!("Set RUN_DISPLAY_DEBUG_TEST to false for normal operation");
-        
-        log::warn!("Test complete!");
-        
-        // Keep running
-        loop {
-            esp_idf_hal::delay::FreeRtos::delay_ms(1000);
-        }
-    }
-    
     // Initialize display
     info!("Initializing display...");
     
@@ -311,6 +303,12 @@ fn main() -> Result<()> {
         peripherals.pins.gpio9,  // RD pin
     )?;
     info!("Display initialized - LCD power and backlight pins kept alive");
+    let boot_fade_ms = config.lock().map(|cfg| cfg.backlight_fade_ms)
+        .unwrap_or(crate::power::backlight::DEFAULT_FADE_MS);
+    if let Err(e) = display_manager.fade_backlight_in(std::time::Duration::from_millis(boot_fade_ms as u64)) {
+        log::warn!("Backlight fade-in failed: {:?}", e);
+        display_manager.set_backlight(255)?;
+    }
     
     // Initialize metrics system AFTER display is working
     crate::metrics::init_metrics();
@@ -322,9 +320,9 @@ fn main() -> Result<()> {
     let shutdown_signal = shutdown_manager.lock().map(|m| m.get_signal()).unwrap_or_else(|_| ShutdownSignal::new());
     info!("Shutdown manager initialized");
 
-    // ESP_LCD: Fast initialization path
+    // ESP_LCD: Fast initialization path (safe mode takes the regular path below)
     #[cfg(feature = "esp_lcd_driver")]
-    {
+    if !safe_mode {
         info!("ESP_LCD: Fast initialization path - skipping all boot animations");
         
         // Simple startup screen
@@ -517,7 +515,7 @@ fn main() -> Result<()> {
             ui_manager,
             display_manager,
             sensor_manager,
-            button_manager,
+            input::local_sources(button_manager, &config),
             network_manager,
             config,
             web_server,
@@ -672,7 +670,6 @@ fn main() -> Result<()> {
     for i in 0..10 {
         boot_manager.render_boot_screen(&mut display_manager)?;
         display_manager.flush()?;
-        display_manager.update_auto_dim(true)?; // Keep display alive during boot
         
         // Extra safety - ensure power pins stay high
         display_manager.ensure_display_on()?;
@@ -826,6 +823,62 @@ fn main() -> Result<()> {
         None
     };
 
+    // Wall clock for scheduled profiles (SNTP keeps retrying until the network is up)
+    if let Err(e) = system::clock::start_sntp() {
+        log::warn!("Failed to start SNTP: {:?}", e);
+    }
+
+    // Remote syslog, also in safe mode (idle until a server is configured)
+    if let Err(e) = logging::syslog::start(config.clone(), shutdown_signal.clone()) {
+        log::warn!("Failed to start syslog forwarder: {:?}", e);
+    }
+
+    // Pollers and optional services, none of which OTA needs
+    if safe_mode {
+        log::warn!("Safe mode: network pollers, remote config sync, profiles and BLE not started");
+    } else {
+        // Optional metrics push exporter (idle until enabled in config)
+        if let Err(e) = network::push_exporter::start(config.clone(), shutdown_signal.clone()) {
+            log::warn!("Failed to start push exporter: {:?}", e);
+        }
+
+        // Weather client (idle until a location is configured)
+        if let Err(e) = network::weather::start(config.clone(), shutdown_signal.clone()) {
+            log::warn!("Failed to start weather client: {:?}", e);
+        }
+
+        // Custom REST widgets (idle until widgets are configured)
+        if let Err(e) = network::rest_widgets::start(config.clone(), shutdown_signal.clone()) {
+            log::warn!("Failed to start REST widget poller: {:?}", e);
+        }
+
+        // Alertmanager poller (idle until a URL is configured)
+        if let Err(e) = network::alertmanager::start(config.clone(), shutdown_signal.clone()) {
+            log::warn!("Failed to start Alertmanager poller: {:?}", e);
+        }
+
+        // Calendar poller for the Agenda screen (idle until a URL is configured)
+        if let Err(e) = network::agenda::start(config.clone(), shutdown_signal.clone()) {
+            log::warn!("Failed to start calendar poller: {:?}", e);
+        }
+
+        // Remote config sync (idle until a URL is configured)
+        if let Err(e) = config::remote_sync::start(config.clone(), shutdown_signal.clone()) {
+            log::warn!("Failed to start remote config sync: {:?}", e);
+        }
+
+        // Day/night profile scheduler (idle until enabled in config)
+        if let Err(e) = config::profiles::start(config.clone(), shutdown_signal.clone()) {
+            log::warn!("Failed to start profile scheduler: {:?}", e);
+        }
+
+        // BLE status/provisioning server runs regardless of WiFi state
+        #[cfg(feature = "ble")]
+        if let Err(e) = network::ble::start(config.clone()) {
+            log::warn!("Failed to start BLE server: {:?}", e);
+        }
+    }
+
     // Complete boot sequence
     boot_manager.set_stage(BootStage::Complete);
     for i in 0..10 {
@@ -885,22 +938,23 @@ fn main() -> Result<()> {
     // Start main application loop
     info!("Starting main loop - UI should now be visible");
     
-    // Ensure backlight is on before entering main loop
-    display_manager.update_auto_dim(true)?; // Keep display on during startup
-    
     info!("Entering run_app function now...");
     
     // Run the main app with crash recovery
     // Initialize Core 1 tasks
     let (mut core1_manager, core1_channels) = core1_tasks::Core1Manager::new()?;
-    core1_manager.start()?;
-    info!("Core 1 background tasks started");
+    if safe_mode {
+        log::warn!("Safe mode: Core 1 tasks not started");
+    } else {
+        core1_manager.start()?;
+        info!("Core 1 background tasks started");
+    }
     
     match run_app(
         ui_manager,
         display_manager,
         sensor_manager,
-        button_manager,
+        input::local_sources(button_manager, &config),
         network_manager,
         config,
         web_server,
@@ -916,7 +970,7 @@ fn main() -> Result<()> {
         Err(e) => {
             log::error!("UI loop crashed: {:?}", e);
             log::error!("Restarting system to recover...");
-            unsafe { esp_idf_sys::esp_restart(); }
+            system::shutdown::restart_now(system::shutdown::RestartReason::Crash);
         }
     }
 
@@ -927,10 +981,10 @@ fn run_app(
     mut ui_manager: UiManager,
     mut display_manager: DisplayManager,
     mut sensor_manager: sensors::SensorManager,
-    mut button_manager: system::ButtonManager,
-    network_manager: NetworkManager,
+    mut input_sources: Vec<Box<dyn input::InputSource>>,
+    mut network_manager: NetworkManager,
     _config: Arc<Mutex<config::Config>>,
-    _web_server: Option<network::web_server::WebConfigServer>,
+    web_server: Option<network::web_server::WebConfigServer>,
     ota_manager: Option<Arc<Mutex<OtaManager>>>,
     _telnet_server: Option<Arc<TelnetLogServer>>,
     core1_channels: core1_tasks::Core1Channels,
@@ -949,6 +1003,20 @@ fn run_app(
         power_save_brightness: 10,
     };
     let mut power_manager = PowerManager::new(power_config);
+    let mut thermal_policy = crate::power::thermal::ThermalPolicy::default();
+    // Battery profile: WiFi duty cycle, frame interval and deep sleep
+    let mut task_power_manager = TaskPowerManager::new();
+    let mut last_frame = Instant::now();
+    let mut input_since_frame = false;
+    // Trace of the last web-triggered input, entered while the frame it caused renders
+    let mut frame_trace: Option<trace::TraceId> = None;
+    // Backlight switched off by a toggle_display button action, and whether it
+    // was lit on the last pass (the toggle turns a dimmed screen back on)
+    let mut display_toggled_off = false;
+    let mut display_lit = true;
+    // Burn-in mitigation: UI offset cycle and idle screensaver
+    let mut pixel_shift = crate::display::burn_in::PixelShift::new(Instant::now());
+    let mut screensaver = crate::display::burn_in::Screensaver::new();
     let _web_dim_deadline: Option<std::time::Instant> = None;
     
     // CRITICAL: Mark activity immediately to prevent instant sleep
@@ -979,7 +1047,8 @@ fn run_app(
     // Main UI loop with performance telemetry
     // Display hardware limitation: ~10 FPS max with parallel GPIO
     const DISPLAY_MAX_FPS: f32 = 10.0;
-    let _target_frame_time = Duration::from_millis(100); // ~10 FPS
+    // Target FPS and pacing strategy come from the config (applied each frame)
+    let mut frame_pacer = display::pacing::FramePacer::default();
     // Sensor update interval currently unused; network status is refreshed separately
     // let mut last_sensor_update = Instant::now();
     // let sensor_update_interval = Duration::from_secs(10);
@@ -993,6 +1062,60 @@ fn run_app(
     // OTA status update timer
     let mut last_ota_check = Instant::now();
     let ota_check_interval = Duration::from_secs(1);
+    let mut last_weather_generation = 0u32;
+    let mut last_widget_generation = 0u32;
+    let mut last_alerts_generation = 0u32;
+    let mut last_agenda_generation = 0u32;
+
+    // Safe mode leaves the optional peripherals and Core 1 alone
+    let safe_mode = system::safe_mode::active();
+    ui_manager.set_safe_mode(safe_mode);
+
+    // Optional LED/buzzer pulsed when a focus timer session completes
+    let timer_alert_gpio = _config.lock().ok().filter(|_| !safe_mode).and_then(|cfg| cfg.timer_alert_gpio);
+    let mut timer_alert_pin = timer_alert_gpio.and_then(|gpio| {
+        system::alert_pin::AlertPin::new(gpio)
+            .map_err(|e| log::warn!("Timer alert pin unavailable: {:?}", e))
+            .ok()
+    });
+
+    // Optional piezo for event sounds
+    if let Some(gpio) = _config.lock().ok().filter(|_| !safe_mode).and_then(|cfg| cfg.buzzer_gpio) {
+        if let Err(e) = hardware::buzzer::init(gpio) {
+            log::warn!("Buzzer unavailable: {:?}", e);
+        }
+    }
+    // Optional WS2812 status LEDs
+    if let Some((gpio, count)) = _config.lock().ok().filter(|_| !safe_mode).and_then(|cfg| cfg.led_gpio.map(|g| (g, cfg.led_count))) {
+        if let Err(e) = hardware::status_led::init(gpio, count) {
+            log::warn!("Status LEDs unavailable: {:?}", e);
+        }
+    }
+    // Optional INA219 power monitor
+    let power_monitor = _config.lock().ok().filter(|_| !safe_mode).and_then(|cfg| {
+        Some((cfg.power_monitor_sda?, cfg.power_monitor_scl?, cfg.power_monitor_address, cfg.power_monitor_shunt_mohm))
+    });
+    if let Some((sda, scl, address, shunt_mohm)) = power_monitor {
+        if let Err(e) = hardware::power_monitor::init(sda, scl, address, shunt_mohm) {
+            log::warn!("Power monitor unavailable: {:?}", e);
+        }
+    }
+    // Optional SCD40 / SGP30 air quality sensors
+    let air_quality = _config.lock().ok().filter(|_| !safe_mode).and_then(|cfg| Some((cfg.air_quality_sda?, cfg.air_quality_scl?)));
+    if let Some((sda, scl)) = air_quality {
+        if let Err(e) = sensors::air_quality::init(sda, scl) {
+            log::warn!("Air quality sensors unavailable: {:?}", e);
+        }
+    }
+    let mut last_alert_total = 0usize;
+    
+    // Panel init retry while headless
+    let mut last_display_retry = Instant::now();
+    let display_retry_interval = Duration::from_secs(30);
+    if !display_manager.is_ready() {
+        log::warn!("Running headless: web server, metrics and OTA stay available; retrying display every {}s",
+            display_retry_interval.as_secs());
+    }
     
     // Watchdog reset tracking
     let mut last_watchdog_reset = Instant::now();
@@ -1024,21 +1147,30 @@ fn run_app(
     #[cfg(feature = "esp_lcd_driver")]
     let memory_check_interval = Duration::from_secs(30);
     
-    // Logging optimization - track previous values to reduce redundant logs
-    let mut last_logged_fps: f32 = 0.0;
-    let mut last_logged_cpu0: u8 = 0;
-    let mut last_logged_cpu1: u8 = 0;
-    const FPS_CHANGE_THRESHOLD: f32 = 5.0;  // Only log if FPS changes by more than 5
-    const CPU_CHANGE_THRESHOLD: u8 = 10;    // Only log if CPU usage changes by more than 10%
+    // SSID last sent to Core 1 for the metrics store
+    let mut last_reported_ssid = String::new();
+    // Counted here, added to the lifetime stats with each one-second report
+    let mut frames_since_report = 0u64;
+    let mut frame_allocs = crate::network::heap_tracker::FrameAllocStats::default();
+    let mut last_wifi_reconnects = 0u32;
+    const SLOW_FRAME: Duration = Duration::from_millis(250); // Name overlapping spans beyond this
     
     log::info!("Main render loop started - entering infinite loop");
 
     // Sensor reading stays on Core 0 but we'll minimize the work
     let mut last_sensor_reading = Instant::now();
-    let sensor_reading_interval = Duration::from_secs(5); // Read sensors every 5 seconds
+    // From the config's sample schedule (re-read every frame); None = sensors off
+    let mut sensor_reading_interval = Some(Duration::from_secs(5));
     let sensor_tx = core1_channels.sensor_tx.clone();
+    // Set when a restart was requested through system::shutdown
+    let mut restart = None;
     
     loop {
+        if let Some(reason) = system::shutdown::pending_restart() {
+            log::info!("Restart pending, exiting main loop...");
+            restart = Some(reason);
+            break;
+        }
         // Check for shutdown signal
         if shutdown_signal.is_shutdown_requested() {
             log::info!("Shutdown requested, exiting main loop...");
@@ -1049,9 +1181,10 @@ fn run_app(
         let frame_start = Instant::now();
         
         // Send sensor data to Core 1 for processing
-        if last_sensor_reading.elapsed() >= sensor_reading_interval {
+        if sensor_reading_interval.is_some_and(|interval| last_sensor_reading.elapsed() >= interval) {
             // Sample sensors quickly on Core 0
             if let Ok(sensor_result) = sensor_manager.sample() {
+                crate::power::events::record_voltage(sensor_result._battery_voltage);
                 let (cpu0_usage, cpu1_usage) = cpu_monitor.get_cpu_usage();
                 
                 // Send to Core 1 for processing
@@ -1065,42 +1198,123 @@ fn run_app(
                     cpu_usage_core1: cpu1_usage,
                 };
                 
-                // Non-blocking send to Core 1
-                if let Err(_) = sensor_tx.send(sensor_update.clone()) {
+                // Non-blocking send to Core 1; in safe mode Core 1 is not
+                // running and the raw reading goes straight to the UI
+                if safe_mode {
+                    ui_manager.update_sensor_data(sensor_result);
+                } else if let Err(_) = sensor_tx.send(sensor_update.clone()) {
                     log::warn!("Core 1 sensor queue full, skipping update");
                 }
             }
             last_sensor_reading = Instant::now();
         }
 
-        // Handle button input with debounce (only check every 20ms)
+        // Hardware self-test (both buttons held 5 s or POST /api/selftest)
+        if system::selftest::take_request() {
+            system::selftest::run(&mut display_manager, &mut sensor_manager, &mut input_sources);
+            ui_manager.redraw_all();
+            display_manager.reset_activity_timer();
+            power_manager.activity_detected();
+            input_since_frame = true;
+        }
+
+        // Display benchmark (POST /api/display/benchmark)
+        if display::benchmark::take_request() {
+            display::benchmark::run(&mut display_manager, &mut input_sources);
+            ui_manager.redraw_all();
+            display_manager.reset_activity_timer();
+            power_manager.activity_detected();
+            input_since_frame = true;
+        }
+
+        // Handle input with debounce (only check every 20ms). Local sources publish to
+        // the input bus alongside remote ones (web UI); the UI takes one event per tick.
         if last_button_check.elapsed() >= button_check_interval {
             let poll_start = Instant::now();
-            if let Some(event) = button_manager.poll() {
+            for source in input_sources.iter_mut() {
+                if let Some(event) = source.poll() {
+                    input::publish(event, input::Origin::Local);
+                }
+            }
+            if let Some((event, trace_id)) = input::next_event() {
+                let _trace = trace_id.map(trace::enter);
+                frame_trace = trace_id.or(frame_trace);
                 let response_time = poll_start.elapsed();
-                log::info!("[BUTTON_TEST] Button event detected: {:?}, Poll latency: {:.2}ms, Time since last check: {:.2}ms", 
+                log::info!("[BUTTON_TEST] Input event: {:?}, Poll latency: {:.2}ms, Time since last check: {:.2}ms", 
                     event, 
                     response_time.as_secs_f32() * 1000.0,
                     last_button_check.elapsed().as_secs_f32() * 1000.0
                 );
                 
                 // Check for shutdown trigger
-                if event == system::ButtonEvent::BothButtonsLongPress {
+                if event == input::InputEvent::ShutdownCombo {
                     log::warn!("Shutdown triggered by button combination!");
                     if let Ok(mut mgr) = shutdown_manager.lock() { mgr.shutdown()?; }
                     break;
                 }
+                if event == input::InputEvent::SelfTestCombo {
+                    system::selftest::request();
+                }
+                
+                // Apply the user's button mapping between the bus and the UI
+                let (button_map, mqtt_broker) = _config.lock()
+                    .map(|cfg| (cfg.button_actions.clone(), cfg.mqtt_broker.clone()))
+                    .unwrap_or_default();
+                let dispatch = button_map.dispatch(event);
+                if dispatch != input::action_map::Dispatch::Device(input::action_map::ButtonAction::ToggleDisplay) {
+                    display_toggled_off = false;
+                }
                 
                 let ui_start = Instant::now();
-                ui_manager.handle_button_event(event)?;
+                match dispatch {
+                    input::action_map::Dispatch::Ui(event) => ui_manager.handle_input(event)?,
+                    input::action_map::Dispatch::Device(action) => {
+                        use input::action_map::ButtonAction;
+                        log::info!("Button action: {:?}", action);
+                        match action {
+                            ButtonAction::ToggleDisplay => display_toggled_off = display_lit,
+                            ButtonAction::OtaCheck => {
+                                if !ui_manager.show_screen("ota") {
+                                    log::warn!("OTA screen is not in the screen order");
+                                }
+                            }
+                            ButtonAction::SelfTest => {
+                                system::selftest::request();
+                            }
+                            ButtonAction::Mqtt { .. } if safe_mode => log::warn!("Safe mode: MQTT action skipped"),
+                            ButtonAction::Mqtt { topic, payload } => {
+                                if let Err(e) = network::mqtt::publish_command(mqtt_broker, topic, payload) {
+                                    log::warn!("MQTT action failed to start: {:?}", e);
+                                }
+                            }
+                            ButtonAction::None | ButtonAction::NextScreen | ButtonAction::PrevScreen => {}
+                        }
+                    }
+                    input::action_map::Dispatch::Ignore => {}
+                }
                 let ui_time = ui_start.elapsed();
+                if let Some(change) = ui_manager.take_setting_change() {
+                    if let Ok(mut cfg) = _config.lock() {
+                        match change {
+                            ui::SettingChange::Brightness(b) => cfg.brightness = b,
+                            ui::SettingChange::TimerMinutes(m) => cfg.pomodoro_minutes = m,
+                            ui::SettingChange::Accessibility(enabled) => cfg.accessibility_mode = enabled,
+                        }
+                        if let Err(e) = cfg.save() {
+                            log::warn!("Failed to save setting from encoder: {:?}", e);
+                        }
+                    }
+                }
                 
-                // Reset activity timer on button press
+                // Reset activity timer on any input
                 display_manager.reset_activity_timer();
                 power_manager.activity_detected();
+                input_since_frame = true;
+                hardware::notify(hardware::Notification::ButtonClick);
                 
                 let total_time = response_time + ui_time;
                 button_events_count += 1;
+                crate::system::lifetime_stats::record(crate::system::lifetime_stats::Counter::ButtonPresses, 1);
                 total_response_time += total_time;
                 if total_time > max_response_time {
                     max_response_time = total_time;
@@ -1124,22 +1338,14 @@ fn run_app(
                     0.0
                 };
                 
-                // Update metrics
-                {
-                    let mut metrics = match crate::metrics::metrics().lock() {
-                        Ok(m) => m,
-                        Err(e) => {
-                            log::error!("Failed to lock metrics: {}", e);
-                            continue;
-                        }
-                    };
-                    metrics.update_button_metrics(
-                        avg_response.as_secs_f32() * 1000.0,
-                        max_response_time.as_secs_f32() * 1000.0,
-                        button_events_count as u64,
-                        events_per_sec
-                    );
-                }
+                // Update metrics (straight into the store: a MetricsGuard copies
+                // every metric and writes them all back, racing Core 1's updates)
+                crate::metrics_rwlock::metrics().update_button_metrics(
+                    avg_response.as_secs_f32() * 1000.0,
+                    max_response_time.as_secs_f32() * 1000.0,
+                    button_events_count as u64,
+                    events_per_sec
+                );
                 
                 // Print summary every 10 button presses
                 if button_events_count % 10 == 0 {
@@ -1168,15 +1374,35 @@ fn run_app(
                     processed_data.temperature, processed_data.battery_percentage);
             }
             
+            if let Some(ref tracker) = uptime_tracker {
+                if let Ok(mut t) = tracker.lock() {
+                    t.observe_temperature(processed_data.temperature);
+                }
+            }
+            
             // Update UI with processed sensor data
-            ui_manager.update_sensor_data(sensors::SensorData {
+            let sensor_data = sensors::SensorData {
                 _temperature: processed_data.temperature,
                 _battery_percentage: processed_data.battery_percentage,
                 _battery_voltage: processed_data.battery_voltage,
                 _is_charging: processed_data.is_charging,
                 _is_on_usb: processed_data.is_on_usb,
                 _light_level: 0,
-            });
+            };
+            let thermal_limit = _config.lock().map(|cfg| cfg.thermal_limit_c)
+                .unwrap_or(crate::power::thermal::DEFAULT_LIMIT_C);
+            if let Some(throttled) = thermal_policy.observe(&sensor_data, thermal_limit) {
+                power_manager.set_brightness_cap(throttled.then_some(crate::power::thermal::THROTTLED_BRIGHTNESS));
+                network_manager.set_mdns_paused(throttled);
+            }
+            ui_manager.update_sensor_data(sensor_data);
+            ui_manager.update_power_warning(crate::power::events::counts().warning());
+            ui_manager.update_power_monitor(hardware::power_monitor::snapshot());
+            let readings = sensors::registry::snapshot();
+            if let Some(lux) = readings.iter().find(|r| r.reading.unit == "lux") {
+                crate::power::adaptive_brightness::observe(lux.reading.value);
+            }
+            ui_manager.update_sensor_readings(readings);
             
             // Update CPU usage display
             ui_manager.update_cpu_usage(
@@ -1184,30 +1410,7 @@ fn run_app(
                 processed_data.cpu_usage_core1
             );
             
-            // Update temperature and battery in metrics
-            {
-                let mut metrics = match crate::metrics::metrics().lock() {
-                    Ok(m) => m,
-                    Err(e) => {
-                        log::error!("Failed to lock metrics: {}", e);
-                        continue;
-                    }
-                };
-                metrics.update_temperature(processed_data.temperature);
-                metrics.update_battery(
-                    processed_data.battery_voltage,
-                    processed_data.battery_percentage,
-                    processed_data.is_charging
-                );
-            }
-            
-            // Update sensor history
-            if let Some(history) = crate::sensors::history::get() {
-                if let Ok(hist) = history.lock() {
-                    hist.add_temperature(processed_data.temperature);
-                    hist.add_battery(processed_data.battery_percentage as f32);
-                }
-            }
+            // Metrics and sensor history were already updated on Core 1
             
             // TEMPORARILY DISABLED: Update power manager with sensor data (skip during startup grace period)
             // if startup_time.elapsed() > startup_grace_period {
@@ -1230,28 +1433,79 @@ fn run_app(
         // Update network status periodically
         // Update network status frequently so on-device screen reflects real state
         if last_network_update.elapsed() >= network_update_interval {
+            hardware::notify(hardware::Notification::WifiState(network_manager.is_connected()));
             ui_manager.update_network_status(
                 network_manager.is_connected(),
                 network_manager.get_ip(),
+                network_manager.get_ipv6(),
                 network_manager.get_ssid().to_string(),
                 network_manager.get_signal_strength(),
                 network_manager.get_gateway(),
                 network_manager.get_mac(),
             );
+            let latency = network::latency_monitor::snapshot();
+            ui_manager.update_latency(latency.gateway.sparkline(), latency.host.sparkline());
+            let reasons = network::disconnect_reasons::histogram();
+            ui_manager.update_disconnect_reasons(network::disconnect_reasons::summary(&reasons.top(2)));
+            network_manager.refresh_mdns();
             last_network_update = Instant::now();
         }
         
         // Update OTA status periodically (if OTA is available)
         if last_ota_check.elapsed() >= ota_check_interval {
-            if let Some(ref ota_mgr) = ota_manager {
-                let ota_status = match ota_mgr.lock() {
-                    Ok(mgr) => mgr.get_status(),
-                    Err(e) => {
-                        log::error!("Failed to lock OTA manager: {}", e);
-                        continue;
+            // Read the published snapshot: the upload handler holds the manager lock
+            if let Some(ref mgr) = ota_manager {
+                let status = ota::progress::status();
+                ui_manager.update_ota_status(status);
+
+                // A staged update goes live once the maintenance window opens
+                if status == ota::OtaStatus::Staged && ota::window::is_open(system::clock::local_minutes_of_day()) {
+                    if let Ok(mut ota) = mgr.try_lock() {
+                        if ota.is_staged() {
+                            match ota.apply_staged() {
+                                Ok(()) => {
+                                    log::info!("OTA: Maintenance window open, applying staged update");
+                                    hardware::notify(hardware::Notification::OtaComplete);
+                                    system::shutdown::request_restart(system::shutdown::RestartReason::OtaComplete);
+                                }
+                                Err(e) => log::error!("OTA: Failed to apply staged update: {}", e),
+                            }
+                        }
                     }
-                };
-                ui_manager.update_ota_status(ota_status);
+                }
+            }
+
+            // Pick up freshly fetched weather for the Weather screen
+            let weather_generation = network::weather::generation();
+            if weather_generation != last_weather_generation {
+                last_weather_generation = weather_generation;
+                if let Some(weather) = network::weather::latest() {
+                    ui_manager.update_weather(weather);
+                }
+            }
+
+            let widget_generation = network::rest_widgets::generation();
+            if widget_generation != last_widget_generation {
+                last_widget_generation = widget_generation;
+                ui_manager.update_custom_widgets(network::rest_widgets::values());
+            }
+
+            let alerts_generation = network::alertmanager::generation();
+            if alerts_generation != last_alerts_generation {
+                last_alerts_generation = alerts_generation;
+                let alerts = network::alertmanager::latest();
+                let alert_total = alerts.as_ref().map_or(0, |a| a.total);
+                if alert_total > last_alert_total {
+                    hardware::notify(hardware::Notification::AlertFired);
+                }
+                last_alert_total = alert_total;
+                ui_manager.update_ops_alerts(alerts);
+            }
+
+            let agenda_generation = network::agenda::generation();
+            if agenda_generation != last_agenda_generation {
+                last_agenda_generation = agenda_generation;
+                ui_manager.update_agenda(network::agenda::latest());
             }
             last_ota_check = Instant::now();
         }
@@ -1265,13 +1519,52 @@ fn run_app(
             if let Some(ref tracker) = uptime_tracker {
                 if let Ok(mut t) = tracker.lock() {
                     let _ = t.save_if_needed();
+                    ui_manager.update_reliability(&t.reliability_report());
                 }
             }
+            crate::system::lifetime_stats::save_if_due();
+            crate::network::disconnect_reasons::save_if_due();
+            ui_manager.update_lifetime_stats(crate::system::lifetime_stats::current());
+            system::safe_mode::observe_uptime(unsafe { esp_idf_sys::esp_timer_get_time() } as u64 / 1_000_000);
         }
         
         // Determine auto-dim state from recent activity
         let mut should_display_on = true;
+        let mut burn_in_shift = false;
         if let Ok(cfg) = _config.lock() {
+            ui_manager.set_timer_minutes(cfg.pomodoro_minutes);
+            ui_manager.set_brightness(cfg.brightness);
+            ui_manager.set_screen_order(&cfg.screen_order);
+            ui_manager.set_language(cfg.language);
+            ui_manager.set_units(cfg.temperature_unit, cfg.clock_format);
+            ui_manager.set_accessibility(cfg.accessibility_mode);
+            ui_manager.set_kiosk(cfg.kiosk_enabled && !safe_mode, &cfg.kiosk_metric);
+            ui_manager.set_animations(cfg.show_animations);
+            let brightness = if cfg.adaptive_brightness {
+                crate::power::adaptive_brightness::level(cfg.brightness).unwrap_or(cfg.brightness)
+            } else {
+                cfg.brightness
+            };
+            power_manager.configure_backlight(brightness, cfg.backlight_fade_ms, cfg.backlight_easing);
+            task_power_manager.set_battery_profile(cfg.battery_profile.then(|| crate::power::duty_cycle::DutyCycleConfig {
+                wake_interval: Duration::from_secs(cfg.battery_wifi_interval_mins as u64 * 60),
+                awake_window: Duration::from_secs(cfg.battery_wifi_window_secs as u64),
+                sleep_after: Duration::from_secs(cfg.battery_sleep_after_secs as u64),
+            }));
+            let quiet = crate::power::quiet_hours::update(
+                cfg.quiet_hours_enabled, cfg.quiet_start_minutes, cfg.quiet_end_minutes,
+                crate::system::clock::local_minutes_of_day(),
+            );
+            ota::window::configure(cfg.ota_window_enabled, cfg.ota_window_start_minutes, cfg.ota_window_end_minutes);
+            hardware::buzzer::configure(&cfg.buzzer_sounds, cfg.buzzer_muted || quiet);
+            hardware::status_led::configure(cfg.led_enabled && !quiet, cfg.led_brightness);
+            metrics_formatter::configure(&cfg.metrics_prefix, &cfg.metrics_labels);
+            burn_in_shift = cfg.burn_in_shift;
+            frame_pacer.configure(cfg.target_fps, cfg.frame_pacing);
+            sensor_reading_interval = cfg.sample_schedule.interval(config::schedule::DataSource::Sensors);
+            config::schedule::publish(&cfg.sample_schedule);
+            power_manager.set_screensaver((cfg.auto_brightness && cfg.burn_in_screensaver)
+                .then(|| Duration::from_secs(cfg.dim_timeout_secs.max(5) as u64)));
             if cfg.auto_brightness {
                 // Keep bright for a short time after boot
                 if startup_time.elapsed() < startup_grace_period {
@@ -1280,38 +1573,132 @@ fn run_app(
                 // Idle dimming using power manager idle timer
                 let idle_secs = power_manager.get_power_stats().idle_time.as_secs();
                 let dim_after = cfg.dim_timeout_secs.max(5) as u64;
-                should_display_on = should_display_on && (idle_secs < dim_after);
+                // With the screensaver the backlight stays on until the sleep timeout
+                if !cfg.burn_in_screensaver {
+                    should_display_on = should_display_on && (idle_secs < dim_after);
+                }
                 // Optional deeper sleep after longer idle
                 let sleep_after = cfg.sleep_timeout_secs.max(dim_after as u32) as u64;
                 if idle_secs >= sleep_after {
                     should_display_on = false;
                 }
             }
+            // Quiet hours: backlight off unless there was input within the dim timeout
+            if quiet && power_manager.get_power_stats().idle_time.as_secs() >= cfg.dim_timeout_secs.max(5) as u64 {
+                should_display_on = false;
+            }
+        }
+        if display_toggled_off {
+            should_display_on = false;
+        }
+        display_lit = should_display_on;
+        // Dim, sleep and wake fade; the only place the backlight is set
+        let backlight = power_manager.update_backlight(should_display_on);
+        display_manager.set_backlight(backlight)?;
+
+        // Battery profile: open/close WiFi windows, deep sleep between interactions
+        task_power_manager.update_wifi();
+        if task_power_manager.should_deep_sleep(power_manager.get_power_stats().idle_time) {
+            if let Some(ref tracker) = uptime_tracker {
+                if let Ok(mut t) = tracker.lock() {
+                    let _ = t.save_if_needed();
+                }
+            }
+            crate::system::lifetime_stats::save();
+            crate::network::disconnect_reasons::save();
+            display_manager.set_backlight(0)?;
+            crate::power::duty_cycle::enter_deep_sleep(task_power_manager.until_next_wifi_window());
         }
 
         // Update and render UI
         ui_manager.update()?;
+        if ui_manager.take_timer_finished() {
+            if let Some(ref mut pin) = timer_alert_pin {
+                pin.pulse(5);
+            }
+            hardware::notify(hardware::Notification::TimerDone);
+        }
+        if let Some(ref mut pin) = timer_alert_pin {
+            pin.update();
+        }
+        hardware::buzzer::update();
+        hardware::status_led::update();
+        hardware::power_monitor::update();
+        sensors::registry::update();
         
-        let render_start = Instant::now();
-        let rendered = ui_manager.render(&mut display_manager)?;
-        let render_time = render_start.elapsed();
-        
-        // Track whether frame was actually rendered or skipped
-        if rendered {
-            perf_metrics.record_render_time(render_time);
-            
-            display_manager.update_auto_dim(should_display_on)?;
-            
-            // Flush to display
-            let flush_start = Instant::now();
-            display_manager.flush()?;
-            let flush_time = flush_start.elapsed();
-            perf_metrics.record_flush_time(flush_time);
-        } else {
-            // Frame was skipped by UI manager
-            perf_metrics.fps_tracker.frame_skipped();
+        // Burn-in mitigation: nudge the whole UI every few minutes
+        if display_manager.is_ready() {
+            let (dx, dy) = if burn_in_shift {
+                pixel_shift.update(Instant::now(), crate::display::burn_in::SHIFT_INTERVAL);
+                pixel_shift.offset()
+            } else {
+                (0, 0)
+            };
+            if display_manager.set_pixel_shift(dx, dy)? {
+                ui_manager.redraw_all();
+                screensaver.reset();
+                input_since_frame = true;
+            }
+        }
+
+        // Panel watchdog: re-initialise a glitched panel without rebooting
+        match display_manager.check_health() {
+            Ok(true) => {
+                ui_manager.redraw_all();
+                screensaver.reset();
+                input_since_frame = true;
+            }
+            Ok(false) => {}
+            Err(e) => log::warn!("Display health check failed: {:?}", e),
+        }
+
+        // Headless: skip rendering and periodically retry the panel
+        if !display_manager.is_ready() {
+            if last_display_retry.elapsed() >= display_retry_interval {
+                last_display_retry = Instant::now();
+                if display_manager.retry_init() {
+                    ui_manager.redraw_all();
+                }
+            }
+        } else if power_manager.screensaver_active() {
+            let text = crate::system::clock::local_minutes_of_day()
+                .map(units::format_time_of_day)
+                .unwrap_or_else(|| "ESP32-S3".into());
+            if screensaver.draw(&mut display_manager, &text, ui::layout::SCREEN_WIDTH, ui::layout::SCREEN_HEIGHT)? {
+                display_manager.flush()?;
+            }
+        } else if power_manager.backlight_dark() {
+            // Nothing drawn now could be seen; the next frame renders as the
+            // backlight starts fading back in
+        } else if input_since_frame || last_frame.elapsed() >= task_power_manager.display_refresh_rate() {
+            last_frame = Instant::now();
+            input_since_frame = false;
+            if screensaver.is_showing() {
+                screensaver.reset();
+                ui_manager.redraw_all();
+            }
+            let _trace = frame_trace.take().map(trace::enter);
+            let alloc_scope = crate::network::heap_tracker::FrameAllocScope::begin();
+            let render_start = Instant::now();
+            let rendered = ui_manager.render(&mut display_manager)?;
+            let render_time = render_start.elapsed();
             
-            display_manager.update_auto_dim(should_display_on)?;
+            // Track whether frame was actually rendered or skipped
+            if rendered {
+                perf_metrics.record_render_time(render_time);
+                
+                // Flush to display
+                let flush_start = Instant::now();
+                display_manager.flush()?;
+                let flush_time = flush_start.elapsed();
+                perf_metrics.record_flush_time(flush_time);
+                frame_pacer.record_flush(flush_time);
+                frames_since_report += 1;
+                frame_allocs.record(alloc_scope.finish());
+            } else {
+                // Frame was skipped by UI manager
+                perf_metrics.fps_tracker.frame_skipped();
+            }
         }
         
         // Track ALL loop iterations for accurate main loop FPS
@@ -1321,6 +1708,12 @@ fn run_app(
 
         // Frame timing and telemetry
         let frame_time = frame_start.elapsed();
+        if frame_time >= SLOW_FRAME {
+            let running = trace::active_spans();
+            if !running.is_empty() {
+                log::warn!("[FRAME] {} ms frame overlapped: {}", frame_time.as_millis(), running);
+            }
+        }
         
         // Update memory stats periodically
         perf_metrics.update_memory_stats();
@@ -1334,13 +1727,6 @@ fn run_app(
                 esp_idf_sys::ets_get_cpu_frequency()
             };
             
-            // Get PSRAM info if available
-            let _psram_free = if crate::psram::PsramAllocator::is_available() {
-                crate::psram::PsramAllocator::get_free_size() / 1024
-            } else {
-                0
-            };
-            
             // Get CPU usage for both cores
             let (cpu0_usage, cpu1_usage) = if last_cpu_check.elapsed() >= cpu_check_interval {
                 last_cpu_check = Instant::now();
@@ -1358,126 +1744,55 @@ fn run_app(
             // Update UI with core stats
             ui_manager.update_core_stats(cpu0_usage, cpu1_usage, core_stats.core0_tasks, core_stats.core1_tasks);
             
-            // Calculate if we're meeting the target
-            let fps_status = if fps_stats.current_fps >= DISPLAY_MAX_FPS * 0.9 {
-                "MAX"  // At hardware limit
-            } else if fps_stats.current_fps >= DISPLAY_MAX_FPS * 0.5 {
-                "OK"   // Acceptable performance
-            } else {
-                "LOW"  // Below target
-            };
-            
-            // Only log if FPS or CPU usage changed significantly
-            let fps_changed = (fps_stats.current_fps - last_logged_fps).abs() > FPS_CHANGE_THRESHOLD;
-            let cpu0_changed = (cpu0_usage as i16 - last_logged_cpu0 as i16).abs() > CPU_CHANGE_THRESHOLD as i16;
-            let cpu1_changed = (cpu1_usage as i16 - last_logged_cpu1 as i16).abs() > CPU_CHANGE_THRESHOLD as i16;
-            
-            if fps_changed || cpu0_changed || cpu1_changed {
-                let perf_msg = format!("[PERF] FPS: {:.1}/{:.0} [{}] | Skip: {:.1}% | Render: {:.1}ms | Flush: {:.1}ms | CPU: {}MHz | Heap: {}KB",
-                    fps_stats.current_fps,
-                    DISPLAY_MAX_FPS,
-                    fps_status,
-                    fps_stats.skip_rate,
-                    perf_metrics.last_render_time.as_secs_f32() * 1000.0,
-                    perf_metrics.last_flush_time.as_secs_f32() * 1000.0,
-                    cpu_freq,
-                    perf_metrics.heap_free / 1024
-                );
-                log::info!("{}", perf_msg);
-                
-                // Format CPU usage - show "N/A" if 0 (not available)
-                let cpu0_str = if cpu0_usage == 0 { "N/A".to_string() } else { format!("{}%", cpu0_usage) };
-                let cpu1_str = if cpu1_usage == 0 { "N/A".to_string() } else { format!("{}%", cpu1_usage) };
-                
-                let cores_msg = format!("[CORES] CPU0: {} | CPU1: {} | Tasks: C0={} C1={} Total={} | Avg: {}μs",
-                    cpu0_str,
-                    cpu1_str,
-                    core_stats.core0_tasks,
-                    core_stats.core1_tasks,
-                    core_stats.total_tasks,
-                    core_stats.avg_task_time_us
-                );
-                log::info!("{}", cores_msg);
-                
-                // Update last logged values
-                last_logged_fps = fps_stats.current_fps;
-                last_logged_cpu0 = cpu0_usage;
-                last_logged_cpu1 = cpu1_usage;
-            }
-            
+            // The hardware limit when uncapped
+            let target_fps = Some(frame_pacer.effective_fps()).filter(|&f| f > 0.0).unwrap_or(DISPLAY_MAX_FPS);
+
             // Update UI manager with accurate FPS
             ui_manager.update_fps(fps_stats.current_fps);
-            
-            // Debug log if FPS is very low or zero
-            if fps_stats.current_fps < 1.0 {
-                log::debug!("[FPS] Low/zero FPS detected: {:.1}, frames: {}, skipped: {}", 
-                    fps_stats.current_fps, fps_stats.total_frames, fps_stats.skipped_frames);
+
+            // Raw figures only; Core 1 updates the metrics store and writes the
+            // [PERF]/[CORES] log lines, so none of that runs inside a frame
+            let ssid = network_manager.get_ssid();
+            let ssid_changed = ssid != last_reported_ssid;
+            if ssid_changed {
+                last_reported_ssid = ssid.to_string();
             }
-            
-            // Update global metrics
+            let (wifi_disconnects, wifi_reconnects) = network_manager.get_connection_stats();
             {
-                let mut metrics = match crate::metrics::metrics().lock() {
-                    Ok(m) => m,
-                    Err(e) => {
-                        log::error!("Failed to lock metrics: {}", e);
-                        continue;
-                    }
-                };
-                
-                // FPS and performance metrics
-                metrics.update_fps(fps_stats.current_fps, DISPLAY_MAX_FPS); // realistic target based on hardware
-                
-                // Frame skip metrics
-                metrics.update_frame_stats(fps_stats.total_frames, fps_stats.skipped_frames);
-                
-                // Debug log what we're storing
-                if fps_stats.current_fps > 100.0 {
-                    log::warn!("[METRICS] Unrealistic FPS being stored: {:.1} (frames: {}, skipped: {})", 
-                        fps_stats.current_fps, fps_stats.total_frames, fps_stats.skipped_frames);
-                }
-                
-                // CPU metrics - both individual cores and average
-                metrics.update_cpu_cores(cpu0_usage, cpu1_usage);
-                metrics.update_cpu((cpu0_usage + cpu1_usage) / 2, cpu_freq as u16);
-                
-                // Timing metrics
-                let render_ms = (perf_metrics.last_render_time.as_secs_f32() * 1000.0) as u32;
-                let flush_ms = (perf_metrics.last_flush_time.as_secs_f32() * 1000.0) as u32;
-                metrics.update_timings(render_ms, flush_ms);
-                
-                // WiFi signal strength and connection status
-                let rssi = network_manager.get_signal_strength();
-                metrics.update_wifi_signal(rssi);
-                metrics.update_wifi_status(
-                    network_manager.is_connected(),
-                    network_manager.get_ssid().to_string()
-                );
-                
-                // Display brightness (static for now, but available for future use)
-                metrics.update_display(255); // Max brightness
-                
-                // PSRAM metrics
-                if crate::psram::PsramAllocator::is_available() {
-                    let psram_free = crate::psram::PsramAllocator::get_free_size() as u32;
-                    let psram_total = crate::psram::PsramAllocator::get_size() as u32;
-                    metrics.update_psram(psram_free, psram_total);
-                }
-                
-                // Connection monitoring
-                let (disconnects, reconnects) = network_manager.get_connection_stats();
-                metrics.update_wifi_reconnects(disconnects, reconnects);
-                
-                // Uptime tracking
-                if let Some(ref tracker) = uptime_tracker {
-                    if let Ok(t) = tracker.lock() {
-                        let uptime_secs = t.get_session_uptime().as_secs();
-                        metrics.update_uptime(uptime_secs);
-                    }
-                }
-                
-                // Note: Temperature and battery are updated from Core 1 data elsewhere
-                // HTTP connection metrics are updated by the web server
-                // Telnet connection metrics are updated by the telnet server
+                use crate::system::lifetime_stats::{record, Counter};
+                record(Counter::FramesRendered, std::mem::take(&mut frames_since_report));
+                record(Counter::WifiReconnects, wifi_reconnects.saturating_sub(last_wifi_reconnects) as u64);
+                last_wifi_reconnects = wifi_reconnects;
+            }
+            let frame_report = core1_tasks::FrameReport {
+                current_fps: fps_stats.current_fps,
+                target_fps,
+                total_frames: fps_stats.total_frames,
+                skipped_frames: fps_stats.skipped_frames,
+                skip_rate: fps_stats.skip_rate,
+                render_us: perf_metrics.last_render_time.as_micros() as u32,
+                flush_us: perf_metrics.last_flush_time.as_micros() as u32,
+                allocs_per_frame: frame_allocs.take_average(),
+                cpu_freq_mhz: cpu_freq,
+                cpu_usage_core0: cpu0_usage,
+                cpu_usage_core1: cpu1_usage,
+                heap_free: perf_metrics.heap_free as u32,
+                core0_tasks: core_stats.core0_tasks,
+                core1_tasks: core_stats.core1_tasks,
+                total_tasks: core_stats.total_tasks,
+                avg_task_time_us: core_stats.avg_task_time_us,
+                rssi: network_manager.get_signal_strength(),
+                wifi_connected: network_manager.is_connected(),
+                ssid: ssid_changed.then(|| last_reported_ssid.clone()),
+                // Backlight level (0-255) right now, fades included; lowered while thermally throttled
+                backlight_level: power_manager.backlight_level(),
+                wifi_disconnects,
+                wifi_reconnects,
+                uptime_secs: uptime_tracker.as_ref()
+                    .and_then(|tracker| tracker.lock().ok().map(|t| t.get_session_uptime().as_secs())),
+            };
+            if !safe_mode && core1_channels.frame_tx.send(frame_report).is_err() {
+                log::warn!("Core 1 frame report channel closed");
             }
             
             // Reset report timer
@@ -1493,30 +1808,38 @@ fn run_app(
             last_memory_check = Instant::now();
         }
         
-        // Frame rate limiting – align to hardware (~10 FPS) and avoid busy-waiting
-        const ENABLE_FPS_CAP: bool = true; // Set to true for production, false for benchmarking
-        if ENABLE_FPS_CAP {
-            let target_frame = Duration::from_millis(100); // ~10 FPS
-            if frame_time < target_frame {
-                let wait_time = target_frame - frame_time;
-                // Fully yield the CPU to other tasks (TCP/IP, HTTPD)
-                esp_idf_hal::delay::FreeRtos::delay_ms(wait_time.as_millis() as u32);
-            }
-        }
+        // Frame rate limiting: configured target, never faster than the panel flushes
+        display::pacing::wait(frame_pacer.wait_for(frame_time));
     }
     
     // Graceful shutdown
     log::info!("Beginning graceful shutdown...");
     
-    // Trigger shutdown manager
+    // Clear display
+    display_manager.clear(colors::BLACK)?;
+    display_manager.draw_text_centered(80, if restart.is_some() { "Restarting..." } else { "Shutting down..." }, colors::WHITE, None, 2)?;
+    if let Some(reason) = restart {
+        display_manager.draw_text_centered(110, reason.label(), colors::TEXT_SECONDARY, None, 1)?;
+    }
+    display_manager.flush()?;
+    
+    if restart.is_some() {
+        // Let the response that asked for the restart go out before the server stops
+        esp_idf_hal::delay::FreeRtos::delay_ms(system::shutdown::response_grace_left().as_millis() as u32);
+    }
+    
+    // Trigger shutdown manager (telnet, pollers and SSE streams see the signal)
     if let Ok(mut mgr) = shutdown_manager.lock() {
         let _ = mgr.shutdown();
     }
+    crate::system::lifetime_stats::save();
+    crate::network::disconnect_reasons::save();
     
-    // Clear display
-    display_manager.clear(colors::BLACK)?;
-    display_manager.draw_text_centered(80, "Shutting down...", colors::WHITE, None, 2)?;
-    display_manager.flush()?;
+    if let Some(reason) = restart {
+        // Stops httpd and closes its sockets
+        drop(web_server);
+        system::shutdown::restart_now(reason);
+    }
     
     // Wait a moment for services to stop
     esp_idf_hal::delay::FreeRtos::delay_ms(1000);
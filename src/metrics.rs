@@ -4,6 +4,8 @@ use std::sync::{Arc, OnceLock};
 // Import the optimized store
 use crate::metrics_rwlock::{self, MetricsStore};
 
+pub use crate::metrics_data::MetricsData;
+
 // Global metrics instance - use OnceLock for safe one-time initialization
 static METRICS: OnceLock<Arc<MetricsWrapper>> = OnceLock::new();
 
@@ -82,6 +84,7 @@ impl Drop for MetricsGuard {
         self.store.update_display(self.data.display_brightness);
         self.store.update_battery(self.data.battery_voltage_mv, self.data.battery_percentage, self.data.battery_charging);
         self.store.update_timings(self.data.render_time_ms, self.data.flush_time_ms);
+        self.store.update_render_allocs(self.data.render_allocs_per_frame);
         self.store.update_frame_stats(self.data.frame_count, self.data.skip_count);
         self.store.update_psram(self.data.psram_free, self.data.psram_total);
         self.store.update_button_metrics(
@@ -96,140 +99,3 @@ impl Drop for MetricsGuard {
         self.store.update_uptime(self.data.uptime_seconds);
     }
 }
-
-#[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
-pub struct MetricsData {
-    // Timestamp for the metrics
-    pub timestamp: u64,
-    // Heap memory
-    pub heap_free: u32,
-    // CPU metrics
-    pub cpu_usage: u8,
-    pub cpu_freq_mhz: u16,
-    pub cpu0_usage: u8,
-    pub cpu1_usage: u8,
-    
-    // Temperature
-    pub temperature: f32,
-    
-    // WiFi
-    pub wifi_rssi: i8,
-    pub wifi_connected: bool,
-    pub wifi_ssid: String,
-    
-    // Display
-    pub display_brightness: u8,
-    
-    // Performance
-    pub fps_actual: f32,
-    pub fps_target: f32,
-    pub render_time_ms: u32,
-    pub flush_time_ms: u32,
-    
-    // Battery
-    pub battery_voltage_mv: u16,
-    pub battery_percentage: u8,
-    pub battery_charging: bool,
-    
-    // Frame statistics
-    pub frame_count: u64,
-    pub skip_count: u64,
-    
-    // PSRAM
-    pub psram_free: u32,
-    pub psram_total: u32,
-    
-    // Button metrics
-    pub button_avg_response_ms: f32,
-    pub button_max_response_ms: f32,
-    pub button_events_total: u64,
-    pub button_events_per_second: f32,
-    
-    // Connection monitoring
-    pub http_connections_active: u32,
-    pub http_connections_total: u64,
-    pub telnet_connections_active: u32,
-    pub telnet_connections_total: u64,
-    pub wifi_disconnects: u32,
-    pub wifi_reconnects: u32,
-    pub uptime_seconds: u64,
-}
-
-impl MetricsData {
-    pub fn update_cpu(&mut self, usage: u8, freq_mhz: u16) {
-        self.cpu_usage = usage;
-        self.cpu_freq_mhz = freq_mhz;
-    }
-    
-    pub fn update_cpu_cores(&mut self, cpu0: u8, cpu1: u8) {
-        self.cpu0_usage = cpu0;
-        self.cpu1_usage = cpu1;
-        // Also update overall CPU usage as average
-        self.cpu_usage = (cpu0 + cpu1) / 2;
-    }
-    
-    pub fn update_temperature(&mut self, temp: f32) {
-        self.temperature = temp;
-    }
-    
-    pub fn update_wifi_signal(&mut self, rssi: i8) {
-        self.wifi_rssi = rssi;
-    }
-    
-    pub fn update_wifi_status(&mut self, connected: bool, ssid: String) {
-        self.wifi_connected = connected;
-        self.wifi_ssid = ssid;
-    }
-    
-    pub fn update_display(&mut self, brightness: u8) {
-        self.display_brightness = brightness;
-    }
-    
-    pub fn update_battery(&mut self, voltage_mv: u16, percentage: u8, is_charging: bool) {
-        self.battery_voltage_mv = voltage_mv;
-        self.battery_percentage = percentage;
-        self.battery_charging = is_charging;
-    }
-    
-    pub fn update_timings(&mut self, render_ms: u32, flush_ms: u32) {
-        self.render_time_ms = render_ms;
-        self.flush_time_ms = flush_ms;
-    }
-    
-    pub fn update_frame_stats(&mut self, total: u64, skipped: u64) {
-        self.frame_count = total;
-        self.skip_count = skipped;
-    }
-    
-    pub fn update_psram(&mut self, free: u32, total: u32) {
-        self.psram_free = free;
-        self.psram_total = total;
-    }
-    
-    pub fn update_button_metrics(&mut self, avg_ms: f32, max_ms: f32, total_events: u64, events_per_sec: f32) {
-        self.button_avg_response_ms = avg_ms;
-        self.button_max_response_ms = max_ms;
-        self.button_events_total = total_events;
-        self.button_events_per_second = events_per_sec;
-    }
-    
-    pub fn update_fps(&mut self, actual: f32, target: f32) {
-        self.fps_actual = actual;
-        self.fps_target = target;
-    }
-    
-    
-    pub fn update_telnet_connections(&mut self, active: u32, total: u64) {
-        self.telnet_connections_active = active;
-        self.telnet_connections_total = total;
-    }
-    
-    pub fn update_wifi_reconnects(&mut self, disconnects: u32, reconnects: u32) {
-        self.wifi_disconnects = disconnects;
-        self.wifi_reconnects = reconnects;
-    }
-    
-    pub fn update_uptime(&mut self, seconds: u64) {
-        self.uptime_seconds = seconds;
-    }
-}
\ No newline at end of file
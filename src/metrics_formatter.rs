@@ -1,21 +1,193 @@
-use crate::metrics::MetricsData;
+use crate::metrics_data::MetricsData;
+use crate::network::disconnect_reasons::ReasonCount;
+use crate::network::latency_monitor::{LatencySnapshot, LatencyStats, BUCKETS_MS};
+use crate::network::net_stats::NetStackStats;
+use crate::sensors::registry::Sample;
+use std::collections::BTreeMap;
 use std::fmt::Write;
+use std::sync::Mutex;
+
+/// Prefix used when none is configured
+pub const DEFAULT_PREFIX: &str = "esp32";
+
+/// Configured prefix and rendered global labels (`site="lab",device_id="a1"`)
+static OPTIONS: Mutex<Option<(String, String)>> = Mutex::new(None);
+
+/// Apply the metric prefix and global labels from Config to every formatter
+/// created afterwards
+pub fn configure(prefix: &str, labels: &BTreeMap<String, String>) {
+    let options = (prefix.to_string(), render_labels(labels));
+    if let Ok(mut slot) = OPTIONS.lock() {
+        if slot.as_ref() != Some(&options) {
+            *slot = Some(options);
+        }
+    }
+}
+
+/// `key="value"` pairs joined by commas, values escaped for the text format
+fn render_labels(labels: &BTreeMap<String, String>) -> String {
+    let mut out = String::new();
+    for (key, value) in labels {
+        if !out.is_empty() {
+            out.push(',');
+        }
+        out.push_str(key);
+        out.push_str("=\"");
+        out.push_str(&escape_label_value(value));
+        out.push('"');
+    }
+    out
+}
+
+/// Escape backslashes, quotes and newlines in a label value
+pub fn escape_label_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Text format of a /metrics response, negotiated from the Accept header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Exposition {
+    /// Prometheus text format 0.0.4
+    #[default]
+    Prometheus,
+    /// OpenMetrics 1.0: counter families without `_total`, exemplars, `# EOF`
+    OpenMetrics,
+}
+
+impl Exposition {
+    /// OpenMetrics if the client lists it with a non-zero quality
+    pub fn from_accept(accept: Option<&str>) -> Self {
+        let wants_openmetrics = accept.unwrap_or("").split(',').any(|range| {
+            let mut params = range.split(';').map(str::trim);
+            params.next().is_some_and(|media| media.eq_ignore_ascii_case("application/openmetrics-text"))
+                && !params.any(|p| p.strip_prefix("q=").and_then(|q| q.parse::<f32>().ok()) == Some(0.0))
+        });
+        if wants_openmetrics { Self::OpenMetrics } else { Self::Prometheus }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Prometheus => "text/plain; version=0.0.4; charset=utf-8",
+            Self::OpenMetrics => "application/openmetrics-text; version=1.0.0; charset=utf-8",
+        }
+    }
+
+    /// Separator between metric families; OpenMetrics forbids blank lines
+    pub fn separator(self) -> &'static str {
+        match self {
+            Self::Prometheus => "\n",
+            Self::OpenMetrics => "",
+        }
+    }
+
+    /// Appended once after the last family
+    pub fn terminator(self) -> &'static str {
+        match self {
+            Self::Prometheus => "",
+            Self::OpenMetrics => "# EOF\n",
+        }
+    }
+}
+
+/// Example observation attached to a counter sample (OpenMetrics only), e.g.
+/// `{ssid="home"} 1`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exemplar {
+    /// Rendered label set without braces
+    pub labels: String,
+    pub value: f64,
+}
+
+/// Scrape-time view of counters whose source can restart or wrap (the
+/// lock-free metrics store keeps 32-bit copies): each scrape adds the increase
+/// since the previous one, or the whole value after a drop, so the exported
+/// counter never goes backwards within a boot
+#[derive(Debug, Default)]
+pub struct MonotonicCounters {
+    /// name -> (last raw value, exported total)
+    counters: BTreeMap<&'static str, (u64, u64)>,
+}
+
+impl MonotonicCounters {
+    pub const fn new() -> Self {
+        Self { counters: BTreeMap::new() }
+    }
+
+    /// Fold in the current raw value and return the exported total
+    pub fn observe(&mut self, name: &'static str, raw: u64) -> u64 {
+        let (last, total) = self.counters.entry(name).or_insert((0, 0));
+        *total += if raw >= *last { raw - *last } else { raw };
+        *last = raw;
+        *total
+    }
+}
+
+static COUNTERS: Mutex<MonotonicCounters> = Mutex::new(MonotonicCounters::new());
+
+/// Exported value of a store-backed counter
+fn monotonic(name: &'static str, raw: u64) -> u64 {
+    COUNTERS.lock().map_or(raw, |mut counters| counters.observe(name, raw))
+}
 
 /// Efficient metrics formatter for Prometheus format
 pub struct MetricsFormatter {
     buffer: String,
+    /// Metric name prefix without the trailing underscore; may be empty
+    prefix: String,
+    /// Labels added to every series, already rendered
+    labels: String,
+    exposition: Exposition,
+}
+
+impl Default for MetricsFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MetricsFormatter {
-    /// Create a new formatter with pre-allocated buffer
+    /// Create a new formatter with pre-allocated buffer, using the configured
+    /// prefix and global labels
     pub fn new() -> Self {
+        let (prefix, labels) = OPTIONS.lock().ok()
+            .and_then(|slot| slot.clone())
+            .unwrap_or_else(|| (DEFAULT_PREFIX.to_string(), String::new()));
         Self {
             // Pre-allocate based on typical metrics size (~2KB)
             buffer: String::with_capacity(2048),
+            prefix,
+            labels,
+            exposition: Exposition::Prometheus,
+        }
+    }
+
+    /// Formatter with an explicit prefix and global labels
+    pub fn with_options(prefix: &str, labels: &BTreeMap<String, String>) -> Self {
+        Self {
+            buffer: String::with_capacity(2048),
+            prefix: prefix.to_string(),
+            labels: render_labels(labels),
+            exposition: Exposition::Prometheus,
         }
     }
 
+    /// Write `exposition` instead of the Prometheus text format
+    pub fn with_exposition(mut self, exposition: Exposition) -> Self {
+        self.exposition = exposition;
+        self
+    }
+
     /// Format all metrics into Prometheus format
+    #[allow(clippy::too_many_arguments)]
     pub fn format_metrics(
         &mut self,
         metrics_data: &MetricsData,
@@ -30,7 +202,7 @@ impl MetricsFormatter {
 
         // Device info
         self.write_metric(
-            "esp32_device_info",
+            "device_info",
             "Device information",
             "gauge",
             &format!("version=\"{}\",board=\"{}\",model=\"{}\"", version, board_type, chip_model),
@@ -38,25 +210,25 @@ impl MetricsFormatter {
         )?;
 
         // System metrics
-        self.write_simple_metric("esp32_uptime_seconds", "Total uptime in seconds", "counter", uptime_seconds as f64)?;
-        self.write_simple_metric("esp32_heap_free_bytes", "Current free heap memory in bytes", "gauge", heap_free as f64)?;
-        self.write_simple_metric("esp32_heap_total_bytes", "Total heap memory in bytes", "gauge", heap_total as f64)?;
+        self.write_simple_metric("uptime_seconds", "Total uptime in seconds", "gauge", uptime_seconds as f64)?;
+        self.write_simple_metric("heap_free_bytes", "Current free heap memory in bytes", "gauge", heap_free as f64)?;
+        self.write_simple_metric("heap_total_bytes", "Total heap memory in bytes", "gauge", heap_total as f64)?;
 
         // Performance metrics
-        self.write_simple_metric("esp32_fps_actual", "Current actual frames per second", "gauge", metrics_data.fps_actual as f64)?;
-        self.write_simple_metric("esp32_fps_target", "Target frames per second", "gauge", metrics_data.fps_target as f64)?;
+        self.write_simple_metric("fps_actual", "Current actual frames per second", "gauge", metrics_data.fps_actual as f64)?;
+        self.write_simple_metric("fps_target", "Target frames per second", "gauge", metrics_data.fps_target as f64)?;
 
         // CPU metrics
-        self.write_simple_metric("esp32_cpu_usage_percent", "CPU usage percentage (average)", "gauge", metrics_data.cpu_usage as f64)?;
-        self.write_simple_metric("esp32_cpu0_usage_percent", "CPU Core 0 usage percentage", "gauge", metrics_data.cpu0_usage as f64)?;
-        self.write_simple_metric("esp32_cpu1_usage_percent", "CPU Core 1 usage percentage", "gauge", metrics_data.cpu1_usage as f64)?;
-        self.write_simple_metric("esp32_cpu_freq_mhz", "CPU frequency in MHz", "gauge", metrics_data.cpu_freq_mhz as f64)?;
+        self.write_simple_metric("cpu_usage_percent", "CPU usage percentage (average)", "gauge", metrics_data.cpu_usage as f64)?;
+        self.write_simple_metric("cpu0_usage_percent", "CPU Core 0 usage percentage", "gauge", metrics_data.cpu0_usage as f64)?;
+        self.write_simple_metric("cpu1_usage_percent", "CPU Core 1 usage percentage", "gauge", metrics_data.cpu1_usage as f64)?;
+        self.write_simple_metric("cpu_freq_mhz", "CPU frequency in MHz", "gauge", metrics_data.cpu_freq_mhz as f64)?;
 
         // Temperature
-        self.write_simple_metric("esp32_temperature_celsius", "Internal temperature in Celsius", "gauge", metrics_data.temperature as f64)?;
+        self.write_simple_metric("temperature_celsius", "Internal temperature in Celsius", "gauge", metrics_data.temperature as f64)?;
 
         // WiFi metrics
-        self.write_simple_metric("esp32_wifi_rssi_dbm", "WiFi signal strength in dBm", "gauge", metrics_data.wifi_rssi as f64)?;
+        self.write_simple_metric("wifi_rssi_dbm", "WiFi signal strength in dBm", "gauge", metrics_data.wifi_rssi as f64)?;
         
         let wifi_ssid = if metrics_data.wifi_connected { 
             &metrics_data.wifi_ssid 
@@ -64,25 +236,26 @@ impl MetricsFormatter {
             "_disconnected" 
         };
         self.write_metric(
-            "esp32_wifi_connected",
+            "wifi_connected",
             "WiFi connection status (0=disconnected, 1=connected)",
             "gauge",
-            &format!("ssid=\"{}\"", wifi_ssid),
+            &format!("ssid=\"{}\"", escape_label_value(wifi_ssid)),
             if metrics_data.wifi_connected { 1.0 } else { 0.0 },
         )?;
 
         // Display metrics
-        self.write_simple_metric("esp32_display_brightness", "Display brightness level (0-255)", "gauge", metrics_data.display_brightness as f64)?;
+        self.write_simple_metric("display_brightness", "Display brightness level (0-255)", "gauge", metrics_data.display_brightness as f64)?;
 
         // Battery metrics
-        self.write_simple_metric("esp32_battery_voltage_mv", "Battery voltage in millivolts", "gauge", metrics_data.battery_voltage_mv as f64)?;
-        self.write_simple_metric("esp32_battery_percentage", "Battery charge percentage", "gauge", metrics_data.battery_percentage as f64)?;
-        self.write_simple_metric("esp32_battery_charging", "Battery charging status (0=not charging, 1=charging)", "gauge", 
+        self.write_simple_metric("battery_voltage_mv", "Battery voltage in millivolts", "gauge", metrics_data.battery_voltage_mv as f64)?;
+        self.write_simple_metric("battery_percentage", "Battery charge percentage", "gauge", metrics_data.battery_percentage as f64)?;
+        self.write_simple_metric("battery_charging", "Battery charging status (0=not charging, 1=charging)", "gauge", 
             if metrics_data.battery_charging { 1.0 } else { 0.0 })?;
 
         // Timing metrics
-        self.write_simple_metric("esp32_render_time_milliseconds", "Display render time in milliseconds", "gauge", metrics_data.render_time_ms as f64)?;
-        self.write_simple_metric("esp32_flush_time_milliseconds", "Display flush time in milliseconds", "gauge", metrics_data.flush_time_ms as f64)?;
+        self.write_simple_metric("render_time_milliseconds", "Display render time in milliseconds", "gauge", metrics_data.render_time_ms as f64)?;
+        self.write_simple_metric("flush_time_milliseconds", "Display flush time in milliseconds", "gauge", metrics_data.flush_time_ms as f64)?;
+        self.write_simple_metric("render_allocs_per_frame", "Heap allocations made while rendering and flushing a frame, averaged", "gauge", metrics_data.render_allocs_per_frame as f64)?;
 
         // Frame statistics
         let skip_rate = if metrics_data.frame_count > 0 {
@@ -90,58 +263,209 @@ impl MetricsFormatter {
         } else {
             0.0
         };
-        self.write_simple_metric("esp32_frame_skip_rate_percent", "Percentage of frames skipped", "gauge", skip_rate)?;
-        self.write_simple_metric("esp32_total_frames_count", "Total number of frames processed", "counter", metrics_data.frame_count as f64)?;
-        self.write_simple_metric("esp32_skipped_frames_count", "Number of frames skipped", "counter", metrics_data.skip_count as f64)?;
+        self.write_simple_metric("frame_skip_rate_percent", "Percentage of frames skipped", "gauge", skip_rate)?;
+        self.write_counter("frames_total", "Total number of frames processed", monotonic("frames", metrics_data.frame_count), None)?;
+        self.write_counter("frames_skipped_total", "Number of frames skipped", monotonic("frames_skipped", metrics_data.skip_count), None)?;
 
         // PSRAM metrics
-        self.write_simple_metric("esp32_psram_free_bytes", "Free PSRAM memory in bytes", "gauge", metrics_data.psram_free as f64)?;
-        self.write_simple_metric("esp32_psram_total_bytes", "Total PSRAM memory in bytes", "gauge", metrics_data.psram_total as f64)?;
+        self.write_simple_metric("psram_free_bytes", "Free PSRAM memory in bytes", "gauge", metrics_data.psram_free as f64)?;
+        self.write_simple_metric("psram_total_bytes", "Total PSRAM memory in bytes", "gauge", metrics_data.psram_total as f64)?;
         
         let psram_usage = if metrics_data.psram_total > 0 {
             (metrics_data.psram_total - metrics_data.psram_free) as f64 / metrics_data.psram_total as f64 * 100.0
         } else {
             0.0
         };
-        self.write_simple_metric("esp32_psram_used_percent", "PSRAM usage percentage", "gauge", psram_usage)?;
+        self.write_simple_metric("psram_used_percent", "PSRAM usage percentage", "gauge", psram_usage)?;
 
         // Button metrics (if available)
         if metrics_data.button_events_total > 0 {
-            self.write_simple_metric("esp32_button_avg_response_ms", "Average button response time in milliseconds", "gauge", metrics_data.button_avg_response_ms as f64)?;
-            self.write_simple_metric("esp32_button_max_response_ms", "Maximum button response time in milliseconds", "gauge", metrics_data.button_max_response_ms as f64)?;
-            self.write_simple_metric("esp32_button_events_total", "Total button events", "counter", metrics_data.button_events_total as f64)?;
-            self.write_simple_metric("esp32_button_events_per_second", "Button events per second", "gauge", metrics_data.button_events_per_second as f64)?;
+            self.write_simple_metric("button_avg_response_ms", "Average button response time in milliseconds", "gauge", metrics_data.button_avg_response_ms as f64)?;
+            self.write_simple_metric("button_max_response_ms", "Maximum button response time in milliseconds", "gauge", metrics_data.button_max_response_ms as f64)?;
+            self.write_counter("button_events_total", "Total button events", monotonic("button_events", metrics_data.button_events_total), None)?;
+            self.write_simple_metric("button_events_per_second", "Button events per second", "gauge", metrics_data.button_events_per_second as f64)?;
         }
         
         // Connection monitoring metrics
-        self.write_simple_metric("esp32_http_connections_active", "Currently active HTTP connections", "gauge", metrics_data.http_connections_active as f64)?;
-        self.write_simple_metric("esp32_http_connections_total", "Total HTTP connections handled", "counter", metrics_data.http_connections_total as f64)?;
-        self.write_simple_metric("esp32_telnet_connections_active", "Currently active telnet connections", "gauge", metrics_data.telnet_connections_active as f64)?;
-        self.write_simple_metric("esp32_telnet_connections_total", "Total telnet connections handled", "counter", metrics_data.telnet_connections_total as f64)?;
-        self.write_simple_metric("esp32_wifi_disconnects_total", "Total WiFi disconnections", "counter", metrics_data.wifi_disconnects as f64)?;
-        self.write_simple_metric("esp32_wifi_reconnects_total", "Total WiFi reconnections", "counter", metrics_data.wifi_reconnects as f64)?;
-        self.write_simple_metric("esp32_session_uptime_seconds", "Current session uptime in seconds", "counter", metrics_data.uptime_seconds as f64)?;
+        self.write_simple_metric("http_connections_active", "Currently active HTTP connections", "gauge", metrics_data.http_connections_active as f64)?;
+        self.write_counter("http_connections_total", "Total HTTP connections handled", monotonic("http_connections", metrics_data.http_connections_total), None)?;
+        self.write_simple_metric("telnet_connections_active", "Currently active telnet connections", "gauge", metrics_data.telnet_connections_active as f64)?;
+        self.write_counter("telnet_connections_total", "Total telnet connections handled", monotonic("telnet_connections", metrics_data.telnet_connections_total), None)?;
+        self.write_counter("wifi_disconnects_total", "Total WiFi disconnections", monotonic("wifi_disconnects", metrics_data.wifi_disconnects as u64), None)?;
+        // The network the last reconnect went to
+        let reconnect_exemplar = metrics_data.wifi_connected.then(|| Exemplar {
+            labels: format!("ssid=\"{}\"", escape_label_value(&metrics_data.wifi_ssid)),
+            value: 1.0,
+        });
+        self.write_counter("wifi_reconnects_total", "Total WiFi reconnections", monotonic("wifi_reconnects", metrics_data.wifi_reconnects as u64), reconnect_exemplar)?;
+        self.write_simple_metric("session_uptime_seconds", "Current session uptime in seconds", "gauge", metrics_data.uptime_seconds as f64)?;
 
         Ok(self.buffer.clone())
     }
 
+    /// Format network stack health (sockets, lwIP counters, DHCP, DNS)
+    pub fn format_net_stack(&mut self, stats: &NetStackStats) -> Result<String, std::fmt::Error> {
+        self.buffer.clear();
+
+        self.write_simple_metric("net_sockets_active", "lwIP sockets currently open", "gauge", stats.sockets_active as f64)?;
+        self.write_simple_metric("net_sockets_max", "lwIP socket table size", "gauge", stats.sockets_max as f64)?;
+        self.write_simple_metric("net_sockets_high_water", "Most lwIP sockets open at once since boot", "gauge", stats.sockets_high_water as f64)?;
+        self.write_simple_metric("net_socket_exhaustion_samples_total", "Collector samples that found every socket in use", "counter", stats.socket_exhaustion_samples as f64)?;
+        self.write_simple_metric("net_accept_failures_total", "TCP connections refused because no PCB could be allocated", "counter", stats.accept_failures as f64)?;
+        self.write_simple_metric("net_tcp_tx_segments_total", "TCP segments sent", "counter", stats.tcp_tx_segments as f64)?;
+        self.write_simple_metric("net_tcp_rx_segments_total", "TCP segments received", "counter", stats.tcp_rx_segments as f64)?;
+        self.write_simple_metric("net_tx_errors_total", "Routing, link and buffer errors on transmit", "counter", stats.tx_errors as f64)?;
+        self.write_simple_metric("net_rx_errors_total", "Checksum, length and protocol errors on receive", "counter", stats.rx_errors as f64)?;
+        self.write_simple_metric("net_dhcp_leases_total", "DHCP leases bound, renewals included", "counter", stats.dhcp_leases as f64)?;
+        self.write_simple_metric("net_dhcp_renews_total", "DHCP renewals that kept the address", "counter", stats.dhcp_renews as f64)?;
+        self.write_simple_metric("net_dns_failures_total", "Failed DNS lookups by outbound HTTP requests", "counter", stats.dns_failures as f64)?;
+
+        Ok(self.buffer.clone())
+    }
+
+    /// Format ping RTT histograms and loss for the gateway and the reference host
+    pub fn format_latency(&mut self, latency: &LatencySnapshot) -> Result<String, std::fmt::Error> {
+        self.buffer.clear();
+
+        let mut targets: Vec<(&str, &LatencyStats)> = vec![("gateway", &latency.gateway)];
+        if !latency.host_name.is_empty() {
+            targets.push((latency.host_name.as_str(), &latency.host));
+        }
+
+        let rtt = self.name("net_ping_rtt_ms");
+        self.write_header(&rtt, "Round-trip time of ICMP echo replies in milliseconds", "histogram")?;
+        for (target, stats) in &targets {
+            let target_label = format!("target=\"{}\"", target);
+            for (bound, count) in BUCKETS_MS.iter().zip(stats.buckets.iter()) {
+                self.write_sample(&format!("{}_bucket", rtt), &format!("{},le=\"{}\"", target_label, bound), *count as f64)?;
+            }
+            self.write_sample(&format!("{}_bucket", rtt), &format!("{},le=\"+Inf\"", target_label), stats.received as f64)?;
+            self.write_sample(&format!("{}_sum", rtt), &target_label, stats.rtt_sum_ms as f64)?;
+            self.write_sample(&format!("{}_count", rtt), &target_label, stats.received as f64)?;
+        }
+        self.buffer.push_str(self.exposition.separator());
+
+        type Family = (&'static str, &'static str, &'static str, fn(&LatencyStats) -> f64);
+        let families: [Family; 3] = [
+            ("net_ping_sent_total", "ICMP echo requests sent", "counter", |s| s.sent as f64),
+            ("net_ping_received_total", "ICMP echo replies received", "counter", |s| s.received as f64),
+            ("net_ping_loss_percent", "Ping loss over the last rounds", "gauge", |s| s.recent_loss_percent() as f64),
+        ];
+        for (name, help, metric_type, value) in families {
+            let name = self.name(name);
+            self.write_header(&name, help, metric_type)?;
+            for (target, stats) in &targets {
+                self.write_sample(&name, &format!("target=\"{}\"", target), value(stats))?;
+            }
+            self.buffer.push_str(self.exposition.separator());
+        }
+
+        Ok(self.buffer.clone())
+    }
+
+    /// WiFi disconnects over the device lifetime, one counter per reason code;
+    /// empty before the first disconnect
+    pub fn format_disconnect_reasons(&mut self, reasons: &[ReasonCount]) -> Result<String, std::fmt::Error> {
+        self.buffer.clear();
+        if reasons.is_empty() {
+            return Ok(String::new());
+        }
+        let name = self.name("wifi_disconnect_reasons_total");
+        self.write_header(&name, "WiFi disconnects by ESP-IDF reason code, kept across reboots", "counter")?;
+        for reason in reasons {
+            let labels = format!("code=\"{}\",reason=\"{}\"", reason.code, escape_label_value(reason.reason));
+            self.write_sample(&name, &labels, reason.count as f64)?;
+        }
+        self.buffer.push_str(self.exposition.separator());
+        Ok(self.buffer.clone())
+    }
+
+    /// Readings of the registered sensors as one gauge family labelled with
+    /// sensor, quantity and unit; empty without sensors
+    pub fn format_readings(&mut self, readings: &[Sample]) -> Result<String, std::fmt::Error> {
+        self.buffer.clear();
+        if readings.is_empty() {
+            return Ok(String::new());
+        }
+        let name = self.name("sensor_reading");
+        self.write_header(&name, "Latest reading of a registered sensor", "gauge")?;
+        for sample in readings {
+            let labels = format!("sensor=\"{}\",quantity=\"{}\",unit=\"{}\"",
+                escape_label_value(sample.sensor), escape_label_value(sample.reading.quantity), escape_label_value(sample.reading.unit));
+            self.write_sample(&name, &labels, sample.reading.value as f64)?;
+        }
+        self.buffer.push_str(self.exposition.separator());
+        Ok(self.buffer.clone())
+    }
+
+    /// Format ad-hoc series given as (name, help, type, labels, value); `labels`
+    /// may be empty
+    pub fn format_series(&mut self, series: &[(&str, &str, &str, &str, f64)]) -> Result<String, std::fmt::Error> {
+        self.buffer.clear();
+        for &(name, help, metric_type, labels, value) in series {
+            self.write_metric(name, help, metric_type, labels, value)?;
+        }
+        Ok(self.buffer.clone())
+    }
+
+    /// Full metric name with the configured prefix
+    fn name(&self, name: &str) -> String {
+        if self.prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}_{}", self.prefix, name)
+        }
+    }
+
+    /// Write one sample line, adding the global labels in front of `labels`
+    fn write_sample(&mut self, full_name: &str, labels: &str, value: f64) -> Result<(), std::fmt::Error> {
+        match (self.labels.is_empty(), labels.is_empty()) {
+            (true, true) => writeln!(&mut self.buffer, "{} {}", full_name, value),
+            (false, true) => writeln!(&mut self.buffer, "{}{{{}}} {}", full_name, self.labels, value),
+            (true, false) => writeln!(&mut self.buffer, "{}{{{}}} {}", full_name, labels, value),
+            (false, false) => writeln!(&mut self.buffer, "{}{{{},{}}} {}", full_name, self.labels, labels, value),
+        }
+    }
+
     /// Write a simple metric without labels
     fn write_simple_metric(&mut self, name: &str, help: &str, metric_type: &str, value: f64) -> Result<(), std::fmt::Error> {
-        writeln!(&mut self.buffer, "# HELP {} {}", name, help)?;
-        writeln!(&mut self.buffer, "# TYPE {} {}", name, metric_type)?;
-        writeln!(&mut self.buffer, "{} {}", name, value)?;
-        writeln!(&mut self.buffer)?;
-        Ok(())
+        self.write_metric(name, help, metric_type, "", value)
     }
 
     /// Write a metric with labels
     fn write_metric(&mut self, name: &str, help: &str, metric_type: &str, labels: &str, value: f64) -> Result<(), std::fmt::Error> {
-        writeln!(&mut self.buffer, "# HELP {} {}", name, help)?;
-        writeln!(&mut self.buffer, "# TYPE {} {}", name, metric_type)?;
-        writeln!(&mut self.buffer, "{}{{{}}} {}", name, labels, value)?;
-        writeln!(&mut self.buffer)?;
+        let name = self.name(name);
+        self.write_header(&name, help, metric_type)?;
+        self.write_sample(&name, labels, value)?;
+        self.buffer.push_str(self.exposition.separator());
+        Ok(())
+    }
+
+    /// Write a counter (`name` ends in `_total`) with an optional exemplar,
+    /// which only the OpenMetrics format can carry
+    fn write_counter(&mut self, name: &str, help: &str, value: u64, exemplar: Option<Exemplar>) -> Result<(), std::fmt::Error> {
+        let name = self.name(name);
+        self.write_header(&name, help, "counter")?;
+        self.write_sample(&name, "", value as f64)?;
+        if let (Exposition::OpenMetrics, Some(exemplar)) = (self.exposition, exemplar) {
+            // Turn the sample's newline into the exemplar suffix
+            self.buffer.pop();
+            writeln!(&mut self.buffer, " # {{{}}} {}", exemplar.labels, exemplar.value)?;
+        }
+        self.buffer.push_str(self.exposition.separator());
         Ok(())
     }
+
+    /// HELP and TYPE lines. OpenMetrics names a counter family without the
+    /// `_total` its samples carry.
+    fn write_header(&mut self, name: &str, help: &str, metric_type: &str) -> Result<(), std::fmt::Error> {
+        let family = match (self.exposition, metric_type) {
+            (Exposition::OpenMetrics, "counter") => name.strip_suffix("_total").unwrap_or(name),
+            _ => name,
+        };
+        writeln!(&mut self.buffer, "# HELP {} {}", family, help)?;
+        writeln!(&mut self.buffer, "# TYPE {} {}", family, metric_type)
+    }
 }
 
 #[cfg(test)]
@@ -154,6 +478,7 @@ mod tests {
         let metrics = MetricsData {
             cpu_usage: 50,
             fps_actual: 30.5,
+            render_allocs_per_frame: 2.5,
             wifi_connected: true,
             wifi_ssid: "TestNetwork".to_string(),
             ..Default::default()
@@ -174,5 +499,81 @@ mod tests {
         assert!(output.contains("esp32_device_info"));
         assert!(output.contains("esp32_cpu_usage_percent 50"));
         assert!(output.contains("esp32_fps_actual 30.5"));
+        assert!(output.contains("esp32_render_allocs_per_frame 2.5"));
+    }
+
+    #[test]
+    fn test_prefix_and_global_labels() {
+        let labels = BTreeMap::from([
+            ("site".to_string(), "lab \"2\"".to_string()),
+            ("device_id".to_string(), "a1".to_string()),
+        ]);
+        let mut formatter = MetricsFormatter::with_options("office", &labels);
+        let output = formatter.format_series(&[
+            ("uptime_seconds", "Uptime", "gauge", "", 5.0),
+            ("config_profile", "Profile", "gauge", "profile=\"day\"", 1.0),
+        ]).unwrap();
+        assert!(output.contains("# TYPE office_uptime_seconds gauge"));
+        assert!(output.contains(r#"office_uptime_seconds{device_id="a1",site="lab \"2\""} 5"#));
+        assert!(output.contains(r#"office_config_profile{device_id="a1",site="lab \"2\"",profile="day"} 1"#));
+
+        let mut latency = LatencySnapshot { gateway: LatencyStats::new(), host_name: String::new(), host: LatencyStats::new() };
+        latency.gateway.record_round(3, &[5, 6, 7]);
+        let output = MetricsFormatter::with_options("", &BTreeMap::new()).format_latency(&latency).unwrap();
+        assert!(output.contains(r#"net_ping_rtt_ms_count{target="gateway"} 3"#));
+        assert!(!output.contains("esp32_"));
+
+        let co2 = Sample {
+            key: "scd40.co2".to_string(),
+            sensor: "scd40",
+            reading: crate::sensors::registry::Reading::new("co2", 812.0, "ppm"),
+        };
+        let output = formatter.format_readings(&[co2]).unwrap();
+        assert!(output.contains("# TYPE office_sensor_reading gauge"));
+        assert!(output.contains(r#"office_sensor_reading{device_id="a1",site="lab \"2\"",sensor="scd40",quantity="co2",unit="ppm"} 812"#));
+        assert!(formatter.format_readings(&[]).unwrap().is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_disconnect_reason_counters() {
+        let mut histogram = crate::network::disconnect_reasons::DisconnectHistogram::default();
+        let mut formatter = MetricsFormatter::with_options("esp32", &BTreeMap::new());
+        assert!(formatter.format_disconnect_reasons(&histogram.counts()).unwrap().is_empty());
+
+        for code in [200, 202, 200] {
+            histogram.record(code);
+        }
+        let output = formatter.format_disconnect_reasons(&histogram.counts()).unwrap();
+        assert!(output.contains("# TYPE esp32_wifi_disconnect_reasons_total counter"));
+        assert!(output.contains(r#"esp32_wifi_disconnect_reasons_total{code="200",reason="beacon_timeout"} 2"#));
+        assert!(output.contains(r#"esp32_wifi_disconnect_reasons_total{code="202",reason="auth_fail"} 1"#));
+    }
+
+    #[test]
+    fn test_openmetrics_negotiation_and_counters() {
+        assert_eq!(Exposition::from_accept(None), Exposition::Prometheus);
+        assert_eq!(Exposition::from_accept(Some("text/plain;version=0.0.4")), Exposition::Prometheus);
+        let prometheus = "application/openmetrics-text;version=1.0.0,application/openmetrics-text;version=0.0.1;q=0.75,text/plain;version=0.0.4;q=0.5,*/*;q=0.1";
+        assert_eq!(Exposition::from_accept(Some(prometheus)), Exposition::OpenMetrics);
+        assert_eq!(Exposition::from_accept(Some("application/openmetrics-text; q=0")), Exposition::Prometheus);
+
+        let mut formatter = MetricsFormatter::with_options("esp32", &BTreeMap::new()).with_exposition(Exposition::OpenMetrics);
+        formatter.write_counter("wifi_reconnects_total", "Reconnects", 3, Some(Exemplar { labels: "ssid=\"home\"".to_string(), value: 1.0 })).unwrap();
+        assert_eq!(formatter.buffer, "# HELP esp32_wifi_reconnects Reconnects\n# TYPE esp32_wifi_reconnects counter\nesp32_wifi_reconnects_total 3 # {ssid=\"home\"} 1\n");
+
+        let mut formatter = MetricsFormatter::with_options("esp32", &BTreeMap::new());
+        formatter.write_counter("wifi_reconnects_total", "Reconnects", 3, Some(Exemplar { labels: String::new(), value: 1.0 })).unwrap();
+        assert_eq!(formatter.buffer, "# HELP esp32_wifi_reconnects_total Reconnects\n# TYPE esp32_wifi_reconnects_total counter\nesp32_wifi_reconnects_total 3\n\n");
+    }
+
+    #[test]
+    fn test_counters_stay_monotonic_across_source_resets() {
+        let mut counters = MonotonicCounters::new();
+        assert_eq!(counters.observe("frames", 10), 10);
+        assert_eq!(counters.observe("frames", 25), 25);
+        // Source restarted from zero
+        assert_eq!(counters.observe("frames", 4), 29);
+        assert_eq!(counters.observe("frames", 6), 31);
+        assert_eq!(counters.observe("other", 2), 2);
+    }
+}
@@ -75,6 +75,7 @@ pub struct ComplexMetrics {
     pub fps_target: f32,
     pub render_time_ms: u32,
     pub flush_time_ms: u32,
+    pub render_allocs_per_frame: f32,
     
     // Button metrics (f32)
     pub button_avg_response_ms: f32,
@@ -91,6 +92,7 @@ impl Default for ComplexMetrics {
             fps_target: 30.0,
             render_time_ms: 0,
             flush_time_ms: 0,
+            render_allocs_per_frame: 0.0,
             button_avg_response_ms: 0.0,
             button_max_response_ms: 0.0,
             button_events_per_second: 0.0,
@@ -161,6 +163,11 @@ impl MetricsStore {
         self.wifi_rssi.store(rssi, Ordering::Relaxed);
     }
     
+    /// Connection state alone, leaving the SSID as it is
+    pub fn update_wifi_connected(&self, connected: bool) {
+        self.wifi_connected.store(connected, Ordering::Relaxed);
+    }
+    
     pub fn update_display(&self, brightness: u8) {
         self.display_brightness.store(brightness, Ordering::Relaxed);
     }
@@ -202,6 +209,12 @@ impl MetricsStore {
         }
     }
     
+    pub fn update_render_allocs(&self, per_frame: f32) {
+        if let Ok(mut data) = self.complex_data.write() {
+            data.render_allocs_per_frame = per_frame;
+        }
+    }
+    
     pub fn update_fps(&self, actual: f32, target: f32) {
         if let Ok(mut data) = self.complex_data.write() {
             data.fps_actual = actual;
@@ -256,6 +269,7 @@ impl MetricsStore {
             fps_target: complex.fps_target,
             render_time_ms: complex.render_time_ms,
             flush_time_ms: complex.flush_time_ms,
+            render_allocs_per_frame: complex.render_allocs_per_frame,
             battery_voltage_mv: self.battery_voltage_mv.load(Ordering::Relaxed),
             battery_percentage: self.battery_percentage.load(Ordering::Relaxed),
             battery_charging: self.battery_charging.load(Ordering::Relaxed),
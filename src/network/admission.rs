@@ -0,0 +1,157 @@
+// Admission control for the web server. An OTA upload, a metrics scrape and
+// a dashboard page load arriving together each allocate tens of KB and hold
+// the httpd task for a while, which starves the render loop. Routes are
+// classed light or heavy; only one heavy request runs at a time and others
+// get 503 with Retry-After instead of queueing up behind it. Light routes
+// (small JSON, health checks) are always admitted.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Seconds a rejected client is asked to wait
+pub const RETRY_AFTER_SECS: u32 = 2;
+
+/// Routes that render pages, stream bodies or build large responses
+const HEAVY_ROUTES: &[&str] = &[
+    "/",
+    "/dashboard",
+    "/metrics",
+    "/ota/update",
+    "/api/metrics/binary",
+    "/api/config/restore",
+    "/api/files/upload",
+    "/api/boot-logo",
+    "/api/v1/display/screenshot",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteClass {
+    Light,
+    Heavy,
+}
+
+impl RouteClass {
+    pub fn of(uri: &str) -> Self {
+        let path = uri.split('?').next().unwrap_or(uri);
+        if HEAVY_ROUTES.contains(&path) {
+            Self::Heavy
+        } else {
+            Self::Light
+        }
+    }
+}
+
+/// Counters for /metrics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AdmissionStats {
+    pub light_admitted: u32,
+    pub heavy_admitted: u32,
+    pub heavy_rejected: u32,
+    pub heavy_in_flight: bool,
+}
+
+pub struct AdmissionController {
+    heavy_in_flight: AtomicBool,
+    light_admitted: AtomicU32,
+    heavy_admitted: AtomicU32,
+    heavy_rejected: AtomicU32,
+}
+
+/// Holds the heavy slot until dropped
+#[must_use = "the request is only admitted while the permit lives"]
+pub struct Permit<'a> {
+    heavy: Option<&'a AtomicBool>,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if let Some(slot) = self.heavy.take() {
+            slot.store(false, Ordering::Release);
+        }
+    }
+}
+
+impl AdmissionController {
+    pub const fn new() -> Self {
+        Self {
+            heavy_in_flight: AtomicBool::new(false),
+            light_admitted: AtomicU32::new(0),
+            heavy_admitted: AtomicU32::new(0),
+            heavy_rejected: AtomicU32::new(0),
+        }
+    }
+
+    /// A permit to run a request of `class`; None while another heavy one runs
+    pub fn admit(&self, class: RouteClass) -> Option<Permit<'_>> {
+        match class {
+            RouteClass::Light => {
+                self.light_admitted.fetch_add(1, Ordering::Relaxed);
+                Some(Permit { heavy: None })
+            }
+            RouteClass::Heavy => {
+                if self.heavy_in_flight.swap(true, Ordering::AcqRel) {
+                    self.heavy_rejected.fetch_add(1, Ordering::Relaxed);
+                    return None;
+                }
+                self.heavy_admitted.fetch_add(1, Ordering::Relaxed);
+                Some(Permit { heavy: Some(&self.heavy_in_flight) })
+            }
+        }
+    }
+
+    pub fn stats(&self) -> AdmissionStats {
+        AdmissionStats {
+            light_admitted: self.light_admitted.load(Ordering::Relaxed),
+            heavy_admitted: self.heavy_admitted.load(Ordering::Relaxed),
+            heavy_rejected: self.heavy_rejected.load(Ordering::Relaxed),
+            heavy_in_flight: self.heavy_in_flight.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Default for AdmissionController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static CONTROLLER: AdmissionController = AdmissionController::new();
+
+/// Admit a request for `uri` on the web server
+pub fn admit(uri: &str) -> Option<Permit<'static>> {
+    CONTROLLER.admit(RouteClass::of(uri))
+}
+
+pub fn stats() -> AdmissionStats {
+    CONTROLLER.stats()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_routes() {
+        assert_eq!(RouteClass::of("/"), RouteClass::Heavy);
+        assert_eq!(RouteClass::of("/metrics"), RouteClass::Heavy);
+        assert_eq!(RouteClass::of("/ota/update?offset=4096"), RouteClass::Heavy);
+        assert_eq!(RouteClass::of("/health"), RouteClass::Light);
+        assert_eq!(RouteClass::of("/api/metrics"), RouteClass::Light);
+    }
+
+    #[test]
+    fn test_one_heavy_request_at_a_time() {
+        let controller = AdmissionController::new();
+        let ota = controller.admit(RouteClass::Heavy).expect("first heavy request runs");
+        assert!(controller.admit(RouteClass::Heavy).is_none());
+        // Light requests are never held back
+        assert!(controller.admit(RouteClass::Light).is_some());
+        assert!(controller.stats().heavy_in_flight);
+
+        drop(ota);
+        assert!(controller.admit(RouteClass::Heavy).is_some());
+        assert_eq!(
+            controller.stats(),
+            AdmissionStats { light_admitted: 1, heavy_admitted: 2, heavy_rejected: 1, heavy_in_flight: false }
+        );
+    }
+}
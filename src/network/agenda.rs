@@ -0,0 +1,136 @@
+// Calendar integration: fetches an ICS feed (a calendar's "secret address",
+// or a CalDAV collection's export URL) every 15 minutes and keeps the upcoming
+// events for the Agenda screen. Parsing lives in `ics`.
+
+use anyhow::{anyhow, Result};
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use esp_idf_hal::delay::FreeRtos;
+use crate::config::Config;
+use crate::system::{clock, ShutdownSignal};
+use super::http_client::{self, RequestOptions};
+use super::ics::{self, Event};
+
+const POLL_INTERVAL_SECS: u32 = 15 * 60;
+const IDLE_POLL_SECS: u32 = 10;
+/// Retry sooner while the feed is unreachable or the clock unset
+const RETRY_SECS: u32 = 60;
+/// Events kept per fetch, so the screen still has some once the first ones end
+const STORED_EVENTS: usize = 20;
+const MAX_RESPONSE_BYTES: usize = 64 * 1024;
+const HTTP_TIMEOUT_MS: u64 = 10_000;
+
+static LATEST: Mutex<Option<Agenda>> = Mutex::new(None);
+static GENERATION: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Agenda {
+    /// Soonest first; may include events that have ended since the fetch
+    pub events: Vec<Event>,
+    /// Events shown (`agenda_events`)
+    pub limit: usize,
+    /// Set when the last fetch failed; `events` are then from the previous one
+    pub error: Option<String>,
+}
+
+impl Agenda {
+    /// The next `limit` events that have not ended at `now` (Unix seconds)
+    pub fn upcoming(&self, now: i64) -> impl Iterator<Item = &Event> {
+        self.events.iter().filter(move |e| e.end > now || e.start >= now).take(self.limit)
+    }
+}
+
+fn fetch(url: &str) -> Result<Vec<Event>> {
+    let options = RequestOptions {
+        timeout: Duration::from_millis(HTTP_TIMEOUT_MS),
+        max_response_bytes: MAX_RESPONSE_BYTES,
+        ..Default::default()
+    };
+    let now = clock::now_epoch().ok_or_else(|| anyhow!("clock not set"))?;
+    let body = http_client::get(url, &options)?.body;
+    Ok(ics::upcoming(&String::from_utf8_lossy(&body), now, STORED_EVENTS, clock::local_to_epoch))
+}
+
+/// Latest agenda, if a calendar URL is configured and has been fetched
+pub fn latest() -> Option<Agenda> {
+    LATEST.lock().ok().and_then(|s| s.clone())
+}
+
+/// Increments whenever the agenda changes
+pub fn generation() -> u32 {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+fn store(agenda: Option<Agenda>) {
+    if let Ok(mut slot) = LATEST.lock() {
+        if *slot != agenda {
+            *slot = agenda;
+            GENERATION.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Start the polling thread. Safe to call multiple times.
+pub fn start(config: Arc<Mutex<Config>>, shutdown: ShutdownSignal) -> Result<()> {
+    static STARTED: AtomicBool = AtomicBool::new(false);
+    if STARTED.swap(true, Ordering::SeqCst) { return Ok(()); }
+
+    std::thread::Builder::new()
+        .name("agenda".to_string())
+        .stack_size(12 * 1024)
+        .spawn(move || {
+            let mut fetched_url = String::new();
+            let mut wait_secs = 0;
+            loop {
+                let target = match config.lock() {
+                    Ok(cfg) if !cfg.agenda_url.is_empty() => Some((cfg.agenda_url.clone(), cfg.agenda_events as usize)),
+                    _ => None,
+                };
+
+                match target {
+                    // A new URL is fetched straight away
+                    Some((url, limit)) if wait_secs == 0 || url != fetched_url => {
+                        wait_secs = match fetch(&url) {
+                            Ok(events) => {
+                                store(Some(Agenda { events, limit, error: None }));
+                                POLL_INTERVAL_SECS
+                            }
+                            Err(e) => {
+                                log::warn!("Calendar fetch failed: {:?}", e);
+                                // Keep the last known events but flag the error
+                                let mut agenda = latest().filter(|_| url == fetched_url)
+                                    .unwrap_or(Agenda { events: Vec::new(), limit, error: None });
+                                agenda.error = Some("Calendar unreachable".to_string());
+                                store(Some(agenda));
+                                RETRY_SECS
+                            }
+                        };
+                        fetched_url = url;
+                    }
+                    Some((_, limit)) => {
+                        if let Some(agenda) = latest().filter(|a| a.limit != limit) {
+                            store(Some(Agenda { limit, ..agenda }));
+                        }
+                    }
+                    None => {
+                        store(None);
+                        fetched_url.clear();
+                        wait_secs = IDLE_POLL_SECS;
+                    }
+                }
+
+                // Checks the config every IDLE_POLL_SECS while waiting
+                for _ in 0..IDLE_POLL_SECS.min(wait_secs) {
+                    if shutdown.is_shutdown_requested() {
+                        return;
+                    }
+                    FreeRtos::delay_ms(1000);
+                }
+                wait_secs = wait_secs.saturating_sub(IDLE_POLL_SECS);
+            }
+        })?;
+
+    log::info!("Calendar poller started");
+    Ok(())
+}
@@ -0,0 +1,224 @@
+// Alertmanager integration: polls `/api/v2/alerts` for active, unsilenced alerts
+// and keeps the count plus the most severe ones for the Alerts screen.
+
+use anyhow::Result;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use esp_idf_hal::delay::FreeRtos;
+use crate::config::Config;
+use crate::system::ShutdownSignal;
+use super::http_client::{self, RequestOptions};
+
+pub const MAX_SHOWN_ALERTS: usize = 4;
+const POLL_INTERVAL_SECS: u32 = 30;
+const IDLE_POLL_SECS: u32 = 10;
+const MAX_RESPONSE_BYTES: usize = 16 * 1024;
+const HTTP_TIMEOUT_MS: u64 = 5000;
+
+static LATEST: Mutex<Option<AlertSummary>> = Mutex::new(None);
+static GENERATION: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Critical,
+    Warning,
+    Info,
+    Other,
+}
+
+impl Severity {
+    fn from_label(label: Option<&str>) -> Self {
+        match label.map(|s| s.to_ascii_lowercase()).as_deref() {
+            Some("critical") | Some("page") => Self::Critical,
+            Some("warning") | Some("warn") => Self::Warning,
+            Some("info") | Some("none") => Self::Info,
+            _ => Self::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FiringAlert {
+    pub name: String,
+    pub severity: Severity,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AlertSummary {
+    pub total: usize,
+    /// Most severe alerts first, at most MAX_SHOWN_ALERTS
+    pub top: Vec<FiringAlert>,
+    /// Set when the last poll failed; `top`/`total` are then from the previous poll
+    pub error: Option<String>,
+}
+
+impl AlertSummary {
+    pub fn worst_severity(&self) -> Option<Severity> {
+        self.top.first().map(|a| a.severity)
+    }
+}
+
+// Subset of the Alertmanager v2 gettableAlert schema
+#[derive(serde::Deserialize)]
+struct ApiAlert {
+    #[serde(default)]
+    labels: HashMap<String, String>,
+    #[serde(default)]
+    annotations: HashMap<String, String>,
+    #[serde(rename = "startsAt", default)]
+    starts_at: String,
+}
+
+/// Percent-encode a query parameter value
+fn encode_query_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() * 3);
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(b as char),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// Build the alerts query URL from a base URL and label matchers like `severity="critical"`
+pub fn build_url(base_url: &str, filters: &[String]) -> String {
+    let mut url = format!(
+        "{}/api/v2/alerts?active=true&silenced=false&inhibited=false",
+        base_url.trim_end_matches('/')
+    );
+    for filter in filters.iter().filter(|f| !f.trim().is_empty()) {
+        url.push_str("&filter=");
+        url.push_str(&encode_query_value(filter.trim()));
+    }
+    url
+}
+
+/// Parse an `/api/v2/alerts` response into a summary
+pub fn parse_alerts(body: &[u8]) -> Result<AlertSummary> {
+    let mut alerts: Vec<ApiAlert> = serde_json::from_slice(body)?;
+    let total = alerts.len();
+
+    // Most severe first, then newest first (RFC 3339 strings sort chronologically)
+    alerts.sort_by(|a, b| {
+        let sa = Severity::from_label(a.labels.get("severity").map(String::as_str));
+        let sb = Severity::from_label(b.labels.get("severity").map(String::as_str));
+        sa.cmp(&sb).then_with(|| b.starts_at.cmp(&a.starts_at))
+    });
+
+    let top = alerts.into_iter()
+        .take(MAX_SHOWN_ALERTS)
+        .map(|mut a| FiringAlert {
+            severity: Severity::from_label(a.labels.get("severity").map(String::as_str)),
+            name: a.labels.remove("alertname").unwrap_or_else(|| "unnamed".to_string()),
+            summary: a.annotations.remove("summary").unwrap_or_default(),
+        })
+        .collect();
+
+    Ok(AlertSummary { total, top, error: None })
+}
+
+fn http_get(url: &str) -> Result<Vec<u8>> {
+    let options = RequestOptions {
+        timeout: Duration::from_millis(HTTP_TIMEOUT_MS),
+        max_response_bytes: MAX_RESPONSE_BYTES,
+        ..Default::default()
+    };
+    Ok(http_client::get(url, &options)?.body)
+}
+
+/// Latest alert summary, if polling is enabled and has run
+pub fn latest() -> Option<AlertSummary> {
+    LATEST.lock().ok().and_then(|s| s.clone())
+}
+
+/// Increments whenever the summary changes
+pub fn generation() -> u32 {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+fn store(summary: Option<AlertSummary>) {
+    if let Ok(mut slot) = LATEST.lock() {
+        if *slot != summary {
+            *slot = summary;
+            GENERATION.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Start the polling thread. Safe to call multiple times.
+pub fn start(config: Arc<Mutex<Config>>, shutdown: ShutdownSignal) -> Result<()> {
+    static STARTED: AtomicBool = AtomicBool::new(false);
+    if STARTED.swap(true, Ordering::SeqCst) { return Ok(()); }
+
+    std::thread::Builder::new()
+        .name("alertmanager".to_string())
+        .stack_size(10 * 1024)
+        .spawn(move || loop {
+            let target = match config.lock() {
+                Ok(cfg) if cfg.alerts_enabled && !cfg.alerts_url.is_empty() => {
+                    Some(build_url(&cfg.alerts_url, &cfg.alerts_filters))
+                }
+                _ => None,
+            };
+
+            let wait_secs = match target {
+                Some(url) => {
+                    match http_get(&url).and_then(|body| parse_alerts(&body)) {
+                        Ok(summary) => store(Some(summary)),
+                        Err(e) => {
+                            log::warn!("Alertmanager poll failed: {:?}", e);
+                            // Keep the last known alerts but flag the error
+                            let mut summary = latest().unwrap_or(AlertSummary { total: 0, top: Vec::new(), error: None });
+                            summary.error = Some("Alertmanager unreachable".to_string());
+                            store(Some(summary));
+                        }
+                    }
+                    POLL_INTERVAL_SECS
+                }
+                None => {
+                    store(None);
+                    IDLE_POLL_SECS
+                }
+            };
+
+            for _ in 0..wait_secs {
+                if shutdown.is_shutdown_requested() {
+                    return;
+                }
+                FreeRtos::delay_ms(1000);
+            }
+        })?;
+
+    log::info!("Alertmanager poller started");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_sort_alerts() {
+        let body = br#"[
+            {"labels":{"alertname":"DiskFull","severity":"warning"},"annotations":{},"startsAt":"2024-05-01T10:00:00Z"},
+            {"labels":{"alertname":"NodeDown","severity":"critical"},"annotations":{"summary":"node1 down"},"startsAt":"2024-05-01T09:00:00Z"},
+            {"labels":{"alertname":"Noise"},"annotations":{},"startsAt":"2024-05-01T11:00:00Z"}
+        ]"#;
+        let summary = parse_alerts(body).unwrap();
+        assert_eq!(summary.total, 3);
+        assert_eq!(summary.top[0].name, "NodeDown");
+        assert_eq!(summary.top[0].summary, "node1 down");
+        assert_eq!(summary.top[1].severity, Severity::Warning);
+        assert_eq!(summary.worst_severity(), Some(Severity::Critical));
+    }
+
+    #[test]
+    fn test_build_url() {
+        let url = build_url("http://am:9093/", &["severity=\"critical\"".to_string()]);
+        assert_eq!(url, "http://am:9093/api/v2/alerts?active=true&silenced=false&inhibited=false&filter=severity%3D%22critical%22");
+    }
+}
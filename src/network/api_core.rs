@@ -0,0 +1,735 @@
+// Hardware-independent parts of the web API: request parsing, validation and
+// response assembly. Handlers in web_server.rs and api_routes.rs only do the
+// HTTP I/O and locking around these, and host-tests drives the same functions
+// through a mock HTTP layer.
+
+use serde_json::{json, Value};
+use crate::config::{Config, ConfigError, CustomWidget, FieldError, PushFormat, Theme};
+use crate::config::profiles::ConfigProfile;
+use crate::hardware::buzzer::SoundMap;
+use crate::i18n::Language;
+use crate::input::action_map::ButtonMap;
+use crate::ui::animation::EasingFunction;
+use crate::display::pacing::PacingStrategy;
+use crate::config::schedule::{DataSource, SourceSchedule};
+use crate::units::{ClockFormat, TemperatureUnit};
+use crate::metrics_data::MetricsData;
+use crate::network::offline_queue::DropPolicy;
+use crate::network::validators;
+use std::collections::BTreeMap;
+use crate::sensors::history::{HistoryPoints, SensorHistory};
+
+/// Largest POST /api/config body (custom widget lists can be long)
+pub const MAX_CONFIG_BODY: usize = 4096;
+
+/// Default window for history exports
+pub const DEFAULT_HISTORY_HOURS: u32 = 24;
+
+/// Free heap below which /api/summary reports "degraded" (same bar as /health)
+pub const SUMMARY_LOW_HEAP: u32 = 50_000;
+
+/// POST /api/config body; every field is optional so clients can send partial updates
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct WebConfigUpdate {
+    pub wifi_ssid: Option<String>,
+    pub wifi_password: Option<String>,
+    pub ap_enabled: Option<bool>,
+    /// Empty uses `<hostname>-setup`
+    pub ap_ssid: Option<String>,
+    pub ap_password: Option<String>,
+    /// 0 keeps the AP up
+    pub ap_auto_off: Option<u32>,
+    /// Empty switches back to DHCP
+    pub static_ip: Option<String>,
+    pub static_netmask: Option<String>,
+    pub static_gateway: Option<String>,
+    pub static_dns: Option<Vec<String>>,
+    pub brightness: Option<u8>,
+    pub auto_dim: Option<bool>,
+    pub dim_timeout: Option<u32>,
+    pub sleep_timeout: Option<u32>,
+    pub auto_update: Option<bool>,
+    pub update_interval: Option<u32>,
+    pub screen_order: Option<Vec<String>>,
+    /// `en`, `de` or `es`
+    pub language: Option<Language>,
+    /// `celsius` or `fahrenheit`
+    pub temperature_unit: Option<TemperatureUnit>,
+    /// `24h` or `12h`
+    pub clock_format: Option<ClockFormat>,
+    pub accessibility_mode: Option<bool>,
+    pub kiosk_enabled: Option<bool>,
+    /// `temperature`, `battery`, a sensor key like `scd40.co2` or `widget:<label>`
+    pub kiosk_metric: Option<String>,
+    pub push_enabled: Option<bool>,
+    pub push_url: Option<String>,
+    pub push_format: Option<PushFormat>,
+    pub push_interval: Option<u32>,
+    pub push_batch_size: Option<u32>,
+    pub push_queue_size: Option<u32>,
+    pub push_drop_policy: Option<DropPolicy>,
+    /// Empty for unprefixed series
+    pub metrics_prefix: Option<String>,
+    /// Replaces all global labels, e.g. `{"site": "lab", "device_id": "desk-1"}`
+    pub metrics_labels: Option<BTreeMap<String, String>>,
+    pub weather_enabled: Option<bool>,
+    pub weather_latitude: Option<f32>,
+    pub weather_longitude: Option<f32>,
+    pub custom_widgets: Option<Vec<CustomWidget>>,
+    pub alerts_enabled: Option<bool>,
+    pub alerts_url: Option<String>,
+    pub alerts_filters: Option<Vec<String>>,
+    /// ICS feed; empty disables the Agenda screen's fetch
+    pub agenda_url: Option<String>,
+    pub agenda_events: Option<u8>,
+    pub pomodoro_minutes: Option<u32>,
+    /// 0 disables the alert pin (GPIO0 is the BOOT button)
+    pub timer_alert_gpio: Option<u8>,
+    pub remote_sync_enabled: Option<bool>,
+    pub remote_sync_url: Option<String>,
+    pub remote_sync_key: Option<String>,
+    pub remote_sync_interval: Option<u32>,
+    pub remote_sync_allow_wifi: Option<bool>,
+    pub profiles_enabled: Option<bool>,
+    pub day_profile: Option<ConfigProfile>,
+    pub night_profile: Option<ConfigProfile>,
+    /// "HH:MM" local time
+    pub day_start: Option<String>,
+    pub night_start: Option<String>,
+    pub timezone: Option<String>,
+    pub hostname: Option<String>,
+    /// Empty disables the reference host ping
+    pub latency_host: Option<String>,
+    pub nettest_url: Option<String>,
+    /// Entries to replace, e.g. `{"sensors": {"interval_ms": 60000, "enabled": true}}`
+    pub sample_schedule: Option<BTreeMap<DataSource, SourceSchedule>>,
+    /// Main-loop frame rate, 0 = uncapped
+    pub target_fps: Option<u8>,
+    pub frame_pacing: Option<PacingStrategy>,
+    /// Die temperature (°C) that triggers thermal throttling
+    pub thermal_limit: Option<f32>,
+    pub quiet_hours_enabled: Option<bool>,
+    /// "HH:MM" local time
+    pub quiet_start: Option<String>,
+    pub quiet_end: Option<String>,
+    pub ota_window_enabled: Option<bool>,
+    /// "HH:MM" local time
+    pub ota_window_start: Option<String>,
+    pub ota_window_end: Option<String>,
+    pub burn_in_shift: Option<bool>,
+    pub burn_in_screensaver: Option<bool>,
+    pub battery_profile: Option<bool>,
+    /// Minutes between WiFi windows
+    pub battery_wifi_interval: Option<u32>,
+    /// Seconds per WiFi window
+    pub battery_wifi_window: Option<u32>,
+    /// Idle seconds before deep sleep
+    pub battery_sleep_after: Option<u32>,
+    /// 0 disables the buzzer
+    pub buzzer_gpio: Option<u8>,
+    pub buzzer_sounds: Option<SoundMap>,
+    pub buzzer_muted: Option<bool>,
+    /// 0 disables the status LEDs
+    pub led_gpio: Option<u8>,
+    pub led_count: Option<u8>,
+    pub led_enabled: Option<bool>,
+    pub led_brightness: Option<u8>,
+    /// 0 disables the INA219 power monitor
+    pub power_monitor_sda: Option<u8>,
+    pub power_monitor_scl: Option<u8>,
+    /// 7-bit I2C address, 0x40-0x4F
+    pub power_monitor_address: Option<u8>,
+    pub power_monitor_shunt_mohm: Option<u16>,
+    /// 0 disables the air quality sensors
+    pub air_quality_sda: Option<u8>,
+    pub air_quality_scl: Option<u8>,
+    /// 0 disables the pin
+    pub encoder_a_gpio: Option<u8>,
+    pub encoder_b_gpio: Option<u8>,
+    pub encoder_button_gpio: Option<u8>,
+    pub button_actions: Option<ButtonMap>,
+    pub mqtt_broker: Option<String>,
+    pub backlight_fade_ms: Option<u32>,
+    pub backlight_easing: Option<EasingFunction>,
+    pub adaptive_brightness: Option<bool>,
+    /// Shared by the display and the web pages
+    pub theme: Option<Theme>,
+    pub syslog_enabled: Option<bool>,
+    pub syslog_server: Option<String>,
+}
+
+/// Parse, validate and apply a POST /api/config body on top of `current`.
+/// Errors are client errors; validation failures come back as
+/// `ConfigError::Fields`, one entry per bad field, for the web UI to show
+/// next to its inputs.
+pub fn apply_config_update(current: &Config, body: &[u8]) -> Result<Config, ConfigError> {
+    let value: Value = serde_json::from_slice(body).map_err(|e| {
+        log::warn!("Invalid config update JSON: {}", e);
+        ConfigError::InvalidJson
+    })?;
+    let update: WebConfigUpdate = match serde_json::from_value(value.clone()) {
+        Ok(update) => update,
+        Err(e) => {
+            log::warn!("Invalid config update: {}", e);
+            return Err(type_errors(&value).unwrap_or(ConfigError::InvalidJson));
+        }
+    };
+
+    let mut errors = validators::FieldErrors::default();
+    if let Some(ref ssid) = update.wifi_ssid {
+        errors.check("wifi_ssid", validators::validate_ssid(ssid));
+    }
+    if update.wifi_password.as_ref().is_some_and(|pw| pw.len() > 64) {
+        errors.check("wifi_password", Err::<(), _>(ConfigError::Invalid("WiFi password must be 64 characters or less".into())));
+    }
+    if let Some(ssid) = update.ap_ssid.as_ref().filter(|s| !s.is_empty()) {
+        errors.check("ap_ssid", validators::validate_ssid(ssid));
+    }
+    if let Some(ref pw) = update.ap_password {
+        errors.check("ap_password", validators::validate_ap_password(pw));
+    }
+    let urls = [
+        ("push_url", &update.push_url), ("alerts_url", &update.alerts_url), ("agenda_url", &update.agenda_url),
+        ("remote_sync_url", &update.remote_sync_url), ("nettest_url", &update.nettest_url),
+    ];
+    for (field, url) in urls {
+        if let Some(url) = url {
+            errors.check(field, validators::validate_push_url(url));
+        }
+    }
+    if let Some(ref prefix) = update.metrics_prefix {
+        errors.check("metrics_prefix", validators::validate_metrics_prefix(prefix));
+    }
+    if let Some(ref labels) = update.metrics_labels {
+        errors.check("metrics_labels", validators::validate_metrics_labels(labels));
+    }
+    if update.kiosk_metric.as_deref().is_some_and(|m| crate::ui::kiosk::KioskSource::parse(m).is_none()) {
+        errors.check("kiosk_metric", Err::<(), _>(ConfigError::Invalid("Kiosk metric must be temperature, battery, a sensor key like scd40.co2 or widget:<label>".into())));
+    }
+    if let Some(ref filters) = update.alerts_filters {
+        if filters.len() > 4 || filters.iter().any(|f| f.len() > 64) {
+            errors.check("alerts_filters", Err::<(), _>(ConfigError::Invalid("At most 4 alert filters of up to 64 characters".into())));
+        }
+    }
+    let optional_pins = [
+        ("timer_alert_gpio", update.timer_alert_gpio), ("buzzer_gpio", update.buzzer_gpio), ("led_gpio", update.led_gpio),
+        ("encoder_a_gpio", update.encoder_a_gpio), ("encoder_b_gpio", update.encoder_b_gpio),
+        ("encoder_button_gpio", update.encoder_button_gpio),
+        ("power_monitor_sda", update.power_monitor_sda), ("power_monitor_scl", update.power_monitor_scl),
+        ("air_quality_sda", update.air_quality_sda), ("air_quality_scl", update.air_quality_scl),
+    ];
+    for (field, gpio) in optional_pins {
+        if let Some(gpio) = gpio.filter(|&g| g != 0 && !validators::is_pin_available(g)) {
+            errors.check(field, Err::<(), _>(ConfigError::Invalid(format!("GPIO{} is reserved or invalid", gpio))));
+        }
+    }
+    if update.power_monitor_address.is_some_and(|a| !(0x40..=0x4F).contains(&a)) {
+        errors.check("power_monitor_address", Err::<(), _>(ConfigError::Invalid("INA219 address must be 0x40-0x4F".into())));
+    }
+    if update.power_monitor_shunt_mohm == Some(0) {
+        errors.check("power_monitor_shunt_mohm", Err::<(), _>(ConfigError::Invalid("Shunt resistance must be above 0".into())));
+    }
+    let mut time_of_day = |field: &str, value: &Option<String>| {
+        value.as_deref().and_then(|v| errors.check(field, validators::parse_time_of_day(v)))
+    };
+    let day_start = time_of_day("day_start", &update.day_start);
+    let night_start = time_of_day("night_start", &update.night_start);
+    let quiet_start = time_of_day("quiet_start", &update.quiet_start);
+    let quiet_end = time_of_day("quiet_end", &update.quiet_end);
+    let ota_window_start = time_of_day("ota_window_start", &update.ota_window_start);
+    let ota_window_end = time_of_day("ota_window_end", &update.ota_window_end);
+    if let Some(ref tz) = update.timezone {
+        errors.check("timezone", validators::validate_timezone(tz));
+    }
+    if let Some(ref name) = update.hostname {
+        errors.check("hostname", validators::validate_hostname(name));
+    }
+    if let Some(ref host) = update.latency_host {
+        errors.check("latency_host", validators::validate_ping_host(host));
+    }
+    if let Some(ref order) = update.screen_order {
+        errors.check("screen_order", validators::validate_screen_order(order));
+    }
+    for (&source, entry) in update.sample_schedule.iter().flatten() {
+        errors.check("sample_schedule", validators::validate_source_schedule(source, entry));
+    }
+    if let Some(ref widgets) = update.custom_widgets {
+        if widgets.len() > validators::MAX_CUSTOM_WIDGETS {
+            errors.check("custom_widgets", Err::<(), _>(ConfigError::Invalid(format!("At most {} custom widgets", validators::MAX_CUSTOM_WIDGETS))));
+        }
+        for widget in widgets {
+            errors.check("custom_widgets", validators::validate_custom_widget(widget));
+        }
+    }
+
+    // Numbers outside their range are rejected rather than clamped, so the
+    // form shows what was wrong instead of saving something else
+    use validators::validate_range as range;
+    let ranges = [
+        ("ap_auto_off", update.ap_auto_off.map(|v| range("AP auto-off", v, 0, 24 * 60, " min"))),
+        ("dim_timeout", update.dim_timeout.map(|v| range("Dim timeout", v, 5, 3600, " s"))),
+        ("sleep_timeout", update.sleep_timeout.map(|v| range("Sleep timeout", v, 10, 24 * 3600, " s"))),
+        ("update_interval", update.update_interval.map(|v| range("Update interval", v, 1, 30 * 24, " h"))),
+        ("push_interval", update.push_interval.map(|v| range("Push interval", v, 5, 3600, " s"))),
+        ("push_batch_size", update.push_batch_size.map(|v| range("Push batch size", v, 1, 60, ""))),
+        ("push_queue_size", update.push_queue_size.map(|v| range("Push queue size", v, 60, 5000, ""))),
+        ("weather_latitude", update.weather_latitude.map(|v| range("Latitude", v, -90.0, 90.0, ""))),
+        ("weather_longitude", update.weather_longitude.map(|v| range("Longitude", v, -180.0, 180.0, ""))),
+        ("agenda_events", update.agenda_events.map(|v| range("Agenda events", v, 1, 10, ""))),
+        ("pomodoro_minutes", update.pomodoro_minutes.map(|v| range("Pomodoro length", v, 1, 180, " min"))),
+        ("remote_sync_interval", update.remote_sync_interval.map(|v| range("Remote sync interval", v, 60, 24 * 3600, " s"))),
+        ("led_count", update.led_count.map(|v| range("LED count", v, 1, crate::hardware::status_led::MAX_LEDS as u8, ""))),
+        ("target_fps", update.target_fps.map(|v| range("Target FPS", v, 0, crate::display::pacing::MAX_TARGET_FPS, ""))),
+        ("thermal_limit", update.thermal_limit.map(|v| range("Thermal limit", v, 50.0, 100.0, " C"))),
+        ("battery_wifi_interval", update.battery_wifi_interval.map(|v| range("Battery WiFi interval", v, 1, 24 * 60, " min"))),
+        ("battery_wifi_window", update.battery_wifi_window.map(|v| range("Battery WiFi window", v, 20, 600, " s"))),
+        ("battery_sleep_after", update.battery_sleep_after.map(|v| range("Battery sleep delay", v, 30, 3600, " s"))),
+        ("backlight_fade_ms", update.backlight_fade_ms.map(|v| range("Backlight fade", v, 0, crate::power::backlight::MAX_FADE_MS, " ms"))),
+    ];
+    for (field, result) in ranges {
+        if let Some(result) = result {
+            errors.check(field, result);
+        }
+    }
+    // The checks on the merged config below assume these passed
+    errors.into_result()?;
+
+    // Start from the existing config and apply only the fields provided
+    let mut cfg = current.clone();
+    if let Some(ssid) = update.wifi_ssid { cfg.wifi_ssid = ssid; }
+    if let Some(pw) = update.wifi_password { cfg.wifi_password = pw; }
+    if let Some(ap) = update.ap_enabled { cfg.ap_enabled = ap; }
+    if let Some(ssid) = update.ap_ssid { cfg.ap_ssid = ssid; }
+    if let Some(pw) = update.ap_password { cfg.ap_password = pw; }
+    if let Some(mins) = update.ap_auto_off { cfg.ap_auto_off_mins = mins; }
+    let static_ip_changed = update.static_ip.is_some() || update.static_netmask.is_some()
+        || update.static_gateway.is_some() || update.static_dns.is_some();
+    if let Some(ip) = update.static_ip { cfg.static_ip = ip.trim().to_string(); }
+    if let Some(mask) = update.static_netmask { cfg.static_netmask = mask.trim().to_string(); }
+    if let Some(gw) = update.static_gateway { cfg.static_gateway = gw.trim().to_string(); }
+    if let Some(dns) = update.static_dns {
+        cfg.static_dns = dns.iter().map(|d| d.trim().to_string()).filter(|d| !d.is_empty()).collect();
+    }
+    if let Some(br) = update.brightness { cfg.brightness = br; }
+    if let Some(ad) = update.auto_dim { cfg.auto_brightness = ad; }
+    if let Some(dim) = update.dim_timeout { cfg.dim_timeout_secs = dim; }
+    if let Some(slp) = update.sleep_timeout { cfg.sleep_timeout_secs = slp; }
+    if let Some(au) = update.auto_update { cfg.ota_enabled = au; }
+    if let Some(iv) = update.update_interval { cfg.ota_check_interval_hours = iv; }
+    if let Some(order) = update.screen_order { cfg.screen_order = order; }
+    if let Some(language) = update.language { cfg.language = language; }
+    if let Some(unit) = update.temperature_unit { cfg.temperature_unit = unit; }
+    if let Some(format) = update.clock_format { cfg.clock_format = format; }
+    if let Some(enabled) = update.accessibility_mode { cfg.accessibility_mode = enabled; }
+    if let Some(enabled) = update.kiosk_enabled { cfg.kiosk_enabled = enabled; }
+    if let Some(metric) = update.kiosk_metric { cfg.kiosk_metric = metric.trim().to_string(); }
+    if let Some(pe) = update.push_enabled { cfg.push_enabled = pe; }
+    if let Some(url) = update.push_url { cfg.push_url = url; }
+    if let Some(fmt) = update.push_format { cfg.push_format = fmt; }
+    if let Some(iv) = update.push_interval { cfg.push_interval_secs = iv; }
+    if let Some(bs) = update.push_batch_size { cfg.push_batch_size = bs; }
+    if let Some(qs) = update.push_queue_size { cfg.push_queue_size = qs; }
+    if let Some(policy) = update.push_drop_policy { cfg.push_drop_policy = policy; }
+    if let Some(prefix) = update.metrics_prefix { cfg.metrics_prefix = prefix; }
+    if let Some(labels) = update.metrics_labels { cfg.metrics_labels = labels; }
+    if let Some(we) = update.weather_enabled { cfg.weather_enabled = we; }
+    if let Some(lat) = update.weather_latitude { cfg.weather_latitude = lat; }
+    if let Some(lon) = update.weather_longitude { cfg.weather_longitude = lon; }
+    if let Some(widgets) = update.custom_widgets { cfg.custom_widgets = widgets; }
+    if let Some(ae) = update.alerts_enabled { cfg.alerts_enabled = ae; }
+    if let Some(url) = update.alerts_url { cfg.alerts_url = url; }
+    if let Some(filters) = update.alerts_filters { cfg.alerts_filters = filters; }
+    if let Some(url) = update.agenda_url { cfg.agenda_url = url; }
+    if let Some(n) = update.agenda_events { cfg.agenda_events = n; }
+    if let Some(mins) = update.pomodoro_minutes { cfg.pomodoro_minutes = mins; }
+    if let Some(gpio) = update.timer_alert_gpio { cfg.timer_alert_gpio = (gpio != 0).then_some(gpio); }
+    if let Some(rs) = update.remote_sync_enabled { cfg.remote_sync_enabled = rs; }
+    if let Some(url) = update.remote_sync_url { cfg.remote_sync_url = url; }
+    if let Some(key) = update.remote_sync_key { cfg.remote_sync_key = key; }
+    if let Some(iv) = update.remote_sync_interval { cfg.remote_sync_interval_secs = iv; }
+    if let Some(aw) = update.remote_sync_allow_wifi { cfg.remote_sync_allow_wifi = aw; }
+    if let Some(pe) = update.profiles_enabled { cfg.profiles_enabled = pe; }
+    if let Some(p) = update.day_profile { cfg.day_profile = p; }
+    if let Some(p) = update.night_profile { cfg.night_profile = p; }
+    if let Some(m) = day_start { cfg.day_start_minutes = m; }
+    if let Some(m) = night_start { cfg.night_start_minutes = m; }
+    if let Some(tz) = update.timezone { cfg.timezone = tz; }
+    if let Some(name) = update.hostname { cfg.hostname = name; }
+    if let Some(host) = update.latency_host { cfg.latency_host = host; }
+    if let Some(url) = update.nettest_url { cfg.nettest_url = url; }
+    if let Some(gpio) = update.buzzer_gpio { cfg.buzzer_gpio = (gpio != 0).then_some(gpio); }
+    if let Some(sounds) = update.buzzer_sounds { cfg.buzzer_sounds = sounds; }
+    if let Some(muted) = update.buzzer_muted { cfg.buzzer_muted = muted; }
+    if let Some(gpio) = update.led_gpio { cfg.led_gpio = (gpio != 0).then_some(gpio); }
+    if let Some(n) = update.led_count { cfg.led_count = n; }
+    if let Some(en) = update.led_enabled { cfg.led_enabled = en; }
+    if let Some(br) = update.led_brightness { cfg.led_brightness = br; }
+    if let Some(gpio) = update.power_monitor_sda { cfg.power_monitor_sda = (gpio != 0).then_some(gpio); }
+    if let Some(gpio) = update.power_monitor_scl { cfg.power_monitor_scl = (gpio != 0).then_some(gpio); }
+    if let Some(addr) = update.power_monitor_address { cfg.power_monitor_address = addr; }
+    if let Some(mohm) = update.power_monitor_shunt_mohm { cfg.power_monitor_shunt_mohm = mohm; }
+    if let Some(gpio) = update.air_quality_sda { cfg.air_quality_sda = (gpio != 0).then_some(gpio); }
+    if let Some(gpio) = update.air_quality_scl { cfg.air_quality_scl = (gpio != 0).then_some(gpio); }
+    if let Some(gpio) = update.encoder_a_gpio { cfg.encoder_a_gpio = (gpio != 0).then_some(gpio); }
+    if let Some(gpio) = update.encoder_b_gpio { cfg.encoder_b_gpio = (gpio != 0).then_some(gpio); }
+    if let Some(gpio) = update.encoder_button_gpio { cfg.encoder_button_gpio = (gpio != 0).then_some(gpio); }
+    for (source, entry) in update.sample_schedule.into_iter().flatten() { cfg.sample_schedule.set(source, entry); }
+    if let Some(fps) = update.target_fps { cfg.target_fps = fps; }
+    if let Some(pacing) = update.frame_pacing { cfg.frame_pacing = pacing; }
+    if let Some(t) = update.thermal_limit { cfg.thermal_limit_c = t; }
+    if let Some(qe) = update.quiet_hours_enabled { cfg.quiet_hours_enabled = qe; }
+    if let Some(m) = quiet_start { cfg.quiet_start_minutes = m; }
+    if let Some(m) = quiet_end { cfg.quiet_end_minutes = m; }
+    if let Some(enabled) = update.ota_window_enabled { cfg.ota_window_enabled = enabled; }
+    if let Some(m) = ota_window_start { cfg.ota_window_start_minutes = m; }
+    if let Some(m) = ota_window_end { cfg.ota_window_end_minutes = m; }
+    if let Some(shift) = update.burn_in_shift { cfg.burn_in_shift = shift; }
+    if let Some(saver) = update.burn_in_screensaver { cfg.burn_in_screensaver = saver; }
+    if let Some(bp) = update.battery_profile { cfg.battery_profile = bp; }
+    if let Some(mins) = update.battery_wifi_interval { cfg.battery_wifi_interval_mins = mins; }
+    if let Some(secs) = update.battery_wifi_window { cfg.battery_wifi_window_secs = secs; }
+    if let Some(secs) = update.battery_sleep_after { cfg.battery_sleep_after_secs = secs; }
+    let update_has_actions = update.button_actions.is_some();
+    if let Some(map) = update.button_actions { cfg.button_actions = map; }
+    if let Some(url) = update.mqtt_broker { cfg.mqtt_broker = url.trim().to_string(); }
+    if let Some(ms) = update.backlight_fade_ms { cfg.backlight_fade_ms = ms; }
+    if let Some(easing) = update.backlight_easing { cfg.backlight_easing = easing; }
+    if let Some(adaptive) = update.adaptive_brightness { cfg.adaptive_brightness = adaptive; }
+    if let Some(theme) = update.theme { cfg.theme = theme; }
+    if let Some(enabled) = update.syslog_enabled { cfg.syslog_enabled = enabled; }
+    if let Some(server) = update.syslog_server { cfg.syslog_server = server.trim().to_string(); }
+    // Checked on the merged config so a partial update can't leave an
+    // unusable combination; reported against the later of two clashing pins
+    let mut errors = validators::FieldErrors::default();
+    let pins: Vec<(&str, u8)> = [
+        ("timer_alert_gpio", cfg.timer_alert_gpio), ("buzzer_gpio", cfg.buzzer_gpio), ("led_gpio", cfg.led_gpio),
+        ("encoder_a_gpio", cfg.encoder_a_gpio), ("encoder_b_gpio", cfg.encoder_b_gpio),
+        ("encoder_button_gpio", cfg.encoder_button_gpio),
+        ("power_monitor_sda", cfg.power_monitor_sda), ("power_monitor_scl", cfg.power_monitor_scl),
+        ("air_quality_sda", cfg.air_quality_sda), ("air_quality_scl", cfg.air_quality_scl),
+    ].into_iter().filter_map(|(field, gpio)| Some((field, gpio?))).collect();
+    if let Some(&(field, _)) = pins.iter().enumerate().find(|(i, (_, gpio))| pins[..*i].iter().any(|p| p.1 == *gpio)).map(|(_, p)| p) {
+        errors.check(field, Err::<(), _>(ConfigError::Invalid("Each optional peripheral needs its own GPIO".into())));
+    }
+    let actions_field = if update_has_actions { "button_actions" } else { "mqtt_broker" };
+    errors.check(actions_field, validators::validate_button_actions(&cfg.button_actions, &cfg.mqtt_broker));
+    errors.check("syslog_server", validators::validate_syslog_server(cfg.syslog_enabled, &cfg.syslog_server));
+    if static_ip_changed {
+        errors.check("static_ip", validators::parse_static_ipv4(&cfg.static_ip, &cfg.static_netmask, &cfg.static_gateway, &cfg.static_dns));
+    }
+    errors.into_result().map(|_| cfg)
+}
+
+/// Per-field errors for a body that is JSON but doesn't fit WebConfigUpdate
+/// (a string for a number, 300 for a u8, an unknown enum name): each key is
+/// tried on its own to find the culprits. None if no single key fails.
+fn type_errors(value: &Value) -> Option<ConfigError> {
+    let errors: Vec<FieldError> = value.as_object()?.iter()
+        .filter_map(|(field, v)| {
+            let single = Value::Object([(field.clone(), v.clone())].into_iter().collect());
+            let e = serde_json::from_value::<WebConfigUpdate>(single).err()?;
+            let detail = e.to_string();
+            let detail = detail.strip_prefix("invalid value: ").or_else(|| detail.strip_prefix("invalid type: ")).unwrap_or(&detail);
+            Some(FieldError { field: field.clone(), message: format!("Invalid {}: {}", field, detail) })
+        })
+        .collect();
+    (!errors.is_empty()).then_some(ConfigError::Fields(errors))
+}
+
+/// Largest POST /api/control body
+pub const MAX_CONTROL_BODY: usize = 512;
+
+/// CPU performance mode requested through /api/control `{"mode": "..."}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PerformanceMode {
+    Eco,
+    Normal,
+    Turbo,
+}
+
+impl PerformanceMode {
+    /// Unknown names fall back to Normal, as the endpoint always has
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "eco" => Self::Eco,
+            "turbo" => Self::Turbo,
+            _ => Self::Normal,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Eco => "eco",
+            Self::Normal => "normal",
+            Self::Turbo => "turbo",
+        }
+    }
+
+    /// (max, min) CPU frequency for the power manager
+    pub fn freq_mhz(self) -> (i32, i32) {
+        match self {
+            Self::Eco => (80, 40),
+            Self::Normal => (160, 80),
+            Self::Turbo => (240, 80),
+        }
+    }
+}
+
+/// POST /api/control body. Every field is optional; fields of the wrong type
+/// are ignored, as clients have always been able to send extra keys.
+#[derive(Debug, Default, PartialEq)]
+pub struct ControlCommand {
+    pub brightness: Option<u8>,
+    pub mute: Option<bool>,
+    pub sound: Option<crate::hardware::buzzer::SoundEvent>,
+    pub input: Option<crate::input::InputEvent>,
+    pub screen: Option<usize>,
+    pub quiet_override: Option<bool>,
+    pub display: Option<bool>,
+    pub mode: Option<PerformanceMode>,
+}
+
+impl ControlCommand {
+    pub fn parse(body: &[u8]) -> Result<Self, ConfigError> {
+        let value: Value = serde_json::from_slice(body).map_err(|_| ConfigError::InvalidJson)?;
+        let sound = match value.get("sound").and_then(Value::as_str) {
+            Some(name) => Some(crate::hardware::buzzer::SoundEvent::from_name(name)
+                .ok_or_else(|| ConfigError::Invalid("Unknown sound".into()))?),
+            None => None,
+        };
+        let input = match value.get("input").and_then(Value::as_str) {
+            Some(name) => Some(crate::input::InputEvent::from_name(name)
+                .ok_or_else(|| ConfigError::Invalid("Unknown input".into()))?),
+            None => None,
+        };
+        Ok(Self {
+            brightness: value.get("brightness").and_then(Value::as_u64).map(|b| b.min(255) as u8),
+            mute: value.get("mute").and_then(Value::as_bool),
+            sound,
+            input,
+            screen: value.get("screen").and_then(Value::as_u64).map(|s| usize::try_from(s).unwrap_or(usize::MAX)),
+            quiet_override: value.get("quiet_override").and_then(Value::as_bool),
+            display: value.get("display").and_then(Value::as_bool),
+            mode: value.get("mode").and_then(Value::as_str).map(PerformanceMode::from_name),
+        })
+    }
+}
+
+/// POST /api/wifi/pin body: `{"bssid": "aa:bb:cc:dd:ee:ff"}`, or `""`/`null`
+/// to unpin. Returns the updated config and the BSSID to apply.
+pub fn apply_bssid_pin(current: &Config, body: &[u8]) -> Result<(Config, Option<[u8; 6]>), ConfigError> {
+    #[derive(serde::Deserialize)]
+    struct PinRequest {
+        bssid: Option<String>,
+    }
+
+    let request: PinRequest = serde_json::from_slice(body).map_err(|_| ConfigError::InvalidJson)?;
+    let bssid = match request.bssid.as_deref().map(str::trim).filter(|b| !b.is_empty()) {
+        Some(text) => Some(
+            crate::network::connection_history::parse_bssid(text)
+                .ok_or_else(|| ConfigError::Invalid("BSSID must look like aa:bb:cc:dd:ee:ff".into()))?,
+        ),
+        None => None,
+    };
+    let mut cfg = current.clone();
+    cfg.wifi_bssid = bssid.map(|b| crate::network::connection_history::format_bssid(&b)).unwrap_or_default();
+    Ok((cfg, bssid))
+}
+
+/// GET /api/metrics body. `metrics` is None when the store was busy, in which
+/// case only the always-available fields are returned.
+pub fn metrics_json(uptime: u64, heap_free: u32, metrics: Option<&MetricsData>) -> Value {
+    let Some(m) = metrics else {
+        return json!({
+            "uptime": uptime,
+            "heap_free": heap_free,
+            "error": "metrics_locked"
+        });
+    };
+    json!({
+        "uptime": uptime,
+        "heap_free": heap_free,
+        "temperature": (m.temperature * 10.0).round() / 10.0,
+        "temperature_unit": "celsius",
+        "fps_actual": (m.fps_actual * 10.0).round() / 10.0,
+        "fps_target": m.fps_target,
+        "render_time_ms": m.render_time_ms,
+        "flush_time_ms": m.flush_time_ms,
+        "cpu_usage": m.cpu_usage,
+        "cpu0_usage": m.cpu0_usage,
+        "cpu1_usage": m.cpu1_usage,
+        "cpu_freq_mhz": m.cpu_freq_mhz,
+        "battery_voltage": m.battery_voltage_mv,
+        "battery_percentage": m.battery_percentage,
+        "battery_charging": m.battery_charging,
+        "wifi_rssi": m.wifi_rssi,
+        "wifi_connected": m.wifi_connected,
+        "wifi_ssid": m.wifi_ssid,
+        "display_brightness": m.display_brightness,
+        "frame_count": m.frame_count,
+        "skip_count": m.skip_count,
+        "skip_rate": if m.frame_count > 0 {
+            m.skip_count as f32 / m.frame_count as f32 * 100.0
+        } else { 0.0 }
+    })
+}
+
+/// GET /api/summary body: one flat object for wall dashboards and ota.sh to
+/// poll, built from a metrics snapshot. `ip` is None while the station has no
+/// address; status is "ok" or "degraded" (no WiFi, low heap, display down).
+pub fn summary_json(m: &MetricsData, ip: Option<&str>, uptime: u64, display_ok: bool, version: &str) -> Value {
+    let healthy = m.wifi_connected && m.heap_free >= SUMMARY_LOW_HEAP && display_ok;
+    json!({
+        "status": if healthy { "ok" } else { "degraded" },
+        "ip": ip,
+        "rssi": m.wifi_connected.then_some(m.wifi_rssi),
+        "temp": (m.temperature * 10.0).round() / 10.0,
+        "battery": m.battery_percentage,
+        "charging": m.battery_charging,
+        "fps": (m.fps_actual * 10.0).round() / 10.0,
+        "heap": m.heap_free,
+        "version": version,
+        "uptime": uptime
+    })
+}
+
+/// `hours` query parameter of the history endpoints, defaulting to 24
+pub fn history_hours(uri: &str) -> u32 {
+    uri.split('?')
+        .nth(1)
+        .and_then(|query| query.split('&').find(|p| p.starts_with("hours=")))
+        .and_then(|p| p.strip_prefix("hours="))
+        .and_then(|h| h.parse::<u32>().ok())
+        .unwrap_or(DEFAULT_HISTORY_HOURS)
+}
+
+/// `key` query parameter of GET /api/v1/sensors/history: a history series
+/// such as `temperature` or `scd40.co2`
+pub fn history_key(uri: &str) -> Option<&str> {
+    uri.split('?')
+        .nth(1)
+        .and_then(|query| query.split('&').find_map(|p| p.strip_prefix("key=")))
+        .filter(|key| !key.is_empty())
+}
+
+/// GET /api/v1/sensors/{temperature,battery}/history body; borrows the
+/// points from the history buffer so it can be streamed without copying them
+#[derive(Debug, serde::Serialize)]
+pub struct HistoryBody<'a> {
+    pub hours: u32,
+    pub data: HistoryPoints<'a>,
+    pub unit: &'a str,
+}
+
+pub fn history_json<'a>(hours: u32, data: HistoryPoints<'a>, unit: &'a str) -> HistoryBody<'a> {
+    HistoryBody { hours, data, unit }
+}
+
+// Grafana "simple JSON" datasource contract, so the device can be added as a
+// datasource for the last few hours without running Prometheus. Grafana POSTs
+// JSON to `<url>/search` and `<url>/query`; GET `<url>` is its connection test.
+
+/// Largest /api/grafana/* request body accepted
+pub const MAX_GRAFANA_BODY: usize = 2048;
+
+/// POST /api/grafana/search: history series whose name contains `target`
+/// (all of them when it is missing or empty)
+pub fn grafana_search(body: &[u8], keys: Vec<String>) -> Vec<String> {
+    let filter = serde_json::from_slice::<Value>(body).ok()
+        .and_then(|v| v.get("target").and_then(Value::as_str).map(str::to_owned))
+        .unwrap_or_default();
+    keys.into_iter().filter(|key| key.contains(filter.as_str())).collect()
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GrafanaQuery {
+    range: GrafanaRange,
+    #[serde(default)]
+    targets: Vec<GrafanaTarget>,
+    max_data_points: Option<usize>,
+}
+
+#[derive(serde::Deserialize)]
+struct GrafanaRange {
+    from: String,
+    to: String,
+}
+
+#[derive(serde::Deserialize)]
+struct GrafanaTarget {
+    #[serde(default)]
+    target: String,
+}
+
+/// One series of the /api/grafana/query response; datapoints are
+/// `[value, unix milliseconds]` pairs, oldest first
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct GrafanaSeries {
+    pub target: String,
+    pub datapoints: Vec<(f32, u64)>,
+}
+
+/// POST /api/grafana/query. Unknown targets come back with no datapoints;
+/// series longer than `maxDataPoints` are thinned to every n-th point.
+pub fn grafana_query(body: &[u8], history: &SensorHistory) -> Result<Vec<GrafanaSeries>, ConfigError> {
+    let query: GrafanaQuery = serde_json::from_slice(body).map_err(|_| ConfigError::InvalidJson)?;
+    let from = parse_rfc3339(&query.range.from)
+        .ok_or_else(|| ConfigError::Invalid("range.from must be an RFC 3339 time".into()))?;
+    let to = parse_rfc3339(&query.range.to)
+        .ok_or_else(|| ConfigError::Invalid("range.to must be an RFC 3339 time".into()))?;
+    let max_points = query.max_data_points.unwrap_or(usize::MAX).max(1);
+
+    Ok(query.targets.into_iter()
+        .filter(|t| !t.target.is_empty())
+        .map(|t| {
+            let points = history.range(&t.target, from.max(0) as u64, to.max(0) as u64);
+            let stride = points.len().div_ceil(max_points).max(1);
+            let datapoints = points.iter()
+                .step_by(stride)
+                .map(|dp| (dp.value, dp.timestamp.saturating_mul(1000)))
+                .collect();
+            GrafanaSeries { target: t.target, datapoints }
+        })
+        .collect())
+}
+
+/// Unix seconds of an RFC 3339 time such as `2024-05-01T12:30:00.123Z` or
+/// `2024-05-01T14:30:00+02:00`; fractions are dropped
+pub fn parse_rfc3339(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let num = |range: core::ops::Range<usize>| -> Option<i64> {
+        let digits = s.get(range)?;
+        digits.bytes().all(|b| b.is_ascii_digit()).then(|| digits.parse().ok())?
+    };
+    if !s.is_ascii() || s.len() < 20 || &s[4..5] != "-" || &s[7..8] != "-" || !matches!(&s[10..11], "T" | "t" | " ")
+        || &s[13..14] != ":" || &s[16..17] != ":" {
+        return None;
+    }
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    let (hour, minute, second) = (num(11..13)?, num(14..16)?, num(17..19)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let mut rest = &s[19..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        rest = &fraction[digits..];
+    }
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ if rest.len() == 6 && matches!(&rest[..1], "+" | "-") && &rest[3..4] == ":"
+            && rest[1..3].bytes().chain(rest[4..6].bytes()).all(|b| b.is_ascii_digit()) => {
+            let offset = rest[1..3].parse::<i64>().ok()? * 3600 + rest[4..6].parse::<i64>().ok()? * 60;
+            if &rest[..1] == "-" { -offset } else { offset }
+        }
+        _ => return None,
+    };
+
+    let days = crate::network::ics::days_from_civil(year as i32, month as u8, day as u8);
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second - offset)
+}
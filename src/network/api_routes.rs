@@ -5,7 +5,10 @@ use std::sync::{Arc, Mutex};
 use crate::config::Config;
 use crate::sensors::history::SensorHistory;
 use crate::network::validators;
+use crate::network::api_core;
+use crate::network::json_stream;
 use crate::network::error_handler::ErrorResponse;
+use crate::network::heap_tracker::TrackedHandlers;
 
 pub fn register_api_v1_routes(
     server: &mut EspHttpServer<'static>,
@@ -15,15 +18,9 @@ pub fn register_api_v1_routes(
     
     // GET /api/v1/sensors/temperature/history?hours=24
     let history_clone = sensor_history.clone();
-    server.fn_handler("/api/v1/sensors/temperature/history", Method::Get, move |req| {
+    server.tracked_handler("/api/v1/sensors/temperature/history", Method::Get, move |req| {
         let instr = crate::network::server_config::RequestInstrumentation::capture(None);
-        let hours = req.uri()
-            .split('?')
-            .nth(1)
-            .and_then(|query| query.split('&').find(|p| p.starts_with("hours=")))
-            .and_then(|p| p.strip_prefix("hours="))
-            .and_then(|h| h.parse::<u32>().ok())
-            .unwrap_or(24);
+        let hours = api_core::history_hours(req.uri());
 
         let history = match history_clone.lock() {
             Ok(h) => h,
@@ -31,36 +28,23 @@ pub fn register_api_v1_routes(
                 return ErrorResponse::bad_request(format!("history lock failed: {}", e)).send(req);
             }
         };
-        let data = history.get_temperature_history(hours);
-        
-        let response = serde_json::json!({
-            "hours": hours,
-            "data": data,
-            "unit": "celsius"
-        });
-
-        let json = serde_json::to_string(&response)?;
         let mut http_response = req.into_response(
             200,
             Some("OK"),
             &[("Content-Type", "application/json")]
         )?;
-        http_response.write_all(json.as_bytes())?;
+        history.with_history("temperature", hours, |data| {
+            json_stream::write_response(&mut http_response, &api_core::history_json(hours, data, "celsius"))
+        })?;
         instr.log_completion("/api/v1/sensors/temperature/history", 200);
         Ok(()) as Result<(), Box<dyn std::error::Error>>
     })?;
 
     // GET /api/v1/sensors/battery/history?hours=24
     let history_clone2 = sensor_history.clone();
-    server.fn_handler("/api/v1/sensors/battery/history", Method::Get, move |req| {
+    server.tracked_handler("/api/v1/sensors/battery/history", Method::Get, move |req| {
         let instr = crate::network::server_config::RequestInstrumentation::capture(None);
-        let hours = req.uri()
-            .split('?')
-            .nth(1)
-            .and_then(|query| query.split('&').find(|p| p.starts_with("hours=")))
-            .and_then(|p| p.strip_prefix("hours="))
-            .and_then(|h| h.parse::<u32>().ok())
-            .unwrap_or(24);
+        let hours = api_core::history_hours(req.uri());
 
         let history = match history_clone2.lock() {
             Ok(h) => h,
@@ -68,27 +52,115 @@ pub fn register_api_v1_routes(
                 return ErrorResponse::bad_request(format!("history lock failed: {}", e)).send(req);
             }
         };
-        let data = history.get_battery_history(hours);
-        
-        let response = serde_json::json!({
-            "hours": hours,
-            "data": data,
-            "unit": "percentage"
-        });
-
-        let json = serde_json::to_string(&response)?;
         let mut http_response = req.into_response(
             200,
             Some("OK"),
             &[("Content-Type", "application/json")]
         )?;
-        http_response.write_all(json.as_bytes())?;
+        history.with_history("battery", hours, |data| {
+            json_stream::write_response(&mut http_response, &api_core::history_json(hours, data, "percentage"))
+        })?;
         instr.log_completion("/api/v1/sensors/battery/history", 200);
         Ok(()) as Result<(), Box<dyn std::error::Error>>
     })?;
 
+    // GET /api/v1/sensors/history?key=scd40.co2&hours=24 (any series, including
+    // registered sensors)
+    let history_clone3 = sensor_history.clone();
+    server.tracked_handler("/api/v1/sensors/history", Method::Get, move |req| {
+        let instr = crate::network::server_config::RequestInstrumentation::capture(None);
+        let hours = api_core::history_hours(req.uri());
+        let Some(key) = api_core::history_key(req.uri()).map(str::to_string) else {
+            return ErrorResponse::bad_request("key is required").send(req);
+        };
+
+        let history = match history_clone3.lock() {
+            Ok(h) => h,
+            Err(e) => {
+                return ErrorResponse::bad_request(format!("history lock failed: {}", e)).send(req);
+            }
+        };
+        if !history.keys().contains(&key) {
+            return ErrorResponse::not_found(format!("no history for {}", key)).send(req);
+        }
+        let unit = crate::sensors::registry::snapshot().into_iter()
+            .find(|sample| sample.key == key)
+            .map(|sample| sample.reading.unit)
+            .unwrap_or("");
+
+        let mut http_response = req.into_response(
+            200,
+            Some("OK"),
+            &[("Content-Type", "application/json")]
+        )?;
+        history.with_history(&key, hours, |data| {
+            json_stream::write_response(&mut http_response, &api_core::history_json(hours, data, unit))
+        })?;
+        instr.log_completion("/api/v1/sensors/history", 200);
+        Ok(()) as Result<(), Box<dyn std::error::Error>>
+    })?;
+
+    // Grafana simple JSON datasource over the same history. Read-only, so no
+    // CSRF check: Grafana's proxy sends its own Origin.
+    // GET /api/grafana/ is Grafana's "Save & test" connection check
+    server.tracked_handler("/api/grafana/", Method::Get, move |req| {
+        req.into_ok_response()?.write_all(b"OK")?;
+        Ok(()) as Result<(), Box<dyn std::error::Error>>
+    })?;
+
+    let history_clone4 = sensor_history.clone();
+    server.tracked_handler("/api/grafana/search", Method::Post, move |mut req| {
+        let mut buf = vec![0; api_core::MAX_GRAFANA_BODY];
+        let len = req.read(&mut buf)?;
+        let keys = match history_clone4.lock() {
+            Ok(h) => h.keys(),
+            Err(e) => {
+                return ErrorResponse::bad_request(format!("history lock failed: {}", e)).send(req);
+            }
+        };
+        let names = api_core::grafana_search(&buf[..len], keys);
+
+        let mut http_response = req.into_response(200, Some("OK"), &[("Content-Type", "application/json")])?;
+        json_stream::write_response(&mut http_response, &names)?;
+        Ok(()) as Result<(), Box<dyn std::error::Error>>
+    })?;
+
+    let history_clone5 = sensor_history.clone();
+    server.tracked_handler("/api/grafana/query", Method::Post, move |mut req| {
+        let instr = crate::network::server_config::RequestInstrumentation::capture(None);
+        let mut buf = vec![0; api_core::MAX_GRAFANA_BODY];
+        let len = req.read(&mut buf)?;
+        let series = match history_clone5.lock() {
+            Ok(h) => api_core::grafana_query(&buf[..len], &h),
+            Err(e) => {
+                return ErrorResponse::bad_request(format!("history lock failed: {}", e)).send(req);
+            }
+        };
+        let series = match series {
+            Ok(series) => series,
+            Err(e) => return ErrorResponse::from_error(&e).send(req),
+        };
+
+        let mut http_response = req.into_response(200, Some("OK"), &[("Content-Type", "application/json")])?;
+        json_stream::write_response(&mut http_response, &series)?;
+        instr.log_completion("/api/grafana/query", 200);
+        Ok(()) as Result<(), Box<dyn std::error::Error>>
+    })?;
+
+    // GET /api/v1/sensors: latest reading of every registered sensor
+    server.tracked_handler("/api/v1/sensors", Method::Get, move |req| {
+        let readings = crate::sensors::registry::snapshot();
+        let mut http_response = req.into_response(
+            200,
+            Some("OK"),
+            &[("Content-Type", "application/json")]
+        )?;
+        json_stream::write_response(&mut http_response, &readings)?;
+        Ok(()) as Result<(), Box<dyn std::error::Error>>
+    })?;
+
     // GET /api/v1/system/processes
-    server.fn_handler("/api/v1/system/processes", Method::Get, move |req| {
+    server.tracked_handler("/api/v1/system/processes", Method::Get, move |req| {
         let instr = crate::network::server_config::RequestInstrumentation::capture(None);
         let mut processes = Vec::new();
         
@@ -128,7 +200,10 @@ pub fn register_api_v1_routes(
     })?;
 
     // POST /api/v1/display/screenshot
-    server.fn_handler("/api/v1/display/screenshot", Method::Post, move |req| {
+    server.tracked_handler("/api/v1/display/screenshot", Method::Post, move |req| {
+        if let Err(reason) = crate::network::csrf::verify(&req) {
+            return ErrorResponse::forbidden(reason).send(req);
+        }
         let instr = crate::network::server_config::RequestInstrumentation::capture(None);
         // For now, return a placeholder response
         // TODO: Implement actual screenshot capture once display module supports it
@@ -158,7 +233,10 @@ pub fn register_api_v1_routes(
 
     // PATCH /api/v1/config/:field
     let config_clone = config.clone();
-    server.fn_handler("/api/v1/config/*", Method::Patch, move |mut req| {
+    server.tracked_handler("/api/v1/config/*", Method::Patch, move |mut req| {
+        if let Err(reason) = crate::network::csrf::verify(&req) {
+            return ErrorResponse::forbidden(reason).send(req);
+        }
         let instr = crate::network::server_config::RequestInstrumentation::capture(None);
         // Extract field name from URL (before any mutable borrows)
         let uri = req.uri().to_string();
@@ -240,7 +318,10 @@ pub fn register_api_v1_routes(
     })?;
 
     // POST /api/v1/debug/log-level {"level":"trace|debug|info|warn|error|off"} (also supports ?level=)
-    server.fn_handler("/api/v1/debug/log-level", Method::Post, move |mut req| {
+    server.tracked_handler("/api/v1/debug/log-level", Method::Post, move |mut req| {
+        if let Err(reason) = crate::network::csrf::verify(&req) {
+            return ErrorResponse::forbidden(reason).send(req);
+        }
         let instr = crate::network::server_config::RequestInstrumentation::capture(None);
         // Read body
         let mut buf = [0u8; 64];
@@ -275,8 +356,8 @@ pub fn register_api_v1_routes(
         }
     })?;
 
-    // GET /api/v1/logs/recent?count=50
-    server.fn_handler("/api/v1/logs/recent", Method::Get, move |req| {
+    // GET /api/v1/logs/recent?count=50&level=warn&module=ota
+    server.tracked_handler("/api/v1/logs/recent", Method::Get, move |req| {
         let instr = crate::network::server_config::RequestInstrumentation::capture(None);
         let count = req.uri()
             .split('?')
@@ -286,9 +367,13 @@ pub fn register_api_v1_routes(
             .and_then(|h| h.parse::<usize>().ok())
             .map(|n| n.min(500))
             .unwrap_or(50);
+        let filter = match crate::network::log_filter::LogFilter::from_query(req.uri().split('?').nth(1).unwrap_or("")) {
+            Ok(filter) => filter,
+            Err(e) => return ErrorResponse::bad_request(e).send(req),
+        };
 
         let streamer = crate::network::log_streamer::init(None);
-        let logs = streamer.get_recent_logs(count);
+        let logs = streamer.get_recent_filtered(count, &filter);
         let json = serde_json::to_string(&logs)?;
         let mut http_response = req.into_response(200, Some("OK"), &[("Content-Type", "application/json")])?;
         http_response.write_all(json.as_bytes())?;
@@ -297,7 +382,7 @@ pub fn register_api_v1_routes(
     })?;
 
     // GET /api/v1/diagnostics/health
-    server.fn_handler("/api/v1/diagnostics/health", Method::Get, move |req| {
+    server.tracked_handler("/api/v1/diagnostics/health", Method::Get, move |req| {
         let instr = crate::network::server_config::RequestInstrumentation::capture(None);
         let heap_free = unsafe { esp_idf_sys::esp_get_free_heap_size() };
         let heap_min = unsafe { esp_idf_sys::esp_get_minimum_free_heap_size() };
@@ -337,6 +422,7 @@ pub fn register_api_v1_routes(
                 "network": {
                     "connected": wifi_connected,
                     "ip": ip,
+                    "fallback_ap": crate::network::softap::is_active(),
                 }
             },
             "timestamp": std::time::SystemTime::now()
@@ -357,7 +443,7 @@ pub fn register_api_v1_routes(
     })?;
 
     // GET /api/v1/diagnostics/last-crash
-    server.fn_handler("/api/v1/diagnostics/last-crash", Method::Get, move |req| {
+    server.tracked_handler("/api/v1/diagnostics/last-crash", Method::Get, move |req| {
         let instr = crate::network::server_config::RequestInstrumentation::capture(None);
         match crate::crash_persist::read_last_crash() {
             Ok(Some(record)) => {
@@ -378,7 +464,10 @@ pub fn register_api_v1_routes(
     })?;
 
     // DELETE /api/v1/diagnostics/last-crash
-    server.fn_handler("/api/v1/diagnostics/last-crash", Method::Delete, move |req| {
+    server.tracked_handler("/api/v1/diagnostics/last-crash", Method::Delete, move |req| {
+        if let Err(reason) = crate::network::csrf::verify(&req) {
+            return ErrorResponse::forbidden(reason).send(req);
+        }
         let instr = crate::network::server_config::RequestInstrumentation::capture(None);
         match crate::crash_persist::clear_last_crash() {
             Ok(()) => {
@@ -391,10 +480,38 @@ pub fn register_api_v1_routes(
         }
     })?;
 
+    // GET /api/brightness/curve — the learned ambient light to backlight curve
+    let config_curve = config.clone();
+    server.tracked_handler("/api/brightness/curve", Method::Get, move |req| {
+        let instr = crate::network::server_config::RequestInstrumentation::capture(None);
+        let (enabled, max_level) = match config_curve.lock() {
+            Ok(cfg) => (cfg.adaptive_brightness, cfg.brightness),
+            Err(e) => return ErrorResponse::bad_request(format!("config lock failed: {}", e)).send(req),
+        };
+        let report = crate::power::adaptive_brightness::report(enabled, max_level);
+        let mut http_response = req.into_response(200, Some("OK"), &[("Content-Type", "application/json")])?;
+        json_stream::write_response(&mut http_response, &report)?;
+        instr.log_completion("/api/brightness/curve", 200);
+        Ok(()) as Result<(), Box<dyn std::error::Error>>
+    })?;
+
+    // DELETE /api/brightness/curve — forget the light history and relearn
+    server.tracked_handler("/api/brightness/curve", Method::Delete, move |req| {
+        if let Err(reason) = crate::network::csrf::verify(&req) {
+            return ErrorResponse::forbidden(reason).send(req);
+        }
+        let instr = crate::network::server_config::RequestInstrumentation::capture(None);
+        crate::power::adaptive_brightness::reset();
+        let mut http_response = req.into_response(200, Some("OK"), &[("Content-Type", "application/json")])?;
+        http_response.write_all(b"{\"status\":\"reset\"}")?;
+        instr.log_completion("/api/brightness/curve", 200);
+        Ok(()) as Result<(), Box<dyn std::error::Error>>
+    })?;
+
     // NOTE: /api/v1/power/voltage removed (voltage monitor disabled)
 
     // GET /api/v1/status/errors — analyze recent logs for httpd/network error patterns
-    server.fn_handler("/api/v1/status/errors", Method::Get, move |req| {
+    server.tracked_handler("/api/v1/status/errors", Method::Get, move |req| {
         let instr = crate::network::server_config::RequestInstrumentation::capture(None);
         let logs = crate::network::log_streamer::init(None).get_recent_logs(500);
         let mut send_err_11 = 0u32;
@@ -1,3 +1,22 @@
+// Binary metrics for GET /api/metrics/binary.
+//
+// v2 (the default) is a framed, self-describing packet:
+//
+//   offset  size  field
+//   0       4     magic "ESPM"
+//   4       1     version (2)
+//   5       1     flags, reserved (0)
+//   6       2     payload length, little-endian
+//   8       n     payload: TLV fields, each tag u8, length u8, value
+//   8+n     4     CRC32 (IEEE, as in gzip) of bytes 0..8+n, little-endian
+//
+// Values are little-endian integers of 1, 2, 4 or 8 bytes, sign-extended for
+// signed fields, so a field can be widened without breaking clients. Decoders
+// skip tags they don't know, and tags are never reused. The fixed v1 layout
+// (`MetricsBinaryPacket`) is still served with `?v=1` and still decodes.
+
+use thiserror::Error;
+
 
 /// Binary metrics packet format for efficient transmission
 #[repr(C, packed)]
@@ -31,6 +50,7 @@ impl MetricsBinaryPacket {
     pub const SIZE: usize = std::mem::size_of::<Self>();
     
     /// Convert from MetricsData
+    #[cfg(target_os = "espidf")]
     pub fn from_metrics(metrics: &crate::metrics::MetricsData) -> Self {
         Self {
             version: Self::VERSION,
@@ -63,9 +83,287 @@ impl MetricsBinaryPacket {
         let slice = unsafe { std::slice::from_raw_parts(ptr, Self::SIZE) };
         slice.to_vec()
     }
-    
+
+    /// Parse a packet as produced by `to_bytes` (little-endian, as on the
+    /// ESP32). None unless the length and version match exactly.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::SIZE || bytes[0] != Self::VERSION {
+            return None;
+        }
+        let mut reader = Reader { bytes, pos: 1 };
+        Some(Self {
+            version: Self::VERSION,
+            timestamp: u64::from_le_bytes(reader.take()),
+            temperature: i16::from_le_bytes(reader.take()),
+            battery_percentage: reader.byte(),
+            battery_voltage_mv: u16::from_le_bytes(reader.take()),
+            battery_charging: reader.byte(),
+            fps_actual: u16::from_le_bytes(reader.take()),
+            fps_target: reader.byte(),
+            cpu_usage: reader.byte(),
+            cpu0_usage: reader.byte(),
+            cpu1_usage: reader.byte(),
+            cpu_freq_mhz: u16::from_le_bytes(reader.take()),
+            heap_free: u32::from_le_bytes(reader.take()),
+            heap_total: u32::from_le_bytes(reader.take()),
+            wifi_rssi: reader.byte() as i8,
+            wifi_connected: reader.byte(),
+            display_brightness: reader.byte(),
+            frame_count: u32::from_le_bytes(reader.take()),
+            skip_count: u32::from_le_bytes(reader.take()),
+            render_time_ms: u16::from_le_bytes(reader.take()),
+            flush_time_ms: u16::from_le_bytes(reader.take()),
+        })
+    }
+}
+
+// Cursor over a buffer whose length was checked up front
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let mut out = [0; N];
+        out.copy_from_slice(&self.bytes[self.pos..self.pos + N]);
+        self.pos += N;
+        out
+    }
+
+    fn byte(&mut self) -> u8 {
+        self.take::<1>()[0]
+    }
+}
+
+
+/// First bytes of a v2 frame
+pub const MAGIC: [u8; 4] = *b"ESPM";
+pub const VERSION_2: u8 = 2;
+const HEADER_LEN: usize = 8;
+const CRC_LEN: usize = 4;
+
+/// Field tags of the v2 payload. Append new fields; never renumber.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Field {
+    Timestamp = 1,
+    /// °C * 10
+    Temperature = 2,
+    BatteryPercentage = 3,
+    BatteryVoltageMv = 4,
+    /// 0 or 1
+    BatteryCharging = 5,
+    /// FPS * 10
+    FpsActual = 6,
+    FpsTarget = 7,
+    CpuUsage = 8,
+    Cpu0Usage = 9,
+    Cpu1Usage = 10,
+    CpuFreqMhz = 11,
+    HeapFree = 12,
+    HeapTotal = 13,
+    WifiRssi = 14,
+    /// 0 or 1
+    WifiConnected = 15,
+    DisplayBrightness = 16,
+    FrameCount = 17,
+    SkipCount = 18,
+    RenderTimeMs = 19,
+    FlushTimeMs = 20,
+}
+
+impl Field {
+    pub const ALL: [Field; 20] = [
+        Field::Timestamp, Field::Temperature, Field::BatteryPercentage, Field::BatteryVoltageMv,
+        Field::BatteryCharging, Field::FpsActual, Field::FpsTarget, Field::CpuUsage,
+        Field::Cpu0Usage, Field::Cpu1Usage, Field::CpuFreqMhz, Field::HeapFree,
+        Field::HeapTotal, Field::WifiRssi, Field::WifiConnected, Field::DisplayBrightness,
+        Field::FrameCount, Field::SkipCount, Field::RenderTimeMs, Field::FlushTimeMs,
+    ];
+
+    pub fn tag(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.tag() == tag)
+    }
+
+    /// Same name as the field in /api/metrics
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Timestamp => "timestamp",
+            Self::Temperature => "temperature",
+            Self::BatteryPercentage => "battery_percentage",
+            Self::BatteryVoltageMv => "battery_voltage_mv",
+            Self::BatteryCharging => "battery_charging",
+            Self::FpsActual => "fps_actual",
+            Self::FpsTarget => "fps_target",
+            Self::CpuUsage => "cpu_usage",
+            Self::Cpu0Usage => "cpu0_usage",
+            Self::Cpu1Usage => "cpu1_usage",
+            Self::CpuFreqMhz => "cpu_freq_mhz",
+            Self::HeapFree => "heap_free",
+            Self::HeapTotal => "heap_total",
+            Self::WifiRssi => "wifi_rssi",
+            Self::WifiConnected => "wifi_connected",
+            Self::DisplayBrightness => "display_brightness",
+            Self::FrameCount => "frame_count",
+            Self::SkipCount => "skip_count",
+            Self::RenderTimeMs => "render_time_ms",
+            Self::FlushTimeMs => "flush_time_ms",
+        }
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(self, Self::Temperature | Self::WifiRssi)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DecodeError {
+    #[error("packet too short")]
+    Truncated,
+    #[error("not a metrics packet")]
+    BadMagic,
+    #[error("unsupported protocol version {0}")]
+    UnsupportedVersion(u8),
+    #[error("length field does not match the packet")]
+    LengthMismatch,
+    #[error("checksum mismatch")]
+    BadChecksum,
+    #[error("field {tag} has invalid width {len}")]
+    BadField { tag: u8, len: u8 },
+}
+
+/// A decoded packet of either version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricsFrame {
+    pub version: u8,
+    /// Known fields in packet order; unknown tags are dropped
+    pub fields: Vec<(Field, i64)>,
+}
+
+impl MetricsFrame {
+    /// The field's value; the last one wins if a packet repeats a tag
+    pub fn get(&self, field: Field) -> Option<i64> {
+        self.fields.iter().rev().find(|(f, _)| *f == field).map(|&(_, value)| value)
+    }
+}
+
+impl MetricsBinaryPacket {
+    /// Field values in tag order
+    pub fn fields(&self) -> [(Field, i64); 20] {
+        [
+            (Field::Timestamp, self.timestamp as i64),
+            (Field::Temperature, self.temperature as i64),
+            (Field::BatteryPercentage, self.battery_percentage as i64),
+            (Field::BatteryVoltageMv, self.battery_voltage_mv as i64),
+            (Field::BatteryCharging, self.battery_charging as i64),
+            (Field::FpsActual, self.fps_actual as i64),
+            (Field::FpsTarget, self.fps_target as i64),
+            (Field::CpuUsage, self.cpu_usage as i64),
+            (Field::Cpu0Usage, self.cpu0_usage as i64),
+            (Field::Cpu1Usage, self.cpu1_usage as i64),
+            (Field::CpuFreqMhz, self.cpu_freq_mhz as i64),
+            (Field::HeapFree, self.heap_free as i64),
+            (Field::HeapTotal, self.heap_total as i64),
+            (Field::WifiRssi, self.wifi_rssi as i64),
+            (Field::WifiConnected, self.wifi_connected as i64),
+            (Field::DisplayBrightness, self.display_brightness as i64),
+            (Field::FrameCount, self.frame_count as i64),
+            (Field::SkipCount, self.skip_count as i64),
+            (Field::RenderTimeMs, self.render_time_ms as i64),
+            (Field::FlushTimeMs, self.flush_time_ms as i64),
+        ]
+    }
+
+    /// The v2 frame carrying the same values, each at its v1 width
+    pub fn to_v2_bytes(&self) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(Self::SIZE + 2 * Field::ALL.len());
+        for (field, value) in self.fields() {
+            let width = match field {
+                Field::Timestamp => 8,
+                Field::HeapFree | Field::HeapTotal | Field::FrameCount | Field::SkipCount => 4,
+                Field::Temperature | Field::BatteryVoltageMv | Field::FpsActual | Field::CpuFreqMhz
+                | Field::RenderTimeMs | Field::FlushTimeMs => 2,
+                _ => 1,
+            };
+            payload.extend_from_slice(&[field.tag(), width as u8]);
+            payload.extend_from_slice(&value.to_le_bytes()[..width]);
+        }
+        encode_frame(&payload)
+    }
 }
 
+/// Wrap a TLV payload in the v2 header and checksum
+pub fn encode_frame(payload: &[u8]) -> Vec<u8> {
+    let len = u16::try_from(payload.len()).expect("metrics payload fits a u16 length");
+    let mut frame = Vec::with_capacity(HEADER_LEN + payload.len() + CRC_LEN);
+    frame.extend_from_slice(&MAGIC);
+    frame.extend_from_slice(&[VERSION_2, 0]);
+    frame.extend_from_slice(&len.to_le_bytes());
+    frame.extend_from_slice(payload);
+    let crc = crc32(&frame);
+    frame.extend_from_slice(&crc.to_le_bytes());
+    frame
+}
+
+/// Decode a v2 frame, or a legacy v1 packet
+pub fn decode(bytes: &[u8]) -> Result<MetricsFrame, DecodeError> {
+    if bytes.first() == Some(&MetricsBinaryPacket::VERSION) && bytes.len() == MetricsBinaryPacket::SIZE {
+        let packet = MetricsBinaryPacket::from_bytes(bytes).ok_or(DecodeError::Truncated)?;
+        return Ok(MetricsFrame { version: MetricsBinaryPacket::VERSION, fields: packet.fields().to_vec() });
+    }
+    if bytes.len() < HEADER_LEN + CRC_LEN {
+        return Err(DecodeError::Truncated);
+    }
+    if bytes[..4] != MAGIC {
+        return Err(DecodeError::BadMagic);
+    }
+    if bytes[4] != VERSION_2 {
+        return Err(DecodeError::UnsupportedVersion(bytes[4]));
+    }
+    let len = u16::from_le_bytes([bytes[6], bytes[7]]) as usize;
+    if bytes.len() != HEADER_LEN + len + CRC_LEN {
+        return Err(DecodeError::LengthMismatch);
+    }
+    let (framed, crc) = bytes.split_at(HEADER_LEN + len);
+    if crc32(framed) != u32::from_le_bytes([crc[0], crc[1], crc[2], crc[3]]) {
+        return Err(DecodeError::BadChecksum);
+    }
+
+    let mut fields = Vec::with_capacity(Field::ALL.len());
+    let mut rest = &framed[HEADER_LEN..];
+    while let [tag, len, tail @ ..] = rest {
+        let width = *len as usize;
+        if tail.len() < width {
+            return Err(DecodeError::Truncated);
+        }
+        let (value, next) = tail.split_at(width);
+        rest = next;
+        let Some(field) = Field::from_tag(*tag) else { continue };
+        if !matches!(width, 1 | 2 | 4 | 8) {
+            return Err(DecodeError::BadField { tag: *tag, len: *len });
+        }
+        let mut raw = [0u8; 8];
+        raw[..width].copy_from_slice(value);
+        if field.is_signed() && value[width - 1] & 0x80 != 0 {
+            raw[width..].fill(0xFF);
+        }
+        fields.push((field, i64::from_le_bytes(raw)));
+    }
+    if !rest.is_empty() {
+        return Err(DecodeError::Truncated);
+    }
+    Ok(MetricsFrame { version: VERSION_2, fields })
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = flate2::Crc::new();
+    crc.update(bytes);
+    crc.sum()
+}
 
 #[cfg(test)]
 mod tests {
@@ -73,7 +371,106 @@ mod tests {
 
     #[test]
     fn test_metrics_packet_size() {
-        assert_eq!(MetricsBinaryPacket::SIZE, 63);
+        assert_eq!(MetricsBinaryPacket::SIZE, 46);
+    }
+
+    fn sample() -> MetricsBinaryPacket {
+        MetricsBinaryPacket {
+            version: MetricsBinaryPacket::VERSION,
+            timestamp: 1_700_000_000,
+            temperature: -125,
+            battery_percentage: 87,
+            battery_voltage_mv: 4012,
+            battery_charging: 1,
+            fps_actual: 298,
+            fps_target: 30,
+            cpu_usage: 41,
+            cpu0_usage: 50,
+            cpu1_usage: 32,
+            cpu_freq_mhz: 240,
+            heap_free: 151_000,
+            heap_total: 98_000,
+            wifi_rssi: -67,
+            wifi_connected: 1,
+            display_brightness: 200,
+            frame_count: 123_456,
+            skip_count: 78,
+            render_time_ms: 12,
+            flush_time_ms: 9,
+        }
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_decode_round_trip() {
+        let bytes = sample().to_bytes();
+        let decoded = MetricsBinaryPacket::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!({ decoded.temperature }, -125);
+        assert_eq!({ decoded.wifi_rssi }, -67);
+
+        assert!(MetricsBinaryPacket::from_bytes(&bytes[..45]).is_none());
+        let mut wrong_version = bytes.clone();
+        wrong_version[0] = 2;
+        assert!(MetricsBinaryPacket::from_bytes(&wrong_version).is_none());
+    }
+
+    #[test]
+    fn test_v2_frame() {
+        let packet = sample();
+        let frame = packet.to_v2_bytes();
+        assert_eq!(&frame[..6], b"ESPM\x02\x00");
+        assert_eq!(u16::from_le_bytes([frame[6], frame[7]]) as usize, frame.len() - 12);
+
+        let decoded = decode(&frame).unwrap();
+        assert_eq!(decoded.version, VERSION_2);
+        assert_eq!(decoded.fields, packet.fields().to_vec());
+        assert_eq!(decoded.get(Field::Temperature), Some(-125));
+        assert_eq!(decoded.get(Field::WifiRssi), Some(-67));
+        assert_eq!(decoded.get(Field::Timestamp), Some(1_700_000_000));
+
+        // The legacy layout decodes to the same values
+        assert_eq!(decode(&packet.to_bytes()).unwrap().fields, decoded.fields);
+    }
+
+    #[test]
+    fn test_v2_rejects_damage() {
+        let frame = sample().to_v2_bytes();
+        let mut flipped = frame.clone();
+        flipped[20] ^= 0x01;
+        assert_eq!(decode(&flipped), Err(DecodeError::BadChecksum));
+        assert_eq!(decode(&frame[..frame.len() - 1]), Err(DecodeError::LengthMismatch));
+        assert_eq!(decode(&frame[..6]), Err(DecodeError::Truncated));
+        assert_eq!(decode(b"HTTP/1.1 200 OK\r\n"), Err(DecodeError::BadMagic));
+
+        let mut future = frame.clone();
+        future[4] = 3;
+        assert_eq!(decode(&future), Err(DecodeError::UnsupportedVersion(3)));
+    }
+
+    #[test]
+    fn test_v2_unknown_and_widened_fields() {
+        // A later firmware: an unknown tag 200, frame_count widened to 8 bytes,
+        // and a one-byte negative RSSI
+        let mut payload = vec![200, 3, 1, 2, 3];
+        payload.extend_from_slice(&[Field::FrameCount.tag(), 8]);
+        payload.extend_from_slice(&(5_000_000_000u64).to_le_bytes());
+        payload.extend_from_slice(&[Field::WifiRssi.tag(), 1, (-80i8) as u8]);
+        let decoded = decode(&encode_frame(&payload)).unwrap();
+        assert_eq!(decoded.fields, vec![(Field::FrameCount, 5_000_000_000), (Field::WifiRssi, -80)]);
+
+        let odd_width = encode_frame(&[Field::HeapFree.tag(), 3, 0, 0, 1]);
+        assert_eq!(decode(&odd_width), Err(DecodeError::BadField { tag: Field::HeapFree.tag(), len: 3 }));
+        let cut_short = encode_frame(&[Field::HeapFree.tag(), 4, 0, 0]);
+        assert_eq!(decode(&cut_short), Err(DecodeError::Truncated));
+    }
+
+    #[test]
+    fn test_field_tags_are_stable() {
+        for (i, field) in Field::ALL.into_iter().enumerate() {
+            assert_eq!(field.tag() as usize, i + 1);
+            assert_eq!(Field::from_tag(field.tag()), Some(field));
+        }
+        // CRC32/IEEE check value, so other decoders can verify theirs
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+}
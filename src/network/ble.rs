@@ -0,0 +1,132 @@
+// BLE GATT server (feature "ble") for status checks and WiFi provisioning from a phone.
+//
+// Services:
+// - Environmental Sensing (0x181A): Temperature (0x2A6E, sint16 in 0.01 °C), read/notify
+// - Battery (0x180F): Battery Level (0x2A19, uint8 %), read/notify
+// - Device Information (0x180A): Firmware Revision (0x2A26, UTF-8), read
+// - Provisioning (custom 128-bit): WiFi credentials, encrypted write only.
+//   Payload is JSON: {"ssid":"...","password":"..."}; the device saves and restarts.
+//
+// Requires NimBLE in sdkconfig: build with
+//   ESP_IDF_SDKCONFIG_DEFAULTS="sdkconfig.defaults;sdkconfig.defaults.ble" cargo build --features ble
+
+use anyhow::Result;
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use esp32_nimble::enums::{AuthReq, SecurityIOCap};
+use esp32_nimble::{uuid128, BLEAdvertisementData, BLEDevice, BLEUuid, NimbleProperties};
+use esp_idf_hal::delay::FreeRtos;
+use crate::config::Config;
+
+const DEVICE_NAME: &str = "ESP32-Dashboard";
+const UPDATE_INTERVAL_MS: u32 = 5000;
+
+const PROVISIONING_SERVICE: BLEUuid = uuid128!("6e400001-8a3a-4c5e-9f21-3d5e1c2b7a10");
+const WIFI_CREDENTIALS_CHAR: BLEUuid = uuid128!("6e400002-8a3a-4c5e-9f21-3d5e1c2b7a10");
+
+#[derive(serde::Deserialize)]
+struct WifiCredentials {
+    ssid: String,
+    password: String,
+}
+
+/// Encode temperature as GATT 0x2A6E (sint16, resolution 0.01 °C, little-endian)
+fn encode_temperature(celsius: f32) -> [u8; 2] {
+    let centi = (celsius * 100.0).round().clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+    centi.to_le_bytes()
+}
+
+fn apply_credentials(config: &Arc<Mutex<Config>>, data: &[u8]) -> Result<()> {
+    let creds: WifiCredentials = serde_json::from_slice(data)?;
+    crate::network::validators::validate_ssid(&creds.ssid)?;
+    if creds.password.len() > 64 {
+        anyhow::bail!("WiFi password must be 64 characters or less");
+    }
+    let mut cfg = config.lock().map_err(|_| anyhow::anyhow!("Configuration lock failed"))?;
+    cfg.wifi_ssid = creds.ssid;
+    cfg.wifi_password = creds.password;
+    cfg.save()?;
+    Ok(())
+}
+
+/// Start advertising and the GATT server. Values are refreshed from metrics
+/// every few seconds on a background thread. Safe to call multiple times.
+pub fn start(config: Arc<Mutex<Config>>) -> Result<()> {
+    static STARTED: AtomicBool = AtomicBool::new(false);
+    if STARTED.swap(true, Ordering::SeqCst) { return Ok(()); }
+
+    let device = BLEDevice::take();
+    BLEDevice::set_device_name(DEVICE_NAME)?;
+    // Credentials writes require an encrypted link (bonded "just works" pairing)
+    device
+        .security()
+        .set_auth(AuthReq::Bond | AuthReq::Sc)
+        .set_io_cap(SecurityIOCap::NoInputNoOutput);
+
+    let server = device.get_server();
+    server.on_connect(|_server, desc| {
+        log::info!("BLE: client connected ({:?})", desc.address());
+    });
+    server.on_disconnect(|_desc, reason| {
+        log::info!("BLE: client disconnected ({:?})", reason);
+    });
+
+    let env_service = server.create_service(BLEUuid::from_uuid16(0x181A));
+    let temperature = env_service.lock().create_characteristic(
+        BLEUuid::from_uuid16(0x2A6E),
+        NimbleProperties::READ | NimbleProperties::NOTIFY,
+    );
+
+    let battery_service = server.create_service(BLEUuid::from_uuid16(0x180F));
+    let battery = battery_service.lock().create_characteristic(
+        BLEUuid::from_uuid16(0x2A19),
+        NimbleProperties::READ | NimbleProperties::NOTIFY,
+    );
+
+    let info_service = server.create_service(BLEUuid::from_uuid16(0x180A));
+    info_service
+        .lock()
+        .create_characteristic(BLEUuid::from_uuid16(0x2A26), NimbleProperties::READ)
+        .lock()
+        .set_value(crate::version::full_version().as_bytes());
+
+    let provisioning = server.create_service(PROVISIONING_SERVICE);
+    let wifi_creds = provisioning.lock().create_characteristic(
+        WIFI_CREDENTIALS_CHAR,
+        NimbleProperties::WRITE | NimbleProperties::WRITE_ENC,
+    );
+    wifi_creds.lock().on_write(move |args| {
+        match apply_credentials(&config, args.recv_data()) {
+            Ok(()) => {
+                log::warn!("BLE: WiFi credentials updated, restarting to apply");
+                crate::system::shutdown::request_restart(crate::system::shutdown::RestartReason::Provisioned);
+            }
+            Err(e) => {
+                log::warn!("BLE: rejected WiFi credentials: {:?}", e);
+                args.reject();
+            }
+        }
+    });
+
+    device.get_advertising().lock().set_data(
+        BLEAdvertisementData::new()
+            .name(DEVICE_NAME)
+            .add_service_uuid(BLEUuid::from_uuid16(0x181A))
+            .add_service_uuid(BLEUuid::from_uuid16(0x180F)),
+    )?;
+    device.get_advertising().lock().start()?;
+    log::info!("BLE GATT server advertising as '{}'", DEVICE_NAME);
+
+    std::thread::Builder::new()
+        .name("ble-status".to_string())
+        .stack_size(4096)
+        .spawn(move || loop {
+            if let Ok(m) = crate::metrics::metrics().try_lock() {
+                temperature.lock().set_value(&encode_temperature(m.temperature)).notify();
+                battery.lock().set_value(&[m.battery_percentage.min(100)]).notify();
+            }
+            FreeRtos::delay_ms(UPDATE_INTERVAL_MS);
+        })?;
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+// gzip for web responses. JSON bodies such as /api/system, /api/config and
+// config backups compress 4-8x, which matters over a weak WiFi link.
+// Bodies under MIN_COMPRESS_BYTES aren't worth the CPU; bodies over
+// MAX_COMPRESS_BYTES are sent as they are. Whole bodies are compressed into
+// one scratch buffer allocated once in PSRAM, so compressing never takes
+// internal RAM; the output is capped at the buffer size and anything that
+// doesn't fit (or doesn't shrink) goes out uncompressed.
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::{self, Write};
+
+/// Smaller bodies are sent uncompressed
+pub const MIN_COMPRESS_BYTES: usize = 1024;
+
+/// Larger bodies are sent uncompressed; also the size of the PSRAM scratch buffer
+pub const MAX_COMPRESS_BYTES: usize = 64 * 1024;
+
+/// Whether an Accept-Encoding header allows gzip (a `q=0` weight refuses it)
+pub fn accepts_gzip(accept_encoding: Option<&str>) -> bool {
+    let Some(header) = accept_encoding else { return false };
+    header.split(',').any(|item| {
+        let mut parts = item.split(';').map(str::trim);
+        let coding = parts.next().unwrap_or_default();
+        if !coding.eq_ignore_ascii_case("gzip") && coding != "*" {
+            return false;
+        }
+        let refused = parts
+            .filter_map(|p| p.strip_prefix("q="))
+            .any(|q| q.parse::<f32>().is_ok_and(|q| q <= 0.0));
+        !refused
+    })
+}
+
+/// A body of `len` bytes should be gzipped for this client
+pub fn should_compress(len: usize, accept_encoding: Option<&str>) -> bool {
+    (MIN_COMPRESS_BYTES..=MAX_COMPRESS_BYTES).contains(&len) && accepts_gzip(accept_encoding)
+}
+
+/// `io::Write` into a fixed slice; fails once it is full
+struct SliceWriter<'a> {
+    out: &'a mut [u8],
+    len: usize,
+}
+
+impl Write for SliceWriter<'_> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let room = self.out.len() - self.len;
+        if room == 0 && !data.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::WriteZero, "compressed body over the size cap"));
+        }
+        let n = data.len().min(room);
+        self.out[self.len..self.len + n].copy_from_slice(&data[..n]);
+        self.len += n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// gzip `body` into `out`; the compressed length, or an error if it doesn't fit
+pub fn gzip_into(body: &[u8], out: &mut [u8]) -> io::Result<usize> {
+    let mut encoder = GzEncoder::new(SliceWriter { out, len: 0 }, Compression::fast());
+    encoder.write_all(body)?;
+    Ok(encoder.finish()?.len)
+}
+
+#[cfg(target_os = "espidf")]
+mod device {
+    use super::*;
+    use esp_idf_svc::http::server::{EspHttpConnection, Request};
+    use esp_idf_svc::io::Write as _;
+    use std::sync::Mutex;
+
+    /// MAX_COMPRESS_BYTES of PSRAM, allocated on first use and kept
+    struct Scratch(*mut u8);
+
+    // Only reached through the SCRATCH mutex
+    unsafe impl Send for Scratch {}
+
+    impl Scratch {
+        fn alloc() -> Option<Self> {
+            let ptr = unsafe {
+                esp_idf_sys::heap_caps_malloc(MAX_COMPRESS_BYTES, esp_idf_sys::MALLOC_CAP_SPIRAM)
+            } as *mut u8;
+            (!ptr.is_null()).then_some(Self(ptr))
+        }
+
+        fn as_mut_slice(&mut self) -> &mut [u8] {
+            unsafe { std::slice::from_raw_parts_mut(self.0, MAX_COMPRESS_BYTES) }
+        }
+    }
+
+    static SCRATCH: Mutex<Option<Scratch>> = Mutex::new(None);
+
+    /// Send `body` with status 200, gzipped when the client accepts it and
+    /// the size is in range; plain otherwise (no PSRAM, buffer busy, no gain)
+    pub fn write_compressed_response(
+        req: Request<&mut EspHttpConnection<'_>>,
+        content_type: &str,
+        extra_headers: &[(&str, &str)],
+        body: &[u8],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut headers = vec![("Content-Type", content_type), ("Vary", "Accept-Encoding")];
+        headers.extend_from_slice(extra_headers);
+
+        if should_compress(body.len(), req.header("Accept-Encoding")) {
+            if let Ok(mut scratch) = SCRATCH.try_lock() {
+                if scratch.is_none() {
+                    *scratch = Scratch::alloc();
+                }
+                if let Some(buf) = scratch.as_mut() {
+                    let out = buf.as_mut_slice();
+                    match gzip_into(body, out) {
+                        Ok(len) if len < body.len() => {
+                            headers.push(("Content-Encoding", "gzip"));
+                            let mut response = req.into_response(200, Some("OK"), &headers)?;
+                            response.write_all(&out[..len])?;
+                            return Ok(());
+                        }
+                        Ok(_) => {}
+                        Err(e) => log::debug!("gzip skipped: {}", e),
+                    }
+                }
+            }
+        }
+
+        let mut response = req.into_response(200, Some("OK"), &headers)?;
+        response.write_all(body)?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub use device::write_compressed_response;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_accept_encoding() {
+        assert!(accepts_gzip(Some("gzip, deflate, br")));
+        assert!(accepts_gzip(Some("br;q=1.0, GZIP;q=0.5")));
+        assert!(accepts_gzip(Some("*")));
+        assert!(!accepts_gzip(Some("gzip;q=0")));
+        assert!(!accepts_gzip(Some("deflate, br")));
+        assert!(!accepts_gzip(None));
+
+        assert!(!should_compress(MIN_COMPRESS_BYTES - 1, Some("gzip")));
+        assert!(should_compress(MIN_COMPRESS_BYTES, Some("gzip")));
+        assert!(!should_compress(MAX_COMPRESS_BYTES + 1, Some("gzip")));
+    }
+
+    #[test]
+    fn test_gzip_round_trip_and_cap() {
+        let body = serde_json::to_vec(&vec![serde_json::json!({"temperature": 24.5, "unit": "celsius"}); 200]).unwrap();
+        let mut out = vec![0u8; MAX_COMPRESS_BYTES];
+        let len = gzip_into(&body, &mut out).unwrap();
+        assert!(len < body.len() / 4, "{} of {}", len, body.len());
+
+        let mut decoded = Vec::new();
+        GzDecoder::new(&out[..len]).read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, body);
+
+        // Output that doesn't fit the buffer is an error, not a truncated body
+        let mut tiny = [0u8; 16];
+        assert!(gzip_into(&body, &mut tiny).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+// Per-association records for roaming diagnostics: which access point (BSSID)
+// the station joined, on what channel and PHY, how long it stayed and why the
+// link dropped. WifiReconnectManager's event handler feeds it; devices that keep
+// hopping to a far AP show up as short records alternating between BSSIDs, and
+// `wifi_bssid` pins the station to one of them.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+#[cfg(target_os = "espidf")]
+use std::sync::Mutex;
+
+/// Associations kept, oldest dropped first
+pub const MAX_RECORDS: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConnectionRecord {
+    /// Seconds since boot when the station associated
+    pub uptime_secs: u64,
+    /// Unix seconds, 0 before SNTP sync
+    pub timestamp: u64,
+    pub bssid: String,
+    pub channel: u8,
+    /// Negotiated PHY, e.g. "11n HT20"
+    pub phy: &'static str,
+    pub rssi: i8,
+    /// Why the previous association ended ("boot" for the first one)
+    pub cause: &'static str,
+    /// None while this is the current association
+    pub duration_secs: Option<u64>,
+    pub disconnect_reason: Option<&'static str>,
+}
+
+/// Newest first
+#[derive(Debug, Default)]
+pub struct ConnectionHistory {
+    records: VecDeque<ConnectionRecord>,
+    pending_cause: Option<&'static str>,
+}
+
+impl ConnectionHistory {
+    pub const fn new() -> Self {
+        Self { records: VecDeque::new(), pending_cause: None }
+    }
+
+    pub fn on_connected(&mut self, uptime_secs: u64, timestamp: u64, bssid: &[u8; 6], channel: u8, phy: &'static str, rssi: i8) {
+        // A connect without a disconnect in between is a roam handled by the driver
+        if self.close_current(uptime_secs, "roamed") {
+            self.pending_cause = Some("roamed");
+        }
+        if self.records.len() == MAX_RECORDS {
+            self.records.pop_back();
+        }
+        self.records.push_front(ConnectionRecord {
+            uptime_secs,
+            timestamp,
+            bssid: format_bssid(bssid),
+            channel,
+            phy,
+            rssi,
+            cause: self.pending_cause.take().unwrap_or("boot"),
+            duration_secs: None,
+            disconnect_reason: None,
+        });
+    }
+
+    /// Close the current association. Failed reconnect attempts also report a
+    /// disconnect; those leave the recorded cause alone.
+    pub fn on_disconnected(&mut self, uptime_secs: u64, reason: u16) {
+        let name = reason_name(reason);
+        if self.close_current(uptime_secs, name) {
+            self.pending_cause = Some(name);
+        }
+    }
+
+    fn close_current(&mut self, uptime_secs: u64, reason: &'static str) -> bool {
+        match self.records.front_mut() {
+            Some(current) if current.duration_secs.is_none() => {
+                current.duration_secs = Some(uptime_secs.saturating_sub(current.uptime_secs));
+                current.disconnect_reason = Some(reason);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn records(&self) -> Vec<ConnectionRecord> {
+        self.records.iter().cloned().collect()
+    }
+}
+
+pub fn format_bssid(bssid: &[u8; 6]) -> String {
+    bssid.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":")
+}
+
+/// `aa:bb:cc:dd:ee:ff` (or `-` separated), case-insensitive
+pub fn parse_bssid(text: &str) -> Option<[u8; 6]> {
+    let mut bssid = [0u8; 6];
+    let mut parts = text.trim().split([':', '-']);
+    for byte in bssid.iter_mut() {
+        let part = parts.next()?;
+        if part.len() != 2 {
+            return None;
+        }
+        *byte = u8::from_str_radix(part, 16).ok()?;
+    }
+    parts.next().is_none().then_some(bssid)
+}
+
+/// Name of an ESP-IDF `wifi_err_reason_t` disconnect code
+pub fn reason_name(reason: u16) -> &'static str {
+    match reason {
+        1 => "unspecified",
+        2 => "auth_expire",
+        3 => "auth_leave",
+        4 => "assoc_expire",
+        5 => "assoc_toomany",
+        6 => "not_authed",
+        7 => "not_assoced",
+        8 => "assoc_leave",
+        15 => "4way_handshake_timeout",
+        16 => "group_key_update_timeout",
+        23 => "802_1x_auth_failed",
+        34 => "missing_acks",
+        200 => "beacon_timeout",
+        201 => "no_ap_found",
+        202 => "auth_fail",
+        203 => "assoc_fail",
+        204 => "handshake_timeout",
+        205 => "connection_fail",
+        206 => "ap_tsf_reset",
+        207 => "roaming",
+        _ => "other",
+    }
+}
+
+#[cfg(target_os = "espidf")]
+static HISTORY: Mutex<ConnectionHistory> = Mutex::new(ConnectionHistory::new());
+
+#[cfg(target_os = "espidf")]
+pub fn with_history<R>(f: impl FnOnce(&mut ConnectionHistory) -> R) -> R {
+    f(&mut HISTORY.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AP1: [u8; 6] = [0x24, 0x0a, 0xc4, 0x01, 0x02, 0x03];
+    const AP2: [u8; 6] = [0x24, 0x0a, 0xc4, 0xaa, 0xbb, 0xcc];
+
+    #[test]
+    fn test_records_track_causes_and_durations() {
+        let mut history = ConnectionHistory::new();
+        history.on_connected(5, 0, &AP1, 6, "11n HT20", -52);
+        history.on_disconnected(125, 200);
+        // Failed attempts while reconnecting don't overwrite the cause
+        history.on_disconnected(130, 201);
+        history.on_connected(140, 0, &AP2, 11, "11g", -78);
+        history.on_connected(200, 0, &AP1, 6, "11n HT20", -50);
+
+        let records = history.records();
+        assert_eq!(records.len(), 3);
+        assert_eq!((records[2].cause, records[2].duration_secs, records[2].disconnect_reason), ("boot", Some(120), Some("beacon_timeout")));
+        assert_eq!((records[1].bssid.as_str(), records[1].cause), ("24:0a:c4:aa:bb:cc", "beacon_timeout"));
+        assert_eq!(records[1].disconnect_reason, Some("roamed"));
+        assert_eq!((records[0].cause, records[0].duration_secs), ("roamed", None));
+    }
+
+    #[test]
+    fn test_parse_bssid() {
+        assert_eq!(parse_bssid("24:0A:c4:aa:bb:cc"), Some(AP2));
+        assert_eq!(parse_bssid("24-0a-c4-01-02-03"), Some(AP1));
+        assert_eq!(parse_bssid(&format_bssid(&AP2)), Some(AP2));
+        assert_eq!(parse_bssid("24:0a:c4:aa:bb"), None);
+        assert_eq!(parse_bssid("24:0a:c4:aa:bb:cc:dd"), None);
+        assert_eq!(parse_bssid("24:0a:c4:aa:bb:c"), None);
+    }
+}
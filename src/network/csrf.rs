@@ -0,0 +1,208 @@
+// CSRF protection for state-changing endpoints. Page renders hand out a session
+// token in the `esp_csrf` cookie (SameSite=Strict); the pages' JS echoes it in
+// the X-CSRF-Token header, which a foreign origin can neither read nor forge.
+// Browser requests must also be same-origin (Origin/Referer vs Host). Requests
+// with no browser headers at all (curl, scripts) carry no ambient credentials
+// to abuse and are let through.
+
+#[cfg(target_os = "espidf")]
+use {
+    embedded_svc::http::Headers,
+    std::sync::Mutex,
+};
+
+pub const COOKIE_NAME: &str = "esp_csrf";
+pub const HEADER_NAME: &str = "X-CSRF-Token";
+/// Concurrent browser sessions remembered; the least recently used is evicted
+const MAX_SESSIONS: usize = 8;
+pub const SESSION_TTL_MS: u64 = 24 * 3600 * 1000;
+
+#[derive(Debug, Default)]
+pub struct SessionStore {
+    /// (token, last used ms)
+    sessions: Vec<(String, u64)>,
+}
+
+impl SessionStore {
+    pub const fn new() -> Self {
+        Self { sessions: Vec::new() }
+    }
+
+    pub fn insert(&mut self, token: String, now_ms: u64) {
+        self.expire(now_ms);
+        if self.sessions.len() >= MAX_SESSIONS {
+            if let Some(lru) = self.sessions.iter().enumerate().min_by_key(|(_, s)| s.1).map(|(i, _)| i) {
+                self.sessions.swap_remove(lru);
+            }
+        }
+        self.sessions.push((token, now_ms));
+    }
+
+    /// Whether `token` belongs to a live session; a hit keeps the session alive
+    pub fn touch(&mut self, token: &str, now_ms: u64) -> bool {
+        self.expire(now_ms);
+        match self.sessions.iter_mut().find(|s| constant_time_eq(s.0.as_bytes(), token.as_bytes())) {
+            Some(session) => {
+                session.1 = now_ms;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn expire(&mut self, now_ms: u64) {
+        self.sessions.retain(|s| now_ms.saturating_sub(s.1) < SESSION_TTL_MS);
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Value of cookie `name` in a `Cookie:` header
+pub fn cookie_value<'a>(cookie_header: &'a str, name: &str) -> Option<&'a str> {
+    cookie_header
+        .split(';')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// `host[:port]` of an absolute URL
+fn authority(url: &str) -> Option<&str> {
+    let rest = url.split_once("://")?.1;
+    let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    Some(&rest[..end])
+}
+
+/// Headers relevant to the CSRF decision
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RequestHeaders<'a> {
+    pub host: Option<&'a str>,
+    pub origin: Option<&'a str>,
+    pub referer: Option<&'a str>,
+    pub sec_fetch_site: Option<&'a str>,
+    pub token: Option<&'a str>,
+}
+
+/// Decide whether a state-changing request may proceed; the error is the 403 message
+pub fn check(headers: &RequestHeaders, token_valid: impl FnOnce(&str) -> bool) -> Result<(), &'static str> {
+    let from_browser = headers.origin.is_some() || headers.referer.is_some() || headers.sec_fetch_site.is_some();
+    if !from_browser {
+        return Ok(());
+    }
+    if matches!(headers.sec_fetch_site, Some(site) if site != "same-origin" && site != "none") {
+        return Err("Cross-origin request rejected");
+    }
+    // Origin wins over Referer; "null" (sandboxed frames, file://) never matches
+    if let Some(source) = headers.origin.or(headers.referer) {
+        if headers.host.is_none() || authority(source) != headers.host {
+            return Err("Cross-origin request rejected");
+        }
+    }
+    match headers.token {
+        Some(token) if token_valid(token) => Ok(()),
+        _ => Err("Missing or invalid CSRF token"),
+    }
+}
+
+#[cfg(target_os = "espidf")]
+static SESSIONS: Mutex<SessionStore> = Mutex::new(SessionStore::new());
+
+#[cfg(target_os = "espidf")]
+fn now_ms() -> u64 {
+    unsafe { (esp_idf_sys::esp_timer_get_time() / 1000) as u64 }
+}
+
+/// `Set-Cookie` value for a page render: keeps the caller's session if it is
+/// still valid, otherwise starts a new one
+#[cfg(target_os = "espidf")]
+pub fn session_cookie(req: &impl Headers) -> String {
+    let mut sessions = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+    let now = now_ms();
+    let token = match req.header("Cookie").and_then(|c| cookie_value(c, COOKIE_NAME)) {
+        Some(token) if sessions.touch(token, now) => token.to_string(),
+        _ => {
+            let token: String = (0..4)
+                .map(|_| format!("{:08x}", unsafe { esp_idf_sys::esp_random() }))
+                .collect();
+            sessions.insert(token.clone(), now);
+            token
+        }
+    };
+    format!("{}={}; Path=/; Max-Age={}; SameSite=Strict", COOKIE_NAME, token, SESSION_TTL_MS / 1000)
+}
+
+/// Same-origin and token check for POST/PUT/PATCH/DELETE handlers
+#[cfg(target_os = "espidf")]
+pub fn verify(req: &impl Headers) -> Result<(), &'static str> {
+    let headers = RequestHeaders {
+        host: req.header("Host"),
+        origin: req.header("Origin"),
+        referer: req.header("Referer"),
+        sec_fetch_site: req.header("Sec-Fetch-Site"),
+        token: req.header(HEADER_NAME),
+    };
+    let result = check(&headers, |token| {
+        SESSIONS.lock().unwrap_or_else(|e| e.into_inner()).touch(token, now_ms())
+    });
+    if let Err(reason) = result {
+        log::warn!("CSRF: {} (origin {:?})", reason, headers.origin.or(headers.referer));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOST: Option<&str> = Some("192.168.1.20");
+
+    #[test]
+    fn test_check_policy() {
+        let valid = |t: &str| t == "secret";
+        // Scripts without browser headers
+        assert!(check(&RequestHeaders { host: HOST, ..Default::default() }, valid).is_ok());
+
+        let same_origin = RequestHeaders {
+            host: HOST,
+            origin: Some("http://192.168.1.20"),
+            sec_fetch_site: Some("same-origin"),
+            token: Some("secret"),
+            ..Default::default()
+        };
+        assert!(check(&same_origin, valid).is_ok());
+        assert_eq!(check(&RequestHeaders { token: Some("guess"), ..same_origin }, valid), Err("Missing or invalid CSRF token"));
+        assert_eq!(check(&RequestHeaders { token: None, ..same_origin }, valid), Err("Missing or invalid CSRF token"));
+
+        let foreign = RequestHeaders { origin: Some("http://evil.example"), sec_fetch_site: None, ..same_origin };
+        assert_eq!(check(&foreign, valid), Err("Cross-origin request rejected"));
+        let null_origin = RequestHeaders { origin: Some("null"), sec_fetch_site: None, ..same_origin };
+        assert!(check(&null_origin, valid).is_err());
+        let cross_site = RequestHeaders { sec_fetch_site: Some("cross-site"), ..same_origin };
+        assert!(check(&cross_site, valid).is_err());
+        let referer_only = RequestHeaders {
+            origin: None,
+            referer: Some("http://192.168.1.20/dashboard?x=1"),
+            sec_fetch_site: None,
+            ..same_origin
+        };
+        assert!(check(&referer_only, valid).is_ok());
+    }
+
+    #[test]
+    fn test_sessions_expire_and_evict() {
+        let mut store = SessionStore::new();
+        store.insert("a".into(), 0);
+        assert!(store.touch("a", 1000));
+        assert!(!store.touch("b", 1000));
+        assert!(!store.touch("a", 1000 + SESSION_TTL_MS));
+
+        for i in 0..MAX_SESSIONS as u64 + 1 {
+            store.insert(format!("t{}", i), i);
+        }
+        assert!(!store.touch("t0", 100));
+        assert!(store.touch(&format!("t{}", MAX_SESSIONS), 100));
+        assert_eq!(cookie_value("theme=dark; esp_csrf=abc123", COOKIE_NAME), Some("abc123"));
+    }
+}
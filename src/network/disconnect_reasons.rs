@@ -0,0 +1,166 @@
+// WiFi disconnect reasons over the device lifetime. Every STA_DISCONNECTED
+// event's ESP-IDF reason code is counted per code, so a wrong password
+// (auth_fail) can be told apart from poor reception (beacon_timeout) long
+// after the event log has rolled over. The event handler counts in RAM; the
+// main loop writes the histogram to NVS at most once per SAVE_INTERVAL.
+
+use crate::network::connection_history::reason_name;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(target_os = "espidf")]
+use crate::storage::kv;
+#[cfg(target_os = "espidf")]
+use std::sync::Mutex;
+#[cfg(target_os = "espidf")]
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "espidf")]
+const NVS_NAMESPACE: &str = "wifi_diag";
+#[cfg(target_os = "espidf")]
+const NVS_KEY_REASONS: &str = "disc_reasons";
+/// A flapping link disconnects every few seconds; keep NVS writes bounded
+#[cfg(target_os = "espidf")]
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Disconnects per reason code
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DisconnectHistogram {
+    counts: BTreeMap<u16, u32>,
+}
+
+/// One bar of the histogram
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ReasonCount {
+    pub code: u16,
+    pub reason: &'static str,
+    pub count: u32,
+}
+
+impl DisconnectHistogram {
+    pub fn record(&mut self, code: u16) {
+        let count = self.counts.entry(code).or_insert(0);
+        *count = count.saturating_add(1);
+    }
+
+    pub fn total(&self) -> u32 {
+        self.counts.values().fold(0, |total, &n| total.saturating_add(n))
+    }
+
+    /// Every code seen, in code order
+    pub fn counts(&self) -> Vec<ReasonCount> {
+        self.counts.iter().map(|(&code, &count)| ReasonCount { code, reason: reason_name(code), count }).collect()
+    }
+
+    /// The `n` most frequent reasons, most frequent first
+    pub fn top(&self, n: usize) -> Vec<ReasonCount> {
+        let mut counts = self.counts();
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then(a.code.cmp(&b.code)));
+        counts.truncate(n);
+        counts
+    }
+}
+
+/// Network screen line, "beacon_timeout 5, auth_fail 2"
+pub fn summary(top: &[ReasonCount]) -> String {
+    top.iter().map(|r| format!("{} {}", r.reason, r.count)).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(target_os = "espidf")]
+struct Reasons {
+    histogram: DisconnectHistogram,
+    dirty: bool,
+    last_save: Instant,
+}
+
+#[cfg(target_os = "espidf")]
+static REASONS: Mutex<Option<Reasons>> = Mutex::new(None);
+
+/// Load the histogram. Call once at startup, after `kv::init` and before WiFi
+/// starts.
+#[cfg(target_os = "espidf")]
+pub fn init() {
+    let histogram: DisconnectHistogram = kv::open(NVS_NAMESPACE).ok()
+        .and_then(|mut store| store.get(NVS_KEY_REASONS))
+        .unwrap_or_default();
+    if histogram.total() > 0 {
+        log::info!("WiFi: {} disconnects on record, mostly {}", histogram.total(), summary(&histogram.top(3)));
+    }
+    if let Ok(mut slot) = REASONS.lock() {
+        *slot = Some(Reasons { histogram, dirty: false, last_save: Instant::now() });
+    }
+}
+
+/// Count a disconnect (WiFi event handler; no flash access)
+#[cfg(target_os = "espidf")]
+pub fn record(code: u16) {
+    let Ok(mut slot) = REASONS.lock() else { return };
+    let Some(reasons) = slot.as_mut() else { return };
+    reasons.histogram.record(code);
+    reasons.dirty = true;
+}
+
+#[cfg(target_os = "espidf")]
+pub fn histogram() -> DisconnectHistogram {
+    REASONS.lock().ok().and_then(|slot| slot.as_ref().map(|r| r.histogram.clone())).unwrap_or_default()
+}
+
+/// Write the histogram if it changed and SAVE_INTERVAL has passed (main loop)
+#[cfg(target_os = "espidf")]
+pub fn save_if_due() {
+    save_when(|r| r.last_save.elapsed() >= SAVE_INTERVAL);
+}
+
+/// Write any unsaved counts now (shutdown, deep sleep)
+#[cfg(target_os = "espidf")]
+pub fn save() {
+    save_when(|_| true);
+}
+
+#[cfg(target_os = "espidf")]
+fn save_when(due: impl Fn(&Reasons) -> bool) {
+    let Ok(mut slot) = REASONS.lock() else { return };
+    let Some(reasons) = slot.as_mut() else { return };
+    if !reasons.dirty || !due(reasons) {
+        return;
+    }
+    reasons.last_save = Instant::now();
+    match kv::open(NVS_NAMESPACE).and_then(|mut store| store.set(NVS_KEY_REASONS, &reasons.histogram)) {
+        Ok(()) => reasons.dirty = false,
+        Err(e) => log::warn!("Failed to save WiFi disconnect reasons: {:?}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_reasons() {
+        let mut histogram = DisconnectHistogram::default();
+        assert_eq!(histogram.total(), 0);
+        assert!(histogram.top(3).is_empty());
+
+        for code in [200, 202, 200, 8, 200, 202, 999] {
+            histogram.record(code);
+        }
+        assert_eq!(histogram.total(), 7);
+        let top = histogram.top(3);
+        assert_eq!(top[0], ReasonCount { code: 200, reason: "beacon_timeout", count: 3 });
+        assert_eq!(top[1], ReasonCount { code: 202, reason: "auth_fail", count: 2 });
+        // Ties go to the lower code
+        assert_eq!((top[2].code, top[2].reason), (8, "assoc_leave"));
+        assert_eq!(summary(&histogram.top(2)), "beacon_timeout 3, auth_fail 2");
+        assert_eq!(histogram.counts().last().map(|r| (r.code, r.reason)), Some((999, "other")));
+    }
+
+    #[test]
+    fn test_histogram_round_trips_through_json() {
+        let mut histogram = DisconnectHistogram::default();
+        histogram.record(201);
+        histogram.record(15);
+        let json = serde_json::to_string(&histogram).unwrap();
+        assert_eq!(json, r#"{"15":1,"201":1}"#);
+        assert_eq!(serde_json::from_str::<DisconnectHistogram>(&json).unwrap(), histogram);
+    }
+}
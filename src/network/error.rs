@@ -0,0 +1,80 @@
+// Outbound network errors (http_client and its DNS pre-check). Pollers and the
+// push exporter use is_transient to tell a failure worth retrying (link down,
+// timeout, 5xx) from one that will fail the same way next time (a rejected
+// request, an oversized response).
+
+use thiserror::Error;
+use crate::error::HttpStatus;
+use super::http_client;
+
+#[derive(Debug, Error)]
+pub enum NetworkError {
+    #[error("DNS lookup failed: {0}")]
+    Dns(String),
+    #[error("connect failed: {0}")]
+    Connect(String),
+    #[error("request timed out")]
+    Timeout,
+    /// Non-2xx response
+    #[error("HTTP {0}")]
+    Status(u16),
+    /// Response longer than the request's max_response_bytes
+    #[error("response exceeds {0} bytes")]
+    TooLarge(usize),
+    #[error("I/O error: {0}")]
+    Io(String),
+}
+
+impl NetworkError {
+    /// Whether the same request may succeed if tried again later
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Status(status) => http_client::is_retryable(*status),
+            Self::TooLarge(_) => false,
+            Self::Dns(_) | Self::Connect(_) | Self::Timeout | Self::Io(_) => true,
+        }
+    }
+}
+
+impl HttpStatus for NetworkError {
+    fn http_status(&self) -> u16 {
+        match self {
+            Self::Timeout => 504,
+            _ => 502,
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl From<esp_idf_sys::EspError> for NetworkError {
+    fn from(e: esp_idf_sys::EspError) -> Self {
+        match e.code() {
+            esp_idf_sys::ESP_ERR_TIMEOUT | esp_idf_sys::ESP_ERR_HTTP_EAGAIN => Self::Timeout,
+            esp_idf_sys::ESP_ERR_HTTP_CONNECT | esp_idf_sys::ESP_ERR_HTTP_CONNECTING => Self::Connect(e.to_string()),
+            _ => Self::Io(e.to_string()),
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl From<esp_idf_svc::io::EspIOError> for NetworkError {
+    fn from(e: esp_idf_svc::io::EspIOError) -> Self {
+        e.0.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transient_errors() {
+        assert!(NetworkError::Timeout.is_transient());
+        assert!(NetworkError::Dns("no such host".into()).is_transient());
+        assert!(NetworkError::Status(503).is_transient());
+        assert!(!NetworkError::Status(404).is_transient());
+        assert!(!NetworkError::TooLarge(8192).is_transient());
+        assert_eq!(NetworkError::Timeout.http_status(), 504);
+        assert_eq!(NetworkError::Status(404).http_status(), 502);
+    }
+}
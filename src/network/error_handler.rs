@@ -1,4 +1,5 @@
 use anyhow::Result;
+use crate::error::HttpStatus;
 use esp_idf_svc::http::server::Request as EspHttpRequest;
 use esp_idf_svc::io::Write;
 use serde::{Serialize, Deserialize};
@@ -9,6 +10,12 @@ use std::error::Error as StdError;
 pub enum ErrorCode {
     NotFound,
     BadRequest,
+    Forbidden,
+    Conflict,
+    Internal,
+    BadGateway,
+    ServiceUnavailable,
+    GatewayTimeout,
 }
 
 impl ErrorCode {
@@ -16,6 +23,39 @@ impl ErrorCode {
         match self {
             ErrorCode::NotFound => "NOT_FOUND",
             ErrorCode::BadRequest => "BAD_REQUEST",
+            ErrorCode::Forbidden => "FORBIDDEN",
+            ErrorCode::Conflict => "CONFLICT",
+            ErrorCode::Internal => "INTERNAL_ERROR",
+            ErrorCode::BadGateway => "BAD_GATEWAY",
+            ErrorCode::ServiceUnavailable => "SERVICE_UNAVAILABLE",
+            ErrorCode::GatewayTimeout => "GATEWAY_TIMEOUT",
+        }
+    }
+
+    pub fn status(&self) -> u16 {
+        match self {
+            ErrorCode::BadRequest => 400,
+            ErrorCode::Forbidden => 403,
+            ErrorCode::NotFound => 404,
+            ErrorCode::Conflict => 409,
+            ErrorCode::Internal => 500,
+            ErrorCode::BadGateway => 502,
+            ErrorCode::ServiceUnavailable => 503,
+            ErrorCode::GatewayTimeout => 504,
+        }
+    }
+
+    /// Code for a status from `HttpStatus`; unlisted 4xx are bad requests
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            403 => ErrorCode::Forbidden,
+            404 => ErrorCode::NotFound,
+            409 => ErrorCode::Conflict,
+            502 => ErrorCode::BadGateway,
+            503 => ErrorCode::ServiceUnavailable,
+            504 => ErrorCode::GatewayTimeout,
+            400..=499 => ErrorCode::BadRequest,
+            _ => ErrorCode::Internal,
         }
     }
 }
@@ -26,6 +66,9 @@ pub struct ApiError {
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub field: Option<String>,
+    /// Every rejected field of a config update
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<crate::config::FieldError>,
     pub request_id: String,
     pub timestamp: u64,
 }
@@ -36,6 +79,7 @@ impl ApiError {
             code: code.as_str().to_string(),
             message: message.into(),
             field: None,
+            fields: Vec::new(),
             request_id: generate_request_id(),
             timestamp: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
@@ -57,15 +101,34 @@ impl std::error::Error for ApiError {}
 #[derive(Serialize)]
 pub struct ErrorResponse {
     pub error: ApiError,
+    #[serde(skip)]
+    status: u16,
 }
 
 impl ErrorResponse {
     pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
         Self {
             error: ApiError::new(code, message),
+            status: code.status(),
         }
     }
 
+    /// Response for a typed error (DisplayError, NetworkError, OtaError,
+    /// ConfigError), with the status it maps to
+    pub fn from_error<E: HttpStatus>(e: &E) -> Self {
+        let status = e.http_status();
+        Self {
+            error: ApiError::new(ErrorCode::from_status(status), e.to_string()),
+            status,
+        }
+    }
+
+    /// Attach the per-field failures of a rejected config update
+    pub fn with_fields(mut self, fields: &[crate::config::FieldError]) -> Self {
+        self.error.fields = fields.to_vec();
+        self
+    }
+
     pub fn not_found(message: impl Into<String>) -> Self {
         Self::new(ErrorCode::NotFound, message)
     }
@@ -74,19 +137,18 @@ impl ErrorResponse {
         Self::new(ErrorCode::BadRequest, message)
     }
 
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Forbidden, message)
+    }
+
     pub fn send<T>(self, req: EspHttpRequest<T>) -> Result<(), Box<dyn std::error::Error>> 
     where 
         T: esp_idf_svc::http::server::Connection,
         <T as esp_idf_svc::io::ErrorType>::Error: StdError + 'static
     {
         let json = serde_json::to_string(&self)?;
-        let status_code = match self.error.code.as_str() {
-            "BAD_REQUEST" => 400,
-            "NOT_FOUND" => 404,
-            _ => 500,
-        };
         // Guard against double send: try to write once; if it fails with already sent, just log
-        match req.into_status_response(status_code) {
+        match req.into_status_response(self.status) {
             Ok(mut response) => {
                 let _ = response.write_all(json.as_bytes());
             }
@@ -0,0 +1,69 @@
+// ETags for the pages and styles the web server serves. Embedded assets get
+// one tag for the whole build: build.rs hashes every template and page
+// source, so it changes exactly when a new firmware changes what is served.
+// The home page is rendered with live figures, so its tag is a hash of the
+// rendered body instead. A request whose If-None-Match carries the current
+// tag gets an empty 304 rather than the page.
+
+/// FNV-1a 64-bit
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3))
+}
+
+/// Quoted ETag for a rendered body
+pub fn content_etag(body: &[u8]) -> String {
+    format!("\"{:016x}\"", fnv1a(body))
+}
+
+/// Whether an If-None-Match header lists `etag` (weak or strong) or is `*`
+pub fn matches(if_none_match: Option<&str>, etag: &str) -> bool {
+    let Some(header) = if_none_match else { return false };
+    header.split(',').map(str::trim).any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+#[cfg(target_os = "espidf")]
+mod device {
+    use esp_idf_svc::http::server::{EspHttpConnection, Request};
+
+    /// ETag of everything embedded in this build
+    pub const ASSETS: &str = concat!("\"", env!("ASSETS_HASH"), "\"");
+
+    /// The client already has `etag`
+    pub fn is_fresh(req: &Request<&mut EspHttpConnection<'_>>, etag: &str) -> bool {
+        super::matches(req.header("If-None-Match"), etag)
+    }
+
+    /// Empty 304 for a fresh client; `extra_headers` are the ones a 200 would
+    /// also have set (cookies, Cache-Control)
+    pub fn send_not_modified(
+        req: Request<&mut EspHttpConnection<'_>>,
+        etag: &str,
+        extra_headers: &[(&str, &str)],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut headers = vec![("ETag", etag)];
+        headers.extend_from_slice(extra_headers);
+        req.into_response(304, Some("Not Modified"), &headers)?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub use device::{is_fresh, send_not_modified, ASSETS};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_if_none_match() {
+        let etag = content_etag(b"<html></html>");
+        assert_eq!(etag.len(), 18);
+        assert_ne!(etag, content_etag(b"<html> </html>"));
+
+        assert!(matches(Some(&etag), &etag));
+        assert!(matches(Some(&format!("\"stale\", W/{}", etag)), &etag));
+        assert!(matches(Some("*"), &etag));
+        assert!(!matches(Some("\"stale\""), &etag));
+        assert!(!matches(None, &etag));
+    }
+}
@@ -1,18 +1,65 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+#[cfg(target_os = "espidf")]
 use esp_idf_svc::http::server::{EspHttpServer, Method};
+#[cfg(target_os = "espidf")]
 use esp_idf_svc::io::Write;
+#[cfg(target_os = "espidf")]
 use std::fs;
+#[cfg(target_os = "espidf")]
 use std::path::PathBuf;
+#[cfg(target_os = "espidf")]
 use crate::network::error_handler::ErrorResponse;
+#[cfg(target_os = "espidf")]
 use crate::network::validators;
+#[cfg(target_os = "espidf")]
+use crate::network::heap_tracker::TrackedHandlers;
 
+#[cfg(target_os = "espidf")]
 const MAX_FILE_SIZE: usize = 256 * 1024; // 256KB for ESP32
+#[cfg(target_os = "espidf")]
 const ALLOWED_EXTENSIONS: &[&str] = &["json", "toml", "log", "bin", "txt", "md"];
+#[cfg(target_os = "espidf")]
 const BASE_PATH: &str = "/spiffs"; // or "/littlefs" based on your partition
 
+/// Uploaded boot logo, shown by BootManager instead of the title text
+pub const BOOT_LOGO_PATH: &str = "/spiffs/bootlogo.565";
+
+/// Boot logo file: "R565", width and height as u16 LE, then rows of
+/// big-endian RGB565 pixels (the panel's byte order, so it streams as-is).
+/// The web UI converts PNGs to this format before uploading.
+pub const LOGO_MAGIC: &[u8; 4] = b"R565";
+pub const LOGO_HEADER_LEN: usize = 8;
+
+/// Largest logo: the boot screen area above the progress bar
+pub const MAX_LOGO_WIDTH: u16 = 300;
+pub const MAX_LOGO_HEIGHT: u16 = 90;
+
+/// Largest valid logo file
+pub const MAX_LOGO_BYTES: usize = LOGO_HEADER_LEN + MAX_LOGO_WIDTH as usize * MAX_LOGO_HEIGHT as usize * 2;
+
+/// Check a boot logo file; returns its (width, height)
+pub fn validate_logo(bytes: &[u8]) -> Result<(u16, u16)> {
+    if bytes.starts_with(b"\x89PNG") {
+        return Err(anyhow!("PNG logos are converted by the settings page; upload RGB565 data"));
+    }
+    if bytes.len() < LOGO_HEADER_LEN || &bytes[..4] != LOGO_MAGIC {
+        return Err(anyhow!("Logo must be an RGB565 image with an R565 header"));
+    }
+    let width = u16::from_le_bytes([bytes[4], bytes[5]]);
+    let height = u16::from_le_bytes([bytes[6], bytes[7]]);
+    if !(1..=MAX_LOGO_WIDTH).contains(&width) || !(1..=MAX_LOGO_HEIGHT).contains(&height) {
+        return Err(anyhow!("Logo must be at most {}x{} pixels", MAX_LOGO_WIDTH, MAX_LOGO_HEIGHT));
+    }
+    if bytes.len() != LOGO_HEADER_LEN + width as usize * height as usize * 2 {
+        return Err(anyhow!("Logo data does not match its {}x{} size", width, height));
+    }
+    Ok((width, height))
+}
+
+#[cfg(target_os = "espidf")]
 pub fn register_file_routes(server: &mut EspHttpServer<'static>) -> Result<()> {
     // GET /api/files - List files
-    server.fn_handler("/api/files", Method::Get, |req| {
+    server.tracked_handler("/api/files", Method::Get, |req| {
         let path = req.uri()
             .split('?')
             .nth(1)
@@ -66,7 +113,7 @@ pub fn register_file_routes(server: &mut EspHttpServer<'static>) -> Result<()> {
     })?;
 
     // GET /api/files/content - Read file content
-    server.fn_handler("/api/files/content", Method::Get, |req| {
+    server.tracked_handler("/api/files/content", Method::Get, |req| {
         let filename = req.uri()
             .split('?')
             .nth(1)
@@ -120,7 +167,10 @@ pub fn register_file_routes(server: &mut EspHttpServer<'static>) -> Result<()> {
     })?;
 
     // PUT /api/files/content - Save file content
-    server.fn_handler("/api/files/content", Method::Put, |mut req| {
+    server.tracked_handler("/api/files/content", Method::Put, |mut req| {
+        if let Err(reason) = crate::network::csrf::verify(&req) {
+            return ErrorResponse::forbidden(reason).send(req);
+        }
         let uri = req.uri().to_string();
         let filename = uri
             .split('?')
@@ -187,7 +237,10 @@ pub fn register_file_routes(server: &mut EspHttpServer<'static>) -> Result<()> {
     })?;
 
     // POST /api/files/upload - Upload file
-    server.fn_handler("/api/files/upload", Method::Post, |mut req| {
+    server.tracked_handler("/api/files/upload", Method::Post, |mut req| {
+        if let Err(reason) = crate::network::csrf::verify(&req) {
+            return ErrorResponse::forbidden(reason).send(req);
+        }
         let filename = req.header("X-Filename")
             .ok_or_else(|| anyhow::anyhow!("Missing X-Filename header"))?
             .to_string();
@@ -247,7 +300,10 @@ pub fn register_file_routes(server: &mut EspHttpServer<'static>) -> Result<()> {
     })?;
 
     // DELETE /api/files - Delete file
-    server.fn_handler("/api/files", Method::Delete, |req| {
+    server.tracked_handler("/api/files", Method::Delete, |req| {
+        if let Err(reason) = crate::network::csrf::verify(&req) {
+            return ErrorResponse::forbidden(reason).send(req);
+        }
         let filename = req.uri()
             .split('?')
             .nth(1)
@@ -293,27 +349,107 @@ pub fn register_file_routes(server: &mut EspHttpServer<'static>) -> Result<()> {
         Ok(()) as Result<(), Box<dyn std::error::Error>>
     })?;
 
+    // POST /api/boot-logo - Replace the boot logo (validated RGB565 file)
+    server.tracked_handler("/api/boot-logo", Method::Post, |mut req| {
+        if let Err(reason) = crate::network::csrf::verify(&req) {
+            return ErrorResponse::forbidden(reason).send(req);
+        }
+        let content_length = req.header("Content-Length")
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(0);
+        if content_length == 0 || content_length > MAX_LOGO_BYTES {
+            return ErrorResponse::bad_request(format!("Logo must be 1-{} bytes", MAX_LOGO_BYTES)).send(req);
+        }
+
+        let mut body = vec![0u8; content_length];
+        let mut read = 0;
+        while read < content_length {
+            match req.read(&mut body[read..])? {
+                0 => break,
+                n => read += n,
+            }
+        }
+        body.truncate(read);
+
+        let (width, height) = match validate_logo(&body) {
+            Ok(size) => size,
+            Err(e) => return ErrorResponse::bad_request(e.to_string()).send(req),
+        };
+        crate::storage::ensure_spiffs_mounted()?;
+        fs::write(BOOT_LOGO_PATH, &body)?;
+        log::info!("Boot logo updated ({}x{})", width, height);
+
+        let response = serde_json::json!({
+            "status": "saved",
+            "width": width,
+            "height": height,
+        });
+        let mut http_response = req.into_response(
+            200,
+            Some("OK"),
+            &[("Content-Type", "application/json")]
+        )?;
+        http_response.write_all(response.to_string().as_bytes())?;
+        Ok(()) as Result<(), Box<dyn std::error::Error>>
+    })?;
+
+    // DELETE /api/boot-logo - Back to the built-in boot animation
+    server.tracked_handler("/api/boot-logo", Method::Delete, |req| {
+        if let Err(reason) = crate::network::csrf::verify(&req) {
+            return ErrorResponse::forbidden(reason).send(req);
+        }
+        crate::storage::ensure_spiffs_mounted()?;
+        let removed = fs::remove_file(BOOT_LOGO_PATH).is_ok();
+        let response = serde_json::json!({ "status": if removed { "deleted" } else { "absent" } });
+        let mut http_response = req.into_response(
+            200,
+            Some("OK"),
+            &[("Content-Type", "application/json")]
+        )?;
+        http_response.write_all(response.to_string().as_bytes())?;
+        Ok(()) as Result<(), Box<dyn std::error::Error>>
+    })?;
+
     // File manager UI page (inject shared navbar if missing)
-    server.fn_handler("/files", Method::Get, |req| {
+    server.tracked_handler("/files", Method::Get, |req| {
         let template = include_str!("../templates/files.html");
-        let mut navbar = include_str!("../templates/partials/navbar.html").to_string();
-        navbar = navbar
-            .replace("{{HOME_ACTIVE}}", "")
-            .replace("{{DASH_ACTIVE}}", "")
-            .replace("{{LOGS_ACTIVE}}", "")
-            .replace("{{FILES_ACTIVE}}", "class=\\\"active\\\"")
-            .replace("{{OTA_ACTIVE}}", "")
-            .replace("{{DEV_ACTIVE}}", "");
+        let navbar = crate::templates::render_navbar("/files");
         let html = if template.contains("<nav class=\"navbar\">") {
             template.to_string()
         } else {
             template.replacen("<body>", &format!("<body>\n{}", navbar), 1)
         };
-        let mut response = req.into_ok_response()?;
+        let html = crate::templates::with_csrf_script(&html);
+        let cookie = crate::network::csrf::session_cookie(&req);
+        let mut response = req.into_response(200, Some("OK"), &[("Content-Type", "text/html; charset=utf-8"), ("Set-Cookie", cookie.as_str())])?;
         response.write_all(html.as_bytes())?;
         Ok(()) as Result<(), Box<dyn std::error::Error>>
     })?;
 
     log::info!("File manager routes registered");
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logo(width: u16, height: u16, pixels: usize) -> Vec<u8> {
+        let mut bytes = LOGO_MAGIC.to_vec();
+        bytes.extend_from_slice(&width.to_le_bytes());
+        bytes.extend_from_slice(&height.to_le_bytes());
+        bytes.resize(LOGO_HEADER_LEN + pixels * 2, 0xff);
+        bytes
+    }
+
+    #[test]
+    fn test_validate_logo() {
+        assert_eq!(validate_logo(&logo(64, 32, 64 * 32)).unwrap(), (64, 32));
+        assert_eq!(validate_logo(&logo(MAX_LOGO_WIDTH, MAX_LOGO_HEIGHT, MAX_LOGO_BYTES / 2 - 4)).unwrap().0, MAX_LOGO_WIDTH);
+        // Truncated data, too tall, empty, not RGB565
+        assert!(validate_logo(&logo(64, 32, 64 * 31)).is_err());
+        assert!(validate_logo(&logo(64, MAX_LOGO_HEIGHT + 1, 64 * (MAX_LOGO_HEIGHT as usize + 1))).is_err());
+        assert!(validate_logo(&logo(0, 32, 0)).is_err());
+        assert!(validate_logo(b"\x89PNG\r\n\x1a\n....").is_err());
+    }
+}
@@ -0,0 +1,308 @@
+// Per-route heap peaks. Template rendering occasionally runs the device out
+// of memory, and free-heap snapshots before and after a handler miss the
+// high-water mark in between. With CONFIG_HEAP_USE_HOOKS the heap calls
+// esp_heap_trace_alloc_hook after every allocation; while a handler runs,
+// allocations made by its task sample the free heap, and the lowest value
+// seen gives the handler's peak. Peaks are kept per route and served, worst
+// first, at /api/perf/http. The same hook counts the allocations the render
+// loop makes per frame, which should stay near zero.
+
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// Routes kept; further routes are not tracked
+pub const MAX_ROUTES: usize = 64;
+
+/// Peak allocation figures for one route
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoutePeak {
+    pub route: String,
+    pub requests: u32,
+    /// Highest peak of any request
+    pub peak_bytes: u32,
+    /// Peak of the most recent request
+    pub last_peak_bytes: u32,
+    /// Mean peak over all requests
+    pub avg_peak_bytes: u32,
+    #[serde(skip)]
+    total_peak_bytes: u64,
+}
+
+/// Peaks by route
+#[derive(Debug, Clone, Default)]
+pub struct RoutePeaks {
+    routes: Vec<RoutePeak>,
+}
+
+impl RoutePeaks {
+    pub const fn new() -> Self {
+        Self { routes: Vec::new() }
+    }
+
+    /// Fold in the peak of one request to `route`
+    pub fn record(&mut self, route: &str, peak_bytes: u32) {
+        let index = match self.routes.iter().position(|r| r.route == route) {
+            Some(index) => index,
+            None if self.routes.len() < MAX_ROUTES => {
+                self.routes.push(RoutePeak {
+                    route: route.to_string(),
+                    requests: 0,
+                    peak_bytes: 0,
+                    last_peak_bytes: 0,
+                    avg_peak_bytes: 0,
+                    total_peak_bytes: 0,
+                });
+                self.routes.len() - 1
+            }
+            None => return,
+        };
+        let entry = &mut self.routes[index];
+        entry.requests = entry.requests.saturating_add(1);
+        entry.peak_bytes = entry.peak_bytes.max(peak_bytes);
+        entry.last_peak_bytes = peak_bytes;
+        entry.total_peak_bytes += peak_bytes as u64;
+        entry.avg_peak_bytes = (entry.total_peak_bytes / entry.requests as u64) as u32;
+    }
+
+    /// All routes, highest peak first
+    pub fn worst_first(&self) -> Vec<RoutePeak> {
+        let mut routes = self.routes.clone();
+        routes.sort_by(|a, b| b.peak_bytes.cmp(&a.peak_bytes).then_with(|| a.route.cmp(&b.route)));
+        routes
+    }
+}
+
+/// Bytes allocated at the peak, from the free heap at the start and the lowest seen
+pub fn peak_bytes(start_free: u32, min_free: u32) -> u32 {
+    start_free.saturating_sub(min_free)
+}
+
+/// Allocations per rendered frame, averaged between reports
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameAllocStats {
+    frames: u32,
+    allocs: u32,
+}
+
+impl FrameAllocStats {
+    pub fn record(&mut self, allocs: u32) {
+        self.frames = self.frames.saturating_add(1);
+        self.allocs = self.allocs.saturating_add(allocs);
+    }
+
+    /// Average since the last call; 0 when no frame was rendered
+    pub fn take_average(&mut self) -> f32 {
+        let Self { frames, allocs } = std::mem::take(self);
+        if frames == 0 {
+            0.0
+        } else {
+            allocs as f32 / frames as f32
+        }
+    }
+}
+
+static PEAKS: Mutex<RoutePeaks> = Mutex::new(RoutePeaks::new());
+
+/// Record a request's peak under `route`
+pub fn record(route: &str, peak_bytes: u32) {
+    if let Ok(mut peaks) = PEAKS.lock() {
+        peaks.record(route, peak_bytes);
+    }
+}
+
+/// Per-route peaks, highest first
+pub fn snapshot() -> Vec<RoutePeak> {
+    PEAKS.lock().map(|peaks| peaks.worst_first()).unwrap_or_default()
+}
+
+#[cfg(target_os = "espidf")]
+mod device {
+    use super::*;
+    use core::ffi::c_void;
+    use core::fmt::Debug;
+    use esp_idf_svc::http::server::{EspHttpConnection, EspHttpServer, Method, Request};
+    use esp_idf_svc::io::Write;
+    use esp_idf_sys::{heap_caps_get_free_size, xTaskGetCurrentTaskHandle, EspError, MALLOC_CAP_DEFAULT};
+    use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU32, Ordering};
+
+    /// Task whose allocations are being sampled; null outside a handler
+    static TRACKED_TASK: AtomicPtr<c_void> = AtomicPtr::new(core::ptr::null_mut());
+    static MIN_FREE: AtomicU32 = AtomicU32::new(u32::MAX);
+    /// Task rendering a frame; null between frames
+    static FRAME_TASK: AtomicPtr<c_void> = AtomicPtr::new(core::ptr::null_mut());
+    static FRAME_ALLOCS: AtomicU32 = AtomicU32::new(0);
+    /// The heap has called the hook at least once (CONFIG_HEAP_USE_HOOKS is on)
+    static HOOKS_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+    /// Called by the heap after every allocation. Runs inside malloc, so it
+    /// must not allocate, lock or log.
+    #[no_mangle]
+    pub extern "C" fn esp_heap_trace_alloc_hook(_ptr: *mut c_void, _size: usize, _caps: u32) {
+        HOOKS_ACTIVE.store(true, Ordering::Relaxed);
+        let tracked = TRACKED_TASK.load(Ordering::Relaxed);
+        let frame_task = FRAME_TASK.load(Ordering::Relaxed);
+        if tracked.is_null() && frame_task.is_null() {
+            return;
+        }
+        let task = unsafe { xTaskGetCurrentTaskHandle() } as *mut c_void;
+        if task == frame_task {
+            FRAME_ALLOCS.fetch_add(1, Ordering::Relaxed);
+        }
+        if task == tracked {
+            let free = unsafe { heap_caps_get_free_size(MALLOC_CAP_DEFAULT) } as u32;
+            MIN_FREE.fetch_min(free, Ordering::Relaxed);
+        }
+    }
+
+    /// False when the firmware was built without CONFIG_HEAP_USE_HOOKS
+    pub fn hooks_active() -> bool {
+        HOOKS_ACTIVE.load(Ordering::Relaxed)
+    }
+
+    /// Samples the current task's allocations until finished or dropped.
+    /// Handlers run one at a time on the httpd task, so one scope at a time;
+    /// a scope opened while another is active measures nothing.
+    pub struct AllocScope {
+        start_free: u32,
+        active: bool,
+    }
+
+    impl AllocScope {
+        pub fn begin() -> Self {
+            let task = unsafe { xTaskGetCurrentTaskHandle() } as *mut c_void;
+            let start_free = unsafe { heap_caps_get_free_size(MALLOC_CAP_DEFAULT) } as u32;
+            let active = TRACKED_TASK
+                .compare_exchange(core::ptr::null_mut(), task, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok();
+            if active {
+                MIN_FREE.store(start_free, Ordering::Relaxed);
+            }
+            Self { start_free, active }
+        }
+
+        /// Stop sampling; the peak bytes allocated while the scope was open
+        pub fn finish(mut self) -> Option<u32> {
+            self.end()
+        }
+
+        fn end(&mut self) -> Option<u32> {
+            if !std::mem::take(&mut self.active) {
+                return None;
+            }
+            TRACKED_TASK.store(core::ptr::null_mut(), Ordering::Release);
+            Some(peak_bytes(self.start_free, MIN_FREE.load(Ordering::Relaxed)))
+        }
+    }
+
+    impl Drop for AllocScope {
+        fn drop(&mut self) {
+            self.end();
+        }
+    }
+
+    /// Counts the current task's allocations while one frame renders
+    pub struct FrameAllocScope(());
+
+    impl FrameAllocScope {
+        pub fn begin() -> Self {
+            FRAME_ALLOCS.store(0, Ordering::Relaxed);
+            FRAME_TASK.store(unsafe { xTaskGetCurrentTaskHandle() } as *mut c_void, Ordering::Release);
+            Self(())
+        }
+
+        /// Stop counting; allocations made since `begin`
+        pub fn finish(self) -> u32 {
+            FRAME_ALLOCS.load(Ordering::Relaxed)
+        }
+    }
+
+    impl Drop for FrameAllocScope {
+        fn drop(&mut self) {
+            FRAME_TASK.store(core::ptr::null_mut(), Ordering::Release);
+        }
+    }
+
+    /// 503 for a heavy request that arrived while another was running
+    fn reject_busy(req: Request<&mut EspHttpConnection<'_>>) {
+        let retry_after = crate::network::admission::RETRY_AFTER_SECS.to_string();
+        let headers = [("Retry-After", retry_after.as_str()), ("Content-Type", "text/plain"), ("Connection", "close")];
+        if let Ok(mut response) = req.into_response(503, Some("Service Unavailable"), &headers) {
+            let _ = response.write_all(b"Busy with another large request, retry shortly");
+        }
+    }
+
+    /// `fn_handler` that applies admission control (see `admission`) and
+    /// records each admitted request's heap peak under its URI
+    pub trait TrackedHandlers {
+        fn tracked_handler<E, F>(&mut self, uri: &'static str, method: Method, handler: F) -> Result<&mut Self, EspError>
+        where
+            F: for<'r> Fn(Request<&mut EspHttpConnection<'r>>) -> Result<(), E> + Send + 'static,
+            E: Debug;
+    }
+
+    impl TrackedHandlers for EspHttpServer<'static> {
+        fn tracked_handler<E, F>(&mut self, uri: &'static str, method: Method, handler: F) -> Result<&mut Self, EspError>
+        where
+            F: for<'r> Fn(Request<&mut EspHttpConnection<'r>>) -> Result<(), E> + Send + 'static,
+            E: Debug,
+        {
+            self.fn_handler(uri, method, move |req| {
+                let Some(_permit) = crate::network::admission::admit(uri) else {
+                    reject_busy(req);
+                    return Ok(());
+                };
+                let scope = AllocScope::begin();
+                let result = handler(req);
+                if let Some(peak) = scope.finish() {
+                    record(uri, peak);
+                }
+                result
+            })
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub use device::{hooks_active, AllocScope, FrameAllocScope, TrackedHandlers};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_peaks() {
+        let mut peaks = RoutePeaks::new();
+        peaks.record("/api/system", 4_000);
+        peaks.record("/", 22_000);
+        peaks.record("/api/system", 6_000);
+        peaks.record("/", 10_000);
+
+        let worst = peaks.worst_first();
+        assert_eq!(worst[0].route, "/");
+        assert_eq!((worst[0].requests, worst[0].peak_bytes, worst[0].last_peak_bytes, worst[0].avg_peak_bytes), (2, 22_000, 10_000, 16_000));
+        assert_eq!((worst[1].peak_bytes, worst[1].avg_peak_bytes), (6_000, 5_000));
+
+        // Table is bounded; known routes keep updating once it is full
+        for i in 0..MAX_ROUTES {
+            peaks.record(&format!("/r{}", i), 1);
+        }
+        assert_eq!(peaks.worst_first().len(), MAX_ROUTES);
+        peaks.record("/", 30_000);
+        assert_eq!(peaks.worst_first()[0].peak_bytes, 30_000);
+
+        assert_eq!(peak_bytes(100_000, 70_000), 30_000);
+        // Other tasks freeing memory mid-request never makes a negative peak
+        assert_eq!(peak_bytes(100_000, 120_000), 0);
+    }
+
+    #[test]
+    fn test_frame_alloc_average() {
+        let mut stats = FrameAllocStats::default();
+        assert_eq!(stats.take_average(), 0.0);
+        for allocs in [0, 3, 0, 1] {
+            stats.record(allocs);
+        }
+        assert_eq!(stats.take_average(), 1.0);
+        assert_eq!(stats.take_average(), 0.0, "each report starts over");
+    }
+}
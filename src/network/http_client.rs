@@ -0,0 +1,267 @@
+// Shared outbound HTTP(S) client used by weather, REST widgets, Alertmanager,
+// remote config sync and the push exporter. Centralises timeouts, TLS (the
+// ESP-IDF certificate bundle), response size caps, retry with jittered backoff
+// and accounting. At most MAX_CONNECTIONS requests run at once; each TLS
+// session costs ~40 KB of internal RAM, so background pollers queue for a slot
+// instead of all handshaking at the same time.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+#[cfg(target_os = "espidf")]
+use {
+    super::error::NetworkError,
+    std::sync::{Condvar, Mutex},
+    esp_idf_hal::delay::FreeRtos,
+};
+
+pub const MAX_CONNECTIONS: u32 = 2;
+const BASE_BACKOFF_MS: u64 = 1000;
+const MAX_BACKOFF_MS: u64 = 30_000;
+
+static REQUESTS: AtomicU32 = AtomicU32::new(0);
+static FAILURES: AtomicU32 = AtomicU32::new(0);
+static BYTES_RECEIVED: AtomicU32 = AtomicU32::new(0);
+
+#[cfg(target_os = "espidf")]
+type Result<T> = core::result::Result<T, NetworkError>;
+
+/// Per-request settings; the defaults suit small JSON APIs
+#[derive(Debug, Clone)]
+pub struct RequestOptions {
+    pub timeout: Duration,
+    pub max_response_bytes: usize,
+    /// Total tries including the first
+    pub attempts: u32,
+    /// Response header to hand back with the body
+    pub capture_header: Option<&'static str>,
+}
+
+impl Default for RequestOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            max_response_bytes: 8 * 1024,
+            attempts: 1,
+            capture_header: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+    pub header: Option<String>,
+}
+
+/// Body read by `download_for`, which discards the data
+#[derive(Debug, Clone, Copy)]
+pub struct Download {
+    pub bytes: u64,
+    /// From the response headers to the last byte read
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone, Copy, Default, serde::Serialize)]
+pub struct HttpStats {
+    pub requests: u32,
+    pub failures: u32,
+    pub bytes_received: u32,
+}
+
+pub fn stats() -> HttpStats {
+    HttpStats {
+        requests: REQUESTS.load(Ordering::Relaxed),
+        failures: FAILURES.load(Ordering::Relaxed),
+        bytes_received: BYTES_RECEIVED.load(Ordering::Relaxed),
+    }
+}
+
+/// Server-side and throttling errors are worth another try; other 4xx are not
+pub fn is_retryable(status: u16) -> bool {
+    status == 408 || status == 429 || status >= 500
+}
+
+/// Exponential backoff for the wait after failed `attempt` (1-based), with up
+/// to ±25% jitter taken from `random` so devices don't retry in lockstep
+pub fn backoff_ms(attempt: u32, random: u32) -> u64 {
+    let base = BASE_BACKOFF_MS.saturating_mul(1 << attempt.saturating_sub(1).min(16)).min(MAX_BACKOFF_MS);
+    let span = base / 2;
+    base - base / 4 + (random as u64 % (span + 1))
+}
+
+/// Counting semaphore for connection slots
+#[cfg(target_os = "espidf")]
+struct Slots {
+    in_use: Mutex<u32>,
+    freed: Condvar,
+}
+
+#[cfg(target_os = "espidf")]
+static SLOTS: Slots = Slots { in_use: Mutex::new(0), freed: Condvar::new() };
+
+#[cfg(target_os = "espidf")]
+struct SlotGuard;
+
+#[cfg(target_os = "espidf")]
+impl SlotGuard {
+    fn acquire() -> Self {
+        let mut in_use = SLOTS.in_use.lock().unwrap_or_else(|e| e.into_inner());
+        while *in_use >= MAX_CONNECTIONS {
+            in_use = SLOTS.freed.wait(in_use).unwrap_or_else(|e| e.into_inner());
+        }
+        *in_use += 1;
+        SlotGuard
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl Drop for SlotGuard {
+    fn drop(&mut self) {
+        let mut in_use = SLOTS.in_use.lock().unwrap_or_else(|e| e.into_inner());
+        *in_use = in_use.saturating_sub(1);
+        SLOTS.freed.notify_one();
+    }
+}
+
+/// GET `url`; non-2xx responses are `NetworkError::Status`
+#[cfg(target_os = "espidf")]
+pub fn get(url: &str, options: &RequestOptions) -> Result<HttpResponse> {
+    with_retries(options, || request_once(url, None, options))
+}
+
+/// POST `body` to `url`; non-2xx responses are `NetworkError::Status`
+#[cfg(target_os = "espidf")]
+pub fn post(url: &str, content_type: &str, body: &[u8], options: &RequestOptions) -> Result<HttpResponse> {
+    with_retries(options, || request_once(url, Some((content_type, body)), options))
+}
+
+/// GET `url` and read (discarding) the body for at most `window`; a single
+/// attempt, `max_response_bytes` does not apply. Used for throughput tests.
+#[cfg(target_os = "espidf")]
+pub fn download_for(url: &str, options: &RequestOptions, window: Duration) -> Result<Download> {
+    use embedded_svc::http::client::Client;
+    use esp_idf_svc::http::client::{Configuration, EspHttpConnection};
+    use esp_idf_svc::io::Read;
+    use std::time::Instant;
+
+    REQUESTS.fetch_add(1, Ordering::Relaxed);
+    let result = (|| -> Result<Download> {
+        crate::network::net_stats::check_dns(url)?;
+        let _slot = SlotGuard::acquire();
+        let conn = EspHttpConnection::new(&Configuration {
+            timeout: Some(options.timeout),
+            crt_bundle_attach: Some(esp_idf_sys::esp_crt_bundle_attach),
+            buffer_size: Some(4096),
+            ..Default::default()
+        })?;
+        let mut client = Client::wrap(conn);
+        let mut response = client.get(url)?.submit()?;
+        if !(200..300).contains(&response.status()) {
+            return Err(NetworkError::Status(response.status()));
+        }
+
+        let started = Instant::now();
+        let mut bytes = 0u64;
+        let mut chunk = vec![0u8; 4096];
+        while started.elapsed() < window {
+            let n = response.read(&mut chunk)?;
+            if n == 0 { break; }
+            bytes += n as u64;
+        }
+        Ok(Download { bytes, elapsed: started.elapsed() })
+    })();
+    match &result {
+        Ok(download) => { BYTES_RECEIVED.fetch_add(download.bytes as u32, Ordering::Relaxed); }
+        Err(_) => { FAILURES.fetch_add(1, Ordering::Relaxed); }
+    }
+    result
+}
+
+#[cfg(target_os = "espidf")]
+fn with_retries(options: &RequestOptions, mut send: impl FnMut() -> Result<HttpResponse>) -> Result<HttpResponse> {
+    let attempts = options.attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        REQUESTS.fetch_add(1, Ordering::Relaxed);
+        let error = match send() {
+            Ok(response) if (200..300).contains(&response.status) => return Ok(response),
+            Ok(response) => NetworkError::Status(response.status),
+            Err(e) => e,
+        };
+        FAILURES.fetch_add(1, Ordering::Relaxed);
+        if attempt >= attempts || !error.is_transient() {
+            return Err(error);
+        }
+        let wait = backoff_ms(attempt, unsafe { esp_idf_sys::esp_random() });
+        log::debug!("HTTP: {} (attempt {}/{}), retrying in {}ms", error, attempt, attempts, wait);
+        FreeRtos::delay_ms(wait as u32);
+        attempt += 1;
+    }
+}
+
+#[cfg(target_os = "espidf")]
+fn request_once(url: &str, post: Option<(&str, &[u8])>, options: &RequestOptions) -> Result<HttpResponse> {
+    use embedded_svc::http::client::Client;
+    use embedded_svc::http::Headers;
+    use esp_idf_svc::http::client::{Configuration, EspHttpConnection};
+    use esp_idf_svc::io::{Read, Write};
+
+    crate::network::net_stats::check_dns(url)?;
+    let _slot = SlotGuard::acquire();
+    let conn = EspHttpConnection::new(&Configuration {
+        timeout: Some(options.timeout),
+        crt_bundle_attach: Some(esp_idf_sys::esp_crt_bundle_attach),
+        ..Default::default()
+    })?;
+    let mut client = Client::wrap(conn);
+    let mut response = match post {
+        Some((content_type, body)) => {
+            let len = body.len().to_string();
+            let headers = [("Content-Type", content_type), ("Content-Length", len.as_str())];
+            let mut request = client.post(url, &headers)?;
+            request.write_all(body)?;
+            request.flush()?;
+            request.submit()?
+        }
+        None => client.get(url)?.submit()?,
+    };
+    let status = response.status();
+    let header = options.capture_header.and_then(|name| response.header(name)).map(str::to_string);
+
+    let mut body = Vec::new();
+    let mut chunk = [0u8; 512];
+    loop {
+        let n = response.read(&mut chunk)?;
+        if n == 0 { break; }
+        if body.len() + n > options.max_response_bytes {
+            return Err(NetworkError::TooLarge(options.max_response_bytes));
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    BYTES_RECEIVED.fetch_add(body.len() as u32, Ordering::Relaxed);
+    Ok(HttpResponse { status, body, header })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_with_bounded_jitter() {
+        assert_eq!(backoff_ms(1, 0), 750);
+        assert_eq!(backoff_ms(1, 500), 1250);
+        assert_eq!(backoff_ms(1, 501), 750);
+        assert_eq!(backoff_ms(3, 0), 3000);
+        // Capped no matter how many attempts
+        assert!(backoff_ms(40, u32::MAX) <= MAX_BACKOFF_MS * 5 / 4);
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(is_retryable(503));
+        assert!(is_retryable(429));
+        assert!(!is_retryable(404));
+        assert!(!is_retryable(401));
+    }
+}
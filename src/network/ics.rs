@@ -0,0 +1,572 @@
+// iCalendar (RFC 5545) parsing for the Agenda screen: the VEVENTs of an ICS
+// feed turned into the next few upcoming occurrences. Kept free of device
+// APIs; the caller supplies "now" and the conversion of local wall-clock
+// times, which the device does with its configured POSIX timezone.
+//
+// Covered: folded lines, UTC / floating / TZID start times (TZID zones are
+// taken to be the dashboard's own timezone), all-day events, DTEND or
+// DURATION, cancelled events, EXDATE, moved occurrences (RECURRENCE-ID) and
+// RRULEs with FREQ=DAILY/WEEKLY/MONTHLY/YEARLY, INTERVAL, COUNT, UNTIL and a
+// weekly BYDAY. Other BY* rules only yield the first occurrence.
+
+use std::collections::HashMap;
+
+/// Bound on occurrences stepped through per recurring event
+const MAX_STEPS: u32 = 2000;
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// Wall-clock date and time, no timezone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Civil {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl Civil {
+    fn date(year: i32, month: u8, day: u8) -> Self {
+        Self { year, month, day, hour: 0, minute: 0, second: 0 }
+    }
+
+    /// Days since 1970-01-01
+    pub fn days(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day)
+    }
+
+    fn seconds_of_day(&self) -> i64 {
+        self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64
+    }
+
+    fn with_days(self, days: i64) -> Self {
+        let (year, month, day) = civil_from_days(days);
+        Self { year, month, day, ..self }
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date
+pub fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let y = year as i64 - (month <= 2) as i64;
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Inverse of `days_from_civil`
+pub fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    ((yoe + era * 400 + (month <= 2) as i64) as i32, month, day)
+}
+
+/// 0 = Monday .. 6 = Sunday
+pub fn weekday(days: i64) -> u8 {
+    (days + 3).rem_euclid(7) as u8
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+/// One upcoming occurrence
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Event {
+    /// Unix seconds
+    pub start: i64,
+    pub end: i64,
+    pub all_day: bool,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Time {
+    Utc(Civil),
+    Local(Civil),
+    Date(Civil),
+}
+
+impl Time {
+    fn civil(self) -> Civil {
+        match self {
+            Time::Utc(c) | Time::Local(c) | Time::Date(c) => c,
+        }
+    }
+
+    fn with_civil(self, civil: Civil) -> Self {
+        match self {
+            Time::Utc(_) => Time::Utc(civil),
+            Time::Local(_) => Time::Local(civil),
+            Time::Date(_) => Time::Date(civil),
+        }
+    }
+
+    fn epoch(self, local_to_epoch: &impl Fn(Civil) -> i64) -> i64 {
+        match self {
+            Time::Utc(c) => c.days() * SECS_PER_DAY + c.seconds_of_day(),
+            Time::Local(c) | Time::Date(c) => local_to_epoch(c),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Freq {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    freq: Freq,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<Time>,
+    /// Weekdays, 0 = Monday; weekly rules only
+    by_day: Vec<u8>,
+    /// A BY* part this parser does not expand
+    unsupported: bool,
+}
+
+#[derive(Default)]
+struct RawEvent {
+    uid: String,
+    start: Option<Time>,
+    end: Option<Time>,
+    duration: Option<i64>,
+    title: String,
+    cancelled: bool,
+    rule: Option<Rule>,
+    exdates: Vec<Time>,
+    recurrence_id: Option<Time>,
+}
+
+/// Undo RFC 5545 line folding (CRLF or LF followed by a space or tab)
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Split `NAME;PARAM=x:value` into the upper-cased name, the params and the value
+fn split_property(line: &str) -> Option<(String, &str, &str)> {
+    let mut quoted = false;
+    let colon = line.char_indices().find(|&(_, c)| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        c == ':' && !quoted
+    })?.0;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let (name, params) = head.split_once(';').unwrap_or((head, ""));
+    Some((name.to_ascii_uppercase(), params, value))
+}
+
+fn param<'a>(params: &'a str, name: &str) -> Option<&'a str> {
+    params.split(';')
+        .filter_map(|p| p.split_once('='))
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim_matches('"'))
+}
+
+fn digits(s: &str, range: core::ops::Range<usize>) -> Option<u32> {
+    let part = s.get(range)?;
+    part.bytes().all(|b| b.is_ascii_digit()).then(|| part.parse().ok())?
+}
+
+/// `20240501`, `20240501T090000` or `20240501T090000Z`
+fn parse_time(value: &str, params: &str) -> Option<Time> {
+    let value = value.trim();
+    let year = digits(value, 0..4)? as i32;
+    let month = digits(value, 4..6)? as u8;
+    let day = digits(value, 6..8)? as u8;
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+    let date = Civil::date(year, month, day);
+    if value.len() == 8 || param(params, "VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE")) {
+        return Some(Time::Date(date));
+    }
+    if value.as_bytes().get(8) != Some(&b'T') {
+        return None;
+    }
+    let civil = Civil {
+        hour: digits(value, 9..11)?.min(23) as u8,
+        minute: digits(value, 11..13)?.min(59) as u8,
+        second: digits(value, 13..15)?.min(59) as u8,
+        ..date
+    };
+    Some(if value.ends_with('Z') { Time::Utc(civil) } else { Time::Local(civil) })
+}
+
+/// `P1D`, `PT1H30M`, `P2W`; negative durations are ignored
+fn parse_duration(value: &str) -> Option<i64> {
+    let rest = value.trim().strip_prefix('+').unwrap_or(value.trim()).strip_prefix('P')?;
+    let mut total = 0i64;
+    let mut number = 0i64;
+    for c in rest.chars() {
+        let unit = match c {
+            '0'..='9' => {
+                number = number.checked_mul(10)?.checked_add(c as i64 - '0' as i64)?;
+                continue;
+            }
+            'T' => continue,
+            'W' => 7 * SECS_PER_DAY,
+            'D' => SECS_PER_DAY,
+            'H' => 3600,
+            'M' => 60,
+            'S' => 1,
+            _ => return None,
+        };
+        total = total.checked_add(number.checked_mul(unit)?)?;
+        number = 0;
+    }
+    Some(total)
+}
+
+fn parse_weekday(s: &str) -> Option<u8> {
+    ["MO", "TU", "WE", "TH", "FR", "SA", "SU"].iter().position(|d| d.eq_ignore_ascii_case(s)).map(|i| i as u8)
+}
+
+fn parse_rule(value: &str) -> Option<Rule> {
+    let mut rule = Rule { freq: Freq::Daily, interval: 1, count: None, until: None, by_day: Vec::new(), unsupported: false };
+    let mut freq = None;
+    for part in value.split(';') {
+        let Some((key, val)) = part.split_once('=') else { continue };
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => freq = match val.to_ascii_uppercase().as_str() {
+                "DAILY" => Some(Freq::Daily),
+                "WEEKLY" => Some(Freq::Weekly),
+                "MONTHLY" => Some(Freq::Monthly),
+                "YEARLY" => Some(Freq::Yearly),
+                _ => None,
+            },
+            "INTERVAL" => rule.interval = val.parse().ok().filter(|&i| i > 0)?,
+            "COUNT" => rule.count = Some(val.parse().ok()?),
+            "UNTIL" => rule.until = parse_time(val, ""),
+            "BYDAY" => match val.split(',').map(parse_weekday).collect::<Option<Vec<_>>>() {
+                Some(days) => rule.by_day = days,
+                // Ordinals like 2TU
+                None => rule.unsupported = true,
+            },
+            "WKST" => {}
+            _ => rule.unsupported = true,
+        }
+    }
+    rule.freq = freq?;
+    if !rule.by_day.is_empty() && rule.freq != Freq::Weekly {
+        rule.unsupported = true;
+    }
+    rule.by_day.sort_unstable();
+    rule.by_day.dedup();
+    Some(rule)
+}
+
+/// Unescape a TEXT value (`\,` `\;` `\n` `\\`) onto one line
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') | Some('N') => out.push(' '),
+                Some(other) => out.push(other),
+                None => {}
+            },
+            c => out.push(c),
+        }
+    }
+    out.trim().to_string()
+}
+
+fn parse_events(ics: &str) -> Vec<RawEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<RawEvent> = None;
+    // Depth of components nested in the VEVENT (VALARM), whose properties are skipped
+    let mut nested = 0u32;
+    for line in unfold(ics) {
+        let Some((name, params, value)) = split_property(&line) else { continue };
+        match (name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => current = Some(RawEvent::default()),
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => events.extend(current.take()),
+            (_, Some(_)) if nested > 0 => {}
+            ("UID", Some(event)) => event.uid = value.to_string(),
+            ("DTSTART", Some(event)) => event.start = parse_time(value, params),
+            ("DTEND", Some(event)) => event.end = parse_time(value, params),
+            ("DURATION", Some(event)) => event.duration = parse_duration(value),
+            ("SUMMARY", Some(event)) => event.title = unescape(value),
+            ("STATUS", Some(event)) => event.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            ("RRULE", Some(event)) => event.rule = parse_rule(value),
+            ("EXDATE", Some(event)) => event.exdates.extend(value.split(',').filter_map(|v| parse_time(v, params))),
+            ("RECURRENCE-ID", Some(event)) => event.recurrence_id = parse_time(value, params),
+            _ => {}
+        }
+    }
+    events
+}
+
+/// Start of each occurrence of `event`, in order, from the first that may still
+/// be running at `now`
+fn occurrences(start: Time, rule: Option<&Rule>, now_days: i64, mut f: impl FnMut(Time) -> bool) {
+    let Some(rule) = rule.filter(|r| !r.unsupported) else {
+        f(start);
+        return;
+    };
+    let first = start.civil();
+    let first_days = first.days();
+    // A date-only UNTIL includes that whole day
+    let until = rule.until.map(|u| match u {
+        Time::Date(c) => Civil { hour: 23, minute: 59, second: 59, ..c },
+        other => other.civil(),
+    });
+    let mut emitted = 0u32;
+    let mut emit = |civil: Civil, index: u32| -> bool {
+        if rule.count.is_some_and(|count| index >= count) || until.is_some_and(|u| civil > u) {
+            return false;
+        }
+        emitted += 1;
+        emitted <= MAX_STEPS && f(start.with_civil(civil))
+    };
+
+    match rule.freq {
+        Freq::Daily | Freq::Weekly => {
+            let period = if rule.freq == Freq::Daily { 1 } else { 7 } * rule.interval as i64;
+            // Weekly rules without BYDAY repeat on the start's weekday
+            let days: Vec<i64> = match rule.freq {
+                Freq::Weekly if !rule.by_day.is_empty() => {
+                    let week_start = first_days - weekday(first_days) as i64;
+                    rule.by_day.iter().map(|&d| week_start + d as i64 - first_days).collect()
+                }
+                _ => vec![0],
+            };
+            // Jump over whole periods that ended before now (a day of slack for
+            // events spanning midnight)
+            let skip = ((now_days - first_days - 1).max(0) / period - 1).max(0);
+            let mut index = 0u32;
+            if skip > 0 {
+                // Occurrences in the skipped periods still count towards COUNT
+                let per_period = days.iter().filter(|&&d| d >= 0).count() as i64;
+                index = (per_period + (skip - 1) * days.len() as i64).min(u32::MAX as i64) as u32;
+            }
+            for n in skip.. {
+                for &offset in &days {
+                    if n == 0 && offset < 0 {
+                        continue;
+                    }
+                    let civil = first.with_days(first_days + n * period + offset);
+                    if !emit(civil, index) {
+                        return;
+                    }
+                    index += 1;
+                }
+            }
+        }
+        Freq::Monthly | Freq::Yearly => {
+            let months = if rule.freq == Freq::Monthly { 1 } else { 12 } * rule.interval as i64;
+            let mut index = 0;
+            for n in 0.. {
+                let month0 = first.month as i64 - 1 + n * months;
+                let year = first.year as i64 + month0.div_euclid(12);
+                if year > 9999 {
+                    return;
+                }
+                let (year, month) = (year as i32, month0.rem_euclid(12) as u8 + 1);
+                // The 31st only recurs in months that have one
+                if first.day > days_in_month(year, month) {
+                    continue;
+                }
+                if !emit(Civil { year, month, ..first }, index) {
+                    return;
+                }
+                index += 1;
+            }
+        }
+    }
+}
+
+/// The next `limit` occurrences in `ics` that have not ended at `now` (Unix
+/// seconds), soonest first. `local_to_epoch` converts floating and TZID times.
+pub fn upcoming(ics: &str, now: i64, limit: usize, local_to_epoch: impl Fn(Civil) -> i64) -> Vec<Event> {
+    let raw = parse_events(ics);
+    let now_days = now.div_euclid(SECS_PER_DAY);
+
+    // Occurrences moved or cancelled individually, by series UID
+    let mut overridden: HashMap<&str, Vec<i64>> = HashMap::new();
+    for event in &raw {
+        if let Some(id) = event.recurrence_id {
+            overridden.entry(event.uid.as_str()).or_default().push(id.epoch(&local_to_epoch));
+        }
+    }
+
+    let mut events = Vec::new();
+    for event in raw.iter().filter(|e| !e.cancelled) {
+        let Some(start) = event.start else { continue };
+        let all_day = matches!(start, Time::Date(_));
+        let length = match (event.end, event.duration) {
+            (Some(end), _) => (end.epoch(&local_to_epoch) - start.epoch(&local_to_epoch)).max(0),
+            (None, Some(duration)) => duration,
+            (None, None) if all_day => SECS_PER_DAY,
+            (None, None) => 0,
+        };
+        let excluded: Vec<i64> = event.exdates.iter().map(|t| t.epoch(&local_to_epoch))
+            .chain(overridden.get(event.uid.as_str()).into_iter().flatten().copied().filter(|_| event.recurrence_id.is_none()))
+            .collect();
+        let rule = event.rule.as_ref().filter(|_| event.recurrence_id.is_none());
+        let mut found = 0;
+        occurrences(start, rule, now_days, |occurrence| {
+            let start = occurrence.epoch(&local_to_epoch);
+            let end = start.saturating_add(length);
+            // Zero-length events count until their start has passed
+            if (end > now || start >= now) && !excluded.contains(&start) {
+                events.push(Event { start, end, all_day, title: event.title.clone() });
+                found += 1;
+            }
+            found < limit
+        });
+    }
+    events.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.title.cmp(&b.title)));
+    events.truncate(limit);
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// UTC+2, like CEST
+    fn cest(civil: Civil) -> i64 {
+        civil.days() * SECS_PER_DAY + civil.seconds_of_day() - 2 * 3600
+    }
+
+    fn epoch(y: i32, mo: u8, d: u8, h: u8, mi: u8) -> i64 {
+        cest(Civil { year: y, month: mo, day: d, hour: h, minute: mi, second: 0 })
+    }
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup\r\n\
+DTSTART;TZID=Europe/Berlin:20240506T093000\r\n\
+DURATION:PT15M\r\n\
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR\r\n\
+EXDATE;TZID=Europe/Berlin:20240515T093000\r\n\
+SUMMARY:Stand-up\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup\r\n\
+RECURRENCE-ID;TZID=Europe/Berlin:20240513T093000\r\n\
+DTSTART;TZID=Europe/Berlin:20240513T110000\r\n\
+DTEND;TZID=Europe/Berlin:20240513T111500\r\n\
+SUMMARY:Stand-up (moved)\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:review\r\n\
+DTSTART:20240514T120000Z\r\n\
+DTEND:20240514T130000Z\r\n\
+SUMMARY:Design review\\, Q2 \r\n\
+\x20roadmap\r\n\
+BEGIN:VALARM\r\n\
+TRIGGER:-PT10M\r\n\
+SUMMARY:Alarm text\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:holiday\r\n\
+DTSTART;VALUE=DATE:20240513\r\n\
+SUMMARY:Team day\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:gone\r\n\
+DTSTART:20240514T080000Z\r\n\
+STATUS:CANCELLED\r\n\
+SUMMARY:Cancelled\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:past\r\n\
+DTSTART:20240101T080000Z\r\n\
+SUMMARY:Long gone\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_civil_days_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2024, 2, 29), 19_782);
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(weekday(0), 3, "1970-01-01 was a Thursday");
+        assert_eq!(weekday(days_from_civil(2024, 5, 13)), 0);
+    }
+
+    #[test]
+    fn test_upcoming_events() {
+        // Monday 2024-05-13 10:00 local
+        let now = epoch(2024, 5, 13, 10, 0);
+        let events = upcoming(CALENDAR, now, 5, cest);
+        let shown: Vec<(i64, &str)> = events.iter().map(|e| (e.start, e.title.as_str())).collect();
+        assert_eq!(shown, [
+            (epoch(2024, 5, 13, 0, 0), "Team day"),
+            (epoch(2024, 5, 13, 11, 0), "Stand-up (moved)"),
+            (epoch(2024, 5, 14, 14, 0), "Design review, Q2 roadmap"),
+            // Wednesday's stand-up is an EXDATE
+            (epoch(2024, 5, 17, 9, 30), "Stand-up"),
+            (epoch(2024, 5, 20, 9, 30), "Stand-up"),
+        ]);
+        assert!(events[0].all_day);
+        assert_eq!(events[0].end - events[0].start, SECS_PER_DAY);
+        assert_eq!(events[3].end - events[3].start, 15 * 60);
+    }
+
+    #[test]
+    fn test_recurrence_limits() {
+        let ics = "BEGIN:VEVENT\nUID:a\nDTSTART:20200101T090000Z\nRRULE:FREQ=DAILY;INTERVAL=2;COUNT=3\nSUMMARY:Short run\nEND:VEVENT\n\
+BEGIN:VEVENT\nUID:b\nDTSTART:20200131T090000Z\nRRULE:FREQ=MONTHLY;UNTIL=20200601T000000Z\nSUMMARY:Month end\nEND:VEVENT\n\
+BEGIN:VEVENT\nUID:c\nDTSTART:20100104T070000Z\nRRULE:FREQ=WEEKLY;BYDAY=MO,TH;COUNT=1000\nSUMMARY:Gym\nEND:VEVENT\n";
+        let utc = |c: Civil| c.days() * SECS_PER_DAY + c.seconds_of_day();
+        let at = |y, mo, d| days_from_civil(y, mo, d) * SECS_PER_DAY;
+
+        let titles = |now| upcoming(ics, now, 10, utc).into_iter().map(|e| (e.start, e.title)).collect::<Vec<_>>();
+        // Jan 1, 3, 5 only
+        assert_eq!(titles(at(2020, 1, 4))[0], (at(2020, 1, 5) + 9 * 3600, "Short run".to_string()));
+        assert!(!titles(at(2020, 1, 6)).iter().any(|(_, t)| t == "Short run"));
+        // 31st of Jan, Mar and May; none after UNTIL
+        let month_end: Vec<i64> = titles(at(2020, 2, 1)).into_iter().filter(|(_, t)| t == "Month end").map(|(s, _)| s).collect();
+        assert_eq!(month_end, [at(2020, 3, 31) + 9 * 3600, at(2020, 5, 31) + 9 * 3600]);
+        // Twice a week from 2010: the 1000th session is in 2019, none in 2024
+        assert!(titles(at(2019, 1, 1)).iter().any(|(_, t)| t == "Gym"));
+        assert!(!titles(at(2024, 1, 1)).iter().any(|(_, t)| t == "Gym"));
+    }
+
+    #[test]
+    fn test_malformed_input_is_skipped() {
+        let ics = "BEGIN:VEVENT\nDTSTART:2024\nSUMMARY:bad date\nEND:VEVENT\nBEGIN:VEVENT\nDTSTART:20240230T100000Z\nEND:VEVENT\n\
+BEGIN:VEVENT\nDTSTART:20240301T100000Z\nRRULE:FREQ=SECONDLY\nSUMMARY:odd rule\nEND:VEVENT\ngarbage\nEND:VEVENT\n";
+        let events = upcoming(ics, 0, 5, |c| c.days() * SECS_PER_DAY);
+        assert_eq!(events.len(), 1, "an unknown FREQ keeps the single occurrence");
+        assert_eq!(events[0].title, "odd rule");
+    }
+}
@@ -0,0 +1,98 @@
+// IPv6 on the station interface. lwIP is built with IPv6 and SLAAC
+// (sdkconfig.defaults); a link-local address is created on every association,
+// after which router advertisements add global addresses. The HTTP server,
+// telnet and mDNS listen on dual-stack sockets, so the device answers on
+// whichever family the client uses.
+
+use std::net::{IpAddr, Ipv6Addr};
+
+/// `ip` as it goes in a URL authority: IPv6 literals are bracketed
+pub fn url_host(ip: &IpAddr) -> String {
+    match ip {
+        IpAddr::V4(v4) => v4.to_string(),
+        IpAddr::V6(v6) => format!("[{}]", v6),
+    }
+}
+
+/// fe80::/10, only reachable on the local link
+pub fn is_link_local(addr: &Ipv6Addr) -> bool {
+    addr.segments()[0] & 0xffc0 == 0xfe80
+}
+
+/// Address worth showing: a global one if the network gave us one, else link-local
+pub fn preferred(addrs: &[Ipv6Addr]) -> Option<Ipv6Addr> {
+    addrs.iter().find(|a| !is_link_local(a)).or(addrs.first()).copied()
+}
+
+/// lwIP keeps addresses as four u32 words in network byte order
+pub fn from_lwip_words(words: [u32; 4]) -> Ipv6Addr {
+    let mut bytes = [0u8; 16];
+    for (chunk, word) in bytes.chunks_exact_mut(4).zip(words) {
+        chunk.copy_from_slice(&word.to_ne_bytes());
+    }
+    Ipv6Addr::from(bytes)
+}
+
+#[cfg(target_os = "espidf")]
+mod device {
+    use super::*;
+    use esp_idf_sys::*;
+
+    fn sta_netif() -> *mut esp_netif_t {
+        unsafe { esp_netif_get_handle_from_ifkey(c"WIFI_STA_DEF".as_ptr()) }
+    }
+
+    /// Start IPv6 on the station; call on every association (WIFI_EVENT_STA_CONNECTED)
+    pub fn enable_link_local() {
+        let netif = sta_netif();
+        if netif.is_null() {
+            return;
+        }
+        let result = unsafe { esp_netif_create_ip6_linklocal(netif) };
+        if result != ESP_OK {
+            log::warn!("IPv6 link-local setup failed: {}", result);
+        }
+    }
+
+    /// Current station IPv6 addresses, global ones first
+    pub fn addresses() -> Vec<Ipv6Addr> {
+        let netif = sta_netif();
+        if netif.is_null() {
+            return Vec::new();
+        }
+        let mut raw: [esp_ip6_addr_t; LWIP_IPV6_NUM_ADDRESSES as usize] = unsafe { core::mem::zeroed() };
+        let count = unsafe { esp_netif_get_all_ip6(netif, raw.as_mut_ptr()) }.max(0) as usize;
+        let mut addrs: Vec<Ipv6Addr> = raw[..count.min(raw.len())].iter()
+            .map(|a| from_lwip_words(a.addr))
+            .collect();
+        addrs.sort_by_key(is_link_local);
+        addrs
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub use device::{addresses, enable_link_local};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_host_and_preference() {
+        let v6: Ipv6Addr = "2001:db8::5".parse().unwrap();
+        let local: Ipv6Addr = "fe80::1234".parse().unwrap();
+        assert_eq!(url_host(&IpAddr::V6(v6)), "[2001:db8::5]");
+        assert_eq!(url_host(&"10.0.0.5".parse().unwrap()), "10.0.0.5");
+
+        assert!(is_link_local(&local) && !is_link_local(&v6));
+        assert_eq!(preferred(&[local, v6]), Some(v6));
+        assert_eq!(preferred(&[local]), Some(local));
+        assert_eq!(preferred(&[]), None);
+
+        let words = {
+            let octets = v6.octets();
+            core::array::from_fn(|i| u32::from_ne_bytes(octets[i * 4..i * 4 + 4].try_into().unwrap()))
+        };
+        assert_eq!(from_lwip_words(words), v6);
+    }
+}
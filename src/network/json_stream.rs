@@ -0,0 +1,115 @@
+// Streaming JSON responses. serde_json::to_string builds the whole body on
+// the heap before the first byte goes out, and a day of history or a few
+// hundred log lines is a large, short-lived block that fragments DRAM.
+// ChunkedWriter serializes into one fixed CHUNK_SIZE buffer and hands it to
+// the response each time it fills, so a body of any length costs one chunk.
+
+use serde::Serialize;
+use std::io;
+
+/// Bytes buffered before a chunk is sent
+pub const CHUNK_SIZE: usize = 1024;
+
+/// `io::Write` that passes data on to `sink` in CHUNK_SIZE pieces
+pub struct ChunkedWriter<F: FnMut(&[u8]) -> io::Result<()>> {
+    buf: Vec<u8>,
+    sink: F,
+    sent: usize,
+}
+
+impl<F: FnMut(&[u8]) -> io::Result<()>> ChunkedWriter<F> {
+    pub fn new(sink: F) -> Self {
+        Self { buf: Vec::with_capacity(CHUNK_SIZE), sink, sent: 0 }
+    }
+
+    /// Send what is still buffered; total bytes sent
+    pub fn finish(mut self) -> io::Result<usize> {
+        self.send_buffered()?;
+        Ok(self.sent)
+    }
+
+    fn send_buffered(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        (self.sink)(&self.buf)?;
+        self.sent += self.buf.len();
+        self.buf.clear();
+        Ok(())
+    }
+}
+
+impl<F: FnMut(&[u8]) -> io::Result<()>> io::Write for ChunkedWriter<F> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let n = data.len().min(CHUNK_SIZE - self.buf.len());
+        self.buf.extend_from_slice(&data[..n]);
+        if self.buf.len() == CHUNK_SIZE {
+            self.send_buffered()?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send_buffered()
+    }
+}
+
+/// Serialize `value` straight into `sink`; total bytes sent
+pub fn write_json<T, F>(value: &T, sink: F) -> io::Result<usize>
+where
+    T: Serialize + ?Sized,
+    F: FnMut(&[u8]) -> io::Result<()>,
+{
+    let mut writer = ChunkedWriter::new(sink);
+    serde_json::to_writer(&mut writer, value)?;
+    writer.finish()
+}
+
+/// Serialize `value` into an HTTP response body
+#[cfg(target_os = "espidf")]
+pub fn write_response<W, T>(response: &mut W, value: &T) -> io::Result<usize>
+where
+    W: esp_idf_svc::io::Write,
+    T: Serialize + ?Sized,
+{
+    write_json(value, |chunk| {
+        response
+            .write_all(chunk)
+            .map_err(|e| io::Error::other(format!("response write failed: {:?}", e)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_large_body_goes_out_in_chunks() {
+        let data: Vec<u32> = (0..1000).collect();
+        let mut chunks = Vec::new();
+        let sent = write_json(&data, |chunk| {
+            chunks.push(chunk.to_vec());
+            Ok(())
+        })
+        .unwrap();
+
+        let body: Vec<u8> = chunks.concat();
+        assert_eq!(sent, body.len());
+        assert_eq!(body, serde_json::to_vec(&data).unwrap());
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.len() <= CHUNK_SIZE));
+        assert!(chunks[..chunks.len() - 1].iter().all(|c| c.len() == CHUNK_SIZE));
+    }
+
+    #[test]
+    fn test_sink_error_stops_serializing() {
+        let data: Vec<u32> = (0..1000).collect();
+        let mut calls = 0;
+        let result = write_json(&data, |_| {
+            calls += 1;
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "client gone"))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
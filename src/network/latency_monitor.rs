@@ -0,0 +1,280 @@
+// Round-trip latency to the gateway and to a reference host on the internet.
+// A thread pinned to Core 1 sends a short burst of ICMP echoes (lwIP raw ping)
+// to both every PING_INTERVAL_SECS, or as set by the `latency` entry of the
+// sample schedule. A slow or lossy gateway points at the WiFi link; a healthy
+// gateway with a slow reference host points at the uplink.
+
+use crate::ring_buffer::{Pod, RingBuffer};
+#[cfg(target_os = "espidf")]
+use {
+    crate::config::{schedule::DataSource, Config},
+    anyhow::{anyhow, Result},
+    core::sync::atomic::{AtomicBool, Ordering},
+    esp_idf_hal::delay::FreeRtos,
+    std::net::Ipv4Addr,
+    std::sync::{Arc, Mutex},
+    std::time::Duration,
+};
+
+/// Default round interval
+pub const PING_INTERVAL_SECS: u32 = 30;
+pub const PINGS_PER_ROUND: u32 = 3;
+/// Rounds kept for the sparkline and the recent loss figure (16 minutes)
+pub const HISTORY_LEN: usize = 32;
+/// Upper bounds (ms) of the RTT histogram buckets; +Inf is the reply count
+pub const BUCKETS_MS: [u32; 8] = [5, 10, 25, 50, 100, 250, 500, 1000];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Round {
+    sent: u32,
+    received: u32,
+    /// 0 when every ping was lost
+    avg_rtt_ms: u16,
+}
+
+// SAFETY: plain integers
+unsafe impl Pod for Round {}
+
+/// RTT histogram and loss for one ping target
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyStats {
+    pub sent: u32,
+    pub received: u32,
+    /// Cumulative: replies with an RTT at or below each BUCKETS_MS bound
+    pub buckets: [u32; BUCKETS_MS.len()],
+    pub rtt_sum_ms: u64,
+    pub last_rtt_ms: Option<u32>,
+    history: RingBuffer<Round>,
+}
+
+impl LatencyStats {
+    pub const fn new() -> Self {
+        Self {
+            sent: 0,
+            received: 0,
+            buckets: [0; BUCKETS_MS.len()],
+            rtt_sum_ms: 0,
+            last_rtt_ms: None,
+            history: RingBuffer::new(HISTORY_LEN),
+        }
+    }
+
+    /// Fold one burst of pings: `sent` echoes, with the RTT of each reply
+    pub fn record_round(&mut self, sent: u32, rtts_ms: &[u32]) {
+        let received = (rtts_ms.len() as u32).min(sent);
+        self.sent += sent;
+        self.received += received;
+        for &rtt in rtts_ms {
+            self.rtt_sum_ms += rtt as u64;
+            for (count, &bound) in self.buckets.iter_mut().zip(BUCKETS_MS.iter()) {
+                if rtt <= bound {
+                    *count += 1;
+                }
+            }
+        }
+
+        let avg_rtt_ms = (!rtts_ms.is_empty())
+            .then(|| (rtts_ms.iter().map(|&r| r as u64).sum::<u64>() / rtts_ms.len() as u64).min(u16::MAX as u64) as u16);
+        if let Some(avg) = avg_rtt_ms {
+            self.last_rtt_ms = Some(avg as u32);
+        }
+        // A real average is at least 1 ms so 0 can stand for "all lost"
+        let avg_rtt_ms = avg_rtt_ms.map_or(0, |avg| avg.max(1));
+        self.history.push(Round { sent, received, avg_rtt_ms });
+    }
+
+    /// Packet loss over the rounds in the history window
+    pub fn recent_loss_percent(&self) -> f32 {
+        let (sent, received) = self.history.iter().fold((0, 0), |(s, r), round| (s + round.sent, r + round.received));
+        if sent == 0 {
+            0.0
+        } else {
+            (sent - received) as f32 * 100.0 / sent as f32
+        }
+    }
+
+    /// Average RTT per round, oldest first; None where every ping was lost
+    pub fn sparkline(&self) -> Vec<Option<u16>> {
+        self.history.iter().map(|round| (round.avg_rtt_ms > 0).then_some(round.avg_rtt_ms)).collect()
+    }
+}
+
+impl Default for LatencyStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencySnapshot {
+    pub gateway: LatencyStats,
+    /// Configured reference host; empty when disabled
+    pub host_name: String,
+    pub host: LatencyStats,
+}
+
+#[cfg(target_os = "espidf")]
+static MONITOR: Mutex<LatencySnapshot> = Mutex::new(LatencySnapshot {
+    gateway: LatencyStats::new(),
+    host_name: String::new(),
+    host: LatencyStats::new(),
+});
+
+#[cfg(target_os = "espidf")]
+pub fn snapshot() -> LatencySnapshot {
+    MONITOR.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Start the ping thread on Core 1 (idempotent). The reference host is re-read
+/// from `config` every round, so changing `latency_host` needs no restart.
+#[cfg(target_os = "espidf")]
+pub fn start(config: Arc<Mutex<Config>>) {
+    use esp_idf_hal::{cpu::Core, task::thread::ThreadSpawnConfiguration};
+
+    static STARTED: AtomicBool = AtomicBool::new(false);
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let pinned = ThreadSpawnConfiguration {
+        name: Some(b"latency\0"),
+        stack_size: 4096,
+        pin_to_core: Some(Core::Core1),
+        ..Default::default()
+    };
+    if let Err(e) = pinned.set() {
+        log::warn!("Latency monitor: could not pin to Core 1: {:?}", e);
+    }
+    let spawned = std::thread::Builder::new()
+        .stack_size(4096)
+        .spawn(move || {
+            // Let DHCP settle before the first round
+            FreeRtos::delay_ms(10_000);
+            loop {
+                let (host, interval) = config.lock()
+                    .map(|c| (c.latency_host.clone(), c.sample_schedule.interval(DataSource::Latency)))
+                    .unwrap_or_default();
+                // Disabled in the sample schedule: check again a round later
+                let Some(interval) = interval else {
+                    FreeRtos::delay_ms(PING_INTERVAL_SECS * 1000);
+                    continue;
+                };
+                run_round(host.trim());
+                FreeRtos::delay_ms(interval.as_millis() as u32);
+            }
+        });
+    // Later threads must not inherit the pinning
+    let _ = ThreadSpawnConfiguration::default().set();
+    if let Err(e) = spawned {
+        log::warn!("Failed to start latency monitor: {:?}", e);
+    }
+}
+
+#[cfg(target_os = "espidf")]
+fn run_round(host: &str) {
+    let Some(gateway) = gateway_addr() else { return }; // not connected
+
+    let gateway_rtts = ping(gateway).unwrap_or_else(|e| {
+        log::warn!("Latency monitor: ping {} failed: {}", gateway, e);
+        Vec::new()
+    });
+    let host_rtts = (!host.is_empty()).then(|| {
+        resolve(host).and_then(ping).unwrap_or_else(|e| {
+            log::debug!("Latency monitor: {}: {}", host, e);
+            Vec::new()
+        })
+    });
+
+    let mut monitor = MONITOR.lock().unwrap_or_else(|e| e.into_inner());
+    monitor.gateway.record_round(PINGS_PER_ROUND, &gateway_rtts);
+    if monitor.host_name != host {
+        monitor.host_name = host.to_string();
+        monitor.host = LatencyStats::new();
+    }
+    if let Some(rtts) = host_rtts {
+        monitor.host.record_round(PINGS_PER_ROUND, &rtts);
+    }
+}
+
+/// RTTs (ms) of the replies to one burst
+#[cfg(target_os = "espidf")]
+fn ping(addr: Ipv4Addr) -> Result<Vec<u32>> {
+    use esp_idf_svc::ping::{Configuration, EspPing, Reply, Summary};
+
+    let conf = Configuration {
+        count: PINGS_PER_ROUND,
+        interval: Duration::from_millis(200),
+        timeout: Duration::from_secs(1),
+        ..Default::default()
+    };
+    let mut rtts = Vec::with_capacity(PINGS_PER_ROUND as usize);
+    let mut on_reply = |_summary: &Summary, reply: &Reply| {
+        if let Reply::Success(info) = reply {
+            rtts.push(info.elapsed_time.as_millis() as u32);
+        }
+    };
+    EspPing::default().ping_details(addr, &conf, &mut on_reply)?;
+    Ok(rtts)
+}
+
+#[cfg(target_os = "espidf")]
+fn resolve(host: &str) -> Result<Ipv4Addr> {
+    use std::net::{SocketAddr, ToSocketAddrs};
+
+    if let Ok(addr) = host.parse() {
+        return Ok(addr);
+    }
+    (host, 0)
+        .to_socket_addrs()?
+        .find_map(|addr| match addr {
+            SocketAddr::V4(v4) => Some(*v4.ip()),
+            SocketAddr::V6(_) => None,
+        })
+        .ok_or_else(|| anyhow!("no IPv4 address"))
+}
+
+/// Station gateway, if the interface has an address
+#[cfg(target_os = "espidf")]
+fn gateway_addr() -> Option<Ipv4Addr> {
+    use esp_idf_sys::*;
+
+    unsafe {
+        let netif = esp_netif_get_handle_from_ifkey(b"WIFI_STA_DEF\0".as_ptr() as *const core::ffi::c_char);
+        if netif.is_null() {
+            return None;
+        }
+        let mut ip_info = esp_netif_ip_info_t::default();
+        if esp_netif_get_ip_info(netif, &mut ip_info) != ESP_OK || ip_info.ip.addr == 0 || ip_info.gw.addr == 0 {
+            return None;
+        }
+        // lwIP keeps addresses in network byte order
+        Some(Ipv4Addr::from(ip_info.gw.addr.to_le_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rounds_histogram_and_loss() {
+        let mut stats = LatencyStats::new();
+        stats.record_round(3, &[4, 12, 30]);
+        stats.record_round(3, &[]);
+        stats.record_round(3, &[2000]);
+
+        assert_eq!((stats.sent, stats.received, stats.rtt_sum_ms), (9, 4, 2046));
+        assert_eq!(stats.buckets, [1, 1, 2, 3, 3, 3, 3, 3]);
+        assert_eq!(stats.last_rtt_ms, Some(2000));
+        assert_eq!(stats.sparkline(), vec![Some(15), None, Some(2000)]);
+        assert!((stats.recent_loss_percent() - 55.555).abs() < 0.01);
+
+        for _ in 0..HISTORY_LEN {
+            stats.record_round(3, &[8, 8, 8]);
+        }
+        // Old losses age out of the window; the counters keep them
+        assert_eq!(stats.recent_loss_percent(), 0.0);
+        assert_eq!(stats.sparkline().len(), HISTORY_LEN);
+        assert_eq!(stats.sent - stats.received, 5);
+    }
+}
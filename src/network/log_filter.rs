@@ -0,0 +1,134 @@
+// Server-side log filtering. Telnet clients (`filter network::wifi debug`) and
+// /api/logs (`?level=warn&module=ota`) pick a minimum level and optionally a
+// module, so constrained clients receive only the lines they asked for rather
+// than the whole stream. Modules are paths without the crate name, as the
+// logger records them (`network::wifi`); a filter matches any run of whole
+// path segments, so `ota` matches `ota::manager` but not `ota_progress`.
+
+use log::{Level, LevelFilter};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    pub level: LevelFilter,
+    pub module: Option<String>,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self { level: LevelFilter::Trace, module: None }
+    }
+}
+
+impl LogFilter {
+    /// From the `level` and `module` query parameters; both optional
+    pub fn from_query(query: &str) -> Result<Self, String> {
+        let mut filter = Self::default();
+        for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+            match key {
+                "level" => filter.level = parse_level(value)?,
+                "module" if !value.is_empty() => filter.module = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Arguments of the telnet `filter` command: `[module] [level]`, or `off`
+    pub fn from_command(args: &str) -> Result<Self, String> {
+        let words: Vec<&str> = args.split_whitespace().collect();
+        match words.as_slice() {
+            [] | ["off"] => Ok(Self::default()),
+            [word] => match parse_level(word) {
+                Ok(level) => Ok(Self { level, module: None }),
+                Err(_) => Ok(Self { level: LevelFilter::Trace, module: Some(word.to_string()) }),
+            },
+            [module, level] => Ok(Self { level: parse_level(level)?, module: Some(module.to_string()) }),
+            _ => Err("usage: filter [module] [level] | filter off".to_string()),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        *self != Self::default()
+    }
+
+    /// Lines without a module (panic and shutdown notices) pass any module filter
+    pub fn matches(&self, level: Level, module: Option<&str>) -> bool {
+        level <= self.level
+            && match (&self.module, module) {
+                (Some(wanted), Some(path)) => module_matches(wanted, path),
+                _ => true,
+            }
+    }
+
+    /// For a stored entry, whose level is the logger's padded label ("WARN ")
+    pub fn matches_entry(&self, level: &str, module: Option<&str>) -> bool {
+        Level::from_str(level.trim()).map_or(true, |level| self.matches(level, module))
+    }
+}
+
+impl core::fmt::Display for LogFilter {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.module {
+            Some(module) => write!(f, "{} {}", module, self.level.as_str().to_ascii_lowercase()),
+            None => f.write_str(&self.level.as_str().to_ascii_lowercase()),
+        }
+    }
+}
+
+fn parse_level(text: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(text).map_err(|_| format!("Unknown log level '{}' (off, error, warn, info, debug, trace)", text))
+}
+
+fn module_matches(wanted: &str, path: &str) -> bool {
+    let wanted: Vec<&str> = wanted.split("::").collect();
+    let path: Vec<&str> = path.split("::").collect();
+    path.windows(wanted.len()).any(|segments| segments == wanted.as_slice())
+}
+
+/// Module path as the logger records it: the crate name dropped for our own modules
+pub fn module_path(full: &str, crate_name: &str) -> String {
+    full.strip_prefix(crate_name)
+        .and_then(|rest| rest.strip_prefix("::"))
+        .unwrap_or(full)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_and_command() {
+        let filter = LogFilter::from_query("count=50&level=warn&module=ota").unwrap();
+        assert_eq!(filter, LogFilter { level: LevelFilter::Warn, module: Some("ota".into()) });
+        assert!(LogFilter::from_query("level=loud").is_err());
+        assert!(!LogFilter::from_query("count=50").unwrap().is_active());
+
+        let filter = LogFilter::from_command(" network::wifi debug").unwrap();
+        assert_eq!(filter.to_string(), "network::wifi debug");
+        assert_eq!(LogFilter::from_command("error").unwrap().module, None);
+        assert_eq!(LogFilter::from_command("ota").unwrap().level, LevelFilter::Trace);
+        assert_eq!(LogFilter::from_command("off").unwrap(), LogFilter::default());
+        assert!(LogFilter::from_command("ota warn extra").is_err());
+    }
+
+    #[test]
+    fn test_matching() {
+        let filter = LogFilter::from_command("network::wifi info").unwrap();
+        assert!(filter.matches(Level::Warn, Some("network::wifi")));
+        assert!(filter.matches(Level::Info, Some("network::wifi::scan")));
+        assert!(!filter.matches(Level::Debug, Some("network::wifi")));
+        assert!(!filter.matches(Level::Info, Some("network::wifi_stats")));
+        assert!(filter.matches(Level::Error, None));
+
+        let ota = LogFilter::from_command("ota").unwrap();
+        assert!(ota.matches(Level::Trace, Some("ota::manager")));
+        assert!(!ota.matches(Level::Info, Some("network::ota_progress_stream")));
+        assert!(ota.matches_entry("WARN ", Some("ota")));
+        assert!(!LogFilter::from_command("error").unwrap().matches_entry("WARN ", Some("ota")));
+
+        assert_eq!(module_path("esp32_dashboard::network::wifi", "esp32_dashboard"), "network::wifi");
+        assert_eq!(module_path("esp_idf_svc::wifi", "esp32_dashboard"), "esp_idf_svc::wifi");
+    }
+}
@@ -1,5 +1,6 @@
 use std::sync::{Arc, Mutex, OnceLock};
 use std::collections::VecDeque;
+use super::log_filter::LogFilter;
 
 // Keep memory use bounded. Target ~2K entries by default; adjust if PSRAM abundant.
 const MAX_LOG_LINES: usize = 2000;
@@ -49,6 +50,22 @@ impl LogStreamer {
             .cloned()
             .collect()
     }
+
+    /// The last `count` entries that pass `filter`
+    pub fn get_recent_filtered(&self, count: usize, filter: &LogFilter) -> Vec<LogEntry> {
+        let buffer = match self.buffer.lock() {
+            Ok(b) => b,
+            Err(_) => return Vec::new(),
+        };
+        let mut logs: Vec<LogEntry> = buffer.iter()
+            .rev()
+            .filter(|entry| filter.matches_entry(&entry.level, entry.module.as_deref()))
+            .take(count)
+            .cloned()
+            .collect();
+        logs.reverse();
+        logs
+    }
 }
 
 static LOG_STREAMER: OnceLock<Arc<LogStreamer>> = OnceLock::new();
@@ -0,0 +1,95 @@
+// mDNS announcements. Discovery tools and the OTA scripts read the TXT
+// records of our services (`version` on _esp32-ota), and responders on the
+// LAN cache them. Whenever the advertised hostname, IP or firmware version
+// changes, NetworkManager registers the services again, which makes the
+// responder send fresh announcements. `reannounce()` forces that from any
+// task, e.g. the post-OTA WiFi recovery path, whose reconnect may not change
+// anything we compare.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Re-register the mDNS services on the next network update
+pub fn reannounce() {
+    REQUESTED.store(true, Ordering::Relaxed);
+}
+
+/// What the services currently advertise
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Advertisement {
+    /// Without `.local`
+    pub hostname: String,
+    pub ip: Option<String>,
+    pub version: String,
+}
+
+impl Advertisement {
+    /// TXT records of the _esp32-ota service
+    pub fn ota_txt(&self) -> [(&str, &str); 2] {
+        [("path", "/ota"), ("version", &self.version)]
+    }
+}
+
+/// Tracks what was last announced
+#[derive(Debug, Default)]
+pub struct Announcer {
+    announced: Option<Advertisement>,
+    announcements: u32,
+}
+
+impl Announcer {
+    /// Whether the services need registering again for `current`
+    pub fn is_due(&self, current: &Advertisement) -> bool {
+        REQUESTED.load(Ordering::Relaxed) || self.announced.as_ref() != Some(current)
+    }
+
+    /// The services were registered with `current`
+    pub fn announced(&mut self, current: Advertisement) {
+        REQUESTED.store(false, Ordering::Relaxed);
+        self.announced = Some(current);
+        self.announcements = self.announcements.saturating_add(1);
+    }
+
+    /// mDNS was stopped; the next start announces from scratch
+    pub fn forget(&mut self) {
+        self.announced = None;
+    }
+
+    /// Registrations since boot, the first one included
+    pub fn announcements(&self) -> u32 {
+        self.announcements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn advert(ip: &str, version: &str) -> Advertisement {
+        Advertisement { hostname: "esp32".into(), ip: Some(ip.into()), version: version.into() }
+    }
+
+    #[test]
+    fn test_reannounces_on_change_or_request() {
+        let mut announcer = Announcer::default();
+        let current = advert("192.168.1.20", "v6.26");
+        assert!(announcer.is_due(&current));
+        announcer.announced(current.clone());
+        assert!(!announcer.is_due(&current));
+
+        assert!(announcer.is_due(&advert("192.168.1.21", "v6.26")), "new DHCP lease");
+        assert!(announcer.is_due(&advert("192.168.1.20", "v6.27")), "new firmware");
+        assert!(announcer.is_due(&Advertisement { hostname: "esp32-a1b2c3".into(), ..current.clone() }));
+
+        reannounce();
+        assert!(announcer.is_due(&current));
+        announcer.announced(current.clone());
+        assert!(!announcer.is_due(&current));
+        assert_eq!(announcer.announcements(), 2);
+
+        announcer.forget();
+        assert!(announcer.is_due(&current));
+        assert_eq!(current.ota_txt()[1], ("version", "v6.26"));
+    }
+}
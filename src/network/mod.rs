@@ -1,18 +1,29 @@
 pub mod wifi;
 pub mod wifi_reconnect;
 pub mod wifi_stats;
+pub mod wifi_scan;
+pub mod connection_history;
+pub mod disconnect_reasons;
 pub mod web_server;
 // pub mod simple_retry; // removed (unused)
 pub mod telnet_server;
 // pub mod sse_broadcaster; // legacy SSE, replaced by sse_v2
 pub mod sse_v2;
+pub mod sse_hub;
+pub mod mdns_manager;
+pub mod ota_progress_stream;
 pub mod api_routes;
+pub mod api_core;
+pub mod error;
 pub mod error_handler;
 pub mod error_wrapper;
 pub mod validators;
+pub mod csrf;
 pub mod log_streamer;
+pub mod log_filter;
 pub mod file_manager;
-// pub mod compression; // removed (unused)
+pub mod compression;
+pub mod etag;
 pub mod binary_protocol;
 pub mod http_config;
 pub mod server_config;
@@ -22,29 +33,77 @@ pub mod streaming_dashboard;
 pub mod template_engine;
 pub mod templated_home;
 pub mod observability;
+pub mod net_stats;
+pub mod latency_monitor;
+pub mod heap_tracker;
+pub mod admission;
+pub mod json_stream;
+pub mod nettest;
+pub mod http_client;
+pub mod push_exporter;
+pub mod offline_queue;
+pub mod weather;
+pub mod rest_widgets;
+pub mod agenda;
+pub mod alertmanager;
+pub mod ics;
+pub mod softap;
+pub mod ipv6;
+pub mod mqtt;
+#[cfg(feature = "ble")]
+pub mod ble;
+#[cfg(feature = "demo_mode")]
+pub mod simulator;
+#[cfg(feature = "demo_mode")]
+pub use simulator::SimulatedNetwork as NetworkManager;
 
+static HOSTNAME: std::sync::Mutex<String> = std::sync::Mutex::new(String::new());
+
+/// Name claimed on mDNS (without `.local`); empty until the network is up
+pub fn hostname() -> String {
+    HOSTNAME.lock().map(|h| h.clone()).unwrap_or_default()
+}
+
+#[cfg(not(feature = "demo_mode"))]
 use anyhow::Result;
+#[cfg(not(feature = "demo_mode"))]
 use esp_idf_hal::modem::Modem;
+#[cfg(not(feature = "demo_mode"))]
 use esp_idf_svc::{
     eventloop::EspSystemEventLoop,
     timer::EspTaskTimerService,
 };
+#[cfg(not(feature = "demo_mode"))]
 use std::sync::{Arc, Mutex};
+#[cfg(not(feature = "demo_mode"))]
+use std::time::Duration;
 
+#[cfg(not(feature = "demo_mode"))]
 use self::wifi::WifiManager;
+#[cfg(not(feature = "demo_mode"))]
 use self::wifi_reconnect::WifiReconnectManager;
+#[cfg(not(feature = "demo_mode"))]
 use crate::config::Config;
+#[cfg(not(feature = "demo_mode"))]
 use esp_idf_svc::mdns::EspMdns;
+#[cfg(not(feature = "demo_mode"))]
+use self::mdns_manager::{Advertisement, Announcer};
 
+// Real WiFi station; replaced by simulator::SimulatedNetwork in demo builds
+#[cfg(not(feature = "demo_mode"))]
 pub struct NetworkManager {
     wifi: WifiManager,
-    _mdns: Option<EspMdns>,
+    mdns: Option<EspMdns>,
+    announcer: Announcer,
+    /// Configured hostname; the claimed one may carry a MAC suffix
+    hostname: String,
     signal_strength: i8,
     _reconnect_manager: Option<Arc<WifiReconnectManager>>,
     disconnect_count: Arc<Mutex<u32>>,
     reconnect_count: Arc<Mutex<u32>>,
 }
 
+#[cfg(not(feature = "demo_mode"))]
 impl NetworkManager {
     pub fn new(
         modem: Modem,
@@ -52,17 +111,40 @@ impl NetworkManager {
         _timer_service: EspTaskTimerService,
         ssid: String,
         password: String,
-        _config: Arc<Mutex<Config>>,
+        config: Arc<Mutex<Config>>,
     ) -> Result<Self> {
-        let wifi = WifiManager::new(modem, sys_loop.clone(), ssid.clone(), password.clone())?;
+        let hostname = config.lock().map(|c| c.hostname.clone()).unwrap_or_else(|_| "esp32".to_string());
+        let bssid = config.lock().ok().and_then(|c| connection_history::parse_bssid(&c.wifi_bssid));
+        if let Some(bssid) = bssid {
+            log::info!("WiFi pinned to BSSID {}", connection_history::format_bssid(&bssid));
+        }
+        let access_point = config.lock().ok()
+            .and_then(|cfg| softap::access_point_configuration(&cfg)
+                .map_err(|e| log::warn!("Fallback AP disabled: {:?}", e))
+                .ok()
+                .flatten());
+        let ap_enabled = access_point.is_some();
+        let static_ip = config.lock().ok()
+            .and_then(|c| validators::parse_static_ipv4(&c.static_ip, &c.static_netmask, &c.static_gateway, &c.static_dns)
+                .map_err(|e| log::warn!("Static IP ignored, using DHCP: {}", e))
+                .ok()
+                .flatten());
+        let wifi = WifiManager::new(modem, sys_loop.clone(), ssid.clone(), password.clone(), &hostname, bssid, access_point, static_ip)?;
         
         // Create reconnection manager
         let reconnect_manager = Arc::new(WifiReconnectManager::new(ssid, password));
         reconnect_manager.register_event_handlers(&sys_loop)?;
+        net_stats::start_collector();
+        latency_monitor::start(config.clone());
+        if ap_enabled {
+            softap::start(config.clone())?;
+        }
 
         Ok(Self {
             wifi,
-            _mdns: None,
+            mdns: None,
+            announcer: Announcer::default(),
+            hostname,
             signal_strength: -100,
             _reconnect_manager: Some(reconnect_manager),
             disconnect_count: Arc::new(Mutex::new(0)),
@@ -77,13 +159,28 @@ impl NetworkManager {
         
         // Start mDNS for network discovery
         match self.start_mdns() {
-            Ok(_) => log::info!("mDNS service started: esp32.local"),
+            Ok(_) => log::info!("mDNS service started: {}.local", hostname()),
             Err(e) => log::warn!("Failed to start mDNS: {:?}", e),
         }
         
         Ok(())
     }
     
+    /// Withdraw or restore the mDNS services, e.g. while thermally throttled
+    pub fn set_mdns_paused(&mut self, paused: bool) {
+        if paused {
+            if self.mdns.take().is_some() {
+                self.announcer.forget();
+                log::info!("mDNS paused");
+            }
+        } else if self.mdns.is_none() && self.is_connected() {
+            match self.start_mdns() {
+                Ok(_) => log::info!("mDNS resumed"),
+                Err(e) => log::warn!("Failed to restart mDNS: {:?}", e),
+            }
+        }
+    }
+    
     fn start_mdns(&mut self) -> Result<()> {
         // Try to take mDNS, but it might already be taken
         let mdns_result = EspMdns::take();
@@ -94,15 +191,57 @@ impl NetworkManager {
                 return Ok(());
             }
         };
-        mdns.set_hostname("esp32")?;
-        
-        // Properties are set via service text records in esp-idf-svc
+        let name = self.claim_hostname(&mdns);
+        if let Ok(mut current) = HOSTNAME.lock() {
+            *current = name;
+        }
         
+        let current = self.advertisement();
+        Self::announce(&mut mdns, &current)?;
+        self.announcer.announced(current);
+        self.mdns = Some(mdns);
+        Ok(())
+    }
+
+    /// Register the services again when what they advertise changed (new
+    /// DHCP lease, hostname, firmware) or `mdns_manager::reannounce()` asked
+    pub fn refresh_mdns(&mut self) {
+        if !self.is_connected() {
+            return;
+        }
+        let current = self.advertisement();
+        let Some(mdns) = self.mdns.as_mut() else {
+            return;
+        };
+        if !self.announcer.is_due(&current) {
+            return;
+        }
+        let result = mdns.remove_services()
+            .map_err(anyhow::Error::from)
+            .and_then(|_| Self::announce(mdns, &current));
+        match result {
+            Ok(()) => {
+                log::info!("mDNS re-announced {}.local ({})", current.hostname, current.version);
+                self.announcer.announced(current);
+            }
+            Err(e) => log::warn!("mDNS re-announce failed: {:?}", e),
+        }
+    }
+
+    fn advertisement(&self) -> Advertisement {
+        Advertisement {
+            hostname: hostname(),
+            ip: self.get_ip(),
+            version: crate::version::DISPLAY_VERSION.to_string(),
+        }
+    }
+
+    // Properties are set via service text records in esp-idf-svc
+    fn announce(mdns: &mut EspMdns, current: &Advertisement) -> Result<()> {
+        mdns.set_hostname(&current.hostname)?;
+
         // Add service for OTA discovery
-        mdns.add_service(None, "_esp32-ota", "_tcp", 80, &[
-            ("path", "/ota"),
-            ("version", crate::version::DISPLAY_VERSION),
-        ])?;
+        mdns.add_service(None, "_esp32-ota", "_tcp", 80, &current.ota_txt())?;
         
         // Add service for web config
         mdns.add_service(None, "_http", "_tcp", 80, &[
@@ -113,10 +252,30 @@ impl NetworkManager {
         mdns.add_service(None, "_telnet", "_tcp", 23, &[
             ("type", "log-streaming"),
         ])?;
-        
-        self._mdns = Some(mdns);
         Ok(())
     }
+
+    /// The configured hostname, or `<hostname>-xxxxxx` (MAC suffix) when another
+    /// device on the LAN already answers for it
+    fn claim_hostname(&mut self, mdns: &EspMdns) -> String {
+        let own_ip = self.wifi.get_ip();
+        let taken = match mdns.query_a(&self.hostname, Duration::from_millis(1500)) {
+            Ok(ip) => Some(ip.to_string()) != own_ip,
+            Err(_) => false, // no answer
+        };
+        if !taken {
+            return self.hostname.clone();
+        }
+        let Some(mac) = self.wifi.get_mac_bytes() else {
+            return self.hostname.clone();
+        };
+        let name = validators::hostname_with_mac_suffix(&self.hostname, &mac);
+        log::warn!("{}.local is already in use, claiming {}.local", self.hostname, name);
+        if let Err(e) = self.wifi.set_hostname(&name) {
+            log::warn!("Failed to update DHCP hostname: {:?}", e);
+        }
+        name
+    }
     
     
     pub fn is_connected(&self) -> bool {
@@ -127,6 +286,11 @@ impl NetworkManager {
         self.wifi.get_ip()
     }
     
+    /// Global IPv6 address if the router advertised a prefix, else link-local
+    pub fn get_ipv6(&self) -> Option<String> {
+        ipv6::preferred(&ipv6::addresses()).map(|a| a.to_string())
+    }
+
     pub fn get_ssid(&self) -> &str {
         if self.wifi.ssid.is_empty() {
             "Not configured"
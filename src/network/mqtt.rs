@@ -0,0 +1,47 @@
+// One-shot MQTT publishes for button actions. Each command connects to the
+// configured broker on its own short-lived thread, publishes once (QoS 1, so
+// the broker confirms it) and disconnects; a button press never waits on the
+// network.
+
+use anyhow::Result;
+use std::sync::mpsc;
+use std::time::Duration;
+use esp_idf_svc::mqtt::client::{EspMqttClient, EventPayload, MqttClientConfiguration, QoS};
+
+/// How long a command may take from connect to broker acknowledgement
+const PUBLISH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Publish `payload` to `topic` in the background
+pub fn publish_command(broker: String, topic: String, payload: String) -> Result<()> {
+    std::thread::Builder::new()
+        .name("mqtt_cmd".into())
+        .stack_size(6144)
+        .spawn(move || match publish_blocking(&broker, &topic, &payload) {
+            Ok(()) => log::info!("MQTT: published to {}", topic),
+            Err(e) => log::warn!("MQTT: publish to {} via {} failed: {:?}", topic, broker, e),
+        })?;
+    Ok(())
+}
+
+fn publish_blocking(broker: &str, topic: &str, payload: &str) -> Result<()> {
+    let client_id = format!("{}-buttons", crate::network::hostname());
+    let conf = MqttClientConfiguration {
+        client_id: Some(&client_id),
+        ..Default::default()
+    };
+    let (tx, rx) = mpsc::channel();
+    let mut client = EspMqttClient::new_cb(broker, &conf, move |event| {
+        if let EventPayload::Published(id) = event.payload() {
+            let _ = tx.send(id);
+        }
+    })?;
+    // Enqueued messages wait in the outbox until the connection is up
+    let id = client.enqueue(topic, QoS::AtLeastOnce, false, payload.as_bytes())?;
+    loop {
+        match rx.recv_timeout(PUBLISH_TIMEOUT) {
+            Ok(acked) if acked == id => return Ok(()),
+            Ok(_) => continue,
+            Err(_) => anyhow::bail!("no acknowledgement within {}s", PUBLISH_TIMEOUT.as_secs()),
+        }
+    }
+}
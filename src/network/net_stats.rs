@@ -0,0 +1,271 @@
+// Network stack health: socket usage, lwIP TCP/IP counters, DHCP lease events
+// and DNS failures. Socket exhaustion used to show up only when the web server
+// stopped accepting connections; a collector thread now samples the stack
+// every COLLECT_INTERVAL_MS and /metrics exports the result.
+
+use serde::Serialize;
+#[cfg(target_os = "espidf")]
+use {
+    super::error::NetworkError,
+    core::sync::atomic::{AtomicBool, AtomicU32, Ordering},
+    esp_idf_hal::delay::FreeRtos,
+    std::sync::Mutex,
+};
+
+pub const COLLECT_INTERVAL_MS: u32 = 5_000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct NetStackStats {
+    pub sockets_active: u32,
+    pub sockets_max: u32,
+    pub sockets_high_water: u32,
+    /// Samples that found every socket in use (accept() fails meanwhile)
+    pub socket_exhaustion_samples: u32,
+    /// TCP PCB allocations that failed (incoming connections refused for lack of memory)
+    pub accept_failures: u32,
+    pub tcp_tx_segments: u32,
+    pub tcp_rx_segments: u32,
+    pub tx_errors: u32,
+    pub rx_errors: u32,
+    /// Leases bound by the station's DHCP client, renewals included
+    pub dhcp_leases: u32,
+    /// Bound leases that kept the address (T1/T2 renewals)
+    pub dhcp_renews: u32,
+    pub dns_failures: u32,
+}
+
+impl NetStackStats {
+    /// Fold one socket-usage sample into the gauges
+    pub fn record_sockets(&mut self, active: u32, max: u32) {
+        self.sockets_active = active;
+        self.sockets_max = max;
+        self.sockets_high_water = self.sockets_high_water.max(active);
+        if max > 0 && active >= max {
+            self.socket_exhaustion_samples += 1;
+        }
+    }
+}
+
+/// Widens one of lwIP's 16-bit statistics counters into a running total;
+/// fine as long as it moves less than 65536 between samples
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WrappingCounter {
+    last: u16,
+    total: u32,
+}
+
+impl WrappingCounter {
+    pub const fn new() -> Self {
+        Self { last: 0, total: 0 }
+    }
+
+    pub fn update(&mut self, raw: u16) -> u32 {
+        self.total = self.total.wrapping_add(raw.wrapping_sub(self.last) as u32);
+        self.last = raw;
+        self.total
+    }
+}
+
+/// Host part of an http(s) URL, without userinfo, port or IPv6 brackets
+pub fn url_host(url: &str) -> Option<&str> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = &rest[..rest.find(['/', '?', '#']).unwrap_or(rest.len())];
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = if let Some(bracketed) = host_port.strip_prefix('[') {
+        bracketed.split_once(']')?.0
+    } else {
+        host_port.split_once(':').map_or(host_port, |(host, _)| host)
+    };
+    (!host.is_empty()).then_some(host)
+}
+
+#[cfg(target_os = "espidf")]
+struct Collector {
+    stats: NetStackStats,
+    tcp_tx: WrappingCounter,
+    tcp_rx: WrappingCounter,
+    accept_failures: WrappingCounter,
+    tx_errors: WrappingCounter,
+    rx_errors: WrappingCounter,
+}
+
+#[cfg(target_os = "espidf")]
+static COLLECTOR: Mutex<Collector> = Mutex::new(Collector {
+    stats: NetStackStats {
+        sockets_active: 0,
+        sockets_max: 0,
+        sockets_high_water: 0,
+        socket_exhaustion_samples: 0,
+        accept_failures: 0,
+        tcp_tx_segments: 0,
+        tcp_rx_segments: 0,
+        tx_errors: 0,
+        rx_errors: 0,
+        dhcp_leases: 0,
+        dhcp_renews: 0,
+        dns_failures: 0,
+    },
+    tcp_tx: WrappingCounter::new(),
+    tcp_rx: WrappingCounter::new(),
+    accept_failures: WrappingCounter::new(),
+    tx_errors: WrappingCounter::new(),
+    rx_errors: WrappingCounter::new(),
+});
+#[cfg(target_os = "espidf")]
+static DHCP_LEASES: AtomicU32 = AtomicU32::new(0);
+#[cfg(target_os = "espidf")]
+static DHCP_RENEWS: AtomicU32 = AtomicU32::new(0);
+#[cfg(target_os = "espidf")]
+static DNS_FAILURES: AtomicU32 = AtomicU32::new(0);
+
+#[cfg(target_os = "espidf")]
+pub fn snapshot() -> NetStackStats {
+    let stats = COLLECTOR.lock().unwrap_or_else(|e| e.into_inner()).stats;
+    NetStackStats {
+        dhcp_leases: DHCP_LEASES.load(Ordering::Relaxed),
+        dhcp_renews: DHCP_RENEWS.load(Ordering::Relaxed),
+        dns_failures: DNS_FAILURES.load(Ordering::Relaxed),
+        ..stats
+    }
+}
+
+/// Resolve the host of `url` ahead of a request so DNS failures are counted
+/// (and reported as such) instead of surfacing as a generic connect error.
+/// lwIP caches the answer, so the client's own lookup costs nothing extra.
+#[cfg(target_os = "espidf")]
+pub fn check_dns(url: &str) -> Result<(), NetworkError> {
+    use std::net::{IpAddr, ToSocketAddrs};
+
+    let Some(host) = url_host(url) else { return Ok(()) };
+    if host.parse::<IpAddr>().is_ok() {
+        return Ok(());
+    }
+    if let Err(e) = (host, 0).to_socket_addrs() {
+        DNS_FAILURES.fetch_add(1, Ordering::Relaxed);
+        return Err(NetworkError::Dns(format!("{}: {}", host, e)));
+    }
+    Ok(())
+}
+
+/// Start the sampling thread and the DHCP event hook (idempotent)
+#[cfg(target_os = "espidf")]
+pub fn start_collector() {
+    static STARTED: AtomicBool = AtomicBool::new(false);
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    unsafe extern "C" fn ip_event_handler(
+        _handler_arg: *mut core::ffi::c_void,
+        _event_base: *const u8,
+        event_id: i32,
+        event_data: *mut core::ffi::c_void,
+    ) {
+        use esp_idf_sys::*;
+        if event_id as u32 == ip_event_t_IP_EVENT_STA_GOT_IP && !event_data.is_null() {
+            let event = &*(event_data as *const ip_event_got_ip_t);
+            DHCP_LEASES.fetch_add(1, Ordering::Relaxed);
+            // esp_netif posts GOT_IP on every DHCP bind; an unchanged address is a renewal
+            if !event.ip_changed {
+                DHCP_RENEWS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    unsafe {
+        use esp_idf_sys::*;
+        let err = esp_event_handler_register(
+            IP_EVENT,
+            ip_event_t_IP_EVENT_STA_GOT_IP as i32,
+            Some(ip_event_handler),
+            core::ptr::null_mut(),
+        );
+        if err != ESP_OK {
+            log::warn!("Failed to register IP event handler: {}", err);
+        }
+    }
+
+    let spawned = std::thread::Builder::new()
+        .name("net_stats".into())
+        .stack_size(3072)
+        .spawn(|| loop {
+            collect();
+            FreeRtos::delay_ms(COLLECT_INTERVAL_MS);
+        });
+    if let Err(e) = spawned {
+        log::warn!("Failed to start network stats collector: {:?}", e);
+    }
+}
+
+#[cfg(target_os = "espidf")]
+fn collect() {
+    use esp_idf_sys::*;
+
+    let max = CONFIG_LWIP_MAX_SOCKETS;
+    let active = open_sockets(max);
+    // SAFETY: plain counters updated by the tcpip task; a torn read only skews one sample
+    let stats = unsafe { &*core::ptr::addr_of!(lwip_stats) };
+    let (tcp, ip, link) = (&stats.tcp, &stats.ip, &stats.link);
+    let tx_errors = [tcp.rterr, ip.rterr, link.err, link.memerr]
+        .iter()
+        .fold(0u16, |sum, &c| sum.wrapping_add(c as u16));
+    let rx_errors = [tcp.chkerr, tcp.lenerr, tcp.proterr, tcp.opterr, ip.chkerr, ip.lenerr, ip.proterr, link.chkerr, link.lenerr]
+        .iter()
+        .fold(0u16, |sum, &c| sum.wrapping_add(c as u16));
+
+    let mut collector = COLLECTOR.lock().unwrap_or_else(|e| e.into_inner());
+    let was_exhausted = collector.stats.socket_exhaustion_samples;
+    collector.stats.record_sockets(active, max);
+    collector.stats.tcp_tx_segments = collector.tcp_tx.update(tcp.xmit as u16);
+    collector.stats.tcp_rx_segments = collector.tcp_rx.update(tcp.recv as u16);
+    collector.stats.accept_failures = collector.accept_failures.update(tcp.memerr as u16);
+    collector.stats.tx_errors = collector.tx_errors.update(tx_errors);
+    collector.stats.rx_errors = collector.rx_errors.update(rx_errors);
+    if collector.stats.socket_exhaustion_samples > was_exhausted {
+        log::warn!("Network: all {} sockets in use", max);
+    }
+}
+
+/// Sockets currently allocated in lwIP's socket table
+#[cfg(target_os = "espidf")]
+fn open_sockets(max: u32) -> u32 {
+    use esp_idf_sys::*;
+
+    // lwIP numbers its sockets after the VFS descriptors (LWIP_SOCKET_OFFSET)
+    let first = (FD_SETSIZE - max) as i32;
+    (first..first + max as i32)
+        .filter(|&fd| unsafe {
+            let mut kind: i32 = 0;
+            let mut len = core::mem::size_of::<i32>() as socklen_t;
+            lwip_getsockopt(fd, SOL_SOCKET as i32, SO_TYPE as i32, &mut kind as *mut i32 as *mut _, &mut len) == 0
+        })
+        .count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters_and_socket_samples() {
+        let mut counter = WrappingCounter::new();
+        assert_eq!(counter.update(100), 100);
+        assert_eq!(counter.update(65_500), 65_500);
+        // lwIP's u16 wrapped between samples
+        assert_eq!(counter.update(36), 65_572);
+
+        let mut stats = NetStackStats::default();
+        stats.record_sockets(3, 16);
+        stats.record_sockets(16, 16);
+        stats.record_sockets(5, 16);
+        assert_eq!((stats.sockets_active, stats.sockets_high_water, stats.socket_exhaustion_samples), (5, 16, 1));
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(url_host("https://api.open-meteo.com/v1/forecast?x=1"), Some("api.open-meteo.com"));
+        assert_eq!(url_host("http://user:pw@grafana.local:3000/api"), Some("grafana.local"));
+        assert_eq!(url_host("http://[fe80::1]:9091/metrics"), Some("fe80::1"));
+        assert_eq!(url_host("http:///path"), None);
+    }
+}
@@ -0,0 +1,136 @@
+// On-demand throughput test: download `nettest_url` for TEST_WINDOW and report
+// the effective rate, so users can check the link quality where the device is
+// mounted. POST /api/nettest starts a run in the background (the download would
+// otherwise hold the single httpd task); GET /api/nettest polls for the result.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::Duration;
+#[cfg(target_os = "espidf")]
+use {
+    core::sync::atomic::{AtomicBool, Ordering},
+    std::sync::Mutex,
+};
+
+/// How long the body is read; the transfer stops early at end of file
+pub const TEST_WINDOW: Duration = Duration::from_secs(5);
+/// Completed runs kept for GET /api/nettest
+pub const MAX_HISTORY: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NetTestResult {
+    /// Unix seconds when the run finished (0 before SNTP sync)
+    pub timestamp: u64,
+    pub url: String,
+    pub bytes: u64,
+    /// Time spent reading the body; connect and TLS setup are excluded
+    pub duration_ms: u64,
+    pub mbps: f32,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NetTestStatus {
+    pub running: bool,
+    /// Newest first
+    pub history: Vec<NetTestResult>,
+}
+
+/// Megabits per second for `bytes` received in `elapsed_ms`
+pub fn throughput_mbps(bytes: u64, elapsed_ms: u64) -> f32 {
+    if elapsed_ms == 0 {
+        return 0.0;
+    }
+    (bytes as f64 * 8.0 / (elapsed_ms as f64 * 1000.0)) as f32
+}
+
+/// Keep the newest MAX_HISTORY results, newest first
+pub fn push_result(history: &mut VecDeque<NetTestResult>, result: NetTestResult) {
+    history.push_front(result);
+    history.truncate(MAX_HISTORY);
+}
+
+#[cfg(target_os = "espidf")]
+static HISTORY: Mutex<VecDeque<NetTestResult>> = Mutex::new(VecDeque::new());
+#[cfg(target_os = "espidf")]
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[cfg(target_os = "espidf")]
+pub fn status() -> NetTestStatus {
+    NetTestStatus {
+        running: RUNNING.load(Ordering::Relaxed),
+        history: HISTORY.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect(),
+    }
+}
+
+/// Start a run against `url`; false if one is already in progress
+#[cfg(target_os = "espidf")]
+pub fn start(url: String) -> bool {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    let spawned = std::thread::Builder::new()
+        .name("nettest".into())
+        .stack_size(8192) // TLS handshake
+        .spawn(move || {
+            let result = run(&url);
+            match &result.error {
+                None => log::info!("Net test: {:.2} Mbps ({} bytes in {} ms) from {}", result.mbps, result.bytes, result.duration_ms, url),
+                Some(e) => log::warn!("Net test against {} failed: {}", url, e),
+            }
+            push_result(&mut HISTORY.lock().unwrap_or_else(|e| e.into_inner()), result);
+            RUNNING.store(false, Ordering::SeqCst);
+        });
+    if let Err(e) = spawned {
+        log::warn!("Failed to start net test thread: {:?}", e);
+        RUNNING.store(false, Ordering::SeqCst);
+        return false;
+    }
+    true
+}
+
+#[cfg(target_os = "espidf")]
+fn run(url: &str) -> NetTestResult {
+    use crate::network::http_client::{self, RequestOptions};
+
+    let options = RequestOptions { timeout: Duration::from_secs(10), ..Default::default() };
+    let (bytes, elapsed, error) = match http_client::download_for(url, &options, TEST_WINDOW) {
+        Ok(download) => (download.bytes, download.elapsed, None),
+        Err(e) => (0, Duration::ZERO, Some(e.to_string())),
+    };
+    let duration_ms = elapsed.as_millis() as u64;
+    NetTestResult {
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        url: url.to_string(),
+        bytes,
+        duration_ms,
+        mbps: throughput_mbps(bytes, duration_ms),
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(bytes: u64) -> NetTestResult {
+        NetTestResult { timestamp: 0, url: String::new(), bytes, duration_ms: 1000, mbps: 0.0, error: None }
+    }
+
+    #[test]
+    fn test_throughput_and_history() {
+        assert_eq!(throughput_mbps(1_250_000, 1000), 10.0);
+        assert_eq!(throughput_mbps(625_000, 5000), 1.0);
+        assert_eq!(throughput_mbps(1000, 0), 0.0);
+
+        let mut history = VecDeque::new();
+        for bytes in 0..(MAX_HISTORY as u64 + 3) {
+            push_result(&mut history, result(bytes));
+        }
+        assert_eq!(history.len(), MAX_HISTORY);
+        assert_eq!(history.front().map(|r| r.bytes), Some(MAX_HISTORY as u64 + 2));
+    }
+}
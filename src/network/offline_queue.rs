@@ -0,0 +1,130 @@
+// Bounded buffer for push samples that couldn't be delivered (WiFi down,
+// endpoint unreachable). It only grows during an outage; once the backing
+// allocation passes CONFIG_SPIRAM_MALLOC_ALWAYSINTERNAL (16 KB) it lands in PSRAM.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// What to discard when the buffer is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DropPolicy {
+    /// Keep the most recent data (default)
+    #[default]
+    Oldest,
+    /// Keep the start of the outage; new samples are discarded
+    Newest,
+}
+
+#[derive(Debug)]
+pub struct OfflineQueue<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+    policy: DropPolicy,
+    dropped: u64,
+}
+
+impl<T: Clone> OfflineQueue<T> {
+    pub fn new(capacity: usize, policy: DropPolicy) -> Self {
+        Self { items: VecDeque::new(), capacity: capacity.max(1), policy, dropped: 0 }
+    }
+
+    /// Apply new limits from config; shrinking drops per the policy
+    pub fn configure(&mut self, capacity: usize, policy: DropPolicy) {
+        self.capacity = capacity.max(1);
+        self.policy = policy;
+        while self.items.len() > self.capacity {
+            match policy {
+                DropPolicy::Oldest => self.items.pop_front(),
+                DropPolicy::Newest => self.items.pop_back(),
+            };
+            self.dropped += 1;
+        }
+    }
+
+    pub fn push(&mut self, item: T) {
+        if self.items.len() >= self.capacity {
+            self.dropped += 1;
+            match self.policy {
+                DropPolicy::Oldest => { self.items.pop_front(); }
+                DropPolicy::Newest => return,
+            }
+        }
+        self.items.push_back(item);
+    }
+
+    /// Oldest `max` items, left queued until `ack`
+    pub fn peek_batch(&self, max: usize) -> Vec<T> {
+        self.items.iter().take(max).cloned().collect()
+    }
+
+    /// Remove `count` delivered items from the front
+    pub fn ack(&mut self, count: usize) {
+        self.items.drain(..count.min(self.items.len()));
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Items discarded because the buffer was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+/// Wall-clock time for a sample taken before the clock was synced, derived from
+/// how long ago (by uptime) it was captured
+pub fn backfill_unix_ms(sample_uptime_s: u64, now_uptime_s: u64, now_unix_ms: u64) -> u64 {
+    now_unix_ms.saturating_sub(now_uptime_s.saturating_sub(sample_uptime_s) * 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_policies() {
+        let mut q = OfflineQueue::new(3, DropPolicy::Oldest);
+        for i in 0..5 {
+            q.push(i);
+        }
+        assert_eq!(q.peek_batch(10), vec![2, 3, 4]);
+        assert_eq!(q.dropped(), 2);
+
+        let mut q = OfflineQueue::new(3, DropPolicy::Newest);
+        for i in 0..5 {
+            q.push(i);
+        }
+        assert_eq!(q.peek_batch(10), vec![0, 1, 2]);
+        assert_eq!(q.dropped(), 2);
+
+        q.configure(2, DropPolicy::Newest);
+        assert_eq!(q.peek_batch(10), vec![0, 1]);
+        assert_eq!(q.dropped(), 3);
+    }
+
+    #[test]
+    fn test_batches_stay_until_acked() {
+        let mut q = OfflineQueue::new(10, DropPolicy::Oldest);
+        for i in 0..5 {
+            q.push(i);
+        }
+        assert_eq!(q.peek_batch(2), vec![0, 1]);
+        assert_eq!(q.len(), 5);
+        q.ack(2);
+        assert_eq!(q.peek_batch(2), vec![2, 3]);
+        q.ack(99);
+        assert!(q.is_empty());
+        // Sampled 60 s before "now"
+        assert_eq!(backfill_unix_ms(40, 100, 1_700_000_100_000), 1_700_000_040_000);
+    }
+}
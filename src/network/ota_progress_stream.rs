@@ -0,0 +1,122 @@
+// GET /api/ota/progress: Server-Sent Events carrying the ota::progress snapshot.
+//
+// ESP-IDF's httpd serves every socket from a single task, so a stream that loops
+// inside its handler (as sse_v2 does) would stall the very upload it reports on.
+// This endpoint is registered on the raw server instead: the handler detaches
+// the request with httpd_req_async_handler_begin and returns, and a small thread
+// feeds the socket while httpd goes back to receiving the firmware.
+
+use anyhow::Result;
+use core::sync::atomic::{AtomicU32, Ordering};
+use esp_idf_hal::delay::FreeRtos;
+use esp_idf_svc::handle::RawHandle;
+use esp_idf_svc::http::server::EspHttpServer;
+use esp_idf_sys::*;
+use std::time::{Duration, Instant};
+
+/// Each stream holds a socket and a 4 KB thread stack
+const MAX_STREAMS: u32 = 2;
+const POLL_INTERVAL_MS: u32 = 250;
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+/// Streams end after this even without an update; EventSource reconnects
+const MAX_STREAM_AGE: Duration = Duration::from_secs(600);
+
+static ACTIVE_STREAMS: AtomicU32 = AtomicU32::new(0);
+
+/// Request copy handed to the streaming thread; httpd keeps the socket open
+/// until httpd_req_async_handler_complete
+struct DetachedRequest(*mut httpd_req_t);
+
+// SAFETY: a detached request is owned by exactly one thread until completed
+unsafe impl Send for DetachedRequest {}
+
+impl DetachedRequest {
+    fn as_ptr(&self) -> *mut httpd_req_t {
+        self.0
+    }
+}
+
+pub fn register(server: &EspHttpServer) -> Result<()> {
+    let mut uri: httpd_uri_t = unsafe { core::mem::zeroed() };
+    uri.uri = b"/api/ota/progress\0".as_ptr() as *const _;
+    uri.method = http_method_HTTP_GET;
+    uri.handler = Some(handle_request);
+    esp!(unsafe { httpd_register_uri_handler(server.handle(), &uri) })?;
+    Ok(())
+}
+
+unsafe extern "C" fn handle_request(req: *mut httpd_req_t) -> esp_err_t {
+    if ACTIVE_STREAMS.fetch_add(1, Ordering::AcqRel) >= MAX_STREAMS {
+        ACTIVE_STREAMS.fetch_sub(1, Ordering::AcqRel);
+        return send_unavailable(req, "Too many progress streams");
+    }
+
+    let mut detached: *mut httpd_req_t = core::ptr::null_mut();
+    if httpd_req_async_handler_begin(req, &mut detached) != ESP_OK {
+        ACTIVE_STREAMS.fetch_sub(1, Ordering::AcqRel);
+        return send_unavailable(req, "Progress stream unavailable");
+    }
+
+    let request = DetachedRequest(detached);
+    let spawned = std::thread::Builder::new()
+        .name("ota_sse".into())
+        .stack_size(4096)
+        .spawn(move || {
+            stream_progress(request.as_ptr());
+            unsafe { httpd_req_async_handler_complete(request.as_ptr()); }
+            ACTIVE_STREAMS.fetch_sub(1, Ordering::AcqRel);
+        });
+    if let Err(e) = spawned {
+        log::warn!("OTA progress: failed to start stream thread: {:?}", e);
+        httpd_req_async_handler_complete(detached);
+        ACTIVE_STREAMS.fetch_sub(1, Ordering::AcqRel);
+        return ESP_FAIL;
+    }
+    ESP_OK
+}
+
+unsafe fn send_unavailable(req: *mut httpd_req_t, message: &str) -> esp_err_t {
+    httpd_resp_set_status(req, b"503 Service Unavailable\0".as_ptr() as *const _);
+    httpd_resp_send(req, message.as_ptr() as *const _, message.len() as _)
+}
+
+fn send(req: *mut httpd_req_t, data: &str) -> bool {
+    unsafe { httpd_resp_send_chunk(req, data.as_ptr() as *const _, data.len() as _) == ESP_OK }
+}
+
+fn stream_progress(req: *mut httpd_req_t) {
+    unsafe {
+        httpd_resp_set_type(req, b"text/event-stream\0".as_ptr() as *const _);
+        httpd_resp_set_hdr(req, b"Cache-Control\0".as_ptr() as *const _, b"no-cache\0".as_ptr() as *const _);
+    }
+    if !send(req, "retry: 2000\n\n") {
+        return;
+    }
+
+    let started = Instant::now();
+    let initial_sequence = crate::ota::progress::snapshot().sequence;
+    let mut last_sequence = None;
+    let mut last_sent = Instant::now();
+    while started.elapsed() < MAX_STREAM_AGE {
+        let progress = crate::ota::progress::snapshot();
+        if last_sequence != Some(progress.sequence) {
+            last_sequence = Some(progress.sequence);
+            let Ok(json) = serde_json::to_string(&progress) else { break };
+            if !send(req, &format!("event: progress\ndata: {}\n\n", json)) {
+                return; // client went away
+            }
+            last_sent = Instant::now();
+            // A result left over from an earlier update doesn't end the stream
+            if progress.status().is_finished() && progress.sequence != initial_sequence {
+                break;
+            }
+        } else if last_sent.elapsed() >= HEARTBEAT_INTERVAL {
+            if !send(req, ": keep-alive\n\n") {
+                return;
+            }
+            last_sent = Instant::now();
+        }
+        FreeRtos::delay_ms(POLL_INTERVAL_MS);
+    }
+    send(req, "");
+}
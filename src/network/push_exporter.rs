@@ -0,0 +1,233 @@
+// Push exporter: periodically POSTs metrics to a user-configured endpoint.
+// Supports InfluxDB line protocol or a generic JSON body. Samples are batched
+// and a failed batch is retried with backoff; one the endpoint rejects outright
+// (4xx) is dropped rather than blocking everything queued behind it. While
+// WiFi is down or the endpoint is unreachable, samples collect in an offline
+// queue and are backfilled (oldest first, with timestamps) once delivery works again.
+
+use anyhow::{Context, Result};
+use core::fmt::Write as _;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use esp_idf_hal::delay::FreeRtos;
+use crate::config::{Config, PushFormat};
+use crate::system::ShutdownSignal;
+use super::error::NetworkError;
+use super::http_client::{self, RequestOptions};
+use super::offline_queue::{backfill_unix_ms, DropPolicy, OfflineQueue};
+
+const MEASUREMENT: &str = "esp32_dashboard";
+const HOST_TAG: &str = "esp32";
+/// Largest batch per POST, also used for backfill chunks
+const MAX_BATCH_SIZE: usize = 60;
+const MAX_ATTEMPTS: u32 = 3;
+const HTTP_TIMEOUT_MS: u64 = 5000;
+
+static QUEUED: AtomicU32 = AtomicU32::new(0);
+static DROPPED: AtomicU32 = AtomicU32::new(0);
+
+/// (samples waiting in the offline queue, samples dropped because it was full)
+pub fn queue_stats() -> (u32, u32) {
+    (QUEUED.load(Ordering::Relaxed), DROPPED.load(Ordering::Relaxed))
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PushSample {
+    /// Wall-clock time in ms; None until the clock has been set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unix_ms: Option<u64>,
+    pub uptime_s: u64,
+    pub heap_free: u32,
+    pub cpu_usage: u8,
+    pub temperature: f32,
+    pub wifi_rssi: i8,
+    pub battery_percentage: u8,
+    pub battery_voltage_mv: u16,
+    pub fps: f32,
+}
+
+impl PushSample {
+    /// Capture a sample from the global metrics snapshot
+    pub fn capture() -> Self {
+        let uptime_s = unsafe { (esp_idf_sys::esp_timer_get_time() / 1_000_000) as u64 };
+        let heap_free = unsafe { esp_idf_sys::esp_get_free_heap_size() };
+        let m = crate::metrics::metrics().lock().map(|g| (*g).clone()).unwrap_or_default();
+        Self {
+            unix_ms: wall_clock_ms(),
+            uptime_s,
+            heap_free,
+            cpu_usage: m.cpu_usage,
+            temperature: m.temperature,
+            wifi_rssi: m.wifi_rssi,
+            battery_percentage: m.battery_percentage,
+            battery_voltage_mv: m.battery_voltage_mv,
+            fps: m.fps_actual,
+        }
+    }
+}
+
+/// Returns wall-clock ms only if the RTC looks synced (after 2020-01-01)
+fn wall_clock_ms() -> Option<u64> {
+    let ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_millis() as u64;
+    if ms > 1_577_836_800_000 { Some(ms) } else { None }
+}
+
+/// Give samples captured before SNTP sync a timestamp once the clock is known
+fn stamp_missing(samples: &mut [PushSample]) {
+    let Some(now_ms) = wall_clock_ms() else { return };
+    let now_uptime_s = unsafe { (esp_idf_sys::esp_timer_get_time() / 1_000_000) as u64 };
+    for s in samples.iter_mut().filter(|s| s.unix_ms.is_none()) {
+        s.unix_ms = Some(backfill_unix_ms(s.uptime_s, now_uptime_s, now_ms));
+    }
+}
+
+/// Format samples as InfluxDB line protocol (one line per sample).
+/// Timestamps are emitted in ms precision when known; use `precision=ms` on the write URL.
+pub fn format_line_protocol(samples: &[PushSample]) -> String {
+    let mut out = String::with_capacity(samples.len() * 160);
+    for s in samples {
+        let _ = write!(
+            out,
+            "{},host={} uptime_s={}i,heap_free={}i,cpu_usage={}i,temperature={:.2},wifi_rssi={}i,battery_percentage={}i,battery_voltage_mv={}i,fps={:.1}",
+            MEASUREMENT, HOST_TAG,
+            s.uptime_s, s.heap_free, s.cpu_usage, s.temperature, s.wifi_rssi,
+            s.battery_percentage, s.battery_voltage_mv, s.fps
+        );
+        if let Some(ts) = s.unix_ms {
+            let _ = write!(out, " {}", ts);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Format samples as a generic JSON document
+pub fn format_json(samples: &[PushSample]) -> Result<String> {
+    let body = serde_json::json!({
+        "device": HOST_TAG,
+        "version": crate::version::DISPLAY_VERSION,
+        // Always SI, whatever the display units are set to
+        "units": { "temperature": "celsius", "battery_voltage_mv": "millivolt" },
+        "samples": samples,
+    });
+    Ok(serde_json::to_string(&body)?)
+}
+
+fn send_batch(url: &str, format: PushFormat, samples: &[PushSample]) -> Result<()> {
+    let (content_type, body) = match format {
+        PushFormat::InfluxLine => ("text/plain; charset=utf-8", format_line_protocol(samples)),
+        PushFormat::Json => ("application/json", format_json(samples)?),
+    };
+    let options = RequestOptions {
+        timeout: Duration::from_millis(HTTP_TIMEOUT_MS),
+        attempts: MAX_ATTEMPTS,
+        ..Default::default()
+    };
+    http_client::post(url, content_type, body.as_bytes(), &options)
+        .with_context(|| format!("push to {} failed", url))?;
+    Ok(())
+}
+
+/// Start the exporter thread. Settings are re-read from config each cycle so
+/// changes made through the web UI apply without a restart. Safe to call multiple times.
+pub fn start(config: Arc<Mutex<Config>>, shutdown: ShutdownSignal) -> Result<()> {
+    static STARTED: AtomicBool = AtomicBool::new(false);
+    if STARTED.swap(true, Ordering::SeqCst) { return Ok(()); }
+
+    std::thread::Builder::new()
+        .name("push-exporter".to_string())
+        .stack_size(8192)
+        .spawn(move || {
+            let mut queue: OfflineQueue<PushSample> = OfflineQueue::new(1, DropPolicy::default());
+            loop {
+                let (enabled, url, format, interval_secs, batch_size, queue_size, drop_policy) = match config.lock() {
+                    Ok(cfg) => (
+                        cfg.push_enabled,
+                        cfg.push_url.clone(),
+                        cfg.push_format,
+                        cfg.push_interval_secs.max(5),
+                        (cfg.push_batch_size.clamp(1, MAX_BATCH_SIZE as u32)) as usize,
+                        cfg.push_queue_size as usize,
+                        cfg.push_drop_policy,
+                    ),
+                    Err(_) => (false, String::new(), PushFormat::InfluxLine, 30, 1, 1, DropPolicy::default()),
+                };
+                queue.configure(queue_size, drop_policy);
+
+                if enabled && !url.is_empty() {
+                    queue.push(PushSample::capture());
+
+                    // Don't burn retries while the link is down; just keep queueing
+                    let online = crate::network::wifi_stats::snapshot().connected;
+                    if online && queue.len() >= batch_size {
+                        // Drain the backlog oldest first, stopping at the first failure
+                        while !queue.is_empty() {
+                            let mut batch = queue.peek_batch(MAX_BATCH_SIZE);
+                            stamp_missing(&mut batch);
+                            match send_batch(&url, format, &batch) {
+                                Ok(()) => queue.ack(batch.len()),
+                                Err(e) if e.downcast_ref::<NetworkError>().is_some_and(|e| !e.is_transient()) => {
+                                    log::warn!("Push exporter: {:#} (dropping {} samples)", e, batch.len());
+                                    queue.ack(batch.len());
+                                }
+                                Err(e) => {
+                                    log::warn!("Push exporter: {:#} (keeping {} samples)", e, queue.len());
+                                    break;
+                                }
+                            }
+                            if shutdown.is_shutdown_requested() {
+                                break;
+                            }
+                        }
+                    }
+                } else if !queue.is_empty() {
+                    queue.clear();
+                }
+                QUEUED.store(queue.len() as u32, Ordering::Relaxed);
+                DROPPED.store(queue.dropped() as u32, Ordering::Relaxed);
+
+                // Sleep in 1s steps so shutdown is noticed promptly
+                for _ in 0..interval_secs {
+                    if shutdown.is_shutdown_requested() {
+                        log::info!("Push exporter stopping");
+                        return;
+                    }
+                    FreeRtos::delay_ms(1000);
+                }
+            }
+        })?;
+
+    log::info!("Push exporter started");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_protocol_format() {
+        let sample = PushSample {
+            unix_ms: Some(1_700_000_000_000),
+            uptime_s: 42,
+            heap_free: 1000,
+            cpu_usage: 12,
+            temperature: 35.5,
+            wifi_rssi: -60,
+            battery_percentage: 80,
+            battery_voltage_mv: 4000,
+            fps: 30.0,
+        };
+        let out = format_line_protocol(&[sample.clone(), PushSample { unix_ms: None, ..sample }]);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("esp32_dashboard,host=esp32 uptime_s=42i,"));
+        assert!(lines[0].contains("temperature=35.50"));
+        assert!(lines[0].ends_with(" 1700000000000"));
+        assert!(lines[1].ends_with("fps=30.0"));
+    }
+}
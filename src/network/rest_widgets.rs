@@ -0,0 +1,174 @@
+// Custom REST widgets: poll user-defined JSON endpoints and extract one value each
+// for the Custom screen. Paths use a small JSONPath subset: `$.a.b[0].c`.
+
+use anyhow::{bail, Result};
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use esp_idf_hal::delay::FreeRtos;
+use serde_json::Value;
+use crate::config::{Config, CustomWidget};
+use crate::system::ShutdownSignal;
+use super::http_client::{self, RequestOptions};
+use super::validators::MAX_CUSTOM_WIDGETS as MAX_WIDGETS;
+
+const MIN_INTERVAL_SECS: u32 = 10;
+const MAX_RESPONSE_BYTES: usize = 8 * 1024;
+const HTTP_TIMEOUT_MS: u64 = 5000;
+
+static VALUES: Mutex<Vec<WidgetValue>> = Mutex::new(Vec::new());
+static GENERATION: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WidgetValue {
+    pub label: String,
+    pub unit: String,
+    /// Extracted value, or None if the last poll failed
+    pub value: Option<String>,
+}
+
+/// Walk `value` along a path like `$.data.items[0].temp` (leading `$` optional)
+pub fn extract<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let path = path.trim();
+    let path = path.strip_prefix('$').unwrap_or(path);
+    let mut current = value;
+    for segment in path.split('.').filter(|s| !s.is_empty()) {
+        // Split "items[0][1]" into key "items" and indices
+        let (key, mut rest) = match segment.find('[') {
+            Some(i) => (&segment[..i], &segment[i..]),
+            None => (segment, ""),
+        };
+        if !key.is_empty() {
+            current = current.get(key)?;
+        }
+        while let Some(stripped) = rest.strip_prefix('[') {
+            let end = stripped.find(']')?;
+            let index: usize = stripped[..end].parse().ok()?;
+            current = current.get(index)?;
+            rest = &stripped[end + 1..];
+        }
+    }
+    Some(current)
+}
+
+/// Render a JSON scalar for display
+pub fn format_value(value: &Value) -> String {
+    match value {
+        Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => i.to_string(),
+            (None, Some(f)) => format!("{:.2}", f),
+            _ => n.to_string(),
+        },
+        Value::String(s) => s.clone(),
+        Value::Bool(b) => if *b { "on".to_string() } else { "off".to_string() },
+        Value::Null => "null".to_string(),
+        _ => "?".to_string(),
+    }
+}
+
+fn http_get(url: &str) -> Result<Vec<u8>> {
+    let options = RequestOptions {
+        timeout: Duration::from_millis(HTTP_TIMEOUT_MS),
+        max_response_bytes: MAX_RESPONSE_BYTES,
+        ..Default::default()
+    };
+    Ok(http_client::get(url, &options)?.body)
+}
+
+fn poll(widget: &CustomWidget) -> Result<String> {
+    let body = http_get(&widget.url)?;
+    let json: Value = serde_json::from_slice(&body)?;
+    match extract(&json, &widget.path) {
+        Some(v) => Ok(format_value(v)),
+        None => bail!("path '{}' not found", widget.path),
+    }
+}
+
+/// Current widget values, in config order
+pub fn values() -> Vec<WidgetValue> {
+    VALUES.lock().map(|v| v.clone()).unwrap_or_default()
+}
+
+/// Increments whenever any widget value changes
+pub fn generation() -> u32 {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+/// Start the polling thread. Widget definitions are re-read from config each
+/// second so edits apply without a restart. Safe to call multiple times.
+pub fn start(config: Arc<Mutex<Config>>, shutdown: ShutdownSignal) -> Result<()> {
+    static STARTED: AtomicBool = AtomicBool::new(false);
+    if STARTED.swap(true, Ordering::SeqCst) { return Ok(()); }
+
+    std::thread::Builder::new()
+        .name("rest-widgets".to_string())
+        .stack_size(10 * 1024)
+        .spawn(move || {
+            let mut last_poll: Vec<Option<Instant>> = vec![None; MAX_WIDGETS];
+            let mut last_defs: Vec<CustomWidget> = Vec::new();
+            while !shutdown.is_shutdown_requested() {
+                let defs: Vec<CustomWidget> = match config.lock() {
+                    Ok(cfg) => cfg.custom_widgets.iter().take(MAX_WIDGETS).cloned().collect(),
+                    Err(_) => Vec::new(),
+                };
+
+                // Reset state when the definitions change
+                if defs != last_defs {
+                    last_poll = vec![None; MAX_WIDGETS];
+                    if let Ok(mut values) = VALUES.lock() {
+                        *values = defs.iter().map(|w| WidgetValue {
+                            label: w.label.clone(),
+                            unit: w.unit.clone(),
+                            value: None,
+                        }).collect();
+                    }
+                    GENERATION.fetch_add(1, Ordering::Relaxed);
+                    last_defs = defs.clone();
+                }
+
+                for (i, widget) in defs.iter().enumerate() {
+                    let interval = Duration::from_secs(widget.interval_secs.max(MIN_INTERVAL_SECS) as u64);
+                    if last_poll[i].is_some_and(|t| t.elapsed() < interval) {
+                        continue;
+                    }
+                    last_poll[i] = Some(Instant::now());
+
+                    let value = match poll(widget) {
+                        Ok(v) => Some(v),
+                        Err(e) => {
+                            log::warn!("Widget '{}' poll failed: {:?}", widget.label, e);
+                            None
+                        }
+                    };
+                    if let Ok(mut values) = VALUES.lock() {
+                        if let Some(slot) = values.get_mut(i) {
+                            if slot.value != value {
+                                slot.value = value;
+                                GENERATION.fetch_add(1, Ordering::Relaxed);
+                            }
+                        }
+                    }
+                }
+
+                FreeRtos::delay_ms(1000);
+            }
+        })?;
+
+    log::info!("REST widget poller started");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_path() {
+        let json: Value = serde_json::from_str(r#"{"data":{"items":[{"temp":21.456},{"temp":3}]},"ok":true}"#).unwrap();
+        assert_eq!(extract(&json, "$.data.items[0].temp").map(format_value), Some("21.46".to_string()));
+        assert_eq!(extract(&json, "data.items[1].temp").map(format_value), Some("3".to_string()));
+        assert_eq!(extract(&json, "$.ok").map(format_value), Some("on".to_string()));
+        assert!(extract(&json, "$.data.items[5]").is_none());
+        assert!(extract(&json, "$.missing").is_none());
+    }
+}
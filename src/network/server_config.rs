@@ -12,7 +12,7 @@ impl StableServerConfig {
         Configuration {
             // Use our tuned values
             stack_size: Self::stack_size(),
-            max_uri_handlers: 80,
+            max_uri_handlers: 96,
             max_open_sockets: Self::max_sockets() as usize,
             max_resp_headers: 12,
             lru_purge_enable: true,
@@ -32,6 +32,8 @@ impl StableServerConfig {
 /// HTTP request instrumentation for diagnostics
 pub struct RequestInstrumentation {
     pub request_id: Option<String>,
+    /// Trace opened for this request; its ID tags the handler's log lines
+    _trace: crate::trace::TraceScope,
     pub start_heap_free: u32,
     pub start_heap_largest: u32,
     pub start_psram_free: u32,
@@ -44,6 +46,7 @@ impl RequestInstrumentation {
         unsafe {
             Self {
                 request_id,
+                _trace: crate::trace::begin(),
                 start_heap_free: esp_idf_sys::esp_get_free_heap_size(),
                 start_heap_largest: esp_idf_sys::heap_caps_get_largest_free_block(
                     esp_idf_sys::MALLOC_CAP_INTERNAL
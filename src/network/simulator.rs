@@ -0,0 +1,100 @@
+// Simulated network for demo builds (`--features demo_mode`). Reports a fixed,
+// always-connected station with a jittering RSSI instead of bringing up WiFi.
+
+use anyhow::Result;
+use esp_idf_hal::modem::Modem;
+use esp_idf_svc::{eventloop::EspSystemEventLoop, timer::EspTaskTimerService};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use crate::config::Config;
+
+const DEMO_SSID: &str = "DemoNet";
+const DEMO_IP: &str = "192.168.4.20";
+const DEMO_IPV6: &str = "2001:db8::20";
+const DEMO_GATEWAY: &str = "192.168.4.1";
+const DEMO_MAC: &str = "02:00:00:DE:00:01";
+const BASE_RSSI: i8 = -58;
+
+/// Signal strength at `t_secs` after start: BASE_RSSI +/-6 dBm, changing once a
+/// second. Uses a fixed integer hash so runs are repeatable.
+pub fn rssi_at(t_secs: u32) -> i8 {
+    let mut x = t_secs.wrapping_mul(0x9E37_79B9) ^ 0x5bd1_e995;
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x2c1b_3c6d);
+    x ^= x >> 12;
+    BASE_RSSI + (x % 13) as i8 - 6
+}
+
+/// Drop-in replacement for NetworkManager when demo_mode is enabled
+pub struct SimulatedNetwork {
+    started: Instant,
+}
+
+impl SimulatedNetwork {
+    /// Same signature as NetworkManager::new; the modem is taken but left idle
+    pub fn new(
+        _modem: Modem,
+        _sys_loop: EspSystemEventLoop,
+        _timer_service: EspTaskTimerService,
+        _ssid: String,
+        _password: String,
+        _config: Arc<Mutex<Config>>,
+    ) -> Result<Self> {
+        log::warn!("Demo mode: using simulated network, WiFi stays off");
+        Ok(Self { started: Instant::now() })
+    }
+
+    pub fn connect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn is_connected(&self) -> bool {
+        true
+    }
+
+    pub fn get_ip(&self) -> Option<String> {
+        Some(DEMO_IP.to_string())
+    }
+
+    pub fn get_ipv6(&self) -> Option<String> {
+        Some(DEMO_IPV6.to_string())
+    }
+
+    pub fn get_ssid(&self) -> &str {
+        DEMO_SSID
+    }
+
+    pub fn get_signal_strength(&self) -> i8 {
+        rssi_at(self.started.elapsed().as_secs() as u32)
+    }
+
+    pub fn get_gateway(&self) -> Option<String> {
+        Some(DEMO_GATEWAY.to_string())
+    }
+
+    pub fn get_mac(&self) -> String {
+        DEMO_MAC.to_string()
+    }
+
+    pub fn get_connection_stats(&self) -> (u32, u32) {
+        (0, 0)
+    }
+
+    // No mDNS in demo builds
+    pub fn set_mdns_paused(&mut self, _paused: bool) {}
+
+    pub fn refresh_mdns(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rssi_jitters_within_range() {
+        let samples: Vec<i8> = (0..300).map(rssi_at).collect();
+        assert!(samples.iter().all(|r| (BASE_RSSI - 6..=BASE_RSSI + 6).contains(r)));
+        assert!(samples.windows(2).any(|w| w[0] != w[1]));
+        assert_eq!(rssi_at(17), rssi_at(17));
+    }
+}
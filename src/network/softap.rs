@@ -0,0 +1,179 @@
+// Fallback access point. With `ap_enabled` the radio runs in APSTA mode: the
+// station joins the home network as usual while a SoftAP with its own SSID
+// serves the same web UI on 192.168.4.1, so the device stays reachable when
+// the home WiFi is down. Once the station link has been stable for
+// `ap_auto_off_mins` the AP is switched off, and it comes back as soon as the
+// station drops.
+
+use std::time::{Duration, Instant};
+use crate::config::Config;
+
+/// Address of the device on its own AP (clients get 192.168.4.x by DHCP)
+pub const AP_IP: [u8; 4] = [192, 168, 4, 1];
+
+/// Most clients the AP accepts at once
+pub const AP_MAX_CLIENTS: u16 = 4;
+
+/// The AP's SSID: the configured one, or `<hostname>-setup` cut to 32 bytes
+pub fn ap_ssid(cfg: &Config) -> String {
+    if cfg.ap_ssid.is_empty() {
+        // Hostnames are ASCII, so any byte is a char boundary
+        let mut ssid = format!("{}-setup", cfg.hostname);
+        ssid.truncate(32);
+        ssid
+    } else {
+        cfg.ap_ssid.clone()
+    }
+}
+
+/// Decides when the AP runs
+#[derive(Debug)]
+pub struct ApPolicy {
+    /// None keeps the AP up for good
+    auto_off: Option<Duration>,
+    /// When the station link came up, if it is up
+    stable_since: Option<Instant>,
+    ap_on: bool,
+}
+
+impl ApPolicy {
+    /// Starts with the AP on; `auto_off_mins` of 0 never switches it off
+    pub fn new(auto_off_mins: u32) -> Self {
+        let auto_off = (auto_off_mins > 0).then(|| Duration::from_secs(auto_off_mins as u64 * 60));
+        Self { auto_off, stable_since: None, ap_on: true }
+    }
+
+    pub fn ap_on(&self) -> bool {
+        self.ap_on
+    }
+
+    /// Feed the station state; returns the new AP state when it should change
+    pub fn update(&mut self, now: Instant, sta_connected: bool) -> Option<bool> {
+        let want = if sta_connected {
+            let since = *self.stable_since.get_or_insert(now);
+            self.ap_on && self.auto_off.is_none_or(|off| now.duration_since(since) < off)
+        } else {
+            self.stable_since = None;
+            true
+        };
+        (want != self.ap_on).then(|| {
+            self.ap_on = want;
+            want
+        })
+    }
+}
+
+#[cfg(target_os = "espidf")]
+mod device {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use esp_idf_svc::wifi::{AccessPointConfiguration, AuthMethod};
+
+    const CHECK_INTERVAL_MS: u32 = 5_000;
+
+    static AP_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+    /// Whether the fallback AP is up right now
+    pub fn is_active() -> bool {
+        AP_ACTIVE.load(Ordering::Relaxed)
+    }
+
+    /// AP half of the APSTA configuration, or None when the AP is disabled
+    pub fn access_point_configuration(cfg: &Config) -> anyhow::Result<Option<AccessPointConfiguration>> {
+        if !cfg.ap_enabled {
+            return Ok(None);
+        }
+        let ssid = ap_ssid(cfg);
+        Ok(Some(AccessPointConfiguration {
+            ssid: ssid.as_str().try_into().map_err(|_| anyhow::anyhow!("Invalid AP SSID: {}", ssid))?,
+            password: cfg.ap_password.as_str().try_into().map_err(|_| anyhow::anyhow!("Invalid AP password"))?,
+            auth_method: if cfg.ap_password.is_empty() { AuthMethod::None } else { AuthMethod::WPA2Personal },
+            max_connections: AP_MAX_CLIENTS,
+            ..Default::default()
+        }))
+    }
+
+    /// Watch the station link and switch the AP on and off; call once the
+    /// radio was configured with `access_point_configuration`
+    pub fn start(config: Arc<Mutex<Config>>) -> anyhow::Result<()> {
+        let (ssid, auto_off_mins) = match config.lock() {
+            Ok(cfg) => (ap_ssid(&cfg), cfg.ap_auto_off_mins),
+            Err(_) => anyhow::bail!("Config lock poisoned"),
+        };
+        AP_ACTIVE.store(true, Ordering::Relaxed);
+        log::info!("Fallback AP '{}' up on {}.{}.{}.{}", ssid, AP_IP[0], AP_IP[1], AP_IP[2], AP_IP[3]);
+
+        std::thread::Builder::new()
+            .name("softap".into())
+            .stack_size(4096)
+            .spawn(move || {
+                let mut policy = ApPolicy::new(auto_off_mins);
+                loop {
+                    esp_idf_hal::delay::FreeRtos::delay_ms(CHECK_INTERVAL_MS);
+                    // The battery profile parks the radio on purpose; leave it alone
+                    if crate::power::duty_cycle::is_radio_parked() {
+                        continue;
+                    }
+                    let connected = unsafe {
+                        let mut ap_info: esp_idf_sys::wifi_ap_record_t = std::mem::zeroed();
+                        esp_idf_sys::esp_wifi_sta_get_ap_info(&mut ap_info) == esp_idf_sys::ESP_OK
+                    };
+                    let Some(on) = policy.update(Instant::now(), connected) else {
+                        continue;
+                    };
+                    let mode = if on {
+                        esp_idf_sys::wifi_mode_t_WIFI_MODE_APSTA
+                    } else {
+                        esp_idf_sys::wifi_mode_t_WIFI_MODE_STA
+                    };
+                    let result = unsafe { esp_idf_sys::esp_wifi_set_mode(mode) };
+                    if result != esp_idf_sys::ESP_OK {
+                        log::warn!("Failed to switch fallback AP {}: {}", if on { "on" } else { "off" }, result);
+                        continue;
+                    }
+                    // Boot-time connect retries may have left the radio stopped
+                    unsafe { esp_idf_sys::esp_wifi_start(); }
+                    AP_ACTIVE.store(on, Ordering::Relaxed);
+                    if on {
+                        log::warn!("Station link down - fallback AP '{}' back on", ssid);
+                    } else {
+                        log::info!("Station stable for {} min - fallback AP off", auto_off_mins);
+                    }
+                }
+            })?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub use device::{access_point_configuration, is_active, start};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ap_follows_station_stability() {
+        let t0 = Instant::now();
+        let mins = |m: u64| t0 + Duration::from_secs(m * 60);
+        let mut policy = ApPolicy::new(10);
+        assert!(policy.ap_on());
+
+        // Stays up until the station has held for 10 minutes
+        assert_eq!(policy.update(t0, true), None);
+        assert_eq!(policy.update(mins(9), true), None);
+        // A drop restarts the clock
+        assert_eq!(policy.update(mins(9), false), None);
+        assert_eq!(policy.update(mins(10), true), None);
+        assert_eq!(policy.update(mins(20), true), Some(false));
+        assert_eq!(policy.update(mins(30), true), None);
+
+        // Back on as soon as the station drops
+        assert_eq!(policy.update(mins(31), false), Some(true));
+
+        let mut always = ApPolicy::new(0);
+        assert_eq!(always.update(t0, true), None);
+        assert_eq!(always.update(mins(600), true), None);
+    }
+}
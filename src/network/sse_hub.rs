@@ -0,0 +1,238 @@
+// SSE client and event bookkeeping for the /api/events, /sse/stats and
+// /sse/logs streams (the socket loop lives in sse_v2.rs).
+//
+// At most `max_clients` streams stay open. A new client evicts the oldest
+// instead of being turned away: the oldest is the one most likely to be a
+// forgotten tab or a dead socket that has not failed a write yet. The evicted
+// handler notices on its next tick, tells the browser to hold off
+// reconnecting and closes.
+//
+// Each stream keeps its last HISTORY_LEN events with increasing ids. Events
+// are produced once per tick for the whole stream, not per client, so every
+// client sees the same ids; a browser that reconnects with Last-Event-ID (or
+// `?last_event_id=` when the page reopens the stream itself) is sent what it
+// missed before live events resume. Ids restart at boot, so an id ahead of
+// the stream means the device restarted and everything kept is replayed.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Comment line sent when nothing else was written for this long, so dead
+/// sockets fail a write and proxies keep the stream open
+pub const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+/// Reconnect delay suggested to browsers (`retry:`)
+pub const RETRY_MS: u32 = 3_000;
+/// Suggested to an evicted client, so two tabs don't keep evicting each other
+pub const EVICTED_RETRY_MS: u32 = 30_000;
+/// Events kept per stream for resuming clients
+pub const HISTORY_LEN: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub id: u64,
+    /// `event:` name; None for the default `message`
+    pub name: Option<&'static str>,
+    pub data: String,
+}
+
+impl Event {
+    /// Wire form; `data` must be a single line (JSON is)
+    pub fn to_wire(&self) -> String {
+        match self.name {
+            Some(name) => format!("id: {}\nevent: {}\ndata: {}\n\n", self.id, name, self.data),
+            None => format!("id: {}\ndata: {}\n\n", self.id, self.data),
+        }
+    }
+}
+
+/// Recent events of one stream
+#[derive(Debug)]
+pub struct EventHistory {
+    events: VecDeque<Event>,
+    next_id: u64,
+    last_tick: Option<Instant>,
+    /// New clients are sent the kept events, not just live ones (logs)
+    replay_on_connect: bool,
+}
+
+impl EventHistory {
+    pub fn new(replay_on_connect: bool) -> Self {
+        Self { events: VecDeque::with_capacity(HISTORY_LEN), next_id: 1, last_tick: None, replay_on_connect }
+    }
+
+    pub fn publish(&mut self, name: Option<&'static str>, data: String) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        if self.events.len() == HISTORY_LEN {
+            self.events.pop_front();
+        }
+        self.events.push_back(Event { id, name, data });
+        id
+    }
+
+    /// Whether the stream should produce events now; the first client to ask
+    /// in each interval gets true and produces for everyone
+    pub fn tick_due(&mut self, now: Instant, interval: Duration) -> bool {
+        let due = self.last_tick.is_none_or(|last| now.duration_since(last) >= interval);
+        if due {
+            self.last_tick = Some(now);
+        }
+        due
+    }
+
+    /// Id of the newest event, 0 before the first
+    pub fn last_id(&self) -> u64 {
+        self.next_id - 1
+    }
+
+    /// Where a new client starts: after its Last-Event-ID when the stream
+    /// still knows it, otherwise live (or from the start of the kept events
+    /// for replaying streams and clients from before a restart)
+    pub fn cursor_for(&self, last_event_id: Option<&str>) -> u64 {
+        match last_event_id.and_then(|id| id.trim().parse::<u64>().ok()) {
+            Some(id) if id <= self.last_id() => id,
+            Some(_) => 0,
+            None if self.replay_on_connect => 0,
+            None => self.last_id(),
+        }
+    }
+
+    /// Events after `cursor`, oldest first
+    pub fn since(&self, cursor: u64) -> Vec<Event> {
+        self.events.iter().filter(|e| e.id > cursor).cloned().collect()
+    }
+}
+
+/// Counters for /metrics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SseStats {
+    pub clients: usize,
+    pub max_clients: usize,
+    pub connections: u32,
+    pub evictions: u32,
+    /// Clients that reconnected with a Last-Event-ID
+    pub resumes: u32,
+}
+
+#[derive(Debug)]
+struct Client {
+    id: u32,
+    evicted: bool,
+}
+
+#[derive(Debug)]
+pub struct ClientTable {
+    clients: Vec<Client>,
+    next_id: u32,
+    max_clients: usize,
+    stats: SseStats,
+}
+
+impl ClientTable {
+    pub fn new(max_clients: usize) -> Self {
+        Self {
+            clients: Vec::new(),
+            next_id: 1,
+            max_clients,
+            stats: SseStats { max_clients, ..SseStats::default() },
+        }
+    }
+
+    /// Register a client; returns its id and the client evicted to make room.
+    /// None when streams are disabled (a limit of zero).
+    pub fn admit(&mut self, resuming: bool) -> Option<(u32, Option<u32>)> {
+        if self.max_clients == 0 {
+            return None;
+        }
+        let evicted = if self.active() >= self.max_clients {
+            let oldest = self.clients.iter_mut().find(|c| !c.evicted)?;
+            oldest.evicted = true;
+            self.stats.evictions += 1;
+            Some(oldest.id)
+        } else {
+            None
+        };
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        self.clients.push(Client { id, evicted: false });
+        self.stats.connections += 1;
+        if resuming {
+            self.stats.resumes += 1;
+        }
+        Some((id, evicted))
+    }
+
+    /// The client was evicted and should close
+    pub fn is_evicted(&self, id: u32) -> bool {
+        self.clients.iter().any(|c| c.id == id && c.evicted)
+    }
+
+    /// The client's handler returned
+    pub fn remove(&mut self, id: u32) {
+        self.clients.retain(|c| c.id != id);
+    }
+
+    fn active(&self) -> usize {
+        self.clients.iter().filter(|c| !c.evicted).count()
+    }
+
+    pub fn stats(&self) -> SseStats {
+        SseStats { clients: self.active(), ..self.stats }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oldest_client_is_evicted() {
+        let mut table = ClientTable::new(2);
+        let (a, none) = table.admit(false).unwrap();
+        let (b, _) = table.admit(false).unwrap();
+        assert_eq!(none, None);
+        let (c, evicted) = table.admit(true).unwrap();
+        assert_eq!(evicted, Some(a));
+        assert!(table.is_evicted(a) && !table.is_evicted(b) && !table.is_evicted(c));
+        // The evicted handler hasn't returned yet but no longer counts
+        assert_eq!(table.stats().clients, 2);
+        table.remove(a);
+        table.remove(b);
+        assert_eq!(table.stats(), SseStats { clients: 1, max_clients: 2, connections: 3, evictions: 1, resumes: 1 });
+        assert!(ClientTable::new(0).admit(false).is_none());
+    }
+
+    #[test]
+    fn test_resume_from_last_event_id() {
+        let mut history = EventHistory::new(false);
+        assert_eq!(history.cursor_for(None), 0);
+        for n in 0..40 {
+            history.publish(None, format!("{{\"n\":{}}}", n));
+        }
+        assert_eq!(history.last_id(), 40);
+        assert!(history.since(history.cursor_for(None)).is_empty(), "new clients get live events only");
+
+        let missed = history.since(history.cursor_for(Some("37")));
+        assert_eq!(missed.iter().map(|e| e.id).collect::<Vec<_>>(), vec![38, 39, 40]);
+        assert_eq!(missed[0].to_wire(), "id: 38\ndata: {\"n\":37}\n\n");
+
+        // Too far behind: everything still kept; from before a restart: the same
+        assert_eq!(history.since(history.cursor_for(Some("2"))).len(), HISTORY_LEN);
+        assert_eq!(history.since(history.cursor_for(Some("9000"))).len(), HISTORY_LEN);
+        assert_eq!(history.cursor_for(Some("junk")), 40);
+
+        let mut logs = EventHistory::new(true);
+        logs.publish(Some("log"), "{}".into());
+        assert_eq!(logs.since(logs.cursor_for(None)).len(), 1);
+        assert_eq!(logs.since(0)[0].to_wire(), "id: 1\nevent: log\ndata: {}\n\n");
+    }
+
+    #[test]
+    fn test_one_producer_per_tick() {
+        let mut history = EventHistory::new(false);
+        let start = Instant::now();
+        assert!(history.tick_due(start, Duration::from_secs(1)));
+        assert!(!history.tick_due(start + Duration::from_millis(500), Duration::from_secs(1)));
+        assert!(history.tick_due(start + Duration::from_secs(1), Duration::from_secs(1)));
+    }
+}
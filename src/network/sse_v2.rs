@@ -6,72 +6,137 @@ use std::time::{Duration, Instant};
 use esp_idf_hal::delay::FreeRtos;
 use log::{info, warn, error};
 
+use crate::network::sse_hub::{self, ClientTable, Event, EventHistory, SseStats};
+
 // SSE configuration constants
-const MAX_SSE_CONNECTIONS: u32 = 1;  // Constrain to 1 to protect sockets/heap during logs viewing
-const SSE_TIMEOUT_SECS: u64 = 300;   // 5 minutes
-const HEARTBEAT_INTERVAL_SECS: u64 = 30;
+const MAX_SSE_CONNECTIONS: usize = 1;  // Constrain to 1 to protect sockets/heap during logs viewing
+const SSE_TIMEOUT_SECS: u64 = 300;   // 5 minutes; browsers reconnect and resume from Last-Event-ID
 const METRICS_UPDATE_INTERVAL_SECS: u64 = 1;
+/// Log lines looked at per tick for /sse/logs
+const LOG_BATCH: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stream {
+    Events,
+    Stats,
+    Logs,
+}
+
+impl Stream {
+    fn path(self) -> &'static str {
+        match self {
+            Self::Events => "/api/events",
+            Self::Stats => "/sse/stats",
+            Self::Logs => "/sse/logs",
+        }
+    }
+}
 
-#[derive(Debug)]
-struct ConnectionInfo {
-    id: u32,
-    _start_time: Instant,
-    _endpoint: String,
+// Clients and per-stream event history, shared by every handler
+struct Hub {
+    clients: ClientTable,
+    events: EventHistory,
+    stats: EventHistory,
+    logs: EventHistory,
+    /// Newest log line already published
+    last_log_ms: u64,
+}
+
+impl Hub {
+    fn history(&mut self, stream: Stream) -> &mut EventHistory {
+        match stream {
+            Stream::Events => &mut self.events,
+            Stream::Stats => &mut self.stats,
+            Stream::Logs => &mut self.logs,
+        }
+    }
+
+    // Publish this tick's events for `stream`
+    fn produce(&mut self, stream: Stream) {
+        match stream {
+            Stream::Events => {
+                if let Some(data) = metrics_event() {
+                    self.events.publish(None, data);
+                }
+            }
+            Stream::Stats => {
+                let data = stats_event();
+                self.stats.publish(Some("stats"), data);
+            }
+            Stream::Logs => {
+                let streamer = crate::network::log_streamer::init(None);
+                let published = self.last_log_ms;
+                for entry in streamer.get_recent_logs(LOG_BATCH).into_iter().filter(|e| e.timestamp > published) {
+                    self.last_log_ms = entry.timestamp;
+                    let data = serde_json::json!({
+                        "level": entry.level,
+                        "module": entry.module.unwrap_or_else(|| "unknown".to_string()),
+                        "msg": entry.message,
+                        "timestamp_ms": entry.timestamp
+                    });
+                    self.logs.publish(Some("log"), data.to_string());
+                }
+            }
+        }
+    }
 }
 
 pub struct SseManager {
-    connections: Arc<Mutex<Vec<ConnectionInfo>>>,
-    next_id: Arc<Mutex<u32>>,
+    hub: Arc<Mutex<Hub>>,
 }
 
 impl SseManager {
     pub fn new() -> Self {
         Self {
-            connections: Arc::new(Mutex::new(Vec::new())),
-            next_id: Arc::new(Mutex::new(1)),
+            hub: Arc::new(Mutex::new(Hub {
+                clients: ClientTable::new(MAX_SSE_CONNECTIONS),
+                events: EventHistory::new(false),
+                stats: EventHistory::new(false),
+                // The logs page starts with the recent lines
+                logs: EventHistory::new(true),
+                last_log_ms: 0,
+            })),
         }
     }
 
-    fn add_connection(&self, endpoint: &str) -> Result<u32> {
-        let mut connections = self.connections.lock().map_err(|e| anyhow::anyhow!("connections lock poisoned: {}", e))?;
-        
-        // Check connection limit
-        if connections.len() >= MAX_SSE_CONNECTIONS as usize {
-            crate::diagnostics::log_sse_event("limit_reject", None);
-            return Err(anyhow::anyhow!("Connection limit reached"));
-        }
-        
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Hub>> {
+        self.hub.lock().map_err(|e| anyhow::anyhow!("SSE hub lock poisoned: {}", e))
+    }
+
+    // Admit a client, evicting the oldest when full; returns its id and
+    // where it starts in the stream
+    fn add_connection(&self, stream: Stream, last_event_id: Option<&str>) -> Result<(u32, u64)> {
         // Check heap before accepting connection
         let free_heap = unsafe { esp_idf_sys::esp_get_free_heap_size() };
         if free_heap < 80 * 1024 { // 80KB minimum
             crate::diagnostics::log_sse_event("heap_reject", None);
             return Err(anyhow::anyhow!("Insufficient heap memory"));
         }
-        
-        let mut next_id = self.next_id.lock().map_err(|e| anyhow::anyhow!("next_id lock poisoned: {}", e))?;
-        let id = *next_id;
-        *next_id += 1;
-        
-        connections.push(ConnectionInfo {
-            id,
-            _start_time: Instant::now(),
-            _endpoint: endpoint.to_string(),
-        });
-        
-        info!("SSE: Connection {} added to {} (total: {})", 
-              id, endpoint, connections.len());
+
+        let mut hub = self.lock()?;
+        let Some((id, evicted)) = hub.clients.admit(last_event_id.is_some()) else {
+            crate::diagnostics::log_sse_event("limit_reject", None);
+            return Err(anyhow::anyhow!("Connection limit reached"));
+        };
+        if let Some(old) = evicted {
+            info!("SSE: Connection {} evicted for connection {}", old, id);
+        }
+        let cursor = hub.history(stream).cursor_for(last_event_id);
+
+        info!("SSE: Connection {} added to {} (total: {}, resuming after event {})",
+              id, stream.path(), hub.clients.stats().clients, cursor);
         crate::diagnostics::log_sse_event("connect", Some(id));
-        Ok(id)
+        Ok((id, cursor))
     }
 
     fn remove_connection(&self, id: u32) {
-        let remaining = match self.connections.lock() {
-            Ok(mut connections) => {
-                connections.retain(|c| c.id != id);
-                connections.len()
+        let remaining = match self.lock() {
+            Ok(mut hub) => {
+                hub.clients.remove(id);
+                hub.clients.stats().clients
             }
             Err(e) => {
-                error!("SSE: connections lock poisoned during remove: {}", e);
+                error!("SSE: {} during remove", e);
                 0
             }
         };
@@ -79,6 +144,23 @@ impl SseManager {
         crate::diagnostics::log_sse_event("disconnect", Some(id));
     }
 
+    // One tick of a client: None once it has been evicted, otherwise the
+    // events after `cursor` (produced first if this tick is due)
+    fn poll(&self, stream: Stream, id: u32, cursor: u64) -> Result<Option<Vec<Event>>> {
+        let mut hub = self.lock()?;
+        if hub.clients.is_evicted(id) {
+            return Ok(None);
+        }
+        if hub.history(stream).tick_due(Instant::now(), Duration::from_secs(METRICS_UPDATE_INTERVAL_SECS)) {
+            hub.produce(stream);
+        }
+        Ok(Some(hub.history(stream).since(cursor)))
+    }
+
+    pub fn stats(&self) -> SseStats {
+        self.lock().map(|hub| hub.clients.stats()).unwrap_or_default()
+    }
+
     pub fn register_endpoints(&self, server: &mut EspHttpServer<'static>) -> Result<()> {
         // Check feature gates before registering
         let features = crate::feature_gates::FeatureStatus::check();
@@ -87,142 +169,15 @@ impl SseManager {
             return Ok(());
         }
 
-        // Register /sse/logs endpoint
-        self.register_logs_endpoint(server)?;
-        
-        // Register /sse/stats endpoint
-        self.register_stats_endpoint(server)?;
-        
-        // Keep /api/events for backward compatibility
-        self.register_events_endpoint(server)?;
-        
-        info!("SSE: All endpoints registered (max {} clients)", features.max_sse_clients);
-        Ok(())
-    }
+        let max_clients = features.max_sse_clients.clamp(1, MAX_SSE_CONNECTIONS);
+        self.lock()?.clients = ClientTable::new(max_clients);
 
-    fn register_logs_endpoint(&self, server: &mut EspHttpServer<'static>) -> Result<()> {
-        let manager = self.clone();
-        
-        server.fn_handler("/sse/logs", Method::Get, move |req| {
-            handle_sse_connection(req, &manager, "logs", |response, heartbeat_count| {
-                // Send only an initial recent batch once to avoid repeated bursts
-                if heartbeat_count == 0 {
-                    let streamer = crate::network::log_streamer::init(None);
-                    let logs = streamer.get_recent_logs(20);
-                    for entry in logs {
-                        let event = format!(
-                            "event: log\ndata: {}\n\n",
-                            serde_json::json!({
-                                "level": entry.level,
-                                "module": entry.module.unwrap_or_else(|| "unknown".to_string()),
-                                "msg": entry.message,
-                                "timestamp_ms": entry.timestamp
-                            })
-                        );
-                        if response.write_all(event.as_bytes()).is_err() {
-                            return Err(anyhow::anyhow!("Failed to write log event"));
-                        }
-                    }
-                }
-                Ok(())
-            })
-        })?;
-        
-        Ok(())
-    }
+        for stream in [Stream::Logs, Stream::Stats, Stream::Events] {
+            let manager = self.clone();
+            server.fn_handler(stream.path(), Method::Get, move |req| handle_sse_connection(req, &manager, stream))?;
+        }
 
-    fn register_stats_endpoint(&self, server: &mut EspHttpServer<'static>) -> Result<()> {
-        let manager = self.clone();
-        
-        server.fn_handler("/sse/stats", Method::Get, move |req| {
-            handle_sse_connection(req, &manager, "stats", |response, _heartbeat_count| {
-                // Send system stats
-                let heap_free = unsafe { esp_idf_sys::esp_get_free_heap_size() };
-                let uptime = unsafe { esp_idf_sys::esp_timer_get_time() } / 1_000_000; // Convert to seconds
-                
-                let event = format!(
-                    "event: stats\ndata: {}\n\n",
-                    serde_json::json!({
-                        "heap": heap_free,
-                        "uptime": uptime,
-                        "count": 1,
-                        "timestamp": std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_secs()
-                    })
-                );
-                
-                if response.write_all(event.as_bytes()).is_err() {
-                    return Err(anyhow::anyhow!("Failed to write stats event"));
-                }
-                
-                Ok(())
-            })
-        })?;
-        
-        Ok(())
-    }
-
-    fn register_events_endpoint(&self, server: &mut EspHttpServer<'static>) -> Result<()> {
-        let manager = self.clone();
-        
-        server.fn_handler("/api/events", Method::Get, move |req| {
-            handle_sse_connection(req, &manager, "events", |response, _heartbeat_count| {
-                // Send comprehensive metrics data for dashboard
-                if let Ok(metrics) = crate::metrics::metrics().try_lock() {
-                    // Get system info
-                    let uptime_ms = unsafe { esp_idf_sys::esp_timer_get_time() / 1000 };
-                    let heap_free = unsafe { esp_idf_sys::esp_get_free_heap_size() };
-                    let psram_free = unsafe { esp_idf_sys::heap_caps_get_free_size(esp_idf_sys::MALLOC_CAP_SPIRAM) };
-                    
-                    // Calculate heap fragmentation
-                    let largest_free = unsafe { esp_idf_sys::heap_caps_get_largest_free_block(esp_idf_sys::MALLOC_CAP_INTERNAL) };
-                    let fragmentation = if heap_free > 0 && largest_free > 0 {
-                        ((1.0 - (largest_free as f32 / heap_free as f32)) * 100.0) as u32
-                    } else {
-                        0
-                    };
-                    
-                    let event = format!(
-                        "data: {}\n\n",
-                        serde_json::json!({
-                            "type": "metrics",
-                            "uptime_ms": uptime_ms,
-                            "temperature": (metrics.temperature * 10.0).round() / 10.0,
-                            "fps_actual": (metrics.fps_actual * 10.0).round() / 10.0,
-                            "temperature_str": format!("{:.1}", (metrics.temperature * 10.0).round() / 10.0),
-                            "fps_actual_str": format!("{:.1}", (metrics.fps_actual * 10.0).round() / 10.0),
-                            "cpu_usage": metrics.cpu_usage,
-                            "cpu0_usage": metrics.cpu0_usage,
-                            "cpu1_usage": metrics.cpu1_usage,
-                            "cpu_freq_mhz": metrics.cpu_freq_mhz,
-                            "wifi_rssi": metrics.wifi_rssi,
-                            "wifi_connected": metrics.wifi_connected,
-                            "wifi_ssid": metrics.wifi_ssid.clone(),
-                            "battery_percentage": metrics.battery_percentage,
-                            "heap_free_kb": heap_free / 1024,
-                            "psram_free_kb": psram_free / 1024,
-                            "heap_fragmentation": fragmentation,
-                            "skip_rate": if metrics.frame_count > 0 {
-                                metrics.skip_count as f32 / metrics.frame_count as f32 * 100.0
-                            } else { 0.0 },
-                            "render_time_ms": metrics.render_time_ms,
-                            // Additional health/diagnostic fields
-                            "reset_reason": crate::system::reset::get_reset_reason(),
-                            "httpd_stack_low_water": crate::network::observability::http_snapshot().httpd_stack_low_water_bytes,
-                            // ip_address intentionally omitted here to avoid stale values
-                        })
-                    );
-                    
-                    if response.write_all(event.as_bytes()).is_err() {
-                        return Err(anyhow::anyhow!("Failed to write metrics event"));
-                    }
-                }
-                Ok(())
-            })
-        })?;
-        
+        info!("SSE: All endpoints registered (max {} clients)", max_clients);
         Ok(())
     }
 }
@@ -230,25 +185,93 @@ impl SseManager {
 impl Clone for SseManager {
     fn clone(&self) -> Self {
         Self {
-            connections: self.connections.clone(),
-            next_id: self.next_id.clone(),
+            hub: self.hub.clone(),
         }
     }
 }
 
+// /api/events payload: comprehensive metrics for the dashboard
+fn metrics_event() -> Option<String> {
+    let metrics = crate::metrics::metrics().try_lock().ok()?;
+    // Get system info
+    let uptime_ms = unsafe { esp_idf_sys::esp_timer_get_time() / 1000 };
+    let heap_free = unsafe { esp_idf_sys::esp_get_free_heap_size() };
+    let psram_free = unsafe { esp_idf_sys::heap_caps_get_free_size(esp_idf_sys::MALLOC_CAP_SPIRAM) };
+
+    // Calculate heap fragmentation
+    let largest_free = unsafe { esp_idf_sys::heap_caps_get_largest_free_block(esp_idf_sys::MALLOC_CAP_INTERNAL) };
+    let fragmentation = if heap_free > 0 && largest_free > 0 {
+        ((1.0 - (largest_free as f32 / heap_free as f32)) * 100.0) as u32
+    } else {
+        0
+    };
+
+    Some(serde_json::json!({
+        "type": "metrics",
+        "uptime_ms": uptime_ms,
+        "temperature": (metrics.temperature * 10.0).round() / 10.0,
+        "fps_actual": (metrics.fps_actual * 10.0).round() / 10.0,
+        "temperature_str": format!("{:.1}", (metrics.temperature * 10.0).round() / 10.0),
+        "fps_actual_str": format!("{:.1}", (metrics.fps_actual * 10.0).round() / 10.0),
+        "cpu_usage": metrics.cpu_usage,
+        "cpu0_usage": metrics.cpu0_usage,
+        "cpu1_usage": metrics.cpu1_usage,
+        "cpu_freq_mhz": metrics.cpu_freq_mhz,
+        "wifi_rssi": metrics.wifi_rssi,
+        "wifi_connected": metrics.wifi_connected,
+        "wifi_ssid": metrics.wifi_ssid.clone(),
+        "battery_percentage": metrics.battery_percentage,
+        "heap_free_kb": heap_free / 1024,
+        "psram_free_kb": psram_free / 1024,
+        "heap_fragmentation": fragmentation,
+        "skip_rate": if metrics.frame_count > 0 {
+            metrics.skip_count as f32 / metrics.frame_count as f32 * 100.0
+        } else { 0.0 },
+        "render_time_ms": metrics.render_time_ms,
+        // Additional health/diagnostic fields
+        "reset_reason": crate::system::reset::get_reset_reason(),
+        "httpd_stack_low_water": crate::network::observability::http_snapshot().httpd_stack_low_water_bytes,
+        // ip_address intentionally omitted here to avoid stale values
+    }).to_string())
+}
+
+// /sse/stats payload
+fn stats_event() -> String {
+    let heap_free = unsafe { esp_idf_sys::esp_get_free_heap_size() };
+    let uptime = unsafe { esp_idf_sys::esp_timer_get_time() } / 1_000_000; // Convert to seconds
+    serde_json::json!({
+        "heap": heap_free,
+        "uptime": uptime,
+        "count": 1,
+        "timestamp": std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    }).to_string()
+}
+
+// Last-Event-ID header (browser auto-reconnect) or `?last_event_id=` (pages
+// that reopen the stream themselves)
+fn last_event_id(req: &esp_idf_svc::http::server::Request<&mut esp_idf_svc::http::server::EspHttpConnection>) -> Option<String> {
+    if let Some(id) = req.header("Last-Event-ID") {
+        return Some(id.to_string());
+    }
+    req.uri().split('?').nth(1)?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("last_event_id="))
+        .map(str::to_string)
+}
+
 // Generic SSE connection handler
-fn handle_sse_connection<F>(
+fn handle_sse_connection(
     req: esp_idf_svc::http::server::Request<&mut esp_idf_svc::http::server::EspHttpConnection>,
     manager: &SseManager,
-    endpoint_name: &str,
-    mut data_sender: F,
-) -> Result<(), Box<dyn std::error::Error>>
-where
-    F: FnMut(&mut esp_idf_svc::http::server::Response<&mut esp_idf_svc::http::server::EspHttpConnection>, u32) -> Result<()>,
-{
+    stream: Stream,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Try to add connection
-    let conn_id = match manager.add_connection(&format!("/sse/{}", endpoint_name)) {
-        Ok(id) => id,
+    let last_event_id = last_event_id(&req);
+    let (conn_id, mut cursor) = match manager.add_connection(stream, last_event_id.as_deref()) {
+        Ok(admitted) => admitted,
         Err(e) => {
             warn!("SSE: Connection rejected: {}", e);
             let mut response = req.into_status_response(503)?;
@@ -271,10 +294,10 @@ where
     
     let mut response = req.into_response(200, Some("OK"), &headers)?;
     
-    // Send initial connection event with a soft timeout guard
+    // Send the reconnect delay and initial connection event
     let init_event = format!(
-        "event: connected\ndata: {{\"connection_id\":{}}}\n\n",
-        conn_id
+        "retry: {}\nevent: connected\ndata: {{\"connection_id\":{}}}\n\n",
+        sse_hub::RETRY_MS, conn_id
     );
     // attempt initial write, if it fails, exit early
     safe_write(&mut response, init_event.as_bytes())?;
@@ -282,42 +305,55 @@ where
     
     // Main event loop
     let start_time = Instant::now();
-    let mut last_update = Instant::now();
-    let mut heartbeat_count = 0u32;
+    let mut last_write = Instant::now();
     
     loop {
+        // Tell the client before the web server goes away
+        if let Some(reason) = crate::system::shutdown::pending_restart() {
+            let event = format!("event: restart\ndata: {}\n\n", serde_json::json!({ "reason": reason.label() }));
+            let _ = safe_write(&mut response, event.as_bytes());
+            let _ = response.flush();
+            info!("SSE: Connection {} closed for restart", conn_id);
+            break;
+        }
+
         // Check timeout
         if start_time.elapsed() > Duration::from_secs(SSE_TIMEOUT_SECS) {
             info!("SSE: Connection {} timeout after 5 minutes", conn_id);
             break;
         }
-        
-        // Send data updates every second
-        if last_update.elapsed() >= Duration::from_secs(METRICS_UPDATE_INTERVAL_SECS) {
-            match data_sender(&mut response, heartbeat_count) {
-                Ok(_) => {
-                    if response.flush().is_err() {
-                        break;
-                    }
-                }
-                Err(e) => {
-                    error!("SSE: Data send error: {}", e);
-                    break;
-                }
+
+        let events = match manager.poll(stream, conn_id, cursor) {
+            Ok(Some(events)) => events,
+            Ok(None) => {
+                // Evicted for a newer client; ask the browser to wait before coming back
+                let event = format!("retry: {}\nevent: evicted\ndata: {{}}\n\n", sse_hub::EVICTED_RETRY_MS);
+                let _ = safe_write(&mut response, event.as_bytes());
+                let _ = response.flush();
+                info!("SSE: Connection {} closed after eviction", conn_id);
+                break;
             }
-            last_update = Instant::now();
-        }
-        
-        // Send heartbeat every 30 seconds
-        heartbeat_count += 1;
-        if heartbeat_count >= HEARTBEAT_INTERVAL_SECS as u32 {
-            heartbeat_count = 0;
-            if safe_write(&mut response, b":heartbeat\n\n").is_err() {
+            Err(e) => {
+                error!("SSE: {}", e);
+                break;
+            }
+        };
+
+        if !events.is_empty() {
+            if events.iter().any(|event| safe_write(&mut response, event.to_wire().as_bytes()).is_err()) {
                 break;
             }
+            cursor = events.last().map_or(cursor, |event| event.id);
             if response.flush().is_err() {
                 break;
             }
+            last_write = Instant::now();
+        } else if last_write.elapsed() >= sse_hub::KEEP_ALIVE_INTERVAL {
+            // Nothing to send: a comment finds dead sockets and keeps proxies open
+            if safe_write(&mut response, b": keep-alive\n\n").is_err() || response.flush().is_err() {
+                break;
+            }
+            last_write = Instant::now();
         }
         
         // Sleep to prevent CPU hogging
@@ -368,4 +404,9 @@ static SSE_MANAGER: OnceLock<Arc<SseManager>> = OnceLock::new();
 
 pub fn init() -> Arc<SseManager> {
     SSE_MANAGER.get_or_init(|| Arc::new(SseManager::new())).clone()
-}
\ No newline at end of file
+}
+
+/// Client counters for /metrics (zero before the web server starts)
+pub fn stats() -> SseStats {
+    SSE_MANAGER.get().map(|manager| manager.stats()).unwrap_or_default()
+}
@@ -126,6 +126,14 @@ pub fn handle_dashboard_streaming(req: Request<&mut EspHttpConnection>) -> Resul
                     <input type="checkbox" id="dc_autodim" />
                     <label for="dc_autodim">Auto-dim when idle</label>
                 </div>
+                <div class="cpu-core" style="display:flex; align-items:center; gap:.5rem">
+                    <input type="checkbox" id="dc_shift" />
+                    <label for="dc_shift">Shift UI to prevent burn-in</label>
+                </div>
+                <div class="cpu-core" style="display:flex; align-items:center; gap:.5rem">
+                    <input type="checkbox" id="dc_saver" />
+                    <label for="dc_saver">Screensaver instead of dimming</label>
+                </div>
                 <div class="cpu-core">
                     <div class="core-label">Performance Mode</div>
                     <select id="dc_mode">
@@ -170,6 +178,8 @@ pub fn handle_dashboard_streaming(req: Request<&mut EspHttpConnection>) -> Resul
     <script>
     // Minimal dashboard functionality
     let updateInterval;
+    let tempUnit = 'celsius';
+    fetch('/api/config').then(r => r.json()).then(c => { tempUnit = c.temperature_unit || 'celsius'; }).catch(() => {});
     
     async function updateDashboard() {
         try {
@@ -193,7 +203,9 @@ pub fn handle_dashboard_streaming(req: Request<&mut EspHttpConnection>) -> Resul
             
             // Update metrics
             document.getElementById('cpu').textContent = metrics.cpu_usage.toFixed(1) + '%';
-            document.getElementById('temp').textContent = metrics.temperature.toFixed(1) + '\u00B0C';
+            document.getElementById('temp').textContent = tempUnit === 'fahrenheit'
+                ? (metrics.temperature * 9 / 5 + 32).toFixed(1) + '\u00B0F'
+                : metrics.temperature.toFixed(1) + '\u00B0C';
             document.getElementById('rssi').textContent = metrics.wifi_rssi + ' dBm';
             document.getElementById('fps').textContent = metrics.fps_actual.toFixed(1) + ' fps';
             document.getElementById('render').textContent = metrics.render_time_ms + ' ms';
@@ -282,9 +294,15 @@ pub fn handle_dashboard_css(req: Request<&mut EspHttpConnection>) -> Result<(),
 /* Additional styles would go here */
 "#;
     
+    let etag = crate::network::etag::ASSETS;
+    if crate::network::etag::is_fresh(&req, etag) {
+        return crate::network::etag::send_not_modified(req, etag, &[("Cache-Control", "public, max-age=3600")]);
+    }
+
     let headers = [
         ("Content-Type", "text/css"),
         ("Cache-Control", "public, max-age=3600"),
+        ("ETag", etag),
     ];
     
     let mut response = req.into_response(200, Some("OK"), &headers)?;
@@ -295,10 +313,17 @@ pub fn handle_dashboard_css(req: Request<&mut EspHttpConnection>) -> Result<(),
 
 /// Handle the enhanced dashboard with SSE support and modern UI
 pub fn handle_dashboard_enhanced(req: Request<&mut EspHttpConnection>) -> Result<(), Box<dyn std::error::Error>> {
-    // Send response headers first
+    // Send response headers first; a browser holding this build's page just revalidates
+    let cookie = crate::network::csrf::session_cookie(&req);
+    let etag = crate::network::etag::ASSETS;
+    if crate::network::etag::is_fresh(&req, etag) {
+        return crate::network::etag::send_not_modified(req, etag, &[("Cache-Control", "no-cache"), ("Set-Cookie", cookie.as_str())]);
+    }
     let headers = [
         ("Content-Type", "text/html; charset=utf-8"),
         ("Cache-Control", "no-cache"),
+        ("Set-Cookie", cookie.as_str()),
+        ("ETag", etag),
     ];
     
     let mut response = req.into_response(200, Some("OK"), &headers)?;
@@ -354,6 +379,10 @@ pub fn handle_dashboard_enhanced(req: Request<&mut EspHttpConnection>) -> Result
             line-height: 1.5;
         }
     </style>
+"#)?;
+    response.write_all(crate::templates::CSRF_SCRIPT.as_bytes())?;
+    response.write_all(crate::templates::THEME_SCRIPT.as_bytes())?;
+    response.write_all(br#"
 </head>
 <body>
 "#)?;
@@ -363,13 +392,9 @@ pub fn handle_dashboard_enhanced(req: Request<&mut EspHttpConnection>) -> Result
     <nav class="navbar">
         <div class="nav-brand">ESP32-S3 Dashboard</div>
         <div class="nav-links">
-            <!-- Global Navbar: Control merged into Dashboard -->
-            <a href="/">Home</a>
-            <a href="/dashboard" class="active">Dashboard</a>
-            <a href="/logs">Logs</a>
-            <a href="/files">Files</a>
-            <a href="/ota">Update</a>
-            <a href="/dev">Dev Tools</a>
+"#)?;
+    response.write_all(crate::templates::render_nav_links("/dashboard").as_bytes())?;
+    response.write_all(br#"
         </div>
         <div style="display:flex; gap:.5rem; align-items:center">
             <button class="theme-toggle" id="themeToggle" title="Toggle theme">
@@ -508,6 +533,46 @@ pub fn handle_dashboard_enhanced(req: Request<&mut EspHttpConnection>) -> Result
                         <span class="network-value" id="sse-status">Disconnected</span>
                     </div>
                 </div>
+                <div class="controls">
+                    <div class="cpu-core">
+                        <div class="core-label">WiFi network (applies after restart)</div>
+                        <div style="display:flex; gap:.5rem">
+                            <input id="net_ssid" type="text" maxlength="32" style="flex:1" />
+                            <button id="net_scan" class="theme-toggle">Scan</button>
+                        </div>
+                        <select id="net_networks" style="width:100%; margin-top:.5rem; display:none"></select>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Password (leave empty to keep)</div>
+                        <input id="net_password" type="password" maxlength="64" autocomplete="new-password" style="width:100%" />
+                    </div>
+                    <div class="cpu-core" style="display:flex; gap:.5rem; flex-wrap:wrap">
+                        <button id="net_wifi_save" class="theme-toggle">Save WiFi</button>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Hostname (.local, applies after restart)</div>
+                        <input id="net_hostname" type="text" maxlength="32" pattern="[a-z0-9]([a-z0-9-]*[a-z0-9])?" style="width:100%" />
+                    </div>
+                    <div class="cpu-core" style="display:flex; gap:.5rem; flex-wrap:wrap">
+                        <button id="net_save" class="theme-toggle">Save Hostname</button>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Throughput test</div>
+                        <div style="display:flex; gap:.5rem; align-items:center">
+                            <button id="net_speedtest" class="theme-toggle">Run Test</button>
+                            <span id="net_speedtest_result" class="perf-value">--</span>
+                        </div>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Connection history</div>
+                        <div id="net_pinned" class="core-label">Any access point</div>
+                        <div id="net_connections" style="font-size:.8rem; max-height:10rem; overflow-y:auto"></div>
+                        <div style="display:flex; gap:.5rem; margin-top:.5rem">
+                            <button id="net_pin" class="theme-toggle">Pin Current AP</button>
+                            <button id="net_unpin" class="theme-toggle">Unpin</button>
+                        </div>
+                    </div>
+                </div>
             </div>
             <div class="metric-card">
                 <h3>System Health</h3>
@@ -541,6 +606,14 @@ pub fn handle_dashboard_enhanced(req: Request<&mut EspHttpConnection>) -> Result
                         <input type="checkbox" id="dc_autodim" />
                         <label for="dc_autodim">Auto-dim when idle</label>
                     </div>
+                    <div class="cpu-core" style="display:flex; align-items:center; gap:.5rem">
+                        <input type="checkbox" id="dc_shift" />
+                        <label for="dc_shift">Shift UI to prevent burn-in</label>
+                    </div>
+                    <div class="cpu-core" style="display:flex; align-items:center; gap:.5rem">
+                        <input type="checkbox" id="dc_saver" />
+                        <label for="dc_saver">Screensaver instead of dimming</label>
+                    </div>
                     <div class="cpu-core">
                         <div class="core-label">Performance Mode</div>
                         <select id="dc_mode">
@@ -549,6 +622,14 @@ pub fn handle_dashboard_enhanced(req: Request<&mut EspHttpConnection>) -> Result
                             <option value="turbo">Turbo</option>
                         </select>
                     </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Theme (display and web)</div>
+                        <select id="dc_theme">
+                            <option value="Auto">Auto (follow browser)</option>
+                            <option value="Dark">Dark</option>
+                            <option value="Light">Light</option>
+                        </select>
+                    </div>
                     <div class="cpu-core">
                         <div class="core-label">Dim Timeout <span id="dc_dim_val" style="float:right">--</span></div>
                         <input id="dc_dim_timeout" type="range" min="10" max="300" step="10" value="30" />
@@ -563,6 +644,363 @@ pub fn handle_dashboard_enhanced(req: Request<&mut EspHttpConnection>) -> Result
                     </div>
                 </div>
             </div>
+            <div class="metric-card">
+                <h3>Metrics Push</h3>
+                <div class="controls">
+                    <div class="cpu-core" style="display:flex; align-items:center; gap:.5rem">
+                        <input type="checkbox" id="mp_enabled" />
+                        <label for="mp_enabled">Push metrics to URL</label>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Endpoint URL</div>
+                        <input id="mp_url" type="url" placeholder="http://influx.local:8086/write?db=esp32&precision=ms" style="width:100%" />
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Format</div>
+                        <select id="mp_format">
+                            <option value="influx_line">InfluxDB line protocol</option>
+                            <option value="json">JSON</option>
+                        </select>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Interval <span id="mp_interval_val" style="float:right">--</span></div>
+                        <input id="mp_interval" type="range" min="5" max="300" step="5" value="30" />
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Samples per batch <span id="mp_batch_val" style="float:right">--</span></div>
+                        <input id="mp_batch" type="range" min="1" max="20" step="1" value="4" />
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">When offline queue is full, drop</div>
+                        <select id="mp_drop">
+                            <option value="oldest">Oldest samples</option>
+                            <option value="newest">Newest samples</option>
+                        </select>
+                    </div>
+                    <div class="cpu-core" style="display:flex; gap:.5rem; flex-wrap:wrap">
+                        <button id="mp_save" class="theme-toggle">Save Push Settings</button>
+                    </div>
+                </div>
+            </div>
+            <div class="metric-card">
+                <h3>Units</h3>
+                <div class="controls">
+                    <div class="cpu-core">
+                        <div class="core-label">Temperature</div>
+                        <select id="un_temp" style="width:100%">
+                            <option value="celsius">Celsius (&deg;C)</option>
+                            <option value="fahrenheit">Fahrenheit (&deg;F)</option>
+                        </select>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Clock</div>
+                        <select id="un_clock" style="width:100%">
+                            <option value="24h">24-hour</option>
+                            <option value="12h">12-hour</option>
+                        </select>
+                    </div>
+                    <div class="cpu-core" style="display:flex; gap:.5rem; flex-wrap:wrap">
+                        <button id="un_save" class="theme-toggle">Save Units</button>
+                    </div>
+                </div>
+            </div>
+            <div class="metric-card">
+                <h3>Accessibility</h3>
+                <div class="controls">
+                    <div class="cpu-core" style="display:flex; align-items:center; gap:.5rem">
+                        <input type="checkbox" id="ax_enabled" />
+                        <label for="ax_enabled">Large text and high contrast on the device screens</label>
+                    </div>
+                </div>
+            </div>
+            <div class="metric-card">
+                <h3>Kiosk Mode</h3>
+                <div class="controls">
+                    <div class="cpu-core" style="display:flex; align-items:center; gap:.5rem">
+                        <input type="checkbox" id="ki_enabled" />
+                        <label for="ki_enabled">Show one metric full screen (wall gauge)</label>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Metric: temperature, battery, a sensor key (scd40.co2) or widget:&lt;label&gt;</div>
+                        <input id="ki_metric" type="text" placeholder="temperature" list="ki_metrics" />
+                        <datalist id="ki_metrics"><option value="temperature"><option value="battery"></datalist>
+                    </div>
+                    <div class="cpu-core" style="display:flex; gap:.5rem; flex-wrap:wrap">
+                        <button id="ki_save" class="theme-toggle">Save Kiosk Mode</button>
+                    </div>
+                </div>
+            </div>
+            <div class="metric-card">
+                <h3>Quiet Hours</h3>
+                <div class="controls">
+                    <div class="cpu-core" style="display:flex; align-items:center; gap:.5rem">
+                        <input type="checkbox" id="qh_enabled" />
+                        <label for="qh_enabled">Display off and notifications silenced (needs time sync)</label>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">From</div>
+                        <input id="qh_start" type="time" style="width:100%" />
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Until</div>
+                        <input id="qh_end" type="time" style="width:100%" />
+                    </div>
+                    <div class="cpu-core" style="display:flex; align-items:center; gap:.5rem">
+                        <input type="checkbox" id="qh_override" />
+                        <label for="qh_override">Stay on tonight</label>
+                        <span id="qh_status" class="core-label"></span>
+                    </div>
+                    <div class="cpu-core" style="display:flex; gap:.5rem; flex-wrap:wrap">
+                        <button id="qh_save" class="theme-toggle">Save Quiet Hours</button>
+                    </div>
+                </div>
+            </div>
+            <div class="metric-card">
+                <h3>OTA Maintenance Window</h3>
+                <div class="controls">
+                    <div class="cpu-core" style="display:flex; align-items:center; gap:.5rem">
+                        <input type="checkbox" id="ow_enabled" />
+                        <label for="ow_enabled">Stage pushed updates and apply them in this window (needs time sync)</label>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">From</div>
+                        <input id="ow_start" type="time" style="width:100%" />
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Until</div>
+                        <input id="ow_end" type="time" style="width:100%" />
+                    </div>
+                    <div class="cpu-core" style="display:flex; gap:.5rem; flex-wrap:wrap; align-items:center">
+                        <button id="ow_save" class="theme-toggle">Save Window</button>
+                        <span id="ow_status" class="core-label"></span>
+                    </div>
+                </div>
+            </div>
+            <div class="metric-card">
+                <h3>Weather</h3>
+                <div class="controls">
+                    <div class="cpu-core" style="display:flex; align-items:center; gap:.5rem">
+                        <input type="checkbox" id="wx_enabled" />
+                        <label for="wx_enabled">Show weather (Open-Meteo)</label>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Latitude</div>
+                        <input id="wx_lat" type="number" min="-90" max="90" step="0.0001" style="width:100%" />
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Longitude</div>
+                        <input id="wx_lon" type="number" min="-180" max="180" step="0.0001" style="width:100%" />
+                    </div>
+                    <div class="cpu-core" style="display:flex; gap:.5rem; flex-wrap:wrap">
+                        <button id="wx_save" class="theme-toggle">Save Weather Settings</button>
+                    </div>
+                </div>
+            </div>
+            <div class="metric-card">
+                <h3>Buzzer</h3>
+                <div class="controls">
+                    <div class="cpu-core">
+                        <div class="core-label">Piezo GPIO (0 = none, applies after restart)</div>
+                        <input id="bz_gpio" type="number" min="0" max="48" style="width:100%" />
+                    </div>
+                    <div class="cpu-core" style="display:flex; align-items:center; gap:.5rem">
+                        <input type="checkbox" id="bz_muted" />
+                        <label for="bz_muted">Mute</label>
+                    </div>
+                    <div class="cpu-core" id="bz_events" style="display:flex; flex-wrap:wrap; gap:.75rem">
+                        <label><input type="checkbox" data-sound="button_click" /> Button click</label>
+                        <label><input type="checkbox" data-sound="ota_complete" /> OTA complete</label>
+                        <label><input type="checkbox" data-sound="alert_fired" /> Alert fired</label>
+                        <label><input type="checkbox" data-sound="timer_done" /> Timer done</label>
+                    </div>
+                    <div class="cpu-core" style="display:flex; gap:.5rem; flex-wrap:wrap">
+                        <button id="bz_save" class="theme-toggle">Save Buzzer Settings</button>
+                        <button id="bz_test" class="theme-toggle">Test</button>
+                    </div>
+                </div>
+            </div>
+            <div class="metric-card">
+                <h3>Status LEDs</h3>
+                <div class="controls">
+                    <div class="cpu-core" style="display:flex; align-items:center; gap:.5rem">
+                        <input type="checkbox" id="led_enabled" />
+                        <label for="led_enabled">Show status on WS2812 LEDs</label>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Data GPIO (0 = none) / LED count, apply after restart</div>
+                        <div style="display:flex; gap:.5rem">
+                            <input id="led_gpio" type="number" min="0" max="48" style="width:50%" />
+                            <input id="led_count" type="number" min="1" max="64" style="width:50%" />
+                        </div>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Brightness <span id="led_brightness_val" style="float:right">--</span></div>
+                        <input id="led_brightness" type="range" min="0" max="255" step="1" value="64" />
+                    </div>
+                    <div class="cpu-core" style="display:flex; gap:.5rem; flex-wrap:wrap">
+                        <button id="led_save" class="theme-toggle">Save LED Settings</button>
+                    </div>
+                </div>
+            </div>
+            <div class="metric-card">
+                <h3>Power Monitor</h3>
+                <div class="controls">
+                    <div class="cpu-core">
+                        <div class="core-label">INA219 SDA / SCL GPIO (0 = none), apply after restart</div>
+                        <div style="display:flex; gap:.5rem">
+                            <input id="pm_sda" type="number" min="0" max="48" style="width:50%" />
+                            <input id="pm_scl" type="number" min="0" max="48" style="width:50%" />
+                        </div>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">I2C address / shunt (m&Omega;)</div>
+                        <div style="display:flex; gap:.5rem">
+                            <input id="pm_address" type="text" placeholder="0x40" style="width:50%" />
+                            <input id="pm_shunt" type="number" min="1" max="65535" style="width:50%" />
+                        </div>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Now <span id="pm_reading" style="float:right">--</span></div>
+                    </div>
+                    <div class="cpu-core" style="display:flex; gap:.5rem; flex-wrap:wrap">
+                        <button id="pm_save" class="theme-toggle">Save Power Monitor</button>
+                    </div>
+                </div>
+            </div>
+            <div class="metric-card">
+                <h3>Air Quality</h3>
+                <div class="controls">
+                    <div class="cpu-core">
+                        <div class="core-label">SCD40 / SGP30 SDA / SCL GPIO (0 = none), apply after restart</div>
+                        <div style="display:flex; gap:.5rem">
+                            <input id="aq_sda" type="number" min="0" max="48" style="width:50%" />
+                            <input id="aq_scl" type="number" min="0" max="48" style="width:50%" />
+                        </div>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Now <span id="aq_reading" style="float:right">--</span></div>
+                    </div>
+                    <div class="cpu-core" style="display:flex; gap:.5rem; flex-wrap:wrap">
+                        <button id="aq_save" class="theme-toggle">Save Air Quality</button>
+                    </div>
+                </div>
+            </div>
+            <div class="metric-card">
+                <h3>Sampling</h3>
+                <div class="controls">
+                    <div class="cpu-core">
+                        <div class="core-label">Sensors (ms)</div>
+                        <div style="display:flex; align-items:center; gap:.5rem">
+                            <input type="checkbox" data-source="sensors" />
+                            <input id="ss_sensors" type="number" min="500" max="3600000" style="width:100%" />
+                        </div>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">WiFi monitor (ms)</div>
+                        <div style="display:flex; align-items:center; gap:.5rem">
+                            <input type="checkbox" data-source="network" />
+                            <input id="ss_network" type="number" min="1000" max="3600000" style="width:100%" />
+                        </div>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Processing (always on) (ms)</div>
+                        <div style="display:flex; align-items:center; gap:.5rem">
+                            <input type="checkbox" data-source="processing" disabled />
+                            <input id="ss_processing" type="number" min="20" max="5000" style="width:100%" />
+                        </div>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Latency pings (ms)</div>
+                        <div style="display:flex; align-items:center; gap:.5rem">
+                            <input type="checkbox" data-source="latency" />
+                            <input id="ss_latency" type="number" min="5000" max="3600000" style="width:100%" />
+                        </div>
+                    </div>
+                    <div class="cpu-core" style="display:flex; gap:.5rem; flex-wrap:wrap">
+                        <button id="ss_save" class="theme-toggle">Save Sampling</button>
+                    </div>
+                </div>
+            </div>
+            <div class="metric-card">
+                <h3>Frame Pacing</h3>
+                <div class="controls">
+                    <div class="cpu-core">
+                        <div class="core-label">Target FPS (0 = uncapped; the panel manages about 10)</div>
+                        <input id="fp_fps" type="number" min="0" max="60" style="width:100%" />
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Wait strategy</div>
+                        <select id="fp_strategy" style="width:100%">
+                            <option value="sleep">Sleep (yields to WiFi and web server)</option>
+                            <option value="hybrid">Hybrid (sleep, then busy-wait the last 2 ms)</option>
+                        </select>
+                    </div>
+                    <div class="cpu-core" style="display:flex; gap:.5rem; flex-wrap:wrap">
+                        <button id="fp_save" class="theme-toggle">Save Frame Pacing</button>
+                    </div>
+                </div>
+            </div>
+            <div class="metric-card">
+                <h3>Day/Night Profiles</h3>
+                <div class="controls">
+                    <div class="cpu-core" style="display:flex; align-items:center; gap:.5rem">
+                        <input type="checkbox" id="pf_enabled" />
+                        <label for="pf_enabled">Switch profiles by time of day</label>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Day starts / Night starts</div>
+                        <div style="display:flex; gap:.5rem">
+                            <input id="pf_day_start" type="time" style="width:50%" />
+                            <input id="pf_night_start" type="time" style="width:50%" />
+                        </div>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Timezone (POSIX TZ)</div>
+                        <input id="pf_tz" type="text" maxlength="64" placeholder="CET-1CEST,M3.5.0,M10.5.0/3" style="width:100%" />
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Day brightness <span id="pf_day_brightness_val" style="float:right">--</span></div>
+                        <input id="pf_day_brightness" type="range" min="0" max="255" step="1" value="128" />
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Day theme</div>
+                        <select id="pf_day_theme">
+                            <option value="Dark">Dark</option>
+                            <option value="Light">Light</option>
+                            <option value="Auto">Auto</option>
+                        </select>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Day dim timeout (s) / push interval (s)</div>
+                        <div style="display:flex; gap:.5rem">
+                            <input id="pf_day_dim" type="number" min="5" max="3600" style="width:50%" />
+                            <input id="pf_day_push" type="number" min="5" max="3600" style="width:50%" />
+                        </div>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Night brightness <span id="pf_night_brightness_val" style="float:right">--</span></div>
+                        <input id="pf_night_brightness" type="range" min="0" max="255" step="1" value="128" />
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Night theme</div>
+                        <select id="pf_night_theme">
+                            <option value="Dark">Dark</option>
+                            <option value="Light">Light</option>
+                            <option value="Auto">Auto</option>
+                        </select>
+                    </div>
+                    <div class="cpu-core">
+                        <div class="core-label">Night dim timeout (s) / push interval (s)</div>
+                        <div style="display:flex; gap:.5rem">
+                            <input id="pf_night_dim" type="number" min="5" max="3600" style="width:50%" />
+                            <input id="pf_night_push" type="number" min="5" max="3600" style="width:50%" />
+                        </div>
+                    </div>
+                    <div class="cpu-core" style="display:flex; gap:.5rem; flex-wrap:wrap">
+                        <button id="pf_save" class="theme-toggle">Save Profiles</button>
+                    </div>
+                </div>
+            </div>
             <div class="metric-card">
                 <h3>Battery</h3>
                 <div class="network-info">
@@ -735,22 +1173,15 @@ pub fn handle_dashboard_enhanced(req: Request<&mut EspHttpConnection>) -> Result
     // Part 10: JavaScript for updates
     response.write_all(br#"
     <script>
-        // Theme toggle
+        // Theme toggle and picker, both stored on the device
         const themeToggle = document.getElementById('themeToggle');
-        const root = document.documentElement;
-        
-        // Load saved theme
-        const savedTheme = localStorage.getItem('theme') || 'dark';
-        root.setAttribute('data-theme', savedTheme);
-        themeToggle.textContent = savedTheme === 'dark' ? 'Dark' : 'Light';
-        
-        themeToggle.addEventListener('click', () => {
-            const currentTheme = root.getAttribute('data-theme');
-            const newTheme = currentTheme === 'dark' ? 'light' : 'dark';
-            root.setAttribute('data-theme', newTheme);
-            localStorage.setItem('theme', newTheme);
-            themeToggle.textContent = newTheme === 'dark' ? 'Dark' : 'Light';
+        const dcTheme = document.getElementById('dc_theme');
+        deviceTheme.onChange((theme, device) => {
+            themeToggle.textContent = theme === 'dark' ? 'Dark' : 'Light';
+            if (dcTheme) dcTheme.value = device;
         });
+        themeToggle.addEventListener('click', () => deviceTheme.toggle());
+        if (dcTheme) dcTheme.addEventListener('change', () => deviceTheme.set(dcTheme.value));
         
         // Restart button
         const restartBtn = document.getElementById('restartBtn');
@@ -825,7 +1256,7 @@ pub fn handle_dashboard_enhanced(req: Request<&mut EspHttpConnection>) -> Result
                 document.getElementById('cpu-freq').textContent = data.cpu_freq_mhz + ' MHz';
             }
             if (data.temperature !== undefined) {
-                document.getElementById('cpu-temp').textContent = data.temperature.toFixed(1) + String.fromCharCode(176) + 'C';
+                document.getElementById('cpu-temp').textContent = formatTemp(data.temperature);
             }
             
             // Update Memory
@@ -899,6 +1330,8 @@ pub fn handle_dashboard_enhanced(req: Request<&mut EspHttpConnection>) -> Result
             const sleepSlider = document.getElementById('dc_sleep_timeout');
             const sleepVal = document.getElementById('dc_sleep_val');
             const savePower = document.getElementById('dc_save_power');
+            const dcShift = document.getElementById('dc_shift');
+            const dcSaver = document.getElementById('dc_saver');
 
             if (dcB) dcB.addEventListener('input', ()=>{
                 dcBVal.textContent = dcB.value;
@@ -926,7 +1359,9 @@ pub fn handle_dashboard_enhanced(req: Request<&mut EspHttpConnection>) -> Result
                 const body = {
                     dim_timeout: Number(dimSlider.value),
                     sleep_timeout: Number(sleepSlider.value),
-                    auto_dim: !!dcAD.checked
+                    auto_dim: !!dcAD.checked,
+                    burn_in_shift: !!(dcShift && dcShift.checked),
+                    burn_in_screensaver: !!(dcSaver && dcSaver.checked)
                 };
                 try { await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)}); } catch(e){}
             });
@@ -944,6 +1379,679 @@ pub fn handle_dashboard_enhanced(req: Request<&mut EspHttpConnection>) -> Result
                         const sleep = (j.sleep_timeout ?? j.sleep_timeout_secs ?? 300);
                         if (dimSlider){ dimSlider.value = dim; dimVal.textContent = dim + 's'; }
                         if (sleepSlider){ sleepSlider.value = sleep; sleepVal.textContent = Math.floor(sleep/60) + 'm'; }
+                        if (dcShift) dcShift.checked = !!j.burn_in_shift;
+                        if (dcSaver) dcSaver.checked = !!j.burn_in_screensaver;
+                    }
+                }catch(e){}
+            })();
+        })();
+
+        // Metrics push settings
+        (function(){
+            const mpEnabled = document.getElementById('mp_enabled');
+            const mpUrl = document.getElementById('mp_url');
+            const mpFormat = document.getElementById('mp_format');
+            const mpInterval = document.getElementById('mp_interval');
+            const mpIntervalVal = document.getElementById('mp_interval_val');
+            const mpBatch = document.getElementById('mp_batch');
+            const mpBatchVal = document.getElementById('mp_batch_val');
+            const mpDrop = document.getElementById('mp_drop');
+            const mpSave = document.getElementById('mp_save');
+            if (!mpSave) return;
+
+            mpInterval.addEventListener('input', ()=>{ mpIntervalVal.textContent = mpInterval.value + 's'; });
+            mpBatch.addEventListener('input', ()=>{ mpBatchVal.textContent = mpBatch.value; });
+            mpSave.addEventListener('click', async ()=>{
+                const body = {
+                    push_enabled: !!mpEnabled.checked,
+                    push_url: mpUrl.value.trim(),
+                    push_format: mpFormat.value,
+                    push_interval: Number(mpInterval.value),
+                    push_batch_size: Number(mpBatch.value),
+                    push_drop_policy: mpDrop.value
+                };
+                try {
+                    const r = await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)});
+                    if (!r.ok){ const j = await r.json().catch(()=>null); alert('Save failed: ' + (j && j.error ? j.error.message : r.status)); }
+                } catch(e){}
+            });
+
+            (async function(){
+                try{
+                    const r = await fetch('/api/config');
+                    const j = await r.json();
+                    if (j){
+                        mpEnabled.checked = !!j.push_enabled;
+                        mpUrl.value = j.push_url || '';
+                        if (j.push_format) mpFormat.value = j.push_format;
+                        if (j.push_drop_policy) mpDrop.value = j.push_drop_policy;
+                        const iv = j.push_interval_secs ?? 30;
+                        const bs = j.push_batch_size ?? 4;
+                        mpInterval.value = iv; mpIntervalVal.textContent = iv + 's';
+                        mpBatch.value = bs; mpBatchVal.textContent = bs;
+                    }
+                }catch(e){}
+            })();
+        })();
+
+        // WiFi scan and join
+        (function(){
+            const netSsid = document.getElementById('net_ssid');
+            const netPassword = document.getElementById('net_password');
+            const netNetworks = document.getElementById('net_networks');
+            const netScan = document.getElementById('net_scan');
+            const netWifiSave = document.getElementById('net_wifi_save');
+            if (!netScan) return;
+
+            async function scan(){
+                netScan.disabled = true;
+                netScan.textContent = 'Scanning...';
+                try{
+                    let j;
+                    for (let i = 0; i < 10; i++){
+                        const r = await fetch('/api/wifi/scan', {cache:'no-store'});
+                        j = await r.json();
+                        if (!j.scanning) break;
+                        await new Promise(res => setTimeout(res, 1000));
+                    }
+                    netNetworks.innerHTML = '';
+                    const placeholder = document.createElement('option');
+                    placeholder.value = '';
+                    placeholder.textContent = j.error ? ('Scan failed: ' + j.error) : (j.networks.length + ' networks found');
+                    netNetworks.appendChild(placeholder);
+                    for (const n of j.networks){
+                        const o = document.createElement('option');
+                        o.value = n.ssid;
+                        o.textContent = n.ssid + ' (' + n.rssi + ' dBm, ' + n.auth + ')';
+                        netNetworks.appendChild(o);
+                    }
+                    netNetworks.style.display = '';
+                }catch(e){}
+                netScan.disabled = false;
+                netScan.textContent = 'Scan';
+            }
+
+            netScan.addEventListener('click', scan);
+            netNetworks.addEventListener('change', ()=>{
+                if (netNetworks.value) {
+                    netSsid.value = netNetworks.value;
+                    netPassword.focus();
+                }
+            });
+            netWifiSave.addEventListener('click', async ()=>{
+                const body = { wifi_ssid: netSsid.value.trim() };
+                if (netPassword.value) body.wifi_password = netPassword.value;
+                try { await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)}); } catch(e){}
+                netPassword.value = '';
+            });
+
+            (async function(){
+                try{
+                    const r = await fetch('/api/config');
+                    const j = await r.json();
+                    if (j){
+                        netSsid.value = j.wifi_ssid || '';
+                    }
+                }catch(e){}
+            })();
+        })();
+
+        // Hostname
+        (function(){
+            const netHostname = document.getElementById('net_hostname');
+            const netSave = document.getElementById('net_save');
+            if (!netSave) return;
+
+            netSave.addEventListener('click', async ()=>{
+                const body = { hostname: netHostname.value.trim().toLowerCase() };
+                try { await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)}); } catch(e){}
+            });
+
+            (async function(){
+                try{
+                    const r = await fetch('/api/config');
+                    const j = await r.json();
+                    if (j){
+                        netHostname.value = j.hostname || 'esp32';
+                    }
+                }catch(e){}
+            })();
+        })();
+
+        // Throughput test
+        (function(){
+            const btn = document.getElementById('net_speedtest');
+            const out = document.getElementById('net_speedtest_result');
+            if (!btn) return;
+
+            function show(j){
+                const last = j.history && j.history[0];
+                if (!last) { out.textContent = '--'; return; }
+                out.textContent = last.error ? ('Failed: ' + last.error) : (last.mbps.toFixed(2) + ' Mbps');
+            }
+
+            btn.addEventListener('click', async ()=>{
+                btn.disabled = true;
+                out.textContent = 'Testing...';
+                try{
+                    const r = await fetch('/api/nettest', {method:'POST'});
+                    let j = await r.json();
+                    if (!r.ok) { out.textContent = (j.error && j.error.message) || 'Failed'; }
+                    else {
+                        for (let i = 0; i < 30 && j.running; i++){
+                            await new Promise(res => setTimeout(res, 1000));
+                            j = await (await fetch('/api/nettest', {cache:'no-store'})).json();
+                        }
+                        show(j);
+                    }
+                }catch(e){ out.textContent = 'Failed'; }
+                btn.disabled = false;
+            });
+
+            (async function(){
+                try{ show(await (await fetch('/api/nettest', {cache:'no-store'})).json()); }catch(e){}
+            })();
+        })();
+
+        // Connection history and BSSID pinning
+        (function(){
+            const list = document.getElementById('net_connections');
+            const pinned = document.getElementById('net_pinned');
+            const pinBtn = document.getElementById('net_pin');
+            const unpinBtn = document.getElementById('net_unpin');
+            if (!list) return;
+            let current = null;
+
+            function fmtDuration(s){
+                if (s == null) return 'now';
+                if (s < 120) return s + 's';
+                if (s < 7200) return Math.round(s / 60) + 'm';
+                return Math.round(s / 3600) + 'h';
+            }
+
+            async function load(){
+                try{
+                    const j = await (await fetch('/api/wifi/connections', {cache:'no-store'})).json();
+                    pinned.textContent = j.pinned_bssid ? ('Pinned to ' + j.pinned_bssid) : 'Any access point';
+                    unpinBtn.disabled = !j.pinned_bssid;
+                    current = j.records.length && j.records[0].duration_secs == null ? j.records[0].bssid : null;
+                    pinBtn.disabled = !current;
+                    list.innerHTML = '';
+                    for (const r of j.records){
+                        const row = document.createElement('div');
+                        row.textContent = r.bssid + '  ch' + r.channel + ' ' + r.phy + ' ' + r.rssi + 'dBm  ' +
+                            fmtDuration(r.duration_secs) + '  (' + r.cause + (r.disconnect_reason ? ' \u2192 ' + r.disconnect_reason : '') + ')';
+                        list.appendChild(row);
+                    }
+                    if (!j.records.length) list.textContent = 'No connections recorded';
+                }catch(e){}
+            }
+
+            async function pin(bssid){
+                try { await fetch('/api/wifi/pin', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify({bssid})}); } catch(e){}
+                setTimeout(load, 3000);
+            }
+
+            pinBtn.addEventListener('click', ()=>{ if (current) pin(current); });
+            unpinBtn.addEventListener('click', ()=>pin(null));
+            load();
+        })();
+
+        // Weather settings
+        // Display units; the API always reports Celsius
+        let tempUnit = 'celsius';
+        function formatTemp(c) {
+            return tempUnit === 'fahrenheit'
+                ? (c * 9 / 5 + 32).toFixed(1) + '\u00B0F'
+                : c.toFixed(1) + '\u00B0C';
+        }
+        (function(){
+            const unTemp = document.getElementById('un_temp');
+            const unClock = document.getElementById('un_clock');
+            const unSave = document.getElementById('un_save');
+            if (!unSave) return;
+
+            unSave.addEventListener('click', async ()=>{
+                const body = { temperature_unit: unTemp.value, clock_format: unClock.value };
+                try { await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)}); } catch(e){}
+                tempUnit = unTemp.value;
+            });
+
+            (async function(){
+                try{
+                    const r = await fetch('/api/config');
+                    const j = await r.json();
+                    if (j){
+                        tempUnit = j.temperature_unit || 'celsius';
+                        unTemp.value = tempUnit;
+                        unClock.value = j.clock_format || '24h';
+                    }
+                }catch(e){}
+            })();
+        })();
+
+        // Accessibility mode; also switched with a long USER press on the Settings screen
+        (function(){
+            const axEnabled = document.getElementById('ax_enabled');
+            if (!axEnabled) return;
+
+            axEnabled.addEventListener('change', async ()=>{
+                const body = { accessibility_mode: axEnabled.checked };
+                try { await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)}); } catch(e){}
+            });
+
+            (async function(){
+                try{
+                    const j = await (await fetch('/api/config')).json();
+                    if (j) axEnabled.checked = !!j.accessibility_mode;
+                }catch(e){}
+            })();
+        })();
+
+        // Kiosk mode: the metric list offers the registered sensors and widgets
+        (function(){
+            const kiEnabled = document.getElementById('ki_enabled');
+            const kiMetric = document.getElementById('ki_metric');
+            const kiMetrics = document.getElementById('ki_metrics');
+            const kiSave = document.getElementById('ki_save');
+            if (!kiSave) return;
+
+            const save = async ()=>{
+                const body = { kiosk_enabled: kiEnabled.checked, kiosk_metric: kiMetric.value.trim() || 'temperature' };
+                try { await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)}); } catch(e){}
+            };
+            kiSave.addEventListener('click', save);
+            kiEnabled.addEventListener('change', save);
+
+            (async function(){
+                try{
+                    const j = await (await fetch('/api/config')).json();
+                    if (j){
+                        kiEnabled.checked = !!j.kiosk_enabled;
+                        kiMetric.value = j.kiosk_metric || 'temperature';
+                        (j.custom_widgets || []).forEach(w => {
+                            const o = document.createElement('option');
+                            o.value = 'widget:' + w.label;
+                            kiMetrics.appendChild(o);
+                        });
+                    }
+                    const readings = await (await fetch('/api/v1/sensors')).json();
+                    (readings || []).forEach(r => {
+                        const o = document.createElement('option');
+                        o.value = r.key;
+                        kiMetrics.appendChild(o);
+                    });
+                }catch(e){}
+            })();
+        })();
+
+        // Quiet hours
+        (function(){
+            const qhEnabled = document.getElementById('qh_enabled');
+            const qhStart = document.getElementById('qh_start');
+            const qhEnd = document.getElementById('qh_end');
+            const qhOverride = document.getElementById('qh_override');
+            const qhStatus = document.getElementById('qh_status');
+            const qhSave = document.getElementById('qh_save');
+            if (!qhSave) return;
+            const hhmm = (m) => String(Math.floor(m / 60)).padStart(2, '0') + ':' + String(m % 60).padStart(2, '0');
+
+            qhSave.addEventListener('click', async ()=>{
+                const body = { quiet_hours_enabled: !!qhEnabled.checked, quiet_start: qhStart.value, quiet_end: qhEnd.value };
+                try { await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)}); } catch(e){}
+            });
+            qhOverride.addEventListener('change', async ()=>{
+                try { await fetch('/api/control', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify({quiet_override: !!qhOverride.checked})}); } catch(e){}
+            });
+
+            (async function(){
+                try{
+                    const r = await fetch('/api/config');
+                    const j = await r.json();
+                    if (j){
+                        qhEnabled.checked = !!j.quiet_hours_enabled;
+                        qhStart.value = hhmm(j.quiet_start_minutes ?? 1380);
+                        qhEnd.value = hhmm(j.quiet_end_minutes ?? 420);
+                    }
+                    const s = await (await fetch('/api/system')).json();
+                    if (s && s.quiet_hours){
+                        qhOverride.checked = !!s.quiet_hours.overridden;
+                        qhStatus.textContent = s.quiet_hours.active ? '(quiet now)' : '';
+                    }
+                }catch(e){}
+            })();
+        })();
+
+        // OTA maintenance window
+        (function(){
+            const owEnabled = document.getElementById('ow_enabled');
+            const owStart = document.getElementById('ow_start');
+            const owEnd = document.getElementById('ow_end');
+            const owStatus = document.getElementById('ow_status');
+            const owSave = document.getElementById('ow_save');
+            if (!owSave) return;
+            const hhmm = (m) => String(Math.floor(m / 60)).padStart(2, '0') + ':' + String(m % 60).padStart(2, '0');
+
+            owSave.addEventListener('click', async ()=>{
+                const body = { ota_window_enabled: !!owEnabled.checked, ota_window_start: owStart.value, ota_window_end: owEnd.value };
+                try { await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)}); } catch(e){}
+            });
+
+            (async function(){
+                try{
+                    const j = await (await fetch('/api/config')).json();
+                    if (j){
+                        owEnabled.checked = !!j.ota_window_enabled;
+                        owStart.value = hhmm(j.ota_window_start_minutes ?? 120);
+                        owEnd.value = hhmm(j.ota_window_end_minutes ?? 240);
+                    }
+                    const s = await (await fetch('/api/ota/status')).json();
+                    if (s && s.pending){
+                        owStatus.textContent = '(update staged for ' + s.window.start + ')';
+                    }
+                }catch(e){}
+            })();
+        })();
+
+        (function(){
+            const wxEnabled = document.getElementById('wx_enabled');
+            const wxLat = document.getElementById('wx_lat');
+            const wxLon = document.getElementById('wx_lon');
+            const wxSave = document.getElementById('wx_save');
+            if (!wxSave) return;
+
+            wxSave.addEventListener('click', async ()=>{
+                const body = {
+                    weather_enabled: !!wxEnabled.checked,
+                    weather_latitude: Number(wxLat.value),
+                    weather_longitude: Number(wxLon.value)
+                };
+                try { await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)}); } catch(e){}
+            });
+
+            (async function(){
+                try{
+                    const r = await fetch('/api/config');
+                    const j = await r.json();
+                    if (j){
+                        wxEnabled.checked = !!j.weather_enabled;
+                        wxLat.value = j.weather_latitude ?? 0;
+                        wxLon.value = j.weather_longitude ?? 0;
+                    }
+                }catch(e){}
+            })();
+        })();
+
+        // Buzzer settings
+        (function(){
+            const bzGpio = document.getElementById('bz_gpio');
+            const bzMuted = document.getElementById('bz_muted');
+            const bzEvents = document.querySelectorAll('#bz_events input[data-sound]');
+            const bzSave = document.getElementById('bz_save');
+            const bzTest = document.getElementById('bz_test');
+            if (!bzSave) return;
+
+            bzSave.addEventListener('click', async ()=>{
+                const sounds = {};
+                bzEvents.forEach(cb => { sounds[cb.dataset.sound] = !!cb.checked; });
+                const body = {
+                    buzzer_gpio: Number(bzGpio.value) || 0,
+                    buzzer_muted: !!bzMuted.checked,
+                    buzzer_sounds: sounds
+                };
+                try { await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)}); } catch(e){}
+            });
+            bzMuted.addEventListener('change', async ()=>{
+                try { await fetch('/api/control', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify({mute: !!bzMuted.checked})}); } catch(e){}
+            });
+            bzTest.addEventListener('click', async ()=>{
+                try { await fetch('/api/control', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify({sound: 'timer_done'})}); } catch(e){}
+            });
+
+            (async function(){
+                try{
+                    const r = await fetch('/api/config');
+                    const j = await r.json();
+                    if (j){
+                        bzGpio.value = j.buzzer_gpio ?? 0;
+                        bzMuted.checked = !!j.buzzer_muted;
+                        const sounds = j.buzzer_sounds || {};
+                        bzEvents.forEach(cb => { cb.checked = !!sounds[cb.dataset.sound]; });
+                    }
+                }catch(e){}
+            })();
+        })();
+
+        // Status LED settings
+        (function(){
+            const ledEnabled = document.getElementById('led_enabled');
+            const ledGpio = document.getElementById('led_gpio');
+            const ledCount = document.getElementById('led_count');
+            const ledBrightness = document.getElementById('led_brightness');
+            const ledBrightnessVal = document.getElementById('led_brightness_val');
+            const ledSave = document.getElementById('led_save');
+            if (!ledSave) return;
+
+            ledBrightness.addEventListener('input', ()=>{ ledBrightnessVal.textContent = ledBrightness.value; });
+            ledSave.addEventListener('click', async ()=>{
+                const body = {
+                    led_enabled: !!ledEnabled.checked,
+                    led_gpio: Number(ledGpio.value) || 0,
+                    led_count: Number(ledCount.value) || 1,
+                    led_brightness: Number(ledBrightness.value)
+                };
+                try { await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)}); } catch(e){}
+            });
+
+            (async function(){
+                try{
+                    const r = await fetch('/api/config');
+                    const j = await r.json();
+                    if (j){
+                        ledEnabled.checked = !!j.led_enabled;
+                        ledGpio.value = j.led_gpio ?? 0;
+                        ledCount.value = j.led_count ?? 8;
+                        ledBrightness.value = j.led_brightness ?? 64;
+                        ledBrightnessVal.textContent = ledBrightness.value;
+                    }
+                }catch(e){}
+            })();
+        })();
+
+        // INA219 power monitor settings
+        (function(){
+            const pmSda = document.getElementById('pm_sda');
+            const pmScl = document.getElementById('pm_scl');
+            const pmAddress = document.getElementById('pm_address');
+            const pmShunt = document.getElementById('pm_shunt');
+            const pmReading = document.getElementById('pm_reading');
+            const pmSave = document.getElementById('pm_save');
+            if (!pmSave) return;
+
+            pmSave.addEventListener('click', async ()=>{
+                const body = {
+                    power_monitor_sda: Number(pmSda.value) || 0,
+                    power_monitor_scl: Number(pmScl.value) || 0,
+                    power_monitor_address: parseInt(pmAddress.value, 16) || 0x40,
+                    power_monitor_shunt_mohm: Number(pmShunt.value) || 100
+                };
+                try { await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)}); } catch(e){}
+            });
+
+            (async function(){
+                try{
+                    const r = await fetch('/api/config');
+                    const j = await r.json();
+                    if (j){
+                        pmSda.value = j.power_monitor_sda ?? 0;
+                        pmScl.value = j.power_monitor_scl ?? 0;
+                        pmAddress.value = '0x' + (j.power_monitor_address ?? 0x40).toString(16);
+                        pmShunt.value = j.power_monitor_shunt_mohm ?? 100;
+                    }
+                    const s = await (await fetch('/api/system')).json();
+                    const p = s && s.power_monitor;
+                    if (p){
+                        pmReading.textContent = p.power_mw.toFixed(0) + ' mW (avg ' + p.average_mw.toFixed(0) + ', ' + p.energy_mwh.toFixed(1) + ' mWh)';
+                    }
+                }catch(e){}
+            })();
+        })();
+
+        // SCD40/SGP30 air quality settings
+        (function(){
+            const aqSda = document.getElementById('aq_sda');
+            const aqScl = document.getElementById('aq_scl');
+            const aqReading = document.getElementById('aq_reading');
+            const aqSave = document.getElementById('aq_save');
+            if (!aqSave) return;
+
+            aqSave.addEventListener('click', async ()=>{
+                const body = {
+                    air_quality_sda: Number(aqSda.value) || 0,
+                    air_quality_scl: Number(aqScl.value) || 0
+                };
+                try { await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)}); } catch(e){}
+            });
+
+            (async function(){
+                try{
+                    const j = await (await fetch('/api/config')).json();
+                    if (j){
+                        aqSda.value = j.air_quality_sda ?? 0;
+                        aqScl.value = j.air_quality_scl ?? 0;
+                    }
+                    const readings = await (await fetch('/api/v1/sensors')).json();
+                    const shown = (readings || []).filter(r => r.sensor === 'scd40' || r.sensor === 'sgp30');
+                    if (shown.length){
+                        aqReading.textContent = shown.map(r => r.value.toFixed(0) + ' ' + r.unit + ' ' + r.quantity).join(', ');
+                    }
+                }catch(e){}
+            })();
+        })();
+
+        // Sample schedule settings
+        (function(){
+            const ssSave = document.getElementById('ss_save');
+            if (!ssSave) return;
+            const sources = ['sensors', 'network', 'processing', 'latency'];
+            const enabled = (s)=> document.querySelector('[data-source="' + s + '"]');
+            const interval = (s)=> document.getElementById('ss_' + s);
+
+            ssSave.addEventListener('click', async ()=>{
+                const schedule = {};
+                for (const s of sources){
+                    schedule[s] = { interval_ms: Number(interval(s).value) || 0, enabled: !!enabled(s).checked };
+                }
+                try {
+                    const r = await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify({sample_schedule: schedule})});
+                    if (!r.ok){ const j = await r.json().catch(()=>null); alert('Save failed: ' + (j && j.error ? j.error.message : r.status)); }
+                } catch(e){}
+            });
+
+            (async function(){
+                try{
+                    const r = await fetch('/api/config');
+                    const j = await r.json();
+                    const schedule = (j && j.sample_schedule) || {};
+                    for (const s of sources){
+                        const entry = schedule[s];
+                        if (!entry) continue;
+                        interval(s).value = entry.interval_ms;
+                        enabled(s).checked = entry.enabled || s === 'processing';
+                    }
+                }catch(e){}
+            })();
+        })();
+
+        // Frame pacing settings
+        (function(){
+            const fpFps = document.getElementById('fp_fps');
+            const fpStrategy = document.getElementById('fp_strategy');
+            const fpSave = document.getElementById('fp_save');
+            if (!fpSave) return;
+
+            fpSave.addEventListener('click', async ()=>{
+                const body = {
+                    target_fps: Math.min(60, Math.max(0, Number(fpFps.value) || 0)),
+                    frame_pacing: fpStrategy.value
+                };
+                try { await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)}); } catch(e){}
+            });
+
+            (async function(){
+                try{
+                    const r = await fetch('/api/config');
+                    const j = await r.json();
+                    if (j){
+                        fpFps.value = j.target_fps ?? 10;
+                        fpStrategy.value = j.frame_pacing || 'sleep';
+                    }
+                }catch(e){}
+            })();
+        })();
+
+        // Day/night profile settings
+        (function(){
+            const pfEnabled = document.getElementById('pf_enabled');
+            const pfDayStart = document.getElementById('pf_day_start');
+            const pfNightStart = document.getElementById('pf_night_start');
+            const pfTz = document.getElementById('pf_tz');
+            const pfSave = document.getElementById('pf_save');
+            if (!pfSave) return;
+
+            const toHhmm = (m)=> String(Math.floor(m / 60)).padStart(2, '0') + ':' + String(m % 60).padStart(2, '0');
+            const fields = (prefix)=>({
+                brightness: document.getElementById(prefix + '_brightness'),
+                brightnessVal: document.getElementById(prefix + '_brightness_val'),
+                theme: document.getElementById(prefix + '_theme'),
+                dim: document.getElementById(prefix + '_dim'),
+                push: document.getElementById(prefix + '_push')
+            });
+            const day = fields('pf_day');
+            const night = fields('pf_night');
+            for (const f of [day, night]){
+                f.brightness.addEventListener('input', ()=>{ f.brightnessVal.textContent = f.brightness.value; });
+            }
+            const readProfile = (f)=>({
+                brightness: Number(f.brightness.value),
+                theme: f.theme.value,
+                dim_timeout_secs: Number(f.dim.value),
+                push_interval_secs: Number(f.push.value)
+            });
+            const fillProfile = (f, p)=>{
+                if (!p) return;
+                f.brightness.value = p.brightness; f.brightnessVal.textContent = p.brightness;
+                f.theme.value = p.theme;
+                f.dim.value = p.dim_timeout_secs;
+                f.push.value = p.push_interval_secs;
+            };
+
+            pfSave.addEventListener('click', async ()=>{
+                const body = {
+                    profiles_enabled: !!pfEnabled.checked,
+                    day_start: pfDayStart.value,
+                    night_start: pfNightStart.value,
+                    timezone: pfTz.value.trim(),
+                    day_profile: readProfile(day),
+                    night_profile: readProfile(night)
+                };
+                try {
+                    const r = await fetch('/api/config', {method:'POST', headers:{'Content-Type':'application/json'}, body: JSON.stringify(body)});
+                    if (!r.ok){ const j = await r.json().catch(()=>null); alert('Save failed: ' + (j && j.error ? j.error.message : r.status)); }
+                } catch(e){}
+            });
+
+            (async function(){
+                try{
+                    const r = await fetch('/api/config');
+                    const j = await r.json();
+                    if (j){
+                        pfEnabled.checked = !!j.profiles_enabled;
+                        pfDayStart.value = toHhmm(j.day_start_minutes ?? 420);
+                        pfNightStart.value = toHhmm(j.night_start_minutes ?? 1320);
+                        pfTz.value = j.timezone || 'UTC0';
+                        fillProfile(day, j.day_profile);
+                        fillProfile(night, j.night_profile);
                     }
                 }catch(e){}
             })();
@@ -8,11 +8,13 @@ pub fn handle_ota_streaming(req: Request<&mut EspHttpConnection>, has_ota_manage
     crate::memory_diagnostics::log_memory_state("OTA streaming - start");
     
     // Create response
+    let cookie = crate::network::csrf::session_cookie(&req);
     let mut response = req.into_response(
         200,
         Some("OK"),
         &[
             ("Content-Type", "text/html; charset=utf-8"),
+            ("Set-Cookie", cookie.as_str()),
             ("Connection", "close"),
             // Don't use compression for OTA page
         ]
@@ -26,14 +28,7 @@ pub fn handle_ota_streaming(req: Request<&mut EspHttpConnection>, has_ota_manage
     };
 
     // Build shared navbar using partial with active state
-    let mut navbar = include_str!("../templates/partials/navbar.html").to_string();
-    navbar = navbar
-        .replace("{{HOME_ACTIVE}}", "")
-        .replace("{{DASH_ACTIVE}}", "")
-        .replace("{{LOGS_ACTIVE}}", "")
-        .replace("{{FILES_ACTIVE}}", "")
-        .replace("{{OTA_ACTIVE}}", "class=\\\"active\\\"")
-        .replace("{{DEV_ACTIVE}}", "");
+    let navbar = crate::templates::render_navbar("/ota");
 
     // Minimal navbar CSS to match global style (uses OTA page CSS variables)
     const NAV_CSS: &str = r#"
@@ -54,6 +49,7 @@ pub fn handle_ota_streaming(req: Request<&mut EspHttpConnection>, has_ota_manage
     if !html.contains(".navbar") {
         html = html.replacen("<head>", &format!("<head>\n{}", NAV_CSS), 1);
     }
+    let html = crate::templates::with_csrf_script(&html);
 
     // Stream in 1KB chunks to avoid large allocations
     let bytes = html.as_bytes();
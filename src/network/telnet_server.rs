@@ -1,6 +1,9 @@
+use super::log_filter::LogFilter;
 use anyhow::Result;
-use std::io::Write;
+use log::Level;
+use std::io::{Read, Write};
 use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -51,10 +54,124 @@ impl LogBuffer {
     }
 }
 
+/// Telnet protocol bytes skipped in client input
+const IAC: u8 = 255;
+const SB: u8 = 250;
+const SE: u8 = 240;
+
+#[derive(Clone, Copy, PartialEq)]
+enum InputState {
+    Text,
+    /// After IAC: a command byte follows
+    Command,
+    /// After WILL/WONT/DO/DONT: an option byte follows
+    Option,
+    /// Inside IAC SB ... IAC SE
+    Subnegotiation,
+}
+
+/// A connected client: its stream, the filter it asked for and the command line typed so far
+struct TelnetClient {
+    stream: TcpStream,
+    filter: LogFilter,
+    line: String,
+    state: InputState,
+    closed: bool,
+}
+
+impl TelnetClient {
+    fn new(stream: TcpStream) -> Self {
+        Self { stream, filter: LogFilter::default(), line: String::new(), state: InputState::Text, closed: false }
+    }
+
+    fn send(&mut self, text: &str) {
+        if self.stream.write_all(text.as_bytes()).and_then(|_| self.stream.flush()).is_err() {
+            self.closed = true;
+        }
+    }
+
+    /// Read whatever the client typed without blocking and run complete lines
+    fn poll_input(&mut self) {
+        if self.stream.set_nonblocking(true).is_err() {
+            self.closed = true;
+            return;
+        }
+        let mut buf = [0u8; 64];
+        loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => {
+                    self.closed = true;
+                    break;
+                }
+                Ok(n) => {
+                    for &byte in &buf[..n] {
+                        self.feed(byte);
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(_) => {
+                    self.closed = true;
+                    break;
+                }
+            }
+        }
+        let _ = self.stream.set_nonblocking(false);
+    }
+
+    fn feed(&mut self, byte: u8) {
+        self.state = match (self.state, byte) {
+            (InputState::Text, IAC) => InputState::Command,
+            (InputState::Text, b'\r' | b'\n') => {
+                let line = core::mem::take(&mut self.line);
+                if !line.trim().is_empty() {
+                    let reply = self.run_command(line.trim());
+                    self.send(&format!("{}\r\n", reply));
+                }
+                InputState::Text
+            }
+            (InputState::Text, 0x08 | 0x7f) => {
+                self.line.pop();
+                InputState::Text
+            }
+            (InputState::Text, b) => {
+                if b.is_ascii_graphic() || b == b' ' {
+                    self.line.push(b as char);
+                }
+                InputState::Text
+            }
+            (InputState::Command, SB) => InputState::Subnegotiation,
+            (InputState::Command, 251..=254) => InputState::Option,
+            (InputState::Command | InputState::Option, _) => InputState::Text,
+            (InputState::Subnegotiation, SE) => InputState::Text,
+            (InputState::Subnegotiation, _) => InputState::Subnegotiation,
+        };
+    }
+
+    fn run_command(&mut self, line: &str) -> String {
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "filter" if args.trim().is_empty() => format!("Filter: {}", self.filter),
+            "filter" => match LogFilter::from_command(args) {
+                Ok(filter) => {
+                    self.filter = filter;
+                    if self.filter.is_active() {
+                        format!("Filter: {}", self.filter)
+                    } else {
+                        "Filter off".to_string()
+                    }
+                }
+                Err(e) => e,
+            },
+            "help" => "Commands: filter [module] [level] (e.g. filter network::wifi debug), filter off, filter, help".to_string(),
+            other => format!("Unknown command '{}' (try help)", other),
+        }
+    }
+}
+
 /// Telnet server for remote log streaming
 pub struct TelnetLogServer {
     log_buffer: Arc<Mutex<LogBuffer>>,
-    clients: Arc<Mutex<Vec<Arc<Mutex<TcpStream>>>>>,
+    clients: Arc<Mutex<Vec<TelnetClient>>>,
     port: u16,
     shutdown_signal: Option<crate::system::ShutdownSignal>,
     total_connections: Arc<Mutex<u64>>,
@@ -98,7 +215,8 @@ impl TelnetLogServer {
     
     /// Main server loop
     fn run_server(&self) -> Result<()> {
-        let listener = TcpListener::bind(format!("0.0.0.0:{}", self.port))?;
+        // [::] is dual-stack in lwIP: it accepts IPv4 and IPv6 clients
+        let listener = TcpListener::bind(format!("[::]:{}", self.port))?;
         listener.set_nonblocking(true)?; // Non-blocking for shutdown check
         
         log::info!("Telnet server listening on port {}", self.port);
@@ -119,29 +237,31 @@ impl TelnetLogServer {
                     stream.set_nodelay(true)?;
                     stream.set_nonblocking(false)?;
                     
-                    let stream = Arc::new(Mutex::new(stream));
+                    let mut s = stream;
                     
                     // Send welcome message and recent logs
-                    if let Ok(mut s) = stream.lock() {
-                        let _ = writeln!(s, "\r\n=== ESP32-S3 Dashboard Remote Log ===\r\n");
-                        let _ = writeln!(s, "Firmware: {}\r", crate::version::DISPLAY_VERSION);
-                        let _ = writeln!(s, "Free heap: {} KB\r", unsafe { esp_idf_sys::esp_get_free_heap_size() } / 1024);
-                        let _ = writeln!(s, "\r\nConnected to device. Streaming live logs...\r\n");
-                        let _ = writeln!(s, "TIP: Use monitor-telnet.py for filtering and commands\r\n");
-                        
-                        // Send recent log history
-                        if let Ok(buffer) = self.log_buffer.lock() {
-                            let _ = writeln!(s, "--- Recent log history ---");
-                            for msg in buffer.get_all() {
-                                let _ = write!(s, "{msg}");
-                            }
-                            let _ = writeln!(s, "--- End of history ---\r\n");
+                    let _ = writeln!(s, "\r\n=== ESP32-S3 Dashboard Remote Log ===\r\n");
+                    let _ = writeln!(s, "Firmware: {}\r", crate::version::DISPLAY_VERSION);
+                    let host = crate::network::hostname();
+                    if !host.is_empty() {
+                        let _ = writeln!(s, "Host: {}.local\r", host);
+                    }
+                    let _ = writeln!(s, "Free heap: {} KB\r", unsafe { esp_idf_sys::esp_get_free_heap_size() } / 1024);
+                    let _ = writeln!(s, "\r\nConnected to device. Streaming live logs...\r\n");
+                    let _ = writeln!(s, "TIP: Type 'filter <module> <level>' (e.g. filter network::wifi debug) or 'help'\r\n");
+                    
+                    // Send recent log history
+                    if let Ok(buffer) = self.log_buffer.lock() {
+                        let _ = writeln!(s, "--- Recent log history ---");
+                        for msg in buffer.get_all() {
+                            let _ = write!(s, "{msg}");
                         }
+                        let _ = writeln!(s, "--- End of history ---\r\n");
                     }
                     
                     // Add to active clients
                     if let Ok(mut clients) = self.clients.lock() {
-                        clients.push(stream);
+                        clients.push(TelnetClient::new(s));
                     }
                     
                     // Increment total connections
@@ -159,65 +279,97 @@ impl TelnetLogServer {
                     if e.kind() != std::io::ErrorKind::WouldBlock {
                         log::error!("Accept error: {:?}", e);
                     }
+                    // Commands typed by clients (filter, help)
+                    self.poll_clients();
                     thread::sleep(Duration::from_millis(100));
                 }
             }
         }
         
         // Disconnect all clients on shutdown
+        let notice = match crate::system::shutdown::pending_restart() {
+            Some(reason) => format!("Device restarting ({})", reason.label()),
+            None => "Server shutting down".to_string(),
+        };
+        self.disconnect_all(&notice);
+        
+        log::info!("Telnet server stopped");
+        Ok(())
+    }
+    
+    /// Send `notice` to every client and close the connections
+    pub fn disconnect_all(&self, notice: &str) {
         if let Ok(mut clients) = self.clients.lock() {
-            for client in clients.iter() {
-                if let Ok(mut stream) = client.lock() {
-                    let _ = writeln!(stream, "\r\n\r\n=== Server shutting down ===\r\n");
-                }
+            for client in clients.iter_mut() {
+                client.send(&format!("\r\n\r\n=== {} ===\r\n\r\n", notice));
             }
             clients.clear();
         }
-        
-        log::info!("Telnet server stopped");
-        Ok(())
+        self.update_metrics();
+    }
+    
+    /// Read client input and drop clients that went away
+    fn poll_clients(&self) {
+        let dropped = match self.clients.lock() {
+            Ok(mut clients) => {
+                clients.iter_mut().for_each(TelnetClient::poll_input);
+                let before = clients.len();
+                clients.retain(|client| !client.closed);
+                before != clients.len()
+            }
+            Err(_) => false,
+        };
+        if dropped {
+            self.update_metrics();
+        }
     }
     
     /// Remove disconnected clients
     fn cleanup_clients(&self) {
         if let Ok(mut clients) = self.clients.lock() {
             clients.retain(|client| {
-                if let Ok(stream) = client.lock() {
-                    // Try to peek to check if connection is alive
-                    let mut buf = [0; 1];
-                    match stream.peek(&mut buf) {
-                        Ok(0) => false, // Connection closed
-                        Ok(_) => true,  // Data available or would block
-                        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => true,
-                        Err(_) => false, // Other errors mean disconnected
-                    }
-                } else {
-                    false
+                if client.closed {
+                    return false;
+                }
+                // Try to peek to check if connection is alive
+                let mut buf = [0; 1];
+                match client.stream.peek(&mut buf) {
+                    Ok(0) => false, // Connection closed
+                    Ok(_) => true,  // Data available or would block
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => true,
+                    Err(_) => false, // Other errors mean disconnected
                 }
             });
-            
-            // Update metrics after cleanup
-            self.update_metrics();
         }
+        // Update metrics after cleanup
+        self.update_metrics();
     }
     
     /// Log a message to buffer and all connected clients
     pub fn log_message(&self, level: &str, message: &str) {
+        let level = Level::from_str(level.trim()).unwrap_or(Level::Info);
+        self.send_line(level, None, message);
+    }
+    
+    /// Log a record from the logger; clients only get it if it passes their filter
+    pub fn log_record(&self, level: Level, module: &str, message: &str) {
+        self.send_line(level, Some(module), message);
+    }
+    
+    fn send_line(&self, level: Level, module: Option<&str>, message: &str) {
         let timestamp = esp_idf_svc::systime::EspSystemTime.now().as_secs();
-        let formatted = format!("[{timestamp:10}] {level:5} {message}\r\n");
+        let label = level.as_str();
+        let formatted = format!("[{timestamp:10}] {label:5} {message}\r\n");
         
         // Add to buffer
         if let Ok(mut buffer) = self.log_buffer.lock() {
             buffer.push(formatted.clone());
         }
         
-        // Send to all connected clients
-        if let Ok(clients) = self.clients.lock() {
-            for client in clients.iter() {
-                if let Ok(mut stream) = client.lock() {
-                    let _ = stream.write_all(formatted.as_bytes());
-                    let _ = stream.flush();
-                }
+        // Send to connected clients whose filter passes it
+        if let Ok(mut clients) = self.clients.lock() {
+            for client in clients.iter_mut().filter(|c| c.filter.matches(level, module)) {
+                client.send(&formatted);
             }
         }
     }
@@ -1,54 +1,245 @@
 /// Simple template engine for ESP32 with minimal memory usage
+///
+/// A small subset of mustache:
+/// - `{{name}}` inserts a value, HTML-escaped
+/// - `{{#name}}...{{/name}}` renders the body once per list item, or once if the
+///   value is `true` / a non-empty string
+/// - `{{^name}}...{{/name}}` renders the body if the value is missing or falsy
+/// - `{{>name}}` includes a partial
+/// - `{{@key}}` inserts the i18n string `key` in the device language, HTML-escaped
+/// - `{{! comment }}` is dropped
+///
+/// Inside a list section, names resolve against the item first and then the
+/// enclosing contexts. Missing names render as nothing.
 use std::collections::HashMap;
 
-/// Basic template engine that supports variable substitution
+/// Partials can include each other; stop before a cycle blows the stack
+const MAX_PARTIAL_DEPTH: usize = 4;
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Text(String),
+    Bool(bool),
+    List(Vec<Context>),
+}
+
+impl Value {
+    fn is_truthy(&self) -> bool {
+        match self {
+            Value::Text(s) => !s.is_empty(),
+            Value::Bool(b) => *b,
+            Value::List(items) => !items.is_empty(),
+        }
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self { Value::Text(s) }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self { Value::Text(s.to_string()) }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self { Value::Bool(b) }
+}
+
+impl From<Vec<Context>> for Value {
+    fn from(items: Vec<Context>) -> Self { Value::List(items) }
+}
+
+/// Named values for one level of a template
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    values: HashMap<&'static str, Value>,
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, name: &'static str, value: impl Into<Value>) -> Self {
+        self.values.insert(name, value.into());
+        self
+    }
+}
+
 pub struct TemplateEngine;
 
 impl TemplateEngine {
-    /// Render a template with variables
-    /// Variables are in the format {{variable_name}}
-    pub fn render(template: &str, vars: &HashMap<&str, String>) -> String {
-        let mut result = String::from(template);
-        
-        for (key, value) in vars {
-            let placeholder = format!("{{{{{}}}}}", key);
-            result = result.replace(&placeholder, value);
+    /// Render `template` against `context`; `partials` maps `{{>name}}` to template text
+    pub fn render(template: &str, context: &Context, partials: &HashMap<&str, &str>) -> String {
+        let mut out = String::with_capacity(template.len());
+        let mut stack = vec![context];
+        render_into(&mut out, template, &mut stack, partials, 0);
+        out
+    }
+}
+
+/// Escape text for use in HTML content and quoted attributes
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
         }
-        
-        result
-    }
-    
-    /// Render with partials support (include other templates)
-    /// Partials are in the format {{>partial_name}}
-    pub fn render_with_partials(
-        template: &str, 
-        vars: &HashMap<&str, String>,
-        partials: &HashMap<&str, &str>
-    ) -> String {
-        let mut result = String::from(template);
-        
-        // First process partials
-        for (name, content) in partials {
-            let placeholder = format!("{{{{>{}}}}}", name);
-            result = result.replace(&placeholder, content);
+    }
+    out
+}
+
+fn lookup<'a>(stack: &[&'a Context], name: &str) -> Option<&'a Value> {
+    stack.iter().rev().find_map(|ctx| ctx.values.get(name))
+}
+
+fn render_into(
+    out: &mut String,
+    template: &str,
+    stack: &mut Vec<&Context>,
+    partials: &HashMap<&str, &str>,
+    depth: usize,
+) {
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            // Unterminated tag: emit as-is
+            out.push_str(&rest[start..]);
+            return;
+        };
+        let tag = after[..end].trim();
+        rest = &after[end + 2..];
+
+        match tag.as_bytes().first() {
+            Some(b'#') | Some(b'^') => {
+                let name = tag[1..].trim();
+                let (body, remaining) = split_section(rest, name);
+                rest = remaining;
+                let value = lookup(stack, name);
+                if tag.starts_with('^') {
+                    if !value.is_some_and(Value::is_truthy) {
+                        render_into(out, body, stack, partials, depth);
+                    }
+                    continue;
+                }
+                match value {
+                    Some(Value::List(items)) => {
+                        for item in items {
+                            stack.push(item);
+                            render_into(out, body, stack, partials, depth);
+                            stack.pop();
+                        }
+                    }
+                    Some(v) if v.is_truthy() => render_into(out, body, stack, partials, depth),
+                    _ => {}
+                }
+            }
+            Some(b'>') => {
+                let name = tag[1..].trim();
+                match partials.get(name) {
+                    Some(partial) if depth < MAX_PARTIAL_DEPTH => {
+                        render_into(out, partial, stack, partials, depth + 1)
+                    }
+                    Some(_) => log::warn!("Template partial '{}' nested too deeply", name),
+                    None => log::warn!("Template partial '{}' not found", name),
+                }
+            }
+            Some(b'@') => {
+                let key = tag[1..].trim();
+                let text = crate::i18n::translate(crate::i18n::language(), key).unwrap_or(key);
+                out.push_str(&escape_html(text));
+            }
+            // Comments and stray closing tags
+            Some(b'!') | Some(b'/') => {}
+            _ => match lookup(stack, tag) {
+                Some(Value::Text(s)) => out.push_str(&escape_html(s)),
+                Some(Value::Bool(b)) => out.push_str(if *b { "true" } else { "false" }),
+                Some(Value::List(_)) | None => {}
+            },
         }
-        
-        // Then process variables
-        Self::render(&result, vars)
-    }
-
-    /// Render with partials and additional string flags (e.g., active navbar classes)
-    pub fn render_with_partials_and_flags(
-        template: &str,
-        vars: &HashMap<&str, String>,
-        partials: &HashMap<&str, &str>,
-        flags: &HashMap<&str, &str>
-    ) -> String {
-        let mut result = Self::render_with_partials(template, vars, partials);
-        for (key, value) in flags {
-            let placeholder = format!("{{{{{}}}}}", key);
-            result = result.replace(&placeholder, value);
+    }
+    out.push_str(rest);
+}
+
+/// Split `rest` at the `{{/name}}` matching an already-consumed section opener.
+/// Returns (body, text after the closing tag); an unclosed section runs to the end.
+fn split_section<'t>(rest: &'t str, name: &str) -> (&'t str, &'t str) {
+    let mut depth = 0;
+    let mut pos = 0;
+    while let Some(start) = rest[pos..].find("{{") {
+        let tag_start = pos + start;
+        let Some(end) = rest[tag_start + 2..].find("}}") else { break };
+        let tag_end = tag_start + 2 + end + 2;
+        let tag = rest[tag_start + 2..tag_end - 2].trim();
+        if let Some(inner) = tag.strip_prefix('#').or_else(|| tag.strip_prefix('^')) {
+            if inner.trim() == name {
+                depth += 1;
+            }
+        } else if let Some(inner) = tag.strip_prefix('/') {
+            if inner.trim() == name {
+                if depth == 0 {
+                    return (&rest[..tag_start], &rest[tag_end..]);
+                }
+                depth -= 1;
+            }
         }
-        result
+        pos = tag_end;
+    }
+    (rest, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, context: &Context) -> String {
+        TemplateEngine::render(template, context, &HashMap::new())
+    }
+
+    #[test]
+    fn test_variables_are_escaped() {
+        let ctx = Context::new().with("ssid", "<Home & \"Lab\">");
+        assert_eq!(render("SSID: {{ ssid }}{{missing}}", &ctx), "SSID: &lt;Home &amp; &quot;Lab&quot;&gt;");
+    }
+
+    #[test]
+    fn test_sections_loop_and_branch() {
+        let networks = vec![
+            Context::new().with("name", "Home").with("active", true),
+            Context::new().with("name", "Office"),
+        ];
+        let ctx = Context::new().with("networks", networks).with("suffix", "!");
+        let template = "{{#networks}}[{{name}}{{#active}}*{{/active}}{{suffix}}]{{/networks}}{{^empty}}none{{/empty}}";
+        assert_eq!(render(template, &ctx), "[Home*!][Office!]none");
+
+        let ctx = Context::new().with("networks", Vec::new());
+        assert_eq!(render("{{#networks}}x{{/networks}}{{^networks}}no networks{{/networks}}", &ctx), "no networks");
+    }
+
+    #[test]
+    fn test_nested_same_name_and_partials() {
+        let ctx = Context::new()
+            .with("outer", vec![Context::new().with("outer", vec![Context::new().with("v", "in")])]);
+        assert_eq!(render("{{#outer}}<{{#outer}}{{v}}{{/outer}}>{{/outer}}", &ctx), "<in>");
+
+        let mut partials = HashMap::new();
+        partials.insert("item", "({{v}})");
+        partials.insert("loop", "{{>loop}}");
+        let ctx = Context::new().with("v", "x");
+        assert_eq!(TemplateEngine::render("{{>item}}{{>loop}}{{! note }}", &ctx, &partials), "(x)");
+    }
+
+    #[test]
+    fn test_localized_strings() {
+        // Tests run with the default language (English)
+        let html = render("<a>{{@ web.nav.logs }}</a>{{@web.no_such_key}}", &Context::new());
+        assert_eq!(html, "<a>Logs</a>web.no_such_key");
     }
-}
\ No newline at end of file
+}
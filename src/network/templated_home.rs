@@ -3,7 +3,7 @@ use esp_idf_svc::http::server::{EspHttpConnection, Request};
 use esp_idf_svc::io::Write;
 use std::collections::HashMap;
 
-use super::template_engine::TemplateEngine;
+use super::template_engine::{Context, TemplateEngine};
 
 // Include templates as bytes at compile time
 const HOME_TEMPLATE: &str = include_str!("../templates/home_template.html");
@@ -101,27 +101,17 @@ const STYLES: &str = r#"<style>
     .theme-toggle:hover { background: var(--accent-hover); }
 </style>
 <script>
-    const theme = localStorage.getItem('theme') || 'light';
-    document.documentElement.setAttribute('data-theme', theme);
-    
     function toggleTheme() {
-        const currentTheme = document.documentElement.getAttribute('data-theme');
-        const newTheme = currentTheme === 'light' ? 'dark' : 'light';
-        document.documentElement.setAttribute('data-theme', newTheme);
-        localStorage.setItem('theme', newTheme);
-        updateThemeButton(newTheme);
-    }
-    
-    function updateThemeButton(theme) {
-        const button = document.getElementById('themeToggle');
-        if (button) {
-            button.textContent = theme === 'light' ? 'Dark' : 'Light';
-        }
+        deviceTheme.toggle();
     }
     
     window.addEventListener('DOMContentLoaded', function() {
-        const theme = document.documentElement.getAttribute('data-theme');
-        updateThemeButton(theme);
+        deviceTheme.onChange(function(theme) {
+            const button = document.getElementById('themeToggle');
+            if (button) {
+                button.textContent = theme === 'light' ? 'Dark' : 'Light';
+            }
+        });
     });
 </script>"#;
 
@@ -143,50 +133,49 @@ pub fn handle_home_templated(req: Request<&mut EspHttpConnection>) -> Result<(),
     // Get memory stats
     let mem_stats = crate::memory_diagnostics::MemoryStats::current();
     
-    // Create template variables
-    let mut vars = HashMap::new();
-    vars.insert("page_title", String::from("ESP32-S3 Dashboard"));
-    vars.insert("title", String::from("ESP32-S3 Dashboard"));
-    vars.insert("version", String::from(version));
-    vars.insert("uptime", format!("{}h {}m {}s", hours, minutes, seconds));
-    vars.insert("free_memory", format!("{} KB", free_heap / 1024));
-    vars.insert("dram_info", format!("{} KB (largest: {} KB)", 
-        mem_stats.internal_free_kb, 
-        mem_stats.internal_largest_kb));
-    vars.insert("dram_style", if mem_stats.internal_largest_kb < 4 {
-        String::from("style=\"color: #ef4444\"")
-    } else {
-        String::new()
-    });
-    vars.insert("psram_info", format!("{} KB", mem_stats.psram_free_kb));
+    // Template values (escaped on output)
+    let context = Context::new()
+        .with("lang", crate::i18n::language().code())
+        .with("page_title", "ESP32-S3 Dashboard")
+        .with("title", "ESP32-S3 Dashboard")
+        .with("version", version)
+        .with("uptime", format!("{}h {}m {}s", hours, minutes, seconds))
+        .with("free_memory", format!("{} KB", free_heap / 1024))
+        .with("dram_info", format!("{} KB (largest: {} KB)",
+            mem_stats.internal_free_kb,
+            mem_stats.internal_largest_kb))
+        .with("dram_low", mem_stats.internal_largest_kb < 4)
+        .with("psram_info", format!("{} KB", mem_stats.psram_free_kb))
+        .with("nav", crate::templates::nav_items("/"));
     
     // Create partials map
     let mut partials = HashMap::new();
     partials.insert("styles", STYLES);
     partials.insert("header", HEADER_PARTIAL);
     partials.insert("metrics", METRICS_PARTIAL);
-    partials.insert("navbar", include_str!("../templates/partials/navbar.html"));
-    
-    // Prepare active flags for navbar
-    let mut flags = HashMap::new();
-    flags.insert("HOME_ACTIVE", "class=\"active\"");
-    flags.insert("DASH_ACTIVE", "");
-    flags.insert("LOGS_ACTIVE", "");
-    flags.insert("FILES_ACTIVE", "");
-    flags.insert("OTA_ACTIVE", "");
-    flags.insert("DEV_ACTIVE", "");
+    partials.insert("navbar", crate::templates::NAVBAR_PARTIAL);
+    partials.insert("nav_links", crate::templates::NAV_LINKS_PARTIAL);
+    partials.insert("csrf", crate::templates::CSRF_SCRIPT);
+    partials.insert("theme", crate::templates::THEME_SCRIPT);
 
     // Render the template
-    let html = TemplateEngine::render_with_partials_and_flags(HOME_TEMPLATE, &vars, &partials, &flags);
+    let html = TemplateEngine::render(HOME_TEMPLATE, &context, &partials);
     
-    // Send response
+    // Send response; the page carries live figures, so its tag is the rendered body's
     let response_bytes = html.as_bytes();
+    let cookie = crate::network::csrf::session_cookie(&req);
+    let etag = crate::network::etag::content_etag(response_bytes);
+    if crate::network::etag::is_fresh(&req, &etag) {
+        return crate::network::etag::send_not_modified(req, &etag, &[("Set-Cookie", cookie.as_str())]);
+    }
     let mut response = req.into_response(
         200,
         Some("OK"),
         &[
             ("Content-Type", "text/html; charset=utf-8"),
             ("Content-Length", &response_bytes.len().to_string()),
+            ("Set-Cookie", cookie.as_str()),
+            ("ETag", etag.as_str()),
             ("Connection", "close"),
         ]
     )?;
@@ -1,14 +1,48 @@
-use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::net::Ipv4Addr;
+use crate::config::schedule::{DataSource, SourceSchedule};
+use crate::config::{ConfigError, FieldError};
+use crate::input::action_map::ButtonMap;
+
+type Result<T> = core::result::Result<T, ConfigError>;
+
+// Validation failure with a formatted message
+macro_rules! invalid {
+    ($($arg:tt)*) => { ConfigError::Invalid(format!($($arg)*)) };
+}
+
+/// Most custom REST widgets the config accepts (and the poller runs)
+pub const MAX_CUSTOM_WIDGETS: usize = 4;
+
+// Pins already used by the LCD, buttons and battery ADC on the T-Display-S3
+const RESERVED_PINS: &[u8] = &[0, 4, 5, 6, 7, 8, 9, 14, 15, 38, 39, 40, 41, 42, 45, 46, 47, 48];
+
+/// Whether `gpio` is free for the optional alert output
+pub fn is_pin_available(gpio: u8) -> bool {
+    gpio <= 48 && !RESERVED_PINS.contains(&gpio) && !(26..=37).contains(&gpio) // 26-37: flash/PSRAM
+}
 
 pub fn validate_ssid(ssid: &str) -> Result<()> {
     if ssid.is_empty() {
-        return Err(anyhow!("WiFi SSID cannot be empty"));
+        return Err(invalid!("WiFi SSID cannot be empty"));
     }
     if ssid.len() > 32 {
-        return Err(anyhow!("WiFi SSID must be 32 characters or less"));
+        return Err(invalid!("WiFi SSID must be 32 characters or less"));
     }
     if ssid.chars().any(|c| c.is_control()) {
-        return Err(anyhow!("WiFi SSID cannot contain control characters"));
+        return Err(invalid!("WiFi SSID cannot contain control characters"));
+    }
+    Ok(())
+}
+
+/// Interval within the source's bounds; only sources that may be switched off can be
+pub fn validate_source_schedule(source: DataSource, entry: &SourceSchedule) -> Result<()> {
+    let (min, max) = source.bounds_ms();
+    if !(min..=max).contains(&entry.interval_ms) {
+        return Err(invalid!("{} interval must be {}-{} ms", source.name(), min, max));
+    }
+    if !entry.enabled && !source.can_disable() {
+        return Err(invalid!("{} cannot be disabled", source.name()));
     }
     Ok(())
 }
@@ -20,20 +54,304 @@ pub fn validate_brightness(_brightness: u8) -> Result<()> {
 
 pub fn validate_filename(filename: &str) -> Result<()> {
     if filename.is_empty() {
-        return Err(anyhow!("Filename cannot be empty"));
+        return Err(invalid!("Filename cannot be empty"));
     }
     if filename.contains("..") {
-        return Err(anyhow!("Filename cannot contain '..'"));
+        return Err(invalid!("Filename cannot contain '..'"));
     }
     if filename.chars().any(|c| matches!(c, '/' | '\\' | '\0')) {
-        return Err(anyhow!("Filename contains invalid characters"));
+        return Err(invalid!("Filename contains invalid characters"));
     }
     if filename.len() > 128 {
-        return Err(anyhow!("Filename must be 128 characters or less"));
+        return Err(invalid!("Filename must be 128 characters or less"));
+    }
+    Ok(())
+}
+
+pub fn validate_push_url(url: &str) -> Result<()> {
+    if url.is_empty() {
+        // Empty URL disables pushing
+        return Ok(());
+    }
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(invalid!("Push URL must start with http:// or https://"));
+    }
+    if url.len() > 256 {
+        return Err(invalid!("Push URL must be 256 characters or less"));
+    }
+    if url.chars().any(|c| c.is_control() || c.is_whitespace()) {
+        return Err(invalid!("Push URL cannot contain whitespace or control characters"));
+    }
+    Ok(())
+}
+
+pub fn validate_custom_widget(widget: &crate::config::CustomWidget) -> Result<()> {
+    if widget.label.is_empty() || widget.label.len() > 20 {
+        return Err(invalid!("Widget label must be 1-20 characters"));
+    }
+    if widget.url.is_empty() {
+        return Err(invalid!("Widget URL cannot be empty"));
+    }
+    // Same rules as push URLs
+    validate_push_url(&widget.url)?;
+    if widget.path.is_empty() || widget.path.len() > 64 {
+        return Err(invalid!("Widget path must be 1-64 characters"));
+    }
+    if widget.unit.len() > 8 {
+        return Err(invalid!("Widget unit must be 8 characters or less"));
+    }
+    Ok(())
+}
+
+/// Parse "HH:MM" (24h) into minutes after midnight
+pub fn parse_time_of_day(value: &str) -> Result<u16> {
+    let parsed = value.trim().split_once(':').and_then(|(h, m)| {
+        let h: u16 = h.parse().ok()?;
+        let m: u16 = m.parse().ok()?;
+        (h < 24 && m < 60).then(|| h * 60 + m)
+    });
+    parsed.ok_or_else(|| invalid!("Time must be HH:MM (24h)"))
+}
+
+pub fn validate_timezone(tz: &str) -> Result<()> {
+    if tz.is_empty() || tz.len() > 64 {
+        return Err(invalid!("Timezone must be 1-64 characters"));
+    }
+    // POSIX TZ strings, e.g. CET-1CEST,M3.5.0,M10.5.0/3 or <+0530>-5:30
+    if !tz.chars().all(|c| c.is_ascii_alphanumeric() || ",.:/+-<>".contains(c)) {
+        return Err(invalid!("Timezone must be a POSIX TZ string"));
+    }
+    Ok(())
+}
+
+/// DNS label rules (RFC 1123), kept to 32 characters so a MAC suffix still fits
+pub fn validate_hostname(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > 32 {
+        return Err(invalid!("Hostname must be 1-32 characters"));
+    }
+    if !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+        return Err(invalid!("Hostname may only contain a-z, 0-9 and '-'"));
+    }
+    if name.starts_with('-') || name.ends_with('-') {
+        return Err(invalid!("Hostname cannot start or end with '-'"));
+    }
+    Ok(())
+}
+
+/// Latency reference host: a DNS name or IPv4 address, or empty to disable
+pub fn validate_ping_host(host: &str) -> Result<()> {
+    if host.len() > 64 {
+        return Err(invalid!("Latency host must be 64 characters or less"));
+    }
+    if !host.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-') {
+        return Err(invalid!("Latency host must be a hostname or IPv4 address"));
+    }
+    Ok(())
+}
+
+/// `screen_order`: up to 16 screen names (a-z, 0-9, '_'); unknown names are
+/// skipped by the UI so a config stays valid across builds
+pub fn validate_screen_order(names: &[String]) -> Result<()> {
+    if names.len() > 16 {
+        return Err(invalid!("At most 16 screens in screen_order"));
+    }
+    let valid = |n: &String| !n.is_empty() && n.len() <= 16
+        && n.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !names.iter().all(valid) {
+        return Err(invalid!("Screen names must be 1-16 characters of a-z, 0-9 and '_'"));
+    }
+    Ok(())
+}
+
+/// Most global labels added to every /metrics series
+pub const MAX_METRICS_LABELS: usize = 8;
+
+/// Labels individual /metrics series already carry; a global label with the
+/// same name would make the series invalid
+const SERIES_LABELS: &[&str] = &["version", "board", "model", "ssid", "profile", "target", "le", "code", "reason"];
+
+fn is_prometheus_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `metrics_prefix`: a Prometheus name of up to 32 characters, or empty for
+/// unprefixed series
+pub fn validate_metrics_prefix(prefix: &str) -> Result<()> {
+    if prefix.len() > 32 {
+        return Err(invalid!("Metrics prefix must be 32 characters or less"));
+    }
+    if !prefix.is_empty() && !is_prometheus_name(prefix) {
+        return Err(invalid!("Metrics prefix may only contain a-z, A-Z, 0-9 and '_', and cannot start with a digit"));
+    }
+    Ok(())
+}
+
+/// `metrics_labels`: up to 8 name/value pairs added to every /metrics series
+pub fn validate_metrics_labels(labels: &BTreeMap<String, String>) -> Result<()> {
+    if labels.len() > MAX_METRICS_LABELS {
+        return Err(invalid!("At most {} metrics labels", MAX_METRICS_LABELS));
+    }
+    for (name, value) in labels {
+        if name.len() > 32 || !is_prometheus_name(name) || name.starts_with("__") {
+            return Err(invalid!("Invalid metrics label name '{}'", name));
+        }
+        if SERIES_LABELS.contains(&name.as_str()) {
+            return Err(invalid!("Metrics label '{}' is already used by some series", name));
+        }
+        if value.len() > 64 || value.chars().any(|c| c.is_control()) {
+            return Err(invalid!("Metrics label values must be up to 64 characters without control characters"));
+        }
+    }
+    Ok(())
+}
+
+/// `ap_password`: empty for an open AP, otherwise a WPA2 passphrase
+pub fn validate_ap_password(password: &str) -> Result<()> {
+    if !password.is_empty() && !(8..=63).contains(&password.len()) {
+        return Err(invalid!("AP password must be empty (open AP) or 8-63 characters"));
+    }
+    Ok(())
+}
+
+/// `mqtt_broker`: empty, or an mqtt:// / mqtts:// URL
+pub fn validate_mqtt_broker(url: &str) -> Result<()> {
+    if url.is_empty() {
+        return Ok(());
+    }
+    if !(url.starts_with("mqtt://") || url.starts_with("mqtts://")) {
+        return Err(invalid!("MQTT broker must start with mqtt:// or mqtts://"));
+    }
+    if url.len() > 128 || url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(invalid!("MQTT broker URL is invalid"));
+    }
+    Ok(())
+}
+
+/// `syslog_server`: "host" or "host:port"; may only be empty while syslog is off
+pub fn validate_syslog_server(enabled: bool, server: &str) -> Result<()> {
+    if server.is_empty() {
+        return if enabled { Err(invalid!("Syslog needs a server")) } else { Ok(()) };
+    }
+    if crate::logging::syslog::parse_server(server).is_none() {
+        return Err(invalid!("Syslog server must be a hostname or IPv4 address with an optional :port"));
     }
     Ok(())
 }
 
+/// MQTT actions need a broker and a concrete topic (no wildcards)
+pub fn validate_button_actions(map: &ButtonMap, broker: &str) -> Result<()> {
+    validate_mqtt_broker(broker)?;
+    let topics = map.mqtt_topics();
+    if !topics.is_empty() && broker.is_empty() {
+        return Err(invalid!("MQTT button actions need an MQTT broker"));
+    }
+    for (slot, topic) in topics {
+        if topic.is_empty() || topic.len() > 128 || topic.contains(['+', '#', '\0']) {
+            return Err(invalid!("MQTT topic for {} must be 1-128 characters without wildcards", slot));
+        }
+    }
+    Ok(())
+}
+
+/// Most DNS servers a static configuration can list
+pub const MAX_STATIC_DNS: usize = 2;
+
+/// Parsed static IPv4 settings for the station
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaticIpv4 {
+    pub ip: Ipv4Addr,
+    /// Netmask as a prefix length, e.g. 24 for 255.255.255.0
+    pub prefix_len: u8,
+    pub gateway: Ipv4Addr,
+    pub dns: Vec<Ipv4Addr>,
+}
+
+fn parse_ipv4(what: &str, text: &str) -> Result<Ipv4Addr> {
+    text.trim().parse().map_err(|_| invalid!("{} '{}' is not an IPv4 address", what, text))
+}
+
+/// Static address settings; `None` for an empty address (DHCP). The address
+/// must be a usable host in the gateway's subnet, and the netmask contiguous
+/// (/8 to /30).
+pub fn parse_static_ipv4(ip: &str, netmask: &str, gateway: &str, dns: &[String]) -> Result<Option<StaticIpv4>> {
+    if ip.trim().is_empty() {
+        return Ok(None);
+    }
+    let ip = parse_ipv4("Static IP", ip)?;
+    let mask = u32::from(parse_ipv4("Netmask", netmask)?);
+    let prefix_len = mask.leading_ones();
+    if mask.count_ones() != prefix_len || !(8..=30).contains(&prefix_len) {
+        return Err(invalid!("Netmask must be contiguous, between /8 and /30"));
+    }
+    let gateway = parse_ipv4("Gateway", gateway)?;
+    for (what, addr) in [("Static IP", ip), ("Gateway", gateway)] {
+        let host = u32::from(addr) & !mask;
+        if addr.is_unspecified() || addr.is_loopback() || addr.is_multicast() || addr.is_broadcast()
+            || host == 0 || host == !mask {
+            return Err(invalid!("{} {} is not a usable host address", what, addr));
+        }
+    }
+    if ip == gateway || (u32::from(ip) & mask) != (u32::from(gateway) & mask) {
+        return Err(invalid!("Gateway must be another address in the static IP's subnet"));
+    }
+    if dns.len() > MAX_STATIC_DNS {
+        return Err(invalid!("At most {} DNS servers", MAX_STATIC_DNS));
+    }
+    let dns = dns.iter().map(|d| parse_ipv4("DNS server", d)).collect::<Result<Vec<_>>>()?;
+    Ok(Some(StaticIpv4 { ip, prefix_len: prefix_len as u8, gateway, dns }))
+}
+
+/// `name` with the last three MAC bytes appended, e.g. `esp32-a1b2c3`
+pub fn hostname_with_mac_suffix(name: &str, mac: &[u8; 6]) -> String {
+    format!("{}-{:02x}{:02x}{:02x}", name, mac[3], mac[4], mac[5])
+}
+
+/// `value` within `min..=max`; the message names the range, e.g. "Dim
+/// timeout must be 5-3600 s" or "Latitude must be -90 to 90"
+pub fn validate_range<T: PartialOrd + Copy + Default + core::fmt::Display>(what: &str, value: T, min: T, max: T, unit: &str) -> Result<()> {
+    if !(min..=max).contains(&value) {
+        let to = if min < T::default() { " to " } else { "-" };
+        return Err(invalid!("{} must be {}{}{}{}", what, min, to, max, unit));
+    }
+    Ok(())
+}
+
+/// Failures of a config update keyed by field, so one 400 lists every bad
+/// field rather than the first
+#[derive(Debug, Default)]
+pub struct FieldErrors(Vec<FieldError>);
+
+impl FieldErrors {
+    /// Record a failed `result` against `field`; the value on success
+    pub fn check<T>(&mut self, field: &str, result: Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(ConfigError::Fields(errors)) => {
+                self.0.extend(errors);
+                None
+            }
+            Err(e) => {
+                self.0.push(FieldError { field: field.to_string(), message: e.to_string() });
+                None
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<()> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Fields(self.0))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -44,6 +362,38 @@ mod tests {
         assert!(validate_ssid("").is_err());
         assert!(validate_ssid("a".repeat(33).as_str()).is_err());
         assert!(validate_ssid("Network\0").is_err());
+        assert!(validate_ap_password("").is_ok());
+        assert!(validate_ap_password("short").is_err());
+        assert!(validate_ap_password("dashboard").is_ok());
+    }
+
+    #[test]
+    fn test_parse_static_ipv4() {
+        let dns = vec!["1.1.1.1".to_string(), "9.9.9.9".to_string()];
+        assert_eq!(parse_static_ipv4("", "", "", &[]).unwrap(), None);
+        let parsed = parse_static_ipv4("192.168.1.50", "255.255.255.0", "192.168.1.1", &dns).unwrap().unwrap();
+        assert_eq!(parsed.prefix_len, 24);
+        assert_eq!(parsed.dns, vec![Ipv4Addr::new(1, 1, 1, 1), Ipv4Addr::new(9, 9, 9, 9)]);
+
+        // Gateway outside the subnet, holey netmask, broadcast host, bad DNS
+        assert!(parse_static_ipv4("192.168.1.50", "255.255.255.0", "192.168.2.1", &[]).is_err());
+        assert!(parse_static_ipv4("192.168.1.50", "255.0.255.0", "192.168.1.1", &[]).is_err());
+        assert!(parse_static_ipv4("192.168.1.255", "255.255.255.0", "192.168.1.1", &[]).is_err());
+        assert!(parse_static_ipv4("192.168.1.50", "255.255.255.0", "192.168.1.1", &["dns.local".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_validate_button_actions() {
+        use crate::input::action_map::ButtonAction;
+        let mut map = ButtonMap::default();
+        assert!(validate_button_actions(&map, "").is_ok());
+        assert!(validate_mqtt_broker("http://broker").is_err());
+
+        map.user_long = ButtonAction::Mqtt { topic: "desk/lamp".into(), payload: "toggle".into() };
+        assert!(validate_button_actions(&map, "").is_err());
+        assert!(validate_button_actions(&map, "mqtt://192.168.1.10:1883").is_ok());
+        map.user_long = ButtonAction::Mqtt { topic: "desk/#".into(), payload: String::new() };
+        assert!(validate_button_actions(&map, "mqtt://192.168.1.10").is_err());
     }
 
     #[test]
@@ -53,4 +403,75 @@ mod tests {
         assert!(validate_filename("file/name").is_err());
         assert!(validate_filename("").is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_validate_push_url() {
+        assert!(validate_push_url("").is_ok());
+        assert!(validate_push_url("http://influx.local:8086/api/v2/write?bucket=esp").is_ok());
+        assert!(validate_push_url("ftp://host/path").is_err());
+        assert!(validate_push_url("http://host/a b").is_err());
+    }
+
+    #[test]
+    fn test_validate_metrics_naming() {
+        assert!(validate_metrics_prefix("").is_ok());
+        assert!(validate_metrics_prefix("office_dash").is_ok());
+        assert!(validate_metrics_prefix("9lives").is_err());
+        assert!(validate_metrics_prefix("esp-32").is_err());
+
+        let labels = |pairs: &[(&str, &str)]| pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        assert!(validate_metrics_labels(&labels(&[("site", "lab"), ("device_id", "desk-1")])).is_ok());
+        assert!(validate_metrics_labels(&labels(&[("__name__", "x")])).is_err());
+        assert!(validate_metrics_labels(&labels(&[("target", "x")])).is_err());
+        assert!(validate_metrics_labels(&labels(&[("site", "a\nb")])).is_err());
+    }
+
+    #[test]
+    fn test_validate_hostname() {
+        assert!(validate_hostname("esp32").is_ok());
+        assert!(validate_hostname("desk-display-2").is_ok());
+        assert!(validate_hostname("").is_err());
+        assert!(validate_hostname("Desk").is_err());
+        assert!(validate_hostname("-desk").is_err());
+        assert!(validate_hostname("desk.local").is_err());
+        assert!(validate_hostname(&"a".repeat(33)).is_err());
+        let suffixed = hostname_with_mac_suffix("esp32", &[0x24, 0x0a, 0xc4, 0xa1, 0xb2, 0xc3]);
+        assert_eq!(suffixed, "esp32-a1b2c3");
+        assert!(validate_hostname(&suffixed).is_ok());
+    }
+
+    #[test]
+    fn test_validate_screen_order() {
+        assert!(validate_screen_order(&[]).is_ok());
+        assert!(validate_screen_order(&["timer".to_string(), "system".to_string()]).is_ok());
+        assert!(validate_screen_order(&["".to_string()]).is_err());
+        assert!(validate_screen_order(&["Timer".to_string()]).is_err());
+        assert!(validate_screen_order(&vec!["system".to_string(); 17]).is_err());
+    }
+
+    #[test]
+    fn test_field_errors_collect_every_field() {
+        assert!(validate_range("Dim timeout", 5u32, 5, 3600, " s").is_ok());
+        assert!(validate_range("Latitude", f32::NAN, -90.0, 90.0, "").is_err());
+
+        let mut errors = FieldErrors::default();
+        assert_eq!(errors.check("hostname", validate_hostname("desk")), Some(()));
+        assert!(errors.is_empty());
+        assert_eq!(errors.check("dim_timeout", validate_range("Dim timeout", 0u32, 5, 3600, " s")), None);
+        errors.check("wifi_ssid", validate_ssid(""));
+        let err = errors.into_result().unwrap_err();
+        assert_eq!(err.field_errors().iter().map(|e| e.field.as_str()).collect::<Vec<_>>(), ["dim_timeout", "wifi_ssid"]);
+        assert_eq!(err.to_string(), "Dim timeout must be 5-3600 s; WiFi SSID cannot be empty");
+    }
+
+    #[test]
+    fn test_parse_time_of_day() {
+        assert_eq!(parse_time_of_day("07:00").unwrap(), 420);
+        assert_eq!(parse_time_of_day("23:59").unwrap(), 1439);
+        assert_eq!(parse_time_of_day("0:05").unwrap(), 5);
+        assert!(parse_time_of_day("24:00").is_err());
+        assert!(parse_time_of_day("12:60").is_err());
+        assert!(parse_time_of_day("noon").is_err());
+        assert!(parse_time_of_day("65535:00").is_err(), "no overflow on out-of-range hours");
+    }
+}
@@ -0,0 +1,224 @@
+// Weather client: fetches current conditions and a short hourly forecast from
+// Open-Meteo (no API key needed) for the configured lat/lon and caches the result
+// for the Weather screen.
+
+use anyhow::Result;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use esp_idf_hal::delay::FreeRtos;
+use crate::config::Config;
+use crate::system::ShutdownSignal;
+use super::http_client::{self, RequestOptions};
+
+const REFRESH_INTERVAL_SECS: u32 = 15 * 60;
+const RETRY_INTERVAL_SECS: u32 = 60;
+const IDLE_POLL_SECS: u32 = 30;
+const MAX_RESPONSE_BYTES: usize = 8 * 1024;
+const HTTP_TIMEOUT_MS: u64 = 10_000;
+pub const FORECAST_HOURS: usize = 3;
+
+static LATEST: Mutex<Option<WeatherData>> = Mutex::new(None);
+static GENERATION: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeatherCondition {
+    Clear,
+    PartlyCloudy,
+    Cloudy,
+    Fog,
+    Rain,
+    Snow,
+    Thunder,
+    Unknown,
+}
+
+impl WeatherCondition {
+    /// Map a WMO weather interpretation code to a condition
+    pub fn from_wmo_code(code: u8) -> Self {
+        match code {
+            0 => Self::Clear,
+            1 | 2 => Self::PartlyCloudy,
+            3 => Self::Cloudy,
+            45 | 48 => Self::Fog,
+            51..=67 | 80..=82 => Self::Rain,
+            71..=77 | 85 | 86 => Self::Snow,
+            95..=99 => Self::Thunder,
+            _ => Self::Unknown,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Clear => "Clear",
+            Self::PartlyCloudy => "Partly cloudy",
+            Self::Cloudy => "Cloudy",
+            Self::Fog => "Fog",
+            Self::Rain => "Rain",
+            Self::Snow => "Snow",
+            Self::Thunder => "Thunderstorm",
+            Self::Unknown => "Unknown",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForecastPoint {
+    pub hour: u8,
+    pub temperature_c: f32,
+    pub condition: WeatherCondition,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeatherData {
+    pub temperature_c: f32,
+    pub wind_kmh: f32,
+    pub condition: WeatherCondition,
+    pub forecast: Vec<ForecastPoint>,
+    /// Uptime (seconds) when this was fetched
+    pub fetched_at_s: u64,
+}
+
+// Subset of the Open-Meteo response we care about
+#[derive(serde::Deserialize)]
+struct ApiResponse {
+    current: ApiCurrent,
+    hourly: ApiHourly,
+}
+
+#[derive(serde::Deserialize)]
+struct ApiCurrent {
+    temperature_2m: f32,
+    weather_code: u8,
+    wind_speed_10m: f32,
+}
+
+#[derive(serde::Deserialize)]
+struct ApiHourly {
+    time: Vec<String>,
+    temperature_2m: Vec<f32>,
+    weather_code: Vec<u8>,
+}
+
+fn build_url(latitude: f32, longitude: f32) -> String {
+    format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={:.4}&longitude={:.4}\
+         &current=temperature_2m,weather_code,wind_speed_10m\
+         &hourly=temperature_2m,weather_code&forecast_hours={}&timezone=auto",
+        latitude, longitude, FORECAST_HOURS
+    )
+}
+
+/// Parse an Open-Meteo response body into `WeatherData`
+pub fn parse_response(body: &[u8], fetched_at_s: u64) -> Result<WeatherData> {
+    let api: ApiResponse = serde_json::from_slice(body)?;
+    let forecast = api.hourly.time.iter()
+        .zip(api.hourly.temperature_2m.iter())
+        .zip(api.hourly.weather_code.iter())
+        .take(FORECAST_HOURS)
+        .map(|((time, temp), code)| ForecastPoint {
+            // ISO 8601 local time, e.g. "2024-05-01T14:00"
+            hour: time.get(11..13).and_then(|h| h.parse().ok()).unwrap_or(0),
+            temperature_c: *temp,
+            condition: WeatherCondition::from_wmo_code(*code),
+        })
+        .collect();
+
+    Ok(WeatherData {
+        temperature_c: api.current.temperature_2m,
+        wind_kmh: api.current.wind_speed_10m,
+        condition: WeatherCondition::from_wmo_code(api.current.weather_code),
+        forecast,
+        fetched_at_s,
+    })
+}
+
+fn http_get(url: &str) -> Result<Vec<u8>> {
+    let options = RequestOptions {
+        timeout: Duration::from_millis(HTTP_TIMEOUT_MS),
+        max_response_bytes: MAX_RESPONSE_BYTES,
+        ..Default::default()
+    };
+    Ok(http_client::get(url, &options)?.body)
+}
+
+fn fetch(latitude: f32, longitude: f32) -> Result<WeatherData> {
+    crate::span!("Weather fetch");
+    let body = http_get(&build_url(latitude, longitude))?;
+    let uptime_s = unsafe { (esp_idf_sys::esp_timer_get_time() / 1_000_000) as u64 };
+    parse_response(&body, uptime_s)
+}
+
+/// Latest cached weather, if any
+pub fn latest() -> Option<WeatherData> {
+    LATEST.lock().ok().and_then(|w| w.clone())
+}
+
+/// Increments every time new weather data is cached
+pub fn generation() -> u32 {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+/// Start the background fetch thread. Safe to call multiple times.
+pub fn start(config: Arc<Mutex<Config>>, shutdown: ShutdownSignal) -> Result<()> {
+    static STARTED: AtomicBool = AtomicBool::new(false);
+    if STARTED.swap(true, Ordering::SeqCst) { return Ok(()); }
+
+    std::thread::Builder::new()
+        .name("weather".to_string())
+        .stack_size(10 * 1024) // TLS handshake needs headroom
+        .spawn(move || loop {
+            // Non-essential: skipped while thermally throttled
+            let location = match config.lock() {
+                Ok(cfg) if cfg.weather_enabled && !crate::power::thermal::is_throttled() => {
+                    Some((cfg.weather_latitude, cfg.weather_longitude))
+                }
+                _ => None,
+            };
+
+            let wait_secs = match location {
+                Some((lat, lon)) => match fetch(lat, lon) {
+                    Ok(data) => {
+                        log::info!("Weather: {:.1}°C {}", data.temperature_c, data.condition.label());
+                        if let Ok(mut slot) = LATEST.lock() {
+                            *slot = Some(data);
+                        }
+                        GENERATION.fetch_add(1, Ordering::Relaxed);
+                        REFRESH_INTERVAL_SECS
+                    }
+                    Err(e) => {
+                        log::warn!("Weather fetch failed: {:?}", e);
+                        RETRY_INTERVAL_SECS
+                    }
+                },
+                None => IDLE_POLL_SECS,
+            };
+
+            for _ in 0..wait_secs {
+                if shutdown.is_shutdown_requested() {
+                    return;
+                }
+                FreeRtos::delay_ms(1000);
+            }
+        })?;
+
+    log::info!("Weather client started");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response() {
+        let body = br#"{"current":{"time":"2024-05-01T13:45","temperature_2m":18.4,"weather_code":61,"wind_speed_10m":12.0},
+            "hourly":{"time":["2024-05-01T14:00","2024-05-01T15:00","2024-05-01T16:00"],
+            "temperature_2m":[18.9,19.2,17.5],"weather_code":[61,3,0]}}"#;
+        let data = parse_response(body, 10).unwrap();
+        assert_eq!(data.condition, WeatherCondition::Rain);
+        assert_eq!(data.forecast.len(), 3);
+        assert_eq!(data.forecast[0].hour, 14);
+        assert_eq!(data.forecast[2].condition, WeatherCondition::Clear);
+    }
+}
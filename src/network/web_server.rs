@@ -3,19 +3,23 @@ use esp_idf_svc::http::server::EspHttpServer;
 use esp_idf_svc::io::Write;
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
-use esp_idf_hal::delay::FreeRtos;
 use crate::config::Config;
-use crate::ota::OtaManager;
+use crate::error::HttpStatus;
+use crate::ota::{OtaError, OtaManager};
 use crate::ota::manager::ensure_ota_boot_if_needed;
-use crate::metrics_formatter::MetricsFormatter;
-// use crate::network::compression::write_compressed_response;
+use crate::metrics_formatter::{Exposition, MetricsFormatter};
+use crate::network::compression::write_compressed_response;
 use crate::network::binary_protocol::MetricsBinaryPacket;
 use crate::network::error_wrapper::error_response;
 use crate::network::error_handler::ErrorResponse;
+use crate::network::heap_tracker::TrackedHandlers;
 
 // Global flag to prevent heavy operations during OTA
 static OTA_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
 
+// Basic password protection for OTA and rollback
+const OTA_PASSWORD: &str = "esp32"; // Change this to your preferred password
+
 pub struct WebConfigServer {
     _server: EspHttpServer<'static>,
 }
@@ -64,7 +68,7 @@ impl WebConfigServer {
         // Reduce accept backlog issues by setting keep-alive where possible is handled per handler
         
         // Home page (templated, fast and memory-safe)
-        server.fn_handler("/", esp_idf_svc::http::Method::Get, |req| {
+        server.tracked_handler("/", esp_idf_svc::http::Method::Get, |req| {
             let instr = crate::network::server_config::RequestInstrumentation::capture(None);
             let result = crate::network::templated_home::handle_home_templated(req);
             let status = if result.is_ok() { 200 } else { 500 };
@@ -139,7 +143,7 @@ impl WebConfigServer {
 
         // Get current configuration
         let config_clone2 = config.clone();
-        server.fn_handler("/api/config", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/api/config", esp_idf_svc::http::Method::Get, move |req| {
             let config = match config_clone2.lock() {
                 Ok(cfg) => cfg,
                 Err(e) => {
@@ -148,69 +152,37 @@ impl WebConfigServer {
                 }
             };
             let json = serde_json::to_string(&*config)?;
-            
-            let mut response = req.into_response(
-                200,
-                Some("OK"),
-                &[("Content-Type", "application/json")]
-            )?;
-            response.write_all(json.as_bytes())?;
+            drop(config);
+
+            write_compressed_response(req, "application/json", &[], json.as_bytes())?;
             Ok(()) as Result<(), Box<dyn std::error::Error>>
         })?;
 
     // Update configuration (accepts partial updates via WebConfigUpdate)
     let config_clone3 = config.clone();
-    server.fn_handler("/api/config", esp_idf_svc::http::Method::Post, move |mut req| {
-            // Cap config payload size to 1KB
-            let mut buf = vec![0; 1024];
+    server.tracked_handler("/api/config", esp_idf_svc::http::Method::Post, move |mut req| {
+        if let Err(reason) = crate::network::csrf::verify(&req) {
+            return ErrorResponse::forbidden(reason).send(req);
+        }
+            // Cap config payload size to 4KB (custom widget lists can be long)
+            let mut buf = vec![0; crate::network::api_core::MAX_CONFIG_BODY];
             let len = req.read(&mut buf)?;
             if len > buf.len() {
-                return error_response(req, 413, "Payload too large (max 1KB)");
+                return error_response(req, 413, "Payload too large (max 4KB)");
             }
             buf.truncate(len);
             
-        let json_str = std::str::from_utf8(&buf)?;
-        
-        // Parse the web config update format; allow partial updates
-        #[derive(serde::Deserialize)]
-        struct WebConfigUpdate {
-            wifi_ssid: Option<String>,
-            wifi_password: Option<String>,
-            brightness: Option<u8>,
-            auto_dim: Option<bool>,
-            dim_timeout: Option<u32>,
-            sleep_timeout: Option<u32>,
-            auto_update: Option<bool>,
-            update_interval: Option<u32>,
-        }
-        let web_update: WebConfigUpdate = match serde_json::from_str(json_str) {
-            Ok(v) => v,
-            Err(e) => {
-                log::warn!("Invalid config update JSON: {}", e);
-                return ErrorResponse::bad_request("Invalid JSON").send(req);
-            }
-        };
-            
-            // Convert to internal config format
-        let new_config = {
-                // Start from existing config to support partial updates
-                let mut cfg = match config_clone3.lock() {
-                    Ok(cfg) => cfg.clone(),
-                    Err(e) => {
-                        log::error!("Failed to lock config: {}", e);
-                        return ErrorResponse::bad_request("Configuration lock failed").send(req);
-                    }
-                };
-            // Apply fields present in request (only those provided)
-            if let Some(ssid) = web_update.wifi_ssid { cfg.wifi_ssid = ssid; }
-            if let Some(pw) = web_update.wifi_password { cfg.wifi_password = pw; }
-            if let Some(br) = web_update.brightness { cfg.brightness = br; }
-            if let Some(ad) = web_update.auto_dim { cfg.auto_brightness = ad; }
-            if let Some(dim) = web_update.dim_timeout { cfg.dim_timeout_secs = dim.clamp(5, 3600); }
-            if let Some(slp) = web_update.sleep_timeout { cfg.sleep_timeout_secs = slp.clamp(10, 24*3600); }
-            if let Some(update) = web_update.auto_update { cfg.ota_enabled = update; }
-            if let Some(iv) = web_update.update_interval { cfg.ota_check_interval_hours = iv.max(1); }
-                cfg
+            // Start from existing config to support partial updates
+            let current = match config_clone3.lock() {
+                Ok(cfg) => cfg.clone(),
+                Err(e) => {
+                    log::error!("Failed to lock config: {}", e);
+                    return ErrorResponse::bad_request("Configuration lock failed").send(req);
+                }
+            };
+            let new_config = match crate::network::api_core::apply_config_update(&current, &buf) {
+                Ok(cfg) => cfg,
+                Err(e) => return ErrorResponse::from_error(&e).with_fields(e.field_errors()).send(req),
             };
             
             // Update and save config
@@ -223,7 +195,10 @@ impl WebConfigServer {
                     }
                 };
                 *config = new_config;
-                config.save()?;
+                if let Err(e) = config.save() {
+                    log::error!("Failed to save config: {}", e);
+                    return ErrorResponse::from_error(&e).send(req);
+                }
             }
             
         let _response = req.into_ok_response()?;
@@ -232,12 +207,12 @@ impl WebConfigServer {
 
         // System info endpoint
         let config_clone_system = config.clone();
-        server.fn_handler("/api/system", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/api/system", esp_idf_svc::http::Method::Get, move |req| {
             let instr = crate::network::server_config::RequestInstrumentation::capture(None);
-            // Get SSID from config
-            let ssid = match config_clone_system.lock() {
-                Ok(cfg) => cfg.wifi_ssid.clone(),
-                Err(_) => "Unknown".to_string(),
+            // Get SSID and remote config state from config
+            let (ssid, remote_sync_enabled, remote_config_version) = match config_clone_system.lock() {
+                Ok(cfg) => (cfg.wifi_ssid.clone(), cfg.remote_sync_enabled, cfg.remote_config_version),
+                Err(_) => ("Unknown".to_string(), false, 0),
             };
             
             let reset_reason_str = crate::system::reset::get_reset_reason();
@@ -253,21 +228,7 @@ impl WebConfigServer {
                 (label, !next.is_null())
             };
 
-            // Compute current IP address if connected
-            let ip_address = unsafe {
-                let key = b"WIFI_STA_DEF\0";
-                let netif = esp_idf_sys::esp_netif_get_handle_from_ifkey(key.as_ptr() as *const ::core::ffi::c_char);
-                if !netif.is_null() {
-                    let mut ip_info = esp_idf_sys::esp_netif_ip_info_t::default();
-                    if esp_idf_sys::esp_netif_get_ip_info(netif, &mut ip_info) == esp_idf_sys::ESP_OK && ip_info.ip.addr != 0 {
-                        Some(format!("{}.{}.{}.{}",
-                            ip_info.ip.addr & 0xff,
-                            (ip_info.ip.addr >> 8) & 0xff,
-                            (ip_info.ip.addr >> 16) & 0xff,
-                            (ip_info.ip.addr >> 24) & 0xff))
-                    } else { None }
-                } else { None }
-            };
+            let ip_address = station_ip();
 
             let json = serde_json::json!({
                 "version": crate::version::DISPLAY_VERSION,
@@ -277,26 +238,275 @@ impl WebConfigServer {
                 "reset_reason": reset_reason_str,
                 "reset_code": reset_code,
                 "wifi": {
-                    "ip": ip_address.unwrap_or_else(|| "".to_string())
+                    "ip": ip_address.unwrap_or_else(|| "".to_string()),
+                    "ipv6": crate::network::ipv6::addresses().iter().map(|a| a.to_string()).collect::<Vec<_>>()
                 },
                 "ota": {
                     "running_partition": running_label,
                     "available": ota_available
+                },
+                "power_events": crate::power::events::counts(),
+                "thermal_throttled": crate::power::thermal::is_throttled(),
+                "safe_mode": crate::system::safe_mode::active(),
+                "adc_calibration": crate::sensors::adc_cal::scheme().as_str(),
+                "power_monitor": crate::hardware::power_monitor::snapshot(),
+                "quiet_hours": crate::power::quiet_hours::status(),
+                "storage": {
+                    "namespaces": crate::storage::kv::usage(),
+                    "nvs": crate::storage::kv::stats()
+                },
+                "remote_config": {
+                    "enabled": remote_sync_enabled,
+                    "version": remote_config_version,
+                    "error": crate::config::remote_sync::last_error()
                 }
             }).to_string();
+            write_compressed_response(req, "application/json", &[], json.as_bytes())?;
+            instr.log_completion("/api/system", 200);
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        // Build provenance and partition layout, for triaging field reports
+        server.tracked_handler("/api/firmware", esp_idf_svc::http::Method::Get, move |req| {
+            let json = serde_json::to_string(&crate::version::firmware_info())?;
+            write_compressed_response(req, "application/json", &[], json.as_bytes())?;
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        // Per-boot reliability records, 7-day availability and MTBF
+        server.tracked_handler("/api/reliability", esp_idf_svc::http::Method::Get, move |req| {
+            let instr = crate::network::server_config::RequestInstrumentation::capture(None);
+            let report = match crate::system::uptime_tracker::latest_report() {
+                Some(report) => report,
+                None => return ErrorResponse::not_found("Reliability tracking unavailable").send(req),
+            };
+            let json = serde_json::to_string(&report)?;
+            write_compressed_response(req, "application/json", &[], json.as_bytes())?;
+            instr.log_completion("/api/reliability", 200);
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        // Lifetime counters (boots, OTA updates, frames, button presses, WiFi reconnects)
+        server.tracked_handler("/api/stats", esp_idf_svc::http::Method::Get, move |req| {
+            let stats = match crate::system::lifetime_stats::current() {
+                Some(stats) => stats,
+                None => return ErrorResponse::not_found("Lifetime stats unavailable").send(req),
+            };
+            let json = serde_json::to_string(&stats)?;
             let mut response = req.into_response(
                 200,
                 Some("OK"),
-                &[("Content-Type", "application/json")]
+                &[("Content-Type", "application/json"), ("Cache-Control", "no-store")]
+            )?;
+            response.write_all(json.as_bytes())?;
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        // Stats page, with the shared navbar
+        server.tracked_handler("/stats", esp_idf_svc::http::Method::Get, move |req| {
+            let html = crate::templates::render_page(crate::templates::STATS_PAGE, "/stats");
+            let mut response = req.into_response(
+                200,
+                Some("OK"),
+                &[("Content-Type", "text/html; charset=utf-8"), ("Cache-Control", "no-cache"), ("Connection", "close")],
+            )?;
+            response.write_all(html.as_bytes())?;
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        // String table for page scripts, e.g. /api/i18n/de.json; unknown keys fall back to English
+        server.tracked_handler("/api/i18n/*", esp_idf_svc::http::Method::Get, move |req| {
+            let code = req.uri()
+                .split('?')
+                .next()
+                .and_then(|path| path.strip_prefix("/api/i18n/"))
+                .and_then(|file| file.strip_suffix(".json"))
+                .unwrap_or("");
+            let Some(language) = crate::i18n::Language::from_code(code) else {
+                return ErrorResponse::not_found(format!("No strings for language '{}'", code)).send(req);
+            };
+            let strings: serde_json::Map<String, serde_json::Value> = crate::i18n::strings(language)
+                .map(|(key, text)| (key.to_string(), text.into()))
+                .collect();
+            let json = serde_json::Value::Object(strings).to_string();
+            // Compiled into the firmware, so the body only changes with an update
+            let etag = crate::network::etag::content_etag(json.as_bytes());
+            if crate::network::etag::is_fresh(&req, &etag) {
+                return crate::network::etag::send_not_modified(req, &etag, &[]);
+            }
+            write_compressed_response(req, "application/json", &[("ETag", etag.as_str())], json.as_bytes())?;
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        // Heap peak per route, worst first, to find handlers that risk OOM
+        server.tracked_handler("/api/perf/http", esp_idf_svc::http::Method::Get, move |req| {
+            let body = serde_json::json!({
+                "hooks_active": crate::network::heap_tracker::hooks_active(),
+                "routes": crate::network::heap_tracker::snapshot(),
+            });
+            let mut response = req.into_response(
+                200,
+                Some("OK"),
+                &[("Content-Type", "application/json"), ("Cache-Control", "no-store")]
+            )?;
+            response.write_all(body.to_string().as_bytes())?;
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        // Roaming diagnostics: recent associations (newest first), the pinned
+        // BSSID and lifetime disconnects per reason code (most frequent first)
+        let config_connections = config.clone();
+        server.tracked_handler("/api/wifi/connections", esp_idf_svc::http::Method::Get, move |req| {
+            let pinned = config_connections.lock().map(|c| c.wifi_bssid.clone()).unwrap_or_default();
+            let records = crate::network::connection_history::with_history(|h| h.records());
+            let reasons = crate::network::disconnect_reasons::histogram();
+            let json = serde_json::to_string(&serde_json::json!({
+                "pinned_bssid": (!pinned.is_empty()).then_some(pinned),
+                "records": records,
+                "disconnect_reasons": reasons.top(usize::MAX),
+            }))?;
+            let mut response = req.into_response(
+                200,
+                Some("OK"),
+                &[("Content-Type", "application/json"), ("Cache-Control", "no-store")]
+            )?;
+            response.write_all(json.as_bytes())?;
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        // Pin the station to one access point (applied now and on every boot)
+        let config_pin = config.clone();
+        server.tracked_handler("/api/wifi/pin", esp_idf_svc::http::Method::Post, move |mut req| {
+            if let Err(reason) = crate::network::csrf::verify(&req) {
+                return ErrorResponse::forbidden(reason).send(req);
+            }
+            let mut buf = [0u8; 128];
+            let len = req.read(&mut buf)?;
+            let current = match config_pin.lock() {
+                Ok(cfg) => cfg.clone(),
+                Err(_) => return ErrorResponse::bad_request("Configuration lock failed").send(req),
+            };
+            let (new_config, bssid) = match crate::network::api_core::apply_bssid_pin(&current, &buf[..len]) {
+                Ok(result) => result,
+                Err(e) => return ErrorResponse::from_error(&e).send(req),
+            };
+            if let Ok(mut config) = config_pin.lock() {
+                *config = new_config;
+                config.save()?;
+            }
+            if let Err(e) = crate::network::wifi_reconnect::WifiReconnectManager::pin_bssid(bssid) {
+                log::warn!("Failed to apply BSSID pin: {:?}", e);
+                return error_response(req, 500, "Pin saved but could not be applied; it takes effect after restart");
+            }
+            let _response = req.into_ok_response()?;
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        // Throughput test: POST starts a run against nettest_url, GET polls until "running" is false
+        server.tracked_handler("/api/nettest", esp_idf_svc::http::Method::Get, move |req| {
+            let json = serde_json::to_string(&crate::network::nettest::status())?;
+            let mut response = req.into_response(
+                200,
+                Some("OK"),
+                &[("Content-Type", "application/json"), ("Cache-Control", "no-store")]
+            )?;
+            response.write_all(json.as_bytes())?;
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        let config_nettest = config.clone();
+        server.tracked_handler("/api/nettest", esp_idf_svc::http::Method::Post, move |req| {
+            if let Err(reason) = crate::network::csrf::verify(&req) {
+                return ErrorResponse::forbidden(reason).send(req);
+            }
+            let url = config_nettest.lock().map(|c| c.nettest_url.clone()).unwrap_or_default();
+            if url.is_empty() {
+                return ErrorResponse::bad_request("No nettest_url configured").send(req);
+            }
+            // A run already in progress is reported instead of starting another
+            crate::network::nettest::start(url);
+            let json = serde_json::to_string(&crate::network::nettest::status())?;
+            let mut response = req.into_response(
+                202,
+                Some("Accepted"),
+                &[("Content-Type", "application/json"), ("Cache-Control", "no-store")]
+            )?;
+            response.write_all(json.as_bytes())?;
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        // Hardware self-test for board bring-up; poll GET until "running" is false
+        server.tracked_handler("/api/selftest", esp_idf_svc::http::Method::Get, move |req| {
+            let json = serde_json::to_string(&crate::system::selftest::status())?;
+            let mut response = req.into_response(
+                200,
+                Some("OK"),
+                &[("Content-Type", "application/json"), ("Cache-Control", "no-store")]
+            )?;
+            response.write_all(json.as_bytes())?;
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        server.tracked_handler("/api/selftest", esp_idf_svc::http::Method::Post, move |req| {
+            if let Err(reason) = crate::network::csrf::verify(&req) {
+                return ErrorResponse::forbidden(reason).send(req);
+            }
+            // The main loop picks the request up; a run in progress is reported as is
+            crate::system::selftest::request();
+            let json = serde_json::to_string(&crate::system::selftest::status())?;
+            let mut response = req.into_response(
+                202,
+                Some("Accepted"),
+                &[("Content-Type", "application/json"), ("Cache-Control", "no-store")]
+            )?;
+            response.write_all(json.as_bytes())?;
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        // Display fill/text/dirty-rect benchmark; poll GET until "running" is false
+        server.tracked_handler("/api/display/benchmark", esp_idf_svc::http::Method::Get, move |req| {
+            let json = serde_json::to_string(&crate::display::benchmark::status())?;
+            let mut response = req.into_response(
+                200,
+                Some("OK"),
+                &[("Content-Type", "application/json"), ("Cache-Control", "no-store")]
+            )?;
+            response.write_all(json.as_bytes())?;
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        server.tracked_handler("/api/display/benchmark", esp_idf_svc::http::Method::Post, move |req| {
+            if let Err(reason) = crate::network::csrf::verify(&req) {
+                return ErrorResponse::forbidden(reason).send(req);
+            }
+            // The main loop picks the request up; a run in progress is reported as is
+            crate::display::benchmark::request();
+            let json = serde_json::to_string(&crate::display::benchmark::status())?;
+            let mut response = req.into_response(
+                202,
+                Some("Accepted"),
+                &[("Content-Type", "application/json"), ("Cache-Control", "no-store")]
+            )?;
+            response.write_all(json.as_bytes())?;
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        // Nearby access points for the WiFi picker; poll until "scanning" is false
+        server.tracked_handler("/api/wifi/scan", esp_idf_svc::http::Method::Get, move |req| {
+            let status = crate::network::wifi_scan::status_or_scan();
+            let json = serde_json::to_string(&status)?;
+            let mut response = req.into_response(
+                200,
+                Some("OK"),
+                &[("Content-Type", "application/json"), ("Cache-Control", "no-store")]
             )?;
             response.write_all(json.as_bytes())?;
-            instr.log_completion("/api/system", 200);
             Ok(()) as Result<(), Box<dyn std::error::Error>>
         })?;
 
         // Health check endpoint - simple and lightweight
         let metrics_health = metrics.clone();
-        server.fn_handler("/health", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/health", esp_idf_svc::http::Method::Get, move |req| {
             // Observability begin
             let _obs_start = crate::network::observability::begin_request();
             // Keep /health minimal and fast: avoid extra logging/work
@@ -316,6 +526,12 @@ impl WebConfigServer {
                 issues.push("low_memory");
             }
             
+            let display_ok = crate::display::display_ok();
+            if !display_ok {
+                status = "warning";
+                issues.push("display_unavailable");
+            }
+            
             // Keep health computation minimal; skip metrics lock to avoid contention
             
             // Simple JSON response
@@ -335,6 +551,8 @@ impl WebConfigServer {
                 "reset_code": reset_code,
                 "wifi_rssi": wifi_rssi,
                 "wifi": wifi_stats,
+                "display_ok": display_ok,
+                "display_reinits": crate::display::display_reinits(),
                 "boot_id": crate::network::observability::boot_id(),
             }).to_string();
             
@@ -354,7 +572,7 @@ impl WebConfigServer {
         })?;
 
         // Ultra-light ping endpoint (keep-alive friendly)
-        server.fn_handler("/ping", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/ping", esp_idf_svc::http::Method::Get, move |req| {
             let _obs_start = crate::network::observability::begin_request();
             let mut response = req.into_response(
                 200,
@@ -371,14 +589,14 @@ impl WebConfigServer {
         })?;
 
         // Debug observability snapshots (on-demand JSON)
-        server.fn_handler("/debug/stats", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/debug/stats", esp_idf_svc::http::Method::Get, move |req| {
             let json = serde_json::to_string(&crate::network::observability::http_snapshot())?;
             let mut response = req.into_response(200, Some("OK"), &[("Content-Type", "application/json"), ("Connection", "close")])?;
             response.write_all(json.as_bytes())?;
             Ok(()) as Result<(), Box<dyn std::error::Error>>
         })?;
 
-        server.fn_handler("/debug/events", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/debug/events", esp_idf_svc::http::Method::Get, move |req| {
             let json = serde_json::to_string(&crate::network::observability::events_snapshot())?;
             let mut response = req.into_response(200, Some("OK"), &[("Content-Type", "application/json"), ("Connection", "close")])?;
             response.write_all(json.as_bytes())?;
@@ -386,7 +604,10 @@ impl WebConfigServer {
         })?;
 
         // Restart endpoint for remote device management - protected
-        server.fn_handler("/restart", esp_idf_svc::http::Method::Post, move |req| {
+        server.tracked_handler("/restart", esp_idf_svc::http::Method::Post, move |req| {
+            if let Err(reason) = crate::network::csrf::verify(&req) {
+                return ErrorResponse::forbidden(reason).send(req);
+            }
             // Check for authentication header
             const RESTART_TOKEN: &str = "esp32-restart";
             let auth_header = req.header("X-Restart-Token").unwrap_or("");
@@ -398,11 +619,8 @@ impl WebConfigServer {
             
             log::warn!("Authenticated restart requested via HTTP");
             
-            // Schedule restart after response
-            std::thread::spawn(|| {
-                FreeRtos::delay_ms(1_000);
-                unsafe { esp_idf_sys::esp_restart(); }
-            });
+            // The main loop restarts once the response is out
+            crate::system::shutdown::request_restart(crate::system::shutdown::RestartReason::Requested);
             
             let response_json = r#"{"status":"ok","message":"Device will restart in 1 second"}"#;
             let mut response = req.into_ok_response()?;
@@ -411,7 +629,7 @@ impl WebConfigServer {
         })?;
 
         // Prometheus metrics endpoint - optimized with formatter
-        server.fn_handler("/metrics", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/metrics", esp_idf_svc::http::Method::Get, move |req| {
             let instr = crate::network::server_config::RequestInstrumentation::capture(None);
             // Check if OTA is in progress
             if OTA_IN_PROGRESS.load(Ordering::Acquire) {
@@ -425,6 +643,8 @@ impl WebConfigServer {
             let heap_free = unsafe { esp_idf_sys::esp_get_free_heap_size() };
             let heap_total = unsafe { esp_idf_sys::esp_get_minimum_free_heap_size() };
             
+            let exposition = Exposition::from_accept(req.header("Accept"));
+            
             // Get device info for labels
             let version = crate::version::DISPLAY_VERSION;
             let board_type = "ESP32-S3";
@@ -436,7 +656,7 @@ impl WebConfigServer {
             let formatted_metrics = match metrics_result {
                 Ok(metrics_guard) => {
                     // Create formatter and format metrics
-                    let mut formatter = MetricsFormatter::new();
+                    let mut formatter = MetricsFormatter::new().with_exposition(exposition);
                     formatter.format_metrics(
                         &*metrics_guard,
                         version,
@@ -450,30 +670,101 @@ impl WebConfigServer {
                 Err(_) => {
                     // If we can't get metrics, return partial data
                     log::warn!("Metrics lock contended, returning partial data");
-                    Ok(format!(
-                        "# HELP esp32_device_info Device information\n\
-                        # TYPE esp32_device_info gauge\n\
-                        esp32_device_info{{version=\"{}\",board=\"{}\",model=\"{}\"}} 1\n\n\
-                        # HELP esp32_uptime_seconds Total uptime in seconds\n\
-                        # TYPE esp32_uptime_seconds counter\n\
-                        esp32_uptime_seconds {}\n\n\
-                        # HELP esp32_heap_free_bytes Current free heap memory in bytes\n\
-                        # TYPE esp32_heap_free_bytes gauge\n\
-                        esp32_heap_free_bytes {}\n\n\
-                        # HELP esp32_metrics_unavailable Metrics temporarily unavailable\n\
-                        # TYPE esp32_metrics_unavailable gauge\n\
-                        esp32_metrics_unavailable 1\n",
-                        version, board_type, chip_model, uptime_seconds, heap_free
-                    ))
+                    let device_labels = format!("version=\"{}\",board=\"{}\",model=\"{}\"", version, board_type, chip_model);
+                    MetricsFormatter::new().with_exposition(exposition).format_series(&[
+                        ("device_info", "Device information", "gauge", &device_labels, 1.0),
+                        ("uptime_seconds", "Total uptime in seconds", "gauge", "", uptime_seconds as f64),
+                        ("heap_free_bytes", "Current free heap memory in bytes", "gauge", "", heap_free as f64),
+                        ("metrics_unavailable", "Metrics temporarily unavailable", "gauge", "", 1.0),
+                    ])
                 }
             };
             
+            // Active day/night profile (when scheduling is on and time is known),
+            // power events, push queue and outbound HTTP counters
+            let formatted_metrics = formatted_metrics.map(|mut metrics| {
+                let mut formatter = MetricsFormatter::new().with_exposition(exposition);
+                if let Some(profile) = crate::config::profiles::current() {
+                    let profile_label = format!("profile=\"{}\"", profile.name());
+                    if let Ok(series) = formatter.format_series(&[
+                        ("config_profile", "Active config profile", "gauge", &profile_label, 1.0),
+                    ]) {
+                        metrics.push_str(exposition.separator());
+                        metrics.push_str(&series);
+                    }
+                }
+                let power = crate::power::events::counts();
+                let admission = crate::network::admission::stats();
+                let (push_queued, push_dropped) = crate::network::push_exporter::queue_stats();
+                let http = crate::network::http_client::stats();
+                let sse = crate::network::sse_v2::stats();
+                if let Ok(series) = formatter.format_series(&[
+                    ("brownout_resets_total", "Brown-out resets since first boot", "counter", "", power.brownout_resets as f64),
+                    ("voltage_dips_total", "Supply voltage dips since first boot", "counter", "", power.voltage_dips as f64),
+                    ("thermal_throttled", "1 while the CPU is throttled for die temperature", "gauge", "",
+                        crate::power::thermal::is_throttled() as u8 as f64),
+                    ("safe_mode", "1 when this boot is in crash-loop safe mode", "gauge", "",
+                        crate::system::safe_mode::active() as u8 as f64),
+                    ("push_queued", "Push samples waiting in the offline queue", "gauge", "", push_queued as f64),
+                    ("push_dropped_total", "Push samples dropped because the offline queue was full", "counter", "", push_dropped as f64),
+                    ("http_client_requests_total", "Outbound HTTP requests (including retries)", "counter", "", http.requests as f64),
+                    ("http_client_failures_total", "Outbound HTTP requests that failed or returned non-2xx", "counter", "", http.failures as f64),
+                    ("http_client_received_bytes_total", "Response bytes received by outbound HTTP requests", "counter", "", http.bytes_received as f64),
+                    ("http_light_admitted_total", "Light web requests admitted", "counter", "", admission.light_admitted as f64),
+                    ("http_heavy_admitted_total", "Heavy web requests admitted (one runs at a time)", "counter", "", admission.heavy_admitted as f64),
+                    ("http_rejected_total", "Heavy web requests turned away with 503 while another ran", "counter", "", admission.heavy_rejected as f64),
+                    ("http_heavy_in_flight", "1 while a heavy web request is running", "gauge", "", admission.heavy_in_flight as u8 as f64),
+                    ("sse_clients", "Open SSE streams", "gauge", "", sse.clients as f64),
+                    ("sse_clients_max", "SSE streams allowed at once", "gauge", "", sse.max_clients as f64),
+                    ("sse_connections_total", "SSE streams opened", "counter", "", sse.connections as f64),
+                    ("sse_evictions_total", "SSE streams closed to make room for a newer client", "counter", "", sse.evictions as f64),
+                    ("sse_resumes_total", "SSE streams reopened with a Last-Event-ID", "counter", "", sse.resumes as f64),
+                ]) {
+                    metrics.push_str(exposition.separator());
+                    metrics.push_str(&series);
+                }
+                if let Some(power) = crate::hardware::power_monitor::snapshot() {
+                    if let Ok(series) = formatter.format_series(&[
+                        ("power_bus_millivolts", "Supply voltage measured by the INA219", "gauge", "", power.reading.bus_mv as f64),
+                        ("power_current_milliamps", "Board current measured by the INA219", "gauge", "", power.reading.current_ma as f64),
+                        ("power_milliwatts", "Board power draw", "gauge", "", power.reading.power_mw as f64),
+                        ("power_average_milliwatts", "Mean board power draw since boot", "gauge", "", power.average_mw as f64),
+                        ("energy_milliwatt_hours_total", "Energy used since boot", "counter", "", power.energy_mwh),
+                    ]) {
+                        metrics.push_str(exposition.separator());
+                        metrics.push_str(&series);
+                    }
+                }
+                if let Ok(readings) = formatter.format_readings(&crate::sensors::registry::snapshot()) {
+                    if !readings.is_empty() {
+                        metrics.push_str(exposition.separator());
+                        metrics.push_str(&readings);
+                    }
+                }
+                if let Ok(net_stack) = formatter.format_net_stack(&crate::network::net_stats::snapshot()) {
+                    metrics.push_str(exposition.separator());
+                    metrics.push_str(&net_stack);
+                }
+                if let Ok(latency) = formatter.format_latency(&crate::network::latency_monitor::snapshot()) {
+                    metrics.push_str(exposition.separator());
+                    metrics.push_str(&latency);
+                }
+                if let Ok(reasons) = formatter.format_disconnect_reasons(&crate::network::disconnect_reasons::histogram().counts()) {
+                    if !reasons.is_empty() {
+                        metrics.push_str(exposition.separator());
+                        metrics.push_str(&reasons);
+                    }
+                }
+                metrics.push_str(exposition.terminator());
+                metrics
+            });
+
             let result = match formatted_metrics {
                 Ok(metrics) => {
                     let mut response = req.into_response(
                         200,
                         Some("OK"),
-                        &[("Content-Type", "text/plain; version=0.0.4")]
+                        &[("Content-Type", exposition.content_type())]
                     )?;
                     response.write_all(metrics.as_bytes())?;
                     Ok(())
@@ -495,7 +786,7 @@ impl WebConfigServer {
             let ota_mgr_clone = ota_manager.clone();
             
             // OTA web interface with streaming
-            server.fn_handler("/ota", esp_idf_svc::http::Method::Get, move |req| {
+            server.tracked_handler("/ota", esp_idf_svc::http::Method::Get, move |req| {
                 log::info!("OTA page requested");
                 
                 // Use streaming handler to avoid large allocations
@@ -509,10 +800,11 @@ impl WebConfigServer {
             
             // OTA update endpoint
             let ota_manager_clone2 = ota_manager.clone();
-            server.fn_handler("/ota/update", esp_idf_svc::http::Method::Post, move |mut req| {
-                // Basic password protection for OTA
-                const OTA_PASSWORD: &str = "esp32"; // Change this to your preferred password
-                
+            server.tracked_handler("/ota/update", esp_idf_svc::http::Method::Post, move |mut req| {
+                let _trace = crate::trace::begin();
+                if let Err(reason) = crate::network::csrf::verify(&req) {
+                    return ErrorResponse::forbidden(reason).send(req);
+                }
                 let auth_header = req.header("X-OTA-Password").unwrap_or("");
                 if auth_header != OTA_PASSWORD {
                     log::warn!("OTA update rejected - invalid password");
@@ -530,10 +822,59 @@ impl WebConfigServer {
                     .and_then(|v| v.parse::<usize>().ok())
                     .ok_or_else(|| anyhow::anyhow!("Missing Content-Length"))?;
                 
-                // Get optional SHA256 header
+                // Get optional SHA256 header (always of the decoded image)
                 let sha256_header = req.header("X-SHA256").map(|s| s.to_string());
                 
-                log::info!("OTA Update started, size: {} bytes", content_length);
+                // Compressed and delta uploads announce the decoded image size separately
+                let gzip = req.header("Content-Encoding").is_some_and(|v| v.eq_ignore_ascii_case("gzip"));
+                let delta = req.header("X-OTA-Delta") == Some("1");
+                // Resumed uploads carry only the rest of the image
+                let (offset, range_total) = match crate::ota::resume::requested_offset(
+                    req.header("X-OTA-Offset"),
+                    req.header("Content-Range"),
+                ) {
+                    Ok(requested) => requested,
+                    Err(msg) => return error_response(req, 400, msg),
+                };
+                if offset > 0 && (gzip || delta) {
+                    return error_response(req, 400, "Resume is only supported for uncompressed uploads");
+                }
+                let announced_size = req.header("X-Image-Size").and_then(|v| v.parse::<usize>().ok());
+                // Outside the maintenance window the update is staged, unless forced
+                let apply_now = crate::ota::window::apply_now(
+                    crate::ota::window::is_forced(req.header(crate::ota::window::APPLY_HEADER)),
+                    crate::system::clock::local_minutes_of_day(),
+                );
+                let image_size = if gzip || delta {
+                    announced_size.ok_or_else(|| anyhow::anyhow!("Missing X-Image-Size"))?
+                } else if offset > 0 {
+                    match range_total.or(announced_size) {
+                        Some(size) => size,
+                        None => return error_response(req, 400, "Resumed upload needs X-Image-Size or a Content-Range total"),
+                    }
+                } else {
+                    content_length
+                };
+                
+                if offset > 0 {
+                    let resume = ota_mgr.lock().ok().and_then(|ota| ota.resume_point());
+                    let valid = resume.is_some_and(|point| {
+                        point.offset == offset
+                            && point.total == image_size
+                            && match (&point.sha256, &sha256_header) {
+                                (Some(expected), Some(announced)) => crate::ota::resume::sha256_matches(expected, announced),
+                                (None, None) => true,
+                                _ => false,
+                            }
+                    });
+                    if !valid {
+                        log::warn!("OTA resume at {} of {} bytes rejected", offset, image_size);
+                        return error_response(req, 409, "Resume does not match the interrupted update - check /api/ota/offset");
+                    }
+                }
+                
+                log::info!("OTA Update started, size: {} bytes (image {} bytes from offset {}{}{})", content_length,
+                    image_size, offset, if gzip { ", gzip" } else { "" }, if delta { ", delta" } else { "" });
                 if let Some(ref sha) = sha256_header {
                     log::info!("OTA Expected SHA256: {}", sha);
                 }
@@ -552,55 +893,90 @@ impl WebConfigServer {
                         }
                     };
                     
-                    // Set expected SHA256 if provided
-                    if let Some(sha) = sha256_header {
+                    // Set expected SHA256 if provided; a resume keeps the interrupted upload's
+                    if let (0, Some(sha)) = (offset, sha256_header) {
                         ota.set_expected_sha256(sha);
                     }
                     
-                    // Begin OTA update
-                    if let Err(e) = ota.begin_update(content_length) {
+                    // Begin OTA update, or continue the interrupted one
+                    let started = if offset > 0 {
+                        ota.resume_update(offset, image_size)
+                    } else {
+                        ota.begin_update(image_size)
+                    };
+                    if let Err(e) = started {
                         log::error!("OTA begin_update failed: {:?}", e);
-                        Err(anyhow::anyhow!("Failed to begin OTA: {:?}", e))
+                        Err(e)
                     } else {
-                        // Read and write firmware in chunks
+                        // Read, decode and write firmware in chunks
                         let mut buffer = [0u8; 4096];  // Stack allocated to reduce heap pressure
                         let mut total_read = 0;
                         let mut write_error = None;
+                        // Connection dropped before the body was complete
+                        let mut interrupted = false;
                         
-                        loop {
-                            match req.read(&mut buffer) {
-                                Ok(0) => break, // EOF
-                                Ok(bytes_read) => {
-                                    if let Err(e) = ota.write_chunk(&buffer[..bytes_read]) {
-                                        log::error!("OTA write_chunk failed after {} bytes: {:?}", total_read, e);
-                                        write_error = Some(anyhow::anyhow!("Failed to write OTA data: {:?}", e));
-                                        break;
-                                    }
-                                    total_read += bytes_read;
-                                    
-                                    // Log progress
-                                    let progress = ota.get_progress();
-                                    if progress % 10 == 0 && progress > 0 {
-                                        log::info!("OTA Progress: {}%", progress);
+                        match crate::ota::decoder::ImageDecoder::new(&mut *ota, gzip, delta) {
+                            Ok(mut decoder) => {
+                                use std::io::Write as _; // decoders are std::io writers
+                                loop {
+                                    match req.read(&mut buffer) {
+                                        Ok(0) => {
+                                            if total_read < content_length {
+                                                interrupted = true;
+                                                write_error = Some(OtaError::Interrupted { received: total_read, expected: content_length });
+                                            }
+                                            break; // EOF
+                                        }
+                                        Ok(bytes_read) => {
+                                            if let Err(e) = crate::span!("OTA write chunk", decoder.write_all(&buffer[..bytes_read])) {
+                                                log::error!("OTA write failed after {} bytes: {:?}", total_read, e);
+                                                write_error = Some(OtaError::from_decoder(e));
+                                                break;
+                                            }
+                                            total_read += bytes_read;
+                                            
+                                            // Log progress
+                                            let progress = decoder.sink().get_progress();
+                                            crate::hardware::notify(crate::hardware::Notification::OtaProgress(progress));
+                                            if progress % 10 == 0 && progress > 0 {
+                                                log::info!("OTA Progress: {}%", progress);
+                                            }
+                                        }
+                                        Err(e) => {
+                                            interrupted = true;
+                                            write_error = Some(OtaError::Read(format!("{:?}", e)));
+                                            break;
+                                        }
                                     }
                                 }
-                                Err(e) => {
-                                    write_error = Some(anyhow::anyhow!("Failed to read request data: {:?}", e));
-                                    break;
+                                if write_error.is_none() {
+                                    if let Err(e) = decoder.finish() {
+                                        write_error = Some(OtaError::from_decoder(e));
+                                    }
                                 }
                             }
+                            Err(e) => write_error = Some(e),
                         }
                         
                         if let Some(e) = write_error {
+                            // Plain uploads can pick up where they stopped; see /api/ota/offset
+                            if interrupted && !(gzip || delta) {
+                                ota.suspend();
+                            } else {
+                                ota.abort();
+                            }
                             Err(e)
                         } else {
                             // Finish update
-                            if let Err(e) = ota.finish_update() {
+                            if let Err(e) = ota.finish_update(apply_now) {
                                 log::error!("OTA finish_update failed: {:?}", e);
-                                Err(anyhow::anyhow!("Failed to finish OTA: {:?}", e))
-                            } else {
+                                Err(e)
+                            } else if apply_now {
                                 log::info!("OTA Update complete, restarting...");
-                                Ok(())
+                                Ok(true)
+                            } else {
+                                log::info!("OTA Update staged for the maintenance window at {}", crate::ota::window::start_text());
+                                Ok(false)
                             }
                         }
                     }
@@ -611,46 +987,48 @@ impl WebConfigServer {
                 
                 // Handle the result and send response
                 match result {
-                    Ok(_) => {
+                    Ok(true) => {
                         let mut response = req.into_ok_response()?;
                         response.write_all(b"Update successful")?;
+                        crate::hardware::notify(crate::hardware::Notification::OtaComplete);
+                        crate::system::shutdown::request_restart(crate::system::shutdown::RestartReason::OtaComplete);
                         
-                        // Schedule restart
-                        std::thread::spawn(|| {
-                        FreeRtos::delay_ms(2_000);
-                            log::info!("OTA complete - restarting...");
-                            unsafe { esp_idf_sys::esp_restart(); }
-                        });
-                        
+                        Ok(())
+                    }
+                    Ok(false) => {
+                        let message = format!(
+                            "Update staged; it applies in the maintenance window from {} (send {}: now to apply immediately)",
+                            crate::ota::window::start_text(), crate::ota::window::APPLY_HEADER,
+                        );
+                        let mut response = req.into_response(202, Some("Accepted"), &[])?;
+                        response.write_all(message.as_bytes())?;
                         Ok(())
                     }
                     Err(e) => {
                         log::error!("OTA update failed: {:?}", e);
-                        error_response(req, 500, &format!("OTA update failed: {e}"))
+                        error_response(req, e.http_status(), &format!("OTA update failed: {e}"))
                     }
                 }
             })?;
             
             // OTA status endpoint
-            let ota_manager_clone3 = ota_manager.clone();
-            server.fn_handler("/api/ota/status", esp_idf_svc::http::Method::Get, move |req| {
-                let status_json = if let Some(ref ota_mgr) = ota_manager_clone3 {
-                    let status = match ota_mgr.lock() {
-                        Ok(mgr) => mgr.get_status(),
-                        Err(e) => {
-                            log::error!("Failed to lock OTA manager: {}", e);
-                            crate::ota::OtaStatus::Failed
-                        }
-                    };
-                    match status {
-                        crate::ota::OtaStatus::Idle => r#"{"status":"idle"}"#.to_string(),
-                        crate::ota::OtaStatus::Downloading { progress } => {
-                            format!(r#"{{"status":"downloading","progress":{progress}}}"#)
+            let ota_available = ota_manager.is_some();
+            server.tracked_handler("/api/ota/status", esp_idf_svc::http::Method::Get, move |req| {
+                let status_json = if ota_available {
+                    // Published snapshot; the manager stays locked for the whole upload
+                    let status = crate::ota::progress::status();
+                    let mut json = serde_json::json!({
+                        "status": match status {
+                            crate::ota::OtaStatus::Downloading { .. } => "downloading",
+                            other => other.phase(),
                         },
-                        crate::ota::OtaStatus::Verifying => r#"{"status":"verifying"}"#.to_string(),
-                        crate::ota::OtaStatus::Ready => r#"{"status":"ready"}"#.to_string(),
-                        crate::ota::OtaStatus::Failed => r#"{"status":"failed"}"#.to_string(),
+                        "pending": status == crate::ota::OtaStatus::Staged,
+                        "window": crate::ota::window::status(crate::system::clock::local_minutes_of_day()),
+                    });
+                    if let crate::ota::OtaStatus::Downloading { progress } = status {
+                        json["progress"] = progress.into();
                     }
+                    json.to_string()
                 } else {
                     r#"{"status":"unavailable","message":"OTA not available on factory partition"}"#.to_string()
                 };
@@ -664,11 +1042,82 @@ impl WebConfigServer {
                 Ok::<(), anyhow::Error>(())
             })?;
             
+            // Where an interrupted upload can continue
+            let ota_manager_clone4 = ota_manager.clone();
+            server.tracked_handler("/api/ota/offset", esp_idf_svc::http::Method::Get, move |req| {
+                let resume = ota_manager_clone4
+                    .as_ref()
+                    .and_then(|ota_mgr| ota_mgr.lock().ok())
+                    .and_then(|ota| ota.resume_point());
+                let json = match resume {
+                    Some(point) => serde_json::json!({
+                        "resumable": true,
+                        "offset": point.offset,
+                        "total": point.total,
+                        "prefix_sha256": point.prefix_sha256,
+                        "sha256": point.sha256,
+                    }),
+                    None => serde_json::json!({ "resumable": false, "offset": 0 }),
+                };
+                let mut response = req.into_response(
+                    200,
+                    Some("OK"),
+                    &[("Content-Type", "application/json")]
+                )?;
+                response.write_all(json.to_string().as_bytes())?;
+                Ok::<(), anyhow::Error>(())
+            })?;
+            
+            // Live progress for the OTA page, streamed from a detached request
+            crate::network::ota_progress_stream::register(&server)?;
+            
+            // Partition table state for the OTA page and fleet tooling
+            server.tracked_handler("/api/ota/partitions", esp_idf_svc::http::Method::Get, move |req| {
+                let json = serde_json::to_string(&crate::ota::partitions::report())?;
+                let mut response = req.into_response(
+                    200,
+                    Some("OK"),
+                    &[("Content-Type", "application/json")]
+                )?;
+                response.write_all(json.as_bytes())?;
+                Ok::<(), anyhow::Error>(())
+            })?;
+            
+            // Boot the previously installed app
+            server.tracked_handler("/api/ota/rollback", esp_idf_svc::http::Method::Post, move |req| {
+                if let Err(reason) = crate::network::csrf::verify(&req) {
+                    return ErrorResponse::forbidden(reason).send(req);
+                }
+                if req.header("X-OTA-Password").unwrap_or("") != OTA_PASSWORD {
+                    log::warn!("OTA rollback rejected - invalid password");
+                    return error_response(req, 401, "Unauthorized - Invalid OTA password");
+                }
+                if OTA_IN_PROGRESS.load(Ordering::Acquire) {
+                    return error_response(req, 409, "OTA update in progress");
+                }
+                
+                match crate::ota::partitions::rollback() {
+                    Ok(label) => {
+                        let body = serde_json::json!({ "status": "rolling_back", "boot": label });
+                        let mut response = req.into_response(
+                            200,
+                            Some("OK"),
+                            &[("Content-Type", "application/json")]
+                        )?;
+                        response.write_all(body.to_string().as_bytes())?;
+                        
+                        crate::system::shutdown::request_restart(crate::system::shutdown::RestartReason::Rollback);
+                        Ok(())
+                    }
+                    Err(e) => error_response(req, 409, &e.to_string()),
+                }
+            })?;
+            
             log::info!("OTA endpoints registered on main web server");
         }
 
         // Dashboard route - enhanced dashboard with SSE-ready UI
-        server.fn_handler("/dashboard", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/dashboard", esp_idf_svc::http::Method::Get, move |req| {
             let instr = crate::network::server_config::RequestInstrumentation::capture(None);
             let result = crate::network::streaming_dashboard::handle_dashboard_enhanced(req);
             let status = if result.is_ok() { 200 } else { 500 };
@@ -677,12 +1126,12 @@ impl WebConfigServer {
         })?;
         
         // Dashboard CSS endpoint (for async loading)
-        server.fn_handler("/dashboard.css", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/dashboard.css", esp_idf_svc::http::Method::Get, move |req| {
             crate::network::streaming_dashboard::handle_dashboard_css(req)
         })?;
 
         // Deprecated Control Center page -> redirect to dashboard
-        server.fn_handler("/control", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/control", esp_idf_svc::http::Method::Get, move |req| {
             let mut response = req.into_response(
                 302,
                 Some("Found"),
@@ -693,38 +1142,33 @@ impl WebConfigServer {
         })?;
 
         // Dev Tools page - serve uncompressed to avoid gzip heap spikes
-        server.fn_handler("/dev", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/dev", esp_idf_svc::http::Method::Get, move |req| {
             let template = include_str!("../templates/dev.html");
-            let mut navbar = include_str!("../templates/partials/navbar.html").to_string();
-            navbar = navbar
-                .replace("{{HOME_ACTIVE}}", "")
-                .replace("{{DASH_ACTIVE}}", "")
-                .replace("{{LOGS_ACTIVE}}", "")
-                .replace("{{FILES_ACTIVE}}", "")
-                .replace("{{OTA_ACTIVE}}", "")
-                .replace("{{DEV_ACTIVE}}", "class=\\\"active\\\"");
+            let navbar = crate::templates::render_navbar("/dev");
             let html = if template.contains("<nav class=\"navbar\">") {
                 template.to_string()
             } else {
                 template.replacen("<header>", &format!("<header>\n{}", navbar), 1)
             };
+            let html = crate::templates::with_csrf_script(&html);
+            let cookie = crate::network::csrf::session_cookie(&req);
             let mut response = req.into_response(
                 200,
                 Some("OK"),
-                &[("Content-Type", "text/html; charset=utf-8"), ("Connection", "close")],
+                &[("Content-Type", "text/html; charset=utf-8"), ("Set-Cookie", cookie.as_str()), ("Connection", "close")],
             )?;
             response.write_all(html.as_bytes())?;
             Ok(()) as Result<(), Box<dyn std::error::Error>>
         })?;
 
         // Common icon paths to suppress noisy 404s or serve tiny placeholder
-        server.fn_handler("/apple-touch-icon.png", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/apple-touch-icon.png", esp_idf_svc::http::Method::Get, move |req| {
             let png: &[u8] = include_bytes!("../../static/icons/apple-touch-icon.png");
             let mut resp = req.into_response(200, Some("OK"), &[("Content-Type", "image/png"), ("Cache-Control", "max-age=86400")])?;
             resp.write_all(png)?;
             Ok(()) as Result<(), Box<dyn std::error::Error>>
         })?;
-        server.fn_handler("/apple-touch-icon-precomposed.png", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/apple-touch-icon-precomposed.png", esp_idf_svc::http::Method::Get, move |req| {
             let png: &[u8] = include_bytes!("../../static/icons/apple-touch-icon.png");
             let mut resp = req.into_response(200, Some("OK"), &[("Content-Type", "image/png"), ("Cache-Control", "max-age=86400")])?;
             resp.write_all(png)?;
@@ -732,12 +1176,17 @@ impl WebConfigServer {
         })?;
         
         // Sensor graphs route - serve uncompressed to reduce peak allocations
-        server.fn_handler("/graphs", esp_idf_svc::http::Method::Get, move |req| {
-            let html = crate::templates::GRAPHS_PAGE;
+        server.tracked_handler("/graphs", esp_idf_svc::http::Method::Get, move |req| {
+            let cookie = crate::network::csrf::session_cookie(&req);
+            let etag = crate::network::etag::ASSETS;
+            if crate::network::etag::is_fresh(&req, etag) {
+                return crate::network::etag::send_not_modified(req, etag, &[("Cache-Control", "no-cache"), ("Set-Cookie", cookie.as_str())]);
+            }
+            let html = crate::templates::with_csrf_script(&crate::templates::with_theme_script(crate::templates::GRAPHS_PAGE));
             let mut response = req.into_response(
                 200,
                 Some("OK"),
-                &[("Content-Type", "text/html; charset=utf-8"), ("Connection", "close")],
+                &[("Content-Type", "text/html; charset=utf-8"), ("Cache-Control", "no-cache"), ("Set-Cookie", cookie.as_str()), ("ETag", etag), ("Connection", "close")],
             )?;
             response.write_all(html.as_bytes())?;
             Ok(()) as Result<(), Box<dyn std::error::Error>>
@@ -745,7 +1194,7 @@ impl WebConfigServer {
         
         // Config backup endpoint - exports current config as JSON
         let config_backup = config.clone();
-        server.fn_handler("/api/config/backup", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/api/config/backup", esp_idf_svc::http::Method::Get, move |req| {
             let config = match config_backup.lock() {
                 Ok(cfg) => cfg,
                 Err(e) => {
@@ -756,23 +1205,24 @@ impl WebConfigServer {
             
             // Export full config as JSON
             let json = serde_json::to_string_pretty(&*config)?;
-            
+            drop(config);
+
             // Return as downloadable file
-            let mut response = req.into_response(
-                200,
-                Some("OK"),
-                &[
-                    ("Content-Type", "application/json"),
-                    ("Content-Disposition", "attachment; filename=\"esp32-config-backup.json\"")
-                ]
+            write_compressed_response(
+                req,
+                "application/json",
+                &[("Content-Disposition", "attachment; filename=\"esp32-config-backup.json\"")],
+                json.as_bytes(),
             )?;
-            response.write_all(json.as_bytes())?;
             Ok(()) as Result<(), Box<dyn std::error::Error>>
         })?;
         
         // Config restore endpoint - imports config from JSON
         let config_restore = config.clone();
-        server.fn_handler("/api/config/restore", esp_idf_svc::http::Method::Post, move |mut req| {
+        server.tracked_handler("/api/config/restore", esp_idf_svc::http::Method::Post, move |mut req| {
+            if let Err(reason) = crate::network::csrf::verify(&req) {
+                return ErrorResponse::forbidden(reason).send(req);
+            }
             // Read uploaded JSON with 4KB cap
             let mut buf = vec![0; 4096];
             let len = req.read(&mut buf)?;
@@ -834,10 +1284,13 @@ impl WebConfigServer {
 
         // Binary metrics endpoint for efficient updates
         let metrics_clone_bin = metrics.clone();
-        server.fn_handler("/api/metrics/binary", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/api/metrics/binary", esp_idf_svc::http::Method::Get, move |req| {
             if let Ok(metrics_guard) = metrics_clone_bin.try_lock() {
                 let packet = MetricsBinaryPacket::from_metrics(&*metrics_guard);
-                let bytes = packet.to_bytes();
+                // v2 framing unless an old client asks for the fixed layout
+                let legacy = req.uri().split('?').nth(1)
+                    .is_some_and(|query| query.split('&').any(|p| p == "v=1"));
+                let bytes = if legacy { packet.to_bytes() } else { packet.to_v2_bytes() };
                 
                 let mut response = req.into_response(
                     200,
@@ -857,88 +1310,72 @@ impl WebConfigServer {
 
         // JSON metrics endpoint for dashboard
         let metrics_clone = metrics.clone();
-        server.fn_handler("/api/metrics", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/api/metrics", esp_idf_svc::http::Method::Get, move |req| {
             // Get basic system info
             let uptime = unsafe { esp_idf_sys::esp_timer_get_time() / 1_000_000 } as u64;
             let heap_free = unsafe { esp_idf_sys::esp_get_free_heap_size() };
             
-            // Try to get metrics data
-            let metrics_json = if let Ok(metrics_guard) = metrics_clone.try_lock() {
-                // Get all metrics from the guard (deref to MetricsData)
-                serde_json::json!({
-                    "uptime": uptime,
-                    "heap_free": heap_free,
-                    "temperature": (metrics_guard.temperature * 10.0).round() / 10.0,
-                    "fps_actual": (metrics_guard.fps_actual * 10.0).round() / 10.0,
-                    "fps_target": metrics_guard.fps_target,
-                    "render_time_ms": metrics_guard.render_time_ms,
-                    "flush_time_ms": metrics_guard.flush_time_ms,
-                    "cpu_usage": metrics_guard.cpu_usage,
-                    "cpu0_usage": metrics_guard.cpu0_usage,
-                    "cpu1_usage": metrics_guard.cpu1_usage,
-                    "cpu_freq_mhz": metrics_guard.cpu_freq_mhz,
-                    "battery_voltage": metrics_guard.battery_voltage_mv,
-                    "battery_percentage": metrics_guard.battery_percentage,
-                    "battery_charging": metrics_guard.battery_charging,
-                    "wifi_rssi": metrics_guard.wifi_rssi,
-                    "wifi_connected": metrics_guard.wifi_connected,
-                    "wifi_ssid": metrics_guard.wifi_ssid.clone(),
-                    "display_brightness": metrics_guard.display_brightness,
-                    "frame_count": metrics_guard.frame_count,
-                    "skip_count": metrics_guard.skip_count,
-                    "skip_rate": if metrics_guard.frame_count > 0 {
-                        metrics_guard.skip_count as f32 / metrics_guard.frame_count as f32 * 100.0
-                    } else { 0.0 }
-                })
-            } else {
-                // Return partial data if metrics locked
-                serde_json::json!({
-                    "uptime": uptime,
-                    "heap_free": heap_free,
-                    "error": "metrics_locked"
-                })
-            };
-            
-            let json_string = serde_json::to_string(&metrics_json)?;
+            // Return partial data if metrics locked
+            let metrics_guard = metrics_clone.try_lock().ok();
+            let metrics_json = crate::network::api_core::metrics_json(uptime, heap_free, metrics_guard.as_deref());
+            drop(metrics_guard);
+
             let mut response = req.into_response(
                 200,
                 Some("OK"),
                 &[("Content-Type", "application/json")]
             )?;
-            response.write_all(json_string.as_bytes())?;
+            crate::network::json_stream::write_response(&mut response, &metrics_json)?;
+            Ok(()) as Result<(), Box<dyn std::error::Error>>
+        })?;
+
+        // Compact status for wall dashboards and ota.sh. Reads the lock-free
+        // metrics store directly instead of going through the write-back guard.
+        server.tracked_handler("/api/summary", esp_idf_svc::http::Method::Get, move |req| {
+            let uptime = unsafe { esp_idf_sys::esp_timer_get_time() / 1_000_000 } as u64;
+            let snapshot = crate::metrics_rwlock::metrics().snapshot();
+            let ip = station_ip();
+            let body = crate::network::api_core::summary_json(
+                &snapshot,
+                ip.as_deref(),
+                uptime,
+                crate::display::display_ok(),
+                crate::version::DISPLAY_VERSION,
+            ).to_string();
+            let mut response = req.into_response(
+                200,
+                Some("OK"),
+                &[("Content-Type", "application/json"), ("Cache-Control", "no-store")]
+            )?;
+            response.write_all(body.as_bytes())?;
             Ok(()) as Result<(), Box<dyn std::error::Error>>
         })?;
 
         // Logs page (SSE-enabled)
         // NOTE (global-nav): This page participates in the shared navbar set.
-        server.fn_handler("/logs", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/logs", esp_idf_svc::http::Method::Get, move |req| {
             // Serve logs page with shared navbar by injecting partials
             let template = include_str!("../templates/logs_enhanced.html");
-            let mut navbar = include_str!("../templates/partials/navbar.html").to_string();
-            navbar = navbar
-                .replace("{{HOME_ACTIVE}}", "")
-                .replace("{{DASH_ACTIVE}}", "")
-                .replace("{{LOGS_ACTIVE}}", "class=\\\"active\\\"")
-                .replace("{{FILES_ACTIVE}}", "")
-                .replace("{{OTA_ACTIVE}}", "")
-                .replace("{{DEV_ACTIVE}}", "");
+            let navbar = crate::templates::render_navbar("/logs");
             // Insert navbar at top of body if not already present
             let html = if template.contains("<nav class=\"navbar\">") {
                 template.to_string()
             } else {
                 template.replacen("<body>", &format!("<body>\n{}", navbar), 1)
             };
+            let html = crate::templates::with_csrf_script(&html);
+            let cookie = crate::network::csrf::session_cookie(&req);
             let mut response = req.into_response(
                 200,
                 Some("OK"),
-                &[("Content-Type", "text/html; charset=utf-8"), ("Connection", "close")],
+                &[("Content-Type", "text/html; charset=utf-8"), ("Set-Cookie", cookie.as_str()), ("Connection", "close")],
             )?;
             response.write_all(html.as_bytes())?;
             Ok(()) as Result<(), Box<dyn std::error::Error>>
         })?;
 
         // Logs API endpoint - returns recent log entries from in-memory streamer
-        server.fn_handler("/api/logs", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/api/logs", esp_idf_svc::http::Method::Get, move |req| {
             // Optional count parameter
             let count = req.uri()
                 .split('?')
@@ -947,83 +1384,122 @@ impl WebConfigServer {
                 .and_then(|p| p.strip_prefix("count="))
                 .and_then(|c| c.parse::<usize>().ok())
                 .unwrap_or(100);
+            // Optional level/module filters, applied before the count
+            let filter = match crate::network::log_filter::LogFilter::from_query(req.uri().split('?').nth(1).unwrap_or("")) {
+                Ok(filter) => filter,
+                Err(e) => return ErrorResponse::bad_request(e).send(req),
+            };
+
+            #[derive(serde::Serialize)]
+            struct Logs<'a> {
+                logs: &'a [crate::network::log_streamer::LogEntry],
+            }
 
             let streamer = crate::network::log_streamer::init(None);
-            let recent_logs = streamer.get_recent_logs(count);
-            let json_string = serde_json::to_string(&serde_json::json!({ "logs": recent_logs }))?;
-            let mut response = req.into_ok_response()?;
-            response.write_all(json_string.as_bytes())?;
+            let recent_logs = streamer.get_recent_filtered(count, &filter);
+            let mut response = req.into_response(
+                200,
+                Some("OK"),
+                &[("Content-Type", "application/json")]
+            )?;
+            crate::network::json_stream::write_response(&mut response, &Logs { logs: &recent_logs })?;
             Ok(()) as Result<(), Box<dyn std::error::Error>>
         })?;
 
         // Device control endpoint
         let config_clone_control = config.clone();
-        server.fn_handler("/api/control", esp_idf_svc::http::Method::Post, move |mut req| {
-            let mut buf = vec![0; 512];
+        server.tracked_handler("/api/control", esp_idf_svc::http::Method::Post, move |mut req| {
+            let _trace = crate::trace::begin();
+            if let Err(reason) = crate::network::csrf::verify(&req) {
+                return ErrorResponse::forbidden(reason).send(req);
+            }
+            let mut buf = vec![0; crate::network::api_core::MAX_CONTROL_BODY + 1];
             let len = req.read(&mut buf)?;
-            if len > buf.len() {
+            if len > crate::network::api_core::MAX_CONTROL_BODY {
                 return error_response(req, 413, "Payload too large (max 512B)");
             }
             buf.truncate(len);
-            
-            let json_str = std::str::from_utf8(&buf)?;
-            let control_cmd: serde_json::Value = serde_json::from_str(json_str)?;
-            
-            // Handle different control commands
-            if let Some(brightness) = control_cmd.get("brightness").and_then(|v| v.as_u64()) {
-                let brightness_u8 = brightness.min(255) as u8;
-                
+
+            let control_cmd = match crate::network::api_core::ControlCommand::parse(&buf) {
+                Ok(cmd) => cmd,
+                Err(e) => return error_response(req, 400, &e.to_string()),
+            };
+
+            if let Some(brightness) = control_cmd.brightness {
                 // Update brightness in config
                 if let Ok(mut cfg) = config_clone_control.lock() {
-                    cfg.brightness = brightness_u8;
+                    cfg.brightness = brightness;
                     let _ = cfg.save();
                 }
-                log::info!("Brightness set to: {} ({}%)", brightness_u8, (brightness_u8 as f32 / 255.0 * 100.0) as u8);
+                log::info!("Brightness set to: {} ({}%)", brightness, (brightness as f32 / 255.0 * 100.0) as u8);
             }
-            
-            if let Some(display_on) = control_cmd.get("display").and_then(|v| v.as_bool()) {
+
+            if let Some(muted) = control_cmd.mute {
+                if let Ok(mut cfg) = config_clone_control.lock() {
+                    cfg.buzzer_muted = muted;
+                    let _ = cfg.save();
+                }
+                log::info!("Buzzer {}", if muted { "muted" } else { "unmuted" });
+            }
+
+            if let Some(event) = control_cmd.sound {
+                crate::hardware::buzzer::test(event);
+            }
+
+            if let Some(event) = control_cmd.input {
+                crate::input::publish(event, crate::input::Origin::Remote);
+            }
+
+            if let Some(screen) = control_cmd.screen {
+                crate::input::publish(crate::input::InputEvent::ShowScreen(screen), crate::input::Origin::Remote);
+            }
+
+            if let Some(on) = control_cmd.quiet_override {
+                crate::power::quiet_hours::set_override(on);
+                log::info!("Quiet hours override {}", if on { "on" } else { "off" });
+            }
+
+            if let Some(display_on) = control_cmd.display {
                 // Display control would require access to the display manager
                 // For now, just log the request
                 log::info!("Display control requested: {} (not yet implemented)", display_on);
             }
-            
-            if let Some(mode) = control_cmd.get("mode").and_then(|v| v.as_str()) {
+
+            if let Some(mode) = control_cmd.mode {
                 // Set CPU frequency based on performance mode
-                let freq_mhz = match mode {
-                    "eco" => 80,
-                    "normal" => 160,
-                    "turbo" => 240,
-                    _ => 160, // default to normal
-                };
-                
+                let (max_freq_mhz, min_freq_mhz) = mode.freq_mhz();
+
                 // Configure power management
                 unsafe {
                     use esp_idf_sys::*;
-                    
+
                     // Create config struct
                     let config = esp_pm_config_t {
-                        max_freq_mhz: freq_mhz,
-                        min_freq_mhz: if mode == "eco" { 40 } else { 80 },
+                        max_freq_mhz,
+                        min_freq_mhz,
                         light_sleep_enable: false,
                     };
-                    
+
                     // Apply the configuration
                     let result = esp_pm_configure(&config as *const esp_pm_config_t as *const core::ffi::c_void);
                     if result == ESP_OK as i32 {
-                        log::info!("Performance mode set to {}: CPU {}MHz", mode, freq_mhz);
+                        log::info!("Performance mode set to {}: CPU {}MHz", mode.name(), max_freq_mhz);
                     } else {
                         log::warn!("Failed to set performance mode: error {}", result);
                     }
                 }
             }
-            
+
             let mut response = req.into_ok_response()?;
             response.write_all(br#"{"status":"ok"}"#)?;
             Ok(()) as Result<(), Box<dyn std::error::Error>>
         })?;
 
         // Restart endpoint - protected
-        server.fn_handler("/api/restart", esp_idf_svc::http::Method::Post, move |req| {
+        server.tracked_handler("/api/restart", esp_idf_svc::http::Method::Post, move |req| {
+            if let Err(reason) = crate::network::csrf::verify(&req) {
+                return ErrorResponse::forbidden(reason).send(req);
+            }
             // Check for authentication header
             const RESTART_TOKEN: &str = "esp32-restart";
             let auth_header = req.header("X-Restart-Token").unwrap_or("");
@@ -1038,12 +1514,8 @@ impl WebConfigServer {
             let mut response = req.into_ok_response()?;
             response.write_all(br#"{"status":"restarting"}"#)?;
             
-            // Schedule restart after response is sent
-            std::thread::spawn(|| {
-                FreeRtos::delay_ms(1_000);
-                log::info!("Manual restart requested...");
-                unsafe { esp_idf_sys::esp_restart(); }
-            });
+            // The main loop restarts once the response is out
+            crate::system::shutdown::request_restart(crate::system::shutdown::RestartReason::Requested);
             
             Ok(()) as Result<(), Box<dyn std::error::Error>>
         })?;
@@ -1065,7 +1537,7 @@ impl WebConfigServer {
         // NOTE: SSE endpoint /api/events is already registered by sse_broadcaster.register_endpoints() above
 
         // Recent logs endpoint for initial load
-        server.fn_handler("/api/logs/recent", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/api/logs/recent", esp_idf_svc::http::Method::Get, move |req| {
             let count = req.uri()
                 .split('?')
                 .nth(1)
@@ -1073,10 +1545,14 @@ impl WebConfigServer {
                 .and_then(|p| p.strip_prefix("count="))
                 .and_then(|c| c.parse::<usize>().ok())
                 .unwrap_or(100);
+            let filter = match crate::network::log_filter::LogFilter::from_query(req.uri().split('?').nth(1).unwrap_or("")) {
+                Ok(filter) => filter,
+                Err(e) => return ErrorResponse::bad_request(e).send(req),
+            };
             
             // Get log streamer instance
             let log_streamer = crate::network::log_streamer::init(None);
-            let recent_logs = log_streamer.get_recent_logs(count);
+            let recent_logs = log_streamer.get_recent_filtered(count, &filter);
             
             let json = serde_json::to_string(&recent_logs)?;
             let mut response = req.into_ok_response()?;
@@ -1084,8 +1560,23 @@ impl WebConfigServer {
             Ok(()) as Result<(), Box<dyn std::error::Error>>
         })?;
 
+        // Log of the last boot that ended in a crash, kept on SPIFFS
+        server.tracked_handler("/api/logs/previous-boot", esp_idf_svc::http::Method::Get, move |req| {
+            match crate::storage::log_ring::read_previous() {
+                Some(log) => {
+                    let mut response = req.into_response(200, Some("OK"), &[
+                        ("Content-Type", "text/plain; charset=utf-8"),
+                        ("Cache-Control", "no-store"),
+                    ])?;
+                    response.write_all(log.as_bytes())?;
+                    Ok(()) as Result<(), Box<dyn std::error::Error>>
+                }
+                None => ErrorResponse::not_found("No crash log from a previous boot").send(req),
+            }
+        })?;
+
         // Service Worker
-        server.fn_handler("/sw.js", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/sw.js", esp_idf_svc::http::Method::Get, move |req| {
             const SW_JS: &str = include_str!("../templates/sw.js");
             let mut response = req.into_response(
                 200,
@@ -1100,7 +1591,7 @@ impl WebConfigServer {
         })?;
 
         // Web App Manifest
-        server.fn_handler("/manifest.json", esp_idf_svc::http::Method::Get, move |req| {
+        server.tracked_handler("/manifest.json", esp_idf_svc::http::Method::Get, move |req| {
             // Use escaped quotes to avoid parsing issues
             const MANIFEST_JSON: &str = "{\"name\":\"ESP32-S3 Dashboard\",\"short_name\":\"ESP32 Dash\",\"description\":\"Control and monitor your ESP32-S3 device\",\"start_url\":\"/dashboard\",\"display\":\"standalone\",\"theme_color\":\"#3b82f6\",\"background_color\":\"#0a0a0a\",\"icons\":[{\"src\":\"/icon-192.png\",\"sizes\":\"192x192\",\"type\":\"image/png\"},{\"src\":\"/icon-512.png\",\"sizes\":\"512x512\",\"type\":\"image/png\"}]}";
             let mut response = req.into_response(
@@ -1116,6 +1607,23 @@ impl WebConfigServer {
     }
 }
 
+/// Station IPv4 address, if it has one
+fn station_ip() -> Option<String> {
+    unsafe {
+        let key = b"WIFI_STA_DEF\0";
+        let netif = esp_idf_sys::esp_netif_get_handle_from_ifkey(key.as_ptr() as *const ::core::ffi::c_char);
+        if netif.is_null() {
+            return None;
+        }
+        let mut ip_info = esp_idf_sys::esp_netif_ip_info_t::default();
+        if esp_idf_sys::esp_netif_get_ip_info(netif, &mut ip_info) != esp_idf_sys::ESP_OK || ip_info.ip.addr == 0 {
+            return None;
+        }
+        // lwIP stores the address in network byte order
+        Some(std::net::Ipv4Addr::from(ip_info.ip.addr.to_le_bytes()).to_string())
+    }
+}
+
 #[derive(serde::Serialize)]
 struct SystemInfo {
     version: String,
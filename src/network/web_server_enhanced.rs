@@ -5,7 +5,6 @@ use esp_idf_svc::http::server::{Configuration as HttpServerConfig, EspHttpServer
 use std::sync::{Arc, Mutex};
 use crate::config::Config;
 use crate::system_info::SystemInfo;
-use esp_idf_hal::delay::FreeRtos;
 
 pub struct WebConfigServer {
     _server: EspHttpServer<'static>,
@@ -75,12 +74,8 @@ impl WebConfigServer {
             let mut response = req.into_ok_response()?;
             response.write_all(b"{\"status\":\"restarting\"}")?;
             
-            // Schedule restart after response is sent
-            std::thread::spawn(|| {
-                FreeRtos::delay_ms(500);
-                log::info!("Restarting device via web request...");
-                unsafe { esp_idf_sys::esp_restart(); }
-            });
+            // The main loop restarts once the response is out
+            crate::system::shutdown::request_restart(crate::system::shutdown::RestartReason::Requested);
             
             Ok(()) as Result<(), Box<dyn std::error::Error>>
         })?;
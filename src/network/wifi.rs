@@ -2,12 +2,42 @@ use anyhow::{Result, bail};
 use esp_idf_hal::modem::Modem;
 use esp_idf_svc::{
     eventloop::EspSystemEventLoop,
+    ipv4,
+    netif::{EspNetif, NetifConfiguration, NetifStack},
     wifi::{
-        ClientConfiguration, Configuration, EspWifi,
-        AuthMethod, BlockingWifi,
+        AccessPointConfiguration, ClientConfiguration, Configuration, EspWifi,
+        AuthMethod, BlockingWifi, WifiDriver,
     },
-    nvs::EspDefaultNvsPartition,
 };
+use crate::network::validators::StaticIpv4;
+
+/// Station interface: fixed address when configured, else DHCP. A static
+/// setup esp-idf rejects falls back to DHCP so the device stays reachable.
+fn sta_netif(static_ip: Option<StaticIpv4>) -> Result<EspNetif> {
+    let Some(settings) = static_ip else {
+        return Ok(EspNetif::new(NetifStack::Sta)?);
+    };
+    let conf = NetifConfiguration {
+        ip_configuration: Some(ipv4::Configuration::Client(ipv4::ClientConfiguration::Fixed(ipv4::ClientSettings {
+            ip: settings.ip,
+            subnet: ipv4::Subnet { gateway: settings.gateway, mask: ipv4::Mask(settings.prefix_len) },
+            // Most home routers forward DNS, so the gateway is a sane default
+            dns: Some(settings.dns.first().copied().unwrap_or(settings.gateway)),
+            secondary_dns: settings.dns.get(1).copied(),
+        }))),
+        ..NetifConfiguration::wifi_default_client()
+    };
+    match EspNetif::new_with_conf(&conf) {
+        Ok(netif) => {
+            log::info!("Static IP {}/{} via {}", settings.ip, settings.prefix_len, settings.gateway);
+            Ok(netif)
+        }
+        Err(e) => {
+            log::warn!("Static IP rejected ({:?}) - falling back to DHCP", e);
+            Ok(EspNetif::new(NetifStack::Sta)?)
+        }
+    }
+}
 
 pub struct WifiManager {
     wifi: BlockingWifi<EspWifi<'static>>,
@@ -16,11 +46,16 @@ pub struct WifiManager {
 }
 
 impl WifiManager {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         modem: Modem,
         sys_loop: EspSystemEventLoop,
         ssid: String,
         password: String,
+        hostname: &str,
+        bssid: Option<[u8; 6]>,
+        access_point: Option<AccessPointConfiguration>,
+        static_ip: Option<StaticIpv4>,
     ) -> Result<Self> {
         log::info!("Initializing WiFi manager for SSID: '{}'", ssid);
         
@@ -30,11 +65,25 @@ impl WifiManager {
             bail!("WiFi SSID cannot be empty");
         }
         
-        let nvs = EspDefaultNvsPartition::take()?;
-        let mut esp_wifi = EspWifi::new(modem, sys_loop.clone(), Some(nvs))?;
+        let nvs = crate::storage::kv::init()?;
+        let driver = WifiDriver::new(modem, sys_loop.clone(), Some(nvs))?;
+        // The fallback AP serves on 192.168.4.1 rather than esp-idf-svc's default subnet
+        let [a, b, c, d] = crate::network::softap::AP_IP;
+        let ap_netif = EspNetif::new_with_conf(&NetifConfiguration {
+            ip_configuration: Some(ipv4::Configuration::Router(ipv4::RouterConfiguration {
+                subnet: ipv4::Subnet { gateway: ipv4::Ipv4Addr::new(a, b, c, d), mask: ipv4::Mask(24) },
+                dhcp_enabled: true,
+                dns: None,
+                secondary_dns: None,
+            })),
+            ..NetifConfiguration::wifi_default_router()
+        })?;
+        let mut esp_wifi = EspWifi::wrap_all(driver, sta_netif(static_ip)?, ap_netif)?;
+        // Must be set before DHCP starts so the router's lease table shows it
+        esp_wifi.sta_netif_mut().set_hostname(hostname)?;
 
         // Configure WiFi
-        let cfg = Configuration::Client(ClientConfiguration {
+        let client = ClientConfiguration {
             ssid: ssid.as_str().try_into()
                 .map_err(|e| {
                     log::error!("Failed to convert SSID '{}': {:?}", ssid, e);
@@ -52,8 +101,16 @@ impl WifiManager {
                 log::info!("Using WPA2 authentication");
                 AuthMethod::WPA2Personal
             },
+            bssid,
             ..Default::default()
-        });
+        };
+        let cfg = match access_point {
+            Some(ap) => {
+                log::info!("Fallback AP '{}' enabled (APSTA mode)", ap.ssid);
+                Configuration::Mixed(client, ap)
+            }
+            None => Configuration::Client(client),
+        };
 
         log::info!("Setting WiFi configuration...");
         esp_wifi.set_configuration(&cfg)?;
@@ -288,8 +345,18 @@ impl WifiManager {
             })
     }
     
-    pub fn get_mac(&self) -> String {
+    /// Update the DHCP hostname; takes effect on the next lease renewal
+    pub fn set_hostname(&mut self, hostname: &str) -> Result<()> {
+        self.wifi.wifi_mut().sta_netif_mut().set_hostname(hostname)?;
+        Ok(())
+    }
+
+    pub fn get_mac_bytes(&self) -> Option<[u8; 6]> {
         self.wifi.wifi().sta_netif().get_mac().ok()
+    }
+
+    pub fn get_mac(&self) -> String {
+        self.get_mac_bytes()
             .map(|mac| format!("{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}", 
                               mac[0], mac[1], mac[2], mac[3], mac[4], mac[5]))
             .unwrap_or_else(|| "Unknown".to_string())
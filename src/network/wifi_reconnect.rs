@@ -52,6 +52,13 @@ impl WifiReconnectManager {
             FreeRtos::delay_ms(15_000);
             
             while monitoring_active.load(Ordering::Relaxed) {
+                // The battery profile switched the radio off on purpose
+                if crate::power::duty_cycle::is_radio_parked() {
+                    is_connected.store(false, Ordering::Relaxed);
+                    FreeRtos::delay_ms(10_000);
+                    continue;
+                }
+                
                 // Check if WiFi is connected
                 let connected = unsafe {
                     let mut ap_info: esp_idf_sys::wifi_ap_record_t = std::mem::zeroed();
@@ -163,6 +170,8 @@ impl WifiReconnectManager {
                         if !event_data.is_null() {
                             let disc = &*(event_data as *const wifi_event_sta_disconnected_t);
                             crate::network::wifi_stats::set_last_reason(disc.reason as u32);
+                            crate::network::connection_history::with_history(|h| h.on_disconnected(uptime_secs(), disc.reason as u16));
+                            crate::network::disconnect_reasons::record(disc.reason as u16);
                             // Record in observability ring (best-effort)
                             crate::network::observability::record_wifi_event(
                                 "sta_disconnected",
@@ -176,10 +185,23 @@ impl WifiReconnectManager {
                     }
                     wifi_event_t_WIFI_EVENT_STA_CONNECTED => {
                         crate::network::wifi_stats::set_connected(true);
+                        crate::network::ipv6::enable_link_local();
                         crate::network::wifi_stats::record_reconnect();
                         // Refresh RSSI/channel
                         let mut ap: wifi_ap_record_t = core::mem::zeroed();
-                        if esp_wifi_sta_get_ap_info(&mut ap) == ESP_OK {
+                        let have_ap = esp_wifi_sta_get_ap_info(&mut ap) == ESP_OK;
+                        if !event_data.is_null() {
+                            let conn = &*(event_data as *const wifi_event_sta_connected_t);
+                            let timestamp = std::time::SystemTime::now()
+                                .duration_since(std::time::UNIX_EPOCH)
+                                .map(|d| d.as_secs())
+                                .unwrap_or(0);
+                            let rssi = if have_ap { ap.rssi } else { 0 };
+                            crate::network::connection_history::with_history(|h| {
+                                h.on_connected(uptime_secs(), timestamp, &conn.bssid, conn.channel, negotiated_phy(), rssi)
+                            });
+                        }
+                        if have_ap {
                             crate::network::wifi_stats::set_rssi_dbm(ap.rssi as i32);
                             crate::network::wifi_stats::set_channel(ap.primary as u32);
                             crate::network::observability::record_wifi_event(
@@ -212,6 +234,33 @@ impl WifiReconnectManager {
         Ok(())
     }
     
+    /// Pin the station to `bssid` (None: any AP with the SSID) and reassociate
+    /// now if it is on a different one. The pin is also applied at boot from
+    /// `wifi_bssid`.
+    pub fn pin_bssid(bssid: Option<[u8; 6]>) -> Result<()> {
+        use esp_idf_sys::*;
+
+        unsafe {
+            let mut cfg: wifi_config_t = core::mem::zeroed();
+            esp!(esp_wifi_get_config(wifi_interface_t_WIFI_IF_STA, &mut cfg))?;
+            cfg.sta.bssid_set = bssid.is_some();
+            cfg.sta.bssid = bssid.unwrap_or_default();
+            esp!(esp_wifi_set_config(wifi_interface_t_WIFI_IF_STA, &mut cfg))?;
+
+            let mut ap: wifi_ap_record_t = core::mem::zeroed();
+            let associated = esp_wifi_sta_get_ap_info(&mut ap) == ESP_OK;
+            if bssid.is_some_and(|pinned| !associated || ap.bssid != pinned) {
+                log::info!("WiFi: moving to pinned BSSID");
+                let _ = esp_wifi_disconnect();
+                let result = esp_wifi_connect();
+                if result != ESP_OK && result != ESP_ERR_WIFI_CONN as i32 {
+                    bail!("Failed to reconnect to pinned BSSID: {}", result);
+                }
+            }
+        }
+        Ok(())
+    }
+    
     /// Stop monitoring
     #[allow(dead_code)] // Will be used for graceful shutdown
     pub fn stop_monitoring(&self) {
@@ -270,6 +319,30 @@ impl WifiReconnectManager {
     }
 }
 
+fn uptime_secs() -> u64 {
+    (unsafe { esp_idf_sys::esp_timer_get_time() } / 1_000_000) as u64
+}
+
+/// Negotiated PHY of the current association
+fn negotiated_phy() -> &'static str {
+    use esp_idf_sys::*;
+
+    let mut mode: wifi_phy_mode_t = 0;
+    if unsafe { esp_wifi_sta_get_negotiated_phymode(&mut mode) } != ESP_OK {
+        return "unknown";
+    }
+    #[allow(non_upper_case_globals)]
+    match mode {
+        wifi_phy_mode_t_WIFI_PHY_MODE_LR => "LR",
+        wifi_phy_mode_t_WIFI_PHY_MODE_11B => "11b",
+        wifi_phy_mode_t_WIFI_PHY_MODE_11G => "11g",
+        wifi_phy_mode_t_WIFI_PHY_MODE_HT20 => "11n HT20",
+        wifi_phy_mode_t_WIFI_PHY_MODE_HT40 => "11n HT40",
+        wifi_phy_mode_t_WIFI_PHY_MODE_HE20 => "11ax HE20",
+        _ => "unknown",
+    }
+}
+
 /// Check if we just completed an OTA update and force reconnection
 pub fn handle_post_ota_wifi() -> Result<()> {
     let reset_reason = unsafe { esp_idf_sys::esp_reset_reason() };
@@ -314,6 +387,8 @@ pub fn handle_post_ota_wifi() -> Result<()> {
             match WifiReconnectManager::force_reconnect() {
                 Ok(_) => {
                     log::info!("WiFi reconnection initiated successfully");
+                    // Responders still cache the previous firmware's records
+                    super::mdns_manager::reannounce();
                     return Ok(());
                 }
                 Err(e) => {
@@ -0,0 +1,174 @@
+// Access point scan for the web UI's network picker. The scan runs in its own
+// thread while the station stays associated: the radio visits each channel for
+// ~120 ms and returns to the home channel in between, so the current connection
+// only sees a short latency bump.
+
+use serde::Serialize;
+#[cfg(target_os = "espidf")]
+use {
+    anyhow::{bail, Result},
+    core::sync::atomic::{AtomicBool, Ordering},
+    std::sync::Mutex,
+};
+
+/// Results older than this trigger a fresh scan on the next request
+pub const MAX_RESULT_AGE_MS: u64 = 30_000;
+/// Most APs fetched from the driver per scan (each record is ~80 bytes)
+pub const MAX_RECORDS: usize = 24;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScanNetwork {
+    pub ssid: String,
+    pub rssi: i8,
+    pub channel: u8,
+    pub auth: &'static str,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScanStatus {
+    pub scanning: bool,
+    /// Milliseconds since the shown results were collected
+    pub age_ms: Option<u64>,
+    pub error: Option<String>,
+    pub networks: Vec<ScanNetwork>,
+}
+
+/// One entry per SSID (its strongest BSSID), hidden networks dropped, strongest first
+pub fn merge_results(records: Vec<ScanNetwork>) -> Vec<ScanNetwork> {
+    let mut networks: Vec<ScanNetwork> = Vec::with_capacity(records.len());
+    for record in records.into_iter().filter(|r| !r.ssid.is_empty()) {
+        match networks.iter_mut().find(|n| n.ssid == record.ssid) {
+            Some(existing) if existing.rssi < record.rssi => *existing = record,
+            Some(_) => {}
+            None => networks.push(record),
+        }
+    }
+    networks.sort_by_key(|n| core::cmp::Reverse(n.rssi));
+    networks
+}
+
+#[cfg(target_os = "espidf")]
+struct ScanState {
+    finished_ms: Option<u64>,
+    error: Option<String>,
+    networks: Vec<ScanNetwork>,
+}
+
+#[cfg(target_os = "espidf")]
+static STATE: Mutex<ScanState> = Mutex::new(ScanState { finished_ms: None, error: None, networks: Vec::new() });
+#[cfg(target_os = "espidf")]
+static SCANNING: AtomicBool = AtomicBool::new(false);
+
+#[cfg(target_os = "espidf")]
+fn now_ms() -> u64 {
+    unsafe { (esp_idf_sys::esp_timer_get_time() / 1000) as u64 }
+}
+
+/// Latest results; kicks off a background scan when they are missing or stale
+#[cfg(target_os = "espidf")]
+pub fn status_or_scan() -> ScanStatus {
+    let now = now_ms();
+    let state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    let age_ms = state.finished_ms.map(|t| now.saturating_sub(t));
+    let stale = age_ms.map_or(true, |age| age > MAX_RESULT_AGE_MS);
+    let status = ScanStatus {
+        scanning: SCANNING.load(Ordering::Relaxed),
+        age_ms,
+        error: state.error.clone(),
+        networks: state.networks.clone(),
+    };
+    drop(state);
+
+    if stale && !SCANNING.swap(true, Ordering::SeqCst) {
+        let spawned = std::thread::Builder::new()
+            .name("wifi_scan".into())
+            .stack_size(4096)
+            .spawn(|| {
+                let result = run_scan();
+                let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+                state.finished_ms = Some(now_ms());
+                match result {
+                    Ok(networks) => {
+                        log::info!("WiFi scan found {} networks", networks.len());
+                        state.networks = networks;
+                        state.error = None;
+                    }
+                    Err(e) => {
+                        log::warn!("WiFi scan failed: {:?}", e);
+                        state.error = Some(e.to_string());
+                    }
+                }
+                SCANNING.store(false, Ordering::SeqCst);
+            });
+        if let Err(e) = spawned {
+            log::warn!("Failed to start WiFi scan thread: {:?}", e);
+            SCANNING.store(false, Ordering::SeqCst);
+            return status;
+        }
+        return ScanStatus { scanning: true, ..status };
+    }
+    status
+}
+
+/// Blocking all-channel active scan
+#[cfg(target_os = "espidf")]
+pub fn run_scan() -> Result<Vec<ScanNetwork>> {
+    use esp_idf_sys::*;
+
+    unsafe {
+        let config: wifi_scan_config_t = core::mem::zeroed(); // all channels, active scan
+        let err = esp_wifi_scan_start(&config, true);
+        if err != ESP_OK {
+            // ESP_ERR_WIFI_STATE while the station is mid-(re)connect
+            bail!("scan not possible right now (error {})", err);
+        }
+        let mut count = MAX_RECORDS as u16;
+        let mut records: Vec<wifi_ap_record_t> = vec![core::mem::zeroed(); MAX_RECORDS];
+        // Also frees the driver's copy of the list
+        esp!(esp_wifi_scan_get_ap_records(&mut count, records.as_mut_ptr()))?;
+        records.truncate(count as usize);
+
+        let networks = records
+            .iter()
+            .map(|r| {
+                let len = r.ssid.iter().position(|&b| b == 0).unwrap_or(r.ssid.len());
+                ScanNetwork {
+                    ssid: String::from_utf8_lossy(&r.ssid[..len]).into_owned(),
+                    rssi: r.rssi,
+                    channel: r.primary,
+                    auth: auth_name(r.authmode),
+                }
+            })
+            .collect();
+        Ok(merge_results(networks))
+    }
+}
+
+#[cfg(target_os = "espidf")]
+fn auth_name(mode: esp_idf_sys::wifi_auth_mode_t) -> &'static str {
+    use esp_idf_sys::*;
+    match mode {
+        wifi_auth_mode_t_WIFI_AUTH_OPEN => "open",
+        wifi_auth_mode_t_WIFI_AUTH_WEP => "wep",
+        wifi_auth_mode_t_WIFI_AUTH_WPA_PSK => "wpa",
+        wifi_auth_mode_t_WIFI_AUTH_WPA2_PSK | wifi_auth_mode_t_WIFI_AUTH_WPA_WPA2_PSK => "wpa2",
+        wifi_auth_mode_t_WIFI_AUTH_WPA3_PSK | wifi_auth_mode_t_WIFI_AUTH_WPA2_WPA3_PSK => "wpa3",
+        wifi_auth_mode_t_WIFI_AUTH_WPA2_ENTERPRISE => "enterprise",
+        _ => "other",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ap(ssid: &str, rssi: i8) -> ScanNetwork {
+        ScanNetwork { ssid: ssid.to_string(), rssi, channel: 6, auth: "wpa2" }
+    }
+
+    #[test]
+    fn test_merge_keeps_strongest_per_ssid() {
+        let merged = merge_results(vec![ap("Home", -70), ap("", -40), ap("Office", -60), ap("Home", -50)]);
+        assert_eq!(merged, vec![ap("Home", -50), ap("Office", -60)]);
+    }
+}
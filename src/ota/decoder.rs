@@ -0,0 +1,68 @@
+// Decoding of OTA upload bodies. Raw images, gzip streams and delta patches
+// (optionally gzipped) all come out as the plain image for the update partition.
+
+use flate2::write::GzDecoder;
+use std::io::{self, Write};
+
+use super::delta::DeltaPatcher;
+use super::error::OtaError;
+use super::partitions::RunningImage;
+
+pub enum ImageDecoder<W: Write> {
+    Raw(W),
+    Gzip(GzDecoder<W>),
+    Delta(DeltaPatcher<RunningImage, W>),
+    GzipDelta(GzDecoder<DeltaPatcher<RunningImage, W>>),
+}
+
+impl<W: Write> ImageDecoder<W> {
+    /// `gzip` from `Content-Encoding: gzip`, `delta` from `X-OTA-Delta: 1`
+    pub fn new(out: W, gzip: bool, delta: bool) -> Result<Self, OtaError> {
+        Ok(match (gzip, delta) {
+            (false, false) => Self::Raw(out),
+            (true, false) => Self::Gzip(GzDecoder::new(out)),
+            (false, true) => Self::Delta(DeltaPatcher::new(RunningImage::new()?, out)),
+            (true, true) => Self::GzipDelta(GzDecoder::new(DeltaPatcher::new(RunningImage::new()?, out))),
+        })
+    }
+
+    /// Where decoded image bytes go
+    pub fn sink(&self) -> &W {
+        match self {
+            Self::Raw(w) => w,
+            Self::Gzip(d) => d.get_ref(),
+            Self::Delta(p) => p.get_ref(),
+            Self::GzipDelta(d) => d.get_ref().get_ref(),
+        }
+    }
+
+    /// Fail if the stream was truncated, otherwise flush everything to the sink
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Raw(mut w) => w.flush(),
+            Self::Gzip(d) => d.finish()?.flush(),
+            Self::Delta(p) => p.finish().map(drop),
+            Self::GzipDelta(d) => d.finish()?.finish().map(drop),
+        }
+    }
+}
+
+impl<W: Write> Write for ImageDecoder<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Raw(w) => w.write(data),
+            Self::Gzip(d) => d.write(data),
+            Self::Delta(p) => p.write(data),
+            Self::GzipDelta(d) => d.write(data),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Raw(w) => w.flush(),
+            Self::Gzip(d) => d.flush(),
+            Self::Delta(p) => p.flush(),
+            Self::GzipDelta(d) => d.flush(),
+        }
+    }
+}
@@ -0,0 +1,300 @@
+// Streaming delta updates: the new image is rebuilt from the running one while
+// the patch is received, so only the differences cross the network.
+//
+// Patch format (little endian), produced by scripts/ota-delta.py from a bsdiff patch:
+//   "ESPDELTA" | base_size u32 | new_size u32 | base_sha256 [u8; 32]
+// followed by records until new_size bytes have been produced:
+//   diff_len u32 | extra_len u32 | seek i32 | diff bytes | extra bytes
+// Each record adds the diff bytes to the base image at the current base offset,
+// appends the extra bytes verbatim, then moves the base offset by `seek`.
+
+use std::io::{self, Write};
+
+pub const MAGIC: &[u8; 8] = b"ESPDELTA";
+pub const HEADER_LEN: usize = 48;
+const RECORD_LEN: usize = 12;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeltaHeader {
+    pub base_size: u32,
+    pub new_size: u32,
+    pub base_sha256: [u8; 32],
+}
+
+impl DeltaHeader {
+    pub fn parse(bytes: &[u8; HEADER_LEN]) -> io::Result<Self> {
+        if &bytes[..8] != MAGIC {
+            return Err(invalid("Not a delta patch"));
+        }
+        let mut base_sha256 = [0u8; 32];
+        base_sha256.copy_from_slice(&bytes[16..48]);
+        Ok(Self {
+            base_size: u32_at(bytes, 8),
+            new_size: u32_at(bytes, 12),
+            base_sha256,
+        })
+    }
+}
+
+/// Image the patch is applied against (the running firmware on the device)
+pub trait BaseImage {
+    /// Fail unless this is the image the patch was made from
+    fn verify(&mut self, header: &DeltaHeader) -> io::Result<()>;
+    fn read_at(&mut self, offset: u32, buf: &mut [u8]) -> io::Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Header,
+    Record,
+    Diff(u32),
+    Extra(u32),
+    Done,
+}
+
+/// Takes patch bytes through `io::Write` and writes the rebuilt image to `out`
+pub struct DeltaPatcher<B: BaseImage, W: Write> {
+    base: B,
+    out: W,
+    state: State,
+    header: Option<DeltaHeader>,
+    // Partially received header or record
+    pending: Vec<u8>,
+    extra_len: u32,
+    seek: i32,
+    base_pos: i64,
+    produced: u32,
+    scratch: Vec<u8>,
+}
+
+impl<B: BaseImage, W: Write> DeltaPatcher<B, W> {
+    pub fn new(base: B, out: W) -> Self {
+        Self {
+            base,
+            out,
+            state: State::Header,
+            header: None,
+            pending: Vec::with_capacity(HEADER_LEN),
+            extra_len: 0,
+            seek: 0,
+            base_pos: 0,
+            produced: 0,
+            scratch: Vec::new(),
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.out
+    }
+
+    /// Check the patch was complete and return the output writer
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.state != State::Done {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Delta patch ended early"));
+        }
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn new_size(&self) -> u32 {
+        self.header.as_ref().map_or(0, |h| h.new_size)
+    }
+
+    fn parse_pending(&mut self) -> io::Result<()> {
+        match self.state {
+            State::Header => {
+                let bytes: &[u8; HEADER_LEN] = self.pending.as_slice().try_into().map_err(|_| invalid("Short header"))?;
+                let header = DeltaHeader::parse(bytes)?;
+                self.base.verify(&header)?;
+                self.header = Some(header);
+                self.state = State::Record;
+            }
+            State::Record => {
+                let diff_len = u32_at(&self.pending, 0);
+                let extra_len = u32_at(&self.pending, 4);
+                let end = self.produced as u64 + diff_len as u64 + extra_len as u64;
+                if end > self.new_size() as u64 {
+                    return Err(invalid("Delta record runs past the image end"));
+                }
+                self.extra_len = extra_len;
+                self.seek = u32_at(&self.pending, 8) as i32;
+                self.state = State::Diff(diff_len);
+            }
+            _ => {}
+        }
+        self.pending.clear();
+        Ok(())
+    }
+
+    fn apply_diff(&mut self, diff: &[u8]) -> io::Result<()> {
+        let base_size = self.header.as_ref().map_or(0, |h| h.base_size) as i64;
+        if self.base_pos < 0 || self.base_pos + diff.len() as i64 > base_size {
+            return Err(invalid("Delta reads outside the base image"));
+        }
+        self.scratch.resize(diff.len(), 0);
+        self.base.read_at(self.base_pos as u32, &mut self.scratch)?;
+        for (b, d) in self.scratch.iter_mut().zip(diff) {
+            *b = b.wrapping_add(*d);
+        }
+        self.out.write_all(&self.scratch)?;
+        self.base_pos += diff.len() as i64;
+        Ok(())
+    }
+
+    // Move past finished diff/extra sections, including empty ones
+    fn settle(&mut self) {
+        loop {
+            match self.state {
+                State::Diff(0) => self.state = State::Extra(self.extra_len),
+                State::Extra(0) => {
+                    self.base_pos += self.seek as i64;
+                    self.state = if self.produced == self.new_size() { State::Done } else { State::Record };
+                    return;
+                }
+                _ => return,
+            }
+        }
+    }
+}
+
+impl<B: BaseImage, W: Write> Write for DeltaPatcher<B, W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let mut rest = data;
+        while !rest.is_empty() {
+            match self.state {
+                State::Header | State::Record => {
+                    let want = if self.state == State::Header { HEADER_LEN } else { RECORD_LEN };
+                    let take = (want - self.pending.len()).min(rest.len());
+                    self.pending.extend_from_slice(&rest[..take]);
+                    rest = &rest[take..];
+                    if self.pending.len() == want {
+                        self.parse_pending()?;
+                    }
+                }
+                State::Diff(remaining) => {
+                    let take = (remaining as usize).min(rest.len());
+                    self.apply_diff(&rest[..take])?;
+                    self.produced += take as u32;
+                    self.state = State::Diff(remaining - take as u32);
+                    rest = &rest[take..];
+                }
+                State::Extra(remaining) => {
+                    let take = (remaining as usize).min(rest.len());
+                    self.out.write_all(&rest[..take])?;
+                    self.produced += take as u32;
+                    self.state = State::Extra(remaining - take as u32);
+                    rest = &rest[take..];
+                }
+                State::Done => return Err(invalid("Trailing data after delta patch")),
+            }
+            self.settle();
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct VecBase {
+        data: Vec<u8>,
+        sha: [u8; 32],
+    }
+
+    impl BaseImage for VecBase {
+        fn verify(&mut self, header: &DeltaHeader) -> io::Result<()> {
+            if header.base_size as usize != self.data.len() || header.base_sha256 != self.sha {
+                return Err(invalid("Delta base does not match"));
+            }
+            Ok(())
+        }
+
+        fn read_at(&mut self, offset: u32, buf: &mut [u8]) -> io::Result<()> {
+            let start = offset as usize;
+            buf.copy_from_slice(&self.data[start..start + buf.len()]);
+            Ok(())
+        }
+    }
+
+    fn base() -> VecBase {
+        VecBase { data: (0u8..64).collect(), sha: [7; 32] }
+    }
+
+    fn header(new_size: u32) -> Vec<u8> {
+        let mut h = MAGIC.to_vec();
+        h.extend_from_slice(&64u32.to_le_bytes());
+        h.extend_from_slice(&new_size.to_le_bytes());
+        h.extend_from_slice(&[7; 32]);
+        h
+    }
+
+    fn record(patch: &mut Vec<u8>, diff: &[u8], extra: &[u8], seek: i32) {
+        patch.extend_from_slice(&(diff.len() as u32).to_le_bytes());
+        patch.extend_from_slice(&(extra.len() as u32).to_le_bytes());
+        patch.extend_from_slice(&seek.to_le_bytes());
+        patch.extend_from_slice(diff);
+        patch.extend_from_slice(extra);
+    }
+
+    // New image: base[0..4] + 1, "NEW", base[32..36] unchanged
+    fn sample_patch() -> (Vec<u8>, Vec<u8>) {
+        let mut patch = header(11);
+        record(&mut patch, &[1, 1, 1, 1], b"NEW", 28);
+        record(&mut patch, &[0, 0, 0, 0], b"", 0);
+        (patch, vec![1, 2, 3, 4, b'N', b'E', b'W', 32, 33, 34, 35])
+    }
+
+    #[test]
+    fn test_applies_patch_in_any_chunking() {
+        let (patch, expected) = sample_patch();
+        for chunk in [1, 5, 13, patch.len()] {
+            let mut patcher = DeltaPatcher::new(base(), Vec::new());
+            for part in patch.chunks(chunk) {
+                patcher.write_all(part).unwrap();
+            }
+            assert_eq!(patcher.finish().unwrap(), expected, "chunk size {}", chunk);
+        }
+    }
+
+    #[test]
+    fn test_rejects_truncated_and_trailing_data() {
+        let (patch, _) = sample_patch();
+        let mut patcher = DeltaPatcher::new(base(), Vec::new());
+        patcher.write_all(&patch[..patch.len() - 1]).unwrap();
+        assert_eq!(patcher.finish().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+        let mut patcher = DeltaPatcher::new(base(), Vec::new());
+        assert!(patcher.write_all(&[patch.as_slice(), &[0]].concat()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_wrong_base_and_bad_records() {
+        let (mut patch, _) = sample_patch();
+        patch[20] ^= 1;
+        assert!(DeltaPatcher::new(base(), Vec::new()).write_all(&patch).is_err());
+
+        // Record longer than the declared image
+        let mut patch = header(2);
+        record(&mut patch, &[0; 3], b"", 0);
+        assert!(DeltaPatcher::new(base(), Vec::new()).write_all(&patch).is_err());
+
+        // Seek before the start of the base
+        let mut patch = header(2);
+        record(&mut patch, &[0], b"", -5);
+        record(&mut patch, &[0], b"", 0);
+        assert!(DeltaPatcher::new(base(), Vec::new()).write_all(&patch).is_err());
+    }
+}
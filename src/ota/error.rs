@@ -0,0 +1,78 @@
+// OTA update errors. Failures caused by the upload (wrong size, bad checksum,
+// truncated or undecodable body) map to 4xx so upload tools can tell them from
+// a device-side problem with the partitions or flash.
+
+use thiserror::Error;
+use crate::error::HttpStatus;
+
+#[derive(Debug, Error)]
+pub enum OtaError {
+    #[error("No update partition available")]
+    NoUpdatePartition,
+    /// Delta updates need the running image as their base
+    #[error("Running partition not found")]
+    NoRunningPartition,
+    #[error("Failed to begin OTA update")]
+    BeginFailed,
+    #[error("Failed to write OTA data")]
+    WriteFailed,
+    #[error("OTA validation failed")]
+    ValidationFailed,
+    #[error("Failed to set boot partition")]
+    BootPartitionFailed,
+    #[error("Invalid firmware size")]
+    InvalidSize,
+    #[error("Resume does not match the interrupted update")]
+    ResumeMismatch,
+    /// The connection closed before the whole body arrived
+    #[error("Upload ended after {received} of {expected} bytes")]
+    Interrupted { received: usize, expected: usize },
+    #[error("Failed to read request data: {0}")]
+    Read(String),
+    /// Corrupt gzip stream or delta patch
+    #[error("Failed to decode OTA data: {0}")]
+    Decode(String),
+}
+
+impl OtaError {
+    /// Error from an OTA decoder: the update partition's own errors come back
+    /// through std::io, anything else is the decoder rejecting the stream
+    pub fn from_decoder(e: std::io::Error) -> Self {
+        let message = e.to_string();
+        match e.into_inner().map(|inner| inner.downcast::<OtaError>()) {
+            Some(Ok(ota)) => *ota,
+            _ => Self::Decode(message),
+        }
+    }
+}
+
+impl HttpStatus for OtaError {
+    fn http_status(&self) -> u16 {
+        match self {
+            Self::NoUpdatePartition => 503,
+            Self::ResumeMismatch => 409,
+            Self::ValidationFailed
+            | Self::InvalidSize
+            | Self::Interrupted { .. }
+            | Self::Read(_)
+            | Self::Decode(_) => 400,
+            Self::NoRunningPartition | Self::BeginFailed | Self::WriteFailed | Self::BootPartitionFailed => 500,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoder_errors() {
+        let flash = std::io::Error::other(OtaError::InvalidSize);
+        assert!(matches!(OtaError::from_decoder(flash), OtaError::InvalidSize));
+        let corrupt = std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid gzip header");
+        let decoded = OtaError::from_decoder(corrupt);
+        assert_eq!(decoded.to_string(), "Failed to decode OTA data: invalid gzip header");
+        assert_eq!(decoded.http_status(), 400);
+        assert_eq!(OtaError::WriteFailed.http_status(), 500);
+    }
+}
@@ -8,44 +8,12 @@ use esp_idf_sys::{
     esp_partition_find_first, esp_partition_type_t_ESP_PARTITION_TYPE_APP as ESP_PARTITION_TYPE_APP,
     esp_partition_subtype_t_ESP_PARTITION_SUBTYPE_APP_OTA_0 as ESP_PARTITION_SUBTYPE_APP_OTA_0,
 };
-use std::fmt;
 use std::ffi::CStr;
 use sha2::{Sha256, Digest};
-use esp_idf_hal::delay::FreeRtos;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum OtaStatus {
-    Idle,
-    Downloading { progress: u8 },
-    Verifying,
-    Ready,
-    Failed,
-}
-
-#[derive(Debug)]
-pub enum OtaError {
-    NoUpdatePartition,
-    BeginFailed,
-    WriteFailed,
-    ValidationFailed,
-    BootPartitionFailed,
-    InvalidSize,
-}
-
-impl fmt::Display for OtaError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            OtaError::NoUpdatePartition => write!(f, "No update partition available"),
-            OtaError::BeginFailed => write!(f, "Failed to begin OTA update"),
-            OtaError::WriteFailed => write!(f, "Failed to write OTA data"),
-            OtaError::ValidationFailed => write!(f, "OTA validation failed"),
-            OtaError::BootPartitionFailed => write!(f, "Failed to set boot partition"),
-            OtaError::InvalidSize => write!(f, "Invalid firmware size"),
-        }
-    }
-}
-
-impl std::error::Error for OtaError {}
+use super::error::OtaError;
+use super::progress::OtaStatus;
+use super::resume::ResumePoint;
 
 pub struct OtaManager {
     update_partition: *const esp_partition_t,
@@ -55,6 +23,7 @@ pub struct OtaManager {
     status: OtaStatus,
     sha256_hasher: Option<Sha256>,
     expected_sha256: Option<String>,
+    sha256_verified: bool,
 }
 
 // SAFETY: OtaManager only contains a pointer to the partition structure which is
@@ -122,9 +91,16 @@ impl OtaManager {
             status: OtaStatus::Idle,
             sha256_hasher: None,
             expected_sha256: None,
+            sha256_verified: false,
         })
     }
     
+    /// Every status change goes through here so ota::progress readers stay in step
+    fn set_status(&mut self, status: OtaStatus) {
+        self.status = status;
+        super::progress::publish(status, self.bytes_written, self.expected_size, self.sha256_verified);
+    }
+    
     pub fn set_expected_sha256(&mut self, sha256: String) {
         self.expected_sha256 = Some(sha256);
     }
@@ -133,9 +109,16 @@ impl OtaManager {
         if size == 0 || size > 4 * 1024 * 1024 {
             // Sanity check: firmware should be between 0 and 4MB
             log::error!("OTA: Invalid firmware size: {} bytes", size);
+            self.set_status(OtaStatus::Failed);
             return Err(OtaError::InvalidSize);
         }
         
+        // A new upload replaces any interrupted one still waiting for a resume
+        if let Some(handle) = self.ota_handle.take() {
+            log::info!("OTA: Discarding interrupted update at {} bytes", self.bytes_written);
+            unsafe { esp_idf_sys::esp_ota_abort(handle); }
+        }
+        
         log::info!("OTA: Beginning update with size: {} bytes", size);
         
         // Log partition info
@@ -184,14 +167,16 @@ impl OtaManager {
                 _ => log::error!("OTA: Unknown error code: {} (0x{:x})", result, result),
             }
             
+            self.set_status(OtaStatus::Failed);
             return Err(OtaError::BeginFailed);
         }
         
         self.ota_handle = Some(handle);
         self.expected_size = size;
         self.bytes_written = 0;
-        self.status = OtaStatus::Downloading { progress: 0 };
+        self.sha256_verified = false;
         self.sha256_hasher = Some(Sha256::new());
+        self.set_status(OtaStatus::Downloading { progress: 0 });
         
         Ok(())
     }
@@ -199,6 +184,13 @@ impl OtaManager {
     pub fn write_chunk(&mut self, data: &[u8]) -> Result<(), OtaError> {
         let handle = self.ota_handle.ok_or(OtaError::WriteFailed)?;
         
+        // Decompressed streams must not grow past the announced image size
+        if self.bytes_written + data.len() > self.expected_size {
+            log::error!("OTA: Image larger than announced {} bytes", self.expected_size);
+            self.set_status(OtaStatus::Failed);
+            return Err(OtaError::InvalidSize);
+        }
+        
         // Update SHA256 hash
         if let Some(ref mut hasher) = self.sha256_hasher {
             hasher.update(data);
@@ -213,7 +205,7 @@ impl OtaManager {
         };
         
         if result != 0 {
-            self.status = OtaStatus::Failed;
+            self.set_status(OtaStatus::Failed);
             return Err(OtaError::WriteFailed);
         }
         
@@ -222,16 +214,25 @@ impl OtaManager {
         // Update progress
         if self.expected_size > 0 {
             let progress = ((self.bytes_written * 100) / self.expected_size) as u8;
-            self.status = OtaStatus::Downloading { progress };
+            self.set_status(OtaStatus::Downloading { progress });
         }
         
         Ok(())
     }
     
-    pub fn finish_update(&mut self) -> Result<(), OtaError> {
+    /// Verify the image; with `apply` the boot slot is switched, otherwise the
+    /// update is staged for `apply_staged` (maintenance window)
+    pub fn finish_update(&mut self, apply: bool) -> Result<(), OtaError> {
         let handle = self.ota_handle.take().ok_or(OtaError::ValidationFailed)?;
         
-        self.status = OtaStatus::Verifying;
+        self.set_status(OtaStatus::Verifying);
+        
+        if self.bytes_written != self.expected_size {
+            log::error!("OTA: Received {} of {} bytes", self.bytes_written, self.expected_size);
+            self.set_status(OtaStatus::Failed);
+            unsafe { esp_ota_end(handle); }
+            return Err(OtaError::InvalidSize);
+        }
         
         // Verify SHA256 if provided
         if let (Some(hasher), Some(expected)) = (self.sha256_hasher.take(), &self.expected_sha256) {
@@ -241,52 +242,127 @@ impl OtaManager {
             
             if computed.to_lowercase() != expected.to_lowercase() {
                 log::error!("OTA: SHA256 mismatch! Update rejected.");
-                self.status = OtaStatus::Failed;
+                self.set_status(OtaStatus::Failed);
                 // Still need to call esp_ota_end to clean up
                 unsafe { esp_ota_end(handle); }
                 return Err(OtaError::ValidationFailed);
             }
             
             log::info!("OTA: SHA256 validation passed");
+            self.sha256_verified = true;
+            self.set_status(OtaStatus::Verifying);
         }
         
         // End the OTA update
         let result = unsafe { esp_ota_end(handle) };
         
         if result != 0 {
-            self.status = OtaStatus::Failed;
+            self.set_status(OtaStatus::Failed);
             return Err(OtaError::ValidationFailed);
         }
         
+        if !apply {
+            log::info!("OTA: Update staged until the maintenance window");
+            self.set_status(OtaStatus::Staged);
+            return Ok(());
+        }
+        self.apply_staged()
+    }
+    
+    pub fn is_staged(&self) -> bool {
+        self.status == OtaStatus::Staged
+    }
+    
+    /// Boot the verified image on the next restart
+    pub fn apply_staged(&mut self) -> Result<(), OtaError> {
         // Set the new boot partition
         let result = unsafe { esp_ota_set_boot_partition(self.update_partition) };
         
         if result != 0 {
-            self.status = OtaStatus::Failed;
+            self.set_status(OtaStatus::Failed);
             return Err(OtaError::BootPartitionFailed);
         }
         
-        self.status = OtaStatus::Ready;
+        self.set_status(OtaStatus::Ready);
         Ok(())
     }
     
-    pub fn get_status(&self) -> OtaStatus {
-        self.status
+    /// Continue an interrupted update at `offset`; the size and SHA256 must be
+    /// those of the upload that was cut off
+    pub fn resume_update(&mut self, offset: usize, size: usize) -> Result<(), OtaError> {
+        let matches_session = self.ota_handle.is_some()
+            && self.sha256_hasher.is_some()
+            && offset == self.bytes_written
+            && size == self.expected_size;
+        if !matches_session {
+            log::error!("OTA: Cannot resume at {} of {} bytes (flashed {} of {})",
+                offset, size, self.bytes_written, self.expected_size);
+            return Err(OtaError::ResumeMismatch);
+        }
+        
+        log::info!("OTA: Resuming update at {} of {} bytes", offset, size);
+        let progress = ((self.bytes_written * 100) / self.expected_size) as u8;
+        self.set_status(OtaStatus::Downloading { progress });
+        Ok(())
+    }
+    
+    /// The upload connection dropped: keep the OTA handle and running hash so
+    /// the rest of the image can follow in a new request
+    pub fn suspend(&mut self) {
+        log::warn!("OTA: Upload interrupted at {} of {} bytes; waiting for resume",
+            self.bytes_written, self.expected_size);
+        self.set_status(OtaStatus::Failed);
+    }
+    
+    /// Interrupted update that a new request can continue
+    pub fn resume_point(&self) -> Option<ResumePoint> {
+        let hasher = self.sha256_hasher.as_ref()?;
+        if self.ota_handle.is_none() || self.bytes_written >= self.expected_size {
+            return None;
+        }
+        Some(ResumePoint {
+            offset: self.bytes_written,
+            total: self.expected_size,
+            prefix_sha256: format!("{:x}", hasher.clone().finalize()),
+            sha256: self.expected_sha256.clone(),
+        })
+    }
+    
+    /// Give up on an update that failed outside the manager (upload read or
+    /// decode errors); frees the OTA handle and reports the failure
+    pub fn abort(&mut self) {
+        if let Some(handle) = self.ota_handle.take() {
+            unsafe { esp_idf_sys::esp_ota_abort(handle); }
+        }
+        self.sha256_hasher = None;
+        self.set_status(OtaStatus::Failed);
     }
     
     pub fn get_progress(&self) -> u8 {
         match self.status {
             OtaStatus::Downloading { progress } => progress,
-            OtaStatus::Ready => 100,
+            OtaStatus::Staged | OtaStatus::Ready => 100,
             _ => 0,
         }
     }
 }
 
+// Lets OTA decoders (gzip, delta) stream straight into the update partition
+impl std::io::Write for OtaManager {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        self.write_chunk(data).map_err(std::io::Error::other)?;
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 /// Ensure the device is booted into an OTA slot (ota_0/ota_1). If currently
 /// running from the factory partition but an OTA partition exists, switch the
-/// boot partition to the first OTA slot and reboot. Returns true if a switch
-/// was initiated.
+/// boot partition to the first OTA slot and reboot. Returns false when no
+/// switch was needed or it failed; a successful switch does not return.
 pub fn ensure_ota_boot_if_needed() -> bool {
     unsafe {
         let running = esp_idf_sys::esp_ota_get_running_partition();
@@ -322,10 +398,7 @@ pub fn ensure_ota_boot_if_needed() -> bool {
         }
 
         log::warn!("OTA: Self-heal: switching boot to ota_0 and restarting (was: {})", running_label);
-        // Small delay to flush logs
-        FreeRtos::delay_ms(500);
-        esp_idf_sys::esp_restart();
-        true
+        crate::system::shutdown::restart_now(crate::system::shutdown::RestartReason::BootSlotRepair)
     }
 }
 
@@ -1,12 +1,22 @@
 // OTA (Over-The-Air) update module
 
+pub mod decoder;
+pub mod delta;
+pub mod error;
 pub mod manager;
+pub mod partitions;
+pub mod progress;
+pub mod resume;
+pub mod window;
 
-pub use manager::{OtaManager, OtaStatus};
+pub use error::OtaError;
+pub use manager::OtaManager;
+pub use progress::OtaStatus;
 
 // OTA update flow:
 // 1. Check for updates (manual or automatic)
 // 2. Download firmware to OTA partition
 // 3. Verify integrity
-// 4. Set boot partition
-// 5. Restart
\ No newline at end of file
+// 4. Stage until the maintenance window, if one is set (window.rs)
+// 5. Set boot partition
+// 6. Restart
\ No newline at end of file
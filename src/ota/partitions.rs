@@ -0,0 +1,208 @@
+// OTA partition state: which app slot is running, which one boots next and what
+// each holds, plus rollback to the previously installed app, read access to
+// the running image for delta updates and the full partition table.
+
+use anyhow::{anyhow, Result};
+use core::ffi::{c_void, CStr};
+use esp_idf_sys::{
+    esp_app_desc_t, esp_ota_get_boot_partition, esp_ota_get_next_update_partition,
+    esp_ota_get_partition_description, esp_ota_get_running_partition,
+    esp_ota_get_state_partition, esp_ota_img_states_t, esp_ota_set_boot_partition,
+    esp_partition_find, esp_partition_get, esp_partition_next, esp_partition_read, esp_partition_t,
+    esp_partition_type_t_ESP_PARTITION_TYPE_ANY as ESP_PARTITION_TYPE_ANY,
+    esp_partition_type_t_ESP_PARTITION_TYPE_APP as ESP_PARTITION_TYPE_APP,
+    esp_partition_subtype_t_ESP_PARTITION_SUBTYPE_ANY as ESP_PARTITION_SUBTYPE_ANY,
+    esp_ota_img_states_t_ESP_OTA_IMG_ABORTED as IMG_ABORTED,
+    esp_ota_img_states_t_ESP_OTA_IMG_INVALID as IMG_INVALID,
+    esp_ota_img_states_t_ESP_OTA_IMG_NEW as IMG_NEW,
+    esp_ota_img_states_t_ESP_OTA_IMG_PENDING_VERIFY as IMG_PENDING_VERIFY,
+    esp_ota_img_states_t_ESP_OTA_IMG_VALID as IMG_VALID,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::io;
+
+use super::delta::{BaseImage, DeltaHeader};
+use super::error::OtaError;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PartitionInfo {
+    pub label: String,
+    pub address: u32,
+    pub size: u32,
+    /// App version from the image descriptor, None if the slot holds no valid app
+    pub version: Option<String>,
+    /// otadata state ("new", "pending_verify", "valid", "invalid", "aborted",
+    /// "undefined"); None for the factory slot, which has no otadata entry
+    pub state: Option<&'static str>,
+}
+
+impl PartitionInfo {
+    /// Whether the slot holds an app that can be booted
+    pub fn is_bootable(&self) -> bool {
+        self.version.is_some() && !matches!(self.state, Some("invalid") | Some("aborted"))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PartitionReport {
+    pub running: Option<PartitionInfo>,
+    pub boot: Option<PartitionInfo>,
+    /// Slot the next OTA update will be written to, i.e. the previous app
+    pub next: Option<PartitionInfo>,
+    pub rollback_available: bool,
+}
+
+fn describe(partition: *const esp_partition_t) -> Option<PartitionInfo> {
+    if partition.is_null() {
+        return None;
+    }
+    // Safety: partition pointers from esp_ota_* point into the static partition table
+    let p = unsafe { &*partition };
+    let label = unsafe { CStr::from_ptr(p.label.as_ptr()) }.to_string_lossy().into_owned();
+
+    let mut desc: esp_app_desc_t = unsafe { core::mem::zeroed() };
+    let version = (unsafe { esp_ota_get_partition_description(partition, &mut desc) } == 0)
+        .then(|| unsafe { CStr::from_ptr(desc.version.as_ptr()) }.to_string_lossy().into_owned());
+
+    let mut img_state: esp_ota_img_states_t = 0;
+    let state = (unsafe { esp_ota_get_state_partition(partition, &mut img_state) } == 0).then_some(match img_state {
+        IMG_NEW => "new",
+        IMG_PENDING_VERIFY => "pending_verify",
+        IMG_VALID => "valid",
+        IMG_INVALID => "invalid",
+        IMG_ABORTED => "aborted",
+        _ => "undefined",
+    });
+
+    Some(PartitionInfo { label, address: p.address, size: p.size, version, state })
+}
+
+/// One row of the flash partition table
+#[derive(Debug, Clone, Serialize)]
+pub struct TableEntry {
+    pub label: String,
+    /// "app" or "data"
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub subtype: u8,
+    pub address: u32,
+    pub size: u32,
+    pub encrypted: bool,
+}
+
+/// Every partition in the table, in flash order
+pub fn table() -> Vec<TableEntry> {
+    let mut entries = Vec::new();
+    let mut it = unsafe { esp_partition_find(ESP_PARTITION_TYPE_ANY, ESP_PARTITION_SUBTYPE_ANY, core::ptr::null()) };
+    while !it.is_null() {
+        // Safety: see describe()
+        let p = unsafe { &*esp_partition_get(it) };
+        entries.push(TableEntry {
+            label: unsafe { CStr::from_ptr(p.label.as_ptr()) }.to_string_lossy().into_owned(),
+            kind: if p.type_ == ESP_PARTITION_TYPE_APP { "app" } else { "data" },
+            subtype: p.subtype as u8,
+            address: p.address,
+            size: p.size,
+            encrypted: p.encrypted,
+        });
+        it = unsafe { esp_partition_next(it) };
+    }
+    // esp_partition_next releases the iterator once it runs out
+    entries.sort_by_key(|entry| entry.address);
+    entries
+}
+
+pub fn report() -> PartitionReport {
+    let running = describe(unsafe { esp_ota_get_running_partition() });
+    let boot = describe(unsafe { esp_ota_get_boot_partition() });
+    let next = describe(unsafe { esp_ota_get_next_update_partition(core::ptr::null()) });
+    let rollback_available = rollback_target(running.as_ref(), next.as_ref()).is_some();
+    PartitionReport { running, boot, next, rollback_available }
+}
+
+/// Label of the running app slot ("factory", "ota_0", ...)
+pub fn running_label() -> String {
+    describe(unsafe { esp_ota_get_running_partition() })
+        .map(|p| p.label)
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+// Only the other OTA slot counts as "previous": rolling back from an OTA slot to
+// factory would be undone by ensure_ota_boot_if_needed on the next boot.
+fn rollback_target<'a>(running: Option<&PartitionInfo>, next: Option<&'a PartitionInfo>) -> Option<&'a PartitionInfo> {
+    let running = running?;
+    let next = next?;
+    (running.label.starts_with("ota_") && next.label != running.label && next.is_bootable()).then_some(next)
+}
+
+/// Make the previously installed app boot next. The caller restarts the device.
+/// Returns the label of the slot that will boot.
+pub fn rollback() -> Result<String> {
+    let running_ptr = unsafe { esp_ota_get_running_partition() };
+    let next_ptr = unsafe { esp_ota_get_next_update_partition(core::ptr::null()) };
+    let running = describe(running_ptr);
+    let next = describe(next_ptr);
+    let target = rollback_target(running.as_ref(), next.as_ref())
+        .ok_or_else(|| anyhow!("No previous app to roll back to"))?;
+
+    let err = unsafe { esp_ota_set_boot_partition(next_ptr) };
+    if err != 0 {
+        return Err(anyhow!("Failed to set boot partition to {}: {}", target.label, err));
+    }
+    log::warn!("OTA: Rollback from {} to {} ({})",
+        running.as_ref().map_or("?", |p| p.label.as_str()),
+        target.label,
+        target.version.as_deref().unwrap_or("unknown version"));
+    Ok(target.label.clone())
+}
+
+/// The running app partition as the base of a delta update
+pub struct RunningImage {
+    partition: *const esp_partition_t,
+}
+
+impl RunningImage {
+    pub fn new() -> Result<Self, OtaError> {
+        let partition = unsafe { esp_ota_get_running_partition() };
+        if partition.is_null() {
+            return Err(OtaError::NoRunningPartition);
+        }
+        Ok(Self { partition })
+    }
+}
+
+impl BaseImage for RunningImage {
+    fn verify(&mut self, header: &DeltaHeader) -> io::Result<()> {
+        // Safety: see describe()
+        let size = unsafe { (*self.partition).size };
+        if header.base_size > size {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Delta base is larger than the running partition"));
+        }
+
+        let mut hasher = Sha256::new();
+        let mut buf = [0u8; 4096];
+        let mut offset = 0;
+        while offset < header.base_size {
+            let len = (header.base_size - offset).min(buf.len() as u32) as usize;
+            self.read_at(offset, &mut buf[..len])?;
+            hasher.update(&buf[..len]);
+            offset += len as u32;
+        }
+        if hasher.finalize().as_slice() != header.base_sha256 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Delta base does not match the running firmware"));
+        }
+        log::info!("OTA: Delta base verified ({} bytes)", header.base_size);
+        Ok(())
+    }
+
+    fn read_at(&mut self, offset: u32, buf: &mut [u8]) -> io::Result<()> {
+        let err = unsafe {
+            esp_partition_read(self.partition, offset as usize, buf.as_mut_ptr() as *mut c_void, buf.len())
+        };
+        if err != 0 {
+            return Err(io::Error::other(format!("Flash read at 0x{:x} failed: {}", offset, err)));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+// Lock-free view of the running update. OtaManager publishes every status
+// change here; the on-device overlay, /api/ota/status and the /api/ota/progress
+// stream read it without touching the manager's mutex, which the upload handler
+// holds for the whole transfer. All readers therefore see the same phase.
+
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, Ordering};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OtaStatus {
+    Idle,
+    Downloading { progress: u8 },
+    Verifying,
+    /// Verified and waiting for the maintenance window (see window.rs)
+    Staged,
+    Ready,
+    Failed,
+}
+
+impl OtaStatus {
+    /// Name used by the web UI and the JSON endpoints
+    pub fn phase(&self) -> &'static str {
+        match self {
+            OtaStatus::Idle => "idle",
+            OtaStatus::Downloading { .. } => "receiving",
+            OtaStatus::Verifying => "verifying",
+            OtaStatus::Staged => "staged",
+            OtaStatus::Ready => "ready",
+            OtaStatus::Failed => "failed",
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self, OtaStatus::Staged | OtaStatus::Ready | OtaStatus::Failed)
+    }
+
+    fn code(&self) -> u8 {
+        match self {
+            OtaStatus::Idle => 0,
+            OtaStatus::Downloading { .. } => 1,
+            OtaStatus::Verifying => 2,
+            OtaStatus::Ready => 3,
+            OtaStatus::Failed => 4,
+            OtaStatus::Staged => 5,
+        }
+    }
+
+    fn from_code(code: u8, percent: u8) -> Self {
+        match code {
+            1 => OtaStatus::Downloading { progress: percent },
+            2 => OtaStatus::Verifying,
+            3 => OtaStatus::Ready,
+            4 => OtaStatus::Failed,
+            5 => OtaStatus::Staged,
+            _ => OtaStatus::Idle,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct OtaProgress {
+    /// Bumped on every publish; lets pollers skip unchanged snapshots
+    pub sequence: u32,
+    pub phase: &'static str,
+    pub percent: u8,
+    /// Decoded image bytes written to flash so far
+    pub bytes_written: u32,
+    pub total_bytes: u32,
+    /// The image matched the uploader's X-SHA256
+    pub sha256_verified: bool,
+}
+
+impl OtaProgress {
+    pub fn status(&self) -> OtaStatus {
+        match self.phase {
+            "receiving" => OtaStatus::Downloading { progress: self.percent },
+            "verifying" => OtaStatus::Verifying,
+            "staged" => OtaStatus::Staged,
+            "ready" => OtaStatus::Ready,
+            "failed" => OtaStatus::Failed,
+            _ => OtaStatus::Idle,
+        }
+    }
+}
+
+// Sequence lock: odd while a publish is in flight, so readers retry instead of
+// mixing fields from two updates. There is a single writer (the upload).
+static SEQUENCE: AtomicU32 = AtomicU32::new(0);
+static PHASE: AtomicU8 = AtomicU8::new(0);
+static PERCENT: AtomicU8 = AtomicU8::new(0);
+static BYTES_WRITTEN: AtomicU32 = AtomicU32::new(0);
+static TOTAL_BYTES: AtomicU32 = AtomicU32::new(0);
+static SHA256_VERIFIED: AtomicBool = AtomicBool::new(false);
+
+pub fn publish(status: OtaStatus, bytes_written: usize, total_bytes: usize, sha256_verified: bool) {
+    SEQUENCE.fetch_add(1, Ordering::AcqRel);
+    let percent = match status {
+        OtaStatus::Downloading { progress } => progress,
+        OtaStatus::Staged | OtaStatus::Ready => 100,
+        _ => PERCENT.load(Ordering::Relaxed),
+    };
+    PHASE.store(status.code(), Ordering::Relaxed);
+    PERCENT.store(percent, Ordering::Relaxed);
+    BYTES_WRITTEN.store(bytes_written as u32, Ordering::Relaxed);
+    TOTAL_BYTES.store(total_bytes as u32, Ordering::Relaxed);
+    SHA256_VERIFIED.store(sha256_verified, Ordering::Relaxed);
+    SEQUENCE.fetch_add(1, Ordering::Release);
+}
+
+pub fn snapshot() -> OtaProgress {
+    loop {
+        let before = SEQUENCE.load(Ordering::Acquire);
+        if before % 2 == 1 {
+            core::hint::spin_loop();
+            continue;
+        }
+        let code = PHASE.load(Ordering::Relaxed);
+        let percent = PERCENT.load(Ordering::Relaxed);
+        let progress = OtaProgress {
+            sequence: before / 2,
+            phase: OtaStatus::from_code(code, percent).phase(),
+            percent,
+            bytes_written: BYTES_WRITTEN.load(Ordering::Relaxed),
+            total_bytes: TOTAL_BYTES.load(Ordering::Relaxed),
+            sha256_verified: SHA256_VERIFIED.load(Ordering::Relaxed),
+        };
+        if SEQUENCE.load(Ordering::Acquire) == before {
+            return progress;
+        }
+    }
+}
+
+pub fn status() -> OtaStatus {
+    snapshot().status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_roundtrip() {
+        let start = snapshot().sequence;
+        publish(OtaStatus::Downloading { progress: 42 }, 420, 1000, false);
+        let progress = snapshot();
+        assert_eq!(progress.sequence, start + 1);
+        assert_eq!((progress.phase, progress.percent, progress.bytes_written), ("receiving", 42, 420));
+        assert_eq!(progress.status(), OtaStatus::Downloading { progress: 42 });
+
+        // Verifying keeps the last percent so the bar doesn't jump back to 0
+        publish(OtaStatus::Verifying, 1000, 1000, false);
+        assert_eq!(snapshot().percent, 42);
+        publish(OtaStatus::Ready, 1000, 1000, true);
+        let done = snapshot();
+        assert!(done.status().is_finished() && done.sha256_verified);
+        assert_eq!(done.percent, 100);
+    }
+}
@@ -0,0 +1,91 @@
+// Resuming interrupted uploads. When a plain (uncompressed) upload drops midway
+// the OTA handle and the running SHA256 stay open, and /api/ota/offset reports
+// how far the image got together with the hash of that prefix. The uploader
+// checks the prefix against its own file and sends the rest with
+// `X-OTA-Offset: N` (or `Content-Range: bytes N-M/TOTAL`).
+//
+// Compressed and delta uploads always restart: their decoders keep state that
+// is not recoverable from the bytes already flashed.
+
+/// Where an interrupted update can continue
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResumePoint {
+    /// Image bytes already written and hashed
+    pub offset: usize,
+    pub total: usize,
+    /// SHA256 of image bytes [0, offset)
+    pub prefix_sha256: String,
+    /// X-SHA256 announced by the interrupted upload
+    pub sha256: Option<String>,
+}
+
+/// `Content-Range: bytes START-END/TOTAL` (TOTAL may be `*`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContentRange {
+    pub start: usize,
+    pub end: usize,
+    pub total: Option<usize>,
+}
+
+pub fn parse_content_range(value: &str) -> Option<ContentRange> {
+    let range = value.trim().strip_prefix("bytes")?.trim_start();
+    let (span, total) = range.split_once('/')?;
+    let (start, end) = span.split_once('-')?;
+    let start = start.trim().parse().ok()?;
+    let end = end.trim().parse().ok()?;
+    let total = match total.trim() {
+        "*" => None,
+        t => Some(t.parse().ok()?),
+    };
+    if end < start || total.is_some_and(|t| end >= t) {
+        return None;
+    }
+    Some(ContentRange { start, end, total })
+}
+
+/// Image offset an upload starts at, from `X-OTA-Offset` or `Content-Range`.
+/// Returns (offset, image size if the range announced it).
+pub fn requested_offset(
+    offset_header: Option<&str>,
+    content_range: Option<&str>,
+) -> Result<(usize, Option<usize>), &'static str> {
+    if let Some(value) = offset_header {
+        let offset = value.trim().parse().map_err(|_| "Invalid X-OTA-Offset")?;
+        return Ok((offset, None));
+    }
+    match content_range {
+        Some(value) => {
+            let range = parse_content_range(value).ok_or("Invalid Content-Range")?;
+            Ok((range.start, range.total))
+        }
+        None => Ok((0, None)),
+    }
+}
+
+/// Hex SHA256 prefixes are compared case-insensitively
+pub fn sha256_matches(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_range_and_offset() {
+        assert_eq!(
+            parse_content_range("bytes 4096-1048575/1048576"),
+            Some(ContentRange { start: 4096, end: 1_048_575, total: Some(1_048_576) })
+        );
+        assert_eq!(parse_content_range("bytes 0-9/*").map(|r| r.total), Some(None));
+        assert_eq!(parse_content_range("bytes 10-5/20"), None);
+        assert_eq!(parse_content_range("bytes 0-20/20"), None);
+        assert_eq!(parse_content_range("items 0-1/2"), None);
+
+        assert_eq!(requested_offset(None, None), Ok((0, None)));
+        assert_eq!(requested_offset(Some("8192"), Some("bytes 0-1/2")), Ok((8192, None)));
+        assert_eq!(requested_offset(None, Some("bytes 100-199/200")), Ok((100, Some(200))));
+        assert!(requested_offset(Some("-1"), None).is_err());
+        assert!(sha256_matches("ABcd", "abCD"));
+    }
+}
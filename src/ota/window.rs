@@ -0,0 +1,88 @@
+// OTA maintenance window. With a window configured (e.g. 02:00-04:00 local
+// time, in the configured timezone), a pushed update is flashed and verified
+// straight away but only staged: the boot slot is switched and the device
+// restarted once local time is inside the window. An upload carrying
+// `X-OTA-Apply: now` skips the wait. Until SNTP has synced the local time is
+// unknown, so staged updates keep waiting. The staged image lives in the
+// inactive slot and is dropped if the device restarts before the window.
+
+use core::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+use serde::Serialize;
+use crate::power::quiet_hours::in_window;
+use crate::units::{format_time_of_day_in, ClockFormat};
+
+/// Upload header that applies the update immediately (`X-OTA-Apply: now`)
+pub const APPLY_HEADER: &str = "X-OTA-Apply";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static START: AtomicU16 = AtomicU16::new(2 * 60);
+static END: AtomicU16 = AtomicU16::new(4 * 60);
+
+/// Reported as `window` in /api/ota/status
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WindowStatus {
+    pub enabled: bool,
+    /// Local "HH:MM"
+    pub start: String,
+    pub end: String,
+    /// Local time is inside the window now
+    pub open: bool,
+}
+
+/// Publish the configured window (main loop, after each config read)
+pub fn configure(enabled: bool, start: u16, end: u16) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+    START.store(start, Ordering::Relaxed);
+    END.store(end, Ordering::Relaxed);
+}
+
+/// Inside the window at `minute` (minutes since local midnight; None before SNTP sync)
+pub fn is_open(minute: Option<u16>) -> bool {
+    minute.is_some_and(|m| in_window(m, START.load(Ordering::Relaxed), END.load(Ordering::Relaxed)))
+}
+
+/// Whether an update that just finished verifying is applied now or staged
+pub fn apply_now(forced: bool, minute: Option<u16>) -> bool {
+    forced || !ENABLED.load(Ordering::Relaxed) || is_open(minute)
+}
+
+/// The value of the `X-OTA-Apply` header asks to skip the window
+pub fn is_forced(header: Option<&str>) -> bool {
+    header.is_some_and(|value| value.trim().eq_ignore_ascii_case("now"))
+}
+
+/// Local start of the window, "HH:MM"
+pub fn start_text() -> String {
+    format_time_of_day_in(ClockFormat::H24, START.load(Ordering::Relaxed)).into()
+}
+
+pub fn status(minute: Option<u16>) -> WindowStatus {
+    WindowStatus {
+        enabled: ENABLED.load(Ordering::Relaxed),
+        start: start_text(),
+        end: format_time_of_day_in(ClockFormat::H24, END.load(Ordering::Relaxed)).into(),
+        open: is_open(minute),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_now() {
+        configure(false, 2 * 60, 4 * 60);
+        assert!(apply_now(false, None), "no window: updates apply immediately");
+
+        configure(true, 2 * 60, 4 * 60);
+        assert!(!apply_now(false, Some(12 * 60)));
+        assert!(!apply_now(false, None), "staged until the clock is known");
+        assert!(apply_now(false, Some(3 * 60)));
+        assert!(apply_now(true, Some(12 * 60)));
+        assert!(is_forced(Some("NOW")) && !is_forced(Some("later")) && !is_forced(None));
+
+        let status = status(Some(2 * 60));
+        assert_eq!((status.start.as_str(), status.end.as_str(), status.open), ("02:00", "04:00", true));
+        configure(false, 2 * 60, 4 * 60);
+    }
+}
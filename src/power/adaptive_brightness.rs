@@ -0,0 +1,248 @@
+// Adaptive backlight. Fixed lux thresholds suit one room and not the next, so
+// with `adaptive_brightness` on the backlight follows a curve learned from
+// where the device actually is: a few days of ambient light samples from the
+// first sensor reporting lux, and the current reading placed by percentile
+// among them. The darkest readings of the history (night) get MIN_LEVEL_PERCENT
+// of the configured brightness, the brightest (midday) all of it, and the
+// curve drifts with the seasons as old samples age out.
+
+use crate::ring_buffer::RingBuffer;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// One sample kept per this interval, however often the sensor is read
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(300);
+/// Three days of samples
+pub const HISTORY_SAMPLES: usize = 3 * 24 * 12;
+/// Samples (two hours) needed before the curve replaces the fixed brightness
+pub const MIN_SAMPLES: usize = 24;
+/// Share of the configured brightness at the darkest percentile
+pub const MIN_LEVEL_PERCENT: u32 = 10;
+/// Curve breakpoints: every tenth percentile, 0 to 100
+pub const CURVE_POINTS: usize = 11;
+
+pub struct BrightnessCurve {
+    samples: RingBuffer<f32>,
+    last_sample: Option<Instant>,
+    last_lux: Option<f32>,
+    /// Lux at each tenth percentile of `samples`; None below MIN_SAMPLES
+    breakpoints: Option<[f32; CURVE_POINTS]>,
+}
+
+/// GET /api/brightness/curve
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CurveReport {
+    /// Whether the curve drives the backlight (enabled and learned)
+    pub active: bool,
+    pub samples: usize,
+    pub min_samples: usize,
+    pub sample_interval_secs: u64,
+    pub current_lux: Option<f32>,
+    pub current_level: Option<u8>,
+    /// Empty until MIN_SAMPLES are in
+    pub points: Vec<CurvePoint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CurvePoint {
+    pub percentile: u8,
+    pub lux: f32,
+    /// Backlight level (0-255) at this lux
+    pub level: u8,
+}
+
+impl BrightnessCurve {
+    pub const fn new() -> Self {
+        Self { samples: RingBuffer::new(HISTORY_SAMPLES), last_sample: None, last_lux: None, breakpoints: None }
+    }
+
+    /// A reading taken at `now`; goes into the history at most once per
+    /// SAMPLE_INTERVAL. Returns whether it was kept.
+    pub fn observe(&mut self, now: Instant, lux: f32) -> bool {
+        if !lux.is_finite() || lux < 0.0 {
+            return false;
+        }
+        self.last_lux = Some(lux);
+        if self.last_sample.is_some_and(|last| now.duration_since(last) < SAMPLE_INTERVAL) {
+            return false;
+        }
+        self.last_sample = Some(now);
+        self.samples.push(lux);
+        self.breakpoints = (self.samples.len() >= MIN_SAMPLES).then(|| self.compute_breakpoints());
+        true
+    }
+
+    fn compute_breakpoints(&self) -> [f32; CURVE_POINTS] {
+        let mut sorted: Vec<f32> = self.samples.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+        let last = sorted.len() - 1;
+        core::array::from_fn(|i| sorted[(i * last + (CURVE_POINTS - 1) / 2) / (CURVE_POINTS - 1)])
+    }
+
+    /// Where `lux` falls in the history, 0.0 (darkest) to 1.0 (brightest).
+    /// A level the history spends a long stretch at (night, a lamp) covers
+    /// several breakpoints; at the ends it maps to 0 or 1, elsewhere to the
+    /// middle of its stretch.
+    pub fn percentile(&self, lux: f32) -> Option<f32> {
+        let points = self.breakpoints.as_ref()?;
+        let last = CURVE_POINTS - 1;
+        let steps = last as f32;
+        let lo = points.partition_point(|&p| p < lux);
+        if lo == 0 {
+            return Some(0.0);
+        }
+        if lo > last {
+            return Some(1.0);
+        }
+        if points[lo] > lux {
+            // Between two breakpoints
+            let below = points[lo - 1];
+            return Some(((lo - 1) as f32 + (lux - below) / (points[lo] - below)) / steps);
+        }
+        let hi = points.partition_point(|&p| p <= lux) - 1;
+        Some(if hi == last { 1.0 } else { (lo + hi) as f32 / 2.0 / steps })
+    }
+
+    /// Backlight level for `lux`, scaling `max_level` (the configured brightness)
+    pub fn level(&self, lux: f32, max_level: u8) -> Option<u8> {
+        self.percentile(lux).map(|p| level_at(p, max_level))
+    }
+
+    /// Level for the latest reading
+    pub fn current_level(&self, max_level: u8) -> Option<u8> {
+        self.level(self.last_lux?, max_level)
+    }
+
+    /// Forget the history and start learning again
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn report(&self, enabled: bool, max_level: u8) -> CurveReport {
+        let points = self.breakpoints.map(|points| {
+            points.iter().enumerate().map(|(i, &lux)| CurvePoint {
+                percentile: (i * 100 / (CURVE_POINTS - 1)) as u8,
+                lux,
+                level: level_at(i as f32 / (CURVE_POINTS - 1) as f32, max_level),
+            }).collect()
+        }).unwrap_or_default();
+        CurveReport {
+            active: enabled && self.breakpoints.is_some(),
+            samples: self.samples.len(),
+            min_samples: MIN_SAMPLES,
+            sample_interval_secs: SAMPLE_INTERVAL.as_secs(),
+            current_lux: self.last_lux,
+            current_level: self.current_level(max_level),
+            points,
+        }
+    }
+}
+
+impl Default for BrightnessCurve {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn level_at(percentile: f32, max_level: u8) -> u8 {
+    let floor = max_level as u32 * MIN_LEVEL_PERCENT / 100;
+    (floor as f32 + (max_level as u32 - floor) as f32 * percentile.clamp(0.0, 1.0)).round() as u8
+}
+
+static CURVE: Mutex<BrightnessCurve> = Mutex::new(BrightnessCurve::new());
+
+fn with_curve<R>(f: impl FnOnce(&mut BrightnessCurve) -> R) -> R {
+    f(&mut CURVE.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Feed an ambient light reading (lux)
+pub fn observe(lux: f32) {
+    with_curve(|curve| curve.observe(Instant::now(), lux));
+}
+
+/// Backlight level for the latest reading; None until the curve is learned
+pub fn level(max_level: u8) -> Option<u8> {
+    with_curve(|curve| curve.current_level(max_level))
+}
+
+pub fn report(enabled: bool, max_level: u8) -> CurveReport {
+    with_curve(|curve| curve.report(enabled, max_level))
+}
+
+pub fn reset() {
+    with_curve(BrightnessCurve::reset);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A day of samples: 12 h at night-time `dark` lux, 12 h ramping up to `bright`
+    fn learn_day(curve: &mut BrightnessCurve, start: Instant, dark: f32, bright: f32) -> Instant {
+        let per_half_day = 12 * 3600 / SAMPLE_INTERVAL.as_secs() as usize;
+        let mut now = start;
+        for i in 0..per_half_day * 2 {
+            let lux = if i < per_half_day { dark } else { dark + (bright - dark) * (i - per_half_day) as f32 / per_half_day as f32 };
+            assert!(curve.observe(now, lux));
+            now += SAMPLE_INTERVAL;
+        }
+        now
+    }
+
+    #[test]
+    fn test_curve_needs_history() {
+        let mut curve = BrightnessCurve::new();
+        let start = Instant::now();
+        assert!(curve.observe(start, 100.0));
+        assert!(!curve.observe(start + Duration::from_secs(10), 500.0), "too soon after the last sample");
+        assert_eq!(curve.level(100.0, 200), None);
+        assert!(!curve.observe(start, f32::NAN));
+
+        let report = curve.report(true, 200);
+        assert!(!report.active);
+        assert_eq!((report.samples, report.current_lux), (1, Some(500.0)));
+        assert!(report.points.is_empty());
+    }
+
+    #[test]
+    fn test_percentile_mapping() {
+        let mut curve = BrightnessCurve::new();
+        learn_day(&mut curve, Instant::now(), 2.0, 800.0);
+
+        // Night-time darkness: the floor; brighter than ever seen: full
+        assert_eq!(curve.level(2.0, 200), Some(20));
+        assert_eq!(curve.level(0.0, 200), Some(20));
+        assert_eq!(curve.level(5_000.0, 200), Some(200));
+        // Half way up the day's ramp sits around the 75th percentile
+        let mid = curve.percentile(401.0).unwrap();
+        assert!((0.7..0.8).contains(&mid), "{}", mid);
+        let levels: Vec<u8> = [2.0, 100.0, 300.0, 600.0, 800.0].iter().map(|&lux| curve.level(lux, 255).unwrap()).collect();
+        assert!(levels.windows(2).all(|w| w[0] <= w[1]), "{:?}", levels);
+
+        let report = curve.report(true, 255);
+        assert!(report.active);
+        assert_eq!(report.points.len(), CURVE_POINTS);
+        assert_eq!((report.points[0].percentile, report.points[0].level), (0, 25));
+        assert_eq!((report.points[10].percentile, report.points[10].level), (100, 255));
+        assert!(!curve.report(false, 255).active);
+
+        curve.reset();
+        assert_eq!(curve.report(true, 255).samples, 0);
+    }
+
+    #[test]
+    fn test_curve_follows_a_darker_room() {
+        let mut curve = BrightnessCurve::new();
+        let now = learn_day(&mut curve, Instant::now(), 50.0, 1000.0);
+        let before = curve.level(60.0, 255).unwrap();
+        // Moved to a dim room: three days later the same 60 lux reads as bright
+        let mut now = now;
+        for _ in 0..3 {
+            now = learn_day(&mut curve, now, 0.0, 80.0);
+        }
+        let after = curve.level(60.0, 255).unwrap();
+        assert!(after > before + 50, "{} -> {}", before, after);
+        assert_eq!(curve.level(0.0, 255), Some(25));
+    }
+}
@@ -0,0 +1,140 @@
+// Backlight fades. PowerManager decides the level the backlight should be at
+// (0 when dimmed off or asleep, the configured brightness otherwise) and a
+// BacklightRamp eases the PWM level there over `backlight_fade_ms` instead of
+// switching it. The main loop samples the ramp once per pass and hands the
+// level to DisplayManager, which is the only thing driving the pin.
+
+use std::time::{Duration, Instant};
+use crate::ui::animation::{Animation, EasingFunction};
+
+/// Default time for a full 0 to 255 fade
+pub const DEFAULT_FADE_MS: u32 = 400;
+
+/// Longest fade the config accepts
+pub const MAX_FADE_MS: u32 = 5_000;
+
+/// PWM duty for a 0-255 backlight level at `resolution_bits`; any level
+/// above 0 stays lit
+pub fn pwm_duty(level: u8, resolution_bits: u32) -> u32 {
+    let max = (1u32 << resolution_bits) - 1;
+    if level == 0 {
+        return 0;
+    }
+    (level as u32 * max / 255).max(1)
+}
+
+/// Eased transitions between backlight levels
+#[derive(Debug, Clone)]
+pub struct BacklightRamp {
+    target: u8,
+    fade: Duration,
+    easing: EasingFunction,
+    /// Running fade, None while holding at `target`
+    ramp: Option<Animation>,
+}
+
+impl BacklightRamp {
+    /// Holding at `level`
+    pub fn new(level: u8) -> Self {
+        Self {
+            target: level,
+            fade: Duration::from_millis(DEFAULT_FADE_MS as u64),
+            easing: EasingFunction::EaseInOut,
+            ramp: None,
+        }
+    }
+
+    /// Fade time for a full-range change and its curve; applies to the next fade
+    pub fn set_fade(&mut self, fade: Duration, easing: EasingFunction) {
+        self.fade = fade;
+        self.easing = easing;
+    }
+
+    pub fn target(&self) -> u8 {
+        self.target
+    }
+
+    /// Start fading towards `target`; smaller steps take proportionally less time
+    pub fn set_target(&mut self, target: u8, now: Instant) {
+        if target == self.target {
+            return;
+        }
+        let from = self.level_at(now);
+        self.target = target;
+        let span = (target as i32 - from as i32).unsigned_abs();
+        let duration = self.fade * span / 255;
+        self.ramp = (!duration.is_zero()).then(|| {
+            let mut animation = Animation::new(from as f32, target as f32, duration, self.easing);
+            animation.start_at(now);
+            animation
+        });
+    }
+
+    /// Level at `now`
+    pub fn level_at(&self, now: Instant) -> u8 {
+        match &self.ramp {
+            Some(animation) => animation.value_at(now).round().clamp(0.0, 255.0) as u8,
+            None => self.target,
+        }
+    }
+
+    /// Level at `now`, ending the fade once it reached its target
+    pub fn update(&mut self, now: Instant) -> u8 {
+        if self.ramp.as_ref().is_some_and(|a| a.is_finished_at(now)) {
+            self.ramp = None;
+        }
+        self.level_at(now)
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.ramp.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pwm_duty() {
+        assert_eq!(pwm_duty(0, 10), 0);
+        assert_eq!(pwm_duty(1, 10), 4);
+        assert_eq!(pwm_duty(128, 10), 513);
+        assert_eq!(pwm_duty(255, 10), 1023);
+        assert_eq!(pwm_duty(1, 4), 1);
+        assert_eq!(pwm_duty(255, 8), 255);
+    }
+
+    #[test]
+    fn test_ramp_eases_between_levels() {
+        let t0 = Instant::now();
+        let ms = |m: u64| t0 + Duration::from_millis(m);
+        let mut ramp = BacklightRamp::new(0);
+        ramp.set_fade(Duration::from_millis(400), EasingFunction::Linear);
+
+        // Boot: fade in over the full duration
+        ramp.set_target(255, t0);
+        assert_eq!(ramp.update(t0), 0);
+        assert_eq!(ramp.update(ms(200)), 128);
+        assert_eq!(ramp.update(ms(400)), 255);
+        assert!(!ramp.is_fading());
+
+        // A smaller step takes proportionally less time
+        ramp.set_target(51, ms(1000));
+        assert_eq!(ramp.update(ms(1160)), 153);
+        assert_eq!(ramp.update(ms(1320)), 51);
+
+        // Reversing mid-fade starts from where the level is now
+        ramp.set_target(0, ms(2000));
+        assert_eq!(ramp.update(ms(2040)), 26);
+        ramp.set_target(255, ms(2040));
+        assert_eq!(ramp.update(ms(2040)), 26);
+        assert!(ramp.is_fading());
+
+        // No fade time switches at once
+        ramp.set_fade(Duration::ZERO, EasingFunction::EaseInOut);
+        ramp.set_target(10, ms(3000));
+        assert_eq!(ramp.update(ms(3000)), 10);
+        assert!(!ramp.is_fading());
+    }
+}
@@ -0,0 +1,158 @@
+// Battery operation profile for portable use. The WiFi radio is kept off and
+// only woken every `wake_interval` for a short window in which queued metrics
+// are pushed and OTA can run; the display refreshes at 1 FPS, and once nobody
+// has touched the device for `sleep_after` (and the radio is parked) the chip
+// deep-sleeps until the next window or a USER button press.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Display refresh interval while the profile is active
+pub const FRAME_INTERVAL: Duration = Duration::from_secs(1);
+
+static RADIO_PARKED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DutyCycleConfig {
+    /// Time between the starts of two WiFi windows
+    pub wake_interval: Duration,
+    /// How long the radio stays up per window
+    pub awake_window: Duration,
+    /// Input idle time before deep sleep (only while the radio is parked)
+    pub sleep_after: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RadioAction {
+    Wake,
+    Park,
+}
+
+/// WiFi on/off schedule
+#[derive(Debug)]
+pub struct WifiDutyCycle {
+    awake_since: Option<Instant>,
+    next_wake: Instant,
+}
+
+impl WifiDutyCycle {
+    /// Starts awake: boot (or a wake from deep sleep) is itself a sync window
+    pub fn new(now: Instant) -> Self {
+        Self { awake_since: Some(now), next_wake: now }
+    }
+
+    /// Advance the schedule; returns the radio change due at `now`, if any
+    pub fn update(&mut self, now: Instant, cfg: &DutyCycleConfig) -> Option<RadioAction> {
+        match self.awake_since {
+            Some(since) if now.duration_since(since) >= cfg.awake_window => {
+                self.awake_since = None;
+                self.next_wake = since + cfg.wake_interval;
+                Some(RadioAction::Park)
+            }
+            None if now >= self.next_wake => {
+                self.awake_since = Some(now);
+                Some(RadioAction::Wake)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_awake(&self) -> bool {
+        self.awake_since.is_some()
+    }
+
+    /// Time until the next window opens; zero while the radio is up
+    pub fn until_next_wake(&self, now: Instant) -> Duration {
+        if self.is_awake() {
+            Duration::ZERO
+        } else {
+            self.next_wake.saturating_duration_since(now)
+        }
+    }
+
+    /// True once the radio is parked and input has been idle for `sleep_after`
+    pub fn should_deep_sleep(&self, idle: Duration, cfg: &DutyCycleConfig) -> bool {
+        !self.is_awake() && idle >= cfg.sleep_after
+    }
+}
+
+/// True while the profile has the radio switched off; the reconnect monitor
+/// leaves it alone and network tasks treat the link as down
+pub fn is_radio_parked() -> bool {
+    RADIO_PARKED.load(Ordering::Relaxed)
+}
+
+/// Start or stop the WiFi radio for the duty cycle
+#[cfg(target_os = "espidf")]
+pub fn set_radio(on: bool) {
+    use esp_idf_sys::*;
+    RADIO_PARKED.store(!on, Ordering::Relaxed);
+    unsafe {
+        if on {
+            log::info!("Battery profile: WiFi window open");
+            let _ = esp_wifi_start();
+            let _ = esp_wifi_connect();
+        } else {
+            log::info!("Battery profile: WiFi parked");
+            let _ = esp_wifi_disconnect();
+            let _ = esp_wifi_stop();
+        }
+    }
+    if !on {
+        crate::network::wifi_stats::set_connected(false);
+    }
+}
+
+/// Deep-sleep until `wake_in` has passed or the USER button (GPIO14) is
+/// pressed. The chip resets on wake, so this never returns.
+#[cfg(target_os = "espidf")]
+pub fn enter_deep_sleep(wake_in: Duration) -> ! {
+    use esp_idf_sys::*;
+    log::warn!("Battery profile: deep sleep for {}s", wake_in.as_secs());
+    unsafe {
+        esp_sleep_enable_timer_wakeup(wake_in.as_micros().max(1) as u64);
+        // Buttons are active low; GPIO0 is a strapping pin so only USER wakes
+        esp_sleep_enable_ext0_wakeup(gpio_num_t_GPIO_NUM_14, 0);
+        esp_deep_sleep_start();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> DutyCycleConfig {
+        DutyCycleConfig {
+            wake_interval: Duration::from_secs(600),
+            awake_window: Duration::from_secs(60),
+            sleep_after: Duration::from_secs(120),
+        }
+    }
+
+    #[test]
+    fn test_window_schedule() {
+        let cfg = config();
+        let t0 = Instant::now();
+        let mut cycle = WifiDutyCycle::new(t0);
+        assert!(cycle.is_awake());
+        assert_eq!(cycle.update(t0 + Duration::from_secs(30), &cfg), None);
+        assert_eq!(cycle.update(t0 + Duration::from_secs(60), &cfg), Some(RadioAction::Park));
+        assert_eq!(cycle.update(t0 + Duration::from_secs(61), &cfg), None);
+        // The interval is measured from the start of the previous window
+        assert_eq!(cycle.until_next_wake(t0 + Duration::from_secs(100)), Duration::from_secs(500));
+        assert_eq!(cycle.update(t0 + Duration::from_secs(599), &cfg), None);
+        assert_eq!(cycle.update(t0 + Duration::from_secs(600), &cfg), Some(RadioAction::Wake));
+        assert_eq!(cycle.until_next_wake(t0 + Duration::from_secs(601)), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_deep_sleep_only_while_parked() {
+        let cfg = config();
+        let t0 = Instant::now();
+        let mut cycle = WifiDutyCycle::new(t0);
+        assert!(!cycle.should_deep_sleep(Duration::from_secs(300), &cfg));
+        cycle.update(t0 + Duration::from_secs(60), &cfg);
+        assert!(!cycle.should_deep_sleep(Duration::from_secs(119), &cfg));
+        assert!(cycle.should_deep_sleep(Duration::from_secs(120), &cfg));
+    }
+}
@@ -0,0 +1,212 @@
+// Brown-out and power-fail tracking: brown-out resets (from the RTC reset
+// reason) and supply voltage dips seen on the battery ADC, counted across
+// reboots in NVS to help diagnose flaky USB supplies.
+
+use serde::Serialize;
+#[cfg(target_os = "espidf")]
+use crate::storage::kv;
+#[cfg(target_os = "espidf")]
+use std::sync::Mutex;
+#[cfg(target_os = "espidf")]
+use std::time::{Duration, Instant};
+
+/// Drop below the running average that counts as a dip
+const DIP_DROP_MV: f32 = 300.0;
+/// A dip ends once the voltage is back within this of the average
+const DIP_RECOVER_MV: f32 = 150.0;
+const BASELINE_ALPHA: f32 = 0.1;
+
+#[cfg(target_os = "espidf")]
+const NVS_NAMESPACE: &str = "power";
+#[cfg(target_os = "espidf")]
+const NVS_KEY_BROWNOUTS: &str = "brownouts";
+#[cfg(target_os = "espidf")]
+const NVS_KEY_DIPS: &str = "dips";
+#[cfg(target_os = "espidf")]
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Spots sudden supply voltage drops against a slow running average
+#[derive(Debug, Default)]
+pub struct DipDetector {
+    baseline_mv: Option<f32>,
+    in_dip: bool,
+}
+
+impl DipDetector {
+    /// Feed one reading in mV (0 = no reading); true when a new dip starts
+    pub fn sample(&mut self, mv: u16) -> bool {
+        if mv == 0 {
+            return false;
+        }
+        let mv = mv as f32;
+        let Some(baseline) = self.baseline_mv else {
+            self.baseline_mv = Some(mv);
+            return false;
+        };
+
+        let drop = baseline - mv;
+        if self.in_dip {
+            // Hold the baseline until the supply recovers
+            if drop > DIP_RECOVER_MV {
+                return false;
+            }
+            self.in_dip = false;
+        } else if drop >= DIP_DROP_MV {
+            self.in_dip = true;
+            return true;
+        }
+        self.baseline_mv = Some(baseline + (mv - baseline) * BASELINE_ALPHA);
+        false
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct PowerEventCounts {
+    /// Brown-out resets over the device lifetime
+    pub brownout_resets: u32,
+    /// Voltage dips over the device lifetime
+    pub voltage_dips: u32,
+    /// This boot was caused by a brown-out
+    pub last_reset_brownout: bool,
+    pub dips_this_boot: u32,
+    /// Lowest supply voltage seen this boot
+    pub min_voltage_mv: Option<u16>,
+}
+
+impl PowerEventCounts {
+    /// Banner text for the Sensor screen; None if this boot has been clean
+    pub fn warning(&self) -> Option<String> {
+        let dips = match self.dips_this_boot {
+            0 => None,
+            1 => Some("1 voltage dip".to_string()),
+            n => Some(format!("{} voltage dips", n)),
+        };
+        match (self.last_reset_brownout, dips) {
+            (false, None) => None,
+            (false, Some(dips)) => Some(dips),
+            (true, None) => Some(format!("Brown-out reset ({} total)", self.brownout_resets)),
+            (true, Some(dips)) => Some(format!("Brown-out reset, {}", dips)),
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
+struct PowerEvents {
+    counts: PowerEventCounts,
+    detector: DipDetector,
+    dirty: bool,
+    last_save: Option<Instant>,
+}
+
+#[cfg(target_os = "espidf")]
+static EVENTS: Mutex<Option<PowerEvents>> = Mutex::new(None);
+
+/// Load the counters and count this boot if it was a brown-out. Call once at startup.
+#[cfg(target_os = "espidf")]
+pub fn init() {
+    let (mut brownouts, dips) = load_counts();
+    let brownout = crate::system::reset::last_reset_was_brownout();
+    if brownout {
+        brownouts += 1;
+        log::warn!("Power: brown-out reset detected ({} total) - check the supply", brownouts);
+        save_counts(brownouts, dips);
+    }
+
+    let counts = PowerEventCounts {
+        brownout_resets: brownouts,
+        voltage_dips: dips,
+        last_reset_brownout: brownout,
+        ..Default::default()
+    };
+    if let Ok(mut slot) = EVENTS.lock() {
+        *slot = Some(PowerEvents { counts, detector: DipDetector::default(), dirty: false, last_save: None });
+    }
+}
+
+/// Feed a supply voltage reading (battery ADC, mV)
+#[cfg(target_os = "espidf")]
+pub fn record_voltage(mv: u16) {
+    let Ok(mut slot) = EVENTS.lock() else { return };
+    let Some(events) = slot.as_mut() else { return };
+
+    if mv > 0 {
+        let min = events.counts.min_voltage_mv.get_or_insert(mv);
+        *min = (*min).min(mv);
+    }
+    if events.detector.sample(mv) {
+        events.counts.voltage_dips += 1;
+        events.counts.dips_this_boot += 1;
+        events.dirty = true;
+        log::warn!("Power: voltage dip to {}mV ({} this boot)", mv, events.counts.dips_this_boot);
+    }
+
+    // A bad supply can dip constantly; keep NVS writes bounded
+    if events.dirty && events.last_save.is_none_or(|t| t.elapsed() >= SAVE_INTERVAL) {
+        save_counts(events.counts.brownout_resets, events.counts.voltage_dips);
+        events.dirty = false;
+        events.last_save = Some(Instant::now());
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub fn counts() -> PowerEventCounts {
+    EVENTS.lock().ok().and_then(|slot| slot.as_ref().map(|e| e.counts)).unwrap_or_default()
+}
+
+#[cfg(target_os = "espidf")]
+fn load_counts() -> (u32, u32) {
+    let Ok(mut store) = kv::open(NVS_NAMESPACE) else { return (0, 0) };
+    let brownouts = store.get(NVS_KEY_BROWNOUTS).unwrap_or(0);
+    let dips = store.get(NVS_KEY_DIPS).unwrap_or(0);
+    (brownouts, dips)
+}
+
+#[cfg(target_os = "espidf")]
+fn save_counts(brownouts: u32, dips: u32) {
+    let result = kv::open(NVS_NAMESPACE).and_then(|mut store| {
+        store.stage(NVS_KEY_BROWNOUTS, &brownouts)?;
+        store.stage(NVS_KEY_DIPS, &dips)?;
+        store.flush()
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to save power event counters: {:?}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dip_detection_with_hysteresis() {
+        let mut d = DipDetector::default();
+        for _ in 0..20 {
+            assert!(!d.sample(4900));
+        }
+        // Slow sag and noise are not dips
+        assert!(!d.sample(4850));
+        assert!(!d.sample(4700));
+        // Sudden drop counts once, staying low does not re-trigger
+        assert!(d.sample(4400));
+        assert!(!d.sample(4300));
+        assert!(!d.sample(4700));
+        // Recovered: the next drop is a new dip
+        assert!(!d.sample(4850));
+        assert!(d.sample(4400));
+        // Missing readings are ignored
+        assert!(!d.sample(0));
+    }
+
+    #[test]
+    fn test_warning_text() {
+        let mut c = PowerEventCounts::default();
+        assert_eq!(c.warning(), None);
+        c.dips_this_boot = 2;
+        assert_eq!(c.warning().as_deref(), Some("2 voltage dips"));
+        c.last_reset_brownout = true;
+        c.brownout_resets = 3;
+        assert_eq!(c.warning().as_deref(), Some("Brown-out reset, 2 voltage dips"));
+        c.dips_this_boot = 0;
+        assert_eq!(c.warning().as_deref(), Some("Brown-out reset (3 total)"));
+    }
+}
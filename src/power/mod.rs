@@ -1,9 +1,17 @@
 // Power management system for ESP32-S3 dashboard
 
 // pub mod voltage_monitor; // removed (unused)
+pub mod adaptive_brightness;
+pub mod backlight;
+pub mod duty_cycle;
+pub mod events;
+pub mod quiet_hours;
+pub mod thermal;
 
 use std::time::{Duration, Instant};
-use esp_idf_hal::gpio::{AnyIOPin, Output, PinDriver};
+use self::backlight::BacklightRamp;
+use self::duty_cycle::{DutyCycleConfig, RadioAction, WifiDutyCycle};
+use crate::ui::animation::EasingFunction;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PowerMode {
@@ -32,8 +40,14 @@ pub struct PowerManager {
     last_activity: Instant,
     config: PowerConfig,
     brightness_level: u8,
-    backlight_pin: Option<PinDriver<'static, AnyIOPin, Output>>,
+    // Configured backlight level (0-255) that brightness_level scales, and
+    // the fade the backlight follows
+    user_brightness: u8,
+    backlight: BacklightRamp,
     force_power_save: bool,
+    brightness_cap: Option<u8>,
+    // Burn-in screensaver: idle time before it replaces the UI, None = off
+    screensaver_after: Option<Duration>,
 }
 
 impl PowerManager {
@@ -43,16 +57,15 @@ impl PowerManager {
             last_activity: Instant::now(),
             config,
             brightness_level: config.active_brightness,
-            backlight_pin: None,
+            user_brightness: 255,
+            // The boot sequence already faded the backlight in
+            backlight: BacklightRamp::new(255),
             force_power_save: false,
+            brightness_cap: None,
+            screensaver_after: None,
         }
     }
     
-    pub fn with_backlight(mut self, backlight_pin: PinDriver<'static, AnyIOPin, Output>) -> Self {
-        self.backlight_pin = Some(backlight_pin);
-        self
-    }
-    
     pub fn activity_detected(&mut self) {
         log::info!("PowerManager: activity_detected called, current_mode = {:?}", self.current_mode);
         self.last_activity = Instant::now();
@@ -74,21 +87,37 @@ impl PowerManager {
             PowerMode::PowerSave => self.config.power_save_brightness,
             PowerMode::Sleep => 0,
         };
-        
-        self.update_backlight();
-    }
-    
-    fn update_backlight(&mut self) {
-        if let Some(ref mut pin) = self.backlight_pin {
-            if self.brightness_level == 0 {
-                // Turn off backlight
-                pin.set_low().ok();
-            } else {
-                // PWM implementation would require LEDC peripheral
-                // Currently using simple on/off control
-                pin.set_high().ok();
-            }
-        }
+        self.apply_brightness_cap();
+    }
+    
+    /// Configured brightness (0-255) and fade settings, from Config
+    pub fn configure_backlight(&mut self, brightness: u8, fade_ms: u32, easing: EasingFunction) {
+        self.user_brightness = brightness;
+        self.backlight.set_fade(Duration::from_millis(fade_ms as u64), easing);
+    }
+    
+    /// Level (0-255) the backlight should be at now; call once per main loop
+    /// pass and hand the result to DisplayManager::set_backlight. Changes of
+    /// `display_on`, the power mode or the brightness fade rather than switch.
+    pub fn update_backlight(&mut self, display_on: bool) -> u8 {
+        let now = Instant::now();
+        let target = if display_on {
+            (self.user_brightness as u32 * self.brightness_level as u32 / 100) as u8
+        } else {
+            0
+        };
+        self.backlight.set_target(target, now);
+        self.backlight.update(now)
+    }
+    
+    /// Current backlight level (0-255), mid-fade included
+    pub fn backlight_level(&self) -> u8 {
+        self.backlight.level_at(Instant::now())
+    }
+    
+    /// Backlight is off and staying off; frames drawn now can't be seen
+    pub fn backlight_dark(&self) -> bool {
+        self.backlight.target() == 0 && !self.backlight.is_fading()
     }
     
     pub fn get_mode(&self) -> PowerMode {
@@ -111,7 +140,7 @@ impl PowerManager {
     pub fn set_brightness(&mut self, brightness: u8) {
         // Manual brightness adjustment
         self.brightness_level = brightness.min(100);
-        self.update_backlight();
+        self.apply_brightness_cap();
         
         // If manually adjusting brightness, ensure we're not in sleep
         if self.current_mode == PowerMode::Sleep && brightness > 0 {
@@ -119,6 +148,30 @@ impl PowerManager {
         }
     }
     
+    /// Limit brightness (e.g. while thermally throttled); None lifts the limit
+    pub fn set_brightness_cap(&mut self, cap: Option<u8>) {
+        self.brightness_cap = cap;
+        // Re-derive the level from the mode so lifting the cap restores it
+        self.set_mode(self.current_mode);
+    }
+    
+    fn apply_brightness_cap(&mut self) {
+        if let Some(cap) = self.brightness_cap {
+            self.brightness_level = self.brightness_level.min(cap);
+        }
+    }
+    
+    /// Show the burn-in screensaver after `after` without input; None disables it
+    pub fn set_screensaver(&mut self, after: Option<Duration>) {
+        self.screensaver_after = after;
+    }
+    
+    /// The screensaver should be drawn instead of the UI
+    pub fn screensaver_active(&self) -> bool {
+        self.current_mode != PowerMode::Sleep
+            && self.screensaver_after.is_some_and(|after| self.last_activity.elapsed() >= after)
+    }
+    
     pub fn get_power_stats(&self) -> PowerStats {
         PowerStats {
             mode: self.current_mode,
@@ -142,6 +195,8 @@ pub struct TaskPowerManager {
     wifi_enabled: bool,
     sensor_polling_rate: Duration,
     display_refresh_rate: Duration,
+    // Battery profile: WiFi duty cycle, None while the profile is off
+    battery: Option<(DutyCycleConfig, WifiDutyCycle)>,
 }
 
 impl TaskPowerManager {
@@ -150,6 +205,56 @@ impl TaskPowerManager {
             wifi_enabled: true,
             sensor_polling_rate: Duration::from_secs(5),
             display_refresh_rate: Duration::from_millis(33),
+            battery: None,
+        }
+    }
+    
+    /// Turn the battery profile on (with its duty cycle) or off. Switching it
+    /// off brings the radio back up if it was parked.
+    pub fn set_battery_profile(&mut self, config: Option<DutyCycleConfig>) {
+        match (config, self.battery.as_mut()) {
+            (Some(config), Some((current, _))) => *current = config,
+            (Some(config), None) => {
+                log::info!("Battery profile enabled: WiFi every {}s for {}s",
+                    config.wake_interval.as_secs(), config.awake_window.as_secs());
+                self.battery = Some((config, WifiDutyCycle::new(Instant::now())));
+            }
+            (None, Some(_)) => {
+                log::info!("Battery profile disabled");
+                self.battery = None;
+            }
+            (None, None) => {}
+        }
+    }
+    
+    /// Advance the WiFi duty cycle and start/stop the radio when a window
+    /// opens or closes
+    pub fn update_wifi(&mut self) {
+        let action = match self.battery.as_mut() {
+            Some((config, cycle)) => cycle.update(Instant::now(), config),
+            None => (!self.wifi_enabled).then_some(RadioAction::Wake),
+        };
+        let Some(action) = action else { return };
+        self.wifi_enabled = action == RadioAction::Wake;
+        duty_cycle::set_radio(self.wifi_enabled);
+    }
+    
+    /// Deep sleep is due: the radio is parked and input has been idle long enough
+    pub fn should_deep_sleep(&self, idle: Duration) -> bool {
+        self.battery.as_ref().is_some_and(|(config, cycle)| cycle.should_deep_sleep(idle, config))
+    }
+    
+    /// Time until the next WiFi window, used as the deep sleep timer
+    pub fn until_next_wifi_window(&self) -> Duration {
+        self.battery.as_ref().map_or(Duration::ZERO, |(_, cycle)| cycle.until_next_wake(Instant::now()))
+    }
+    
+    /// Minimum time between display frames
+    pub fn display_refresh_rate(&self) -> Duration {
+        if self.battery.is_some() {
+            duty_cycle::FRAME_INTERVAL
+        } else {
+            self.display_refresh_rate
         }
     }
     
@@ -0,0 +1,90 @@
+// Scheduled quiet hours: between two local times (once SNTP has synced) the
+// backlight stays off and the buzzer and status LEDs are silenced. Any input
+// lights the display again for the dim timeout. The web override skips the
+// current (or next) quiet period.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use serde::Serialize;
+
+static IN_WINDOW: AtomicBool = AtomicBool::new(false);
+static OVERRIDE: AtomicBool = AtomicBool::new(false);
+
+/// Reported as `quiet_hours` in /api/system
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct QuietStatus {
+    /// Inside the configured window
+    pub scheduled: bool,
+    pub overridden: bool,
+    /// Quiet hours are in effect right now
+    pub active: bool,
+}
+
+/// True if `minute` (minutes since local midnight) falls in `[start, end)`.
+/// Windows may wrap past midnight; `start == end` is an empty window.
+pub fn in_window(minute: u16, start: u16, end: u16) -> bool {
+    if start <= end {
+        minute >= start && minute < end
+    } else {
+        minute >= start || minute < end
+    }
+}
+
+/// Re-evaluate the schedule (`minute` is None until the clock has synced)
+/// and return whether quiet hours are in effect. Leaving the window clears
+/// the override.
+pub fn update(enabled: bool, start: u16, end: u16, minute: Option<u16>) -> bool {
+    let scheduled = enabled && minute.is_some_and(|m| in_window(m, start, end));
+    if !scheduled && IN_WINDOW.load(Ordering::Relaxed) {
+        OVERRIDE.store(false, Ordering::Relaxed);
+    }
+    IN_WINDOW.store(scheduled, Ordering::Relaxed);
+    is_active()
+}
+
+/// Keep the display and notifications on through the current or next quiet period
+pub fn set_override(on: bool) {
+    OVERRIDE.store(on, Ordering::Relaxed);
+}
+
+pub fn is_active() -> bool {
+    IN_WINDOW.load(Ordering::Relaxed) && !OVERRIDE.load(Ordering::Relaxed)
+}
+
+pub fn status() -> QuietStatus {
+    QuietStatus {
+        scheduled: IN_WINDOW.load(Ordering::Relaxed),
+        overridden: OVERRIDE.load(Ordering::Relaxed),
+        active: is_active(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: u16 = 23 * 60;
+    const END: u16 = 7 * 60;
+
+    #[test]
+    fn test_window_wraps_midnight() {
+        assert!(in_window(23 * 60, START, END));
+        assert!(in_window(2 * 60, START, END));
+        assert!(!in_window(7 * 60, START, END));
+        assert!(!in_window(12 * 60, START, END));
+        assert!(in_window(13 * 60, 12 * 60, 14 * 60));
+        assert!(!in_window(13 * 60, 12 * 60, 12 * 60));
+    }
+
+    #[test]
+    fn test_override_lasts_until_window_ends() {
+        assert!(!update(true, START, END, None), "no quiet hours before SNTP sync");
+        assert!(update(true, START, END, Some(23 * 60 + 30)));
+        set_override(true);
+        assert!(!update(true, START, END, Some(1)));
+        assert_eq!(status(), QuietStatus { scheduled: true, overridden: true, active: false });
+        // The morning clears the override for the next night
+        assert!(!update(true, START, END, Some(END)));
+        assert!(update(true, START, END, Some(START)));
+        assert!(!update(false, START, END, Some(START)));
+    }
+}
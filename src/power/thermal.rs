@@ -0,0 +1,110 @@
+// Thermal throttling: once the die temperature passes the configured limit,
+// cap the CPU clock via esp_pm, dim the display and pause non-essential
+// background work (weather polling, mDNS announcements). Everything is restored
+// when the chip has cooled HYSTERESIS_C below the limit.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(target_os = "espidf")]
+use crate::sensors::SensorData;
+
+pub const DEFAULT_LIMIT_C: f32 = 70.0;
+/// Cool-down below the limit needed before throttling is lifted
+pub const HYSTERESIS_C: f32 = 5.0;
+pub const NORMAL_MAX_FREQ_MHZ: i32 = 240;
+pub const THROTTLED_MAX_FREQ_MHZ: i32 = 160;
+pub const MIN_FREQ_MHZ: i32 = 80;
+/// Display brightness cap (percent) while throttled
+pub const THROTTLED_BRIGHTNESS: u8 = 30;
+
+static THROTTLED: AtomicBool = AtomicBool::new(false);
+
+/// Over-temperature state machine with hysteresis
+#[derive(Debug, Default)]
+pub struct ThermalPolicy {
+    throttled: bool,
+}
+
+impl ThermalPolicy {
+    /// Feed a die temperature reading; returns the new throttled state when it changes
+    pub fn update(&mut self, temp_c: f32, limit_c: f32) -> Option<bool> {
+        if !temp_c.is_finite() {
+            return None;
+        }
+        let throttled = if self.throttled {
+            temp_c > limit_c - HYSTERESIS_C
+        } else {
+            temp_c >= limit_c
+        };
+        if throttled == self.throttled {
+            return None;
+        }
+        self.throttled = throttled;
+        Some(throttled)
+    }
+
+    pub fn is_throttled(&self) -> bool {
+        self.throttled
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl ThermalPolicy {
+    /// Update from a sensor sample and apply the CPU clock cap on a state change.
+    /// The caller handles the display and mDNS, which it owns.
+    pub fn observe(&mut self, data: &SensorData, limit_c: f32) -> Option<bool> {
+        let throttled = self.update(data._temperature, limit_c)?;
+        THROTTLED.store(throttled, Ordering::Relaxed);
+        if throttled {
+            log::warn!("Thermal: {:.1}°C >= {:.0}°C limit, throttling", data._temperature, limit_c);
+            configure_cpu(THROTTLED_MAX_FREQ_MHZ);
+        } else {
+            log::info!("Thermal: cooled to {:.1}°C, restoring full performance", data._temperature);
+            configure_cpu(NORMAL_MAX_FREQ_MHZ);
+        }
+        Some(throttled)
+    }
+}
+
+/// True while over temperature; background tasks skip non-essential work
+pub fn is_throttled() -> bool {
+    THROTTLED.load(Ordering::Relaxed)
+}
+
+/// Configure dynamic frequency scaling between MIN_FREQ_MHZ and `max_freq_mhz`
+#[cfg(target_os = "espidf")]
+pub fn configure_cpu(max_freq_mhz: i32) {
+    use esp_idf_sys::*;
+    let pm_config = esp_pm_config_esp32s3_t {
+        max_freq_mhz,
+        min_freq_mhz: MIN_FREQ_MHZ,
+        light_sleep_enable: false, // Keep false for responsiveness
+    };
+    let result = unsafe {
+        esp_pm_configure(&pm_config as *const esp_pm_config_esp32s3_t as *const core::ffi::c_void)
+    };
+    if result == ESP_OK {
+        log::info!("Power management configured: {}-{}MHz DFS", MIN_FREQ_MHZ, max_freq_mhz);
+    } else {
+        log::warn!("Failed to configure power management: {:?}", result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_with_hysteresis() {
+        let mut p = ThermalPolicy::default();
+        assert_eq!(p.update(60.0, 70.0), None);
+        assert_eq!(p.update(70.0, 70.0), Some(true));
+        assert_eq!(p.update(72.0, 70.0), None);
+        // Still inside the hysteresis band
+        assert_eq!(p.update(66.0, 70.0), None);
+        assert!(p.is_throttled());
+        assert_eq!(p.update(65.0, 70.0), Some(false));
+        assert_eq!(p.update(69.0, 70.0), None);
+        // Bad readings never change state
+        assert_eq!(p.update(f32::NAN, 70.0), None);
+    }
+}
@@ -0,0 +1,80 @@
+// Release numbers. Cargo.toml's `version` is the only place one is written:
+// build.rs derives the on-screen DISPLAY_VERSION from it with this module,
+// esp_app_desc! stamps it into the app descriptor of every image, and
+// scripts/ota.sh reads that back to refuse older images. Plain std only, since
+// build.rs compiles this file too.
+
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ReleaseVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl ReleaseVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// Cargo form ("0.6.26") or display form ("v6.26", "6.26"); a pre-release
+    /// or build suffix ("-dirty", "+abc") is ignored
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let s = s.strip_prefix('v').unwrap_or(s);
+        let core = s.split(['-', '+']).next()?;
+        let parts: Vec<u32> = core.split('.').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+        match parts[..] {
+            [major, minor, patch] => Some(Self::new(major, minor, patch)),
+            // Display form of a 0.x release
+            [minor, patch] => Some(Self::new(0, minor, patch)),
+            _ => None,
+        }
+    }
+
+    /// On-screen form: 0.x releases as "v6.26" (Cargo 0.6.26), later ones in full
+    pub fn display(&self) -> String {
+        if self.major == 0 {
+            format!("v{}.{:02}", self.minor, self.patch)
+        } else {
+            format!("v{}", self)
+        }
+    }
+}
+
+/// Cargo form, "0.6.26"
+impl fmt::Display for ReleaseVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cargo_and_display_forms_agree() {
+        let v = ReleaseVersion::parse("0.6.26").unwrap();
+        assert_eq!(v, ReleaseVersion::new(0, 6, 26));
+        assert_eq!(v.display(), "v6.26");
+        assert_eq!(v.to_string(), "0.6.26");
+        assert_eq!(ReleaseVersion::parse(&v.display()), Some(v));
+        assert_eq!(ReleaseVersion::parse("6.26"), Some(v));
+        assert_eq!(ReleaseVersion::parse("0.7.3").unwrap().display(), "v7.03");
+        assert_eq!(ReleaseVersion::parse("1.2.0-rc1").unwrap().display(), "v1.2.0");
+        assert_eq!(ReleaseVersion::parse("v5.2.0+abc"), Some(ReleaseVersion::new(5, 2, 0)));
+        for bad in ["", "v", "6", "0.6.x", "1.2.3.4"] {
+            assert_eq!(ReleaseVersion::parse(bad), None, "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_ordering_is_numeric() {
+        let parse = |s| ReleaseVersion::parse(s).unwrap();
+        assert!(parse("0.6.9") < parse("0.6.10"));
+        assert!(parse("v6.26") < parse("0.7.0"));
+        assert!(parse("0.9.99") < parse("1.0.0"));
+    }
+}
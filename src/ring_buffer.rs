@@ -1,256 +1,279 @@
-// Efficient ring buffer implementation for performance tracking
-use std::time::Duration;
+// Fixed-capacity ring buffer for time series. Samples are plain data, so the
+// storage is one zeroed block that never grows or moves: on the device it is
+// taken from PSRAM (falling back to internal RAM when there is none), which
+// keeps hours of history out of the DRAM the WiFi stack and web server need.
+// The block is allocated on the first push, so an empty buffer costs nothing
+// and `new` can build statics. Readers borrow the samples in place: at most
+// two contiguous slices, oldest first, which exports stream chunk by chunk.
 
-/// Fixed-size ring buffer for storing frame times without allocations
-pub struct RingBuffer<T, const N: usize> {
-    buffer: [T; N],
+use core::ptr::NonNull;
+use std::alloc::{self, Layout};
+
+/// Sample types the buffer can hold: `Copy`, no pointers, and valid when
+/// every byte is zero, so the storage can come from a zeroed allocation.
+///
+/// # Safety
+/// An all-zero bit pattern must be a valid value of the type.
+pub unsafe trait Pod: Copy + Send + Sync + 'static {}
+
+unsafe impl Pod for u8 {}
+unsafe impl Pod for u16 {}
+unsafe impl Pod for u32 {}
+unsafe impl Pod for u64 {}
+unsafe impl Pod for i32 {}
+unsafe impl Pod for f32 {}
+
+pub struct RingBuffer<T: Pod> {
+    /// None until the first push
+    buf: Option<NonNull<T>>,
+    capacity: usize,
+    /// Index of the oldest sample
     head: usize,
     len: usize,
+    in_psram: bool,
 }
 
-impl<T: Default + Copy, const N: usize> RingBuffer<T, N> {
-    pub fn new() -> Self {
+// The buffer owns its block exclusively, like a Vec<T>
+unsafe impl<T: Pod> Send for RingBuffer<T> {}
+unsafe impl<T: Pod> Sync for RingBuffer<T> {}
+
+impl<T: Pod> RingBuffer<T> {
+    /// Empty buffer holding up to `capacity` samples (at least one)
+    pub const fn new(capacity: usize) -> Self {
         Self {
-            buffer: [T::default(); N],
+            buf: None,
+            capacity: if capacity == 0 { 1 } else { capacity },
             head: 0,
             len: 0,
+            in_psram: false,
         }
     }
-    
-    /// Push a new value, overwriting the oldest if full
-    pub fn push(&mut self, value: T) {
-        self.buffer[self.head] = value;
-        self.head = (self.head + 1) % N;
-        if self.len < N {
-            self.len += 1;
-        }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
     }
-    
-    /// Get the number of elements in the buffer
+
     pub fn len(&self) -> usize {
         self.len
     }
-    
-    /// Check if buffer is empty
+
     pub fn is_empty(&self) -> bool {
         self.len == 0
     }
-    
-    /// Get an iterator over the elements (oldest to newest)
-    pub fn iter(&self) -> RingBufferIter<T, N> {
-        RingBufferIter {
-            buffer: &self.buffer,
-            head: self.head,
-            len: self.len,
-            index: 0,
-        }
+
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity
     }
-    
-    /// Get the last N elements (newest)
-    pub fn last_n(&self, n: usize) -> impl Iterator<Item = &T> {
-        let start = if self.len <= n {
-            0
-        } else {
-            self.len - n
+
+    /// Whether the storage was allocated in PSRAM
+    pub fn in_psram(&self) -> bool {
+        self.in_psram
+    }
+
+    /// Append a sample, overwriting the oldest when full
+    pub fn push(&mut self, value: T) {
+        let buf = match self.buf {
+            Some(buf) => buf,
+            None => {
+                let (buf, in_psram) = allocate::<T>(self.capacity);
+                self.buf = Some(buf);
+                self.in_psram = in_psram;
+                buf
+            }
         };
-        
-        self.iter().skip(start)
+        let tail = (self.head + self.len) % self.capacity;
+        // SAFETY: tail < capacity, the size of the block
+        unsafe { buf.as_ptr().add(tail).write(value) };
+        if self.len == self.capacity {
+            self.head = (self.head + 1) % self.capacity;
+        } else {
+            self.len += 1;
+        }
     }
-    
-    /// Clear the buffer
+
+    /// Drop every sample; the storage is kept
     pub fn clear(&mut self) {
         self.head = 0;
         self.len = 0;
     }
-}
-
-/// Iterator for ring buffer
-pub struct RingBufferIter<'a, T, const N: usize> {
-    buffer: &'a [T; N],
-    head: usize,
-    len: usize,
-    index: usize,
-}
 
-impl<'a, T, const N: usize> Iterator for RingBufferIter<'a, T, N> {
-    type Item = &'a T;
-    
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.index >= self.len {
-            return None;
-        }
-        
-        // Calculate actual position in buffer
-        let start = if self.len < N {
-            0
-        } else {
-            self.head
+    /// The samples as two contiguous runs, oldest first; the second is empty
+    /// until the buffer wraps
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let Some(buf) = self.buf else {
+            return (&[], &[]);
         };
-        
-        let pos = (start + self.index) % N;
-        self.index += 1;
-        
-        Some(&self.buffer[pos])
+        // SAFETY: the block holds `capacity` initialized (zeroed or pushed)
+        // values and lives as long as `self`
+        let all = unsafe { core::slice::from_raw_parts(buf.as_ptr(), self.capacity) };
+        let first_len = self.len.min(self.capacity - self.head);
+        (&all[self.head..self.head + first_len], &all[..self.len - first_len])
     }
-}
 
-/// Specialized ring buffer for Duration values with built-in statistics
-pub struct DurationRingBuffer<const N: usize> {
-    buffer: RingBuffer<Duration, N>,
-    sum: Duration,
-    min: Duration,
-    max: Duration,
-}
+    /// Oldest to newest
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        let (first, second) = self.as_slices();
+        first.iter().chain(second)
+    }
 
-impl<const N: usize> DurationRingBuffer<N> {
-    pub fn new() -> Self {
-        Self {
-            buffer: RingBuffer::new(),
-            sum: Duration::ZERO,
-            min: Duration::MAX,
-            max: Duration::ZERO,
-        }
+    /// The samples in borrowed slices of at most `max_len`, oldest first
+    pub fn chunks(&self, max_len: usize) -> impl Iterator<Item = &[T]> + '_ {
+        let (first, second) = self.as_slices();
+        let max_len = max_len.max(1);
+        first.chunks(max_len).chain(second.chunks(max_len))
     }
-    
-    /// Push a new duration and update statistics incrementally
-    pub fn push(&mut self, duration: Duration) {
-        // If buffer is full, subtract the oldest value from sum
-        if self.buffer.len() == N {
-            let oldest_index = if self.buffer.len < N {
-                0
-            } else {
-                self.buffer.head
-            };
-            self.sum = self.sum.saturating_sub(self.buffer.buffer[oldest_index]);
-        }
-        
-        // Add new value
-        self.buffer.push(duration);
-        self.sum = self.sum.saturating_add(duration);
-        
-        // Update min/max
-        if duration < self.min {
-            self.min = duration;
-        }
-        if duration > self.max {
-            self.max = duration;
-        }
-        
-        // Recalculate min/max if needed (when buffer wraps)
-        if self.buffer.len() == N {
-            self.recalculate_min_max();
-        }
+
+    pub fn newest(&self) -> Option<&T> {
+        self.iter().next_back()
     }
-    
-    /// Get average duration
-    pub fn average(&self) -> Option<Duration> {
-        if self.buffer.is_empty() {
-            return None;
+
+    /// Samples from the first one for which `keep` holds, as in `as_slices`.
+    /// `keep` must be false then true along the buffer, e.g. a time cutoff.
+    pub fn slices_from(&self, keep: impl Fn(&T) -> bool) -> (&[T], &[T]) {
+        let (first, second) = self.as_slices();
+        match first.last() {
+            Some(last) if keep(last) => (&first[first.partition_point(|v| !keep(v))..], second),
+            _ => (&[], &second[second.partition_point(|v| !keep(v))..]),
         }
-        
-        Some(self.sum / self.buffer.len() as u32)
     }
-    
-    /// Get average of last N samples
-    pub fn average_last_n(&self, n: usize) -> Option<Duration> {
-        let samples: Vec<Duration> = self.buffer.last_n(n).copied().collect();
-        if samples.is_empty() {
-            return None;
+}
+
+impl<T: Pod> Drop for RingBuffer<T> {
+    fn drop(&mut self) {
+        if let Some(buf) = self.buf.take() {
+            // SAFETY: allocated by `allocate` with this capacity and placement
+            unsafe { deallocate(buf, self.capacity, self.in_psram) };
         }
-        
-        let sum: Duration = samples.iter().sum();
-        Some(sum / samples.len() as u32)
     }
-    
-    /// Get minimum duration
-    pub fn min(&self) -> Option<Duration> {
-        if self.buffer.is_empty() {
-            None
-        } else {
-            Some(self.min)
+}
+
+impl<T: Pod> Clone for RingBuffer<T> {
+    fn clone(&self) -> Self {
+        let mut copy = Self::new(self.capacity);
+        for &value in self.iter() {
+            copy.push(value);
         }
+        copy
     }
-    
-    /// Get maximum duration
-    pub fn max(&self) -> Option<Duration> {
-        if self.buffer.is_empty() {
-            None
-        } else {
-            Some(self.max)
-        }
+}
+
+impl<T: Pod + PartialEq> PartialEq for RingBuffer<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.capacity == other.capacity && self.iter().eq(other.iter())
     }
-    
-    /// Get number of samples
-    pub fn len(&self) -> usize {
-        self.buffer.len()
+}
+
+impl<T: Pod + core::fmt::Debug> core::fmt::Debug for RingBuffer<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RingBuffer")
+            .field("capacity", &self.capacity)
+            .field("samples", &self.iter().collect::<Vec<_>>())
+            .finish()
     }
-    
-    /// Clear all samples
-    pub fn clear(&mut self) {
-        self.buffer.clear();
-        self.sum = Duration::ZERO;
-        self.min = Duration::MAX;
-        self.max = Duration::ZERO;
+}
+
+fn layout<T>(capacity: usize) -> Layout {
+    Layout::array::<T>(capacity).expect("ring buffer capacity overflows")
+}
+
+/// A zeroed block for `capacity` samples and whether it is in PSRAM
+fn allocate<T: Pod>(capacity: usize) -> (NonNull<T>, bool) {
+    let layout = layout::<T>(capacity);
+    if layout.size() == 0 {
+        return (NonNull::dangling(), false);
     }
-    
-    /// Recalculate min/max when buffer wraps
-    fn recalculate_min_max(&mut self) {
-        self.min = Duration::MAX;
-        self.max = Duration::ZERO;
-        
-        for &duration in self.buffer.iter() {
-            if duration < self.min {
-                self.min = duration;
-            }
-            if duration > self.max {
-                self.max = duration;
-            }
+    #[cfg(target_os = "espidf")]
+    {
+        use esp_idf_sys::{heap_caps_aligned_calloc, MALLOC_CAP_8BIT, MALLOC_CAP_SPIRAM};
+        // SAFETY: plain C allocation; null when there is no PSRAM or it is full
+        let ptr = unsafe { heap_caps_aligned_calloc(layout.align(), capacity, core::mem::size_of::<T>(), MALLOC_CAP_SPIRAM | MALLOC_CAP_8BIT) };
+        if let Some(ptr) = NonNull::new(ptr as *mut T) {
+            return (ptr, true);
         }
     }
+    // SAFETY: the layout has a non-zero size
+    let ptr = unsafe { alloc::alloc_zeroed(layout) } as *mut T;
+    match NonNull::new(ptr) {
+        Some(ptr) => (ptr, false),
+        None => alloc::handle_alloc_error(layout),
+    }
+}
+
+/// # Safety
+/// `buf` must come from `allocate::<T>(capacity)`, which returned `in_psram`
+unsafe fn deallocate<T>(buf: NonNull<T>, capacity: usize, in_psram: bool) {
+    let layout = layout::<T>(capacity);
+    if layout.size() == 0 {
+        return;
+    }
+    if in_psram {
+        #[cfg(target_os = "espidf")]
+        esp_idf_sys::heap_caps_free(buf.as_ptr() as *mut core::ffi::c_void);
+        return;
+    }
+    alloc::dealloc(buf.as_ptr() as *mut u8, layout);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+    use std::sync::{Arc, Mutex};
+
     #[test]
-    fn test_ring_buffer_basic() {
-        let mut buffer: RingBuffer<u32, 3> = RingBuffer::new();
-        
-        assert_eq!(buffer.len(), 0);
-        
-        buffer.push(1);
-        buffer.push(2);
-        buffer.push(3);
-        
-        assert_eq!(buffer.len(), 3);
-        
-        let values: Vec<u32> = buffer.iter().copied().collect();
-        assert_eq!(values, vec![1, 2, 3]);
-        
-        // Push one more, should overwrite oldest
-        buffer.push(4);
-        
-        let values: Vec<u32> = buffer.iter().copied().collect();
-        assert_eq!(values, vec![2, 3, 4]);
+    fn test_wrap_around_keeps_newest_in_order() {
+        let mut ring = RingBuffer::<u32>::new(4);
+        assert_eq!(ring.as_slices(), (&[][..], &[][..]));
+        assert_eq!(ring.newest(), None);
+
+        for v in 1..=3 {
+            ring.push(v);
+        }
+        assert_eq!(ring.as_slices(), (&[1, 2, 3][..], &[][..]));
+
+        for v in 4..=10 {
+            ring.push(v);
+        }
+        assert!(ring.is_full());
+        assert_eq!(ring.iter().copied().collect::<Vec<_>>(), [7, 8, 9, 10]);
+        assert_eq!(ring.as_slices(), (&[7, 8][..], &[9, 10][..]));
+        assert_eq!(ring.newest(), Some(&10));
+        assert_eq!(ring.chunks(3).map(<[u32]>::to_vec).collect::<Vec<_>>(), [vec![7, 8], vec![9, 10]]);
+        assert_eq!(ring.slices_from(|&v| v >= 9), (&[][..], &[9, 10][..]));
+        assert_eq!(ring.slices_from(|&v| v >= 8), (&[8][..], &[9, 10][..]));
+        assert_eq!(ring.clone(), ring);
+
+        ring.clear();
+        assert!(ring.is_empty());
+        ring.push(11);
+        assert_eq!(ring.iter().copied().collect::<Vec<_>>(), [11]);
+        assert!(!ring.in_psram(), "no PSRAM on the host");
     }
-    
+
     #[test]
-    fn test_duration_ring_buffer() {
-        let mut buffer: DurationRingBuffer<3> = DurationRingBuffer::new();
-        
-        buffer.push(Duration::from_millis(10));
-        buffer.push(Duration::from_millis(20));
-        buffer.push(Duration::from_millis(30));
-        
-        assert_eq!(buffer.average(), Some(Duration::from_millis(20)));
-        assert_eq!(buffer.min(), Some(Duration::from_millis(10)));
-        assert_eq!(buffer.max(), Some(Duration::from_millis(30)));
-        
-        // Overwrite oldest
-        buffer.push(Duration::from_millis(5));
-        
-        assert_eq!(buffer.average(), Some(Duration::from_millis(18))); // (20+30+5)/3
-        assert_eq!(buffer.min(), Some(Duration::from_millis(5)));
+    fn test_concurrent_push_and_iterate() {
+        const CAPACITY: usize = 64;
+        let ring = Arc::new(Mutex::new(RingBuffer::<u64>::new(CAPACITY)));
+
+        let writer = {
+            let ring = ring.clone();
+            std::thread::spawn(move || {
+                for v in 0..20_000u64 {
+                    ring.lock().unwrap().push(v);
+                }
+            })
+        };
+        // Every view a reader gets is a run of consecutive values ending at
+        // the newest, however the writer interleaves
+        let mut views = 0;
+        while !writer.is_finished() || views == 0 {
+            let ring = ring.lock().unwrap();
+            let values: Vec<u64> = ring.chunks(7).flatten().copied().collect();
+            assert!(values.len() <= CAPACITY);
+            assert!(values.windows(2).all(|w| w[1] == w[0] + 1), "{:?}", values);
+            assert_eq!(values.last(), ring.newest());
+            views += 1;
+        }
+        writer.join().unwrap();
+        assert_eq!(ring.lock().unwrap().iter().copied().collect::<Vec<_>>(), (20_000 - CAPACITY as u64..20_000).collect::<Vec<_>>());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,134 @@
+// Battery ADC calibration. The ESP32-S3's ADC gain and offset vary from
+// chip to chip by several percent; Espressif measures each chip at the
+// factory and burns the curve-fitting coefficients into eFuse. When they are
+// present readings go through the ESP-IDF calibration driver, otherwise the
+// old linear estimate (0-3100 mV over the 12-bit range at 11 dB) is used.
+// The scheme in use is reported by /api/system.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Battery voltage is halved by a 100k/100k divider before GPIO4
+pub const DIVIDER_RATIO: u16 = 2;
+
+/// Pin voltage at full scale for the linear estimate
+const LINEAR_FULL_SCALE_MV: u32 = 3100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalibrationScheme {
+    /// Per-chip curve from eFuse
+    CurveFitting,
+    /// No eFuse data (or the driver failed); fixed linear scale
+    LinearEstimate,
+}
+
+impl CalibrationScheme {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CurveFitting => "curve_fitting",
+            Self::LinearEstimate => "linear_estimate",
+        }
+    }
+}
+
+static SCHEME: AtomicU8 = AtomicU8::new(0);
+
+/// Scheme the battery ADC readings are converted with
+pub fn scheme() -> CalibrationScheme {
+    match SCHEME.load(Ordering::Relaxed) {
+        1 => CalibrationScheme::CurveFitting,
+        _ => CalibrationScheme::LinearEstimate,
+    }
+}
+
+/// Uncalibrated pin voltage for a 12-bit reading at 11 dB attenuation
+pub fn linear_pin_millivolts(raw: u16) -> u16 {
+    (raw.min(4095) as u32 * LINEAR_FULL_SCALE_MV / 4095) as u16
+}
+
+/// Battery voltage for a pin voltage
+pub fn battery_millivolts(pin_mv: u16) -> u16 {
+    pin_mv.saturating_mul(DIVIDER_RATIO)
+}
+
+#[cfg(target_os = "espidf")]
+mod device {
+    use super::*;
+    use esp_idf_sys::*;
+
+    fn set_scheme(scheme: CalibrationScheme) {
+        let value = match scheme {
+            CalibrationScheme::CurveFitting => 1,
+            CalibrationScheme::LinearEstimate => 0,
+        };
+        SCHEME.store(value, Ordering::Relaxed);
+    }
+
+    /// Converts raw ADC1 channel 3 (GPIO4) readings to millivolts
+    pub struct AdcCalibrator {
+        handle: Option<adc_cali_handle_t>,
+    }
+
+    impl AdcCalibrator {
+        /// Uses the eFuse curve when the chip has one
+        pub fn new() -> Self {
+            let config = adc_cali_curve_fitting_config_t {
+                unit_id: adc_unit_t_ADC_UNIT_1,
+                chan: adc_channel_t_ADC_CHANNEL_3,
+                atten: adc_atten_t_ADC_ATTEN_DB_11,
+                bitwidth: adc_bitwidth_t_ADC_BITWIDTH_12,
+            };
+            let mut handle: adc_cali_handle_t = std::ptr::null_mut();
+            let ret = unsafe { adc_cali_create_scheme_curve_fitting(&config, &mut handle) };
+            let handle = if ret == ESP_OK {
+                log::info!("ADC calibration: curve fitting from eFuse");
+                set_scheme(CalibrationScheme::CurveFitting);
+                Some(handle)
+            } else {
+                // ESP_ERR_NOT_SUPPORTED when the eFuse holds no calibration data
+                log::warn!("ADC calibration unavailable ({}), using linear estimate", ret);
+                set_scheme(CalibrationScheme::LinearEstimate);
+                None
+            };
+            Self { handle }
+        }
+
+        /// Pin voltage for a raw reading
+        pub fn pin_millivolts(&self, raw: u16) -> u16 {
+            if let Some(handle) = self.handle {
+                let mut mv: i32 = 0;
+                if unsafe { adc_cali_raw_to_voltage(handle, raw as i32, &mut mv) } == ESP_OK {
+                    return mv.clamp(0, u16::MAX as i32) as u16;
+                }
+            }
+            linear_pin_millivolts(raw)
+        }
+    }
+
+    impl Drop for AdcCalibrator {
+        fn drop(&mut self) {
+            if let Some(handle) = self.handle.take() {
+                unsafe { adc_cali_delete_scheme_curve_fitting(handle) };
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub use device::AdcCalibrator;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_fallback() {
+        assert_eq!(linear_pin_millivolts(0), 0);
+        assert_eq!(linear_pin_millivolts(4095), 3100);
+        assert_eq!(linear_pin_millivolts(2048), 1550);
+        // Out-of-range readings clamp to full scale
+        assert_eq!(linear_pin_millivolts(u16::MAX), 3100);
+        assert_eq!(battery_millivolts(2100), 4200);
+        assert_eq!(scheme(), CalibrationScheme::LinearEstimate);
+        assert_eq!(scheme().as_str(), "linear_estimate");
+    }
+}
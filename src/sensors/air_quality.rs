@@ -0,0 +1,307 @@
+// Air quality sensors on their own I2C bus (Config `air_quality_sda` /
+// `air_quality_scl`): a Sensirion SCD40 (true CO2 by photoacoustic NDIR, plus
+// temperature and humidity) and/or an SGP30 (TVOC and an eCO2 estimate derived
+// from it). Whichever answer at startup are added to the sensor registry, which
+// takes care of the Sensor screen line, /metrics and history; the Air Quality
+// screen reads the same keys. Both chips frame every 16-bit word with a CRC-8,
+// decoded here so it can be tested on the host.
+
+use std::time::Duration;
+
+pub const SCD40_ADDRESS: u8 = 0x62;
+pub const SGP30_ADDRESS: u8 = 0x58;
+
+/// The SCD40 has a new measurement every 5 s in periodic mode
+pub const SCD40_INTERVAL: Duration = Duration::from_secs(5);
+/// The SGP30's baseline algorithm expects a measurement every second
+pub const SGP30_INTERVAL: Duration = Duration::from_secs(1);
+/// The SGP30 reports fixed 400 ppm / 0 ppb while it warms up
+pub const SGP30_WARM_UP: Duration = Duration::from_secs(15);
+
+/// Registry keys the Air Quality screen shows
+pub const CO2_KEY: &str = "scd40.co2";
+pub const ECO2_KEY: &str = "sgp30.eco2";
+pub const TVOC_KEY: &str = "sgp30.tvoc";
+pub const TEMPERATURE_KEY: &str = "scd40.temperature";
+pub const HUMIDITY_KEY: &str = "scd40.humidity";
+
+/// Sensirion CRC-8 (polynomial 0x31, init 0xFF) of one word
+pub fn crc8(bytes: &[u8]) -> u8 {
+    let mut crc = 0xFFu8;
+    for &byte in bytes {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x31 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+/// Split a response into its words; None when a CRC doesn't match
+pub fn words<const N: usize>(response: &[u8]) -> Option<[u16; N]> {
+    if response.len() != N * 3 {
+        return None;
+    }
+    let mut out = [0u16; N];
+    for (word, chunk) in out.iter_mut().zip(response.chunks_exact(3)) {
+        if crc8(&chunk[..2]) != chunk[2] {
+            return None;
+        }
+        *word = u16::from_be_bytes([chunk[0], chunk[1]]);
+    }
+    Some(out)
+}
+
+/// SCD40 `read_measurement` result
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scd40Measurement {
+    pub co2_ppm: u16,
+    pub temperature_c: f32,
+    pub humidity_percent: f32,
+}
+
+impl Scd40Measurement {
+    pub fn decode(response: &[u8; 9]) -> Option<Self> {
+        let [co2, temperature, humidity] = words::<3>(response)?;
+        Some(Self {
+            co2_ppm: co2,
+            temperature_c: -45.0 + 175.0 * temperature as f32 / 65535.0,
+            humidity_percent: 100.0 * humidity as f32 / 65535.0,
+        })
+    }
+}
+
+/// SGP30 `measure_air_quality` result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sgp30Measurement {
+    pub eco2_ppm: u16,
+    pub tvoc_ppb: u16,
+}
+
+impl Sgp30Measurement {
+    pub fn decode(response: &[u8; 6]) -> Option<Self> {
+        let [eco2, tvoc] = words::<2>(response)?;
+        Some(Self { eco2_ppm: eco2, tvoc_ppb: tvoc })
+    }
+}
+
+/// Indoor air quality band, from the usual ventilation guidance for CO2 and
+/// the German UBA levels for TVOC
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AirQuality {
+    Good,
+    Moderate,
+    Poor,
+    Bad,
+}
+
+impl AirQuality {
+    pub fn from_co2(ppm: f32) -> Self {
+        match ppm {
+            p if p < 800.0 => Self::Good,
+            p if p < 1200.0 => Self::Moderate,
+            p if p < 2000.0 => Self::Poor,
+            _ => Self::Bad,
+        }
+    }
+
+    pub fn from_tvoc(ppb: f32) -> Self {
+        match ppb {
+            p if p < 220.0 => Self::Good,
+            p if p < 660.0 => Self::Moderate,
+            p if p < 2200.0 => Self::Poor,
+            _ => Self::Bad,
+        }
+    }
+
+    /// i18n key of the band's label
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Self::Good => "aq.good",
+            Self::Moderate => "aq.moderate",
+            Self::Poor => "aq.poor",
+            Self::Bad => "aq.bad",
+        }
+    }
+}
+
+#[cfg(target_os = "espidf")]
+mod device {
+    use super::*;
+    use anyhow::{anyhow, Result};
+    use esp_idf_hal::delay::FreeRtos;
+    use std::time::Instant;
+    use crate::hardware::i2c::Bus;
+    use crate::sensors::registry::{self, Reading, Readings, Sensor};
+
+    const I2C_PORT: esp_idf_sys::i2c_port_t = 1;
+    const I2C_CLOCK_HZ: u32 = 100_000;
+
+    /// Send a 16-bit command, wait for it to execute, then read the response
+    fn command(bus: &Bus, address: u8, cmd: u16, wait_ms: u32, out: &mut [u8]) -> Result<()> {
+        bus.write(address, &cmd.to_be_bytes())?;
+        FreeRtos::delay_ms(wait_ms);
+        if !out.is_empty() {
+            bus.read(address, out)?;
+        }
+        Ok(())
+    }
+
+    struct Scd40 {
+        bus: Bus,
+    }
+
+    impl Scd40 {
+        const STOP_PERIODIC: u16 = 0x3F86;
+        const START_PERIODIC: u16 = 0x21B1;
+        const DATA_READY: u16 = 0xE4B8;
+        const READ_MEASUREMENT: u16 = 0xEC05;
+        const SERIAL_NUMBER: u16 = 0x3682;
+
+        fn probe(bus: Bus) -> Result<Self> {
+            // A warm restart leaves it measuring, and it ignores most commands then
+            command(&bus, SCD40_ADDRESS, Self::STOP_PERIODIC, 500, &mut [])?;
+            let mut serial = [0u8; 9];
+            command(&bus, SCD40_ADDRESS, Self::SERIAL_NUMBER, 1, &mut serial)?;
+            words::<3>(&serial).ok_or_else(|| anyhow!("SCD40 serial CRC mismatch"))?;
+            command(&bus, SCD40_ADDRESS, Self::START_PERIODIC, 1, &mut [])?;
+            Ok(Self { bus })
+        }
+    }
+
+    impl Sensor for Scd40 {
+        fn id(&self) -> &'static str { "scd40" }
+
+        fn poll_interval(&self) -> Duration { SCD40_INTERVAL }
+
+        fn read(&mut self) -> Result<Readings> {
+            let mut status = [0u8; 3];
+            command(&self.bus, SCD40_ADDRESS, Self::DATA_READY, 1, &mut status)?;
+            let [status] = words::<1>(&status).ok_or_else(|| anyhow!("SCD40 status CRC mismatch"))?;
+            if status & 0x07FF == 0 {
+                return Ok(Vec::new());
+            }
+            let mut response = [0u8; 9];
+            command(&self.bus, SCD40_ADDRESS, Self::READ_MEASUREMENT, 1, &mut response)?;
+            let m = Scd40Measurement::decode(&response).ok_or_else(|| anyhow!("SCD40 measurement CRC mismatch"))?;
+            Ok(vec![
+                Reading::new("co2", m.co2_ppm as f32, "ppm").with_decimals(0),
+                Reading::new("temperature", m.temperature_c, "C"),
+                Reading::new("humidity", m.humidity_percent, "%").with_decimals(0),
+            ])
+        }
+    }
+
+    struct Sgp30 {
+        bus: Bus,
+        started: Instant,
+    }
+
+    impl Sgp30 {
+        const INIT_AIR_QUALITY: u16 = 0x2003;
+        const MEASURE_AIR_QUALITY: u16 = 0x2008;
+        const SERIAL_ID: u16 = 0x3682;
+
+        fn probe(bus: Bus) -> Result<Self> {
+            let mut serial = [0u8; 9];
+            command(&bus, SGP30_ADDRESS, Self::SERIAL_ID, 1, &mut serial)?;
+            words::<3>(&serial).ok_or_else(|| anyhow!("SGP30 serial CRC mismatch"))?;
+            command(&bus, SGP30_ADDRESS, Self::INIT_AIR_QUALITY, 10, &mut [])?;
+            Ok(Self { bus, started: Instant::now() })
+        }
+    }
+
+    impl Sensor for Sgp30 {
+        fn id(&self) -> &'static str { "sgp30" }
+
+        fn poll_interval(&self) -> Duration { SGP30_INTERVAL }
+
+        fn read(&mut self) -> Result<Readings> {
+            let mut response = [0u8; 6];
+            command(&self.bus, SGP30_ADDRESS, Self::MEASURE_AIR_QUALITY, 12, &mut response)?;
+            let m = Sgp30Measurement::decode(&response).ok_or_else(|| anyhow!("SGP30 measurement CRC mismatch"))?;
+            // Measured anyway: the baseline needs the steady 1 s cadence
+            if self.started.elapsed() < SGP30_WARM_UP {
+                return Ok(Vec::new());
+            }
+            Ok(vec![
+                Reading::new("eco2", m.eco2_ppm as f32, "ppm").with_decimals(0),
+                Reading::new("tvoc", m.tvoc_ppb as f32, "ppb").with_decimals(0),
+            ])
+        }
+    }
+
+    /// Install the bus on `sda`/`scl` and register whichever sensors answer.
+    /// Call once at startup.
+    pub fn init(sda: u8, scl: u8) -> Result<()> {
+        let bus = Bus::install(I2C_PORT, sda, scl, I2C_CLOCK_HZ)?;
+        let mut found = 0;
+        match Scd40::probe(bus) {
+            Ok(scd40) => {
+                registry::register(Box::new(scd40))?;
+                found += 1;
+            }
+            Err(e) => log::info!("No SCD40 on SDA GPIO{} / SCL GPIO{}: {:?}", sda, scl, e),
+        }
+        match Sgp30::probe(bus) {
+            Ok(sgp30) => {
+                registry::register(Box::new(sgp30))?;
+                found += 1;
+            }
+            Err(e) => log::info!("No SGP30 on SDA GPIO{} / SCL GPIO{}: {:?}", sda, scl, e),
+        }
+        if found == 0 {
+            bus.uninstall();
+            return Err(anyhow!("No air quality sensor answered"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub use device::init;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(words: &[u16]) -> Vec<u8> {
+        words.iter().flat_map(|w| {
+            let [hi, lo] = w.to_be_bytes();
+            [hi, lo, crc8(&[hi, lo])]
+        }).collect()
+    }
+
+    #[test]
+    fn test_crc_matches_datasheet() {
+        assert_eq!(crc8(&[0xBE, 0xEF]), 0x92);
+    }
+
+    #[test]
+    fn test_decode_measurements() {
+        // Datasheet example: 500 ppm, 25 °C, 37 %RH
+        let response: [u8; 9] = frame(&[0x01F4, 0x6667, 0x5EB9]).try_into().unwrap();
+        let m = Scd40Measurement::decode(&response).unwrap();
+        assert_eq!(m.co2_ppm, 500);
+        assert!((m.temperature_c - 25.0).abs() < 0.01);
+        assert!((m.humidity_percent - 37.0).abs() < 0.01);
+
+        let mut corrupt = response;
+        corrupt[4] ^= 0x01;
+        assert_eq!(Scd40Measurement::decode(&corrupt), None);
+
+        let response: [u8; 6] = frame(&[612, 87]).try_into().unwrap();
+        assert_eq!(Sgp30Measurement::decode(&response), Some(Sgp30Measurement { eco2_ppm: 612, tvoc_ppb: 87 }));
+        assert_eq!(words::<2>(&response[..5]), None);
+    }
+
+    #[test]
+    fn test_quality_bands() {
+        assert_eq!(AirQuality::from_co2(450.0), AirQuality::Good);
+        assert_eq!(AirQuality::from_co2(800.0), AirQuality::Moderate);
+        assert_eq!(AirQuality::from_co2(1500.0), AirQuality::Poor);
+        assert_eq!(AirQuality::from_co2(2400.0), AirQuality::Bad);
+        assert_eq!(AirQuality::from_tvoc(100.0), AirQuality::Good);
+        assert_eq!(AirQuality::from_tvoc(3000.0), AirQuality::Bad);
+    }
+}
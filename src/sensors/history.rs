@@ -1,5 +1,6 @@
-use std::collections::VecDeque;
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex, OnceLock};
+use crate::ring_buffer::{Pod, RingBuffer};
 
 const MAX_HISTORY_POINTS: usize = 360; // 6 hours at 1 sample/minute for memory efficiency
 
@@ -16,80 +17,131 @@ pub fn get() -> Option<Arc<Mutex<SensorHistory>>> {
     SENSOR_HISTORY.get().cloned()
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
 pub struct DataPoint {
     pub timestamp: u64,
     pub value: f32,
 }
 
+// SAFETY: two numbers; all zeroes is the epoch and 0.0
+unsafe impl Pod for DataPoint {}
+
+/// Points of one series, borrowed from its ring buffer and oldest first.
+/// Serializes as a JSON array straight from the buffer, so an export never
+/// copies the series.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HistoryPoints<'a> {
+    runs: (&'a [DataPoint], &'a [DataPoint]),
+}
+
+impl<'a> HistoryPoints<'a> {
+    pub fn len(&self) -> usize {
+        self.runs.0.len() + self.runs.1.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'a DataPoint> + 'a {
+        self.runs.0.iter().chain(self.runs.1)
+    }
+}
+
+impl serde::Serialize for HistoryPoints<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Series keyed by name: `temperature` and `battery` from the board, and
+/// `<sensor id>.<quantity>` from the sensor registry. Each series is a
+/// MAX_HISTORY_POINTS ring buffer, in PSRAM on the device.
 pub struct SensorHistory {
-    temperature: Mutex<VecDeque<DataPoint>>,
-    battery: Mutex<VecDeque<DataPoint>>,
+    series: Mutex<BTreeMap<String, RingBuffer<DataPoint>>>,
+}
+
+impl Default for SensorHistory {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SensorHistory {
     pub fn new() -> Self {
         Self {
-            temperature: Mutex::new(VecDeque::with_capacity(MAX_HISTORY_POINTS)),
-            battery: Mutex::new(VecDeque::with_capacity(MAX_HISTORY_POINTS)),
+            series: Mutex::new(BTreeMap::new()),
         }
     }
 
     pub fn add_temperature(&self, value: f32) {
-        self.add_data_point(&self.temperature, value);
+        self.add("temperature", value);
     }
 
     pub fn add_battery(&self, value: f32) {
-        self.add_data_point(&self.battery, value);
+        self.add("battery", value);
     }
 
-    fn add_data_point(&self, queue: &Mutex<VecDeque<DataPoint>>, value: f32) {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+    pub fn add(&self, key: &str, value: f32) {
+        let timestamp = now_secs();
 
-        let mut data = match queue.lock() {
+        let mut series = match self.series.lock() {
             Ok(g) => g,
             Err(e) => {
-                log::error!("SensorHistory lock failed in add_data_point: {}", e);
+                log::error!("SensorHistory lock failed in add: {}", e);
                 return;
             }
         };
-        data.push_back(DataPoint { timestamp, value });
-
-        // Remove old data points
-        while data.len() > MAX_HISTORY_POINTS {
-            data.pop_front();
-        }
+        series.entry(key.to_string())
+            .or_insert_with(|| RingBuffer::new(MAX_HISTORY_POINTS))
+            .push(DataPoint { timestamp, value });
     }
 
-    pub fn get_temperature_history(&self, hours: u32) -> Vec<DataPoint> {
-        self.get_history(&self.temperature, hours)
+    /// Series names with at least one point
+    pub fn keys(&self) -> Vec<String> {
+        self.series.lock().map(|series| series.keys().cloned().collect()).unwrap_or_default()
     }
 
-    pub fn get_battery_history(&self, hours: u32) -> Vec<DataPoint> {
-        self.get_history(&self.battery, hours)
+    pub fn get_history(&self, key: &str, hours: u32) -> Vec<DataPoint> {
+        self.with_history(key, hours, |points| points.iter().copied().collect())
     }
 
-    fn get_history(&self, queue: &Mutex<VecDeque<DataPoint>>, hours: u32) -> Vec<DataPoint> {
-        let cutoff = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs()
-            .saturating_sub(hours as u64 * 3600);
+    /// Run `f` on the last `hours` of `key` without copying them; the series
+    /// is locked meanwhile. Unknown keys give no points.
+    pub fn with_history<R>(&self, key: &str, hours: u32, f: impl FnOnce(HistoryPoints<'_>) -> R) -> R {
+        let cutoff = now_secs().saturating_sub(hours as u64 * 3600);
+        let series = match self.series.lock() {
+            Ok(g) => g,
+            Err(e) => {
+                log::error!("SensorHistory lock failed in with_history: {}", e);
+                return f(HistoryPoints::default());
+            }
+        };
+        let runs = series.get(key)
+            .map(|data| data.slices_from(|dp| dp.timestamp >= cutoff))
+            .unwrap_or_default();
+        f(HistoryPoints { runs })
+    }
 
-        let data = match queue.lock() {
+    /// Points of `key` with `from <= timestamp <= to` (Unix seconds)
+    pub fn range(&self, key: &str, from: u64, to: u64) -> Vec<DataPoint> {
+        let series = match self.series.lock() {
             Ok(g) => g,
             Err(e) => {
-                log::error!("SensorHistory lock failed in get_history: {}", e);
+                log::error!("SensorHistory lock failed in range: {}", e);
                 return Vec::new();
             }
         };
-        data.iter()
-            .filter(|dp| dp.timestamp >= cutoff)
-            .cloned()
-            .collect()
+        series.get(key)
+            .map(|data| data.iter().filter(|dp| (from..=to).contains(&dp.timestamp)).copied().collect())
+            .unwrap_or_default()
     }
 
 }
\ No newline at end of file
@@ -1,12 +1,28 @@
 // Sensor abstraction layer for ESP32-S3 dashboard
 
+pub mod adc_cal;
+pub mod air_quality;
 pub mod history;
+pub mod power_state;
+pub mod registry;
+#[cfg(feature = "demo_mode")]
+pub mod simulator;
+#[cfg(feature = "demo_mode")]
+pub use simulator::SimulatedSensors as SensorManager;
 
+#[cfg(not(feature = "demo_mode"))]
 use anyhow::Result;
+#[cfg(not(feature = "demo_mode"))]
 use esp_idf_hal::gpio::Gpio4;
+#[cfg(not(feature = "demo_mode"))]
 use esp_idf_hal::adc::ADC1;
+#[cfg(not(feature = "demo_mode"))]
+use power_state::{PowerStateFilter, SAMPLES_PER_READ};
+#[cfg(not(feature = "demo_mode"))]
+use adc_cal::AdcCalibrator;
 
 // Battery monitoring helper functions
+#[cfg(not(feature = "demo_mode"))]
 fn voltage_to_percentage(voltage: u16) -> u8 {
     // Convert millivolts to volts for calculation
     let voltage_v = voltage as f32 / 1000.0;
@@ -15,18 +31,6 @@ fn voltage_to_percentage(voltage: u16) -> u8 {
     percentage as u8
 }
 
-fn is_battery_connected(_adc_raw: u16, voltage: u16) -> bool {
-    voltage > 2500 // Battery connected if voltage > 2.5V (in millivolts)
-}
-
-fn is_on_usb_power(voltage: u16, battery_connected: bool) -> bool {
-    voltage > 4500 || !battery_connected // > 4.5V indicates USB power
-}
-
-fn is_charging(voltage: u16, battery_connected: bool) -> bool {
-    battery_connected && voltage > 4000 // > 4.0V and battery connected = charging
-}
-
 // Sensor data struct for UI consumption
 #[derive(Debug, Clone)]
 pub struct SensorData {
@@ -52,6 +56,7 @@ impl Default for SensorData {
 }
 
 // Sensor manager for coordinating multiple sensors
+#[cfg(not(feature = "demo_mode"))]
 pub struct SensorManager {
     temp_sensor_handle: Option<esp_idf_sys::temperature_sensor_handle_t>,
     last_battery_voltage: u16,
@@ -60,8 +65,12 @@ pub struct SensorManager {
     battery_pin: u8,
     // Stability: avoid global mutable counters
     sample_count: u32,
+    // Debounces the USB/charging guesses made from the battery voltage
+    power_filter: PowerStateFilter,
+    adc_cal: AdcCalibrator,
 }
 
+#[cfg(not(feature = "demo_mode"))]
 impl SensorManager {
     pub fn new(_adc1: ADC1, _battery_pin: Gpio4) -> Result<Self> {
         // For now, we'll use direct ADC register access for battery monitoring
@@ -108,10 +117,11 @@ impl SensorManager {
         
         // Initialize ADC for battery monitoring using direct register access
         Self::init_adc_direct();
+        let adc_cal = AdcCalibrator::new();
         
         // Read initial battery voltage
         let initial_raw = Self::read_adc_direct(4); // GPIO4 is ADC1 channel 3
-        let initial_voltage = Self::adc_to_millivolts(initial_raw);
+        let initial_voltage = adc_cal::battery_millivolts(adc_cal.pin_millivolts(initial_raw));
         log::info!("Initial battery reading: {} raw, {} mV", initial_raw, initial_voltage);
         
         Ok(Self {
@@ -120,6 +130,8 @@ impl SensorManager {
             last_adc_raw: initial_raw,
             battery_pin: 4, // GPIO4
             sample_count: 0,
+            power_filter: PowerStateFilter::new(),
+            adc_cal,
         })
     }
     
@@ -165,20 +177,10 @@ impl SensorManager {
         }
     }
     
-    // Convert ADC reading to millivolts
-    // ESP32-S3 ADC with 11dB attenuation: 0-3100mV range
-    fn adc_to_millivolts(adc_value: u16) -> u16 {
-        // T-Display-S3 has a voltage divider on the battery pin (GPIO4)
-        // The divider is 100k + 100k, so we measure half the battery voltage
-        // With 11dB attenuation, the ADC range is approximately 0-3100mV
-        // ADC resolution is 12-bit (0-4095)
-        
-        // First convert ADC to measured voltage
-        let measured_mv = ((adc_value as u32 * 3100) / 4095) as u16;
-        
-        // Then double it to get actual battery voltage (due to 1:1 voltage divider)
-        let battery_mv = measured_mv * 2;
-        battery_mv
+    // Convert ADC reading to battery millivolts (calibrated pin voltage
+    // times the 1:1 divider on GPIO4)
+    fn adc_to_millivolts(&self, adc_value: u16) -> u16 {
+        adc_cal::battery_millivolts(self.adc_cal.pin_millivolts(adc_value))
     }
     
     // Update battery voltage from external ADC reading
@@ -186,9 +188,14 @@ impl SensorManager {
         // Read internal temperature sensor
         let temperature = self.read_internal_temperature();
         
-        // Read battery voltage from ADC using direct register access
-        let adc_raw = Self::read_adc_direct(self.battery_pin);
-        let battery_voltage = Self::adc_to_millivolts(adc_raw);
+        // Read battery voltage from ADC using direct register access; the
+        // median of a few samples drops single noisy conversions
+        let mut samples = [0u16; SAMPLES_PER_READ];
+        for sample in samples.iter_mut() {
+            *sample = Self::read_adc_direct(self.battery_pin);
+        }
+        let adc_raw = power_state::median(&mut samples);
+        let battery_voltage = self.adc_to_millivolts(adc_raw);
         
         // Update stored values
         self.last_adc_raw = adc_raw;
@@ -196,9 +203,8 @@ impl SensorManager {
         
         // Calculate battery metrics
         let battery_percentage = voltage_to_percentage(battery_voltage);
-        let battery_connected = is_battery_connected(adc_raw, battery_voltage);
-        let is_on_usb = is_on_usb_power(battery_voltage, battery_connected);
-        let is_charging = is_charging(battery_voltage, battery_connected);
+        let power = self.power_filter.update(battery_voltage, std::time::Instant::now());
+        let (is_on_usb, is_charging) = (power.on_usb, power.charging);
         
         // Log battery readings periodically (every 10th sample to reduce spam)
         self.sample_count = self.sample_count.wrapping_add(1);
@@ -207,8 +213,9 @@ impl SensorManager {
                 battery_voltage, battery_voltage as f32 / 1000.0, battery_percentage, adc_raw, is_on_usb, is_charging);
             
             // Extra debug for voltage divider calculation
-            let measured_at_pin = ((adc_raw as u32 * 3100) / 4095) as u16;
-            log::warn!("[BATTERY_SAMPLE] ADC pin voltage: {}mV (before 2x multiplier)", measured_at_pin);
+            let measured_at_pin = self.adc_cal.pin_millivolts(adc_raw);
+            log::warn!("[BATTERY_SAMPLE] ADC pin voltage: {}mV (before 2x multiplier, {})",
+                measured_at_pin, adc_cal::scheme().as_str());
         }
         
         Ok(SensorData {
@@ -253,6 +260,7 @@ impl SensorManager {
     
 }
 
+#[cfg(not(feature = "demo_mode"))]
 impl Drop for SensorManager {
     fn drop(&mut self) {
         if let Some(handle) = self.temp_sensor_handle {
@@ -0,0 +1,152 @@
+// USB/charging detection from the battery voltage. The T-Display-S3 has no
+// charger status pin, so both states are guessed from the voltage on GPIO4,
+// which sits right at the thresholds while charging tops off or the cell
+// rests after unplugging. Three filters keep the header from flickering:
+// each reading is the median of several ADC samples, every threshold has a
+// hysteresis band, and a new state must hold for MIN_STATE_HOLD before it is
+// reported, so each indicator changes at most once per hold time.
+
+use std::time::{Duration, Instant};
+
+/// ADC samples per reading; the median is used
+pub const SAMPLES_PER_READ: usize = 5;
+
+/// How long a changed state must persist before it is reported
+pub const MIN_STATE_HOLD: Duration = Duration::from_secs(5);
+
+/// Battery present above 2.5 V
+const CONNECTED_BAND: Band = Band { rise_mv: 2500, fall_mv: 2400 };
+/// Only USB pushes the rail above 4.5 V
+const USB_BAND: Band = Band { rise_mv: 4500, fall_mv: 4400 };
+/// A connected cell above 4.0 V is on the charger
+const CHARGING_BAND: Band = Band { rise_mv: 4000, fall_mv: 3950 };
+
+/// Median of `samples` (reorders them); 0 when empty
+pub fn median(samples: &mut [u16]) -> u16 {
+    if samples.is_empty() {
+        return 0;
+    }
+    samples.sort_unstable();
+    samples[samples.len() / 2]
+}
+
+/// Switches on above `rise_mv` and back off only below `fall_mv`
+#[derive(Debug, Clone, Copy)]
+struct Band {
+    rise_mv: u16,
+    fall_mv: u16,
+}
+
+impl Band {
+    fn apply(&self, on: bool, mv: u16) -> bool {
+        if on { mv >= self.fall_mv } else { mv > self.rise_mv }
+    }
+}
+
+/// A flag that only follows its input once the input has held steady
+#[derive(Debug, Clone, Default)]
+struct Debounced {
+    shown: Option<bool>,
+    pending_since: Option<Instant>,
+}
+
+impl Debounced {
+    fn update(&mut self, raw: bool, now: Instant) -> bool {
+        let Some(shown) = self.shown else {
+            // The first reading is shown as is
+            self.shown = Some(raw);
+            return raw;
+        };
+        if raw == shown {
+            self.pending_since = None;
+            return shown;
+        }
+        let since = *self.pending_since.get_or_insert(now);
+        if now.duration_since(since) < MIN_STATE_HOLD {
+            return shown;
+        }
+        self.pending_since = None;
+        self.shown = Some(raw);
+        raw
+    }
+}
+
+/// Power source as reported to the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerState {
+    pub battery_connected: bool,
+    pub on_usb: bool,
+    pub charging: bool,
+}
+
+/// Turns battery voltage readings into a steady PowerState
+#[derive(Debug, Clone, Default)]
+pub struct PowerStateFilter {
+    connected: bool,
+    above_usb: bool,
+    above_charging: bool,
+    on_usb: Debounced,
+    charging: Debounced,
+}
+
+impl PowerStateFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one (median) battery voltage reading taken at `now`
+    pub fn update(&mut self, battery_mv: u16, now: Instant) -> PowerState {
+        self.connected = CONNECTED_BAND.apply(self.connected, battery_mv);
+        self.above_usb = USB_BAND.apply(self.above_usb, battery_mv);
+        self.above_charging = CHARGING_BAND.apply(self.above_charging, battery_mv);
+
+        let on_usb = self.on_usb.update(self.above_usb || !self.connected, now);
+        let charging = self.charging.update(self.connected && self.above_charging, now);
+        PowerState { battery_connected: self.connected, on_usb, charging }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median_rejects_outliers() {
+        assert_eq!(median(&mut []), 0);
+        assert_eq!(median(&mut [2100, 0, 2110, 4095, 2105]), 2105);
+        assert_eq!(median(&mut [7]), 7);
+    }
+
+    #[test]
+    fn test_state_changes_need_band_and_hold_time() {
+        let t0 = Instant::now();
+        let s = |secs: u64| t0 + Duration::from_secs(secs);
+        let mut filter = PowerStateFilter::new();
+
+        // First reading is reported straight away
+        let state = filter.update(3900, t0);
+        assert_eq!(state, PowerState { battery_connected: true, on_usb: false, charging: false });
+
+        // Noise around the charging threshold: inside the band nothing changes back
+        assert!(!filter.update(4010, s(1)).charging, "must hold first");
+        assert!(!filter.update(3960, s(3)).charging);
+        assert!(filter.update(3990, s(6)).charging, "held above the band for 5 s");
+        assert!(filter.update(3960, s(7)).charging, "still inside the band");
+
+        // Dropping below the band starts a new hold; bouncing back cancels it
+        assert!(filter.update(3900, s(8)).charging);
+        assert!(filter.update(4010, s(9)).charging);
+        assert!(filter.update(3900, s(10)).charging);
+        assert!(!filter.update(3900, s(15)).charging);
+
+        // USB rail
+        assert!(!filter.update(4600, s(20)).on_usb);
+        let state = filter.update(4450, s(25));
+        assert!(state.on_usb && state.charging);
+
+        // No battery reads as USB power
+        let mut filter = PowerStateFilter::new();
+        let state = filter.update(100, t0);
+        assert_eq!(state, PowerState { battery_connected: false, on_usb: true, charging: false });
+    }
+}
@@ -0,0 +1,260 @@
+// Pluggable sensors. The board's own readings (die temperature, battery)
+// come from SensorManager; anything wired on top, a CO2 or soil-moisture
+// sensor say, implements `Sensor` and is registered once at startup. The
+// registry polls every sensor on its own interval from the main loop and
+// publishes all readings in one map keyed `<sensor id>.<quantity>` (e.g.
+// `scd40.co2`). The Sensor screen, /metrics, /api/v1/sensors and the sensor
+// history read that map, so a new sensor needs no changes outside its impl.
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Consecutive failed reads before a sensor's readings are withdrawn, so the
+/// screen and /metrics don't keep showing a frozen value
+pub const MAX_FAILURES: u32 = 3;
+
+/// At most one history point per key this often, so the history covers hours
+/// whatever the poll interval
+pub const HISTORY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// One measured quantity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Reading {
+    /// Lowercase, e.g. `co2`; second part of the key
+    pub quantity: &'static str,
+    pub value: f32,
+    /// Display unit, e.g. `ppm`; empty for plain numbers
+    pub unit: &'static str,
+    /// Decimals shown on screen
+    #[serde(skip)]
+    pub decimals: u8,
+}
+
+impl Reading {
+    pub fn new(quantity: &'static str, value: f32, unit: &'static str) -> Self {
+        Self { quantity, value, unit, decimals: 1 }
+    }
+
+    pub fn with_decimals(self, decimals: u8) -> Self {
+        Self { decimals, ..self }
+    }
+
+    /// Value and unit as shown on screen, e.g. `812 ppm`
+    pub fn display(&self) -> String {
+        match self.unit {
+            "" => format!("{:.*}", self.decimals as usize, self.value),
+            unit => format!("{:.*} {}", self.decimals as usize, self.value, unit),
+        }
+    }
+}
+
+pub type Readings = Vec<Reading>;
+
+/// A sensor the registry polls
+pub trait Sensor: Send {
+    /// Lowercase and unique among registered sensors; first part of every key
+    fn id(&self) -> &'static str;
+
+    fn poll_interval(&self) -> Duration;
+
+    /// Take one measurement; an empty result means nothing new yet
+    fn read(&mut self) -> Result<Readings>;
+}
+
+/// A reading with the sensor it came from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Sample {
+    pub key: String,
+    pub sensor: &'static str,
+    #[serde(flatten)]
+    pub reading: Reading,
+}
+
+struct Entry {
+    sensor: Box<dyn Sensor>,
+    next_due: Option<Instant>,
+    failures: u32,
+}
+
+#[derive(Default)]
+pub struct SensorRegistry {
+    entries: Vec<Entry>,
+    latest: BTreeMap<String, Sample>,
+    /// When each key last went into the history
+    recorded: BTreeMap<String, Instant>,
+}
+
+impl SensorRegistry {
+    pub const fn new() -> Self {
+        Self { entries: Vec::new(), latest: BTreeMap::new(), recorded: BTreeMap::new() }
+    }
+
+    pub fn register(&mut self, sensor: Box<dyn Sensor>) -> Result<()> {
+        let id = sensor.id();
+        if self.entries.iter().any(|e| e.sensor.id() == id) {
+            return Err(anyhow!("Sensor {} is already registered", id));
+        }
+        self.entries.push(Entry { sensor, next_due: None, failures: 0 });
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Read every sensor that is due at `now`; returns the new samples
+    pub fn poll(&mut self, now: Instant) -> Vec<Sample> {
+        let mut fresh = Vec::new();
+        for entry in &mut self.entries {
+            if entry.next_due.is_some_and(|due| now < due) {
+                continue;
+            }
+            entry.next_due = Some(now + entry.sensor.poll_interval());
+            let id = entry.sensor.id();
+            match entry.sensor.read() {
+                Ok(readings) => {
+                    entry.failures = 0;
+                    for reading in readings {
+                        let sample = Sample { key: format!("{}.{}", id, reading.quantity), sensor: id, reading };
+                        self.latest.insert(sample.key.clone(), sample.clone());
+                        fresh.push(sample);
+                    }
+                }
+                Err(e) => {
+                    entry.failures += 1;
+                    log::warn!("Sensor {}: {:?}", id, e);
+                    if entry.failures == MAX_FAILURES {
+                        log::warn!("Sensor {} failed {} reads in a row, withdrawing its readings", id, MAX_FAILURES);
+                        self.latest.retain(|_, sample| sample.sensor != id);
+                    }
+                }
+            }
+        }
+        fresh
+    }
+
+    /// Latest reading of every quantity, ordered by key
+    pub fn readings(&self) -> Vec<Sample> {
+        self.latest.values().cloned().collect()
+    }
+
+    /// Whether a sample of `key` taken at `now` goes into the history
+    pub fn history_due(&mut self, key: &str, now: Instant) -> bool {
+        if self.recorded.get(key).is_some_and(|&at| now.duration_since(at) < HISTORY_INTERVAL) {
+            return false;
+        }
+        self.recorded.insert(key.to_string(), now);
+        true
+    }
+}
+
+static REGISTRY: Mutex<SensorRegistry> = Mutex::new(SensorRegistry::new());
+static LATEST: Mutex<Vec<Sample>> = Mutex::new(Vec::new());
+
+/// Add a sensor; call at startup
+pub fn register(sensor: Box<dyn Sensor>) -> Result<()> {
+    let id = sensor.id();
+    REGISTRY.lock().map_err(|_| anyhow!("sensor registry poisoned"))?.register(sensor)?;
+    log::info!("Sensor {} registered", id);
+    Ok(())
+}
+
+/// Poll the sensors that are due and publish their readings; call from the
+/// main loop
+pub fn update() {
+    let Ok(mut registry) = REGISTRY.lock() else { return };
+    if registry.is_empty() {
+        return;
+    }
+    let now = Instant::now();
+    let fresh = registry.poll(now);
+    if fresh.is_empty() {
+        return;
+    }
+    if let Some(history) = super::history::get() {
+        if let Ok(history) = history.lock() {
+            for sample in &fresh {
+                if registry.history_due(&sample.key, now) {
+                    history.add(&sample.key, sample.reading.value);
+                }
+            }
+        }
+    }
+    if let Ok(mut latest) = LATEST.lock() {
+        *latest = registry.readings();
+    }
+}
+
+/// Latest readings of all registered sensors, ordered by key
+pub fn snapshot() -> Vec<Sample> {
+    LATEST.lock().map(|latest| latest.clone()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Soil {
+        moisture: Vec<Result<f32>>,
+    }
+
+    impl Sensor for Soil {
+        fn id(&self) -> &'static str {
+            "soil"
+        }
+
+        fn poll_interval(&self) -> Duration {
+            Duration::from_secs(10)
+        }
+
+        fn read(&mut self) -> Result<Readings> {
+            let moisture = self.moisture.remove(0)?;
+            Ok(vec![Reading::new("moisture", moisture, "%").with_decimals(0), Reading::new("raw", moisture * 10.0, "")])
+        }
+    }
+
+    #[test]
+    fn test_polls_on_interval_and_keys_readings() {
+        let mut registry = SensorRegistry::new();
+        registry.register(Box::new(Soil { moisture: vec![Ok(41.0), Ok(43.4)] })).unwrap();
+        assert!(registry.register(Box::new(Soil { moisture: Vec::new() })).is_err(), "ids are unique");
+
+        let t0 = Instant::now();
+        let fresh = registry.poll(t0);
+        assert_eq!(fresh.iter().map(|s| s.key.as_str()).collect::<Vec<_>>(), ["soil.moisture", "soil.raw"]);
+        assert!(registry.poll(t0 + Duration::from_secs(9)).is_empty(), "not due yet");
+
+        registry.poll(t0 + Duration::from_secs(10));
+        let readings = registry.readings();
+        assert_eq!(readings[0].reading.display(), "43 %");
+        assert_eq!(readings[1].reading.display(), "434.0");
+        assert_eq!(serde_json::to_value(&readings[0]).unwrap()["quantity"], "moisture");
+
+        assert!(registry.history_due("soil.moisture", t0));
+        assert!(!registry.history_due("soil.moisture", t0 + Duration::from_secs(10)));
+        assert!(registry.history_due("soil.raw", t0 + Duration::from_secs(10)));
+        assert!(registry.history_due("soil.moisture", t0 + HISTORY_INTERVAL));
+    }
+
+    #[test]
+    fn test_failing_sensor_is_withdrawn() {
+        let mut registry = SensorRegistry::new();
+        let mut moisture = vec![Ok(40.0)];
+        moisture.extend((0..MAX_FAILURES).map(|_| Err(anyhow!("i2c timeout"))));
+        moisture.push(Ok(42.0));
+        registry.register(Box::new(Soil { moisture })).unwrap();
+
+        let t0 = Instant::now();
+        for n in 0..MAX_FAILURES {
+            registry.poll(t0 + Duration::from_secs(10) * n);
+            assert_eq!(registry.readings().len(), 2, "a failure or two keeps the last value");
+        }
+        registry.poll(t0 + Duration::from_secs(10) * MAX_FAILURES);
+        assert!(registry.readings().is_empty());
+        registry.poll(t0 + Duration::from_secs(10) * (MAX_FAILURES + 1));
+        assert_eq!(registry.readings()[0].reading.value, 42.0);
+    }
+}
@@ -0,0 +1,94 @@
+// Simulated sensors for demo builds (`--features demo_mode`). Produces plausible
+// time-varying readings without touching the ADC or temperature sensor, so the
+// device can run demos on a bare board and UI code sees repeatable data.
+
+use anyhow::Result;
+use esp_idf_hal::adc::ADC1;
+use esp_idf_hal::gpio::Gpio4;
+use std::time::Instant;
+use super::SensorData;
+
+/// Battery drains from full to the low mark, then charges back on USB
+const DRAIN_SECS: u32 = 30 * 60;
+const CHARGE_SECS: u32 = 10 * 60;
+const LOW_PERCENT: u32 = 15;
+/// Temperature swings +/-3°C around 24°C over this period
+const TEMP_PERIOD_SECS: u32 = 600;
+/// Light level sweeps 50-350 over this period
+const LIGHT_PERIOD_SECS: u32 = 40 * 60;
+
+fn wave(t_secs: u32, period_secs: u32) -> f32 {
+    let phase = (t_secs % period_secs) as f32 / period_secs as f32;
+    (2.0 * core::f32::consts::PI * phase).sin()
+}
+
+/// Readings at `t_secs` after start. Pure so the waveform can be tested and
+/// replayed deterministically.
+pub fn reading_at(t_secs: u32) -> SensorData {
+    let temperature = 24.0 + 3.0 * wave(t_secs, TEMP_PERIOD_SECS);
+
+    let cycle = t_secs % (DRAIN_SECS + CHARGE_SECS);
+    let span = 100 - LOW_PERCENT;
+    let (percentage, charging) = if cycle < DRAIN_SECS {
+        (100 - span * cycle / DRAIN_SECS, false)
+    } else {
+        (LOW_PERCENT + span * (cycle - DRAIN_SECS) / CHARGE_SECS, true)
+    };
+    // Inverse of voltage_to_percentage: 3.0V = 0%, 4.2V = 100%
+    let voltage = 3000 + percentage as u16 * 12;
+
+    let light = 200.0 + 150.0 * wave(t_secs, LIGHT_PERIOD_SECS);
+
+    SensorData {
+        _temperature: temperature,
+        _battery_percentage: percentage as u8,
+        _battery_voltage: voltage,
+        _is_charging: charging,
+        _is_on_usb: charging,
+        _light_level: light as u16,
+    }
+}
+
+/// Drop-in replacement for SensorManager when demo_mode is enabled
+pub struct SimulatedSensors {
+    started: Instant,
+}
+
+impl SimulatedSensors {
+    /// Same signature as SensorManager::new; the peripherals are taken but unused
+    pub fn new(_adc1: ADC1, _battery_pin: Gpio4) -> Result<Self> {
+        log::warn!("Demo mode: using simulated sensor data");
+        Ok(Self { started: Instant::now() })
+    }
+
+    pub fn sample(&mut self) -> Result<SensorData> {
+        Ok(reading_at(self.started.elapsed().as_secs() as u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_battery_drains_then_charges() {
+        let start = reading_at(0);
+        assert_eq!(start._battery_percentage, 100);
+        assert!(!start._is_charging);
+
+        let low = reading_at(DRAIN_SECS);
+        assert_eq!(low._battery_percentage, LOW_PERCENT as u8);
+        assert!(low._is_charging && low._is_on_usb);
+
+        assert!(reading_at(DRAIN_SECS / 2)._battery_percentage < 100);
+        assert_eq!(reading_at(DRAIN_SECS + CHARGE_SECS)._battery_percentage, 100);
+    }
+
+    #[test]
+    fn test_temperature_stays_in_band() {
+        for t in (0..1200).step_by(7) {
+            let temp = reading_at(t)._temperature;
+            assert!((21.0..=27.0).contains(&temp), "t={} temp={}", t, temp);
+        }
+    }
+}
@@ -0,0 +1,359 @@
+// Typed key-value store over NVS. Values are serde types stored as JSON blobs
+// under a namespace. Writes are wear-aware: unchanged values are never
+// rewritten, and `stage` batches frequent updates until the next `flush`.
+// Per-namespace byte counts are tracked for the storage report in /api/system.
+//
+// All handles share one NVS partition taken at boot (`init`), since the
+// default partition can only be taken once while WiFi holds it.
+
+use anyhow::{anyhow, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// NVS limit for namespace and key names
+pub const MAX_NAME_LEN: usize = 15;
+
+static USAGE: Mutex<BTreeMap<&'static str, Usage>> = Mutex::new(BTreeMap::new());
+
+/// Raw blob access underneath a `Store`
+pub trait Backend {
+    fn read(&mut self, key: &str) -> Result<Option<Vec<u8>>>;
+    fn write(&mut self, key: &str, bytes: &[u8]) -> Result<()>;
+    fn remove(&mut self, key: &str) -> Result<()>;
+}
+
+/// Stored keys and their encoded size
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct Usage {
+    pub keys: usize,
+    pub bytes: usize,
+}
+
+/// One namespace
+pub struct Store<B: Backend> {
+    backend: B,
+    namespace: &'static str,
+    // Bytes known to be in flash, per key; used to skip redundant writes
+    persisted: BTreeMap<&'static str, Vec<u8>>,
+    // Staged values not yet written
+    pending: BTreeMap<&'static str, Vec<u8>>,
+    last_flush: Option<Instant>,
+}
+
+impl<B: Backend> Store<B> {
+    pub fn new(namespace: &'static str, backend: B) -> Result<Self> {
+        check_name(namespace)?;
+        Ok(Self { backend, namespace, persisted: BTreeMap::new(), pending: BTreeMap::new(), last_flush: None })
+    }
+
+    /// Read and decode a value; staged values win over flash. None if the key
+    /// is missing or no longer decodes as `T`.
+    pub fn get<T: DeserializeOwned>(&mut self, key: &'static str) -> Option<T> {
+        if let Some(bytes) = self.pending.get(key) {
+            return serde_json::from_slice(bytes).ok();
+        }
+        let decoded = serde_json::from_slice(self.load(key)?);
+        if decoded.is_err() {
+            log::warn!("KV: {}/{} does not decode, ignoring it", self.namespace, key);
+        }
+        decoded.ok()
+    }
+
+    // Flash contents of `key`, read once and then cached
+    fn load(&mut self, key: &'static str) -> Option<&Vec<u8>> {
+        if !self.persisted.contains_key(key) {
+            match self.backend.read(key) {
+                Ok(Some(bytes)) => {
+                    self.persisted.insert(key, bytes);
+                    self.publish_usage();
+                }
+                Ok(None) => return None,
+                Err(e) => {
+                    log::warn!("KV: failed to read {}/{}: {:?}", self.namespace, key, e);
+                    return None;
+                }
+            }
+        }
+        self.persisted.get(key)
+    }
+
+    /// Write a value now (skipped if flash already holds the same bytes)
+    pub fn set<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        self.stage(key, value)?;
+        self.flush_key(key)
+    }
+
+    /// Queue a value for the next `flush`; repeated updates to one key cost a
+    /// single write
+    pub fn stage<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        check_name(key)?;
+        let bytes = serde_json::to_vec(value)?;
+        // Reads don't wear flash, so compare against what is stored first
+        if self.load(key) == Some(&bytes) {
+            self.pending.remove(key);
+        } else {
+            self.pending.insert(key, bytes);
+        }
+        Ok(())
+    }
+
+    /// Write every staged value; returns how many keys were written
+    pub fn flush(&mut self) -> Result<usize> {
+        let keys: Vec<&'static str> = self.pending.keys().copied().collect();
+        for key in &keys {
+            self.flush_key(key)?;
+        }
+        self.last_flush = Some(Instant::now());
+        Ok(keys.len())
+    }
+
+    /// Flush if at least `interval` has passed since the last flush
+    pub fn flush_if_due(&mut self, interval: Duration) -> Result<usize> {
+        if self.pending.is_empty() || self.last_flush.is_some_and(|t| t.elapsed() < interval) {
+            return Ok(0);
+        }
+        self.flush()
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    pub fn remove(&mut self, key: &'static str) -> Result<()> {
+        self.pending.remove(key);
+        self.backend.remove(key)?;
+        self.persisted.remove(key);
+        self.publish_usage();
+        Ok(())
+    }
+
+    /// Keys and bytes of this namespace seen so far
+    pub fn usage(&self) -> Usage {
+        Usage { keys: self.persisted.len(), bytes: self.persisted.values().map(Vec::len).sum() }
+    }
+
+    fn flush_key(&mut self, key: &'static str) -> Result<()> {
+        let Some(bytes) = self.pending.remove(key) else { return Ok(()) };
+        if let Err(e) = self.backend.write(key, &bytes) {
+            // Keep it staged so the next flush retries
+            self.pending.insert(key, bytes);
+            return Err(e);
+        }
+        self.persisted.insert(key, bytes);
+        self.publish_usage();
+        Ok(())
+    }
+
+    fn publish_usage(&self) {
+        if let Ok(mut usage) = USAGE.lock() {
+            usage.insert(self.namespace, self.usage());
+        }
+    }
+}
+
+/// Usage of every namespace opened since boot
+pub fn usage() -> BTreeMap<&'static str, Usage> {
+    USAGE.lock().map(|u| u.clone()).unwrap_or_default()
+}
+
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(anyhow!("NVS name '{}' must be 1-{} characters", name, MAX_NAME_LEN));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "espidf")]
+pub use self::nvs::{init, open, stats, NvsBackend, NvsStats};
+
+#[cfg(target_os = "espidf")]
+pub type NvsStore = Store<NvsBackend>;
+
+#[cfg(target_os = "espidf")]
+mod nvs {
+    use super::{Backend, Store};
+    use anyhow::{anyhow, Result};
+    use esp_idf_svc::nvs::{EspDefaultNvsPartition, EspNvs, NvsDefault};
+    use std::sync::OnceLock;
+
+    static PARTITION: OnceLock<EspDefaultNvsPartition> = OnceLock::new();
+
+    /// Flash usage of the whole NVS partition, in entries
+    #[derive(Debug, Clone, Copy, serde::Serialize)]
+    pub struct NvsStats {
+        pub used_entries: usize,
+        pub free_entries: usize,
+        pub total_entries: usize,
+        pub namespaces: usize,
+    }
+
+    /// Take (and if needed repair) the default NVS partition. Call once at
+    /// boot, before WiFi; later calls return the same handle.
+    pub fn init() -> Result<EspDefaultNvsPartition> {
+        if let Some(partition) = PARTITION.get() {
+            return Ok(partition.clone());
+        }
+        unsafe {
+            let res = esp_idf_sys::nvs_flash_init();
+            if res == esp_idf_sys::ESP_ERR_NVS_NO_FREE_PAGES || res == esp_idf_sys::ESP_ERR_NVS_NEW_VERSION_FOUND {
+                log::warn!("NVS: partition needs erasing ({}), resetting it", res);
+                let _ = esp_idf_sys::nvs_flash_erase();
+                let _ = esp_idf_sys::nvs_flash_init();
+            }
+        }
+        let partition = EspDefaultNvsPartition::take()?;
+        Ok(PARTITION.get_or_init(|| partition).clone())
+    }
+
+    /// Open (creating if needed) a namespace on the shared partition
+    pub fn open(namespace: &'static str) -> Result<Store<NvsBackend>> {
+        let partition = PARTITION.get().cloned().ok_or_else(|| anyhow!("NVS not initialised"))?;
+        let nvs = EspNvs::new(partition, namespace, true)?;
+        Store::new(namespace, NvsBackend { nvs, legacy: Vec::new() })
+    }
+
+    pub fn stats() -> Option<NvsStats> {
+        let mut stats: esp_idf_sys::nvs_stats_t = unsafe { core::mem::zeroed() };
+        if unsafe { esp_idf_sys::nvs_get_stats(core::ptr::null(), &mut stats) } != esp_idf_sys::ESP_OK {
+            return None;
+        }
+        Some(NvsStats {
+            used_entries: stats.used_entries as usize,
+            free_entries: stats.free_entries as usize,
+            total_entries: stats.total_entries as usize,
+            namespaces: stats.namespace_count as usize,
+        })
+    }
+
+    pub struct NvsBackend {
+        nvs: EspNvs<NvsDefault>,
+        // Keys read from pre-kv integer entries; erased before the first blob write
+        legacy: Vec<String>,
+    }
+
+    impl Backend for NvsBackend {
+        fn read(&mut self, key: &str) -> Result<Option<Vec<u8>>> {
+            if let Some(len) = self.nvs.blob_len(key)? {
+                let mut buf = vec![0u8; len];
+                return Ok(self.nvs.get_blob(key, &mut buf)?.map(<[u8]>::to_vec));
+            }
+            // Older firmware stored counters as native integers; JSON numbers decode the same
+            let legacy = match self.nvs.get_u32(key) {
+                Ok(Some(v)) => Some(v as u64),
+                _ => self.nvs.get_u64(key).ok().flatten(),
+            };
+            Ok(legacy.map(|v| {
+                self.legacy.push(key.to_string());
+                v.to_string().into_bytes()
+            }))
+        }
+
+        fn write(&mut self, key: &str, bytes: &[u8]) -> Result<()> {
+            if let Some(i) = self.legacy.iter().position(|k| k == key) {
+                self.nvs.remove(key)?;
+                self.legacy.swap_remove(i);
+            }
+            self.nvs.set_blob(key, bytes)?;
+            Ok(())
+        }
+
+        fn remove(&mut self, key: &str) -> Result<()> {
+            self.nvs.remove(key)?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+
+    /// In-memory flash that counts writes
+    #[derive(Default, Clone)]
+    struct MemoryBackend {
+        data: Rc<RefCell<HashMap<String, Vec<u8>>>>,
+        writes: Rc<RefCell<usize>>,
+    }
+
+    impl Backend for MemoryBackend {
+        fn read(&mut self, key: &str) -> Result<Option<Vec<u8>>> {
+            Ok(self.data.borrow().get(key).cloned())
+        }
+
+        fn write(&mut self, key: &str, bytes: &[u8]) -> Result<()> {
+            *self.writes.borrow_mut() += 1;
+            self.data.borrow_mut().insert(key.to_string(), bytes.to_vec());
+            Ok(())
+        }
+
+        fn remove(&mut self, key: &str) -> Result<()> {
+            self.data.borrow_mut().remove(key);
+            Ok(())
+        }
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Counters {
+        boots: u32,
+        label: String,
+    }
+
+    #[test]
+    fn test_typed_round_trip() {
+        let flash = MemoryBackend::default();
+        let mut store = Store::new("test_rt", flash.clone()).unwrap();
+        let value = Counters { boots: 3, label: "a".into() };
+        store.set("counters", &value).unwrap();
+        store.set("total", &42u64).unwrap();
+
+        // A fresh handle reads what is in flash
+        let mut reopened = Store::new("test_rt", flash).unwrap();
+        assert_eq!(reopened.get::<Counters>("counters"), Some(value));
+        assert_eq!(reopened.get::<u64>("total"), Some(42));
+        assert_eq!(reopened.get::<u32>("missing"), None);
+        // Wrong type decodes to None rather than garbage
+        assert_eq!(reopened.get::<Counters>("total"), None);
+        assert_eq!(reopened.usage(), Usage { keys: 2, bytes: 23 + 2 });
+        assert_eq!(usage().get("test_rt"), Some(&Usage { keys: 2, bytes: 25 }));
+    }
+
+    #[test]
+    fn test_unchanged_values_are_not_rewritten() {
+        let flash = MemoryBackend::default();
+        let mut store = Store::new("test_wear", flash.clone()).unwrap();
+        store.set("n", &1u32).unwrap();
+        store.set("n", &1u32).unwrap();
+        assert_eq!(*flash.writes.borrow(), 1);
+
+        // Staged updates collapse into one write per key
+        for n in 2..10u32 {
+            store.stage("n", &n).unwrap();
+        }
+        assert_eq!(store.get::<u32>("n"), Some(9));
+        assert_eq!(*flash.writes.borrow(), 1);
+        assert_eq!(store.flush().unwrap(), 1);
+        assert_eq!(*flash.writes.borrow(), 2);
+        let mut reopened = Store::new("test_wear", flash.clone()).unwrap();
+        reopened.set("n", &9u32).unwrap();
+        assert_eq!(*flash.writes.borrow(), 2);
+
+        // Staging the value already in flash cancels the write
+        store.stage("n", &3u32).unwrap();
+        store.stage("n", &9u32).unwrap();
+        assert!(!store.has_pending());
+        assert_eq!(store.flush_if_due(Duration::ZERO).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_names_are_checked() {
+        assert!(Store::new("", MemoryBackend::default()).is_err());
+        let mut store = Store::new("test_names", MemoryBackend::default()).unwrap();
+        assert!(store.set("sixteen_chars_xx", &1u8).is_err());
+        store.remove("nothing").unwrap();
+    }
+}
@@ -0,0 +1,168 @@
+// Crash-safe log ring. Every log line is also copied into a byte ring in a
+// PSRAM section the startup code leaves uninitialised, so it survives panics,
+// watchdog resets and esp_restart (not power loss). On the boot after a crash
+// the previous contents are written to SPIFFS before the ring is reused, and
+// served at /api/logs/previous-boot.
+
+/// Marks a ring that was initialised by a previous boot
+const RING_MAGIC: u32 = 0x4c4f_4752; // "LOGR"
+
+/// Size of the device ring
+pub const RING_BYTES: usize = 64 * 1024;
+
+/// Where the previous boot's log is kept on SPIFFS
+pub const PREVIOUS_BOOT_PATH: &str = "/spiffs/prevboot.log";
+
+/// Byte ring of newline-terminated lines
+#[repr(C)]
+pub struct LogRing<const N: usize> {
+    magic: u32,
+    /// Bytes ever written; the ring holds the last min(written, N)
+    written: u64,
+    data: [u8; N],
+}
+
+impl<const N: usize> Default for LogRing<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> LogRing<N> {
+    pub const fn new() -> Self {
+        Self { magic: RING_MAGIC, written: 0, data: [0; N] }
+    }
+
+    /// Whether the ring holds data from an earlier boot rather than garbage
+    pub fn is_valid(&self) -> bool {
+        self.magic == RING_MAGIC
+    }
+
+    /// Empty the ring (also makes uninitialised memory valid)
+    pub fn clear(&mut self) {
+        self.magic = RING_MAGIC;
+        self.written = 0;
+    }
+
+    pub fn push_line(&mut self, line: &str) {
+        for bytes in [line.as_bytes(), b"\n"] {
+            // Only the last N bytes of an over-long line can survive anyway
+            let bytes = &bytes[bytes.len().saturating_sub(N)..];
+            let start = (self.written % N as u64) as usize;
+            let first = bytes.len().min(N - start);
+            self.data[start..start + first].copy_from_slice(&bytes[..first]);
+            self.data[..bytes.len() - first].copy_from_slice(&bytes[first..]);
+            self.written += bytes.len() as u64;
+        }
+    }
+
+    /// Buffered lines, oldest first. After a wrap the partial oldest line is dropped.
+    pub fn contents(&self) -> String {
+        let bytes: Vec<u8> = if self.written <= N as u64 {
+            self.data[..self.written as usize].to_vec()
+        } else {
+            let start = (self.written % N as u64) as usize;
+            let wrapped = [&self.data[start..], &self.data[..start]].concat();
+            match wrapped.iter().position(|&b| b == b'\n') {
+                Some(end) => wrapped[end + 1..].to_vec(),
+                None => wrapped,
+            }
+        };
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+}
+
+/// File written after a crash: a header naming the reset, then the log
+pub fn previous_boot_report(reset_reason: &str, log: &str) -> String {
+    format!("# Previous boot ended with: {}\n{}", reset_reason, log)
+}
+
+#[cfg(target_os = "espidf")]
+mod device {
+    use super::*;
+    use core::mem::MaybeUninit;
+    use core::ptr::addr_of_mut;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    // Needs CONFIG_SPIRAM_ALLOW_NOINIT_SEG_EXTERNAL_MEMORY (sdkconfig.defaults)
+    #[link_section = ".ext_ram_noinit"]
+    static mut RING: MaybeUninit<LogRing<RING_BYTES>> = MaybeUninit::uninit();
+
+    /// Set once `recover` has validated the ring
+    static READY: AtomicBool = AtomicBool::new(false);
+    /// Held while a line is copied in; writers that find it taken drop their line
+    static BUSY: AtomicBool = AtomicBool::new(false);
+
+    fn ring() -> *mut LogRing<RING_BYTES> {
+        // SAFETY: only the address is taken; access is serialised by BUSY
+        unsafe { addr_of_mut!(RING).cast() }
+    }
+
+    /// Call once before the logger starts. Returns what the ring held if the
+    /// last reset was a crash, then empties it for this boot.
+    pub fn recover() -> Option<String> {
+        let ring = ring();
+        // SAFETY: nothing else touches the ring before READY is set. The magic
+        // is read volatile because the memory may be uninitialised.
+        let previous = unsafe {
+            let valid = core::ptr::read_volatile(addr_of_mut!((*ring).magic)) == RING_MAGIC;
+            let previous = (valid && crate::system::reset::last_reset_was_crash())
+                .then(|| (*ring).contents())
+                .filter(|log| !log.is_empty());
+            (*ring).clear();
+            previous
+        };
+        READY.store(true, Ordering::Release);
+        previous
+    }
+
+    /// Append one formatted log line; never blocks
+    pub fn write_line(line: &str) {
+        if !READY.load(Ordering::Acquire) || BUSY.swap(true, Ordering::Acquire) {
+            return;
+        }
+        // SAFETY: BUSY gives exclusive access
+        unsafe { (*ring()).push_line(line) };
+        BUSY.store(false, Ordering::Release);
+    }
+
+    /// Mount SPIFFS if nothing has yet and write the previous boot's log
+    pub fn save_previous(reset_reason: &str, log: &str) -> anyhow::Result<()> {
+        crate::storage::ensure_spiffs_mounted()?;
+        std::fs::write(PREVIOUS_BOOT_PATH, previous_boot_report(reset_reason, log))?;
+        Ok(())
+    }
+
+    /// The saved log of the last boot that crashed, if any
+    pub fn read_previous() -> Option<String> {
+        std::fs::read_to_string(PREVIOUS_BOOT_PATH).ok()
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub use device::{read_previous, recover, save_previous, write_line};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_keeps_whole_recent_lines() {
+        let mut ring = LogRing::<32>::new();
+        ring.push_line("first");
+        ring.push_line("second");
+        assert_eq!(ring.contents(), "first\nsecond\n");
+
+        // Wraps: the oldest, partly overwritten line is dropped
+        ring.push_line("third line");
+        ring.push_line("fourth line");
+        assert_eq!(ring.contents(), "second\nthird line\nfourth line\n");
+
+        ring.push_line(&"x".repeat(40));
+        assert!(ring.contents().len() <= 32);
+
+        ring.clear();
+        assert!(ring.is_valid());
+        assert_eq!(ring.contents(), "");
+    }
+}
@@ -0,0 +1,24 @@
+// Persistent storage. Subsystems that keep state across reboots go through
+// `kv` rather than opening NVS themselves.
+
+pub mod kv;
+pub mod log_ring;
+
+/// Mount the SPIFFS `storage` partition at /spiffs unless something already has.
+/// Early boot code (crash log, boot logo) runs before the file manager mounts it.
+#[cfg(target_os = "espidf")]
+pub fn ensure_spiffs_mounted() -> anyhow::Result<()> {
+    use esp_idf_sys::*;
+    unsafe {
+        if !esp_spiffs_mounted(c"storage".as_ptr()) {
+            let conf = esp_vfs_spiffs_conf_t {
+                base_path: c"/spiffs".as_ptr(),
+                partition_label: c"storage".as_ptr(),
+                max_files: 5,
+                format_if_mount_failed: false,
+            };
+            esp!(esp_vfs_spiffs_register(&conf))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,45 @@
+// Optional GPIO output (LED or active buzzer) pulsed on events like timer completion
+
+use anyhow::{anyhow, Result};
+use esp_idf_hal::gpio::{AnyOutputPin, Output, PinDriver};
+use std::time::{Duration, Instant};
+use crate::network::validators::is_pin_available;
+
+const PULSE_PERIOD: Duration = Duration::from_millis(200);
+
+pub struct AlertPin {
+    pin: PinDriver<'static, AnyOutputPin, Output>,
+    // Remaining on/off edges of the current pattern
+    edges_remaining: u32,
+    last_edge: Instant,
+}
+
+impl AlertPin {
+    pub fn new(gpio: u8) -> Result<Self> {
+        if !is_pin_available(gpio) {
+            return Err(anyhow!("GPIO{} is reserved or invalid", gpio));
+        }
+        // Safety: the pin was checked against every GPIO claimed elsewhere in main
+        let mut pin = PinDriver::output(unsafe { AnyOutputPin::new(gpio as i32) })?;
+        pin.set_low()?;
+        log::info!("Alert output on GPIO{}", gpio);
+        Ok(Self { pin, edges_remaining: 0, last_edge: Instant::now() })
+    }
+
+    /// Start a pattern of `count` pulses; driven by `update()`
+    pub fn pulse(&mut self, count: u32) {
+        self.edges_remaining = count * 2;
+        self.last_edge = Instant::now() - PULSE_PERIOD;
+    }
+
+    /// Advance the pulse pattern; call from the main loop
+    pub fn update(&mut self) {
+        if self.edges_remaining == 0 || self.last_edge.elapsed() < PULSE_PERIOD {
+            return;
+        }
+        self.last_edge = Instant::now();
+        self.edges_remaining -= 1;
+        // Odd remaining count means we are entering the "on" half of a pulse
+        let _ = if self.edges_remaining % 2 == 1 { self.pin.set_high() } else { self.pin.set_low() };
+    }
+}
@@ -1,9 +1,12 @@
 use anyhow::Result;
 use esp_idf_hal::gpio::{PinDriver, Input, Pull, AnyIOPin};
 use std::time::{Duration, Instant};
+use crate::input::{InputEvent, InputSource, Key};
 
 const DEBOUNCE_TIME: Duration = Duration::from_millis(50);
 const LONG_PRESS_TIME: Duration = Duration::from_millis(1000);
+/// Holding both buttons this long starts the hardware self-test instead of shutdown
+const SELFTEST_HOLD_TIME: Duration = Duration::from_millis(5000);
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ButtonEvent {
@@ -15,7 +18,25 @@ pub enum ButtonEvent {
     Button2Release,
     Button2Click,
     Button2LongPress,
-    BothButtonsLongPress, // Shutdown trigger
+    BothButtonsLongPress, // Shutdown trigger (released after 1-5 s)
+    BothButtonsSelfTest,  // Held for 5 s
+}
+
+impl From<ButtonEvent> for InputEvent {
+    fn from(event: ButtonEvent) -> Self {
+        match event {
+            ButtonEvent::Button1Press => InputEvent::Press(Key::Boot),
+            ButtonEvent::Button1Release => InputEvent::Release(Key::Boot),
+            ButtonEvent::Button1Click => InputEvent::Click(Key::Boot),
+            ButtonEvent::Button1LongPress => InputEvent::LongPress(Key::Boot),
+            ButtonEvent::Button2Press => InputEvent::Press(Key::User),
+            ButtonEvent::Button2Release => InputEvent::Release(Key::User),
+            ButtonEvent::Button2Click => InputEvent::Click(Key::User),
+            ButtonEvent::Button2LongPress => InputEvent::LongPress(Key::User),
+            ButtonEvent::BothButtonsLongPress => InputEvent::ShutdownCombo,
+            ButtonEvent::BothButtonsSelfTest => InputEvent::SelfTestCombo,
+        }
+    }
 }
 
 pub struct ButtonManager {
@@ -23,6 +44,9 @@ pub struct ButtonManager {
     button2: PinDriver<'static, AnyIOPin, Input>,
     button1_state: ButtonState,
     button2_state: ButtonState,
+    /// When both buttons went down together, and whether the self-test fired
+    combo_since: Option<Instant>,
+    combo_fired: bool,
 }
 
 struct ButtonState {
@@ -60,6 +84,8 @@ impl ButtonManager {
             button2,
             button1_state: ButtonState::default(),
             button2_state: ButtonState::default(),
+            combo_since: None,
+            combo_fired: false,
         })
     }
 
@@ -68,21 +94,23 @@ impl ButtonManager {
         let button1_pressed = self.button1.is_low(); // Active low
         let button2_pressed = self.button2.is_low(); // Active low
         
-        // Check for both buttons long press (shutdown trigger)
+        // Both buttons: shutdown on release after 1-5 s, self-test once held 5 s.
+        // Individual clicks and long presses are suppressed for the combo.
         if button1_pressed && button2_pressed {
-            if let (Some(press1), Some(press2)) = (self.button1_state.press_time, self.button2_state.press_time) {
-                let now = Instant::now();
-                let duration1 = now.duration_since(press1);
-                let duration2 = now.duration_since(press2);
-                
-                // Both held for long press time and we haven't fired this event yet
-                if duration1 >= LONG_PRESS_TIME && duration2 >= LONG_PRESS_TIME 
-                    && !self.button1_state.long_press_fired && !self.button2_state.long_press_fired {
-                    self.button1_state.long_press_fired = true;
-                    self.button2_state.long_press_fired = true;
-                    return Some(ButtonEvent::BothButtonsLongPress);
+            if self.button1_state.press_time.is_some() && self.button2_state.press_time.is_some() {
+                let since = *self.combo_since.get_or_insert_with(Instant::now);
+                self.button1_state.long_press_fired = true;
+                self.button2_state.long_press_fired = true;
+                if !self.combo_fired && since.elapsed() >= SELFTEST_HOLD_TIME {
+                    self.combo_fired = true;
+                    return Some(ButtonEvent::BothButtonsSelfTest);
                 }
             }
+        } else if let Some(since) = self.combo_since.take() {
+            let fired = std::mem::take(&mut self.combo_fired);
+            if !fired && since.elapsed() >= LONG_PRESS_TIME {
+                return Some(ButtonEvent::BothButtonsLongPress);
+            }
         }
         
         // Check button 1
@@ -166,4 +194,10 @@ impl ButtonManager {
 
         None
     }
-}
\ No newline at end of file
+}
+
+impl InputSource for ButtonManager {
+    fn poll(&mut self) -> Option<InputEvent> {
+        ButtonManager::poll(self).map(InputEvent::from)
+    }
+}
@@ -0,0 +1,75 @@
+// Wall clock: SNTP time sync and local time-of-day via the POSIX TZ rules in newlib
+
+use anyhow::Result;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Anything before 2020-01-01 means SNTP has not synced yet
+const MIN_VALID_EPOCH_SECS: i64 = 1_577_836_800;
+
+/// Start background SNTP sync (pool.ntp.org). Safe to call multiple times.
+pub fn start_sntp() -> Result<()> {
+    static STARTED: AtomicBool = AtomicBool::new(false);
+    if STARTED.swap(true, Ordering::SeqCst) { return Ok(()); }
+
+    match esp_idf_svc::sntp::EspSntp::new_default() {
+        Ok(sntp) => {
+            // Keep the service running for the lifetime of the firmware
+            core::mem::forget(sntp);
+            log::info!("SNTP time sync started");
+            Ok(())
+        }
+        Err(e) => {
+            STARTED.store(false, Ordering::SeqCst);
+            Err(e.into())
+        }
+    }
+}
+
+/// Set the local timezone as a POSIX TZ string, e.g. `CET-1CEST,M3.5.0,M10.5.0/3`
+pub fn set_timezone(tz: &str) {
+    std::env::set_var("TZ", tz);
+    unsafe { esp_idf_sys::tzset(); }
+}
+
+/// Minutes since local midnight, or None if the clock has not been set
+pub fn local_minutes_of_day() -> Option<u16> {
+    let mut now = unsafe { esp_idf_sys::time(core::ptr::null_mut()) };
+    if (now as i64) < MIN_VALID_EPOCH_SECS {
+        return None;
+    }
+    let mut tm: esp_idf_sys::tm = unsafe { core::mem::zeroed() };
+    if unsafe { esp_idf_sys::localtime_r(&mut now, &mut tm) }.is_null() {
+        return None;
+    }
+    Some((tm.tm_hour * 60 + tm.tm_min) as u16)
+}
+
+/// Unix seconds now, or None if the clock has not been set
+pub fn now_epoch() -> Option<i64> {
+    let now = unsafe { esp_idf_sys::time(core::ptr::null_mut()) } as i64;
+    (now >= MIN_VALID_EPOCH_SECS).then_some(now)
+}
+
+/// Unix seconds of a local wall-clock time; DST is resolved by the TZ rules
+pub fn local_to_epoch(civil: crate::network::ics::Civil) -> i64 {
+    let mut tm: esp_idf_sys::tm = unsafe { core::mem::zeroed() };
+    tm.tm_year = civil.year - 1900;
+    tm.tm_mon = civil.month as i32 - 1;
+    tm.tm_mday = civil.day as i32;
+    tm.tm_hour = civil.hour as i32;
+    tm.tm_min = civil.minute as i32;
+    tm.tm_sec = civil.second as i32;
+    tm.tm_isdst = -1;
+    unsafe { esp_idf_sys::mktime(&mut tm) as i64 }
+}
+
+/// Local day (days since 1970-01-01) and minutes since midnight of `epoch`
+pub fn local_day_and_minutes(epoch: i64) -> Option<(i64, u16)> {
+    let mut time = epoch as esp_idf_sys::time_t;
+    let mut tm: esp_idf_sys::tm = unsafe { core::mem::zeroed() };
+    if unsafe { esp_idf_sys::localtime_r(&mut time, &mut tm) }.is_null() {
+        return None;
+    }
+    let day = crate::network::ics::days_from_civil(tm.tm_year + 1900, (tm.tm_mon + 1) as u8, tm.tm_mday as u8);
+    Some((day, (tm.tm_hour * 60 + tm.tm_min) as u16))
+}
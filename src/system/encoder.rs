@@ -0,0 +1,156 @@
+// Optional quadrature rotary encoder with push button. Rotation is counted in
+// hardware by a PCNT unit and published to the input bus like the buttons.
+
+#[cfg(target_os = "espidf")]
+use {
+    anyhow::{anyhow, Result},
+    esp_idf_hal::gpio::{AnyIOPin, Input, PinDriver, Pull},
+    esp_idf_sys::*,
+    std::time::{Duration, Instant},
+    crate::input::{InputEvent, InputSource},
+    crate::network::validators::is_pin_available,
+};
+
+/// Quadrature edges per mechanical detent (both edges of both channels)
+const COUNTS_PER_DETENT: i32 = 4;
+#[cfg(target_os = "espidf")]
+const COUNT_LIMIT: i32 = 1000;
+#[cfg(target_os = "espidf")]
+const GLITCH_FILTER_NS: u32 = 1000;
+#[cfg(target_os = "espidf")]
+const DEBOUNCE_TIME: Duration = Duration::from_millis(50);
+
+/// Turns raw quadrature counts into whole detent steps, keeping the remainder
+#[derive(Debug, Default)]
+pub struct StepCounter {
+    remainder: i32,
+}
+
+impl StepCounter {
+    pub fn add(&mut self, counts: i32) -> i32 {
+        self.remainder += counts;
+        let steps = self.remainder / COUNTS_PER_DETENT;
+        self.remainder -= steps * COUNTS_PER_DETENT;
+        steps
+    }
+}
+
+#[cfg(target_os = "espidf")]
+pub struct RotaryEncoder {
+    unit: pcnt_unit_handle_t,
+    steps: StepCounter,
+    button: Option<PinDriver<'static, AnyIOPin, Input>>,
+    button_pressed: bool,
+    last_button_change: Instant,
+}
+
+#[cfg(target_os = "espidf")]
+impl RotaryEncoder {
+    pub fn new(a: u8, b: u8, button_gpio: Option<u8>) -> Result<Self> {
+        for gpio in [Some(a), Some(b), button_gpio].into_iter().flatten() {
+            if !is_pin_available(gpio) {
+                return Err(anyhow!("GPIO{} is reserved or invalid", gpio));
+            }
+        }
+
+        let unit = unsafe { Self::init_pcnt(a as i32, b as i32)? };
+        let button = match button_gpio {
+            Some(gpio) => {
+                // Safety: the pin was checked against every GPIO claimed elsewhere in main
+                let mut pin = PinDriver::input(unsafe { AnyIOPin::new(gpio as i32) })?;
+                pin.set_pull(Pull::Up)?;
+                Some(pin)
+            }
+            None => None,
+        };
+        log::info!("Rotary encoder on GPIO{}/GPIO{}, button {:?}", a, b, button_gpio);
+        Ok(Self { unit, steps: StepCounter::default(), button, button_pressed: false, last_button_change: Instant::now() })
+    }
+
+    unsafe fn init_pcnt(a: i32, b: i32) -> Result<pcnt_unit_handle_t> {
+        let check = |result: esp_err_t, what: &str| {
+            if result == ESP_OK { Ok(()) } else { Err(anyhow!("{} failed: {}", what, result)) }
+        };
+
+        let unit_config = pcnt_unit_config_t {
+            low_limit: -COUNT_LIMIT,
+            high_limit: COUNT_LIMIT,
+            ..Default::default()
+        };
+        let mut unit: pcnt_unit_handle_t = core::ptr::null_mut();
+        check(pcnt_new_unit(&unit_config, &mut unit), "pcnt_new_unit")?;
+        let filter = pcnt_glitch_filter_config_t { max_glitch_ns: GLITCH_FILTER_NS };
+        check(pcnt_unit_set_glitch_filter(unit, &filter), "pcnt glitch filter")?;
+
+        // Standard x4 quadrature decoding: each channel counts edges on one pin,
+        // with the other pin's level giving the direction
+        for (edge, level, rising, falling) in [
+            (a, b, pcnt_channel_edge_action_t_PCNT_CHANNEL_EDGE_ACTION_DECREASE, pcnt_channel_edge_action_t_PCNT_CHANNEL_EDGE_ACTION_INCREASE),
+            (b, a, pcnt_channel_edge_action_t_PCNT_CHANNEL_EDGE_ACTION_INCREASE, pcnt_channel_edge_action_t_PCNT_CHANNEL_EDGE_ACTION_DECREASE),
+        ] {
+            let chan_config = pcnt_chan_config_t { edge_gpio_num: edge, level_gpio_num: level, ..Default::default() };
+            let mut chan: pcnt_channel_handle_t = core::ptr::null_mut();
+            check(pcnt_new_channel(unit, &chan_config, &mut chan), "pcnt_new_channel")?;
+            check(pcnt_channel_set_edge_action(chan, rising, falling), "pcnt edge action")?;
+            check(pcnt_channel_set_level_action(
+                chan,
+                pcnt_channel_level_action_t_PCNT_CHANNEL_LEVEL_ACTION_KEEP,
+                pcnt_channel_level_action_t_PCNT_CHANNEL_LEVEL_ACTION_INVERSE,
+            ), "pcnt level action")?;
+        }
+        // Most bare encoders rely on the MCU pull-ups
+        gpio_set_pull_mode(a, gpio_pull_mode_t_GPIO_PULLUP_ONLY);
+        gpio_set_pull_mode(b, gpio_pull_mode_t_GPIO_PULLUP_ONLY);
+
+        check(pcnt_unit_enable(unit), "pcnt_unit_enable")?;
+        check(pcnt_unit_clear_count(unit), "pcnt_unit_clear_count")?;
+        check(pcnt_unit_start(unit), "pcnt_unit_start")?;
+        Ok(unit)
+    }
+}
+
+#[cfg(target_os = "espidf")]
+impl InputSource for RotaryEncoder {
+    /// Next rotation or press event
+    fn poll(&mut self) -> Option<InputEvent> {
+        if let Some(ref pin) = self.button {
+            let pressed = pin.is_low(); // Active low
+            if pressed != self.button_pressed && self.last_button_change.elapsed() >= DEBOUNCE_TIME {
+                self.button_pressed = pressed;
+                self.last_button_change = Instant::now();
+                if pressed {
+                    return Some(InputEvent::Select);
+                }
+            }
+        }
+
+        let mut count = 0;
+        if unsafe { pcnt_unit_get_count(self.unit, &mut count) } != ESP_OK || count == 0 {
+            return None;
+        }
+        // Clearing right after the read can drop an edge in between, which is
+        // harmless at hand-turning speeds
+        unsafe { pcnt_unit_clear_count(self.unit) };
+        match self.steps.add(count) {
+            0 => None,
+            steps => Some(InputEvent::Rotate(steps)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_keep_partial_detents() {
+        let mut counter = StepCounter::default();
+        assert_eq!(counter.add(3), 0);
+        assert_eq!(counter.add(1), 1);
+        assert_eq!(counter.add(9), 2);
+        // One count left over carries into the reverse turn
+        assert_eq!(counter.add(-5), -1);
+        assert_eq!(counter.add(-3), 0);
+        assert_eq!(counter.add(-1), -1);
+    }
+}
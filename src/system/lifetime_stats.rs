@@ -0,0 +1,178 @@
+// Lifetime counters: boots, OTA updates applied, frames rendered, button
+// presses and WiFi reconnects, summed over the whole life of the device.
+// The main loop counts in RAM; the totals go to NVS in one batch every
+// SAVE_INTERVAL and at shutdown, so counting every frame costs no flash wear.
+// An OTA counts as applied when the app boots from a different slot than last time.
+
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "espidf")]
+use crate::storage::kv;
+#[cfg(target_os = "espidf")]
+use std::sync::Mutex;
+#[cfg(target_os = "espidf")]
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "espidf")]
+const NVS_NAMESPACE: &str = "lifetime";
+#[cfg(target_os = "espidf")]
+const NVS_KEY_STATS: &str = "stats";
+#[cfg(target_os = "espidf")]
+const NVS_KEY_SLOT: &str = "last_slot";
+/// At most one write per 10 minutes; a crash loses at most that much counting
+#[cfg(target_os = "espidf")]
+const SAVE_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Counters fed from the main loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Counter {
+    FramesRendered,
+    ButtonPresses,
+    WifiReconnects,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LifetimeStats {
+    #[serde(default)]
+    pub boots: u32,
+    #[serde(default)]
+    pub ota_updates: u32,
+    #[serde(default)]
+    pub frames_rendered: u64,
+    #[serde(default)]
+    pub button_presses: u64,
+    #[serde(default)]
+    pub wifi_reconnects: u64,
+}
+
+impl LifetimeStats {
+    /// Count this boot; `running_slot` is the app partition label, `last_slot`
+    /// the one saved by the previous boot (None on the first boot with stats)
+    pub fn record_boot(&mut self, last_slot: Option<&str>, running_slot: &str) {
+        self.boots = self.boots.saturating_add(1);
+        if last_slot.is_some_and(|slot| slot != running_slot) {
+            self.ota_updates = self.ota_updates.saturating_add(1);
+        }
+    }
+
+    pub fn add(&mut self, counter: Counter, n: u64) {
+        let total = match counter {
+            Counter::FramesRendered => &mut self.frames_rendered,
+            Counter::ButtonPresses => &mut self.button_presses,
+            Counter::WifiReconnects => &mut self.wifi_reconnects,
+        };
+        *total = total.saturating_add(n);
+    }
+}
+
+#[cfg(target_os = "espidf")]
+struct Lifetime {
+    stats: LifetimeStats,
+    dirty: bool,
+    last_save: Instant,
+}
+
+#[cfg(target_os = "espidf")]
+static LIFETIME: Mutex<Option<Lifetime>> = Mutex::new(None);
+
+/// Load the totals and count this boot. Call once at startup, after `kv::init`.
+#[cfg(target_os = "espidf")]
+pub fn init() {
+    let running = crate::ota::partitions::running_label();
+    let mut stats = LifetimeStats::default();
+    match kv::open(NVS_NAMESPACE) {
+        Ok(mut store) => {
+            stats = store.get(NVS_KEY_STATS).unwrap_or_default();
+            let last_slot: Option<String> = store.get(NVS_KEY_SLOT);
+            stats.record_boot(last_slot.as_deref(), &running);
+            // Boots are saved straight away: a crash loop should still count
+            let result = store.stage(NVS_KEY_STATS, &stats)
+                .and_then(|_| store.stage(NVS_KEY_SLOT, &running))
+                .and_then(|_| store.flush());
+            if let Err(e) = result {
+                log::warn!("Failed to save lifetime stats: {:?}", e);
+            }
+        }
+        Err(e) => log::warn!("Lifetime stats: NVS unavailable, counting this boot only: {:?}", e),
+    }
+    log::info!("Lifetime stats: boot #{}, {} OTA updates, {} frames", stats.boots, stats.ota_updates, stats.frames_rendered);
+
+    if let Ok(mut slot) = LIFETIME.lock() {
+        *slot = Some(Lifetime { stats, dirty: false, last_save: Instant::now() });
+    }
+}
+
+/// Add `n` to a counter; saved with the next batch
+#[cfg(target_os = "espidf")]
+pub fn record(counter: Counter, n: u64) {
+    if n == 0 {
+        return;
+    }
+    let Ok(mut slot) = LIFETIME.lock() else { return };
+    if let Some(lifetime) = slot.as_mut() {
+        lifetime.stats.add(counter, n);
+        lifetime.dirty = true;
+    }
+}
+
+/// Current totals, None before `init`
+#[cfg(target_os = "espidf")]
+pub fn current() -> Option<LifetimeStats> {
+    LIFETIME.lock().ok().and_then(|slot| slot.as_ref().map(|l| l.stats))
+}
+
+/// Write the totals if they changed and SAVE_INTERVAL has passed (main loop)
+#[cfg(target_os = "espidf")]
+pub fn save_if_due() {
+    save_when(|l| l.last_save.elapsed() >= SAVE_INTERVAL);
+}
+
+/// Write any unsaved counts now (shutdown, deep sleep)
+#[cfg(target_os = "espidf")]
+pub fn save() {
+    save_when(|_| true);
+}
+
+#[cfg(target_os = "espidf")]
+fn save_when(due: impl Fn(&Lifetime) -> bool) {
+    let Ok(mut slot) = LIFETIME.lock() else { return };
+    let Some(lifetime) = slot.as_mut() else { return };
+    if !lifetime.dirty || !due(lifetime) {
+        return;
+    }
+    lifetime.last_save = Instant::now();
+    match kv::open(NVS_NAMESPACE).and_then(|mut store| store.set(NVS_KEY_STATS, &lifetime.stats)) {
+        Ok(()) => lifetime.dirty = false,
+        Err(e) => log::warn!("Failed to save lifetime stats: {:?}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boots_and_ota_updates() {
+        let mut stats = LifetimeStats::default();
+        stats.record_boot(None, "ota_0");
+        stats.record_boot(Some("ota_0"), "ota_0");
+        assert_eq!((stats.boots, stats.ota_updates), (2, 0));
+        stats.record_boot(Some("ota_0"), "ota_1");
+        assert_eq!((stats.boots, stats.ota_updates), (3, 1));
+    }
+
+    #[test]
+    fn test_counters_and_stored_format() {
+        let mut stats = LifetimeStats::default();
+        stats.add(Counter::FramesRendered, 600);
+        stats.add(Counter::FramesRendered, 400);
+        stats.add(Counter::ButtonPresses, 3);
+        stats.add(Counter::WifiReconnects, 1);
+        stats.add(Counter::ButtonPresses, u64::MAX);
+        assert_eq!(stats.frames_rendered, 1000);
+        assert_eq!(stats.button_presses, u64::MAX);
+
+        // Counters added by later firmware start at zero
+        let stored: LifetimeStats = serde_json::from_str(r#"{"boots": 7, "frames_rendered": 42}"#).unwrap();
+        assert_eq!(stored, LifetimeStats { boots: 7, frames_rendered: 42, ..Default::default() });
+    }
+}
@@ -1,10 +1,16 @@
 pub mod button;
+pub mod encoder;
 pub mod info;
 pub mod reset;
 pub mod uptime_tracker;
 pub mod shutdown;
+pub mod alert_pin;
+pub mod clock;
+pub mod selftest;
+pub mod lifetime_stats;
+pub mod safe_mode;
 
-pub use button::{ButtonManager, ButtonEvent};
+pub use button::ButtonManager;
 pub use info::SystemInfo;
 // pub use reset::perform_deep_reset; // Unused - kept for future use
 pub use uptime_tracker::UptimeTracker;
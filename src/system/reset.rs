@@ -19,3 +19,21 @@ pub fn get_reset_reason() -> &'static str {
         _ => "Unknown reason code",
     }
 }
+
+/// True if the last reset was the brown-out detector tripping (supply sagged)
+pub fn last_reset_was_brownout() -> bool {
+    unsafe { esp_idf_sys::esp_reset_reason() == esp_idf_sys::esp_reset_reason_t_ESP_RST_BROWNOUT }
+}
+
+/// True if the last reset was caused by a panic, watchdog or brown-out
+pub fn last_reset_was_crash() -> bool {
+    let reason = unsafe { esp_idf_sys::esp_reset_reason() };
+    matches!(
+        reason,
+        esp_idf_sys::esp_reset_reason_t_ESP_RST_PANIC
+            | esp_idf_sys::esp_reset_reason_t_ESP_RST_INT_WDT
+            | esp_idf_sys::esp_reset_reason_t_ESP_RST_TASK_WDT
+            | esp_idf_sys::esp_reset_reason_t_ESP_RST_WDT
+            | esp_idf_sys::esp_reset_reason_t_ESP_RST_BROWNOUT
+    )
+}
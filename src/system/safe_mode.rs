@@ -0,0 +1,132 @@
+// Crash-loop guard. A boot that ends in a panic, watchdog or brown-out before
+// STABLE_SECS of uptime counts as an early crash; after CRASH_LIMIT of them in
+// a row the next boot is a safe mode boot that skips the optional subsystems
+// (Core 1 tasks, network pollers, MQTT actions, extra peripherals, the esp_lcd
+// fast path) but keeps WiFi, the web server and OTA so a fixed image can be
+// uploaded. The count lives in NVS and is cleared once a boot stays up.
+
+use serde::{Deserialize, Serialize};
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(target_os = "espidf")]
+use crate::storage::kv;
+
+/// Uptime after which a boot counts as stable
+pub const STABLE_SECS: u64 = 60;
+/// Early crashes in a row that trigger safe mode
+pub const CRASH_LIMIT: u8 = 3;
+
+#[cfg(target_os = "espidf")]
+const NVS_NAMESPACE: &str = "safe_mode";
+#[cfg(target_os = "espidf")]
+const NVS_KEY_GUARD: &str = "guard";
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+#[cfg(target_os = "espidf")]
+static STABLE: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BootGuard {
+    /// Consecutive boots that crashed before reaching STABLE_SECS
+    #[serde(default)]
+    pub early_crashes: u8,
+    /// Set at boot and cleared at STABLE_SECS; still set on the next boot if
+    /// this one ended early
+    #[serde(default)]
+    pub unstable: bool,
+}
+
+impl BootGuard {
+    /// Count this boot; `crashed` is whether the reset that started it was a
+    /// crash. Returns whether to boot in safe mode.
+    pub fn record_boot(&mut self, crashed: bool) -> bool {
+        self.early_crashes = if self.unstable && crashed { self.early_crashes.saturating_add(1) } else { 0 };
+        self.unstable = true;
+        self.early_crashes >= CRASH_LIMIT
+    }
+
+    pub fn mark_stable(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Whether this boot is in safe mode
+pub fn active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Count this boot and decide on safe mode. Call once at startup, after `kv::init`.
+#[cfg(target_os = "espidf")]
+pub fn init() -> bool {
+    let mut guard = BootGuard::default();
+    let safe = match kv::open(NVS_NAMESPACE) {
+        Ok(mut store) => {
+            guard = store.get(NVS_KEY_GUARD).unwrap_or_default();
+            let safe = guard.record_boot(super::reset::last_reset_was_crash());
+            if let Err(e) = store.set(NVS_KEY_GUARD, &guard) {
+                log::warn!("Failed to save boot guard: {:?}", e);
+            }
+            safe
+        }
+        Err(e) => {
+            log::warn!("Boot guard: NVS unavailable, crash loops go undetected: {:?}", e);
+            false
+        }
+    };
+    if safe {
+        log::error!("SAFE MODE: {} crashes within {}s of boot; optional subsystems disabled until a boot stays up",
+            guard.early_crashes, STABLE_SECS);
+    }
+    ACTIVE.store(safe, Ordering::Relaxed);
+    safe
+}
+
+/// Clear the crash count once this boot has been up STABLE_SECS. Cheap to call every second.
+#[cfg(target_os = "espidf")]
+pub fn observe_uptime(uptime_secs: u64) {
+    if uptime_secs < STABLE_SECS || STABLE.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    match kv::open(NVS_NAMESPACE) {
+        Ok(mut store) => {
+            let mut guard: BootGuard = store.get(NVS_KEY_GUARD).unwrap_or_default();
+            guard.mark_stable();
+            if let Err(e) = store.set(NVS_KEY_GUARD, &guard) {
+                log::warn!("Failed to clear boot guard: {:?}", e);
+            }
+        }
+        Err(e) => log::warn!("Boot guard: NVS unavailable: {:?}", e),
+    }
+    log::info!("Boot stable after {}s; crash count cleared", STABLE_SECS);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crash_loop_enters_safe_mode() {
+        let mut guard = BootGuard::default();
+        assert!(!guard.record_boot(false));
+        assert!(!guard.record_boot(true));
+        assert!(!guard.record_boot(true));
+        assert!(guard.record_boot(true));
+        assert_eq!(guard.early_crashes, CRASH_LIMIT);
+        // Safe mode stays on while the crashes continue
+        assert!(guard.record_boot(true));
+    }
+
+    #[test]
+    fn test_stable_boot_or_clean_reset_clears_count() {
+        let mut guard = BootGuard::default();
+        guard.record_boot(true);
+        guard.record_boot(true);
+        guard.mark_stable();
+        // A crash after a stable boot is not an early crash
+        assert!(!guard.record_boot(true));
+        assert_eq!(guard.early_crashes, 0);
+
+        guard.record_boot(true);
+        assert!(!guard.record_boot(false), "a clean reset, e.g. after OTA");
+        assert_eq!(guard.early_crashes, 0);
+    }
+}
@@ -0,0 +1,377 @@
+// Hardware self-test for board bring-up: display colour bars and a pixel
+// sweep, both buttons, battery ADC, temperature sensor, WiFi scan, SPIFFS and
+// PSRAM. Started by holding both buttons for 5 s or POST /api/selftest; the
+// main loop runs it (it owns the display, sensors and buttons) and the result
+// is shown on screen and served as JSON from GET /api/selftest.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// Battery ADC readings outside this range mean a broken divider or ADC
+pub const BATTERY_MV_RANGE: (u16, u16) = (2800, 4500);
+/// The USB rail through the divider reads up to here
+pub const USB_MV_MAX: u16 = 5300;
+/// Die temperature a working sensor can plausibly report
+pub const TEMPERATURE_RANGE_C: (f32, f32) = (-20.0, 110.0);
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static LATEST: Mutex<Option<SelfTestReport>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Pass,
+    Fail,
+    /// Not applicable on this board/run
+    Skip,
+}
+
+impl Outcome {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Pass => "PASS",
+            Self::Fail => "FAIL",
+            Self::Skip => "SKIP",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub outcome: Outcome,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SelfTestReport {
+    /// True when no check failed
+    pub passed: bool,
+    pub duration_ms: u64,
+    pub checks: Vec<CheckResult>,
+}
+
+impl SelfTestReport {
+    pub fn record(&mut self, name: &'static str, (outcome, detail): (Outcome, String)) {
+        log::info!("Self-test: {} {} ({})", name, outcome.label(), detail);
+        self.checks.push(CheckResult { name, outcome, detail });
+        self.passed = self.checks.iter().all(|c| c.outcome != Outcome::Fail);
+    }
+}
+
+/// Battery divider reading; on USB without a cell the rail voltage is seen instead
+pub fn check_battery_mv(mv: u16, on_usb: bool) -> (Outcome, String) {
+    let (min, max) = BATTERY_MV_RANGE;
+    let max = if on_usb { USB_MV_MAX } else { max };
+    let outcome = if (min..=max).contains(&mv) { Outcome::Pass } else { Outcome::Fail };
+    (outcome, format!("{} mV", mv))
+}
+
+pub fn check_temperature(celsius: f32) -> (Outcome, String) {
+    let (min, max) = TEMPERATURE_RANGE_C;
+    let outcome = if celsius.is_finite() && celsius > min && celsius < max { Outcome::Pass } else { Outcome::Fail };
+    (outcome, format!("{:.1} C", celsius))
+}
+
+/// Walking-ones and address-in-address patterns; true if every word reads back
+pub fn memory_pattern_ok(words: &mut [u32]) -> bool {
+    for bit in 0..32 {
+        let pattern = 1u32 << bit;
+        words.iter_mut().for_each(|w| *w = pattern);
+        if words.iter().any(|&w| w != pattern) {
+            return false;
+        }
+    }
+    for (i, w) in words.iter_mut().enumerate() {
+        *w = !(i as u32);
+    }
+    words.iter().enumerate().all(|(i, &w)| w == !(i as u32))
+}
+
+/// GET /api/selftest
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestStatus {
+    pub running: bool,
+    pub report: Option<SelfTestReport>,
+}
+
+/// Ask the main loop to run the self-test; false if one is already running
+pub fn request() -> bool {
+    if RUNNING.load(Ordering::Relaxed) {
+        return false;
+    }
+    REQUESTED.store(true, Ordering::Relaxed);
+    true
+}
+
+/// Consume a pending request (main loop)
+pub fn take_request() -> bool {
+    REQUESTED.swap(false, Ordering::Relaxed)
+}
+
+pub fn is_running() -> bool {
+    RUNNING.load(Ordering::Relaxed) || REQUESTED.load(Ordering::Relaxed)
+}
+
+/// Last finished report
+pub fn latest() -> Option<SelfTestReport> {
+    LATEST.lock().ok().and_then(|r| r.clone())
+}
+
+pub fn status() -> SelfTestStatus {
+    SelfTestStatus { running: is_running(), report: latest() }
+}
+
+#[cfg(target_os = "espidf")]
+pub use self::device::run;
+
+#[cfg(target_os = "espidf")]
+mod device {
+    use super::*;
+    use anyhow::Result;
+    use esp_idf_hal::delay::FreeRtos;
+    use std::time::{Duration, Instant};
+    use crate::display::{colors, DisplayManager};
+    use crate::input::{InputEvent, InputSource, Key};
+    use crate::sensors::SensorManager;
+    use crate::ui::layout::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+    /// Time allowed for each button press
+    const BUTTON_TIMEOUT: Duration = Duration::from_secs(10);
+    /// How long the summary stays up without a button press
+    const SUMMARY_TIMEOUT: Duration = Duration::from_secs(60);
+    const PSRAM_TEST_BYTES: usize = 64 * 1024;
+    const SPIFFS_LABEL: &core::ffi::CStr = c"storage";
+    const SPIFFS_BASE: &core::ffi::CStr = c"/spiffs";
+
+    const BARS: [u16; 8] = [
+        colors::WHITE, colors::YELLOW, 0x07FF, 0x07E0, 0xF81F, 0xF800, 0x001F, colors::BLACK,
+    ];
+
+    /// Run every check, show the summary until a button press and publish
+    /// the report. Blocks the main loop for roughly 10-30 s.
+    pub fn run(
+        display: &mut DisplayManager,
+        sensors: &mut SensorManager,
+        inputs: &mut [Box<dyn InputSource>],
+    ) -> SelfTestReport {
+        RUNNING.store(true, Ordering::Relaxed);
+        log::warn!("Self-test started");
+        let started = Instant::now();
+        let mut report = SelfTestReport::default();
+
+        report.record("display", check_display(display));
+        report.record("button_boot", check_button(display, inputs, Key::Boot, "BOOT"));
+        report.record("button_user", check_button(display, inputs, Key::User, "USER"));
+        match sensors.sample() {
+            Ok(data) => {
+                report.record("battery_adc", check_battery_mv(data._battery_voltage, data._is_on_usb));
+                report.record("temperature", check_temperature(data._temperature));
+            }
+            Err(e) => {
+                report.record("battery_adc", (Outcome::Fail, format!("{:?}", e)));
+                report.record("temperature", (Outcome::Fail, format!("{:?}", e)));
+            }
+        }
+        feed_watchdog();
+        report.record("wifi_scan", check_wifi_scan());
+        feed_watchdog();
+        report.record("spiffs", check_spiffs());
+        report.record("psram", check_psram());
+        report.duration_ms = started.elapsed().as_millis() as u64;
+
+        if let Ok(mut latest) = LATEST.lock() {
+            *latest = Some(report.clone());
+        }
+        RUNNING.store(false, Ordering::Relaxed);
+        log::warn!("Self-test finished: {}", if report.passed { "PASS" } else { "FAIL" });
+
+        if let Err(e) = show_summary(display, &report) {
+            log::warn!("Self-test: failed to draw summary: {:?}", e);
+        }
+        wait_for_key(inputs, None, SUMMARY_TIMEOUT);
+        report
+    }
+
+    fn feed_watchdog() {
+        unsafe { esp_idf_sys::esp_task_wdt_reset(); }
+    }
+
+    fn check_display(display: &mut DisplayManager) -> (Outcome, String) {
+        if !display.is_ready() {
+            return (Outcome::Fail, "panel did not answer".into());
+        }
+        let result = (|| -> Result<()> {
+            let bar_width = SCREEN_WIDTH / BARS.len() as u16;
+            for (i, &color) in BARS.iter().enumerate() {
+                display.fill_rect(i as u16 * bar_width, 0, bar_width, SCREEN_HEIGHT, color)?;
+            }
+            display.flush()?;
+            FreeRtos::delay_ms(1500);
+            feed_watchdog();
+            // Sweep a white column across black to expose dead pixel columns
+            for x in (0..SCREEN_WIDTH).step_by(12) {
+                display.clear(colors::BLACK)?;
+                display.fill_rect(x, 0, 4, SCREEN_HEIGHT, colors::WHITE)?;
+                display.flush()?;
+                feed_watchdog();
+            }
+            Ok(())
+        })();
+        match result {
+            Ok(()) => (Outcome::Pass, "colour bars and sweep drawn (check visually)".into()),
+            Err(e) => (Outcome::Fail, format!("{:?}", e)),
+        }
+    }
+
+    fn check_button(display: &mut DisplayManager, inputs: &mut [Box<dyn InputSource>], key: Key, label: &str) -> (Outcome, String) {
+        let _ = prompt(display, &format!("Press {}", label));
+        if wait_for_key(inputs, Some(key), BUTTON_TIMEOUT) {
+            (Outcome::Pass, "pressed".into())
+        } else {
+            (Outcome::Fail, format!("no press within {}s", BUTTON_TIMEOUT.as_secs()))
+        }
+    }
+
+    fn prompt(display: &mut DisplayManager, text: &str) -> Result<()> {
+        display.clear(colors::BLACK)?;
+        display.draw_text_centered(40, "SELF-TEST", colors::ACCENT_ORANGE, None, 2)?;
+        display.draw_text_centered(90, text, colors::WHITE, None, 2)?;
+        display.flush()?;
+        Ok(())
+    }
+
+    /// Wait for a press of `key` (any key if None); true if it came in time
+    fn wait_for_key(inputs: &mut [Box<dyn InputSource>], key: Option<Key>, timeout: Duration) -> bool {
+        let start = Instant::now();
+        while start.elapsed() < timeout {
+            for source in inputs.iter_mut() {
+                if let Some(InputEvent::Press(pressed) | InputEvent::Click(pressed)) = source.poll() {
+                    if key.is_none_or(|k| k == pressed) {
+                        return true;
+                    }
+                }
+            }
+            feed_watchdog();
+            FreeRtos::delay_ms(20);
+        }
+        false
+    }
+
+    fn check_wifi_scan() -> (Outcome, String) {
+        match crate::network::wifi_scan::run_scan() {
+            Ok(networks) if !networks.is_empty() => (Outcome::Pass, format!("{} networks", networks.len())),
+            Ok(_) => (Outcome::Fail, "no networks found".into()),
+            Err(e) => (Outcome::Fail, e.to_string()),
+        }
+    }
+
+    fn check_spiffs() -> (Outcome, String) {
+        use esp_idf_sys::*;
+        unsafe {
+            // Mount temporarily unless the file manager already has it
+            let mounted = esp_spiffs_mounted(SPIFFS_LABEL.as_ptr());
+            if !mounted {
+                let conf = esp_vfs_spiffs_conf_t {
+                    base_path: SPIFFS_BASE.as_ptr(),
+                    partition_label: SPIFFS_LABEL.as_ptr(),
+                    max_files: 2,
+                    format_if_mount_failed: false,
+                };
+                let err = esp_vfs_spiffs_register(&conf);
+                if err != ESP_OK {
+                    return (Outcome::Fail, format!("mount failed ({})", err));
+                }
+            }
+            let (mut total, mut used) = (0usize, 0usize);
+            let err = esp_spiffs_info(SPIFFS_LABEL.as_ptr(), &mut total, &mut used);
+            if !mounted {
+                esp_vfs_spiffs_unregister(SPIFFS_LABEL.as_ptr());
+            }
+            if err == ESP_OK {
+                (Outcome::Pass, format!("{} of {} KB used", used / 1024, total / 1024))
+            } else {
+                (Outcome::Fail, format!("info failed ({})", err))
+            }
+        }
+    }
+
+    fn check_psram() -> (Outcome, String) {
+        use esp_idf_sys::*;
+        if !crate::psram::PsramAllocator::is_available() {
+            return (Outcome::Fail, "not detected".into());
+        }
+        unsafe {
+            let ptr = heap_caps_malloc(PSRAM_TEST_BYTES, MALLOC_CAP_SPIRAM) as *mut u32;
+            if ptr.is_null() {
+                return (Outcome::Fail, "allocation failed".into());
+            }
+            let words = core::slice::from_raw_parts_mut(ptr, PSRAM_TEST_BYTES / 4);
+            let ok = memory_pattern_ok(words);
+            heap_caps_free(ptr as *mut core::ffi::c_void);
+            let size_kb = crate::psram::PsramAllocator::get_size() / 1024;
+            if ok {
+                (Outcome::Pass, format!("{} KB, {} KB pattern ok", size_kb, PSRAM_TEST_BYTES / 1024))
+            } else {
+                (Outcome::Fail, "pattern mismatch".into())
+            }
+        }
+    }
+
+    fn show_summary(display: &mut DisplayManager, report: &SelfTestReport) -> Result<()> {
+        display.clear(colors::BLACK)?;
+        let (title, color) = if report.passed { ("SELF-TEST PASS", colors::PRIMARY_GREEN) } else { ("SELF-TEST FAIL", colors::PRIMARY_RED) };
+        display.draw_text_centered(6, title, color, None, 2)?;
+        for (i, check) in report.checks.iter().enumerate() {
+            let y = 30 + i as u16 * 14;
+            let color = match check.outcome {
+                Outcome::Pass => colors::PRIMARY_GREEN,
+                Outcome::Fail => colors::PRIMARY_RED,
+                Outcome::Skip => colors::TEXT_SECONDARY,
+            };
+            display.draw_text(10, y, check.outcome.label(), color, None, 1)?;
+            display.draw_text(44, y, check.name, colors::WHITE, None, 1)?;
+            let detail: String = check.detail.chars().filter(|c| c.is_ascii()).take(30).collect();
+            display.draw_text(124, y, &detail, colors::TEXT_SECONDARY, None, 1)?;
+        }
+        display.draw_text_centered(SCREEN_HEIGHT - 12, "Press any button", colors::TEXT_SECONDARY, None, 1)?;
+        display.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plausibility_checks() {
+        assert_eq!(check_battery_mv(3900, false).0, Outcome::Pass);
+        assert_eq!(check_battery_mv(0, false).0, Outcome::Fail);
+        assert_eq!(check_battery_mv(5000, false).0, Outcome::Fail);
+        assert_eq!(check_battery_mv(5000, true).0, Outcome::Pass);
+        assert_eq!(check_temperature(42.0).0, Outcome::Pass);
+        assert_eq!(check_temperature(f32::NAN).0, Outcome::Fail);
+        assert_eq!(check_temperature(-40.0).0, Outcome::Fail);
+    }
+
+    #[test]
+    fn test_report_fails_on_any_failure() {
+        let mut report = SelfTestReport::default();
+        report.record("a", (Outcome::Pass, String::new()));
+        report.record("b", (Outcome::Skip, String::new()));
+        assert!(report.passed);
+        report.record("c", (Outcome::Fail, String::new()));
+        assert!(!report.passed);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["checks"][2]["outcome"], "fail");
+    }
+
+    #[test]
+    fn test_memory_pattern() {
+        let mut words = vec![0u32; 256];
+        assert!(memory_pattern_ok(&mut words));
+    }
+}
@@ -1,7 +1,106 @@
 /// Graceful shutdown management for ESP32 services
-use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+///
+/// Restarts go through `request_restart` instead of calling esp_restart from
+/// whichever thread wants one: the main loop shows a "restarting" screen, runs
+/// the ShutdownManager (SSE and telnet clients are told and disconnected),
+/// stops the web server, then `restart_now` saves pending NVS writes and
+/// flushes the logs before the reset.
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+use std::time::{Duration, Instant};
 use anyhow::Result;
 
+/// Time for the response that triggered a restart to reach the client
+pub const RESPONSE_GRACE: Duration = Duration::from_millis(1_000);
+/// Restart without the main loop if it hasn't taken over by then
+const FALLBACK_AFTER: Duration = Duration::from_secs(10);
+
+/// Why the device restarts; logged and shown on the restart screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartReason {
+    /// /api/restart or /api/system/restart
+    Requested,
+    OtaComplete,
+    Rollback,
+    /// New WiFi credentials over BLE
+    Provisioned,
+    /// Boot partition switched from factory to ota_0
+    BootSlotRepair,
+    /// The UI loop returned an error
+    Crash,
+}
+
+impl RestartReason {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Requested => "Restart requested",
+            Self::OtaComplete => "Update installed",
+            Self::Rollback => "Rolling back",
+            Self::Provisioned => "WiFi updated",
+            Self::BootSlotRepair => "Switching boot slot",
+            Self::Crash => "Recovering from error",
+        }
+    }
+}
+
+struct RestartRequest {
+    reason: RestartReason,
+    at: Instant,
+}
+
+static RESTART: Mutex<Option<RestartRequest>> = Mutex::new(None);
+
+/// Ask for an orderly restart; the main loop carries it out. Safe to call from
+/// HTTP handlers right after writing their response. Later requests are ignored.
+pub fn request_restart(reason: RestartReason) {
+    let Ok(mut slot) = RESTART.lock() else { return restart_now(reason) };
+    if slot.is_some() {
+        return;
+    }
+    *slot = Some(RestartRequest { reason, at: Instant::now() });
+    drop(slot);
+    log::warn!("Restart requested: {}", reason.label());
+
+    // The main loop may be stuck or not running yet
+    let spawned = std::thread::Builder::new()
+        .name("restart-fallback".to_string())
+        .stack_size(4096)
+        .spawn(move || {
+            std::thread::sleep(FALLBACK_AFTER);
+            log::error!("Main loop did not restart in {}s, restarting directly", FALLBACK_AFTER.as_secs());
+            restart_now(reason);
+        });
+    if spawned.is_err() {
+        log::error!("Failed to start restart fallback thread");
+    }
+}
+
+/// The pending restart, if any
+pub fn pending_restart() -> Option<RestartReason> {
+    RESTART.lock().ok().and_then(|slot| slot.as_ref().map(|r| r.reason))
+}
+
+/// Time left until the response that asked for the restart has gone out
+pub fn response_grace_left() -> Duration {
+    RESTART.lock().ok()
+        .and_then(|slot| slot.as_ref().map(|r| RESPONSE_GRACE.saturating_sub(r.at.elapsed())))
+        .unwrap_or(Duration::ZERO)
+}
+
+/// Save what is still in RAM, flush the logs and reset. The services should
+/// already be stopped; this is also the path of last resort when they can't be.
+pub fn restart_now(reason: RestartReason) -> ! {
+    log::warn!("Restarting: {}", reason.label());
+    crate::system::lifetime_stats::save();
+    crate::network::disconnect_reasons::save();
+    if let Some(telnet) = crate::logging::get_telnet_server() {
+        telnet.disconnect_all(&format!("Device restarting ({})", reason.label()));
+    }
+    log::logger().flush();
+    // UART drains at 115200 baud; give the last lines time to leave
+    esp_idf_hal::delay::FreeRtos::delay_ms(100);
+    unsafe { esp_idf_sys::esp_restart() }
+}
+
 /// Shutdown signal that can be shared across threads
 #[derive(Clone)]
 pub struct ShutdownSignal {
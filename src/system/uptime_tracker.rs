@@ -1,77 +1,108 @@
 use anyhow::Result;
-use esp_idf_svc::nvs::{EspNvs, NvsDefault};
+use crate::storage::kv::{self, NvsStore};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
 const NVS_NAMESPACE: &str = "uptime";
 const NVS_KEY_TOTAL: &str = "total_secs";
 const NVS_KEY_BOOTS: &str = "boot_count";
+const NVS_KEY_RECORDS: &str = "records";
 const SAVE_INTERVAL: Duration = Duration::from_secs(60); // Save every minute
+const MAX_RECORDS: usize = 16;
+const AVAILABILITY_WINDOW_SECS: u64 = 7 * 24 * 3600;
+
+static LATEST_REPORT: Mutex<Option<ReliabilityReport>> = Mutex::new(None);
+
+/// One boot session, kept in NVS for reliability reporting
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BootRecord {
+    /// Reset reason that started this boot
+    pub reason: String,
+    /// Unix time the boot started, 0 if the clock was never set
+    pub started_at: u64,
+    pub duration_secs: u64,
+    /// Set on the following boot if this one ended in a panic, watchdog or brown-out
+    pub crashed: bool,
+    pub min_heap: u32,
+    pub max_temp_c: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReliabilityReport {
+    pub boot_count: u32,
+    pub total_uptime_secs: u64,
+    pub crashes: usize,
+    /// Percent of the last 7 days the device was up; None until the clock has been set
+    pub availability_7d: Option<f32>,
+    /// Mean recorded uptime between crashes; None when no crash is recorded
+    pub mtbf_secs: Option<u64>,
+    /// Oldest first, current boot last
+    pub boots: Vec<BootRecord>,
+}
 
 pub struct UptimeTracker {
-    nvs: Option<EspNvs<NvsDefault>>,
+    store: Option<NvsStore>,
     boot_time: Instant,
     total_uptime_at_boot: u64,
     boot_count: u32,
     last_save: Instant,
+    records: Vec<BootRecord>,
 }
 
 impl UptimeTracker {
     pub fn new() -> Result<Self> {
-        // Ensure NVS is initialized and recover if needed
-        unsafe {
-            let init_res = esp_idf_sys::nvs_flash_init();
-            if init_res == esp_idf_sys::ESP_ERR_NVS_NO_FREE_PAGES
-                || init_res == esp_idf_sys::ESP_ERR_NVS_NEW_VERSION_FOUND
-            {
-                let _ = esp_idf_sys::nvs_flash_erase();
-                let _ = esp_idf_sys::nvs_flash_init();
-            }
-        }
-
-        // Try to take default NVS partition first; on INVALID_STATE fall back silently
-        let nvs_result = esp_idf_svc::nvs::EspNvsPartition::<NvsDefault>::take()
-            .or_else(|_e| {
-                // Avoid noisy warnings; just try the default NVS partition
-                esp_idf_svc::nvs::EspDefaultNvsPartition::take().map(|p| p.into())
-            });
-        
-        let (nvs, total_uptime, boot_count) = match nvs_result {
-            Ok(nvs_partition) => {
-                match EspNvs::new(nvs_partition, NVS_NAMESPACE, true) {
-                    Ok(nvs) => {
-                        // Read previous values
-                        let total = nvs.get_u64(NVS_KEY_TOTAL).ok().flatten().unwrap_or(0);
-                        let boots = nvs.get_u32(NVS_KEY_BOOTS).ok().flatten().unwrap_or(0);
-                        
-                        // Increment boot count
-                        let new_boots = boots + 1;
-                        let _ = nvs.set_u32(NVS_KEY_BOOTS, new_boots);
-                        
-                        log::info!("Uptime tracker initialized - Total: {} hours, Boots: {}", 
-                                  total / 3600, new_boots);
-                        
-                        (Some(nvs), total, new_boots)
-                    }
-                    Err(e) => {
-                        log::warn!("Failed to open NVS namespace: {:?}", e);
-                        (None, 0, 0)
-                    }
-                }
+        let (store, total_uptime, boot_count) = match kv::open(NVS_NAMESPACE) {
+            Ok(mut store) => {
+                // Read previous values
+                let total: u64 = store.get(NVS_KEY_TOTAL).unwrap_or(0);
+                let boots: u32 = store.get(NVS_KEY_BOOTS).unwrap_or(0);
+                
+                // Increment boot count
+                let new_boots = boots + 1;
+                let _ = store.set(NVS_KEY_BOOTS, &new_boots);
+                
+                log::info!("Uptime tracker initialized - Total: {} hours, Boots: {}", 
+                          total / 3600, new_boots);
+                
+                (Some(store), total, new_boots)
             }
             Err(e) => {
-                // Common when another subsystem holds the default NVS; run without persistence
-                log::debug!("UptimeTracker: NVS unavailable: {:?}", e);
+                log::warn!("UptimeTracker: NVS unavailable, running without persistence: {:?}", e);
                 (None, 0, 0)
             }
         };
         
-        Ok(Self {
-            nvs,
+        // The reset reason of this boot tells how the previous one ended
+        let mut records: Vec<BootRecord> = store.as_mut().and_then(|s| s.get(NVS_KEY_RECORDS)).unwrap_or_default();
+        if super::reset::last_reset_was_crash() {
+            if let Some(previous) = records.last_mut() {
+                previous.crashed = true;
+            }
+        }
+        records.push(BootRecord {
+            reason: super::reset::get_reset_reason().to_string(),
+            started_at: 0,
+            duration_secs: 0,
+            crashed: false,
+            min_heap: unsafe { esp_idf_sys::esp_get_minimum_free_heap_size() },
+            max_temp_c: None,
+        });
+        if records.len() > MAX_RECORDS {
+            records.drain(..records.len() - MAX_RECORDS);
+        }
+        
+        let mut tracker = Self {
+            store,
             boot_time: Instant::now(),
             total_uptime_at_boot: total_uptime,
             boot_count,
             last_save: Instant::now(),
-        })
+            records,
+        };
+        tracker.refresh_current_record();
+        tracker.save_records();
+        Ok(tracker)
     }
     
     /// Get current session uptime
@@ -90,17 +121,30 @@ impl UptimeTracker {
         self.boot_count
     }
     
+    /// Track the highest temperature seen this boot
+    pub fn observe_temperature(&mut self, temp_c: f32) {
+        if let Some(current) = self.records.last_mut() {
+            current.max_temp_c = Some(current.max_temp_c.map_or(temp_c, |max| max.max(temp_c)));
+        }
+    }
+    
     /// Save current uptime to NVS (call periodically)
     pub fn save_if_needed(&mut self) -> Result<()> {
         if self.last_save.elapsed() < SAVE_INTERVAL {
             return Ok(());
         }
         
-        // Calculate total uptime before borrowing nvs
+        self.refresh_current_record();
+        
+        // Calculate total uptime before borrowing the store
         let total_secs = self.get_total_uptime().as_secs();
         
-        if let Some(ref mut nvs) = self.nvs {
-            match nvs.set_u64(NVS_KEY_TOTAL, total_secs) {
+        if let Some(ref mut store) = self.store {
+            // Total and boot records go out together in one flush
+            let result = store.stage(NVS_KEY_TOTAL, &total_secs)
+                .and_then(|_| store.stage(NVS_KEY_RECORDS, &self.records))
+                .and_then(|_| store.flush());
+            match result {
                 Ok(_) => {
                     self.last_save = Instant::now();
                     log::debug!("Saved uptime: {} hours", total_secs / 3600);
@@ -124,7 +168,85 @@ impl UptimeTracker {
         format_duration(self.get_total_uptime())
     }
     
-    // Removed structured stats aggregation to reduce footprint
+    pub fn reliability_report(&self) -> ReliabilityReport {
+        let now = unix_now();
+        ReliabilityReport {
+            boot_count: self.boot_count,
+            total_uptime_secs: self.get_total_uptime().as_secs(),
+            crashes: self.records.iter().filter(|r| r.crashed).count(),
+            availability_7d: now.and_then(|now| availability_percent(&self.records, now, AVAILABILITY_WINDOW_SECS)),
+            mtbf_secs: mtbf_secs(&self.records),
+            boots: self.records.clone(),
+        }
+    }
+    
+    // Bring the current boot's record up to date and publish a fresh report
+    fn refresh_current_record(&mut self) {
+        let session_secs = self.get_session_uptime().as_secs();
+        if let Some(current) = self.records.last_mut() {
+            current.duration_secs = session_secs;
+            current.min_heap = unsafe { esp_idf_sys::esp_get_minimum_free_heap_size() };
+            // Backfill the start time once the clock becomes valid
+            if current.started_at == 0 {
+                if let Some(now) = unix_now() {
+                    current.started_at = now.saturating_sub(session_secs);
+                }
+            }
+        }
+        if let Ok(mut slot) = LATEST_REPORT.lock() {
+            *slot = Some(self.reliability_report());
+        }
+    }
+    
+    fn save_records(&mut self) {
+        let Some(ref mut store) = self.store else { return };
+        if let Err(e) = store.set(NVS_KEY_RECORDS, &self.records) {
+            log::warn!("Failed to save boot records: {:?}", e);
+        }
+    }
+}
+
+/// Report published by the tracker on every save, for the web API
+pub fn latest_report() -> Option<ReliabilityReport> {
+    LATEST_REPORT.lock().ok().and_then(|r| r.clone())
+}
+
+/// Wall-clock time, or None while the RTC is unset (before 2020)
+fn unix_now() -> Option<u64> {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs())
+        .filter(|&secs| secs >= 1_577_836_800)
+}
+
+/// Percent of the window ending at `now` covered by boot sessions. The window
+/// is shortened to the first timestamped boot so a new device isn't penalised.
+fn availability_percent(records: &[BootRecord], now: u64, window_secs: u64) -> Option<f32> {
+    let first_start = records.iter().filter(|r| r.started_at > 0).map(|r| r.started_at).min()?;
+    let window_start = now.saturating_sub(window_secs).max(first_start);
+    let span = now.saturating_sub(window_start);
+    if span == 0 {
+        return None;
+    }
+    let up: u64 = records.iter()
+        .filter(|r| r.started_at > 0)
+        .map(|r| {
+            let start = r.started_at.max(window_start);
+            let end = (r.started_at + r.duration_secs).min(now);
+            end.saturating_sub(start)
+        })
+        .sum();
+    Some((up as f32 * 100.0 / span as f32).min(100.0))
+}
+
+/// Mean recorded uptime between crashes
+fn mtbf_secs(records: &[BootRecord]) -> Option<u64> {
+    let crashes = records.iter().filter(|r| r.crashed).count() as u64;
+    if crashes == 0 {
+        return None;
+    }
+    Some(records.iter().map(|r| r.duration_secs).sum::<u64>() / crashes)
 }
 
 // Removed: UptimeStats, prefer direct accessors for less overhead
@@ -160,4 +282,25 @@ mod tests {
         assert_eq!(format_duration(Duration::from_secs(3725)), "1h 2m 5s");
         assert_eq!(format_duration(Duration::from_secs(90125)), "1d 1h 2m");
     }
+    
+    fn record(started_at: u64, duration_secs: u64, crashed: bool) -> BootRecord {
+        BootRecord { reason: "Power-on".to_string(), started_at, duration_secs, crashed, min_heap: 0, max_temp_c: None }
+    }
+    
+    #[test]
+    fn test_availability_and_mtbf() {
+        let now = 1_700_000_000;
+        let records = vec![
+            record(now - 1000, 400, true),
+            // 100s gap (restart after crash)
+            record(now - 500, 500, false),
+        ];
+        let availability = availability_percent(&records, now, AVAILABILITY_WINDOW_SECS).unwrap();
+        assert!((availability - 90.0).abs() < 0.01);
+        assert_eq!(mtbf_secs(&records), Some(900));
+        
+        // Untimestamped boots can't contribute to availability
+        assert_eq!(availability_percent(&[record(0, 100, false)], now, AVAILABILITY_WINDOW_SECS), None);
+        assert_eq!(mtbf_secs(&[record(0, 100, false)]), None);
+    }
 }
\ No newline at end of file
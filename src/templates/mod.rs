@@ -1,15 +1,84 @@
 // HTML templates for web server
 // Separated from web_server.rs for better maintainability
 
+use std::collections::HashMap;
+use crate::network::template_engine::{Context, TemplateEngine};
+
 /// Sensor graphs page template
 pub const GRAPHS_PAGE: &str = include_str!("graphs.html");
 
+/// Lifetime stats page template
+pub const STATS_PAGE: &str = include_str!("stats.html");
+
 /// OTA update page template
 pub const OTA_PAGE: &str = include_str!("ota.html");
 
 /// OTA unavailable page template
 pub const OTA_UNAVAILABLE_PAGE: &str = include_str!("ota_unavailable.html");
 
+/// Shared navbar; expects `nav` (see `nav_items`) and the nav_links partial
+pub const NAVBAR_PARTIAL: &str = include_str!("partials/navbar.html");
+pub const NAV_LINKS_PARTIAL: &str = include_str!("partials/nav_links.html");
+
+/// Navbar pages and the i18n keys of their labels
+const NAV_PAGES: &[(&str, &str)] = &[
+    ("/", "web.nav.home"),
+    ("/dashboard", "web.nav.dashboard"),
+    ("/logs", "web.nav.logs"),
+    ("/files", "web.nav.files"),
+    ("/ota", "web.nav.update"),
+    ("/stats", "web.nav.stats"),
+    ("/dev", "web.nav.dev"),
+];
+
+/// Navbar entries in the device language with the page at `active` highlighted
+pub fn nav_items(active: &str) -> Vec<Context> {
+    NAV_PAGES
+        .iter()
+        .map(|&(href, key)| Context::new().with("href", href).with("label", crate::i18n::t(key)).with("active", href == active))
+        .collect()
+}
+
+/// Full shared navbar, for pages served as static HTML
+pub fn render_navbar(active: &str) -> String {
+    let partials = HashMap::from([("nav_links", NAV_LINKS_PARTIAL)]);
+    TemplateEngine::render(NAVBAR_PARTIAL, &Context::new().with("nav", nav_items(active)), &partials)
+}
+
+/// Only the navbar links, for pages with their own nav layout
+pub fn render_nav_links(active: &str) -> String {
+    TemplateEngine::render(NAV_LINKS_PARTIAL, &Context::new().with("nav", nav_items(active)), &HashMap::new())
+}
+
+/// Render a page template with the shared navbar (`{{>navbar}}`, `active` highlighted)
+/// and its `{{@key}}` strings in the device language
+pub fn render_page(template: &str, active: &str) -> String {
+    let context = Context::new()
+        .with("lang", crate::i18n::language().code())
+        .with("nav", nav_items(active));
+    let partials = HashMap::from([("navbar", NAVBAR_PARTIAL), ("nav_links", NAV_LINKS_PARTIAL)]);
+    TemplateEngine::render(template, &context, &partials)
+}
+
+/// Sends the session's CSRF token with every state-changing fetch/XHR;
+/// the page response must carry `csrf::session_cookie`
+pub const CSRF_SCRIPT: &str = include_str!("partials/csrf.html");
+
+/// `html` with CSRF_SCRIPT inserted before `</head>`
+pub fn with_csrf_script(html: &str) -> String {
+    html.replacen("</head>", &format!("{}</head>", CSRF_SCRIPT), 1)
+}
+
+/// Keeps the page's `data-theme` in step with the device theme and exposes
+/// `deviceTheme.set/toggle/onChange`; stores through POST /api/config, so
+/// the page also needs CSRF_SCRIPT
+pub const THEME_SCRIPT: &str = include_str!("partials/theme.html");
+
+/// `html` with THEME_SCRIPT inserted before `</head>`
+pub fn with_theme_script(html: &str) -> String {
+    html.replacen("</head>", &format!("{}</head>", THEME_SCRIPT), 1)
+}
+
 // Removed: home page rendering helpers (unused)
 
 #[cfg(test)]
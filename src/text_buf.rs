@@ -0,0 +1,177 @@
+// Stack-allocated text for the render path. Every `format!` in a frame is a
+// heap allocation freed a few microseconds later; at 30+ FPS that is steady
+// churn that fragments DRAM. TextBuf formats into a fixed array instead and
+// truncates at a character boundary when the text does not fit, which on a
+// 320 px wide screen is what a label would do anyway.
+
+use core::fmt;
+
+/// Bytes held by the buffers `text_buf!` builds: two rows of the 1x font
+pub const TEXT_BUF_LEN: usize = 64;
+
+#[derive(Clone, Copy)]
+pub struct TextBuf<const N: usize = TEXT_BUF_LEN> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> TextBuf<N> {
+    pub const fn new() -> Self {
+        Self { bytes: [0; N], len: 0 }
+    }
+
+    /// `args` formatted, cut to fit
+    pub fn from_args(args: fmt::Arguments<'_>) -> Self {
+        let mut buf = Self::new();
+        let _ = fmt::Write::write_fmt(&mut buf, args);
+        buf
+    }
+
+    pub fn as_str(&self) -> &str {
+        // SAFETY: only whole UTF-8 characters are ever copied in
+        unsafe { core::str::from_utf8_unchecked(&self.bytes[..self.len]) }
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Append what fits of `s`; false when some of it was cut
+    pub fn push_str(&mut self, s: &str) -> bool {
+        let room = N - self.len;
+        let mut take = s.len().min(room);
+        while !s.is_char_boundary(take) {
+            take -= 1;
+        }
+        self.bytes[self.len..self.len + take].copy_from_slice(&s.as_bytes()[..take]);
+        self.len += take;
+        take == s.len()
+    }
+}
+
+impl<const N: usize> Default for TextBuf<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Write for TextBuf<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // Cut silently: a failed write would abandon the rest of the format
+        // string, but the remaining pieces do not fit either
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl<const N: usize> core::ops::Deref for TextBuf<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for TextBuf<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> fmt::Display for TextBuf<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<const N: usize> fmt::Debug for TextBuf<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> PartialEq for TextBuf<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for TextBuf<N> {}
+
+impl<const N: usize> PartialEq<str> for TextBuf<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for TextBuf<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize> FromIterator<char> for TextBuf<N> {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut buf = Self::new();
+        let mut utf8 = [0; 4];
+        for c in iter {
+            if !buf.push_str(c.encode_utf8(&mut utf8)) {
+                break;
+            }
+        }
+        buf
+    }
+}
+
+impl<const N: usize> From<&str> for TextBuf<N> {
+    fn from(s: &str) -> Self {
+        let mut buf = Self::new();
+        buf.push_str(s);
+        buf
+    }
+}
+
+/// For the places that keep text, e.g. `TextRow`; allocates, so not per frame
+impl<const N: usize> From<TextBuf<N>> for String {
+    fn from(buf: TextBuf<N>) -> Self {
+        buf.as_str().to_string()
+    }
+}
+
+/// `format!` into a TEXT_BUF_LEN-byte `TextBuf` on the stack:
+/// `display.draw_text(x, y, &text_buf!("{:.1} FPS", fps), ...)`
+#[macro_export]
+macro_rules! text_buf {
+    ($($arg:tt)*) => {
+        $crate::text_buf::TextBuf::<{ $crate::text_buf::TEXT_BUF_LEN }>::from_args(format_args!($($arg)*))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formats_without_allocating() {
+        let text = crate::text_buf!("{:.1} FPS {}%", 29.96, 7);
+        assert_eq!(text, "30.0 FPS 7%");
+        assert_eq!(text.len(), 11);
+        assert_eq!(String::from(text), "30.0 FPS 7%");
+
+        let mut buf = TextBuf::<8>::new();
+        assert!(buf.push_str("abc"));
+        buf.clear();
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_truncates_at_char_boundary() {
+        // "⚠" is three bytes; only one whole copy fits in five
+        assert_eq!(TextBuf::<5>::from_args(format_args!("{}{}", "⚠", "⚠")), "⚠");
+        assert_eq!(TextBuf::<4>::from_args(format_args!("{}", 123456)), "1234");
+        assert_eq!("a⚠bc".chars().collect::<TextBuf<4>>(), "a⚠");
+        let mut buf = TextBuf::<3>::new();
+        assert!(!buf.push_str("abcd"));
+        assert_eq!(buf, "abc");
+    }
+}
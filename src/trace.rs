@@ -0,0 +1,180 @@
+// Lightweight request tracing. A web handler opens a trace with a short ID;
+// while it is entered on a thread, every log line from that thread carries
+// "[#id]", and events handed to the render loop through the input bus carry
+// the ID so the UI work they cause logs under it too. Spans time a named piece
+// of work and are listed in a global table while they run, so the render loop
+// can name what overlapped a slow frame (e.g. "OTA write chunk #2a").
+
+use std::cell::Cell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Spans running at least this long are logged as warnings
+pub const SLOW_SPAN: Duration = Duration::from_millis(100);
+
+/// Spans listed at once; more are still timed but not listed
+const MAX_ACTIVE: usize = 16;
+
+/// Short ID shared by everything one request causes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceId(u32);
+
+static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+
+impl TraceId {
+    pub fn next() -> Self {
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl fmt::Display for TraceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:x}", self.0)
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Option<TraceId>> = const { Cell::new(None) };
+}
+
+/// Trace entered on this thread, if any
+pub fn current() -> Option<TraceId> {
+    CURRENT.with(|c| c.get())
+}
+
+/// Makes a trace current on this thread until dropped
+#[must_use = "the trace is only current while the scope lives"]
+pub struct TraceScope {
+    id: TraceId,
+    previous: Option<TraceId>,
+    // Restores thread-local state, so it must drop on the thread that made it
+    _not_send: PhantomData<*const ()>,
+}
+
+impl TraceScope {
+    pub fn id(&self) -> TraceId {
+        self.id
+    }
+}
+
+impl Drop for TraceScope {
+    fn drop(&mut self) {
+        CURRENT.with(|c| c.set(self.previous));
+    }
+}
+
+/// Enter an existing trace, e.g. one received with an event
+pub fn enter(id: TraceId) -> TraceScope {
+    let previous = CURRENT.with(|c| c.replace(Some(id)));
+    TraceScope { id, previous, _not_send: PhantomData }
+}
+
+/// Start a new trace; call at the top of a request handler
+pub fn begin() -> TraceScope {
+    enter(TraceId::next())
+}
+
+struct ActiveSpan {
+    key: u32,
+    name: &'static str,
+    trace: Option<TraceId>,
+    start: Instant,
+}
+
+static ACTIVE: Mutex<Vec<ActiveSpan>> = Mutex::new(Vec::new());
+static NEXT_SPAN: AtomicU32 = AtomicU32::new(0);
+
+/// Timed piece of work; see `span!`
+#[must_use = "the span ends when dropped"]
+pub struct Span {
+    key: u32,
+    name: &'static str,
+    start: Instant,
+}
+
+impl Span {
+    pub fn enter(name: &'static str) -> Self {
+        let key = NEXT_SPAN.fetch_add(1, Ordering::Relaxed);
+        let start = Instant::now();
+        if let Ok(mut active) = ACTIVE.lock() {
+            if active.len() < MAX_ACTIVE {
+                active.push(ActiveSpan { key, name, trace: current(), start });
+            }
+        }
+        Self { key, name, start }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Ok(mut active) = ACTIVE.lock() {
+            active.retain(|span| span.key != self.key);
+        }
+        let elapsed = self.start.elapsed();
+        if elapsed >= SLOW_SPAN {
+            log::warn!("{} took {} ms", self.name, elapsed.as_millis());
+        } else {
+            log::trace!("{} took {} us", self.name, elapsed.as_micros());
+        }
+    }
+}
+
+/// Spans running right now on any thread, e.g.
+/// "OTA write chunk #2a (12 ms), Weather fetch (850 ms)"; empty if none
+pub fn active_spans() -> String {
+    let Ok(active) = ACTIVE.lock() else {
+        return String::new();
+    };
+    active.iter()
+        .map(|span| {
+            let trace = span.trace.map(|id| format!(" {}", id)).unwrap_or_default();
+            format!("{}{} ({} ms)", span.name, trace, span.start.elapsed().as_millis())
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Time the rest of the enclosing block, or just `$body`, as a named span:
+/// `span!("Weather fetch");` or `span!("OTA write chunk", decoder.write_all(data))`
+#[macro_export]
+macro_rules! span {
+    ($name:expr) => {
+        let _span = $crate::trace::Span::enter($name);
+    };
+    ($name:expr, $body:expr) => {{
+        let _span = $crate::trace::Span::enter($name);
+        $body
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scopes_nest_per_thread() {
+        assert_eq!(current(), None);
+        let outer = begin();
+        {
+            let inner = begin();
+            assert_ne!(inner.id(), outer.id());
+            assert_eq!(current(), Some(inner.id()));
+            // Other threads are unaffected
+            assert_eq!(std::thread::spawn(current).join().unwrap(), None);
+        }
+        assert_eq!(current(), Some(outer.id()));
+        drop(outer);
+        assert_eq!(current(), None);
+    }
+
+    #[test]
+    fn test_active_spans_name_their_trace() {
+        let scope = begin();
+        let listed = crate::span!("Test span", active_spans());
+        assert!(listed.contains(&format!("Test span {} (", scope.id())));
+        assert!(!active_spans().contains("Test span"));
+    }
+}
@@ -0,0 +1,219 @@
+// Time-based animations and the screen transitions built on them. An
+// Animation eases a value between two endpoints over a fixed duration; the
+// render loop samples it every frame, so smoothness follows the frame rate.
+// Screen transitions redraw the incoming screen each frame either shifted
+// horizontally (slide) or blended up from black (fade), and are skipped when
+// the frame rate is too low for them to look like anything but stutter.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Below this many frames per second screens switch without a transition
+pub const MIN_TRANSITION_FPS: f32 = 15.0;
+
+pub const SLIDE_DURATION: Duration = Duration::from_millis(250);
+pub const FADE_DURATION: Duration = Duration::from_millis(200);
+
+/// Shape of an animation's progress curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EasingFunction {
+    Linear,
+    /// Starts slow
+    EaseIn,
+    /// Ends slow
+    EaseOut,
+    EaseInOut,
+}
+
+impl EasingFunction {
+    /// Eased progress for linear progress `t` (clamped to 0..=1)
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Self::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// A value moving from `from` to `to`; it holds `from` until started
+#[derive(Debug, Clone)]
+pub struct Animation {
+    from: f32,
+    to: f32,
+    duration: Duration,
+    easing: EasingFunction,
+    started: Option<Instant>,
+}
+
+impl Animation {
+    pub fn new(from: f32, to: f32, duration: Duration, easing: EasingFunction) -> Self {
+        Self { from, to, duration, easing, started: None }
+    }
+
+    pub fn start(&mut self) {
+        self.start_at(Instant::now());
+    }
+
+    pub fn start_at(&mut self, now: Instant) {
+        self.started = Some(now);
+    }
+
+    /// Current value
+    pub fn update(&self) -> f32 {
+        self.value_at(Instant::now())
+    }
+
+    pub fn value_at(&self, now: Instant) -> f32 {
+        let t = match self.started {
+            None => 0.0,
+            Some(_) if self.duration.is_zero() => 1.0,
+            Some(start) => now.saturating_duration_since(start).as_secs_f32() / self.duration.as_secs_f32(),
+        };
+        self.from + (self.to - self.from) * self.easing.apply(t)
+    }
+
+    /// Started and at its end value
+    pub fn is_finished_at(&self, now: Instant) -> bool {
+        self.started.is_some_and(|start| now.saturating_duration_since(start) >= self.duration)
+    }
+}
+
+/// How the incoming screen enters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionStyle {
+    /// Slides in from the right (next screen) or left (previous screen)
+    Slide { from_right: bool },
+    /// Fades in from black; used for jumps, e.g. to the OTA screen
+    Fade,
+}
+
+/// Drawing adjustments for one transition frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransitionFrame {
+    /// Horizontal shift of everything the screen draws, in pixels
+    pub offset_x: i16,
+    /// Brightness of the screen's colours, 255 = unchanged
+    pub fade: u8,
+}
+
+impl Default for TransitionFrame {
+    fn default() -> Self {
+        Self { offset_x: 0, fade: 255 }
+    }
+}
+
+/// A running switch to a new screen
+#[derive(Debug, Clone)]
+pub struct ScreenTransition {
+    style: TransitionStyle,
+    width: u16,
+    animation: Animation,
+}
+
+impl ScreenTransition {
+    /// Transition from screen `from` to `to` out of `count`: stepping to a
+    /// neighbour (wrapping around) slides, anything else fades
+    pub fn between(from: usize, to: usize, count: usize, width: u16, now: Instant) -> Self {
+        // With two screens both ways are a neighbour; that counts as forward
+        let style = if count >= 2 && to == (from + 1) % count {
+            TransitionStyle::Slide { from_right: true }
+        } else if count > 2 && from == (to + 1) % count {
+            TransitionStyle::Slide { from_right: false }
+        } else {
+            TransitionStyle::Fade
+        };
+        let (duration, easing) = match style {
+            TransitionStyle::Slide { .. } => (SLIDE_DURATION, EasingFunction::EaseOut),
+            TransitionStyle::Fade => (FADE_DURATION, EasingFunction::EaseInOut),
+        };
+        let mut animation = Animation::new(0.0, 1.0, duration, easing);
+        animation.start_at(now);
+        Self { style, width, animation }
+    }
+
+    pub fn style(&self) -> TransitionStyle {
+        self.style
+    }
+
+    /// Adjustments to draw with at `now`, or None once the transition is over
+    pub fn frame_at(&self, now: Instant) -> Option<TransitionFrame> {
+        if self.animation.is_finished_at(now) {
+            return None;
+        }
+        let progress = self.animation.value_at(now);
+        Some(match self.style {
+            TransitionStyle::Slide { from_right } => {
+                let remaining = ((1.0 - progress) * self.width as f32).round() as i16;
+                TransitionFrame { offset_x: if from_right { remaining } else { -remaining }, fade: 255 }
+            }
+            TransitionStyle::Fade => TransitionFrame { offset_x: 0, fade: (progress * 255.0).round() as u8 },
+        })
+    }
+}
+
+/// Whether a screen switch should animate: enabled in config and the frame
+/// rate is high enough to show it
+pub fn should_animate(enabled: bool, fps: f32) -> bool {
+    enabled && fps >= MIN_TRANSITION_FPS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_easing_and_animation() {
+        for easing in [EasingFunction::Linear, EasingFunction::EaseIn, EasingFunction::EaseOut, EasingFunction::EaseInOut] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6);
+            assert_eq!(easing.apply(2.0), easing.apply(1.0));
+        }
+        assert!(EasingFunction::EaseOut.apply(0.25) > 0.25);
+        assert!(EasingFunction::EaseIn.apply(0.25) < 0.25);
+        assert!((EasingFunction::EaseInOut.apply(0.5) - 0.5).abs() < 1e-6);
+
+        let t0 = Instant::now();
+        let mut anim = Animation::new(0.0, 360.0, Duration::from_secs(2), EasingFunction::Linear);
+        // Holds its start value until started
+        assert_eq!(anim.value_at(t0 + Duration::from_secs(5)), 0.0);
+        anim.start_at(t0);
+        assert_eq!(anim.value_at(t0 + Duration::from_secs(1)), 180.0);
+        assert!(!anim.is_finished_at(t0 + Duration::from_secs(1)));
+        assert_eq!(anim.value_at(t0 + Duration::from_secs(3)), 360.0);
+        assert!(anim.is_finished_at(t0 + Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_transition_direction_and_frames() {
+        let t0 = Instant::now();
+        let next = ScreenTransition::between(4, 0, 5, 300, t0);
+        assert_eq!(next.style(), TransitionStyle::Slide { from_right: true });
+        assert_eq!(next.frame_at(t0), Some(TransitionFrame { offset_x: 300, fade: 255 }));
+        let mid = next.frame_at(t0 + SLIDE_DURATION / 2).unwrap();
+        assert!(mid.offset_x > 0 && mid.offset_x < 150, "eases out: {:?}", mid);
+        assert_eq!(next.frame_at(t0 + SLIDE_DURATION), None);
+
+        let prev = ScreenTransition::between(0, 4, 5, 300, t0);
+        assert_eq!(prev.style(), TransitionStyle::Slide { from_right: false });
+        assert_eq!(prev.frame_at(t0).unwrap().offset_x, -300);
+
+        let jump = ScreenTransition::between(0, 3, 5, 300, t0);
+        assert_eq!(jump.style(), TransitionStyle::Fade);
+        assert_eq!(jump.frame_at(t0), Some(TransitionFrame { offset_x: 0, fade: 0 }));
+        assert_eq!(jump.frame_at(t0 + FADE_DURATION / 2).unwrap().fade, 128);
+
+        assert!(should_animate(true, 30.0));
+        assert!(!should_animate(true, MIN_TRANSITION_FPS - 1.0));
+        assert!(!should_animate(false, 30.0));
+    }
+}
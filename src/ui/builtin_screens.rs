@@ -0,0 +1,333 @@
+// Built-in screens. Each one hashes the UiManager state it shows and forwards
+// to its render and text view methods; optional screens are only registered
+// when their feature is enabled.
+
+use anyhow::Result;
+use crate::display::DisplayManager;
+use crate::input::InputEvent;
+use super::registry::{hash_state, Screen, ScreenRegistry, TextView};
+use super::{SettingChange, UiManager, BRIGHTNESS_STEP};
+
+/// Name of the timer screen; the timer keeps ticking while other screens are shown
+#[cfg(feature = "screen_timer")]
+pub const TIMER_SCREEN: &str = "timer";
+
+/// Register every screen compiled into this build, in the default order
+pub fn register_all(registry: &mut ScreenRegistry<UiManager, DisplayManager>) {
+    registry.register(&SystemScreen);
+    registry.register(&NetworkScreen);
+    registry.register(&SensorScreen);
+    registry.register(&SettingsScreen);
+    registry.register(&OtaScreen);
+    #[cfg(feature = "screen_weather")]
+    registry.register(&WeatherScreen);
+    #[cfg(feature = "screen_widgets")]
+    registry.register(&CustomScreen);
+    #[cfg(feature = "screen_alerts")]
+    registry.register(&AlertsScreen);
+    #[cfg(feature = "screen_agenda")]
+    registry.register(&AgendaScreen);
+    #[cfg(feature = "screen_stats")]
+    registry.register(&StatsScreen);
+    #[cfg(feature = "screen_air_quality")]
+    registry.register(&AirQualityScreen);
+    // Timer is last so USER can control it while BOOT still navigates back
+    #[cfg(feature = "screen_timer")]
+    registry.register(&TimerScreen);
+}
+
+pub struct SystemScreen;
+
+impl Screen<UiManager, DisplayManager> for SystemScreen {
+    fn name(&self) -> &'static str { "system" }
+
+    fn state_hash(&self, ui: &UiManager) -> u64 {
+        let data = &ui.sensor_data;
+        hash_state(&(
+            ui.system_info.get_uptime().as_secs(),
+            ui.system_info.get_free_heap_kb(),
+            (ui.cpu0_usage, ui.cpu1_usage),
+            data._temperature.to_bits(),
+            (data._battery_percentage, data._is_charging, data._is_on_usb),
+            crate::psram::PsramAllocator::get_info().free_size / 1024 / 1024,
+            (&ui.reliability_summary, ui.reliability_crashes),
+            ui.progress_percent(),
+        ))
+    }
+
+    fn render(&self, ui: &mut UiManager, display: &mut DisplayManager, entered: bool) -> Result<()> {
+        ui.render_system_screen(display, entered)
+    }
+
+    fn text_view(&self, ui: &UiManager) -> Option<TextView> {
+        Some(ui.system_text_view())
+    }
+}
+
+pub struct NetworkScreen;
+
+impl Screen<UiManager, DisplayManager> for NetworkScreen {
+    fn name(&self) -> &'static str { "network" }
+
+    fn state_hash(&self, ui: &UiManager) -> u64 {
+        hash_state(&(
+            ui.header_tick(),
+            (ui.network_connected, &ui.network_ssid, &ui.network_ip, &ui.network_ipv6, ui.network_signal),
+            (&ui.network_mac, &ui.network_gateway),
+            (&ui.latency_gateway, &ui.latency_host),
+        ))
+    }
+
+    fn render(&self, ui: &mut UiManager, display: &mut DisplayManager, entered: bool) -> Result<()> {
+        ui.render_network_screen(display, entered)
+    }
+
+    fn text_view(&self, ui: &UiManager) -> Option<TextView> {
+        Some(ui.network_text_view())
+    }
+}
+
+pub struct SensorScreen;
+
+impl Screen<UiManager, DisplayManager> for SensorScreen {
+    fn name(&self) -> &'static str { "sensor" }
+
+    fn state_hash(&self, ui: &UiManager) -> u64 {
+        let data = &ui.sensor_data;
+        hash_state(&(
+            (data._battery_percentage, data._battery_voltage, data._is_charging, data._is_on_usb),
+            data._temperature.to_bits(),
+            data._light_level,
+            &ui.power_warning,
+            ui.power_monitor.map(|p| (p.reading.power_mw.round() as i32, (p.energy_mwh * 10.0).round() as i64)),
+            ui.sensor_readings.iter().map(|s| (&s.key, s.reading.display())).collect::<Vec<_>>(),
+            ui.progress_percent(),
+        ))
+    }
+
+    fn render(&self, ui: &mut UiManager, display: &mut DisplayManager, entered: bool) -> Result<()> {
+        ui.render_sensor_screen(display, entered)
+    }
+
+    fn text_view(&self, ui: &UiManager) -> Option<TextView> {
+        Some(ui.sensor_text_view())
+    }
+}
+
+pub struct SettingsScreen;
+
+impl Screen<UiManager, DisplayManager> for SettingsScreen {
+    fn name(&self) -> &'static str { "settings" }
+
+    fn state_hash(&self, ui: &UiManager) -> u64 {
+        hash_state(&(ui.brightness, ui.accessibility))
+    }
+
+    fn render(&self, ui: &mut UiManager, display: &mut DisplayManager, entered: bool) -> Result<()> {
+        ui.render_settings_screen(display, entered)
+    }
+
+    fn text_view(&self, ui: &UiManager) -> Option<TextView> {
+        Some(ui.settings_text_view())
+    }
+
+    /// The encoder adjusts brightness here instead of scrolling; a long USER
+    /// press switches accessibility mode
+    fn handle_input(&self, ui: &mut UiManager, event: InputEvent) -> bool {
+        match event {
+            InputEvent::Rotate(steps) => {
+                ui.brightness = (ui.brightness as i32 + steps * BRIGHTNESS_STEP).clamp(0, 255) as u8;
+                ui.pending_setting = Some(SettingChange::Brightness(ui.brightness));
+            }
+            InputEvent::LongPress(crate::input::Key::User) => {
+                ui.set_accessibility(!ui.accessibility);
+                ui.pending_setting = Some(SettingChange::Accessibility(ui.accessibility));
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+pub struct OtaScreen;
+
+impl Screen<UiManager, DisplayManager> for OtaScreen {
+    fn name(&self) -> &'static str { "ota" }
+
+    fn state_hash(&self, ui: &UiManager) -> u64 {
+        hash_state(&(ui.header_tick(), ui.ota_status, ui.network_connected, &ui.network_ip))
+    }
+
+    fn render(&self, ui: &mut UiManager, display: &mut DisplayManager, entered: bool) -> Result<()> {
+        ui.render_ota_screen(display, entered)
+    }
+
+    fn text_view(&self, ui: &UiManager) -> Option<TextView> {
+        Some(ui.ota_text_view())
+    }
+}
+
+#[cfg(feature = "screen_weather")]
+pub struct WeatherScreen;
+
+#[cfg(feature = "screen_weather")]
+impl Screen<UiManager, DisplayManager> for WeatherScreen {
+    fn name(&self) -> &'static str { "weather" }
+
+    /// Each fetch has a new timestamp; the "ago" label moves once a minute
+    fn state_hash(&self, ui: &UiManager) -> u64 {
+        hash_state(&(ui.weather.as_ref().map(|w| w.fetched_at_s), ui.weather_age_minutes()))
+    }
+
+    fn render(&self, ui: &mut UiManager, display: &mut DisplayManager, entered: bool) -> Result<()> {
+        ui.render_weather_screen(display, entered)
+    }
+
+    fn text_view(&self, ui: &UiManager) -> Option<TextView> {
+        Some(ui.weather_text_view())
+    }
+}
+
+#[cfg(feature = "screen_widgets")]
+pub struct CustomScreen;
+
+#[cfg(feature = "screen_widgets")]
+impl Screen<UiManager, DisplayManager> for CustomScreen {
+    fn name(&self) -> &'static str { "widgets" }
+
+    fn state_hash(&self, ui: &UiManager) -> u64 {
+        hash_state(&ui.custom_widgets)
+    }
+
+    fn render(&self, ui: &mut UiManager, display: &mut DisplayManager, entered: bool) -> Result<()> {
+        ui.render_custom_screen(display, entered)
+    }
+
+    fn text_view(&self, ui: &UiManager) -> Option<TextView> {
+        Some(ui.custom_text_view())
+    }
+}
+
+#[cfg(feature = "screen_alerts")]
+pub struct AlertsScreen;
+
+#[cfg(feature = "screen_alerts")]
+impl Screen<UiManager, DisplayManager> for AlertsScreen {
+    fn name(&self) -> &'static str { "alerts" }
+
+    fn state_hash(&self, ui: &UiManager) -> u64 {
+        hash_state(&ui.ops_alerts)
+    }
+
+    fn render(&self, ui: &mut UiManager, display: &mut DisplayManager, entered: bool) -> Result<()> {
+        ui.render_ops_alerts_screen(display, entered)
+    }
+
+    fn text_view(&self, ui: &UiManager) -> Option<TextView> {
+        Some(ui.ops_alerts_text_view())
+    }
+}
+
+#[cfg(feature = "screen_agenda")]
+pub struct AgendaScreen;
+
+#[cfg(feature = "screen_agenda")]
+impl Screen<UiManager, DisplayManager> for AgendaScreen {
+    fn name(&self) -> &'static str { "agenda" }
+
+    /// The rows as shown, so events drop off and turn to "Now" on time
+    fn state_hash(&self, ui: &UiManager) -> u64 {
+        hash_state(&(ui.agenda_rows(), ui.agenda.as_ref().map(|a| &a.error)))
+    }
+
+    fn render(&self, ui: &mut UiManager, display: &mut DisplayManager, entered: bool) -> Result<()> {
+        ui.render_agenda_screen(display, entered)
+    }
+
+    fn text_view(&self, ui: &UiManager) -> Option<TextView> {
+        Some(ui.agenda_text_view())
+    }
+}
+
+#[cfg(feature = "screen_stats")]
+pub struct StatsScreen;
+
+#[cfg(feature = "screen_stats")]
+impl Screen<UiManager, DisplayManager> for StatsScreen {
+    fn name(&self) -> &'static str { "stats" }
+
+    fn state_hash(&self, ui: &UiManager) -> u64 {
+        hash_state(&ui.lifetime_stats)
+    }
+
+    fn render(&self, ui: &mut UiManager, display: &mut DisplayManager, entered: bool) -> Result<()> {
+        ui.render_stats_screen(display, entered)
+    }
+
+    fn text_view(&self, ui: &UiManager) -> Option<TextView> {
+        Some(ui.stats_text_view())
+    }
+}
+
+#[cfg(feature = "screen_air_quality")]
+pub struct AirQualityScreen;
+
+#[cfg(feature = "screen_air_quality")]
+impl Screen<UiManager, DisplayManager> for AirQualityScreen {
+    fn name(&self) -> &'static str { "air_quality" }
+
+    /// Values at their displayed precision; the graph moves with the history,
+    /// which only grows when a reading does
+    fn state_hash(&self, ui: &UiManager) -> u64 {
+        hash_state(&ui.sensor_readings.iter().map(|s| (&s.key, s.reading.display())).collect::<Vec<_>>())
+    }
+
+    fn render(&self, ui: &mut UiManager, display: &mut DisplayManager, entered: bool) -> Result<()> {
+        ui.render_air_quality_screen(display, entered)
+    }
+
+    fn text_view(&self, ui: &UiManager) -> Option<TextView> {
+        Some(ui.air_quality_text_view())
+    }
+}
+
+#[cfg(feature = "screen_timer")]
+pub struct TimerScreen;
+
+#[cfg(feature = "screen_timer")]
+impl Screen<UiManager, DisplayManager> for TimerScreen {
+    fn name(&self) -> &'static str { TIMER_SCREEN }
+
+    fn state_hash(&self, ui: &UiManager) -> u64 {
+        hash_state(&(ui.timer.state(), ui.timer.remaining().as_secs(), ui.timer.sessions_today()))
+    }
+
+    fn render(&self, ui: &mut UiManager, display: &mut DisplayManager, entered: bool) -> Result<()> {
+        ui.render_timer_screen(display, entered)
+    }
+
+    fn text_view(&self, ui: &UiManager) -> Option<TextView> {
+        Some(ui.timer_text_view())
+    }
+
+    /// USER starts/pauses (long press resets); the encoder sets the length while idle
+    fn handle_input(&self, ui: &mut UiManager, event: InputEvent) -> bool {
+        match event {
+            InputEvent::Click(crate::input::Key::User) => {
+                ui.timer.toggle();
+                log::info!("Timer: {:?}", ui.timer.state());
+            }
+            InputEvent::LongPress(crate::input::Key::User) => {
+                log::info!("Timer reset");
+                ui.timer.reset();
+            }
+            InputEvent::Rotate(steps) if ui.timer.state() == super::pomodoro::TimerState::Idle => {
+                let minutes = (ui.timer.minutes() as i32 + steps).clamp(1, 180) as u32;
+                ui.timer.set_minutes(minutes);
+                ui.pending_setting = Some(SettingChange::TimerMinutes(minutes));
+            }
+            _ => return false,
+        }
+        true
+    }
+}
@@ -1,7 +1,7 @@
 // Loading spinner animations
 
 use crate::display::{Display, Color};
-use crate::animation::{Animation, EasingFunction};
+use crate::ui::animation::{Animation, EasingFunction};
 use embassy_time::{Duration, Instant};
 
 #[derive(Debug, Clone, Copy)]
@@ -0,0 +1,115 @@
+// Text value that only redraws the character cells that changed, replacing the
+// fill_rect-then-draw_text pattern
+
+use crate::display::error::DisplayError;
+use core::ops::Range;
+use crate::display::DisplayManager;
+use crate::ui::layout::{Rect, CHAR_WIDTH, FONT_HEIGHT};
+use crate::text_buf::TextBuf;
+
+/// Room for a run of a full-width field at 1x scale, multi-byte glyphs included
+const SEGMENT_BYTES: usize = 160;
+
+type Result<T> = core::result::Result<T, DisplayError>;
+
+pub struct TextField {
+    x: u16,
+    y: u16,
+    scale: u8,
+    background: u16,
+    max_chars: usize,
+    // What is currently on screen
+    drawn: Vec<char>,
+    drawn_color: Option<u16>,
+    // The text being drawn; swapped with `drawn` so neither reallocates
+    pending: Vec<char>,
+}
+
+impl TextField {
+    pub fn new(x: u16, y: u16, max_width: u16, scale: u8, background: u16) -> Self {
+        let cell = CHAR_WIDTH * scale as u16;
+        Self {
+            x,
+            y,
+            scale,
+            background,
+            max_chars: (max_width / cell.max(1)) as usize,
+            drawn: Vec::new(),
+            drawn_color: None,
+            pending: Vec::new(),
+        }
+    }
+    
+    /// Field filling a layout zone, text vertically centred
+    pub fn in_zone(zone: Rect, scale: u8, background: u16) -> Self {
+        Self::new(zone.x, zone.text_y(scale), zone.w, scale, background)
+    }
+    
+    /// Show `text`, touching only cells that differ from what is on screen.
+    /// A colour change redraws the whole string.
+    pub fn set(&mut self, display: &mut DisplayManager, text: &str, color: u16) -> Result<()> {
+        let mut new = std::mem::take(&mut self.pending);
+        new.clear();
+        new.extend(text.chars().take(self.max_chars));
+        let cell = CHAR_WIDTH * self.scale as u16;
+        let recolor = self.drawn_color.is_some_and(|c| c != color);
+        
+        for run in changed_runs(&self.drawn, &new, recolor) {
+            let segment: TextBuf<SEGMENT_BYTES> = new[run.clone()].iter().copied().collect();
+            let x = self.x + run.start as u16 * cell;
+            display.draw_text(x, self.y, &segment, color, Some(self.background), self.scale)?;
+        }
+        
+        // Erase leftover cells when the text got shorter
+        if new.len() < self.drawn.len() {
+            let x = self.x + new.len() as u16 * cell;
+            let w = (self.drawn.len() - new.len()) as u16 * cell;
+            display.fill_rect(x, self.y, w, FONT_HEIGHT * self.scale as u16, self.background)?;
+        }
+        
+        self.pending = std::mem::replace(&mut self.drawn, new);
+        self.drawn_color = Some(color);
+        Ok(())
+    }
+    
+    /// Forget the on-screen contents, e.g. after the screen was cleared
+    pub fn invalidate(&mut self) {
+        self.drawn.clear();
+        self.drawn_color = None;
+    }
+}
+
+/// Index ranges of `new` whose characters differ from `old`; all of `new` when `all` is set
+fn changed_runs<'a>(old: &'a [char], new: &'a [char], all: bool) -> impl Iterator<Item = Range<usize>> + 'a {
+    let differs = move |i: usize| all || old.get(i) != Some(&new[i]);
+    let mut i = 0;
+    core::iter::from_fn(move || {
+        while i < new.len() && !differs(i) {
+            i += 1;
+        }
+        let start = i;
+        while i < new.len() && differs(i) {
+            i += 1;
+        }
+        (start < i).then_some(start..i)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn test_changed_runs() {
+        let runs = |old: &str, new: &str, all| changed_runs(&chars(old), &chars(new), all).collect::<Vec<_>>();
+        assert_eq!(runs("12m 59s", "13m 00s", false), vec![1..2, 4..6]);
+        assert_eq!(runs("abc", "abc", false), Vec::<Range<usize>>::new());
+        assert_eq!(runs("ab", "abcd", false), vec![2..4]);
+        assert_eq!(runs("abc", "abc", true), vec![0..3]);
+        assert_eq!(runs("", "hi", false), vec![0..2]);
+    }
+}
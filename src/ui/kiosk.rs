@@ -0,0 +1,110 @@
+// Kiosk mode: the display shows one metric in giant digits with today's
+// minimum and maximum, for mounting as a wall gauge. Which metric is a config
+// string; this module parses it and keeps the day's range, UiManager supplies
+// the values.
+
+/// What kiosk mode shows, from config `kiosk_metric`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KioskSource {
+    /// `temperature`: the board's ambient estimate, in the configured unit
+    Temperature,
+    /// `battery`: percent
+    Battery,
+    /// A sensor registry key, e.g. `scd40.co2`
+    Sensor(String),
+    /// `widget:<label>`: a REST widget's value
+    Widget(String),
+}
+
+impl KioskSource {
+    pub fn parse(metric: &str) -> Option<Self> {
+        let metric = metric.trim();
+        match metric {
+            "temperature" => Some(Self::Temperature),
+            "battery" => Some(Self::Battery),
+            _ => match metric.strip_prefix("widget:") {
+                Some(label) if !label.is_empty() => Some(Self::Widget(label.to_string())),
+                Some(_) => None,
+                // Registry keys are `<sensor id>.<quantity>`, both lowercase
+                None => {
+                    let (sensor, quantity) = metric.split_once('.')?;
+                    let valid = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_');
+                    (valid(sensor) && valid(quantity)).then(|| Self::Sensor(metric.to_string()))
+                }
+            },
+        }
+    }
+}
+
+/// Lowest and highest value seen on one local day
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DayRange {
+    day: i64,
+    range: Option<(f32, f32)>,
+}
+
+impl DayRange {
+    /// Add a value seen on `day` (days since the epoch); a new day starts over
+    pub fn update(&mut self, day: i64, value: f32) {
+        if !value.is_finite() {
+            return;
+        }
+        if day != self.day {
+            *self = Self { day, range: None };
+        }
+        self.range = Some(match self.range {
+            Some((min, max)) => (min.min(value), max.max(value)),
+            None => (value, value),
+        });
+    }
+
+    /// Forget the range, e.g. when the metric changes
+    pub fn reset(&mut self) {
+        self.range = None;
+    }
+
+    pub fn min_max(&self) -> Option<(f32, f32)> {
+        self.range
+    }
+}
+
+/// Largest font scale at which `text` fits the screen width
+pub fn value_scale(text: &str, width: u16) -> u8 {
+    [10u8, 8, 6, 4, 3, 2].into_iter()
+        .find(|&scale| text.chars().count() as u16 * (5 * scale as u16 + 1) <= width.saturating_sub(10))
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_metric() {
+        assert_eq!(KioskSource::parse("temperature"), Some(KioskSource::Temperature));
+        assert_eq!(KioskSource::parse(" scd40.co2 "), Some(KioskSource::Sensor("scd40.co2".into())));
+        assert_eq!(KioskSource::parse("widget:Solar"), Some(KioskSource::Widget("Solar".into())));
+        for bad in ["", "widget:", "co2", "SCD40.co2", "scd40.", "a.b c"] {
+            assert_eq!(KioskSource::parse(bad), None, "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_day_range_resets_at_midnight() {
+        let mut range = DayRange::default();
+        range.update(100, 21.0);
+        range.update(100, 18.5);
+        range.update(100, f32::NAN);
+        range.update(100, 23.0);
+        assert_eq!(range.min_max(), Some((18.5, 23.0)));
+        range.update(101, 20.0);
+        assert_eq!(range.min_max(), Some((20.0, 20.0)));
+    }
+
+    #[test]
+    fn test_value_scale() {
+        assert_eq!(value_scale("21.5", 300), 10);
+        assert_eq!(value_scale("1234.5", 300), 8);
+        assert_eq!(value_scale("12345678", 300), 6);
+    }
+}
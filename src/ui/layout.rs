@@ -0,0 +1,172 @@
+// Lightweight layout engine: split the screen into rows/columns of fixed or
+// flexible zones so screens don't hard-code every coordinate.
+
+/// Visible display area (see display::DISPLAY_WIDTH/HEIGHT)
+pub const SCREEN_WIDTH: u16 = 300;
+pub const SCREEN_HEIGHT: u16 = 168;
+
+/// Glyph cell of the built-in 5x7 font at scale 1, including the 1px spacing column
+pub const CHAR_WIDTH: u16 = 6;
+pub const FONT_HEIGHT: u16 = 7;
+
+/// Label/value rows per page of the large-text (accessibility) layout
+pub const LARGE_TEXT_ROWS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub w: u16,
+    pub h: u16,
+}
+
+/// Size of one zone along the split axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Size {
+    /// Exact number of pixels
+    Fixed(u16),
+    /// Share of the remaining space, weighted
+    Fill(u16),
+}
+
+impl Rect {
+    pub const fn new(x: u16, y: u16, w: u16, h: u16) -> Self {
+        Self { x, y, w, h }
+    }
+
+    pub const fn screen() -> Self {
+        Self::new(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT)
+    }
+
+    /// Shrink by `pad` on every side
+    pub fn inset(self, pad: u16) -> Self {
+        self.inset_xy(pad, pad)
+    }
+
+    /// Shrink by `px` left/right and `py` top/bottom
+    pub fn inset_xy(self, px: u16, py: u16) -> Self {
+        let px = px.min(self.w / 2);
+        let py = py.min(self.h / 2);
+        Self::new(self.x + px, self.y + py, self.w - 2 * px, self.h - 2 * py)
+    }
+
+    /// Split vertically into stacked zones separated by `gap` pixels
+    pub fn rows<const N: usize>(self, sizes: [Size; N], gap: u16) -> [Rect; N] {
+        let mut out = [self; N];
+        for (rect, (offset, len)) in out.iter_mut().zip(split(self.y, self.h, &sizes, gap)) {
+            rect.y = offset;
+            rect.h = len;
+        }
+        out
+    }
+
+    /// Split horizontally into side-by-side zones separated by `gap` pixels
+    pub fn cols<const N: usize>(self, sizes: [Size; N], gap: u16) -> [Rect; N] {
+        let mut out = [self; N];
+        for (rect, (offset, len)) in out.iter_mut().zip(split(self.x, self.w, &sizes, gap)) {
+            rect.x = offset;
+            rect.w = len;
+        }
+        out
+    }
+
+    /// Y coordinate that vertically centres text of the given scale
+    pub fn text_y(&self, scale: u8) -> u16 {
+        self.y + self.h.saturating_sub(FONT_HEIGHT * scale as u16) / 2
+    }
+}
+
+/// Pages of the large-text layout needed for `rows` rows
+pub fn text_pages(rows: usize) -> usize {
+    rows.div_ceil(LARGE_TEXT_ROWS).max(1)
+}
+
+/// First row of the page `steps` pages on from the one showing row `first`;
+/// None when that is past either end
+pub fn scroll_page(first: usize, steps: i32, rows: usize) -> Option<usize> {
+    let page = (first / LARGE_TEXT_ROWS) as i32 + steps;
+    (0..text_pages(rows) as i32).contains(&page).then(|| page as usize * LARGE_TEXT_ROWS)
+}
+
+// Resolve sizes along one axis into (offset, length) pairs. Fixed zones are
+// clamped to the available space; fill zones share what remains by weight,
+// with rounding leftovers going to the last fill zone.
+fn split<const N: usize>(start: u16, total: u16, sizes: &[Size; N], gap: u16) -> [(u16, u16); N] {
+    let gaps = gap.saturating_mul(N.saturating_sub(1) as u16);
+    let mut remaining = total.saturating_sub(gaps);
+
+    let fixed: u16 = sizes.iter()
+        .map(|s| if let Size::Fixed(px) = s { *px } else { 0 })
+        .fold(0u16, |acc, px| acc.saturating_add(px));
+    let weights: u16 = sizes.iter()
+        .map(|s| if let Size::Fill(w) = s { *w } else { 0 })
+        .sum();
+    let flexible = remaining.saturating_sub(fixed);
+    let last_fill = sizes.iter().rposition(|s| matches!(s, Size::Fill(_)));
+
+    let mut out = [(0u16, 0u16); N];
+    let mut offset = start;
+    let mut handed_out = 0u16;
+    for (i, size) in sizes.iter().enumerate() {
+        let len = match *size {
+            Size::Fixed(px) => px,
+            Size::Fill(_) if Some(i) == last_fill => flexible - handed_out,
+            Size::Fill(w) => {
+                let share = (flexible as u32 * w as u32 / weights.max(1) as u32) as u16;
+                handed_out += share;
+                share
+            }
+        }
+        .min(remaining);
+        out[i] = (offset, len);
+        remaining -= len;
+        offset = offset.saturating_add(len).saturating_add(gap);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows_fixed_and_fill() {
+        let [header, body, footer] = Rect::screen().rows([Size::Fixed(30), Size::Fill(1), Size::Fixed(14)], 4);
+        assert_eq!(header, Rect::new(0, 0, 300, 30));
+        assert_eq!(body, Rect::new(0, 34, 300, 116));
+        assert_eq!(footer, Rect::new(0, 154, 300, 14));
+    }
+
+    #[test]
+    fn test_weighted_cols_cover_width() {
+        let [a, b, c] = Rect::new(10, 0, 100, 20).cols([Size::Fill(1), Size::Fill(2), Size::Fill(1)], 0);
+        assert_eq!((a.x, a.w), (10, 25));
+        assert_eq!((b.x, b.w), (35, 50));
+        assert_eq!(c.x + c.w, 110);
+    }
+
+    #[test]
+    fn test_overflow_is_clamped() {
+        let [a, b] = Rect::new(0, 0, 50, 10).cols([Size::Fixed(40), Size::Fixed(40)], 0);
+        assert_eq!(a.w, 40);
+        assert_eq!(b.w, 10);
+        assert_eq!(Rect::new(0, 0, 10, 10).inset(8), Rect::new(5, 5, 0, 0));
+    }
+
+    #[test]
+    fn test_large_text_pages() {
+        assert_eq!((text_pages(0), text_pages(3), text_pages(7)), (1, 1, 3));
+        assert_eq!(scroll_page(0, 1, 7), Some(3));
+        assert_eq!(scroll_page(4, 1, 7), Some(6));
+        assert_eq!(scroll_page(6, -2, 7), Some(0));
+        assert_eq!(scroll_page(6, 1, 7), None);
+        assert_eq!(scroll_page(0, -1, 7), None);
+        assert_eq!(scroll_page(0, 1, 2), None);
+    }
+
+    #[test]
+    fn test_text_y_centres() {
+        assert_eq!(Rect::new(0, 40, 100, 20).text_y(1), 46);
+        assert_eq!(Rect::new(0, 40, 100, 4).text_y(1), 40);
+    }
+}
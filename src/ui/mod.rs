@@ -1,192 +1,445 @@
+pub mod animation;
+mod builtin_screens;
+mod components;
+pub mod kiosk;
+pub mod layout;
+mod pomodoro;
+pub mod registry;
+mod render_cache;
+pub mod ui_state;
+mod views;
+
 use anyhow::Result;
 use crate::display::{DisplayManager, colors::*};
+use crate::display::draw_list::{DrawList, FramePresenter};
 use crate::sensors::SensorData;
-use crate::system::{ButtonEvent, SystemInfo};
+use crate::system::SystemInfo;
+use crate::i18n::{self, t, Language};
+use crate::input::{InputEvent, Key};
+use crate::units::{self, ClockFormat, TemperatureUnit};
+use layout::SCREEN_WIDTH;
+use registry::{hash_state, FrameGate, ScreenRegistry, TextRow, TextView};
+use crate::system::uptime_tracker::ReliabilityReport;
+use crate::system::lifetime_stats::LifetimeStats;
 use crate::ota::OtaStatus;
+use crate::network::weather::{WeatherCondition, WeatherData};
+use crate::network::rest_widgets::WidgetValue;
+use crate::network::alertmanager::{AlertSummary, Severity};
+use crate::network::agenda::Agenda;
+use animation::ScreenTransition;
+use kiosk::{DayRange, KioskSource};
+use components::CircularProgress;
+use pomodoro::PomodoroTimer;
+use pomodoro::TimerState;
+use render_cache::RenderCache;
+use ui_state::{AlertFlags, Navigation};
+use crate::hardware::power_monitor::PowerSnapshot;
+use crate::sensors::registry::Sample;
+#[cfg(feature = "screen_air_quality")]
+use crate::sensors::air_quality;
+#[cfg(feature = "screen_agenda")]
+use views::AgendaRow;
+use views::{LargeTextLayout, NetworkFields, NetworkView, OtaFields, PowerRow, SensorFields, SensorView, SettingsFields, SystemFields, SystemLayout};
 use std::time::Instant;
 
-// Text cache entry
-#[derive(Clone)]
-struct TextCache {
-    text: String,
-    x: u16,
-    y: u16,
-    color: u16,
-    rendered: bool,
+/// Brightness change per encoder detent (0-255 scale)
+const BRIGHTNESS_STEP: i32 = 8;
+
+/// A setting changed on the device (rotary encoder) that main should persist
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SettingChange {
+    Brightness(u8),
+    TimerMinutes(u32),
+    Accessibility(bool),
 }
 
 pub struct UiManager {
-    current_screen: usize,
+    // Screens in display order; `nav` holds a position in it
+    screens: ScreenRegistry<UiManager, DisplayManager>,
+    nav: Navigation,
     sensor_data: SensorData,
     last_update: Instant,
     animation_progress: f32,
-    last_rendered_screen: Option<usize>,
+    // Config `show_animations`, and the screen switch being animated
+    show_animations: bool,
+    transition: Option<ScreenTransition>,
     system_info: SystemInfo,
     network_connected: bool,
     network_ip: Option<String>,
+    network_ipv6: Option<String>,
     network_ssid: String,
     network_signal: i8,
     network_gateway: Option<String>,
     network_mac: String,
+    latency_gateway: Vec<Option<u16>>,
+    latency_host: Vec<Option<u16>>,
+    network_disconnect_reasons: String,
     ota_status: OtaStatus,
     // FPS tracking
     fps: f32,
-    // Cached values to avoid redundant updates
-    cached_uptime: String,
-    cached_heap: String,
-    cached_cpu: String,
-    cached_flash: String,
+    // Reliability summary for the System screen
+    reliability_summary: String,
+    reliability_crashes: usize,
+    // Value fields per screen; they redraw only changed characters
+    system_fields: SystemFields,
+    network_fields: NetworkFields,
+    sensor_fields: SensorFields,
+    settings_fields: SettingsFields,
+    ota_fields: OtaFields,
     // Dual-core stats
     cpu0_usage: u8,
     cpu1_usage: u8,
     core_tasks: (u32, u32),
-    cached_temp: String,
-    cached_battery: u8,
     // Pre-allocated string buffer for formatting
     string_buffer: String,
     // Skip render counter
     skip_renders: u32,
-    // FPS rendering
-    force_fps_render: bool,
     total_renders: u32,
-    // Text cache for static labels
-    text_cache: Vec<TextCache>,
-    // OTA screen caching
-    cached_ota_time: u64,
-    cached_network_ip: Option<String>,
-    cached_ota_status_enum: Option<OtaStatus>,
-    ota_screen_initialized: bool,
-    // Screen initialization flags for optimized rendering
-    system_screen_initialized: bool,
-    network_screen_initialized: bool,
-    sensor_screen_initialized: bool,
-    settings_screen_initialized: bool,
-    // Global time caching for all screens
-    global_cached_time: u64,
-    // Alert states
-    temperature_alert: bool,
-    wifi_signal_alert: bool,
-    battery_alert: bool,
-    // Request a full re-render when dynamic state changes
-    render_dirty: bool,
-    // Stability: avoid global mutable statics by caching per-instance
-    render_needed: bool,
-    last_progress_value: u8,
-    sensor_last_battery: u8,
-    sensor_last_temp: f32,
-    sensor_last_light: u16,
-    last_fps_rendered: f32,
+    // What the current screen last drew, for partial updates
+    cache: RenderCache,
+    // Config brightness shown (and adjusted) on the Settings screen
+    brightness: u8,
+    pending_setting: Option<SettingChange>,
+    // Latest weather from network::weather
+    weather: Option<WeatherData>,
+    // Latest values from network::rest_widgets
+    custom_widgets: Vec<WidgetValue>,
+    // Latest summary from network::alertmanager (None when disabled)
+    ops_alerts: Option<AlertSummary>,
+    // Latest events from network::agenda (None when disabled)
+    agenda: Option<Agenda>,
+    // Kiosk mode: config `kiosk_enabled`, the parsed `kiosk_metric` (None if
+    // invalid) and its range today, tracked whether or not kiosk mode is on
+    kiosk_enabled: bool,
+    kiosk_metric: String,
+    kiosk_source: Option<KioskSource>,
+    kiosk_range: DayRange,
+    // Crash-loop safe mode (system::safe_mode), flagged in the alert bar
+    safe_mode: bool,
+    // Pomodoro timer screen
+    timer: PomodoroTimer,
+    timer_ring: CircularProgress,
+    timer_finished_pending: bool,
+    alerts: AlertFlags,
+    // State hashes of the last rendered screen content and overlays
+    screen_gate: FrameGate,
+    overlay_gate: FrameGate,
+    // Last frame of the screens that record draw lists
+    frame_presenter: FramePresenter,
+    // Running app slot, fixed for the lifetime of the boot
+    ota_partition: String,
+    power_warning: Option<String>,
+    // Latest INA219 reading, None when no power monitor is fitted
+    power_monitor: Option<PowerSnapshot>,
+    // Latest readings of the registered sensors, ordered by key
+    sensor_readings: Vec<Sample>,
+    // Lifetime counters, refreshed every few seconds
+    lifetime_stats: Option<LifetimeStats>,
+    // Config `accessibility_mode`: large-text layout in the high-contrast
+    // palette, and the first row of the page shown
+    accessibility: bool,
+    text_first: usize,
 }
 
 impl UiManager {
     pub fn new(_display: &mut DisplayManager) -> Result<Self> {
+        let mut screens = ScreenRegistry::new();
+        builtin_screens::register_all(&mut screens);
+        log::info!("UI: screens {:?}", screens.names());
         Ok(Self {
-            current_screen: 0,
+            screens,
+            nav: Navigation::default(),
             sensor_data: SensorData::default(),
             last_update: Instant::now(),
             animation_progress: 0.0,
-            last_rendered_screen: None,
+            show_animations: true,
+            transition: None,
             system_info: SystemInfo::new(),
             network_connected: false,
             network_ip: None,
+            network_ipv6: None,
             network_ssid: String::from("Not connected"),
             network_signal: -100,
             network_gateway: None,
             network_mac: String::from("Unknown"),
+            latency_gateway: Vec::new(),
+            latency_host: Vec::new(),
+            network_disconnect_reasons: String::new(),
             ota_status: OtaStatus::Idle,
             fps: 0.0,
-            cached_uptime: String::new(),
-            cached_heap: String::new(),
-            cached_cpu: String::new(),
-            cached_flash: String::new(),
-            cached_temp: String::new(),
-            cached_battery: 0,
+            reliability_summary: String::new(),
+            reliability_crashes: 0,
+            system_fields: SystemFields::new(&SystemLayout::new()),
+            network_fields: NetworkFields::new(),
+            sensor_fields: SensorFields::new(),
+            settings_fields: SettingsFields::new(),
+            ota_fields: OtaFields::new(),
             string_buffer: String::with_capacity(32),
             skip_renders: 0,
-            force_fps_render: false,
             total_renders: 0,
-            text_cache: Vec::with_capacity(20),
-            cached_ota_time: 0,
-            cached_network_ip: None,
-            cached_ota_status_enum: None,
-            ota_screen_initialized: false,
-            system_screen_initialized: false,
-            network_screen_initialized: false,
-            sensor_screen_initialized: false,
-            settings_screen_initialized: false,
-            global_cached_time: 0,
+            cache: RenderCache::default(),
+            brightness: 204,
+            pending_setting: None,
+            weather: None,
+            custom_widgets: Vec::new(),
+            ops_alerts: None,
+            agenda: None,
+            kiosk_enabled: false,
+            kiosk_metric: String::new(),
+            kiosk_source: None,
+            kiosk_range: DayRange::default(),
+            safe_mode: false,
+            timer: PomodoroTimer::new(25),
+            timer_ring: CircularProgress::new(150, 92, 62, 8),
+            timer_finished_pending: false,
             cpu0_usage: 0,
             cpu1_usage: 0,
             core_tasks: (0, 0),
-            temperature_alert: false,
-            wifi_signal_alert: false,
-            battery_alert: false,
-            render_dirty: true,
-            render_needed: true,
-            last_progress_value: 255,
-            sensor_last_battery: 255,
-            sensor_last_temp: -999.0,
-            sensor_last_light: 65535,
-            last_fps_rendered: -1.0,
+            alerts: AlertFlags::default(),
+            screen_gate: FrameGate::default(),
+            overlay_gate: FrameGate::default(),
+            frame_presenter: FramePresenter::default(),
+            ota_partition: crate::ota::partitions::running_label(),
+            power_warning: None,
+            power_monitor: None,
+            sensor_readings: Vec::new(),
+            lifetime_stats: None,
+            accessibility: false,
+            text_first: 0,
         })
     }
 
 
-    pub fn handle_button_event(&mut self, event: ButtonEvent) -> Result<()> {
+    /// Apply one event from the input bus
+    pub fn handle_input(&mut self, event: InputEvent) -> Result<()> {
+        // A wall gauge has nothing to navigate
+        if self.kiosk_enabled {
+            return Ok(());
+        }
+        
+        // The visible screen gets first refusal (e.g. USER controls the timer)
+        if let Some(screen) = self.screens.get(self.nav.current()) {
+            if screen.handle_input(self, event) {
+                return Ok(());
+            }
+            if self.accessibility && self.scroll_text(screen, event) {
+                return Ok(());
+            }
+        }
+        
         match event {
-            ButtonEvent::Button1Click => {
+            InputEvent::Click(Key::Boot) => {
                 log::info!("Previous screen");
-                self.current_screen = self.current_screen.saturating_sub(1);
+                self.nav.previous();
                 self.animation_progress = 0.0;
             }
-            ButtonEvent::Button2Click => {
+            InputEvent::Click(Key::User) => {
                 log::info!("Next screen");
-                self.current_screen = (self.current_screen + 1) % 5;
+                self.nav.next(self.screens.len());
                 self.animation_progress = 0.0;
             }
-            ButtonEvent::Button1LongPress | ButtonEvent::Button2LongPress => {
+            InputEvent::LongPress(_) => {
                 log::info!("Long press detected");
             }
-            ButtonEvent::Button1Press | ButtonEvent::Button1Release | 
-            ButtonEvent::Button2Press | ButtonEvent::Button2Release => {
+            InputEvent::Press(_) | InputEvent::Release(_) => {
                 // Ignore press/release events, only handle clicks
             }
-            ButtonEvent::BothButtonsLongPress => {
-                // Shutdown is handled in main loop, ignore here
-                log::info!("UI: Shutdown button combination detected");
+            InputEvent::ShutdownCombo | InputEvent::SelfTestCombo => {
+                // Shutdown and self-test are handled in main loop, ignore here
+                log::info!("UI: {:?} button combination detected", event);
+            }
+            InputEvent::Rotate(steps) => {
+                // Screens with an adjustable value consumed this above
+                self.nav.rotate(steps, self.screens.len());
+                self.animation_progress = 0.0;
+            }
+            InputEvent::Select => {
+                // The encoder button acts as the USER button
+                return self.handle_input(InputEvent::Click(Key::User));
+            }
+            InputEvent::ShowScreen(screen) => {
+                if self.nav.show(screen, self.screens.len()) {
+                    self.animation_progress = 0.0;
+                } else {
+                    log::warn!("UI: no screen {}", screen);
+                }
             }
         }
         Ok(())
     }
 
+    /// In the large-text layout the encoder pages through a long screen before
+    /// moving on to the next one, and a long BOOT press pages round it
+    fn scroll_text(&mut self, screen: &dyn registry::Screen<UiManager, DisplayManager>, event: InputEvent) -> bool {
+        let Some(view) = screen.text_view(self) else { return false };
+        let first = match event {
+            InputEvent::Rotate(steps) => layout::scroll_page(self.text_first, steps, view.rows.len()),
+            InputEvent::LongPress(Key::Boot) => Some(layout::scroll_page(self.text_first, 1, view.rows.len()).unwrap_or(0)),
+            _ => None,
+        };
+        let Some(first) = first else { return false };
+        self.text_first = first;
+        true
+    }
+
+    /// Config `accessibility_mode`: every screen in the large-text layout and
+    /// the high-contrast palette
+    pub fn set_accessibility(&mut self, enabled: bool) {
+        if self.accessibility != enabled {
+            self.accessibility = enabled;
+            self.text_first = 0;
+            self.redraw_all();
+        }
+    }
+
+    /// Config `kiosk_enabled` / `kiosk_metric`: one metric full screen instead
+    /// of the screen rotation
+    pub fn set_kiosk(&mut self, enabled: bool, metric: &str) {
+        if self.kiosk_metric != metric {
+            self.kiosk_metric = metric.to_string();
+            self.kiosk_source = KioskSource::parse(metric);
+            self.kiosk_range.reset();
+            self.redraw_all();
+        }
+        if self.kiosk_enabled != enabled {
+            self.kiosk_enabled = enabled;
+            log::info!("Kiosk mode {}", if enabled { "on" } else { "off" });
+            self.redraw_all();
+        }
+    }
+
+    /// Booted in safe mode: a "SAFE MODE" bar stays up on every screen
+    pub fn set_safe_mode(&mut self, safe_mode: bool) {
+        self.safe_mode = safe_mode;
+    }
+
+    /// Switch the on-screen language and redraw with it
+    pub fn set_language(&mut self, language: Language) {
+        if i18n::language() != language {
+            i18n::set_language(language);
+            self.redraw_all();
+        }
+    }
+
+    /// Switch display units and redraw with them
+    pub fn set_units(&mut self, temperature: TemperatureUnit, clock: ClockFormat) {
+        if units::temperature_unit() != temperature || units::clock_format() != clock {
+            units::set_units(temperature, clock);
+            self.redraw_all();
+        }
+    }
+
+    /// Local time once SNTP has synced, uptime until then
+    fn header_clock(&self) -> String {
+        crate::system::clock::local_minutes_of_day()
+            .map(|minutes| units::format_time_of_day(minutes).into())
+            .unwrap_or_else(|| self.system_info.format_uptime())
+    }
+
+    /// Changes as often as the header clock is refreshed (every 5 s)
+    fn header_tick(&self) -> u64 {
+        ui_state::header_tick(self.system_info.get_uptime().as_secs())
+    }
+
+    /// Activity animation progress in percent
+    fn progress_percent(&self) -> u8 {
+        ui_state::progress_percent(self.animation_progress)
+    }
+
+    /// Show the named screens in this order (config `screen_order`); empty shows all
+    /// Config `show_animations`; switching it off also ends a running transition
+    pub fn set_animations(&mut self, enabled: bool) {
+        self.show_animations = enabled;
+        if !enabled && self.transition.is_some() {
+            self.transition = None;
+            self.redraw_all();
+        }
+    }
+
+    pub fn set_screen_order(&mut self, names: &[String]) {
+        let current = self.current_screen_name();
+        if !self.screens.set_order(names) {
+            return;
+        }
+        log::info!("UI: screen order {:?}", self.screens.names());
+        // Stay on the same screen if it is still in the rotation
+        self.nav.reset(current.and_then(|name| self.screens.position(name)).unwrap_or(0));
+    }
+
+    /// Name of the visible screen
+    pub fn current_screen_name(&self) -> Option<&'static str> {
+        self.screens.get(self.nav.current()).map(|s| s.name())
+    }
+
+    /// Jump to the named screen; false if it is not in the rotation
+    pub fn show_screen(&mut self, name: &str) -> bool {
+        let Some(index) = self.screens.position(name) else {
+            return false;
+        };
+        self.animation_progress = 0.0;
+        self.nav.show(index, self.screens.len())
+    }
+
+    /// Setting changed with the encoder since the last call
+    pub fn take_setting_change(&mut self) -> Option<SettingChange> {
+        self.pending_setting.take()
+    }
+
     pub fn update_sensor_data(&mut self, data: SensorData) {
-        // Check for temperature alert (>35°C is high for ambient temperature)
-        self.temperature_alert = data._temperature > 35.0;
-        
-        // Check for battery alert (<10% is critical)
-        self.battery_alert = data._battery_percentage < 10 && !data._is_on_usb;
-        
+        self.alerts.update_sensors(data._temperature, data._battery_percentage, data._is_on_usb);
         self.sensor_data = data;
-        
-        // Force render when sensor data updates
-        self.force_next_render();
-        self.render_dirty = true;
-        self.render_needed = true;
     }
     
-    pub fn update_network_status(&mut self, connected: bool, ip: Option<String>, ssid: String, signal: i8, gateway: Option<String>, mac: String) {
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_network_status(&mut self, connected: bool, ip: Option<String>, ipv6: Option<String>, ssid: String, signal: i8, gateway: Option<String>, mac: String) {
         self.network_connected = connected;
         self.network_ip = ip;
+        self.network_ipv6 = ipv6;
         self.network_ssid = ssid;
         self.network_signal = signal;
         self.network_gateway = gateway;
         self.network_mac = mac;
-        
-        // Check for WiFi signal alert (<-80 dBm is poor signal)
-        self.wifi_signal_alert = connected && signal < -80;
-        // Mark UI dirty so the Network screen re-renders immediately
-        self.render_dirty = true;
-        self.render_needed = true;
+        self.alerts.update_network(connected, signal);
+    }
+    
+    /// Ping history for the Network screen sparkline
+    pub fn update_latency(&mut self, gateway: Vec<Option<u16>>, host: Vec<Option<u16>>) {
+        self.latency_gateway = gateway;
+        self.latency_host = host;
+    }
+    
+    /// Top WiFi disconnect reasons for the Network screen
+    pub fn update_disconnect_reasons(&mut self, summary: String) {
+        self.network_disconnect_reasons = summary;
+    }
+    
+    pub fn update_weather(&mut self, weather: WeatherData) {
+        self.weather = Some(weather);
+    }
+    
+    pub fn update_custom_widgets(&mut self, widgets: Vec<WidgetValue>) {
+        self.custom_widgets = widgets;
+    }
+    
+    pub fn update_ops_alerts(&mut self, alerts: Option<AlertSummary>) {
+        self.ops_alerts = alerts;
+    }
+    
+    pub fn update_agenda(&mut self, agenda: Option<Agenda>) {
+        self.agenda = agenda;
+    }
+    
+    pub fn set_timer_minutes(&mut self, minutes: u32) {
+        self.timer.set_minutes(minutes);
+    }
+    
+    pub fn set_brightness(&mut self, brightness: u8) {
+        self.brightness = brightness;
+    }
+    
+    /// True once after a timer session completes (for buzzer/LED output)
+    pub fn take_timer_finished(&mut self) -> bool {
+        std::mem::take(&mut self.timer_finished_pending)
     }
     
     pub fn update_ota_status(&mut self, status: OtaStatus) {
@@ -199,6 +452,27 @@ impl UiManager {
         self.core_tasks = (tasks0, tasks1);
     }
     
+    pub fn update_reliability(&mut self, report: &ReliabilityReport) {
+        self.reliability_crashes = report.crashes;
+        self.reliability_summary = ui_state::reliability_summary(report.availability_7d, report.boot_count, report.crashes);
+    }
+    
+    pub fn update_power_warning(&mut self, warning: Option<String>) {
+        self.power_warning = warning;
+    }
+    
+    pub fn update_power_monitor(&mut self, snapshot: Option<PowerSnapshot>) {
+        self.power_monitor = snapshot;
+    }
+    
+    pub fn update_sensor_readings(&mut self, readings: Vec<Sample>) {
+        self.sensor_readings = readings;
+    }
+    
+    pub fn update_lifetime_stats(&mut self, stats: Option<LifetimeStats>) {
+        self.lifetime_stats = stats;
+    }
+    
     pub fn update_fps(&mut self, fps: f32) {
         self.fps = fps;
     }
@@ -208,14 +482,9 @@ impl UiManager {
         self.cpu1_usage = cpu1;
     }
     
-    fn force_next_render(&mut self) {
-        // Force render by clearing cached values
-        self.cached_uptime.clear();
-        self.cached_heap.clear();
-        self.cached_cpu.clear();
-        self.cached_flash.clear();
-        self.cached_temp.clear();
-        self.cached_battery = 255; // Invalid value to force update
+    /// Redraw the current screen from scratch (e.g. after the panel was re-initialised)
+    pub fn redraw_all(&mut self) {
+        self.nav.invalidate();
     }
 
     pub fn update(&mut self) -> Result<()> {
@@ -228,40 +497,75 @@ impl UiManager {
             self.last_update = Instant::now();
         }
         
+        // Timer runs regardless of the visible screen
+        if self.timer.tick() {
+            log::info!("Timer session complete ({} today)", self.timer.sessions_today());
+            self.timer_finished_pending = true;
+        }
+        
         Ok(())
     }
 
     pub fn render(&mut self, display: &mut DisplayManager) -> Result<bool> {
-        // Track if anything needs updating (per-instance, no globals)
         self.total_renders += 1;
-        // If state changed, request a render
-        if self.render_dirty {
-            self.render_needed = true;
-        }
         
         // Check if screen changed
-        let screen_changed = self.last_rendered_screen != Some(self.current_screen);
+        let now = Instant::now();
+        let change = self.nav.enter();
+        let mut screen_changed = change.is_some();
+        if let Some(change) = change {
+            log::info!("Switching to screen {}", change.to);
+            self.text_first = 0;
+            // redraw_all() forgets the previous screen, so it never animates
+            self.transition = change.from
+                .filter(|_| animation::should_animate(self.show_animations, self.fps))
+                .map(|from| ScreenTransition::between(from, change.to, self.screens.len(), SCREEN_WIDTH, now));
+        }
+        
+        // A transition redraws the whole screen every frame, shifted or faded,
+        // and once more untransformed when it ends
+        let transform = match &self.transition {
+            Some(transition) => {
+                let frame = transition.frame_at(now);
+                if frame.is_none() {
+                    self.transition = None;
+                }
+                screen_changed = true;
+                frame.unwrap_or_default()
+            }
+            None => Default::default(),
+        };
         if screen_changed {
-            log::info!("Switching to screen {}", self.current_screen);
-            self.last_rendered_screen = Some(self.current_screen);
-            self.render_needed = true;
-            
-            // Reset all screen initialization flags when switching
-            self.system_screen_initialized = false;
-            self.network_screen_initialized = false;
-            self.sensor_screen_initialized = false;
-            self.settings_screen_initialized = false;
-            self.ota_screen_initialized = false;
+            self.screen_gate.invalidate();
+            self.frame_presenter.invalidate();
+            self.cache.invalidate();
+        }
+        
+        self.track_kiosk_range();
+        if self.kiosk_enabled {
+            return self.render_kiosk(display);
         }
         
-        // Skip render if nothing changed (except on screen change)
-        if !self.render_needed && !screen_changed {
+        // Skip the frame if neither the screen's state nor the overlays changed
+        let screen = self.screens.get(self.nav.current());
+        let text_view = screen.filter(|_| self.accessibility).and_then(|s| s.text_view(self));
+        if text_view.as_ref().is_some_and(|view| self.text_first >= view.rows.len()) {
+            self.text_first = 0;
+        }
+        let screen_hash = match &text_view {
+            Some(view) => hash_state(&(view, self.text_first)),
+            None => screen.map_or(0, |s| s.state_hash(self)),
+        };
+        let alerts = self.active_alerts();
+        let overlay_hash = hash_state(&(self.ota_status, &alerts, Self::alert_slot(alerts.len())));
+        let screen_due = self.screen_gate.should_render(screen_hash);
+        let overlay_due = self.overlay_gate.should_render(overlay_hash);
+        if !screen_due && !overlay_due {
             self.skip_renders += 1;
             // Still need to update and render FPS counter
             self.render_fps_counter(display)?;
             return Ok(false); // Frame was skipped
         }
-        self.render_needed = false; // Reset flag
         
         // Log render efficiency every 100 renders
         if self.total_renders % 100 == 0 {
@@ -271,233 +575,414 @@ impl UiManager {
         }
         
         // Render the current screen
-        match self.current_screen {
-            0 => self.render_system_screen(display, screen_changed)?,
-            1 => self.render_network_screen(display, screen_changed)?,
-            2 => self.render_sensor_screen(display, screen_changed)?,
-            3 => self.render_settings_screen(display, screen_changed)?,
-            4 => self.render_ota_screen(display, screen_changed)?,
-            _ => {}
+        display.set_high_contrast(self.accessibility);
+        if let (Some(screen), true) = (screen, screen_due) {
+            display.set_transform(transform.offset_x, transform.fade);
+            let result = match &text_view {
+                Some(view) => self.render_text_view(display, view),
+                None => screen.render(self, display, screen_changed),
+            };
+            display.set_transform(0, 255);
+            result?;
         }
         
         // Render FPS counter (always visible in corner)
-        // Force render after screen change to ensure it's visible
-        if screen_changed {
-            self.force_fps_render = true;
-        }
         self.render_fps_counter(display)?;
         
         // Render OTA overlay if OTA is in progress
-        if let OtaStatus::Downloading { progress } = self.ota_status {
-            self.render_ota_overlay(display, progress)?;
+        if let Some(progress) = ui_state::ota_overlay(self.ota_status) {
+            views::draw_ota_overlay(display, progress)?;
         }
         
         // Render alerts if any are active
-        self.render_alerts(display)?;
+        if !alerts.is_empty() {
+            let (label, value, color) = &alerts[Self::alert_slot(alerts.len())];
+            let scale = if self.accessibility { 2 } else { 1 };
+            views::draw_alert_bar(display, label, value, *color, scale)?;
+        }
         
-        // Frame was rendered; clear dirty flag
-        self.render_dirty = false;
         Ok(true) // Frame was rendered
     }
 
-    fn render_system_screen(&mut self, display: &mut DisplayManager, screen_changed: bool) -> Result<()> {
-        // Early exit if nothing needs updating
-        if !screen_changed && self.system_screen_initialized {
-            // Check if any values actually changed
-            let heap_kb = self.system_info.get_free_heap_kb();
-            let heap_str = format!("{heap_kb} KB");
-            let cpu_freq = self.system_info.get_cpu_freq_mhz();
-            let cpu_str = format!("{cpu_freq} MHz");
-            let temp_str = format!("{:.1}°C", self.sensor_data._temperature);
-            
-            if heap_str == self.cached_heap && 
-               cpu_str == self.cached_cpu && 
-               temp_str == self.cached_temp &&
-               self.sensor_data._battery_percentage == self.cached_battery {
-                // Only update time every 5 seconds
-                let current_seconds = self.system_info.get_uptime().as_secs();
-                if current_seconds >= self.global_cached_time + 5 {
-                    self.global_cached_time = current_seconds;
-                    display.fill_rect(235, 5, 65, 20, PRIMARY_BLUE)?;
-                    let time_str = self.system_info.format_uptime();
-                    display.draw_text(240, 8, &time_str, WHITE, None, 1)?;
+    /// Current value of the kiosk metric: label, number (°C for temperature),
+    /// unit and decimals shown
+    fn kiosk_reading(&self) -> (String, Option<f32>, &str, usize) {
+        let Some(source) = self.kiosk_source.as_ref() else {
+            return (self.kiosk_metric.clone(), None, "", 0);
+        };
+        match source {
+            KioskSource::Temperature => (t("kiosk.temperature").to_string(), Some(self.sensor_data._temperature), units::temperature_unit().suffix(), 1),
+            KioskSource::Battery => {
+                let percent = (self.sensor_data._battery_voltage > 0).then_some(self.sensor_data._battery_percentage as f32);
+                (t("kiosk.battery").to_string(), percent, "%", 0)
+            }
+            KioskSource::Sensor(key) => match self.sensor_readings.iter().find(|s| &s.key == key) {
+                Some(sample) => (key.clone(), Some(sample.reading.value), sample.reading.unit, sample.reading.decimals as usize),
+                None => (key.clone(), None, "", 0),
+            },
+            KioskSource::Widget(label) => match self.custom_widgets.iter().find(|w| &w.label == label) {
+                Some(widget) => {
+                    let value = widget.value.as_deref();
+                    let decimals = value.and_then(|v| v.split_once('.')).map_or(0, |(_, frac)| frac.len());
+                    (label.clone(), value.and_then(|v| v.trim().parse().ok()), widget.unit.as_str(), decimals)
                 }
-                return Ok(());
+                None => (label.clone(), None, "", 0),
+            },
+        }
+    }
+
+    /// Feed today's min/max with the current kiosk value
+    fn track_kiosk_range(&mut self) {
+        let (_, value, _, _) = self.kiosk_reading();
+        let Some(value) = value else { return };
+        let day = crate::system::clock::now_epoch()
+            .and_then(crate::system::clock::local_day_and_minutes)
+            .map_or(0, |(day, _)| day);
+        self.kiosk_range.update(day, value);
+    }
+
+    /// Kiosk mode replaces the screens; the OTA overlay still shows progress
+    fn render_kiosk(&mut self, display: &mut DisplayManager) -> Result<bool> {
+        let (label, value, unit, decimals) = self.kiosk_reading();
+        let is_temperature = self.kiosk_source == Some(KioskSource::Temperature);
+        let format = |n: f32| {
+            let n = if is_temperature { units::temperature_unit().from_celsius(n) } else { n };
+            format!("{:.*}", decimals, n)
+        };
+        let value = value.map(format);
+        let min_max = self.kiosk_range.min_max().map(|(min, max)| (format(min), format(max)));
+        let frame = views::kiosk_frame(&views::KioskView {
+            label: &label,
+            value: value.as_deref(),
+            unit,
+            min_max: min_max.as_ref().map(|(min, max)| (min.as_str(), max.as_str())),
+        });
+        let hash = hash_state(&(&label, &value, unit, &min_max, self.ota_status));
+        if !self.screen_gate.should_render(hash) {
+            self.skip_renders += 1;
+            return Ok(false);
+        }
+        display.set_high_contrast(self.accessibility);
+        self.frame_presenter.present(frame, display)?;
+        if let Some(progress) = ui_state::ota_overlay(self.ota_status) {
+            views::draw_ota_overlay(display, progress)?;
+            // The overlay covered part of the frame
+            self.frame_presenter.invalidate();
+        }
+        Ok(true)
+    }
+
+    /// Any screen in the large-text layout; only commands that changed are redrawn
+    fn render_text_view(&mut self, display: &mut DisplayManager, view: &TextView) -> Result<()> {
+        let frame = views::large_text_frame(&LargeTextLayout::new(), view, self.text_first);
+        self.frame_presenter.present(frame, display)?;
+        Ok(())
+    }
+
+    fn system_text_view(&self) -> TextView {
+        let mut uptime = String::new();
+        ui_state::write_uptime(&mut uptime, self.system_info.get_uptime().as_secs());
+        let (flash_total, app_size) = self.system_info.get_flash_info();
+        let psram_info = crate::psram::PsramAllocator::get_info();
+        let psram = ui_state::format_psram(psram_info.available, psram_info.free_size);
+        let temperature = self.sensor_data._temperature;
+        let values = [
+            (uptime, PRIMARY_GREEN),
+            (ui_state::format_heap(self.system_info.get_free_heap_kb()).into(), PRIMARY_GREEN),
+            (ui_state::format_cpu(self.system_info.get_cpu_freq_mhz(), self.cpu0_usage, self.cpu1_usage).into(), PRIMARY_GREEN),
+            (ui_state::format_flash(app_size, flash_total).into(), PRIMARY_GREEN),
+            (units::format_temperature(temperature, 1).into(), ui_state::temperature_color(temperature)),
+            (psram.0.into(), psram.1),
+            (self.reliability_summary.clone(), if self.reliability_crashes > 0 { YELLOW } else { PRIMARY_GREEN }),
+        ];
+        TextView {
+            title: t("system.title"),
+            rows: views::SYSTEM_LABELS.iter().zip(values).map(|(label, (value, color))| TextRow::new(t(label), value, color)).collect(),
+        }
+    }
+
+    fn network_text_view(&self) -> TextView {
+        let (status, status_color) = if self.network_connected { (t("network.connected"), PRIMARY_GREEN) } else { (t("network.disconnected"), PRIMARY_RED) };
+        let mut rows = vec![
+            TextRow::new(t("common.status"), status, status_color),
+            TextRow::new(t("network.ssid"), self.network_ssid.as_str(), TEXT_PRIMARY),
+        ];
+        for ip in [&self.network_ip, &self.network_ipv6].into_iter().flatten() {
+            rows.push(TextRow::new(t("network.ip"), ip.as_str(), TEXT_PRIMARY));
+        }
+        if self.network_connected {
+            let (quality, color) = views::signal_quality(self.network_signal);
+            rows.push(TextRow::new(t("network.signal"), format!("{} dBm {}", self.network_signal, quality), color));
+            rows.push(TextRow::new(t("network.gateway"), self.network_gateway.as_deref().unwrap_or(t("common.not_available")), TEXT_PRIMARY));
+            rows.push(TextRow::new(t("network.mac"), self.network_mac.as_str(), TEXT_PRIMARY));
+        }
+        TextView { title: t("network.title"), rows }
+    }
+
+    fn sensor_text_view(&self) -> TextView {
+        let data = &self.sensor_data;
+        let battery_color = if data._is_charging { PRIMARY_BLUE }
+                            else if data._battery_percentage > 20 { PRIMARY_GREEN }
+                            else { PRIMARY_RED };
+        let battery = match data._battery_voltage {
+            0 => t("sensor.no_battery").to_string(),
+            mv => format!("{}% {:.2}V", data._battery_percentage, mv as f32 / 1000.0),
+        };
+        let mut rows = vec![
+            TextRow::new(t("sensor.battery"), battery, battery_color),
+            TextRow::new(t("sensor.temp"), units::format_temperature(data._temperature, 1), ui_state::temperature_color(data._temperature)),
+        ];
+        match self.power_monitor {
+            Some(power) => rows.push(TextRow::new(t("sensor.power"), format!("{:.0} mW", power.reading.power_mw), TEXT_PRIMARY)),
+            None if data._light_level > 0 => rows.push(TextRow::new(t("sensor.light"), format!("{} lux", data._light_level), TEXT_PRIMARY)),
+            None => {}
+        }
+        if let Some(warning) = &self.power_warning {
+            rows.push(TextRow::new(t("sensor.power"), warning.as_str(), YELLOW));
+        }
+        for sample in &self.sensor_readings {
+            rows.push(TextRow::new(sample.reading.quantity, sample.reading.display(), TEXT_PRIMARY));
+        }
+        TextView { title: t("sensor.title"), rows }
+    }
+
+    fn settings_text_view(&self) -> TextView {
+        let large_text = if self.accessibility { t("settings.on") } else { t("settings.off") };
+        TextView {
+            title: t("settings.title"),
+            rows: vec![
+                TextRow::new(t("settings.brightness"), format!("{}%", self.brightness as u32 * 100 / 255), TEXT_PRIMARY),
+                TextRow::new(t("settings.large_text"), large_text, PRIMARY_GREEN),
+                TextRow::new(t("settings.version"), crate::version::DISPLAY_VERSION, TEXT_PRIMARY),
+            ],
+        }
+    }
+
+    fn ota_text_view(&self) -> TextView {
+        let mut buf = String::new();
+        let (status, status_color) = ui_state::ota_status_text(self.ota_status, &mut buf);
+        let mut rows = vec![
+            TextRow::new(t("common.status"), status, status_color),
+            TextRow::new(t("ota.firmware"), crate::version::DISPLAY_VERSION, TEXT_PRIMARY),
+            TextRow::new(t("ota.partition"), self.ota_partition.as_str(), TEXT_PRIMARY),
+        ];
+        match self.network_ip.as_deref().filter(|_| self.network_connected) {
+            Some(ip) => rows.push(TextRow::new(t("ota.upload"), format!("{}:8080/ota", ip), TEXT_PRIMARY)),
+            None => rows.push(TextRow::new(t("ota.network_required"), "", PRIMARY_RED)),
+        }
+        TextView { title: t("ota.title"), rows }
+    }
+
+    #[cfg(feature = "screen_weather")]
+    fn weather_text_view(&self) -> TextView {
+        let Some(weather) = self.weather.as_ref() else {
+            return TextView { title: t("weather.title"), rows: vec![TextRow::new(t("weather.no_data"), "", TEXT_PRIMARY)] };
+        };
+        let mut rows = vec![
+            TextRow::new(weather.condition.label(), units::format_temperature(weather.temperature_c, 1), WHITE),
+            TextRow::new("Wind", format!("{:.0} km/h", weather.wind_kmh), TEXT_PRIMARY),
+        ];
+        for point in &weather.forecast {
+            rows.push(TextRow::new(units::format_time_of_day(point.hour as u16 * 60), units::format_temperature(point.temperature_c, 0), TEXT_PRIMARY));
+        }
+        TextView { title: t("weather.title"), rows }
+    }
+
+    #[cfg(feature = "screen_widgets")]
+    fn custom_text_view(&self) -> TextView {
+        if self.custom_widgets.is_empty() {
+            return TextView { title: t("widgets.title"), rows: vec![TextRow::new(t("widgets.none"), "", TEXT_PRIMARY)] };
+        }
+        let rows = self.custom_widgets.iter().map(|widget| match widget.value {
+            Some(ref value) => TextRow::new(widget.label.as_str(), format!("{} {}", value, widget.unit).trim_end(), PRIMARY_GREEN),
+            None => TextRow::new(widget.label.as_str(), "--", TEXT_SECONDARY),
+        }).collect();
+        TextView { title: t("widgets.title"), rows }
+    }
+
+    #[cfg(feature = "screen_alerts")]
+    fn ops_alerts_text_view(&self) -> TextView {
+        let Some(summary) = self.ops_alerts.as_ref() else {
+            return TextView { title: t("alerts.title"), rows: vec![TextRow::new(t("alerts.not_configured"), "", TEXT_PRIMARY)] };
+        };
+        let label = t(if summary.total == 1 { "alerts.firing_one" } else { "alerts.firing_many" });
+        let count_color = summary.worst_severity().filter(|_| summary.total > 0).map_or(PRIMARY_GREEN, severity_color);
+        let mut rows = vec![TextRow::new(label, summary.total.to_string(), count_color)];
+        if let Some(ref error) = summary.error {
+            rows.push(TextRow::new(t("common.status"), error.as_str(), YELLOW));
+        }
+        for alert in &summary.top {
+            rows.push(TextRow::new(alert.name.as_str(), alert.summary.as_str(), severity_color(alert.severity)));
+        }
+        TextView { title: t("alerts.title"), rows }
+    }
+
+    /// Latest value of a registry key, e.g. `scd40.co2`
+    #[cfg(feature = "screen_air_quality")]
+    fn sensor_value(&self, key: &str) -> Option<f32> {
+        self.sensor_readings.iter().find(|s| s.key == key).map(|s| s.reading.value)
+    }
+
+    /// Measured CO2 from the SCD40, else the SGP30's estimate; with its key
+    #[cfg(feature = "screen_air_quality")]
+    fn co2_reading(&self) -> Option<(&'static str, f32)> {
+        [air_quality::CO2_KEY, air_quality::ECO2_KEY].into_iter()
+            .find_map(|key| self.sensor_value(key).map(|ppm| (key, ppm)))
+    }
+
+    #[cfg(feature = "screen_air_quality")]
+    fn air_quality_text_view(&self) -> TextView {
+        let mut rows = Vec::new();
+        if let Some((key, ppm)) = self.co2_reading() {
+            let quality = air_quality::AirQuality::from_co2(ppm);
+            let label = if key == air_quality::CO2_KEY { "CO2" } else { t("aq.estimated") };
+            rows.push(TextRow::new(label, format!("{:.0} ppm {}", ppm, t(quality.label_key())), views::air_quality_color(quality)));
+        }
+        if let Some(tvoc) = self.sensor_value(air_quality::TVOC_KEY) {
+            rows.push(TextRow::new("TVOC", format!("{:.0} ppb", tvoc), views::air_quality_color(air_quality::AirQuality::from_tvoc(tvoc))));
+        }
+        if let Some(temperature) = self.sensor_value(air_quality::TEMPERATURE_KEY) {
+            rows.push(TextRow::new(t("sensor.temp"), units::format_temperature(temperature, 1), TEXT_PRIMARY));
+        }
+        if let Some(humidity) = self.sensor_value(air_quality::HUMIDITY_KEY) {
+            rows.push(TextRow::new(t("aq.humidity"), format!("{:.0}%", humidity), TEXT_PRIMARY));
+        }
+        if rows.is_empty() {
+            rows.push(TextRow::new(t("aq.none"), "", TEXT_PRIMARY));
+        }
+        TextView { title: t("aq.title"), rows }
+    }
+
+    /// Upcoming events as shown, with their start relative to today
+    #[cfg(feature = "screen_agenda")]
+    fn agenda_rows(&self) -> Option<Vec<AgendaRow<'_>>> {
+        use crate::network::ics;
+        use crate::system::clock;
+        let agenda = self.agenda.as_ref()?;
+        let now = clock::now_epoch().unwrap_or(0);
+        let (today, _) = clock::local_day_and_minutes(now).unwrap_or_default();
+        Some(agenda.upcoming(now).map(|event| {
+            let (day, minutes) = clock::local_day_and_minutes(event.start).unwrap_or((today, 0));
+            let (_, _, day_of_month) = ics::civil_from_days(day);
+            let minutes = (!event.all_day).then_some(minutes);
+            AgendaRow {
+                when: ui_state::agenda_when(day - today, ics::weekday(day), day_of_month, minutes, event.start <= now),
+                title: &event.title,
+                today: day <= today,
             }
+        }).collect())
+    }
+
+    #[cfg(feature = "screen_agenda")]
+    fn agenda_text_view(&self) -> TextView {
+        let Some(rows) = self.agenda_rows() else {
+            return TextView { title: t("agenda.title"), rows: vec![TextRow::new(t("agenda.not_configured"), "", TEXT_PRIMARY)] };
+        };
+        let mut text_rows: Vec<TextRow> = rows.into_iter()
+            .map(|row| TextRow::new(row.when, row.title, if row.today { PRIMARY_GREEN } else { TEXT_PRIMARY }))
+            .collect();
+        if text_rows.is_empty() {
+            text_rows.push(TextRow::new(t("agenda.empty"), "", TEXT_PRIMARY));
+        }
+        if let Some(error) = self.agenda.as_ref().and_then(|a| a.error.as_deref()) {
+            text_rows.push(TextRow::new(error, "", YELLOW));
         }
+        TextView { title: t("agenda.title"), rows: text_rows }
+    }
+
+    #[cfg(feature = "screen_stats")]
+    fn stats_text_view(&self) -> TextView {
+        let Some(stats) = self.lifetime_stats else {
+            return TextView { title: t("stats.title"), rows: vec![TextRow::new(t("stats.unavailable"), "", TEXT_PRIMARY)] };
+        };
+        let rows = [
+            ("stats.boots", stats.boots as u64),
+            ("stats.ota", stats.ota_updates as u64),
+            ("stats.frames", stats.frames_rendered),
+            ("stats.buttons", stats.button_presses),
+            ("stats.wifi", stats.wifi_reconnects),
+        ];
+        TextView {
+            title: t("stats.title"),
+            rows: rows.into_iter().map(|(label, value)| TextRow::new(t(label), ui_state::format_count(value), PRIMARY_GREEN)).collect(),
+        }
+    }
+
+    #[cfg(feature = "screen_timer")]
+    fn timer_text_view(&self) -> TextView {
+        let (state_label, color) = timer_state_style(self.timer.state());
+        let remaining = self.timer.remaining().as_secs();
+        TextView {
+            title: t("timer.title"),
+            rows: vec![
+                TextRow::new(state_label, format!("{:02}:{:02}", remaining / 60, remaining % 60), color),
+                TextRow::new(t("timer.today"), self.timer.sessions_today().to_string(), TEXT_PRIMARY),
+            ],
+        }
+    }
+
+    fn render_system_screen(&mut self, display: &mut DisplayManager, screen_changed: bool) -> Result<()> {
+        let layout = SystemLayout::new();
         
         // Only clear screen when switching to this screen
         if screen_changed {
             log::info!("render_system_screen: Clearing screen for new screen");
-            display.clear(BLACK)?;
-            display.flush()?; // Flush immediately to clear old content
-            
-            // Draw static elements that don't change
-            // Header (using actual display width)
-            display.fill_rect(0, 0, 300, 30, PRIMARY_BLUE)?;
-            display.draw_text_centered(8, "System Status", WHITE, None, 2)?;
-            
-            // Reset initialization flag
-            self.system_screen_initialized = false;
-            
-            // Static labels - cache them instead of redrawing
-            let y_start = 45;
-            let line_height = 20;
-            
-            // Add static labels to cache
-            self.text_cache.clear();
-            self.text_cache.push(TextCache { text: "Uptime:".to_string(), x: 10, y: y_start, color: TEXT_PRIMARY, rendered: false });
-            self.text_cache.push(TextCache { text: "Free Heap:".to_string(), x: 10, y: y_start + line_height, color: TEXT_PRIMARY, rendered: false });
-            self.text_cache.push(TextCache { text: "CPU Freq:".to_string(), x: 10, y: y_start + line_height * 2, color: TEXT_PRIMARY, rendered: false });
-            self.text_cache.push(TextCache { text: "Flash:".to_string(), x: 10, y: y_start + line_height * 3, color: TEXT_PRIMARY, rendered: false });
-            self.text_cache.push(TextCache { text: "Temp:".to_string(), x: 10, y: y_start + line_height * 4, color: TEXT_PRIMARY, rendered: false });
-            self.text_cache.push(TextCache { text: "PSRAM/DMA:".to_string(), x: 10, y: y_start + line_height * 5, color: TEXT_PRIMARY, rendered: false });
-            
-            // Render cached text
-            for cache_entry in &mut self.text_cache {
-                if !cache_entry.rendered {
-                    display.draw_text(cache_entry.x, cache_entry.y, &cache_entry.text, cache_entry.color, None, 1)?;
-                    cache_entry.rendered = true;
-                }
-            }
-            
-            // Button hints (moved up to avoid overlap)
-            display.draw_text(10, 150, "[BOOT] Prev", TEXT_SECONDARY, None, 1)?;
-            display.draw_text(200, 150, "[USER] Next", TEXT_SECONDARY, None, 1)?;
+            views::draw_system_chrome(display, &layout)?;
+            self.system_fields = SystemFields::new(&layout);
         }
         
-        // Set screen as initialized after first render
-        self.system_screen_initialized = true;
-        
-        // Update time in header (only update every 5 seconds or on first render)
+        // Uptime clock in header (only update every 5 seconds or on first render)
         let current_seconds = self.system_info.get_uptime().as_secs();
-        if current_seconds >= self.global_cached_time + 5 || !self.system_screen_initialized {
-            self.global_cached_time = current_seconds;
-            display.fill_rect(235, 5, 65, 20, PRIMARY_BLUE)?;
-            let time_str = self.system_info.format_uptime();
-            display.draw_text(240, 8, &time_str, WHITE, None, 1)?;
+        if self.cache.header_clock_due(current_seconds) {
+            let time_str = self.header_clock();
+            self.system_fields.clock.set(display, &time_str, WHITE)?;
         }
         
-        // Battery indicator in header (moved to right side with icon)
-        if self.sensor_data._battery_percentage != self.cached_battery || self.sensor_data._is_charging {
-            // Clear battery area on right side
-            display.fill_rect(220, 5, 95, 20, PRIMARY_BLUE)?;
-            
-            // Draw battery icon
-            display.draw_battery_icon(225, 7, self.sensor_data._battery_percentage, 
-                                     self.sensor_data._is_charging, 1)?;
-            
-            // Draw percentage text next to icon
-            let battery_color = if self.sensor_data._is_charging { WHITE }
-                               else if self.sensor_data._battery_percentage > 50 { PRIMARY_GREEN } 
-                               else if self.sensor_data._battery_percentage > 20 { YELLOW } 
-                               else { PRIMARY_RED };
-            
-            let battery_str = if self.sensor_data._is_charging {
-                format!("{}%", self.sensor_data._battery_percentage)
-            } else if self.sensor_data._is_on_usb && self.sensor_data._battery_percentage == 0 {
-                "USB".to_string()
-            } else {
-                format!("{}%", self.sensor_data._battery_percentage)
-            };
-            display.draw_text(255, 8, &battery_str, battery_color, None, 1)?;
-            
-            // Show voltage if charging
-            if self.sensor_data._is_charging {
-                let voltage_str = format!("{:.1}V", self.sensor_data._battery_voltage as f32 / 1000.0);
-                display.draw_text(285, 8, &voltage_str, TEXT_SECONDARY, None, 1)?;
-            }
-            
-            self.cached_battery = self.sensor_data._battery_percentage;
+        // Battery indicator on the right of the header
+        if self.cache.battery_due(self.sensor_data._battery_percentage, self.sensor_data._is_charging) {
+            views::draw_system_battery(display, &layout, &mut self.system_fields, self.sensor_data._battery_percentage,
+                                       self.sensor_data._is_charging, self.sensor_data._is_on_usb)?;
         }
         
-        // Move time to left side
-        let current_seconds = self.system_info.get_uptime().as_secs();
-        if current_seconds >= self.global_cached_time + 5 || !self.system_screen_initialized {
-            self.global_cached_time = current_seconds;
-            display.fill_rect(5, 5, 65, 20, PRIMARY_BLUE)?;
-            let time_str = self.system_info.format_uptime();
-            display.draw_text(10, 8, &time_str, WHITE, None, 1)?;
-        }
+        // Dynamic content - fields only redraw the characters that changed
+        let [uptime_field, heap_field, cpu_field, flash_field, temp_field, psram_field, reliability_field] = &mut self.system_fields.values;
         
-        // Dynamic content - update values by clearing their areas first
-        let y_start = 45;
-        let line_height = 20;
-        
-        // Uptime value (only update if changed)
-        let uptime = self.system_info.get_uptime();
-        let uptime_seconds = uptime.as_secs();
+        // Uptime value
+        let uptime_seconds = self.system_info.get_uptime().as_secs();
         
         // Use pre-allocated buffer for formatting
-        self.string_buffer.clear();
-        if uptime_seconds < 3600 {
-            use std::fmt::Write;
-            let _ = write!(&mut self.string_buffer, "{}m {}s", uptime_seconds / 60, uptime_seconds % 60);
-        } else {
-            use std::fmt::Write;
-            let _ = write!(&mut self.string_buffer, "{}h {}m", uptime_seconds / 3600, (uptime_seconds % 3600) / 60);
-        }
-        let uptime_str = self.string_buffer.clone();
-        if uptime_str != self.cached_uptime {
-            display.fill_rect(120, y_start, 120, 16, BLACK)?;
-            display.draw_text(120, y_start, &uptime_str, PRIMARY_GREEN, None, 1)?;
-            self.cached_uptime = uptime_str;
-        }
+        ui_state::write_uptime(&mut self.string_buffer, uptime_seconds);
+        uptime_field.set(display, &self.string_buffer, PRIMARY_GREEN)?;
         
-        // Memory value (only update if changed)
+        // Memory value
         let heap_kb = self.system_info.get_free_heap_kb();
-        let heap_str = format!("{} KB", heap_kb);
-        if heap_str != self.cached_heap {
-            display.fill_rect(120, y_start + line_height, 120, 16, BLACK)?;
-            display.draw_text(120, y_start + line_height, &heap_str, PRIMARY_GREEN, None, 1)?;
-            self.cached_heap = heap_str;
-        }
+        heap_field.set(display, &ui_state::format_heap(heap_kb), PRIMARY_GREEN)?;
         
-        // CPU value with dual-core usage (only update if changed)
+        // CPU value with dual-core usage
         let cpu_freq = self.system_info.get_cpu_freq_mhz();
-        let cpu_str = format!("{} MHz C0:{}% C1:{}%", cpu_freq, self.cpu0_usage, self.cpu1_usage);
-        if cpu_str != self.cached_cpu {
-            display.fill_rect(120, y_start + line_height * 2, 180, 16, BLACK)?;
-            display.draw_text(120, y_start + line_height * 2, &cpu_str, PRIMARY_GREEN, None, 1)?;
-            self.cached_cpu = cpu_str;
-        }
+        cpu_field.set(display, &ui_state::format_cpu(cpu_freq, self.cpu0_usage, self.cpu1_usage), PRIMARY_GREEN)?;
         
-        // Flash storage value (only update if changed)
+        // Flash storage value
         let (flash_total, app_size) = self.system_info.get_flash_info();
-        let flash_str = format!("{}/{}MB", app_size, flash_total);
-        if flash_str != self.cached_flash {
-            display.fill_rect(120, y_start + line_height * 3, 120, 16, BLACK)?;
-            display.draw_text(120, y_start + line_height * 3, &flash_str, PRIMARY_GREEN, None, 1)?;
-            self.cached_flash = flash_str;
-        }
+        flash_field.set(display, &ui_state::format_flash(app_size, flash_total), PRIMARY_GREEN)?;
         
-        // Temperature value (only update if changed)
-        let temp_str = format!("{:.1}°C", self.sensor_data._temperature);
-        if temp_str != self.cached_temp {
-            display.fill_rect(120, y_start + line_height * 4, 120, 16, BLACK)?;
-            let temp_color = if self.sensor_data._temperature > 50.0 { PRIMARY_RED } 
-                            else if self.sensor_data._temperature > 40.0 { YELLOW } 
-                            else { PRIMARY_GREEN };
-            display.draw_text(120, y_start + line_height * 4, &temp_str, temp_color, None, 1)?;
-            self.cached_temp = temp_str;
-        }
+        // Temperature value
+        let temp_color = ui_state::temperature_color(self.sensor_data._temperature);
+        temp_field.set(display, &units::format_temperature(self.sensor_data._temperature, 1), temp_color)?;
         
         // PSRAM status (without DMA check since it's not available in this version)
         let psram_info = crate::psram::PsramAllocator::get_info();
-        let psram_str = if psram_info.available {
-            format!("{}MB free", psram_info.free_size / 1024 / 1024)
-        } else {
-            "Not available".to_string()
-        };
-        display.fill_rect(120, y_start + line_height * 5, 180, 16, BLACK)?;
-        let psram_color = if psram_info.available { PRIMARY_GREEN } else { YELLOW };
-        display.draw_text(120, y_start + line_height * 5, &psram_str, psram_color, None, 1)?;
+        let (psram_str, psram_color) = ui_state::format_psram(psram_info.available, psram_info.free_size);
+        psram_field.set(display, &psram_str, psram_color)?;
+        
+        // Reliability summary from the uptime tracker
+        let reliability_color = if self.reliability_crashes > 0 { YELLOW } else { PRIMARY_GREEN };
+        reliability_field.set(display, &self.reliability_summary, reliability_color)?;
         
         // Progress indicator (only update when progress changes)
-        let progress = (self.animation_progress * 100.0) as u8;
-        if progress != self.last_progress_value {
-            self.last_progress_value = progress;
-            display.draw_progress_bar(10, 138, 280, 8, progress, PRIMARY_GREEN, SURFACE_LIGHT, BORDER_COLOR)?;
+        let progress = self.progress_percent();
+        if self.cache.progress_due(progress) {
+            views::draw_system_progress(display, &layout, progress)?;
         }
         
         Ok(())
@@ -505,558 +990,482 @@ impl UiManager {
 
     fn render_network_screen(&mut self, display: &mut DisplayManager, screen_changed: bool) -> Result<()> {
         if screen_changed {
-            // Clear screen
-            display.clear(BLACK)?;
-            display.flush()?; // Flush immediately to clear old content
-            
-            // Header
-            display.fill_rect(0, 0, 300, 30, PRIMARY_PURPLE)?;
-            display.draw_text_centered(8, "Network Status", WHITE, None, 2)?;
-            
-            // Reset initialization
-            self.network_screen_initialized = false;
-            
-            // Static labels - consistent layout
-            let y_start = 38;
-            let line_height = 20;
-            display.draw_text(10, y_start, "Status:", TEXT_PRIMARY, None, 1)?;
-            display.draw_text(10, y_start + line_height, "SSID:", TEXT_PRIMARY, None, 1)?;
-            display.draw_text(10, y_start + line_height * 2, "IP:", TEXT_PRIMARY, None, 1)?;
-            display.draw_text(10, y_start + line_height * 3, "Signal:", TEXT_PRIMARY, None, 1)?;
-            
-            // Button hints
-            display.draw_text(10, 155, "[BOOT] Prev", TEXT_SECONDARY, None, 1)?;
-            display.draw_text(200, 155, "[USER] Next", TEXT_SECONDARY, None, 1)?;
+            views::draw_network_chrome(display)?;
+            self.network_fields = NetworkFields::new();
         }
         
         // Update time in header (every 5 seconds)
         let current_seconds = self.system_info.get_uptime().as_secs();
-        if current_seconds >= self.global_cached_time + 5 || screen_changed {
-            self.global_cached_time = current_seconds;
-            display.fill_rect(240, 5, 75, 20, PRIMARY_PURPLE)?;
-            let time_str = self.system_info.format_uptime();
-            display.draw_text(245, 8, &time_str, WHITE, None, 1)?;
-        }
-        
-        // Dynamic content - consistent spacing
-        let y_start = 38;
-        let line_height = 20;
-        let value_x = 65; // Better aligned position for values
-        
-        // WiFi Status
-        display.fill_rect(value_x, y_start, 240, 16, BLACK)?;
-        let status_text = if self.network_connected { "Connected" } else { "Disconnected" };
-        let status_color = if self.network_connected { PRIMARY_GREEN } else { PRIMARY_RED };
-        display.draw_text(value_x, y_start, status_text, status_color, None, 1)?;
-        
-        // SSID
-        display.fill_rect(value_x, y_start + line_height, 240, 16, BLACK)?;
-        let ssid_color = if self.network_connected { TEXT_PRIMARY } else { TEXT_SECONDARY };
-        display.draw_text(value_x, y_start + line_height, &self.network_ssid, ssid_color, None, 1)?;
-        
-        // IP Address
-        let ip_y = y_start + line_height * 2;
-        display.fill_rect(value_x, ip_y, 200, 16, BLACK)?;
-        
-        if let Some(ref ip) = self.network_ip {
-            display.draw_text(value_x, ip_y, ip, TEXT_PRIMARY, None, 1)?;
-        } else if self.network_ssid.is_empty() || self.network_ssid == "Not connected" {
-            // No WiFi credentials configured
-            display.draw_text(value_x, ip_y, "No WiFi Config", YELLOW, None, 1)?;
-        } else {
-            // WiFi configured but no IP yet
-            display.draw_text(value_x, ip_y, "Obtaining IP...", YELLOW, None, 1)?;
+        if self.cache.header_clock_due(current_seconds) {
+            let time_str = self.header_clock();
+            self.network_fields.clock.set(display, &time_str, WHITE)?;
         }
         
-        // Signal strength - just text, no graph
-        let signal_y = y_start + line_height * 3;
-        display.fill_rect(value_x, signal_y, 200, 16, BLACK)?;
-        
-        if self.network_connected {
-            let signal_quality = match self.network_signal {
-                -50..=0 => "Excellent",
-                -60..=-51 => "Good",
-                -70..=-61 => "Fair",
-                -80..=-71 => "Weak",
-                _ => "Poor"
-            };
-            
-            let signal_color = match self.network_signal {
-                -50..=0 => PRIMARY_GREEN,
-                -60..=-51 => PRIMARY_GREEN,
-                -70..=-61 => YELLOW,
-                -80..=-71 => ACCENT_ORANGE,
-                _ => PRIMARY_RED
-            };
-            
-            display.draw_text(value_x, signal_y, &format!("{} dBm ({})", self.network_signal, signal_quality), signal_color, None, 1)?;
-        } else {
-            display.draw_text(value_x, signal_y, "No signal", TEXT_SECONDARY, None, 1)?;
-        }
-        
-        // Additional network information
-        if self.network_connected {
-            let info_y = y_start + line_height * 4 + 5;
-            
-            // MAC Address
-            display.draw_text(10, info_y, "MAC:", TEXT_PRIMARY, None, 1)?;
-            display.fill_rect(value_x, info_y, 240, 16, BLACK)?;
-            display.draw_text(value_x, info_y, &self.network_mac, TEXT_SECONDARY, None, 1)?;
-            
-            // Gateway
-            display.draw_text(10, info_y + line_height, "Gateway:", TEXT_PRIMARY, None, 1)?;
-            display.fill_rect(value_x, info_y + line_height, 240, 16, BLACK)?;
-            if let Some(ref gateway) = self.network_gateway {
-                display.draw_text(value_x, info_y + line_height, gateway, TEXT_SECONDARY, None, 1)?;
-            } else {
-                display.draw_text(value_x, info_y + line_height, "Not available", TEXT_SECONDARY, None, 1)?;
-            }
-            
-            // Web interface section - ensure no overlap
-            let web_section_y = info_y + line_height * 2 + 10; // Dynamic positioning
-            display.draw_line(10, web_section_y - 5, 290, web_section_y - 5, BORDER_COLOR)?;
-            
-            display.draw_text_centered(web_section_y + 5, "Web Configuration", TEXT_SECONDARY, None, 1)?;
-            if let Some(ref ip) = self.network_ip {
-                display.draw_text_centered(web_section_y + 20, &format!("http://{}", ip), PRIMARY_BLUE, None, 1)?;
-            }
-        } else {
-            // Not connected - show help
-            let help_y = y_start + line_height * 4 + 10;
-            display.draw_line(10, help_y - 5, 290, help_y - 5, BORDER_COLOR)?;
-            
-            if self.network_ssid.is_empty() || self.network_ssid == "Not connected" {
-                // No WiFi credentials
-                display.draw_text_centered(help_y + 10, "WiFi Not Configured", ACCENT_ORANGE, None, 1)?;
-                display.draw_text_centered(help_y + 28, "Edit wifi_config.h:", TEXT_PRIMARY, None, 1)?;
-                display.draw_text_centered(help_y + 42, "#define WIFI_SSID \"YourSSID\"", PRIMARY_BLUE, None, 1)?;
-                display.draw_text_centered(help_y + 56, "#define WIFI_PASSWORD \"YourPass\"", PRIMARY_BLUE, None, 1)?;
-                display.draw_text_centered(help_y + 74, "Then rebuild & flash", TEXT_SECONDARY, None, 1)?;
-            } else {
-                // WiFi configured but not connected
-                display.draw_text_centered(help_y + 10, "WiFi Connection Failed", PRIMARY_RED, None, 1)?;
-                display.draw_text_centered(help_y + 28, &format!("SSID: {}", self.network_ssid), TEXT_SECONDARY, None, 1)?;
-                display.draw_text_centered(help_y + 42, "Check password & signal", TEXT_SECONDARY, None, 1)?;
-                display.draw_text_centered(help_y + 65, "Retrying connection...", TEXT_SECONDARY, None, 1)?;
-            }
-        }
-        
-        // Mark screen as initialized
-        self.network_screen_initialized = true;
-        
+        let view = NetworkView {
+            connected: self.network_connected,
+            ssid: &self.network_ssid,
+            ip: self.network_ip.as_deref(),
+            ipv6: self.network_ipv6.as_deref(),
+            // Both addresses share the IP row, alternating with the header clock
+            show_ipv6: self.header_tick() % 2 == 1,
+            signal: self.network_signal,
+            mac: &self.network_mac,
+            gateway: self.network_gateway.as_deref(),
+            gateway_rtt: &self.latency_gateway,
+            host_rtt: &self.latency_host,
+            disconnect_reasons: &self.network_disconnect_reasons,
+        };
+        views::draw_network(display, &mut self.network_fields, &view)?;
         Ok(())
     }
 
     fn render_sensor_screen(&mut self, display: &mut DisplayManager, screen_changed: bool) -> Result<()> {
-        // Early exit if nothing needs updating
-        if !screen_changed && self.sensor_screen_initialized &&
-           self.sensor_last_battery == self.sensor_data._battery_percentage &&
-           (self.sensor_last_temp - self.sensor_data._temperature).abs() < 0.5 &&
-           self.sensor_last_light == self.sensor_data._light_level {
-            return Ok(());
-        }
-        // Update cached values
-        self.sensor_last_battery = self.sensor_data._battery_percentage;
-        self.sensor_last_temp = self.sensor_data._temperature;
-        self.sensor_last_light = self.sensor_data._light_level;
+        // The warning banner replaces part of the chrome, so redraw it when that changes
+        let warning_changed = self.cache.sensor_power_warning != self.power_warning;
+        let power_row = self.power_monitor.is_some();
+        let readings = (!self.sensor_readings.is_empty()).then(|| {
+            self.sensor_readings.iter()
+                .map(|sample| format!("{} {}", sample.reading.quantity, sample.reading.display()))
+                .collect::<Vec<_>>()
+                .join("  ")
+        });
         
         // Only clear screen when switching to this screen
-        if screen_changed {
-            display.clear(BLACK)?;
-            display.flush()?; // Flush immediately to clear old content
-            
-            // Header
-            display.fill_rect(0, 0, 300, 30, PRIMARY_GREEN)?;
-            display.draw_text_centered(8, "Sensor Data", WHITE, None, 2)?;
-            
-            // Reset initialization
-            self.sensor_screen_initialized = false;
-            
-            // Static labels
-            let y_start = 50;
-            let line_height = 30;
-            display.draw_text(10, y_start, "Battery:", TEXT_PRIMARY, None, 1)?;
-            display.draw_text(10, y_start + line_height, "Temp:", TEXT_PRIMARY, None, 1)?;
-            display.draw_text(10, y_start + line_height * 2, "Light:", TEXT_PRIMARY, None, 1)?;
-            
-            // Button hints (moved up to avoid overlap)
-            display.draw_text(10, 150, "[BOOT] Prev", TEXT_SECONDARY, None, 1)?;
-            display.draw_text(230, 150, "[USER] Next", TEXT_SECONDARY, None, 1)?;
-        }
-        
-        // Mark screen as initialized
-        self.sensor_screen_initialized = true;
-        
-        // Dynamic sensor values (only update if changed)
-        let y_start = 50;
-        let line_height = 30;
-        
-        // Battery value and bar with voltage and charging status
-        let battery_percent = self.sensor_data._battery_percentage;
-        let battery_voltage = self.sensor_data._battery_voltage;
-        let is_charging = self.sensor_data._is_charging;
-        let is_on_usb = self.sensor_data._is_on_usb;
-        
-        // Color based on battery state
-        let battery_color = if is_charging { PRIMARY_BLUE }
-                           else if battery_percent > 50 { PRIMARY_GREEN }
-                           else if battery_percent > 20 { YELLOW }
-                           else { PRIMARY_RED };
-        
-        // Draw progress bar
-        display.draw_progress_bar(100, y_start, 120, 15, battery_percent, battery_color, SURFACE_LIGHT, BORDER_COLOR)?;
-        
-        // Draw percentage and voltage
-        display.fill_rect(225, y_start, 70, 16, BLACK)?;
-        display.draw_text(225, y_start, &format!("{}%", battery_percent), battery_color, None, 1)?;
-        
-        // Draw voltage below percentage with debug info
-        display.fill_rect(100, y_start + 18, 195, 14, BLACK)?;
-        if battery_voltage > 0 {
-            // Show precise voltage for debugging
-            let voltage_str = format!("{:.3}V ({}mV)", battery_voltage as f32 / 1000.0, battery_voltage);
-            display.draw_text(100, y_start + 18, &voltage_str, TEXT_SECONDARY, None, 1)?;
-            
-            // Show charging/USB status
-            if is_charging {
-                display.draw_text(210, y_start + 18, "CHG", PRIMARY_BLUE, None, 1)?;
-            } else if is_on_usb {
-                display.draw_text(210, y_start + 18, "USB", ACCENT_ORANGE, None, 1)?;
-            }
-        } else {
-            display.draw_text(100, y_start + 18, "No Battery", TEXT_SECONDARY, None, 1)?;
-        }
-        
-        
-        // Temperature value (adjusted position due to battery info)
-        let temp_y = y_start + line_height + 5;
-        display.fill_rect(100, temp_y, 100, 20, BLACK)?;
-        display.draw_text(100, temp_y, &format!("{:.1}°C", self.sensor_data._temperature), TEXT_PRIMARY, None, 1)?;
-        
-        // Light level value (adjusted position)
-        let light_y = y_start + line_height * 2 + 5;
-        display.fill_rect(100, light_y, 100, 20, BLACK)?;
-        if self.sensor_data._light_level > 0 {
-            display.draw_text(100, light_y, &format!("{} lux", self.sensor_data._light_level), TEXT_PRIMARY, None, 1)?;
-        } else {
-            display.draw_text(100, light_y, "N/A", TEXT_SECONDARY, None, 1)?;
-        }
-        
-        // Visual indicator
-        let radius = 20;
-        let cx = 160;
-        let cy = 130;
-        display.draw_circle(cx, cy, radius, BORDER_COLOR)?;
-        let fill_radius = (radius as f32 * self.animation_progress) as u16;
-        if fill_radius > 0 {
-            display.fill_circle(cx, cy, fill_radius, PRIMARY_GREEN)?;
+        if screen_changed || warning_changed || self.cache.sensor_power_row != power_row
+            || self.cache.sensor_readings_row != readings.is_some() {
+            views::draw_sensor_chrome(display, power_row)?;
+            self.sensor_fields = SensorFields::new();
+            self.cache.sensor_power_warning = self.power_warning.clone();
+            self.cache.sensor_power_row = power_row;
+            self.cache.sensor_readings_row = readings.is_some();
         }
         
+        let view = SensorView {
+            battery_percent: self.sensor_data._battery_percentage,
+            battery_mv: self.sensor_data._battery_voltage,
+            charging: self.sensor_data._is_charging,
+            on_usb: self.sensor_data._is_on_usb,
+            temperature: self.sensor_data._temperature,
+            light: self.sensor_data._light_level,
+            activity: self.animation_progress,
+            power_warning: self.power_warning.as_deref(),
+            power: self.power_monitor.map(|p| PowerRow {
+                now_mw: p.reading.power_mw,
+                average_mw: p.average_mw,
+                energy_mwh: p.energy_mwh,
+            }),
+            readings: readings.as_deref(),
+        };
+        views::draw_sensor(display, &mut self.sensor_fields, &view)?;
         Ok(())
     }
 
     fn render_settings_screen(&mut self, display: &mut DisplayManager, screen_changed: bool) -> Result<()> {
-        // Early exit - settings screen is mostly static
-        if !screen_changed && self.settings_screen_initialized {
-            return Ok(());
-        }
-        
         if screen_changed {
-            // Clear screen only on screen change
-            display.clear(BLACK)?;
-            display.flush()?; // Flush immediately to clear old content
-            
-            // Header
-            display.fill_rect(0, 0, 300, 30, ACCENT_ORANGE)?;
-            display.draw_text_centered(8, "Settings", WHITE, None, 2)?;
-            
-            // Reset initialization
-            self.settings_screen_initialized = false;
-            
-            // Settings options
-            let y_start = 50;
-            let line_height = 30;
-            
-            // Static labels
-            display.draw_text(10, y_start, "Brightness:", TEXT_PRIMARY, None, 1)?;
-            display.draw_text(10, y_start + line_height, "Auto-dim:", TEXT_PRIMARY, None, 1)?;
-            display.draw_text(10, y_start + line_height * 2, "Update:", TEXT_PRIMARY, None, 1)?;
-            display.draw_text(10, y_start + line_height * 3, "Version:", TEXT_PRIMARY, None, 1)?;
-            
-            // Button hints (moved up to avoid overlap)
-            display.draw_text(10, 150, "[BOOT] Prev", TEXT_SECONDARY, None, 1)?;
-            display.draw_text(200, 150, "[USER] Select", TEXT_SECONDARY, None, 1)?;
+            views::draw_settings_chrome(display)?;
+            self.settings_fields = SettingsFields::new();
         }
         
-        // Dynamic values (always update)
-        let y_start = 50;
-        let line_height = 30;
-        
-        // Brightness bar and value
-        display.draw_progress_bar(120, y_start, 100, 15, 80, PRIMARY_BLUE, SURFACE_LIGHT, BORDER_COLOR)?;
-        display.draw_text(230, y_start, "80%", TEXT_PRIMARY, None, 1)?;
-        
-        // Auto-dim status
-        display.fill_rect(120, y_start + line_height, 60, 20, BLACK)?;
-        display.draw_text(120, y_start + line_height, "ON", PRIMARY_GREEN, None, 1)?;
-        
-        // Update speed
-        display.fill_rect(120, y_start + line_height * 2, 80, 20, BLACK)?;
-        display.draw_text(120, y_start + line_height * 2, "Normal", TEXT_PRIMARY, None, 1)?;
-        
-        // Version
-        display.fill_rect(120, y_start + line_height * 3, 100, 20, BLACK)?;
-        display.draw_text(120, y_start + line_height * 3, crate::version::DISPLAY_VERSION, TEXT_SECONDARY, None, 1)?;
-        
-        // Mark screen as initialized
-        self.settings_screen_initialized = true;
-        
+        let brightness_percent = (self.brightness as u32 * 100 / 255) as u8;
+        let version = format!("{} @{}", crate::version::DISPLAY_VERSION, crate::version::short_commit());
+        views::draw_settings(display, &mut self.settings_fields, brightness_percent, self.accessibility, &version)?;
         Ok(())
     }
     
     fn render_ota_screen(&mut self, display: &mut DisplayManager, screen_changed: bool) -> Result<()> {
-        // Early exit if nothing needs updating
+        // Only the header clock moved
         if !screen_changed && 
-           self.cached_ota_status_enum == Some(self.ota_status) &&
-           self.cached_network_ip == self.network_ip &&
-           self.ota_screen_initialized {
+           self.cache.ota_status == Some(self.ota_status) &&
+           self.cache.ota_network_ip == self.network_ip {
             // Only update time every 5 seconds to reduce operations
             let current_seconds = self.system_info.get_uptime().as_secs();
-            if current_seconds >= self.cached_ota_time + 5 {
-                self.cached_ota_time = current_seconds;
-                display.fill_rect(240, 5, 60, 20, ACCENT_ORANGE)?;
-                let time_str = self.system_info.format_uptime();
-                display.draw_text(245, 8, &time_str, WHITE, None, 1)?;
+            if self.cache.ota_clock_due(current_seconds) {
+                let time_str = self.header_clock();
+                self.ota_fields.clock.set(display, &time_str, WHITE)?;
             }
             return Ok(());
         }
         
+        // Chrome, time and static content on first render
         if screen_changed {
-            // Clear screen
-            display.clear(BLACK)?;
-            display.flush()?;
-            
-            // Header
-            display.fill_rect(0, 0, 300, 30, ACCENT_ORANGE)?;
-            display.draw_text_centered(8, "OTA Updates", WHITE, None, 2)?;
-            
-            // Button hints
-            display.draw_text(10, 155, "[BOOT] Prev", TEXT_SECONDARY, None, 1)?;
-            display.draw_text(200, 155, "[USER] Check", TEXT_SECONDARY, None, 1)?;
-            
-            // Reset initialization flag
-            self.ota_screen_initialized = false;
+            views::draw_ota_chrome(display)?;
+            self.ota_fields = OtaFields::new();
+            self.cache.ota_clock_due(self.system_info.get_uptime().as_secs());
+            let time_str = self.header_clock();
+            self.ota_fields.clock.set(display, &time_str, WHITE)?;
+            views::draw_ota_info(display, crate::version::DISPLAY_VERSION, &self.ota_partition)?;
         }
         
-        // Update time on first render
-        if !self.ota_screen_initialized {
-            let current_seconds = self.system_info.get_uptime().as_secs();
-            self.cached_ota_time = current_seconds;
-            display.fill_rect(240, 5, 60, 20, ACCENT_ORANGE)?;
-            let time_str = self.system_info.format_uptime();
-            display.draw_text(245, 8, &time_str, WHITE, None, 1)?;
+        // OTA Status - only format and update if truly changed
+        let was_downloading = matches!(self.cache.ota_status, Some(OtaStatus::Downloading { .. }));
+        let status_changed = self.cache.ota_status != Some(self.ota_status);
+        if status_changed {
+            self.cache.ota_status = Some(self.ota_status);
+            
+            let (status_text, status_color) = ui_state::ota_status_text(self.ota_status, &mut self.string_buffer);
+            self.ota_fields.status.set(display, status_text, status_color)?;
         }
         
-        // Main content area - adjusted spacing
-        let y_start = 36;
-        let line_height = 16;
+        // Progress bar; cleared when transitioning away from downloading
+        if let OtaStatus::Downloading { progress } = self.ota_status {
+            views::draw_ota_progress(display, Some(progress))?;
+        } else if status_changed && was_downloading {
+            views::draw_ota_progress(display, None)?;
+        }
         
-        // Static content - only draw once
-        if !self.ota_screen_initialized || screen_changed {
-            // Current version info
-            display.draw_text(10, y_start, "Firmware:", TEXT_PRIMARY, None, 1)?;
-            display.draw_text(80, y_start, crate::version::DISPLAY_VERSION, PRIMARY_BLUE, None, 1)?;
-            
-            // Partition info
-            display.draw_text(180, y_start, "Partition:", TEXT_PRIMARY, None, 1)?;
-            display.draw_text(240, y_start, "Factory", TEXT_SECONDARY, None, 1)?;
-            
-            // Status label
-            display.draw_text(10, y_start + line_height, "Status:", TEXT_PRIMARY, None, 1)?;
-            
-            // Draw separator line once
-            let base_server_y = y_start + line_height * 2 + 8;
-            display.draw_line(10, base_server_y - 3, 290, base_server_y - 3, BORDER_COLOR)?;
+        // Network section - only update on actual changes
+        let network_changed = self.cache.ota_network_ip != self.network_ip;
+        if network_changed || screen_changed {
+            views::draw_ota_endpoints(display, self.network_connected, self.network_ip.as_deref())?;
+            self.cache.ota_network_ip = if self.network_connected { self.network_ip.clone() } else { None };
         }
         
-        // OTA Status - only format and update if truly changed
-        let status_changed = self.cached_ota_status_enum != Some(self.ota_status);
-        if status_changed || !self.ota_screen_initialized {
-            self.cached_ota_status_enum = Some(self.ota_status);
+        Ok(())
+    }
+    
+    #[cfg(feature = "screen_weather")]
+    fn render_weather_screen(&mut self, display: &mut DisplayManager, _screen_changed: bool) -> Result<()> {
+        let mut frame = DrawList::new(SCREEN_WIDTH);
+        frame.clear(BLACK);
+        
+        // Header
+        frame.fill_rect(0, 0, 300, 30, PRIMARY_BLUE);
+        frame.draw_text_centered(8, t("weather.title"), WHITE, None, 2);
+        
+        // Button hints
+        frame.draw_text(10, 155, t("hint.prev"), TEXT_SECONDARY, None, 1);
+        frame.draw_text(200, 155, t("hint.next"), TEXT_SECONDARY, None, 1);
+        
+        if let Some(weather) = self.weather.as_ref() {
+            // Current conditions: icon on the left, values on the right
+            Self::draw_weather_icon(&mut frame, 45, 70, 20, weather.condition);
             
-            let (status_text, status_color) = match &self.ota_status {
-                OtaStatus::Idle => ("Ready", TEXT_SECONDARY),
-                OtaStatus::Downloading { progress } => {
-                    self.string_buffer.clear();
-                    use std::fmt::Write;
-                    let _ = write!(&mut self.string_buffer, "Downloading {}%", progress);
-                    (self.string_buffer.as_str(), PRIMARY_BLUE)
-                },
-                OtaStatus::Verifying => ("Verifying Update", YELLOW),
-                OtaStatus::Ready => ("Update Ready - Restart", PRIMARY_GREEN),
-                OtaStatus::Failed => ("Update Failed", PRIMARY_RED),
-            };
+            let temp_color = if weather.temperature_c >= 30.0 { PRIMARY_RED }
+                             else if weather.temperature_c <= 0.0 { PRIMARY_BLUE }
+                             else { WHITE };
+            frame.draw_text(100, 42, &units::format_temperature(weather.temperature_c, 1), temp_color, None, 3);
+            frame.draw_text(100, 72, weather.condition.label(), TEXT_PRIMARY, None, 1);
+            frame.draw_text(100, 86, &crate::text_buf!("Wind {:.0} km/h", weather.wind_kmh), TEXT_SECONDARY, None, 1);
             
-            display.fill_rect(80, y_start + line_height, 200, 16, BLACK)?;
-            display.draw_text(80, y_start + line_height, status_text, status_color, None, 1)?;
+            // Age of the data
+            frame.draw_text(220, 86, &crate::text_buf!("{}m ago", self.weather_age_minutes()), TEXT_SECONDARY, None, 1);
+            
+            // Hourly forecast row
+            frame.draw_line(10, 102, 290, 102, BORDER_COLOR);
+            for (i, point) in weather.forecast.iter().enumerate() {
+                let col_x = 10 + i as u16 * 95;
+                Self::draw_weather_icon(&mut frame, col_x + 12, 125, 8, point.condition);
+                frame.draw_text(col_x + 28, 110, &units::format_time_of_day(point.hour as u16 * 60), TEXT_SECONDARY, None, 1);
+                frame.draw_text(col_x + 28, 126, &units::format_temperature(point.temperature_c, 0), TEXT_PRIMARY, None, 1);
+            }
+        } else {
+            frame.draw_text_centered(60, t("weather.no_data"), TEXT_PRIMARY, None, 1);
+            frame.draw_text_centered(80, t("weather.set_location"), TEXT_SECONDARY, None, 1);
         }
         
-        // Progress bar - use fixed position to leverage caching
-        let progress_y = y_start + line_height * 2 + 4;
-        if let OtaStatus::Downloading { progress } = self.ota_status {
-            display.draw_progress_bar(10, progress_y, 280, 10, progress, PRIMARY_BLUE, SURFACE_LIGHT, BORDER_COLOR)?;
-        } else if status_changed && matches!(self.cached_ota_status_enum, Some(OtaStatus::Downloading { .. })) {
-            // Clear progress bar area when transitioning away from downloading
-            display.fill_rect(10, progress_y, 280, 10, BLACK)?;
+        self.frame_presenter.present(frame, display)?;
+        Ok(())
+    }
+    
+    /// Minutes since the shown weather was fetched
+    #[cfg(feature = "screen_weather")]
+    fn weather_age_minutes(&self) -> u64 {
+        let now_s = self.system_info.get_uptime().as_secs();
+        self.weather.as_ref().map_or(0, |w| now_s.saturating_sub(w.fetched_at_s) / 60)
+    }
+    
+    #[cfg(feature = "screen_widgets")]
+    fn render_custom_screen(&mut self, display: &mut DisplayManager, _screen_changed: bool) -> Result<()> {
+        let mut frame = DrawList::new(SCREEN_WIDTH);
+        frame.clear(BLACK);
+        
+        // Header
+        frame.fill_rect(0, 0, 300, 30, PRIMARY_PURPLE);
+        frame.draw_text_centered(8, t("widgets.title"), WHITE, None, 2);
+        
+        // Button hints
+        frame.draw_text(10, 155, t("hint.prev"), TEXT_SECONDARY, None, 1);
+        frame.draw_text(200, 155, t("hint.next"), TEXT_SECONDARY, None, 1);
+        
+        if self.custom_widgets.is_empty() {
+            frame.draw_text_centered(60, t("widgets.none"), TEXT_PRIMARY, None, 1);
+            frame.draw_text_centered(80, t("widgets.add"), TEXT_SECONDARY, None, 1);
+        } else {
+            let y_start = 40;
+            let line_height = 28;
+            for (i, widget) in self.custom_widgets.iter().enumerate() {
+                let y = y_start + i as u16 * line_height;
+                frame.draw_text(10, y + 4, &widget.label, TEXT_PRIMARY, None, 1);
+                match widget.value {
+                    Some(ref value) => {
+                        let text = crate::text_buf!("{} {}", value, widget.unit);
+                        frame.draw_text(130, y, text.trim_end(), PRIMARY_GREEN, None, 2);
+                    }
+                    None => frame.draw_text(130, y + 4, "--", TEXT_SECONDARY, None, 1),
+                }
+            }
         }
         
-        // Fixed server section position
-        let server_section_y = y_start + line_height * 2 + 24;
+        // Only widgets whose value changed are redrawn
+        self.frame_presenter.present(frame, display)?;
+        Ok(())
+    }
+    
+    #[cfg(feature = "screen_alerts")]
+    fn render_ops_alerts_screen(&mut self, display: &mut DisplayManager, _screen_changed: bool) -> Result<()> {
+        // Header color doubles as the alert light
+        let header_color = match self.ops_alerts.as_ref() {
+            Some(summary) if summary.total > 0 => summary.worst_severity().map(severity_color).unwrap_or(TEXT_SECONDARY),
+            Some(_) => PRIMARY_GREEN,
+            None => SURFACE_DARK,
+        };
         
-        // Network section - only update on actual changes
-        let network_changed = self.cached_network_ip != self.network_ip;
-        if network_changed || !self.ota_screen_initialized {
-            // Clear the entire network section area
-            display.fill_rect(10, server_section_y, 290, 80, BLACK)?;
+        let mut frame = DrawList::new(SCREEN_WIDTH);
+        frame.clear(BLACK);
+        frame.fill_rect(0, 0, 300, 30, header_color);
+        frame.draw_text_centered(8, t("alerts.title"), WHITE, None, 2);
+        frame.draw_text(10, 155, t("hint.prev"), TEXT_SECONDARY, None, 1);
+        frame.draw_text(200, 155, t("hint.next"), TEXT_SECONDARY, None, 1);
+        
+        if let Some(summary) = self.ops_alerts.as_ref() {
+            // Firing count
+            let count_color = if summary.total == 0 { PRIMARY_GREEN } else { header_color };
+            frame.draw_text(10, 38, &summary.total.to_string(), count_color, None, 3);
+            frame.draw_text(70, 46, t(if summary.total == 1 { "alerts.firing_one" } else { "alerts.firing_many" }), TEXT_PRIMARY, None, 1);
+            if let Some(ref error) = summary.error {
+                frame.draw_text(180, 46, error, YELLOW, None, 1);
+            }
             
-            if self.network_connected {
-                display.draw_text_centered(server_section_y + 4, "OTA Endpoints", TEXT_SECONDARY, None, 1)?;
-                
-                if let Some(ref ip) = self.network_ip {
-                    self.cached_network_ip = Some(ip.clone());
-                    
-                    // Format endpoints using pre-allocated buffer
-                    let endpoint_y = server_section_y + 20;
-                    display.draw_text(10, endpoint_y, "Upload:", TEXT_PRIMARY, None, 1)?;
-                    
-                    self.string_buffer.clear();
-                    use std::fmt::Write;
-                    let _ = write!(&mut self.string_buffer, "http://{}:8080/ota", ip);
-                    display.draw_text(60, endpoint_y, &self.string_buffer, PRIMARY_BLUE, None, 1)?;
-                    
-                    let status_y = endpoint_y + 16;
-                    display.draw_text(10, status_y, "Status:", TEXT_PRIMARY, None, 1)?;
-                    
-                    self.string_buffer.clear();
-                    let _ = write!(&mut self.string_buffer, "http://{}:8080/api/ota/status", ip);
-                    display.draw_text(60, status_y, &self.string_buffer, PRIMARY_BLUE, None, 1)?;
-                    
-                    let guide_y = status_y + 20;
-                    display.draw_text_centered(guide_y, "Upload .bin file at OTA URL", TEXT_SECONDARY, None, 1)?;
-                    display.draw_text_centered(guide_y + 14, "Device auto-restarts after update", TEXT_SECONDARY, None, 1)?;
-                } else {
-                    self.cached_network_ip = None;
+            // Top alerts with a severity marker
+            let y_start = 66;
+            let line_height = 21;
+            for (i, alert) in summary.top.iter().enumerate() {
+                let y = y_start + i as u16 * line_height;
+                frame.fill_rect(10, y, 6, 16, severity_color(alert.severity));
+                frame.draw_text(22, y, &alert.name, WHITE, None, 1);
+                if !alert.summary.is_empty() {
+                    frame.draw_text(22, y + 9, &alert.summary, TEXT_SECONDARY, None, 1);
                 }
-            } else {
-                self.cached_network_ip = None;
-                display.draw_text_centered(server_section_y + 8, "Network Required", PRIMARY_RED, None, 1)?;
-                display.draw_text_centered(server_section_y + 24, "Connect to WiFi to enable OTA", TEXT_SECONDARY, None, 1)?;
             }
+        } else {
+            frame.draw_text_centered(60, t("alerts.not_configured"), TEXT_PRIMARY, None, 1);
+            frame.draw_text_centered(80, t("alerts.set_url"), TEXT_SECONDARY, None, 1);
         }
         
-        // Mark screen as initialized
-        self.ota_screen_initialized = true;
-        
+        self.frame_presenter.present(frame, display)?;
         Ok(())
     }
     
-    fn render_fps_counter(&mut self, display: &mut DisplayManager) -> Result<()> {
-        // Only update FPS counter if it changed significantly
-        if !self.force_fps_render && self.last_fps_rendered >= 0.0 && self.fps > 0.0 && (self.fps - self.last_fps_rendered).abs() < 0.5 {
-            return Ok(()); // Skip update if change is less than 0.5 FPS
-        }
-        self.last_fps_rendered = self.fps;
-        
-        // Clear the force render flag
-        self.force_fps_render = false;
-        
-        // Draw FPS in top-right corner, below header
-        let fps_text = format!("{:.1} FPS", self.fps);
-        let x = 245;
-        let y = 32;
-        
-        // Clear background for FPS counter
-        display.fill_rect(x, y, 55, 12, BLACK)?;
-        
-        // Draw FPS text with smaller font
-        let color = if self.fps >= 15.0 { PRIMARY_GREEN } 
-                    else if self.fps >= 10.0 { YELLOW } 
-                    else { PRIMARY_RED };
-        display.draw_text(x, y, &fps_text, color, None, 1)?;
-        
-        // Note: The display manager already marks the area as dirty when we draw
-        // so we don't need to explicitly mark it here
-        
+    #[cfg(feature = "screen_air_quality")]
+    fn render_air_quality_screen(&mut self, display: &mut DisplayManager, _screen_changed: bool) -> Result<()> {
+        let co2 = self.co2_reading();
+        let co2_history: Vec<f32> = co2
+            .and_then(|(key, _)| crate::sensors::history::get()
+                .and_then(|history| history.lock().ok().map(|h| h.get_history(key, 6))))
+            .map(|points| points.iter().map(|p| p.value).collect())
+            .unwrap_or_default();
+        let view = views::AirQualityView {
+            co2: co2.map(|(key, ppm)| (ppm, key != air_quality::CO2_KEY)),
+            tvoc_ppb: self.sensor_value(air_quality::TVOC_KEY),
+            temperature: self.sensor_value(air_quality::TEMPERATURE_KEY),
+            humidity: self.sensor_value(air_quality::HUMIDITY_KEY),
+            co2_history: &co2_history,
+        };
+        self.frame_presenter.present(views::air_quality_frame(&view), display)?;
         Ok(())
     }
     
-    fn render_ota_overlay(&mut self, display: &mut DisplayManager, progress: u8) -> Result<()> {
-        // Draw semi-transparent overlay
-        let overlay_y = 50;
-        let overlay_height = 80;
-        
-        // Draw background box with border
-        display.fill_rect(20, overlay_y, 260, overlay_height, SURFACE_DARK)?;
-        display.draw_rect(20, overlay_y, 260, overlay_height, ACCENT_ORANGE)?;
-        
-        // OTA Update title
-        display.draw_text_centered(overlay_y + 10, "OTA UPDATE IN PROGRESS", ACCENT_ORANGE, None, 2)?;
-        
-        // Progress bar
-        let bar_y = overlay_y + 35;
-        display.draw_progress_bar(40, bar_y, 220, 20, progress, PRIMARY_BLUE, SURFACE_LIGHT, WHITE)?;
-        
-        // Progress text
-        let progress_text = format!("{}%", progress);
-        display.draw_text_centered(bar_y + 25, &progress_text, WHITE, None, 1)?;
-        
-        // Warning text
-        display.draw_text_centered(bar_y + 40, "DO NOT POWER OFF", PRIMARY_RED, None, 1)?;
+    #[cfg(feature = "screen_agenda")]
+    fn render_agenda_screen(&mut self, display: &mut DisplayManager, _screen_changed: bool) -> Result<()> {
+        let rows = self.agenda_rows();
+        let view = views::AgendaView {
+            rows: rows.as_deref(),
+            error: self.agenda.as_ref().and_then(|a| a.error.as_deref()),
+        };
+        let frame = views::agenda_frame(&view);
+        self.frame_presenter.present(frame, display)?;
+        Ok(())
+    }
+    
+    #[cfg(feature = "screen_stats")]
+    fn render_stats_screen(&mut self, display: &mut DisplayManager, _screen_changed: bool) -> Result<()> {
+        let mut frame = DrawList::new(SCREEN_WIDTH);
+        frame.clear(BLACK);
+        frame.fill_rect(0, 0, 300, 30, PRIMARY_BLUE);
+        frame.draw_text_centered(8, t("stats.title"), WHITE, None, 2);
+        frame.draw_text(10, 155, t("hint.prev"), TEXT_SECONDARY, None, 1);
+        frame.draw_text(200, 155, t("hint.next"), TEXT_SECONDARY, None, 1);
+        
+        if let Some(stats) = self.lifetime_stats {
+            let rows = [
+                ("stats.boots", stats.boots as u64),
+                ("stats.ota", stats.ota_updates as u64),
+                ("stats.frames", stats.frames_rendered),
+                ("stats.buttons", stats.button_presses),
+                ("stats.wifi", stats.wifi_reconnects),
+            ];
+            let y_start = 40;
+            let line_height = 22;
+            for (i, (label, value)) in rows.into_iter().enumerate() {
+                let y = y_start + i as u16 * line_height;
+                frame.draw_text(10, y + 4, t(label), TEXT_PRIMARY, None, 1);
+                frame.draw_text(150, y, &ui_state::format_count(value), PRIMARY_GREEN, None, 2);
+            }
+        } else {
+            frame.draw_text_centered(70, t("stats.unavailable"), TEXT_PRIMARY, None, 1);
+        }
         
+        self.frame_presenter.present(frame, display)?;
         Ok(())
     }
     
-    fn render_alerts(&mut self, display: &mut DisplayManager) -> Result<()> {
-        // Count active alerts
-        let mut active_alerts = Vec::new();
+    #[cfg(feature = "screen_timer")]
+    fn render_timer_screen(&mut self, display: &mut DisplayManager, _screen_changed: bool) -> Result<()> {
+        let state = self.timer.state();
+        let (state_label, ring_color) = timer_state_style(state);
         
-        if self.temperature_alert {
-            active_alerts.push(("TEMP HIGH", format!("{:.1}°C", self.sensor_data._temperature), PRIMARY_RED));
+        // Full redraw on entry or state change; otherwise only the ring delta and clock
+        if self.cache.timer_state != Some(state) {
+            display.clear(BLACK)?;
+            display.flush()?;
+            
+            display.draw_text(10, 8, t("timer.title"), TEXT_SECONDARY, None, 1)?;
+            display.draw_text(10, 155, t("hint.prev"), TEXT_SECONDARY, None, 1)?;
+            let hint = match state {
+                TimerState::Running => t("timer.pause"),
+                TimerState::Finished => t("hint.next"),
+                _ => t("timer.start"),
+            };
+            display.draw_text(210, 155, hint, TEXT_SECONDARY, None, 1)?;
+            display.draw_text(222, 8, t("timer.hold_reset"), TEXT_SECONDARY, None, 1)?;
+            
+            display.draw_text(150 - (state_label.len() as u16 * 6) / 2, 110, state_label, ring_color, None, 1)?;
+            
+            self.timer_ring.set_colors(ring_color, SURFACE_LIGHT);
+            self.timer_ring.set_value(self.timer.progress_percent());
+            self.timer_ring.draw(display)?;
+            self.cache.timer_secs = None;
+            self.cache.timer_state = Some(state);
+        } else {
+            self.timer_ring.set_value(self.timer.progress_percent());
+            self.timer_ring.draw_changes(display)?;
         }
         
-        if self.wifi_signal_alert {
-            active_alerts.push(("WEAK WIFI", format!("{}dBm", self.network_signal), YELLOW));
+        // Countdown clock in the ring centre
+        let remaining = self.timer.remaining().as_secs();
+        if self.cache.timer_secs != Some(remaining) {
+            self.cache.timer_secs = Some(remaining);
+            let clock = crate::text_buf!("{:02}:{:02}", remaining / 60, remaining % 60);
+            display.fill_rect(104, 80, 94, 24, BLACK)?;
+            display.draw_text(106, 80, &clock, WHITE, None, 3)?;
+            
+            let sessions = crate::text_buf!("{} {}", t("timer.today"), self.timer.sessions_today());
+            display.fill_rect(10, 140, 100, 10, BLACK)?;
+            display.draw_text(10, 140, &sessions, TEXT_SECONDARY, None, 1)?;
         }
         
-        if self.battery_alert {
-            active_alerts.push(("LOW BATTERY", format!("{}%", self.sensor_data._battery_percentage), PRIMARY_RED));
-        }
+        Ok(())
+    }
+    
+    /// Simple vector weather icon centered at (cx, cy) with radius `r`
+    fn draw_weather_icon(frame: &mut DrawList, cx: u16, cy: u16, r: u16, condition: WeatherCondition) {
+        let cloud_color = rgb565(180, 180, 190);
+        let draw_cloud = |frame: &mut DrawList, color: u16| {
+            frame.fill_circle(cx - r / 2, cy, r / 2, color);
+            frame.fill_circle(cx + r / 3, cy - r / 4, r * 2 / 3, color);
+            frame.fill_rect(cx - r / 2, cy, r + r / 3, r / 2, color);
+        };
         
-        // If no alerts, return early
-        if active_alerts.is_empty() {
+        match condition {
+            WeatherCondition::Clear => {
+                frame.fill_circle(cx, cy, r * 2 / 3, YELLOW);
+                // Rays
+                frame.draw_line(cx, cy - r, cx, cy - r * 5 / 6, YELLOW);
+                frame.draw_line(cx, cy + r * 5 / 6, cx, cy + r, YELLOW);
+                frame.draw_line(cx - r, cy, cx - r * 5 / 6, cy, YELLOW);
+                frame.draw_line(cx + r * 5 / 6, cy, cx + r, cy, YELLOW);
+            }
+            WeatherCondition::PartlyCloudy => {
+                frame.fill_circle(cx + r / 3, cy - r / 2, r / 2, YELLOW);
+                draw_cloud(frame, cloud_color);
+            }
+            WeatherCondition::Cloudy | WeatherCondition::Unknown => {
+                draw_cloud(frame, cloud_color);
+            }
+            WeatherCondition::Fog => {
+                for i in 0..3 {
+                    let y = cy - r / 2 + i * (r / 2);
+                    frame.draw_line(cx - r, y, cx + r, y, cloud_color);
+                }
+            }
+            WeatherCondition::Rain => {
+                draw_cloud(frame, cloud_color);
+                for dx in [0, r / 2, r] {
+                    let x = cx - r / 2 + dx;
+                    frame.draw_line(x, cy + r * 2 / 3, x.saturating_sub(2), cy + r, PRIMARY_BLUE);
+                }
+            }
+            WeatherCondition::Snow => {
+                draw_cloud(frame, cloud_color);
+                for dx in [0, r / 2, r] {
+                    frame.fill_circle(cx - r / 2 + dx, cy + r * 5 / 6, (r / 10).max(1), WHITE);
+                }
+            }
+            WeatherCondition::Thunder => {
+                draw_cloud(frame, rgb565(120, 120, 130));
+                frame.draw_line(cx, cy + r / 2, cx - r / 4, cy + r * 3 / 4, YELLOW);
+                frame.draw_line(cx - r / 4, cy + r * 3 / 4, cx + r / 6, cy + r * 3 / 4, YELLOW);
+                frame.draw_line(cx + r / 6, cy + r * 3 / 4, cx - r / 6, cy + r, YELLOW);
+            }
+        }
+    }
+    
+    fn render_fps_counter(&mut self, display: &mut DisplayManager) -> Result<()> {
+        // Only update FPS counter if it changed significantly; the large-text
+        // layout has no room for it
+        if self.accessibility || !self.cache.fps_due(self.fps) {
             return Ok(());
         }
-        
-        // Render alert bar at the top of the screen
-        let alert_height = 20;
-        let alert_y = 2;
-        
-        // Cycle through alerts if multiple (show one at a time)
-        let current_time = std::time::SystemTime::now()
+        views::draw_fps(display, self.fps)?;
+        Ok(())
+    }
+    
+    /// Alert bar entries: label, value and colour
+    fn active_alerts(&self) -> Vec<(&'static str, String, u16)> {
+        // Safe mode is shown alone so it never rotates out
+        if self.safe_mode {
+            return vec![(t("alert.safe_mode"), t("alert.safe_mode_detail").to_string(), PRIMARY_RED)];
+        }
+        self.alerts.entries(self.sensor_data._temperature, self.sensor_data._battery_percentage, self.network_signal)
+    }
+    
+    /// Multiple alerts take turns in the bar
+    fn alert_slot(count: usize) -> usize {
+        let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs();
-        let alert_index = ((current_time / 3) % active_alerts.len() as u64) as usize;
-        
-        let (label, value, color) = &active_alerts[alert_index];
-        
-        // Draw alert background
-        display.fill_rect(0, alert_y, 300, alert_height, color.clone())?;
-        
-        // Draw alert text
-        let alert_text = format!("⚠ {}: {}", label, value);
-        display.draw_text_centered(alert_y + 6, &alert_text, BLACK, None, 1)?;
-        
-        Ok(())
+        ui_state::alert_slot(count, now)
+    }
+}
+
+#[cfg(feature = "screen_alerts")]
+fn severity_color(severity: Severity) -> u16 {
+    match severity {
+        Severity::Critical => PRIMARY_RED,
+        Severity::Warning => ACCENT_ORANGE,
+        Severity::Info => PRIMARY_BLUE,
+        Severity::Other => TEXT_SECONDARY,
+    }
+}
+
+/// Timer state label and ring colour
+#[cfg(feature = "screen_timer")]
+fn timer_state_style(state: TimerState) -> (&'static str, u16) {
+    match state {
+        TimerState::Idle => (t("timer.ready"), PRIMARY_BLUE),
+        TimerState::Running => (t("timer.running"), PRIMARY_RED),
+        TimerState::Paused => (t("timer.paused"), YELLOW),
+        TimerState::Finished => (t("timer.done"), PRIMARY_GREEN),
     }
-}
\ No newline at end of file
+}
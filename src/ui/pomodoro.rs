@@ -0,0 +1,154 @@
+// Pomodoro / focus timer state and persisted session stats
+
+use crate::storage::kv;
+use std::time::{Duration, Instant};
+
+const NVS_NAMESPACE: &str = "pomodoro";
+const NVS_KEY_DAY: &str = "day";
+const NVS_KEY_SESSIONS: &str = "sessions";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimerState {
+    Idle,
+    Running,
+    Paused,
+    Finished,
+}
+
+pub struct PomodoroTimer {
+    duration: Duration,
+    // Remaining time when last started/paused
+    remaining: Duration,
+    resumed_at: Option<Instant>,
+    state: TimerState,
+    sessions_today: u32,
+    day: u32,
+}
+
+/// Days since the Unix epoch, or 0 if the clock has not been set
+fn current_day() -> u32 {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    // Treat anything before 2020 as an unset RTC
+    if secs < 1_577_836_800 { 0 } else { (secs / 86_400) as u32 }
+}
+
+impl PomodoroTimer {
+    pub fn new(minutes: u32) -> Self {
+        let duration = Duration::from_secs(minutes.clamp(1, 180) as u64 * 60);
+        let (day, sessions_today) = load_stats();
+        let mut timer = Self {
+            duration,
+            remaining: duration,
+            resumed_at: None,
+            state: TimerState::Idle,
+            sessions_today,
+            day,
+        };
+        timer.roll_day();
+        timer
+    }
+
+    /// Start, pause or resume the countdown
+    pub fn toggle(&mut self) {
+        match self.state {
+            TimerState::Idle | TimerState::Paused => {
+                self.resumed_at = Some(Instant::now());
+                self.state = TimerState::Running;
+            }
+            TimerState::Running => {
+                self.remaining = self.remaining();
+                self.resumed_at = None;
+                self.state = TimerState::Paused;
+            }
+            TimerState::Finished => {
+                // Start the next session straight away
+                self.reset();
+                self.toggle();
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.remaining = self.duration;
+        self.resumed_at = None;
+        self.state = TimerState::Idle;
+    }
+
+    /// Apply a new session length; takes effect immediately when not running
+    pub fn set_minutes(&mut self, minutes: u32) {
+        self.duration = Duration::from_secs(minutes.clamp(1, 180) as u64 * 60);
+        if self.state == TimerState::Idle {
+            self.remaining = self.duration;
+        }
+    }
+
+    pub fn minutes(&self) -> u32 {
+        (self.duration.as_secs() / 60) as u32
+    }
+
+    /// Advance the timer. Returns true exactly once when a session completes.
+    pub fn tick(&mut self) -> bool {
+        if self.state != TimerState::Running || !self.remaining().is_zero() {
+            return false;
+        }
+        self.remaining = Duration::ZERO;
+        self.resumed_at = None;
+        self.state = TimerState::Finished;
+        self.roll_day();
+        self.sessions_today += 1;
+        save_stats(self.day, self.sessions_today);
+        true
+    }
+
+    pub fn remaining(&self) -> Duration {
+        match self.resumed_at {
+            Some(t) => self.remaining.saturating_sub(t.elapsed()),
+            None => self.remaining,
+        }
+    }
+
+    /// Elapsed fraction of the session, 0-100
+    pub fn progress_percent(&self) -> f32 {
+        let total = self.duration.as_secs_f32();
+        if total <= 0.0 { return 100.0; }
+        (1.0 - self.remaining().as_secs_f32() / total) * 100.0
+    }
+
+    pub fn state(&self) -> TimerState {
+        self.state
+    }
+
+    pub fn sessions_today(&self) -> u32 {
+        self.sessions_today
+    }
+
+    // Reset the daily counter when the date changes
+    fn roll_day(&mut self) {
+        let today = current_day();
+        if today != self.day {
+            self.day = today;
+            self.sessions_today = 0;
+        }
+    }
+}
+
+fn load_stats() -> (u32, u32) {
+    let Ok(mut store) = kv::open(NVS_NAMESPACE) else { return (0, 0) };
+    let day = store.get(NVS_KEY_DAY).unwrap_or(0);
+    let sessions = store.get(NVS_KEY_SESSIONS).unwrap_or(0);
+    (day, sessions)
+}
+
+fn save_stats(day: u32, sessions: u32) {
+    let result = kv::open(NVS_NAMESPACE).and_then(|mut store| {
+        store.stage(NVS_KEY_DAY, &day)?;
+        store.stage(NVS_KEY_SESSIONS, &sessions)?;
+        store.flush()
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to save pomodoro stats: {:?}", e);
+    }
+}
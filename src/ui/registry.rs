@@ -0,0 +1,241 @@
+// Screen registry. Each screen registers a stable name plus its render and
+// input handlers; UiManager only navigates the resulting rotation, so optional
+// screens are added (or compiled out) without touching the core render loop.
+// Screens also hash the state their output depends on, and UiManager skips
+// frames whose hash matches the last rendered one. In accessibility mode a
+// screen is shown through its `text_view` instead, in the large-text layout.
+
+use anyhow::Result;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use crate::input::InputEvent;
+
+/// One screen in the rotation. `Ui` owns the shared state and `Display` is
+/// the draw target, so screens can be plain unit structs.
+pub trait Screen<Ui, Display> {
+    /// Stable name used by `screen_order` and the web API
+    fn name(&self) -> &'static str;
+
+    /// Hash of everything the screen's output depends on (see `hash_state`).
+    /// `render` is only called when this changes or the screen is entered.
+    fn state_hash(&self, ui: &Ui) -> u64;
+
+    /// Draw the screen; `entered` is true on the first frame after switching to it
+    fn render(&self, ui: &mut Ui, display: &mut Display, entered: bool) -> Result<()>;
+
+    /// Handle an event before default navigation sees it; true if consumed
+    fn handle_input(&self, _ui: &mut Ui, _event: InputEvent) -> bool {
+        false
+    }
+
+    /// The screen's content as titled label/value rows, drawn instead of
+    /// `render` by the large-text layout; None keeps the normal layout
+    fn text_view(&self, _ui: &Ui) -> Option<TextView> {
+        None
+    }
+}
+
+/// A screen reduced to label/value rows (see `Screen::text_view`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextView {
+    pub title: &'static str,
+    pub rows: Vec<TextRow>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextRow {
+    pub label: String,
+    pub value: String,
+    pub color: u16,
+}
+
+impl TextRow {
+    pub fn new(label: impl Into<String>, value: impl Into<String>, color: u16) -> Self {
+        Self { label: label.into(), value: value.into(), color }
+    }
+}
+
+/// All registered screens and the order they are shown in
+pub struct ScreenRegistry<Ui: 'static, Display: 'static> {
+    registered: Vec<&'static dyn Screen<Ui, Display>>,
+    // Indices into `registered`, in display order
+    rotation: Vec<usize>,
+    // Names last passed to `set_order`, to skip redundant reorders
+    order: Vec<String>,
+}
+
+impl<Ui: 'static, Display: 'static> ScreenRegistry<Ui, Display> {
+    pub fn new() -> Self {
+        Self { registered: Vec::new(), rotation: Vec::new(), order: Vec::new() }
+    }
+
+    /// Add a screen to the end of the default rotation. A name that is
+    /// already registered is ignored.
+    pub fn register(&mut self, screen: &'static dyn Screen<Ui, Display>) {
+        if self.registered.iter().any(|s| s.name() == screen.name()) {
+            log::warn!("UI: screen '{}' registered twice", screen.name());
+            return;
+        }
+        self.registered.push(screen);
+        self.apply_order();
+    }
+
+    /// Show the named screens in this order. Unknown names are skipped and an
+    /// empty list (or one naming no known screen) shows every screen in
+    /// registration order. Returns false if the order was already in effect.
+    pub fn set_order(&mut self, names: &[String]) -> bool {
+        if self.order == names {
+            return false;
+        }
+        self.order = names.to_vec();
+        self.apply_order();
+        true
+    }
+
+    fn apply_order(&mut self) {
+        self.rotation.clear();
+        for name in &self.order {
+            match self.registered.iter().position(|s| s.name() == name) {
+                Some(i) if !self.rotation.contains(&i) => self.rotation.push(i),
+                Some(_) => {}
+                None => log::warn!("UI: unknown screen '{}' in screen_order", name),
+            }
+        }
+        if self.rotation.is_empty() {
+            self.rotation = (0..self.registered.len()).collect();
+        }
+    }
+
+    /// Number of screens in the rotation
+    pub fn len(&self) -> usize {
+        self.rotation.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rotation.is_empty()
+    }
+
+    /// Screen at a rotation position
+    pub fn get(&self, position: usize) -> Option<&'static dyn Screen<Ui, Display>> {
+        self.rotation.get(position).map(|&i| self.registered[i])
+    }
+
+    /// Rotation position of a screen
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.rotation.iter().position(|&i| self.registered[i].name() == name)
+    }
+
+    /// Names in rotation order
+    pub fn names(&self) -> Vec<&'static str> {
+        self.rotation.iter().map(|&i| self.registered[i].name()).collect()
+    }
+}
+
+impl<Ui: 'static, Display: 'static> Default for ScreenRegistry<Ui, Display> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Content hash for `Screen::state_hash`; pass f32 fields as `to_bits()`
+pub fn hash_state<T: Hash + ?Sized>(state: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Remembers the state hash of the last rendered frame
+#[derive(Debug, Default)]
+pub struct FrameGate {
+    last: Option<u64>,
+}
+
+impl FrameGate {
+    /// True if `hash` differs from the last rendered frame; records it as rendered
+    pub fn should_render(&mut self, hash: u64) -> bool {
+        if self.last == Some(hash) {
+            return false;
+        }
+        self.last = Some(hash);
+        true
+    }
+
+    /// Render the next frame whatever its hash (e.g. after the screen was cleared)
+    pub fn invalidate(&mut self) {
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Named(&'static str);
+
+    impl Screen<Vec<&'static str>, ()> for Named {
+        fn name(&self) -> &'static str { self.0 }
+
+        fn state_hash(&self, ui: &Vec<&'static str>) -> u64 { hash_state(ui) }
+
+        fn render(&self, ui: &mut Vec<&'static str>, _display: &mut (), _entered: bool) -> Result<()> {
+            ui.push(self.0);
+            Ok(())
+        }
+    }
+
+    static A: Named = Named("a");
+    static B: Named = Named("b");
+    static C: Named = Named("c");
+
+    fn registry() -> ScreenRegistry<Vec<&'static str>, ()> {
+        let mut registry = ScreenRegistry::new();
+        registry.register(&A);
+        registry.register(&B);
+        registry.register(&C);
+        // Duplicate names are ignored
+        registry.register(&Named("a"));
+        registry
+    }
+
+    fn order(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_registration_order_is_default() {
+        let registry = registry();
+        assert_eq!(registry.names(), ["a", "b", "c"]);
+        let mut drawn = Vec::new();
+        registry.get(1).unwrap().render(&mut drawn, &mut (), true).unwrap();
+        assert_eq!(drawn, ["b"]);
+    }
+
+    #[test]
+    fn test_set_order_filters_and_reorders() {
+        let mut registry = registry();
+        assert!(registry.set_order(&order(&["c", "missing", "a", "c"])));
+        assert_eq!(registry.names(), ["c", "a"]);
+        assert_eq!(registry.position("a"), Some(1));
+        assert_eq!(registry.position("b"), None);
+        assert!(!registry.set_order(&order(&["c", "missing", "a", "c"])));
+
+        // Nothing usable falls back to every screen
+        assert!(registry.set_order(&order(&["missing"])));
+        assert_eq!(registry.len(), 3);
+        assert!(registry.set_order(&[]));
+        assert_eq!(registry.names(), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_frame_gate_skips_unchanged_state() {
+        let mut gate = FrameGate::default();
+        let mut state = vec!["a"];
+        assert!(gate.should_render(A.state_hash(&state)));
+        assert!(!gate.should_render(A.state_hash(&state)));
+        state.push("b");
+        assert!(gate.should_render(A.state_hash(&state)));
+        gate.invalidate();
+        assert!(gate.should_render(A.state_hash(&state)));
+        assert_ne!(hash_state(&(1u8, 0.5f32.to_bits())), hash_state(&(1u8, 0.25f32.to_bits())));
+    }
+}
@@ -0,0 +1,134 @@
+// What UiManager last drew, so a frame can skip the parts that haven't
+// changed. Everything lives here, per UiManager instance, rather than in
+// function-local statics; `invalidate` makes the next frame draw in full.
+
+use crate::ota::OtaStatus;
+use super::pomodoro::TimerState;
+
+/// Header clocks are redrawn at most this often (seconds)
+pub const CLOCK_REFRESH_SECS: u64 = 5;
+
+/// Smallest FPS change worth redrawing the counter for
+const FPS_REDRAW_DELTA: f32 = 0.5;
+
+#[derive(Debug, Clone, Default)]
+pub struct RenderCache {
+    /// Uptime second the System/Network header clock was drawn at
+    header_clock_secs: Option<u64>,
+    /// Same for the OTA screen, which draws its header on its own schedule
+    ota_clock_secs: Option<u64>,
+    battery: Option<u8>,
+    progress: Option<u8>,
+    fps: Option<f32>,
+    /// OTA screen: status and address it was drawn with
+    pub ota_status: Option<OtaStatus>,
+    pub ota_network_ip: Option<String>,
+    /// Timer screen: state the screen was fully drawn for, countdown shown
+    pub timer_state: Option<TimerState>,
+    pub timer_secs: Option<u64>,
+    /// Sensor screen: power warning, power monitor row and readings line its
+    /// chrome was drawn for
+    pub sensor_power_warning: Option<String>,
+    pub sensor_power_row: bool,
+    pub sensor_readings_row: bool,
+}
+
+impl RenderCache {
+    /// Forget everything drawn; the next frame redraws it all
+    pub fn invalidate(&mut self) {
+        *self = Self::default();
+    }
+
+    /// The header clock is due at uptime `secs`; records it as drawn
+    pub fn header_clock_due(&mut self, secs: u64) -> bool {
+        Self::clock_due(&mut self.header_clock_secs, secs)
+    }
+
+    /// The OTA screen's header clock is due at uptime `secs`; records it as drawn
+    pub fn ota_clock_due(&mut self, secs: u64) -> bool {
+        Self::clock_due(&mut self.ota_clock_secs, secs)
+    }
+
+    fn clock_due(drawn: &mut Option<u64>, secs: u64) -> bool {
+        if drawn.is_some_and(|at| secs < at + CLOCK_REFRESH_SECS) {
+            return false;
+        }
+        *drawn = Some(secs);
+        true
+    }
+
+    /// The battery indicator needs drawing (charging animates, so always)
+    pub fn battery_due(&mut self, percent: u8, charging: bool) -> bool {
+        let due = charging || self.battery != Some(percent);
+        self.battery = Some(percent);
+        due
+    }
+
+    pub fn progress_due(&mut self, progress: u8) -> bool {
+        let due = self.progress != Some(progress);
+        self.progress = Some(progress);
+        due
+    }
+
+    /// The FPS counter moved enough to redraw (or wasn't drawn yet)
+    pub fn fps_due(&mut self, fps: f32) -> bool {
+        if self.fps.is_some_and(|drawn| fps > 0.0 && (fps - drawn).abs() < FPS_REDRAW_DELTA) {
+            return false;
+        }
+        self.fps = Some(fps);
+        true
+    }
+
+    /// Redraw the FPS counter on the next frame (e.g. the screen was cleared)
+    pub fn invalidate_fps(&mut self) {
+        self.fps = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_redraw_only_on_change() {
+        let mut cache = RenderCache::default();
+        assert!(cache.header_clock_due(100));
+        assert!(!cache.header_clock_due(104));
+        assert!(cache.header_clock_due(105));
+        // The OTA clock keeps its own schedule
+        assert!(cache.ota_clock_due(104));
+
+        assert!(cache.battery_due(80, false));
+        assert!(!cache.battery_due(80, false));
+        assert!(cache.battery_due(80, true));
+        assert!(cache.battery_due(79, false));
+
+        assert!(cache.progress_due(10));
+        assert!(!cache.progress_due(10));
+
+        assert!(cache.fps_due(30.0));
+        assert!(!cache.fps_due(30.4));
+        assert!(cache.fps_due(31.0));
+        cache.invalidate_fps();
+        assert!(cache.fps_due(31.0));
+    }
+
+    #[test]
+    fn test_invalidate_forces_full_redraw() {
+        let mut cache = RenderCache::default();
+        cache.header_clock_due(100);
+        cache.battery_due(50, false);
+        cache.progress_due(20);
+        cache.fps_due(12.0);
+        cache.ota_status = Some(OtaStatus::Idle);
+        cache.timer_state = Some(TimerState::Running);
+
+        cache.invalidate();
+        assert!(cache.header_clock_due(101));
+        assert!(cache.battery_due(50, false));
+        assert!(cache.progress_due(20));
+        assert!(cache.fps_due(12.0));
+        assert_eq!(cache.ota_status, None);
+        assert_eq!(cache.timer_state, None);
+    }
+}
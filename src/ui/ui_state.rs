@@ -0,0 +1,356 @@
+// Display-free UI logic: which screen is up and whether it still has to be
+// drawn from scratch, the OTA overlay, alert rotation and the strings the
+// screens show. UiManager keeps the state and does the drawing; everything
+// in here is plain data in, plain data out, so host-tests can cover it.
+
+use std::fmt::Write;
+use crate::display::colors::*;
+use crate::i18n::t;
+use crate::ota::progress::OtaStatus;
+use crate::text_buf::TextBuf;
+use crate::units;
+
+/// Ambient temperature above this raises the alert bar (°C)
+pub const TEMPERATURE_ALERT_C: f32 = 35.0;
+/// Battery below this raises the alert bar when not on USB (%)
+pub const BATTERY_ALERT_PERCENT: u8 = 10;
+/// Signal below this raises the alert bar while connected (dBm)
+pub const WIFI_ALERT_DBM: i8 = -80;
+
+/// Seconds each alert stays in the bar when several are active
+const ALERT_SLOT_SECS: u64 = 3;
+
+/// Seconds between header clock refreshes
+const HEADER_TICK_SECS: u64 = 5;
+
+/// A switch to a screen that hasn't been drawn yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenChange {
+    /// Screen drawn before, None after `invalidate` (nothing to animate from)
+    pub from: Option<usize>,
+    pub to: usize,
+}
+
+/// Position in the screen rotation and what was last drawn
+#[derive(Debug, Clone, Default)]
+pub struct Navigation {
+    current: usize,
+    rendered: Option<usize>,
+}
+
+impl Navigation {
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// BOOT: one screen back, stopping at the first
+    pub fn previous(&mut self) {
+        self.current = self.current.saturating_sub(1);
+    }
+
+    /// USER: one screen on, wrapping around
+    pub fn next(&mut self, count: usize) {
+        self.current = (self.current + 1) % count.max(1);
+    }
+
+    /// Encoder: `steps` screens either way, wrapping around
+    pub fn rotate(&mut self, steps: i32, count: usize) {
+        let count = count.max(1) as i32;
+        self.current = (self.current as i32 + steps).rem_euclid(count) as usize;
+    }
+
+    /// Jump to `index`; false if there is no such screen
+    pub fn show(&mut self, index: usize, count: usize) -> bool {
+        if index >= count {
+            return false;
+        }
+        self.current = index;
+        true
+    }
+
+    /// The rotation itself changed: move to `index` and draw it from scratch
+    pub fn reset(&mut self, index: usize) {
+        self.current = index;
+        self.invalidate();
+    }
+
+    /// Draw the current screen from scratch on the next frame
+    pub fn invalidate(&mut self) {
+        self.rendered = None;
+    }
+
+    /// The current screen is about to be drawn; Some if it wasn't the last one drawn
+    pub fn enter(&mut self) -> Option<ScreenChange> {
+        if self.rendered == Some(self.current) {
+            return None;
+        }
+        let change = ScreenChange { from: self.rendered, to: self.current };
+        self.rendered = Some(self.current);
+        Some(change)
+    }
+}
+
+/// Progress shown in the OTA overlay, None when there is no overlay
+pub fn ota_overlay(status: OtaStatus) -> Option<u8> {
+    match status {
+        OtaStatus::Downloading { progress } => Some(progress),
+        _ => None,
+    }
+}
+
+/// OTA screen status line and its colour; `buf` holds the text when it is formatted
+pub fn ota_status_text(status: OtaStatus, buf: &mut String) -> (&str, u16) {
+    match status {
+        OtaStatus::Idle => (t("ota.ready"), TEXT_SECONDARY),
+        OtaStatus::Downloading { progress } => {
+            buf.clear();
+            let _ = write!(buf, "{} {}%", t("ota.downloading"), progress);
+            (buf.as_str(), PRIMARY_BLUE)
+        }
+        OtaStatus::Verifying => (t("ota.verifying"), YELLOW),
+        OtaStatus::Staged => {
+            buf.clear();
+            let _ = write!(buf, "{} {}", t("ota.staged"), crate::ota::window::start_text());
+            (buf.as_str(), YELLOW)
+        }
+        OtaStatus::Ready => (t("ota.restart"), PRIMARY_GREEN),
+        OtaStatus::Failed => (t("ota.failed"), PRIMARY_RED),
+    }
+}
+
+/// System screen uptime: minutes and seconds for the first hour, then hours and minutes
+pub fn write_uptime(buf: &mut String, secs: u64) {
+    buf.clear();
+    let _ = if secs < 3600 {
+        write!(buf, "{}m {}s", secs / 60, secs % 60)
+    } else {
+        write!(buf, "{}h {}m", secs / 3600, (secs % 3600) / 60)
+    };
+}
+
+pub fn format_heap(free_kb: u32) -> TextBuf {
+    crate::text_buf!("{} KB", free_kb)
+}
+
+pub fn format_cpu(freq_mhz: u32, cpu0: u8, cpu1: u8) -> TextBuf {
+    crate::text_buf!("{} MHz C0:{}% C1:{}%", freq_mhz, cpu0, cpu1)
+}
+
+pub fn format_flash(app_mb: u32, total_mb: u32) -> TextBuf {
+    crate::text_buf!("{}/{}MB", app_mb, total_mb)
+}
+
+/// PSRAM row and its colour
+pub fn format_psram(available: bool, free_bytes: usize) -> (TextBuf, u16) {
+    if available {
+        (crate::text_buf!("{}MB free", free_bytes / 1024 / 1024), PRIMARY_GREEN)
+    } else {
+        (TextBuf::from(t("common.not_available")), YELLOW)
+    }
+}
+
+/// Chip temperature colour on the System screen
+pub fn temperature_color(celsius: f32) -> u16 {
+    if celsius > 50.0 {
+        PRIMARY_RED
+    } else if celsius > 40.0 {
+        YELLOW
+    } else {
+        PRIMARY_GREEN
+    }
+}
+
+/// Reliability row: 7-day availability once there is enough history, boot count before
+pub fn reliability_summary(availability_7d: Option<f32>, boot_count: u32, crashes: usize) -> String {
+    match availability_7d {
+        Some(pct) => format!("{:.1}% 7d, {} crashes", pct, crashes),
+        None => format!("{} boots, {} crashes", boot_count, crashes),
+    }
+}
+
+/// Lifetime counter for the Stats screen: exact below 10 000, then k/M
+pub fn format_count(n: u64) -> String {
+    match n {
+        0..=9_999 => n.to_string(),
+        10_000..=999_999 => format!("{:.1}k", n as f64 / 1_000.0),
+        _ => format!("{:.1}M", n as f64 / 1_000_000.0),
+    }
+}
+
+/// Alert conditions, recomputed from each sensor and network update
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AlertFlags {
+    pub temperature: bool,
+    pub wifi_signal: bool,
+    pub battery: bool,
+}
+
+impl AlertFlags {
+    pub fn update_sensors(&mut self, temperature: f32, battery_percent: u8, on_usb: bool) {
+        self.temperature = temperature > TEMPERATURE_ALERT_C;
+        self.battery = battery_percent < BATTERY_ALERT_PERCENT && !on_usb;
+    }
+
+    pub fn update_network(&mut self, connected: bool, signal: i8) {
+        self.wifi_signal = connected && signal < WIFI_ALERT_DBM;
+    }
+
+    /// Alert bar entries: label, value and colour
+    pub fn entries(&self, temperature: f32, battery_percent: u8, signal: i8) -> Vec<(&'static str, String, u16)> {
+        let mut entries = Vec::new();
+        if self.temperature {
+            entries.push((t("alert.temp_high"), units::format_temperature(temperature, 1).into(), PRIMARY_RED));
+        }
+        if self.wifi_signal {
+            entries.push((t("alert.weak_wifi"), format!("{}dBm", signal), YELLOW));
+        }
+        if self.battery {
+            entries.push((t("alert.low_battery"), format!("{}%", battery_percent), PRIMARY_RED));
+        }
+        entries
+    }
+}
+
+/// Which of `count` alerts the bar shows at `unix_secs`
+pub fn alert_slot(count: usize, unix_secs: u64) -> usize {
+    if count == 0 {
+        return 0;
+    }
+    ((unix_secs / ALERT_SLOT_SECS) % count as u64) as usize
+}
+
+/// Changes as often as the header clock is refreshed
+pub fn header_tick(uptime_secs: u64) -> u64 {
+    uptime_secs / HEADER_TICK_SECS
+}
+
+/// Activity animation progress (0..=1) in percent
+pub fn progress_percent(animation_progress: f32) -> u8 {
+    (animation_progress * 100.0) as u8
+}
+
+const WEEKDAY_KEYS: [&str; 7] = [
+    "agenda.mon", "agenda.tue", "agenda.wed", "agenda.thu", "agenda.fri", "agenda.sat", "agenda.sun",
+];
+
+/// When an agenda event starts: `09:30` today, `Tomorrow 09:30`, `Fri 17 09:30`
+/// later on; all-day events have no time and running ones show as `Now`.
+/// `weekday` is 0 for Monday; `minutes` is None for all-day events.
+pub fn agenda_when(day_offset: i64, weekday: u8, day_of_month: u8, minutes: Option<u16>, started: bool) -> String {
+    let day = match day_offset {
+        ..=0 => None,
+        1 => Some(t("agenda.tomorrow").to_string()),
+        _ => Some(format!("{} {}", t(WEEKDAY_KEYS[weekday as usize % 7]), day_of_month)),
+    };
+    let time = match minutes {
+        Some(_) if started => Some(t("agenda.now").to_string()),
+        Some(m) => Some(units::format_time_of_day(m).into()),
+        None => None,
+    };
+    match (day, time) {
+        (Some(day), Some(time)) => format!("{} {}", day, time),
+        (Some(text), None) | (None, Some(text)) => text,
+        (None, None) => t("agenda.all_day").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_navigation_and_redraw() {
+        let mut nav = Navigation::default();
+        // First frame draws from scratch with nothing to animate from
+        assert_eq!(nav.enter(), Some(ScreenChange { from: None, to: 0 }));
+        assert_eq!(nav.enter(), None);
+
+        nav.previous();
+        assert_eq!(nav.current(), 0, "BOOT stops at the first screen");
+        nav.rotate(-1, 5);
+        assert_eq!(nav.current(), 4);
+        nav.next(5);
+        assert_eq!(nav.current(), 0);
+        nav.rotate(7, 5);
+        assert_eq!(nav.current(), 2);
+        assert_eq!(nav.enter(), Some(ScreenChange { from: Some(0), to: 2 }));
+
+        assert!(!nav.show(5, 5));
+        assert!(nav.show(2, 5));
+        assert_eq!(nav.enter(), None, "already on screen");
+
+        nav.invalidate();
+        assert_eq!(nav.enter(), Some(ScreenChange { from: None, to: 2 }));
+        nav.reset(1);
+        assert_eq!(nav.enter(), Some(ScreenChange { from: None, to: 1 }));
+    }
+
+    #[test]
+    fn test_formatting() {
+        let mut buf = String::new();
+        write_uptime(&mut buf, 59);
+        assert_eq!(buf, "0m 59s");
+        write_uptime(&mut buf, 3599);
+        assert_eq!(buf, "59m 59s");
+        write_uptime(&mut buf, 90_061);
+        assert_eq!(buf, "25h 1m");
+
+        assert_eq!(format_heap(184), "184 KB");
+        assert_eq!(format_cpu(240, 12, 7), "240 MHz C0:12% C1:7%");
+        assert_eq!(format_flash(2, 16), "2/16MB");
+        assert_eq!(format_psram(true, 7 * 1024 * 1024 + 1), (TextBuf::from("7MB free"), PRIMARY_GREEN));
+        assert_eq!(format_psram(false, 0).1, YELLOW);
+        assert_eq!(temperature_color(40.0), PRIMARY_GREEN);
+        assert_eq!(temperature_color(45.0), YELLOW);
+        assert_eq!(temperature_color(50.5), PRIMARY_RED);
+        assert_eq!(reliability_summary(Some(99.94), 12, 1), "99.9% 7d, 1 crashes");
+        assert_eq!(reliability_summary(None, 3, 0), "3 boots, 0 crashes");
+        assert_eq!(format_count(9_999), "9999");
+        assert_eq!(format_count(12_345), "12.3k");
+        assert_eq!(format_count(48_200_000), "48.2M");
+
+        assert_eq!(ota_overlay(OtaStatus::Downloading { progress: 42 }), Some(42));
+        assert_eq!(ota_overlay(OtaStatus::Verifying), None);
+        let (text, color) = ota_status_text(OtaStatus::Downloading { progress: 42 }, &mut buf);
+        assert!(text.ends_with(" 42%"), "{}", text);
+        assert_eq!(color, PRIMARY_BLUE);
+        assert_eq!(ota_status_text(OtaStatus::Failed, &mut buf).1, PRIMARY_RED);
+        let (text, color) = ota_status_text(OtaStatus::Staged, &mut buf);
+        assert!(text.ends_with(" 02:00"), "{}", text);
+        assert_eq!(color, YELLOW);
+
+        assert_eq!(header_tick(9), 1);
+        assert_eq!(progress_percent(0.5), 50);
+    }
+
+    #[test]
+    fn test_alerts() {
+        let mut alerts = AlertFlags::default();
+        alerts.update_sensors(36.0, 5, true);
+        assert_eq!(alerts, AlertFlags { temperature: true, wifi_signal: false, battery: false });
+        alerts.update_sensors(20.0, 5, false);
+        alerts.update_network(true, -85);
+        assert_eq!(alerts, AlertFlags { temperature: false, wifi_signal: true, battery: true });
+        // A disconnected radio has no signal to warn about
+        alerts.update_network(false, -100);
+        assert!(!alerts.wifi_signal);
+
+        let entries = alerts.entries(20.0, 5, -100);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].1, "5%");
+
+        assert_eq!(alert_slot(0, 100), 0);
+        assert_eq!(alert_slot(3, 0), 0);
+        assert_eq!(alert_slot(3, 3), 1);
+        assert_eq!(alert_slot(3, 9), 0);
+    }
+
+    #[test]
+    fn test_agenda_when() {
+        assert_eq!(agenda_when(0, 0, 13, Some(9 * 60 + 30), false), "09:30");
+        assert_eq!(agenda_when(0, 0, 13, Some(9 * 60 + 30), true), "Now");
+        assert_eq!(agenda_when(0, 0, 13, None, true), "All day");
+        assert_eq!(agenda_when(1, 1, 14, Some(14 * 60), false), "Tomorrow 14:00");
+        assert_eq!(agenda_when(4, 4, 17, None, false), "Fri 17");
+    }
+}
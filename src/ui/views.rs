@@ -0,0 +1,848 @@
+// Drawing for the core screens and overlays, kept free of device state so the
+// same code renders on the host (see host-tests screen snapshots). UiManager
+// decides what changed and feeds these functions plain values.
+
+use crate::display::error::DisplayError;
+use crate::i18n::t;
+use crate::text_buf::TextBuf;
+use crate::units;
+use crate::display::{DisplayManager, colors::*};
+use crate::display::draw_list::DrawList;
+use super::components::TextField;
+use super::layout::{text_pages, Rect, Size, FONT_HEIGHT, LARGE_TEXT_ROWS, SCREEN_WIDTH};
+use super::registry::TextView;
+use crate::sensors::air_quality::AirQuality;
+
+type Result<T> = core::result::Result<T, DisplayError>;
+
+/// i18n keys of the System screen rows
+pub const SYSTEM_LABELS: [&str; 7] = [
+    "system.uptime", "system.free_heap", "system.cpu_freq", "system.flash",
+    "system.temp", "system.psram", "system.reliability",
+];
+
+/// Zones of the System screen, derived from the screen size
+pub struct SystemLayout {
+    pub header: Rect,
+    pub clock: Rect,
+    pub battery: Rect,
+    pub labels: [Rect; 7],
+    pub values: [Rect; 7],
+    pub progress: Rect,
+    pub hints: [Rect; 2],
+}
+
+impl SystemLayout {
+    pub fn new() -> Self {
+        let [header, body, progress, footer] = Rect::screen()
+            .rows([Size::Fixed(30), Size::Fill(1), Size::Fixed(8), Size::Fixed(12)], 4);
+        let [clock, _title, battery] = header.inset(5)
+            .cols([Size::Fixed(64), Size::Fill(1), Size::Fixed(72)], 4);
+        let rows = body.inset_xy(10, 0).rows([Size::Fill(1); 7], 0);
+        let labels = rows.map(|row| row.cols([Size::Fixed(110), Size::Fill(1)], 0)[0]);
+        let values = rows.map(|row| row.cols([Size::Fixed(110), Size::Fill(1)], 0)[1]);
+        Self {
+            header,
+            clock,
+            battery,
+            labels,
+            values,
+            progress: progress.inset_xy(10, 0),
+            hints: footer.inset_xy(10, 0).cols([Size::Fill(1), Size::Fixed(66)], 0),
+        }
+    }
+}
+
+impl Default for SystemLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Value fields of the System screen
+pub struct SystemFields {
+    pub clock: TextField,
+    pub battery: TextField,
+    pub values: [TextField; 7],
+}
+
+impl SystemFields {
+    pub fn new(layout: &SystemLayout) -> Self {
+        let [_icon, text] = layout.battery.cols([Size::Fixed(30), Size::Fill(1)], 0);
+        Self {
+            clock: TextField::in_zone(layout.clock, 1, PRIMARY_BLUE),
+            battery: TextField::in_zone(text, 1, PRIMARY_BLUE),
+            values: layout.values.map(|zone| TextField::in_zone(zone, 1, BLACK)),
+        }
+    }
+}
+
+pub struct NetworkFields {
+    pub clock: TextField,
+    status: TextField,
+    ssid: TextField,
+    ip: TextField,
+    signal: TextField,
+    mac: TextField,
+    gateway: TextField,
+    drops: TextField,
+}
+
+impl NetworkFields {
+    pub fn new() -> Self {
+        let (value_x, y_start, line_height) = (65, 38, 20);
+        let info_y = y_start + line_height * 4 + 5;
+        Self {
+            clock: TextField::new(245, 8, 55, 1, PRIMARY_PURPLE),
+            status: TextField::new(value_x, y_start, 235, 1, BLACK),
+            ssid: TextField::new(value_x, y_start + line_height, 235, 1, BLACK),
+            ip: TextField::new(value_x, y_start + line_height * 2, 235, 1, BLACK),
+            signal: TextField::new(value_x, y_start + line_height * 3, 235, 1, BLACK),
+            mac: TextField::new(value_x, info_y, 235, 1, BLACK),
+            gateway: TextField::new(value_x, info_y + line_height, 235, 1, BLACK),
+            drops: TextField::new(value_x, info_y - 13, 235, 1, BLACK),
+        }
+    }
+}
+
+impl Default for NetworkFields {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct SensorFields {
+    battery: TextField,
+    voltage: TextField,
+    power: TextField,
+    temp: TextField,
+    light: TextField,
+    readings: TextField,
+}
+
+impl SensorFields {
+    pub fn new() -> Self {
+        let (y_start, line_height) = (50, 30);
+        Self {
+            battery: TextField::new(225, y_start, 70, 1, BLACK),
+            voltage: TextField::new(100, y_start + 18, 108, 1, BLACK),
+            power: TextField::new(210, y_start + 18, 85, 1, BLACK),
+            temp: TextField::new(100, y_start + line_height + 5, 100, 1, BLACK),
+            light: TextField::new(100, y_start + line_height * 2 + 5, 190, 1, BLACK),
+            readings: TextField::new(10, 126, 280, 1, BLACK),
+        }
+    }
+}
+
+impl Default for SensorFields {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct SettingsFields {
+    brightness: TextField,
+    auto_dim: TextField,
+    update_rate: TextField,
+    large_text: TextField,
+    version: TextField,
+}
+
+impl SettingsFields {
+    pub fn new() -> Self {
+        let (y_start, line_height) = (SETTINGS_Y_START, SETTINGS_LINE_HEIGHT);
+        Self {
+            brightness: TextField::new(230, y_start, 60, 1, BLACK),
+            auto_dim: TextField::new(120, y_start + line_height, 60, 1, BLACK),
+            update_rate: TextField::new(120, y_start + line_height * 2, 80, 1, BLACK),
+            large_text: TextField::new(120, y_start + line_height * 3, 60, 1, BLACK),
+            version: TextField::new(120, y_start + line_height * 4, 170, 1, BLACK),
+        }
+    }
+}
+
+impl Default for SettingsFields {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct OtaFields {
+    pub clock: TextField,
+    pub status: TextField,
+}
+
+impl OtaFields {
+    pub fn new() -> Self {
+        Self {
+            clock: TextField::new(245, 8, 55, 1, ACCENT_ORANGE),
+            status: TextField::new(80, 52, 210, 1, BLACK),
+        }
+    }
+}
+
+impl Default for OtaFields {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct NetworkView<'a> {
+    pub connected: bool,
+    pub ssid: &'a str,
+    pub ip: Option<&'a str>,
+    pub ipv6: Option<&'a str>,
+    /// Show the IPv6 address (when there is one) on the IP row this frame
+    pub show_ipv6: bool,
+    pub signal: i8,
+    pub mac: &'a str,
+    pub gateway: Option<&'a str>,
+    /// Average ping RTT per round, oldest first; None where every ping was lost
+    pub gateway_rtt: &'a [Option<u16>],
+    pub host_rtt: &'a [Option<u16>],
+    /// Most frequent WiFi disconnect reasons, "beacon_timeout 5, auth_fail 2";
+    /// empty hides the row
+    pub disconnect_reasons: &'a str,
+}
+
+pub struct SensorView<'a> {
+    pub battery_percent: u8,
+    pub battery_mv: u16,
+    pub charging: bool,
+    pub on_usb: bool,
+    pub temperature: f32,
+    pub light: u16,
+    /// 0.0-1.0 fill of the activity indicator
+    pub activity: f32,
+    /// Brown-out / voltage dip banner, shown in place of the activity indicator
+    pub power_warning: Option<&'a str>,
+    /// Power monitor reading, shown in place of the light level
+    pub power: Option<PowerRow>,
+    /// Registered sensors' readings on one line, shown in place of the
+    /// activity indicator
+    pub readings: Option<&'a str>,
+}
+
+/// Measured board power for the Sensor screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerRow {
+    pub now_mw: f32,
+    pub average_mw: f32,
+    pub energy_mwh: f64,
+}
+
+// Clear the screen and draw a coloured header bar with a centred title
+fn draw_header(display: &mut DisplayManager, title: &str, color: u16) -> Result<()> {
+    display.clear(BLACK)?;
+    display.flush()?; // Flush immediately to clear old content
+    display.fill_rect(0, 0, 300, 30, color)?;
+    display.draw_text_centered(8, title, WHITE, None, 2)
+}
+
+pub fn draw_system_chrome(display: &mut DisplayManager, layout: &SystemLayout) -> Result<()> {
+    display.clear(BLACK)?;
+    display.flush()?;
+
+    let header = layout.header;
+    display.fill_rect(header.x, header.y, header.w, header.h, PRIMARY_BLUE)?;
+    display.draw_text_centered(header.text_y(2), t("system.title"), WHITE, None, 2)?;
+
+    for (label, row) in SYSTEM_LABELS.iter().zip(layout.labels.iter()) {
+        display.draw_text(row.x, row.text_y(1), t(label), TEXT_PRIMARY, None, 1)?;
+    }
+
+    let [prev, next] = layout.hints;
+    display.draw_text(prev.x, prev.text_y(1), t("hint.prev"), TEXT_SECONDARY, None, 1)?;
+    display.draw_text(next.x, next.text_y(1), t("hint.next"), TEXT_SECONDARY, None, 1)
+}
+
+/// Battery icon and percentage in the System header
+pub fn draw_system_battery(display: &mut DisplayManager, layout: &SystemLayout, fields: &mut SystemFields, percent: u8, charging: bool, on_usb: bool) -> Result<()> {
+    let battery = layout.battery;
+    display.fill_rect(battery.x, battery.y, 28, battery.h, PRIMARY_BLUE)?;
+    display.draw_battery_icon(battery.x, battery.text_y(1) - 1, percent, charging, 1)?;
+
+    let color = if charging { WHITE }
+                else if percent > 50 { PRIMARY_GREEN }
+                else if percent > 20 { YELLOW }
+                else { PRIMARY_RED };
+    let text = if on_usb && !charging && percent == 0 {
+        TextBuf::from("USB")
+    } else {
+        crate::text_buf!("{}%", percent)
+    };
+    fields.battery.set(display, &text, color)
+}
+
+pub fn draw_system_progress(display: &mut DisplayManager, layout: &SystemLayout, progress: u8) -> Result<()> {
+    let bar = layout.progress;
+    display.draw_progress_bar(bar.x, bar.y, bar.w, bar.h, progress, PRIMARY_GREEN, SURFACE_LIGHT, BORDER_COLOR)
+}
+
+pub fn draw_network_chrome(display: &mut DisplayManager) -> Result<()> {
+    draw_header(display, t("network.title"), PRIMARY_PURPLE)?;
+
+    let y_start = 38;
+    let line_height = 20;
+    display.draw_text(10, y_start, t("common.status"), TEXT_PRIMARY, None, 1)?;
+    display.draw_text(10, y_start + line_height, t("network.ssid"), TEXT_PRIMARY, None, 1)?;
+    display.draw_text(10, y_start + line_height * 2, t("network.ip"), TEXT_PRIMARY, None, 1)?;
+    display.draw_text(10, y_start + line_height * 3, t("network.signal"), TEXT_PRIMARY, None, 1)?;
+
+    display.draw_text(10, 155, t("hint.prev"), TEXT_SECONDARY, None, 1)?;
+    display.draw_text(200, 155, t("hint.next"), TEXT_SECONDARY, None, 1)
+}
+
+/// Label and colour for an RSSI in dBm
+pub fn signal_quality(signal: i8) -> (&'static str, u16) {
+    match signal {
+        -50..=0 => (t("network.excellent"), PRIMARY_GREEN),
+        -60..=-51 => (t("network.good"), PRIMARY_GREEN),
+        -70..=-61 => (t("network.fair"), YELLOW),
+        -80..=-71 => (t("network.weak"), ACCENT_ORANGE),
+        _ => (t("network.poor"), PRIMARY_RED),
+    }
+}
+
+pub fn draw_network(display: &mut DisplayManager, fields: &mut NetworkFields, view: &NetworkView) -> Result<()> {
+    let y_start = 38;
+    let line_height = 20;
+    let unconfigured = view.ssid.is_empty() || view.ssid == "Not connected";
+
+    let (status_text, status_color) = if view.connected { (t("network.connected"), PRIMARY_GREEN) } else { (t("network.disconnected"), PRIMARY_RED) };
+    fields.status.set(display, status_text, status_color)?;
+
+    let ssid_color = if view.connected { TEXT_PRIMARY } else { TEXT_SECONDARY };
+    fields.ssid.set(display, view.ssid, ssid_color)?;
+
+    match (view.ip, view.ipv6) {
+        (_, Some(ipv6)) if view.show_ipv6 || view.ip.is_none() => fields.ip.set(display, ipv6, TEXT_PRIMARY)?,
+        (Some(ip), _) => fields.ip.set(display, ip, TEXT_PRIMARY)?,
+        // No WiFi credentials configured
+        (None, _) if unconfigured => fields.ip.set(display, t("network.no_config"), YELLOW)?,
+        (None, _) => fields.ip.set(display, t("network.obtaining_ip"), YELLOW)?,
+    }
+
+    if view.connected {
+        let (quality, color) = signal_quality(view.signal);
+        fields.signal.set(display, &crate::text_buf!("{} dBm ({})", view.signal, quality), color)?;
+    } else {
+        fields.signal.set(display, t("network.no_signal"), TEXT_SECONDARY)?;
+    }
+
+    // Between the signal row and the details below, in either state
+    if !view.disconnect_reasons.is_empty() {
+        display.draw_text(10, y_start + line_height * 4 - 8, t("network.drops"), TEXT_PRIMARY, None, 1)?;
+        fields.drops.set(display, view.disconnect_reasons, TEXT_SECONDARY)?;
+    }
+
+    if view.connected {
+        let info_y = y_start + line_height * 4 + 5;
+        display.draw_text(10, info_y, t("network.mac"), TEXT_PRIMARY, None, 1)?;
+        fields.mac.set(display, view.mac, TEXT_SECONDARY)?;
+
+        display.draw_text(10, info_y + line_height, t("network.gateway"), TEXT_PRIMARY, None, 1)?;
+        fields.gateway.set(display, view.gateway.unwrap_or(t("common.not_available")), TEXT_SECONDARY)?;
+
+        if !view.gateway_rtt.is_empty() || !view.host_rtt.is_empty() {
+            draw_latency(display, 185, info_y - 5, 105, 32, view.gateway_rtt, view.host_rtt)?;
+        }
+
+        // Web interface section
+        let web_section_y = info_y + line_height * 2 + 10;
+        display.draw_line(10, web_section_y - 5, 290, web_section_y - 5, BORDER_COLOR)?;
+        display.draw_text_centered(web_section_y + 5, t("network.web_config"), TEXT_SECONDARY, None, 1)?;
+        if let Some(ip) = view.ip {
+            display.draw_text_centered(web_section_y + 20, &crate::text_buf!("http://{}", ip), PRIMARY_BLUE, None, 1)?;
+        }
+        if let Some(ipv6) = view.ipv6 {
+            display.draw_text_centered(web_section_y + 34, &crate::text_buf!("http://[{}]", ipv6), PRIMARY_BLUE, None, 1)?;
+        }
+    } else {
+        // Not connected - show help
+        let help_y = y_start + line_height * 4 + 10;
+        display.draw_line(10, help_y - 5, 290, help_y - 5, BORDER_COLOR)?;
+
+        if unconfigured {
+            display.draw_text_centered(help_y + 10, t("network.not_configured"), ACCENT_ORANGE, None, 1)?;
+            display.draw_text_centered(help_y + 28, t("network.edit_config"), TEXT_PRIMARY, None, 1)?;
+            display.draw_text_centered(help_y + 42, "#define WIFI_SSID \"YourSSID\"", PRIMARY_BLUE, None, 1)?;
+            display.draw_text_centered(help_y + 56, "#define WIFI_PASSWORD \"YourPass\"", PRIMARY_BLUE, None, 1)?;
+            display.draw_text_centered(help_y + 74, t("network.rebuild"), TEXT_SECONDARY, None, 1)?;
+        } else {
+            display.draw_text_centered(help_y + 10, t("network.failed"), PRIMARY_RED, None, 1)?;
+            display.draw_text_centered(help_y + 28, &crate::text_buf!("SSID: {}", view.ssid), TEXT_SECONDARY, None, 1)?;
+            display.draw_text_centered(help_y + 42, t("network.check"), TEXT_SECONDARY, None, 1)?;
+            display.draw_text_centered(help_y + 65, t("network.retrying"), TEXT_SECONDARY, None, 1)?;
+        }
+    }
+    Ok(())
+}
+
+/// Latest RTTs over a sparkline of recent rounds: gateway in green, reference
+/// host in blue, red ticks for rounds where every ping was lost
+fn draw_latency(display: &mut DisplayManager, x: u16, y: u16, w: u16, h: u16, gateway: &[Option<u16>], host: &[Option<u16>]) -> Result<()> {
+    display.fill_rect(x, y, w, h, BLACK)?;
+
+    let label = |samples: &[Option<u16>]| match samples.last() {
+        Some(Some(rtt)) => crate::text_buf!("{}ms", rtt),
+        Some(None) => TextBuf::from("lost"),
+        None => TextBuf::from("--"),
+    };
+    display.draw_text(x, y, &crate::text_buf!("GW {}", label(gateway)), PRIMARY_GREEN, None, 1)?;
+    if !host.is_empty() {
+        let text = crate::text_buf!("NET {}", label(host));
+        display.draw_text(x + w - text.len() as u16 * 6, y, &text, PRIMARY_BLUE, None, 1)?;
+    }
+
+    let (graph_y, graph_h) = (y + 12, h - 12);
+    display.draw_line(x, graph_y + graph_h - 1, x + w - 1, graph_y + graph_h - 1, BORDER_COLOR)?;
+    let max = gateway.iter().chain(host).flatten().copied().max().unwrap_or(0).max(10) as u32;
+    for (samples, color) in [(gateway, PRIMARY_GREEN), (host, PRIMARY_BLUE)] {
+        // Newest sample at the right edge, 3px apart
+        let visible = samples.len().min((w as usize - 1) / 3 + 1);
+        let start_x = x + w - 1 - visible.saturating_sub(1) as u16 * 3;
+        let skip = samples.len() - visible;
+        let mut prev: Option<(u16, u16)> = None;
+        for (i, sample) in samples.iter().skip(skip).enumerate() {
+            let px = start_x + i as u16 * 3;
+            match sample {
+                Some(rtt) => {
+                    let py = graph_y + graph_h - 2 - ((*rtt as u32).min(max) * (graph_h as u32 - 2) / max) as u16;
+                    match prev {
+                        Some((lx, ly)) => display.draw_line(lx, ly, px, py, color)?,
+                        None => display.fill_rect(px, py, 1, 1, color)?,
+                    }
+                    prev = Some((px, py));
+                }
+                None => {
+                    display.draw_line(px, graph_y, px, graph_y + graph_h - 2, PRIMARY_RED)?;
+                    prev = None;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `power_row`: a power monitor is fitted and takes the light row
+pub fn draw_sensor_chrome(display: &mut DisplayManager, power_row: bool) -> Result<()> {
+    draw_header(display, t("sensor.title"), PRIMARY_GREEN)?;
+
+    let y_start = 50;
+    let line_height = 30;
+    display.draw_text(10, y_start, t("sensor.battery"), TEXT_PRIMARY, None, 1)?;
+    display.draw_text(10, y_start + line_height, t("sensor.temp"), TEXT_PRIMARY, None, 1)?;
+    let third_row = if power_row { t("sensor.power") } else { t("sensor.light") };
+    display.draw_text(10, y_start + line_height * 2, third_row, TEXT_PRIMARY, None, 1)?;
+
+    display.draw_text(10, 150, t("hint.prev"), TEXT_SECONDARY, None, 1)?;
+    display.draw_text(230, 150, t("hint.next"), TEXT_SECONDARY, None, 1)
+}
+
+pub fn draw_sensor(display: &mut DisplayManager, fields: &mut SensorFields, view: &SensorView) -> Result<()> {
+    let y_start = 50;
+    let battery_color = if view.charging { PRIMARY_BLUE }
+                        else if view.battery_percent > 50 { PRIMARY_GREEN }
+                        else if view.battery_percent > 20 { YELLOW }
+                        else { PRIMARY_RED };
+
+    display.draw_progress_bar(100, y_start, 120, 15, view.battery_percent, battery_color, SURFACE_LIGHT, BORDER_COLOR)?;
+    fields.battery.set(display, &crate::text_buf!("{}%", view.battery_percent), battery_color)?;
+
+    if view.battery_mv > 0 {
+        // Precise voltage for debugging
+        let voltage = crate::text_buf!("{:.3}V ({}mV)", view.battery_mv as f32 / 1000.0, view.battery_mv);
+        fields.voltage.set(display, &voltage, TEXT_SECONDARY)?;
+        if view.charging {
+            fields.power.set(display, "CHG", PRIMARY_BLUE)?;
+        } else if view.on_usb {
+            fields.power.set(display, "USB", ACCENT_ORANGE)?;
+        } else {
+            fields.power.set(display, "", TEXT_SECONDARY)?;
+        }
+    } else {
+        fields.voltage.set(display, t("sensor.no_battery"), TEXT_SECONDARY)?;
+        fields.power.set(display, "", TEXT_SECONDARY)?;
+    }
+
+    fields.temp.set(display, &units::format_temperature(view.temperature, 1), TEXT_PRIMARY)?;
+    if let Some(power) = view.power {
+        let text = crate::text_buf!("{:.0}mW avg {:.0} {:.1}mWh", power.now_mw, power.average_mw, power.energy_mwh);
+        fields.light.set(display, &text, TEXT_PRIMARY)?;
+    } else if view.light > 0 {
+        fields.light.set(display, &crate::text_buf!("{} lux", view.light), TEXT_PRIMARY)?;
+    } else {
+        fields.light.set(display, "N/A", TEXT_SECONDARY)?;
+    }
+
+    if let Some(warning) = view.power_warning {
+        display.fill_rect(10, 124, 280, 20, ACCENT_ORANGE)?;
+        return display.draw_text_centered(130, &crate::text_buf!("⚠ {}", warning), BLACK, None, 1);
+    }
+    if let Some(readings) = view.readings {
+        return fields.readings.set(display, readings, TEXT_PRIMARY);
+    }
+
+    // Activity indicator
+    let (cx, cy, radius) = (160, 130, 20);
+    display.draw_circle(cx, cy, radius, BORDER_COLOR)?;
+    let fill_radius = (radius as f32 * view.activity) as u16;
+    if fill_radius > 0 {
+        display.fill_circle(cx, cy, fill_radius, PRIMARY_GREEN)?;
+    }
+    Ok(())
+}
+
+const SETTINGS_Y_START: u16 = 40;
+const SETTINGS_LINE_HEIGHT: u16 = 22;
+
+pub fn draw_settings_chrome(display: &mut DisplayManager) -> Result<()> {
+    draw_header(display, t("settings.title"), ACCENT_ORANGE)?;
+
+    let labels = ["settings.brightness", "settings.auto_dim", "settings.update", "settings.large_text", "settings.version"];
+    for (i, label) in labels.into_iter().enumerate() {
+        display.draw_text(10, SETTINGS_Y_START + SETTINGS_LINE_HEIGHT * i as u16, t(label), TEXT_PRIMARY, None, 1)?;
+    }
+
+    display.draw_text(10, 150, t("hint.prev"), TEXT_SECONDARY, None, 1)?;
+    display.draw_text(200, 150, t("hint.select"), TEXT_SECONDARY, None, 1)
+}
+
+/// `large_text`: accessibility mode is on
+pub fn draw_settings(display: &mut DisplayManager, fields: &mut SettingsFields, brightness_percent: u8, large_text: bool, version: &str) -> Result<()> {
+    display.draw_progress_bar(120, SETTINGS_Y_START, 100, 15, brightness_percent, PRIMARY_BLUE, SURFACE_LIGHT, BORDER_COLOR)?;
+    fields.brightness.set(display, &crate::text_buf!("{}%", brightness_percent), TEXT_PRIMARY)?;
+    fields.auto_dim.set(display, t("settings.on"), PRIMARY_GREEN)?;
+    fields.update_rate.set(display, t("settings.normal"), TEXT_PRIMARY)?;
+    if large_text {
+        fields.large_text.set(display, t("settings.on"), PRIMARY_GREEN)?;
+    } else {
+        fields.large_text.set(display, t("settings.off"), TEXT_SECONDARY)?;
+    }
+    fields.version.set(display, version, TEXT_SECONDARY)
+}
+
+const OTA_Y_START: u16 = 36;
+const OTA_LINE_HEIGHT: u16 = 16;
+
+pub fn draw_ota_chrome(display: &mut DisplayManager) -> Result<()> {
+    draw_header(display, t("ota.title"), ACCENT_ORANGE)?;
+    display.draw_text(10, 155, t("hint.prev"), TEXT_SECONDARY, None, 1)?;
+    display.draw_text(200, 155, t("hint.check"), TEXT_SECONDARY, None, 1)
+}
+
+/// Firmware/partition line, status label and separator
+pub fn draw_ota_info(display: &mut DisplayManager, version: &str, partition: &str) -> Result<()> {
+    display.draw_text(10, OTA_Y_START, t("ota.firmware"), TEXT_PRIMARY, None, 1)?;
+    display.draw_text(80, OTA_Y_START, version, PRIMARY_BLUE, None, 1)?;
+    display.draw_text(180, OTA_Y_START, t("ota.partition"), TEXT_PRIMARY, None, 1)?;
+    display.draw_text(240, OTA_Y_START, partition, TEXT_SECONDARY, None, 1)?;
+    display.draw_text(10, OTA_Y_START + OTA_LINE_HEIGHT, t("common.status"), TEXT_PRIMARY, None, 1)?;
+
+    let separator_y = OTA_Y_START + OTA_LINE_HEIGHT * 2 + 5;
+    display.draw_line(10, separator_y, 290, separator_y, BORDER_COLOR)
+}
+
+/// Download progress bar, or clear it with None
+pub fn draw_ota_progress(display: &mut DisplayManager, progress: Option<u8>) -> Result<()> {
+    let progress_y = OTA_Y_START + OTA_LINE_HEIGHT * 2 + 4;
+    match progress {
+        Some(p) => display.draw_progress_bar(10, progress_y, 280, 10, p, PRIMARY_BLUE, SURFACE_LIGHT, BORDER_COLOR),
+        None => display.fill_rect(10, progress_y, 280, 10, BLACK),
+    }
+}
+
+/// Upload/status URLs when connected, or a network-required notice
+pub fn draw_ota_endpoints(display: &mut DisplayManager, connected: bool, ip: Option<&str>) -> Result<()> {
+    let section_y = OTA_Y_START + OTA_LINE_HEIGHT * 2 + 24;
+    display.fill_rect(10, section_y, 290, 80, BLACK)?;
+
+    if !connected {
+        display.draw_text_centered(section_y + 8, t("ota.network_required"), PRIMARY_RED, None, 1)?;
+        return display.draw_text_centered(section_y + 24, t("ota.connect_wifi"), TEXT_SECONDARY, None, 1);
+    }
+
+    display.draw_text_centered(section_y + 4, t("ota.endpoints"), TEXT_SECONDARY, None, 1)?;
+    if let Some(ip) = ip {
+        let endpoint_y = section_y + 20;
+        display.draw_text(10, endpoint_y, t("ota.upload"), TEXT_PRIMARY, None, 1)?;
+        display.draw_text(60, endpoint_y, &crate::text_buf!("http://{}:8080/ota", ip), PRIMARY_BLUE, None, 1)?;
+
+        let status_y = endpoint_y + 16;
+        display.draw_text(10, status_y, t("common.status"), TEXT_PRIMARY, None, 1)?;
+        display.draw_text(60, status_y, &crate::text_buf!("http://{}:8080/api/ota/status", ip), PRIMARY_BLUE, None, 1)?;
+
+        let guide_y = status_y + 20;
+        display.draw_text_centered(guide_y, t("ota.upload_hint"), TEXT_SECONDARY, None, 1)?;
+        display.draw_text_centered(guide_y + 14, t("ota.restart_hint"), TEXT_SECONDARY, None, 1)?;
+    }
+    Ok(())
+}
+
+/// FPS readout in the top-right corner, below the header
+pub fn draw_fps(display: &mut DisplayManager, fps: f32) -> Result<()> {
+    let (x, y) = (245, 32);
+    display.fill_rect(x, y, 55, 12, BLACK)?;
+    let color = if fps >= 15.0 { PRIMARY_GREEN }
+                else if fps >= 10.0 { YELLOW }
+                else { PRIMARY_RED };
+    display.draw_text(x, y, &crate::text_buf!("{:.1} FPS", fps), color, None, 1)
+}
+
+pub fn draw_ota_overlay(display: &mut DisplayManager, progress: u8) -> Result<()> {
+    let overlay_y = 50;
+    let overlay_height = 80;
+    display.fill_rect(20, overlay_y, 260, overlay_height, SURFACE_DARK)?;
+    display.draw_rect(20, overlay_y, 260, overlay_height, ACCENT_ORANGE)?;
+    display.draw_text_centered(overlay_y + 10, t("ota.in_progress"), ACCENT_ORANGE, None, 2)?;
+
+    let bar_y = overlay_y + 35;
+    display.draw_progress_bar(40, bar_y, 220, 20, progress, PRIMARY_BLUE, SURFACE_LIGHT, WHITE)?;
+    display.draw_text_centered(bar_y + 25, &crate::text_buf!("{}%", progress), WHITE, None, 1)?;
+    display.draw_text_centered(bar_y + 40, t("ota.do_not_power_off"), PRIMARY_RED, None, 1)
+}
+
+/// Alert banner across the top of the screen; text `scale` 2 in the large-text layout
+pub fn draw_alert_bar(display: &mut DisplayManager, label: &str, value: &str, color: u16, scale: u8) -> Result<()> {
+    let (alert_y, alert_height) = (2, 20);
+    display.fill_rect(0, alert_y, 300, alert_height, color)?;
+    let text_y = Rect::new(0, alert_y, 300, alert_height).text_y(scale);
+    display.draw_text_centered(text_y, &crate::text_buf!("⚠ {}: {}", label, value), BLACK, None, scale)
+}
+
+/// Zones of the large-text layout: header, a page of label/value rows and the page number
+pub struct LargeTextLayout {
+    pub header: Rect,
+    pub rows: [Rect; LARGE_TEXT_ROWS],
+    pub footer: Rect,
+}
+
+impl LargeTextLayout {
+    pub fn new() -> Self {
+        let [header, body, footer] = Rect::screen()
+            .rows([Size::Fixed(30), Size::Fill(1), Size::Fixed(16)], 2);
+        Self {
+            header,
+            rows: body.inset_xy(10, 0).rows([Size::Fill(1); LARGE_TEXT_ROWS], 2),
+            footer: footer.inset_xy(10, 0),
+        }
+    }
+}
+
+impl Default for LargeTextLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Any screen in the large-text layout: scale-2 label and value lines, one
+/// page starting at row `first`, with the page number when there are more
+pub fn large_text_frame(layout: &LargeTextLayout, view: &TextView, first: usize) -> DrawList {
+    let mut frame = DrawList::new(SCREEN_WIDTH);
+    frame.clear(BLACK);
+    frame.draw_text_centered(layout.header.text_y(2), view.title, WHITE, None, 2);
+    let underline = layout.header.y + layout.header.h - 2;
+    frame.fill_rect(layout.header.x, underline, layout.header.w, 2, WHITE);
+
+    for (zone, row) in layout.rows.iter().zip(view.rows.iter().skip(first)) {
+        frame.draw_text(zone.x, zone.y, &row.label, WHITE, None, 2);
+        frame.draw_text(zone.x, zone.y + FONT_HEIGHT * 2 + 3, &row.value, row.color, None, 2);
+    }
+
+    let pages = text_pages(view.rows.len());
+    if pages > 1 {
+        let page = crate::text_buf!("{}/{}", first / LARGE_TEXT_ROWS + 1, pages);
+        // Scale-2 glyphs are 11px apart
+        let x = (layout.footer.x + layout.footer.w).saturating_sub(page.len() as u16 * 11);
+        frame.draw_text(x, layout.footer.text_y(2), &page, WHITE, None, 2);
+    }
+    frame
+}
+
+/// What the Air Quality screen shows; None where no sensor reports it
+pub struct AirQualityView<'a> {
+    /// ppm, and whether it is the SGP30's estimate rather than the SCD40's
+    /// measurement
+    pub co2: Option<(f32, bool)>,
+    pub tvoc_ppb: Option<f32>,
+    pub temperature: Option<f32>,
+    pub humidity: Option<f32>,
+    /// CO2 history, oldest first
+    pub co2_history: &'a [f32],
+}
+
+/// Colour of an air quality band
+pub fn air_quality_color(quality: AirQuality) -> u16 {
+    match quality {
+        AirQuality::Good => PRIMARY_GREEN,
+        AirQuality::Moderate => YELLOW,
+        AirQuality::Poor => ACCENT_ORANGE,
+        AirQuality::Bad => PRIMARY_RED,
+    }
+}
+
+/// Air Quality screen: CO2 large in the colour of its band (or of the TVOC
+/// band when only an SGP30 is fitted), the other readings below and the CO2
+/// history along the bottom
+pub fn air_quality_frame(view: &AirQualityView) -> DrawList {
+    let mut frame = DrawList::new(SCREEN_WIDTH);
+    frame.clear(BLACK);
+    let quality = view.co2.map(|(ppm, _)| AirQuality::from_co2(ppm))
+        .max(view.tvoc_ppb.map(AirQuality::from_tvoc));
+    let header_color = quality.map(air_quality_color).unwrap_or(PRIMARY_GREEN);
+    frame.fill_rect(0, 0, 300, 30, header_color);
+    frame.draw_text_centered(8, t("aq.title"), BLACK, None, 2);
+    frame.draw_text(10, 155, t("hint.prev"), TEXT_SECONDARY, None, 1);
+    frame.draw_text(200, 155, t("hint.next"), TEXT_SECONDARY, None, 1);
+
+    let Some(quality) = quality else {
+        frame.draw_text_centered(70, t("aq.none"), TEXT_PRIMARY, None, 1);
+        frame.draw_text_centered(90, t("aq.set_pins"), TEXT_SECONDARY, None, 1);
+        return frame;
+    };
+
+    if let Some((ppm, estimated)) = view.co2 {
+        let value = crate::text_buf!("{:.0}", ppm);
+        frame.draw_text(10, 42, &value, air_quality_color(AirQuality::from_co2(ppm)), None, 4);
+        let unit_x = 10 + value.len() as u16 * 22 + 6;
+        frame.draw_text(unit_x, 44, "ppm", TEXT_SECONDARY, None, 1);
+        let label = if estimated { t("aq.estimated") } else { "CO2" };
+        frame.draw_text(unit_x, 60, label, TEXT_SECONDARY, None, 1);
+    }
+    frame.draw_text(190, 48, t(quality.label_key()), air_quality_color(quality), None, 2);
+
+    let details = [
+        view.temperature.map(|temperature| (units::format_temperature(temperature, 1), TEXT_PRIMARY)),
+        view.humidity.map(|humidity| (crate::text_buf!("{} {:.0}%", t("aq.humidity"), humidity), TEXT_PRIMARY)),
+        view.tvoc_ppb.map(|tvoc| (crate::text_buf!("TVOC {:.0} ppb", tvoc), air_quality_color(AirQuality::from_tvoc(tvoc)))),
+    ];
+    let mut x = 10;
+    for (text, color) in details.iter().flatten() {
+        frame.draw_text(x, 82, text, *color, None, 1);
+        x += text.len() as u16 * 6 + 18;
+    }
+
+    draw_co2_history(&mut frame, 10, 98, 280, 48, view.co2_history);
+    frame
+}
+
+// Line graph scaled to 400 ppm (outdoor air) up to the highest value shown,
+// with the Moderate threshold dotted in when it falls inside
+fn draw_co2_history(frame: &mut DrawList, x: u16, y: u16, w: u16, h: u16, history: &[f32]) {
+    frame.draw_line(x, y + h - 1, x + w - 1, y + h - 1, BORDER_COLOR);
+    if history.len() < 2 {
+        return;
+    }
+    // One point per 2px at most, evenly picked from the whole history
+    let points = history.len().min(w as usize / 2);
+    let step = history.len() as f32 / points as f32;
+    let sampled: Vec<f32> = (0..points).map(|i| history[(i as f32 * step) as usize]).collect();
+    let low = 400.0f32;
+    let high = sampled.iter().copied().fold(1000.0f32, f32::max);
+    let to_y = |ppm: f32| y + h - 2 - (((ppm.clamp(low, high) - low) / (high - low)) * (h - 3) as f32) as u16;
+
+    let threshold = to_y(800.0);
+    for dx in (0..w).step_by(6) {
+        frame.fill_rect(x + dx, threshold, 2, 1, TEXT_SECONDARY);
+    }
+    let spacing = (w - 1) as f32 / (points - 1) as f32;
+    for (i, pair) in sampled.windows(2).enumerate() {
+        let x0 = x + (i as f32 * spacing) as u16;
+        let x1 = x + ((i + 1) as f32 * spacing) as u16;
+        frame.draw_line(x0, to_y(pair[0]), x1, to_y(pair[1]), air_quality_color(AirQuality::from_co2(pair[1])));
+    }
+}
+
+/// One Agenda line
+#[derive(Hash)]
+pub struct AgendaRow<'a> {
+    /// From `ui_state::agenda_when`
+    pub when: String,
+    pub title: &'a str,
+    pub today: bool,
+}
+
+pub struct AgendaView<'a> {
+    /// None until a calendar URL is configured
+    pub rows: Option<&'a [AgendaRow<'a>]>,
+    /// Shown when the last fetch failed
+    pub error: Option<&'a str>,
+}
+
+/// Rows that fit on the Agenda screen; the large-text view pages through more
+pub const AGENDA_ROWS: usize = 5;
+
+/// Agenda screen: the next events, today's marked green
+pub fn agenda_frame(view: &AgendaView) -> DrawList {
+    let mut frame = DrawList::new(SCREEN_WIDTH);
+    frame.clear(BLACK);
+    frame.fill_rect(0, 0, 300, 30, PRIMARY_BLUE);
+    frame.draw_text_centered(8, t("agenda.title"), WHITE, None, 2);
+    frame.draw_text(10, 155, t("hint.prev"), TEXT_SECONDARY, None, 1);
+    frame.draw_text(200, 155, t("hint.next"), TEXT_SECONDARY, None, 1);
+
+    let Some(rows) = view.rows else {
+        frame.draw_text_centered(60, t("agenda.not_configured"), TEXT_PRIMARY, None, 1);
+        frame.draw_text_centered(80, t("agenda.set_url"), TEXT_SECONDARY, None, 1);
+        return frame;
+    };
+    if rows.is_empty() {
+        frame.draw_text_centered(70, t("agenda.empty"), TEXT_PRIMARY, None, 1);
+    }
+
+    let y_start = 38;
+    let line_height = 21;
+    for (i, row) in rows.iter().take(AGENDA_ROWS).enumerate() {
+        let y = y_start + i as u16 * line_height;
+        let color = if row.today { PRIMARY_GREEN } else { TEXT_SECONDARY };
+        frame.fill_rect(10, y, 3, 14, color);
+        frame.draw_text(18, y + 3, &row.when, color, None, 1);
+        // 28 characters fit right of the time column
+        let title: String = row.title.chars().take(28).collect();
+        frame.draw_text(120, y + 3, &title, TEXT_PRIMARY, None, 1);
+        if i + 1 < rows.len().min(AGENDA_ROWS) {
+            frame.draw_line(18, y + line_height - 3, 290, y + line_height - 3, BORDER_COLOR);
+        }
+    }
+    if let Some(error) = view.error {
+        frame.draw_text_centered(142, error, YELLOW, None, 1);
+    }
+    frame
+}
+
+/// Kiosk mode: one metric, already formatted
+pub struct KioskView<'a> {
+    pub label: &'a str,
+    /// None while the metric has no value
+    pub value: Option<&'a str>,
+    pub unit: &'a str,
+    /// Today's range, formatted like the value
+    pub min_max: Option<(&'a str, &'a str)>,
+}
+
+/// Kiosk mode: label on top, the value as large as it fits, today's min/max below
+pub fn kiosk_frame(view: &KioskView) -> DrawList {
+    let mut frame = DrawList::new(SCREEN_WIDTH);
+    frame.clear(BLACK);
+    frame.draw_text_centered(6, view.label, TEXT_SECONDARY, None, 2);
+
+    let value = view.value.unwrap_or("--");
+    let scale = super::kiosk::value_scale(value, SCREEN_WIDTH);
+    let height = FONT_HEIGHT * scale as u16;
+    // Centred in the band between label and unit
+    let y = 30 + (70u16.saturating_sub(height)) / 2;
+    let color = if view.value.is_some() { WHITE } else { TEXT_SECONDARY };
+    frame.draw_text_centered(y, value, color, None, scale);
+    frame.draw_text_centered(106, view.unit, TEXT_SECONDARY, None, 2);
+
+    if let Some((min, max)) = view.min_max {
+        frame.draw_line(10, 128, 290, 128, BORDER_COLOR);
+        frame.draw_text(10, 140, t("kiosk.min"), TEXT_SECONDARY, None, 1);
+        frame.draw_text(40, 136, min, PRIMARY_BLUE, None, 2);
+        let max_x = 300 - 10 - max.chars().count() as u16 * 11;
+        frame.draw_text(max_x - 30, 140, t("kiosk.max"), TEXT_SECONDARY, None, 1);
+        frame.draw_text(max_x, 136, max, PRIMARY_RED, None, 2);
+    }
+    frame
+}
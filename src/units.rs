@@ -0,0 +1,113 @@
+// Display units. Sensors and exports always work in SI (°C, 24 h); only what
+// is shown to a person goes through these helpers.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+use serde::{Deserialize, Serialize};
+use crate::text_buf::TextBuf;
+
+// Bit 0: Fahrenheit, bit 1: 12-hour clock
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    /// Convert from °C
+    pub fn from_celsius(self, celsius: f32) -> f32 {
+        match self {
+            Self::Celsius => celsius,
+            Self::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+        }
+    }
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Self::Celsius => "°C",
+            Self::Fahrenheit => "°F",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ClockFormat {
+    #[default]
+    #[serde(rename = "24h")]
+    H24,
+    #[serde(rename = "12h")]
+    H12,
+}
+
+/// Switch the units used by the formatting helpers below
+pub fn set_units(temperature: TemperatureUnit, clock: ClockFormat) {
+    let bits = (temperature == TemperatureUnit::Fahrenheit) as u8 | ((clock == ClockFormat::H12) as u8) << 1;
+    CURRENT.store(bits, Ordering::Relaxed);
+}
+
+pub fn temperature_unit() -> TemperatureUnit {
+    if CURRENT.load(Ordering::Relaxed) & 1 != 0 { TemperatureUnit::Fahrenheit } else { TemperatureUnit::Celsius }
+}
+
+pub fn clock_format() -> ClockFormat {
+    if CURRENT.load(Ordering::Relaxed) & 2 != 0 { ClockFormat::H12 } else { ClockFormat::H24 }
+}
+
+/// °C reading in the current unit with `decimals` places, e.g. `74.3°F`
+pub fn format_temperature(celsius: f32, decimals: usize) -> TextBuf {
+    format_temperature_in(temperature_unit(), celsius, decimals)
+}
+
+pub fn format_temperature_in(unit: TemperatureUnit, celsius: f32, decimals: usize) -> TextBuf {
+    crate::text_buf!("{:.*}{}", decimals, unit.from_celsius(celsius), unit.suffix())
+}
+
+/// Minutes after midnight in the current clock format
+pub fn format_time_of_day(minutes: u16) -> TextBuf {
+    format_time_of_day_in(clock_format(), minutes)
+}
+
+/// `14:05` or `2:05 PM`
+pub fn format_time_of_day_in(format: ClockFormat, minutes: u16) -> TextBuf {
+    let (hour, minute) = ((minutes / 60) % 24, minutes % 60);
+    match format {
+        ClockFormat::H24 => crate::text_buf!("{:02}:{:02}", hour, minute),
+        ClockFormat::H12 => {
+            let suffix = if hour < 12 { "AM" } else { "PM" };
+            let hour12 = if hour % 12 == 0 { 12 } else { hour % 12 };
+            crate::text_buf!("{}:{:02} {}", hour12, minute, suffix)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temperature_conversion() {
+        assert_eq!(TemperatureUnit::Fahrenheit.from_celsius(0.0), 32.0);
+        assert_eq!(TemperatureUnit::Fahrenheit.from_celsius(100.0), 212.0);
+        assert_eq!(TemperatureUnit::Fahrenheit.from_celsius(-40.0), -40.0);
+        assert_eq!(format_temperature_in(TemperatureUnit::Celsius, 23.46, 1), "23.5°C");
+        assert_eq!(format_temperature_in(TemperatureUnit::Fahrenheit, 23.5, 0), "74°F");
+    }
+
+    #[test]
+    fn test_time_of_day() {
+        assert_eq!(format_time_of_day_in(ClockFormat::H24, 0), "00:00");
+        assert_eq!(format_time_of_day_in(ClockFormat::H24, 14 * 60 + 5), "14:05");
+        assert_eq!(format_time_of_day_in(ClockFormat::H12, 0), "12:00 AM");
+        assert_eq!(format_time_of_day_in(ClockFormat::H12, 12 * 60 + 30), "12:30 PM");
+        assert_eq!(format_time_of_day_in(ClockFormat::H12, 14 * 60 + 5), "2:05 PM");
+    }
+
+    #[test]
+    fn test_config_names() {
+        assert_eq!(serde_json::to_string(&ClockFormat::H12).unwrap(), "\"12h\"");
+        assert_eq!(serde_json::from_str::<TemperatureUnit>("\"fahrenheit\"").unwrap(), TemperatureUnit::Fahrenheit);
+    }
+}
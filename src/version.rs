@@ -1,10 +1,11 @@
 // Centralized version information
 
-// Display version - this is what users see on screen
-// Update this when making significant changes
-pub const DISPLAY_VERSION: &str = "v6.26";
+// Display version - this is what users see on screen. Derived by build.rs
+// from Cargo.toml's version (0.6.26 shows as v6.26, see release_version.rs);
+// bump that, not this
+pub const DISPLAY_VERSION: &str = env!("DISPLAY_VERSION");
 
-// Cargo package version from Cargo.toml
+// Cargo package version from Cargo.toml; also in the image's app descriptor
 pub const CARGO_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 // Full version string including Cargo version
@@ -12,3 +13,52 @@ pub fn full_version() -> String {
     format!("{} ({})", DISPLAY_VERSION, CARGO_VERSION)
 }
 
+
+// Build provenance, set by build.rs
+pub const GIT_COMMIT: &str = env!("GIT_COMMIT");
+pub const BUILD_TIMESTAMP: &str = env!("BUILD_TIMESTAMP");
+pub const RUSTC_VERSION: &str = env!("RUSTC_VERSION");
+/// Cargo features the firmware was built with, comma-separated
+pub const FEATURES: &str = env!("ENABLED_FEATURES");
+
+/// Commit abbreviated for the screen ("a1b2c3d", "a1b2c3d+" when built from a dirty tree)
+pub fn short_commit() -> String {
+    let hash = GIT_COMMIT.trim_end_matches("-dirty");
+    let short = &hash[..hash.len().min(7)];
+    if hash.len() < GIT_COMMIT.len() { format!("{}+", short) } else { short.to_string() }
+}
+
+/// Returned by /api/firmware for triaging field reports
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FirmwareInfo {
+    pub version: &'static str,
+    pub cargo_version: &'static str,
+    pub git_commit: &'static str,
+    pub build_timestamp: &'static str,
+    pub rustc_version: &'static str,
+    pub features: Vec<&'static str>,
+    /// From the app descriptor of the running image
+    pub project_name: String,
+    pub idf_version: String,
+    pub elf_sha256: String,
+    pub partitions: Vec<crate::ota::partitions::TableEntry>,
+}
+
+pub fn firmware_info() -> FirmwareInfo {
+    use core::ffi::CStr;
+    // Safety: the descriptor is a static in the running image
+    let desc = unsafe { &*esp_idf_sys::esp_app_get_description() };
+    let text = |field: &[core::ffi::c_char]| unsafe { CStr::from_ptr(field.as_ptr()) }.to_string_lossy().into_owned();
+    FirmwareInfo {
+        version: DISPLAY_VERSION,
+        cargo_version: CARGO_VERSION,
+        git_commit: GIT_COMMIT,
+        build_timestamp: BUILD_TIMESTAMP,
+        rustc_version: RUSTC_VERSION,
+        features: FEATURES.split(',').filter(|f| !f.is_empty()).collect(),
+        project_name: text(&desc.project_name),
+        idf_version: text(&desc.idf_ver),
+        elf_sha256: desc.app_elf_sha256.iter().map(|b| format!("{:02x}", b)).collect(),
+        partitions: crate::ota::partitions::table(),
+    }
+}